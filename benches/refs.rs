@@ -0,0 +1,62 @@
+//! Measures recording commands that reference the same few images many times.
+//!
+//! Encoder keeps each image once no matter how many commands use it,
+//! so time per command should not grow with the number of commands.
+//!
+//! Run with `cargo bench --bench refs`.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::time::{Duration, Instant};
+
+use mev::PipelineStages;
+
+const IMAGES: usize = 10;
+const ROUNDS: usize = 10;
+
+/// Returns time spent recording `commands` image barriers cycling through `images`.
+fn measure(queue: &mut mev::Queue, images: &[mev::Image], commands: usize) -> Duration {
+    let mut encoder = queue.new_command_encoder().unwrap();
+
+    let start = Instant::now();
+    for image in images.iter().cycle().take(commands) {
+        encoder.sync_image(PipelineStages::TRANSFER, PipelineStages::TRANSFER, image);
+    }
+    let elapsed = start.elapsed();
+
+    let cbuf = encoder.finish().unwrap();
+
+    // Only Vulkan backend reports referenced resources.
+    #[cfg(all(debug_assertions, not(any(target_os = "macos", target_os = "ios"))))]
+    assert_eq!(cbuf.referenced_resources().resources.len(), images.len());
+
+    drop(cbuf);
+    elapsed
+}
+
+fn main() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let images: Vec<_> = (0..IMAGES)
+        .map(|_| common::render_target(&device, 4, 4))
+        .collect();
+
+    let mut few = Duration::MAX;
+    let mut many = Duration::MAX;
+
+    for _ in 0..ROUNDS {
+        few = few.min(measure(&mut queue, &images, 2_000));
+        many = many.min(measure(&mut queue, &images, 20_000));
+    }
+
+    println!(
+        "{IMAGES} images, best of {ROUNDS} rounds:\n  \
+         2000 commands:  {few:?} ({:?} per command)\n  \
+         20000 commands: {many:?} ({:?} per command)",
+        few / 2_000,
+        many / 20_000,
+    );
+}
@@ -0,0 +1,61 @@
+//! Compares submitting single command buffer
+//! with `Queue::submit` and `Queue::submit_one`.
+//!
+//! Run with `cargo bench --bench submit`.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::time::{Duration, Instant};
+
+const SUBMITS: usize = 1000;
+const ROUNDS: usize = 10;
+
+fn command_buffers(queue: &mut mev::Queue) -> Vec<mev::CommandBuffer> {
+    (0..SUBMITS)
+        .map(|_| queue.new_command_encoder().unwrap().finish().unwrap())
+        .collect()
+}
+
+/// Returns time spent submitting pre-recorded command buffers.
+fn measure(
+    queue: &mut mev::Queue,
+    mut submit: impl FnMut(&mut mev::Queue, mev::CommandBuffer),
+) -> Duration {
+    let cbufs = command_buffers(queue);
+
+    let start = Instant::now();
+    for cbuf in cbufs {
+        submit(queue, cbuf);
+    }
+    let elapsed = start.elapsed();
+
+    queue.wait_idle().unwrap();
+    elapsed
+}
+
+fn main() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut iter = Duration::MAX;
+    let mut one = Duration::MAX;
+
+    for _ in 0..ROUNDS {
+        iter = iter.min(measure(&mut queue, |queue, cbuf| {
+            queue.submit(std::iter::once(cbuf), false).unwrap();
+        }));
+        one = one.min(measure(&mut queue, |queue, cbuf| {
+            queue.submit_one(cbuf, false).unwrap();
+        }));
+    }
+
+    println!(
+        "{SUBMITS} submits, best of {ROUNDS} rounds:\n  \
+         submit(once(cbuf)): {iter:?} ({:?} per submit)\n  \
+         submit_one(cbuf):   {one:?} ({:?} per submit)",
+        iter / SUBMITS as u32,
+        one / SUBMITS as u32,
+    );
+}
@@ -0,0 +1,184 @@
+//! Renders egui window over a cleared frame.
+//!
+//! Only pointer input is forwarded to egui to keep the example short.
+
+use std::time::Instant;
+
+use winit::application::ApplicationHandler;
+
+struct EguiApp {
+    queue: mev::Queue,
+    window: Option<winit::window::Window>,
+    surface: Option<mev::Surface>,
+    last_format: Option<mev::PixelFormat>,
+    renderer: Option<mev_egui::EguiRenderer>,
+    ctx: egui::Context,
+    events: Vec<egui::Event>,
+    start: Instant,
+    value: f32,
+}
+
+impl ApplicationHandler for EguiApp {
+    fn window_event(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        _window_id: winit::window::WindowId,
+        event: winit::event::WindowEvent,
+    ) {
+        match event {
+            winit::event::WindowEvent::CloseRequested => {
+                event_loop.exit();
+            }
+            winit::event::WindowEvent::CursorMoved { position, .. } => {
+                let scale = self.window.as_ref().unwrap().scale_factor() as f32;
+                self.events.push(egui::Event::PointerMoved(egui::pos2(
+                    position.x as f32 / scale,
+                    position.y as f32 / scale,
+                )));
+            }
+            winit::event::WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    winit::event::MouseButton::Left => egui::PointerButton::Primary,
+                    winit::event::MouseButton::Right => egui::PointerButton::Secondary,
+                    _ => return,
+                };
+                let pos = self.ctx.input(|input| input.pointer.latest_pos());
+                if let Some(pos) = pos {
+                    self.events.push(egui::Event::PointerButton {
+                        pos,
+                        button,
+                        pressed: state.is_pressed(),
+                        modifiers: egui::Modifiers::NONE,
+                    });
+                }
+            }
+            winit::event::WindowEvent::RedrawRequested => {
+                self.render();
+                self.window.as_ref().unwrap().request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if self.window.is_none() {
+            let window = event_loop
+                .create_window(winit::window::Window::default_attributes())
+                .unwrap();
+            let surface = self.queue.new_surface(&window, &window).unwrap();
+
+            self.window = Some(window);
+            self.surface = Some(surface);
+        }
+
+        self.window.as_ref().unwrap().request_redraw();
+    }
+}
+
+impl EguiApp {
+    fn render(&mut self) {
+        let mut frame = self.surface.as_mut().unwrap().next_frame().unwrap();
+        let target_format = frame.image().format();
+        let target_extent = frame.image().extent().into_2d();
+        let window = self.window.as_ref().unwrap();
+        let pixels_per_point = window.scale_factor() as f32;
+
+        if self.renderer.is_none() || self.last_format != Some(target_format) {
+            let renderer = mev_egui::EguiRenderer::new(&self.queue, target_format).unwrap();
+            self.renderer = Some(renderer);
+            self.last_format = Some(target_format);
+        }
+
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(
+                    target_extent.width() as f32 / pixels_per_point,
+                    target_extent.height() as f32 / pixels_per_point,
+                ),
+            )),
+            time: Some(self.start.elapsed().as_secs_f64()),
+            events: std::mem::take(&mut self.events),
+            ..Default::default()
+        };
+
+        let value = &mut self.value;
+        let output = self.ctx.run(input, |ctx| {
+            egui::Window::new("mev").show(ctx, |ui| {
+                ui.label("egui rendered with mev");
+                ui.add(egui::Slider::new(value, 0.0..=1.0).text("value"));
+            });
+        });
+
+        let primitives = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+        let screen = mev_egui::ScreenDesc {
+            size_in_pixels: [target_extent.width(), target_extent.height()],
+            pixels_per_point: output.pixels_per_point,
+        };
+
+        let renderer = self.renderer.as_mut().unwrap();
+        renderer
+            .update_textures(&mut self.queue, &output.textures_delta)
+            .unwrap();
+        renderer.update_buffers(&self.queue, &primitives).unwrap();
+
+        let mut encoder = self.queue.new_command_encoder().unwrap();
+        encoder.init_image(
+            mev::PipelineStages::empty(),
+            mev::PipelineStages::FRAGMENT_SHADER,
+            frame.image(),
+        );
+        {
+            let mut render = encoder.render(mev::RenderPassDesc {
+                name: "egui",
+                color_attachments: &[mev::AttachmentDesc::clear_color(
+                    frame.image(),
+                    mev::ClearColor::DARK_GRAY,
+                )],
+                depth_stencil_attachment: None,
+                flags: mev::RenderPassFlags::empty(),
+                expected_draws: primitives.len() as u32,
+            });
+
+            renderer.render(&mut render, &primitives, &screen);
+        }
+
+        self.queue
+            .sync_frame(&mut frame, mev::PipelineStages::FRAGMENT_SHADER);
+        encoder.present(frame, mev::PipelineStages::FRAGMENT_SHADER);
+        let cbuf = encoder.finish().unwrap();
+
+        window.pre_present_notify();
+        self.queue.submit_one(cbuf, true).unwrap();
+    }
+}
+
+fn main() {
+    let instance = mev::Instance::load().expect("Failed to init graphics");
+
+    let (_device, mut queues) = instance
+        .create(mev::DeviceDesc {
+            idx: 0,
+            queues: &[0],
+            features: mev::Features::SURFACE,
+            quirks: mev::Quirks::empty(),
+            allocator: mev::AllocatorConfig::default(),
+        })
+        .unwrap();
+    let queue = queues.pop().unwrap();
+
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let mut app = EguiApp {
+        queue,
+        window: None,
+        surface: None,
+        last_format: None,
+        renderer: None,
+        ctx: egui::Context::default(),
+        events: Vec::new(),
+        start: Instant::now(),
+        value: 0.5,
+    };
+
+    let _ = event_loop.run_app(&mut app);
+}
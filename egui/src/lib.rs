@@ -0,0 +1,533 @@
+//! [egui](https://docs.rs/egui) renderer built on mev.
+//!
+//! Uses only public mev API, so it also serves as an example
+//! of textured, scissored and blended rendering.
+//!
+//! ```ignore
+//! let mut egui_renderer = mev_egui::EguiRenderer::new(&queue, frame.image().format())?;
+//!
+//! // Every frame.
+//! let output = ctx.run(input, |ctx| ui(ctx));
+//! let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+//! let screen = mev_egui::ScreenDesc {
+//!     size_in_pixels: [extent.width(), extent.height()],
+//!     pixels_per_point: output.pixels_per_point,
+//! };
+//!
+//! egui_renderer.update_textures(&mut queue, &output.textures_delta)?;
+//! egui_renderer.update_buffers(&queue, &primitives)?;
+//!
+//! let mut render = encoder.render(mev::RenderPassDesc {
+//!     name: "egui",
+//!     color_attachments: &[mev::AttachmentDesc::load(frame.image())],
+//!     depth_stencil_attachment: None,
+//!     flags: mev::RenderPassFlags::empty(),
+//!     expected_draws: 0,
+//! });
+//! egui_renderer.render(&mut render, &primitives, &screen);
+//! ```
+
+use std::mem::{offset_of, size_of};
+
+use egui::epaint::{ImageData, Primitive, Vertex};
+use hashbrown::HashMap;
+use mev::{Arguments, DeviceRepr};
+
+/// Size of the screen the UI is rendered to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenDesc {
+    /// Size of the render target in pixels.
+    pub size_in_pixels: [u32; 2],
+
+    /// Number of pixels per egui point.
+    pub pixels_per_point: f32,
+}
+
+impl ScreenDesc {
+    /// Returns size of the screen in points.
+    fn size_in_points(&self) -> [f32; 2] {
+        [
+            self.size_in_pixels[0] as f32 / self.pixels_per_point,
+            self.size_in_pixels[1] as f32 / self.pixels_per_point,
+        ]
+    }
+}
+
+#[derive(mev::Arguments)]
+struct EguiArguments {
+    #[mev(sampled, fragment)]
+    texture: mev::Image,
+    #[mev(fragment)]
+    sampler: mev::Sampler,
+}
+
+#[derive(mev::DeviceRepr)]
+struct EguiConstants {
+    width: f32,
+    height: f32,
+    output_srgb: u32,
+}
+
+/// Vertex and index buffers of one frame.
+#[derive(Default)]
+struct FrameBuffers {
+    vertices: Option<mev::Buffer>,
+    indices: Option<mev::Buffer>,
+}
+
+/// Renders egui primitives into a render pass.
+pub struct EguiRenderer {
+    pipeline: mev::RenderPipeline,
+    output_srgb: bool,
+    textures: HashMap<egui::TextureId, EguiArguments>,
+    samplers: HashMap<egui::TextureOptions, mev::Sampler>,
+    next_user_texture: u64,
+
+    /// Textures freed by the last delta.
+    /// They may be used by primitives of the same frame,
+    /// so they are removed on the next update.
+    pending_free: Vec<egui::TextureId>,
+
+    buffers: mev::PerFrame<FrameBuffers>,
+
+    /// Buffers written by the last `update_buffers` call.
+    current: Option<(mev::Buffer, mev::Buffer)>,
+}
+
+impl EguiRenderer {
+    /// Creates renderer for render targets of the given format.
+    ///
+    /// With sRGB output format blending happens in linear space.
+    /// With linear output format blending happens in gamma space,
+    /// which is what egui is designed for.
+    pub fn new(
+        device: &mev::Device,
+        output_format: mev::PixelFormat,
+    ) -> Result<Self, mev::CreatePipelineError> {
+        let library = device
+            .new_shader_library(mev::LibraryDesc {
+                name: "egui",
+                input: mev::include_library!("shaders/egui.wgsl" as mev::ShaderLanguage::Wgsl),
+            })
+            .expect("egui shader must be valid");
+
+        let pipeline = device.new_render_pipeline(mev::RenderPipelineDesc {
+            name: "egui",
+            vertex_shader: mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            },
+            vertex_attributes: vec![
+                mev::VertexAttributeDesc {
+                    format: mev::VertexFormat::Float32x2,
+                    buffer_index: 0,
+                    offset: offset_of!(Vertex, pos) as u32,
+                },
+                mev::VertexAttributeDesc {
+                    format: mev::VertexFormat::Float32x2,
+                    buffer_index: 0,
+                    offset: offset_of!(Vertex, uv) as u32,
+                },
+                mev::VertexAttributeDesc {
+                    format: mev::VertexFormat::Unorm8x4,
+                    buffer_index: 0,
+                    offset: offset_of!(Vertex, color) as u32,
+                },
+            ],
+            vertex_layouts: vec![mev::VertexLayoutDesc {
+                stride: size_of::<Vertex>() as u32,
+                step_mode: mev::VertexStepMode::Vertex,
+            }],
+            primitive_topology: mev::PrimitiveTopology::Triangle,
+            raster: Some(mev::RasterDesc {
+                fragment_shader: Some(mev::Shader {
+                    library,
+                    entry: "fs_main".into(),
+                }),
+                color_targets: vec![mev::ColorTargetDesc {
+                    format: output_format,
+                    // Colors are premultiplied.
+                    blend: Some(mev::BlendDesc {
+                        mask: mev::WriteMask::all(),
+                        color: mev::Blend {
+                            op: mev::BlendOp::Add,
+                            src: mev::BlendFactor::One,
+                            dst: mev::BlendFactor::OneMinusSrcAlpha,
+                        },
+                        alpha: mev::Blend {
+                            op: mev::BlendOp::Add,
+                            src: mev::BlendFactor::OneMinusDstAlpha,
+                            dst: mev::BlendFactor::One,
+                        },
+                    }),
+                }],
+                depth_stencil: None,
+                front_face: mev::FrontFace::default(),
+                culling: mev::Culling::None,
+                line_width: 1.0,
+            }),
+            constants: EguiConstants::SIZE,
+            arguments: &[EguiArguments::LAYOUT],
+        })?;
+
+        Ok(EguiRenderer {
+            pipeline,
+            output_srgb: output_format.is_srgb(),
+            textures: HashMap::new(),
+            samplers: HashMap::new(),
+            next_user_texture: 0,
+            pending_free: Vec::new(),
+            buffers: mev::PerFrame::new(|_| FrameBuffers::default()),
+            current: None,
+        })
+    }
+
+    /// Registers image to be used with [`egui::TextureId::User`] id.
+    ///
+    /// Image must have `SAMPLED` usage and be synchronized
+    /// for fragment shader reads before rendering.
+    pub fn register_texture(
+        &mut self,
+        image: mev::Image,
+        sampler: mev::Sampler,
+    ) -> egui::TextureId {
+        let id = egui::TextureId::User(self.next_user_texture);
+        self.next_user_texture += 1;
+        self.textures.insert(
+            id,
+            EguiArguments {
+                texture: image,
+                sampler,
+            },
+        );
+        id
+    }
+
+    /// Removes image registered with [`register_texture`](EguiRenderer::register_texture).
+    pub fn unregister_texture(&mut self, id: egui::TextureId) {
+        self.textures.remove(&id);
+    }
+
+    /// Creates and updates textures from the delta.
+    ///
+    /// Textures freed by the delta are kept until the next call,
+    /// so primitives of the same frame may still use them.
+    ///
+    /// Uploads are submitted to the queue immediately
+    /// and are complete before commands submitted afterwards read the textures.
+    ///
+    /// # Panics
+    ///
+    /// Panics if delta updates a region of a texture that was not created.
+    pub fn update_textures(
+        &mut self,
+        queue: &mut mev::Queue,
+        delta: &egui::TexturesDelta,
+    ) -> Result<(), mev::DeviceError> {
+        for id in self.pending_free.drain(..) {
+            self.textures.remove(&id);
+        }
+        self.pending_free.extend(delta.free.iter().copied());
+
+        if delta.set.is_empty() {
+            return Ok(());
+        }
+
+        let mut encoder = queue.new_command_encoder()?;
+        let mut copy = encoder.copy();
+
+        for (id, image_delta) in &delta.set {
+            let ImageData::Color(data) = &image_delta.image;
+            let [width, height] = data.size.map(|size| size as u32);
+
+            let sampler = match self.samplers.get(&image_delta.options) {
+                Some(sampler) => sampler.clone(),
+                None => {
+                    let sampler = queue.new_sampler(sampler_desc(image_delta.options))?;
+                    self.samplers.insert(image_delta.options, sampler.clone());
+                    sampler
+                }
+            };
+
+            let (image, offset) = match image_delta.pos {
+                None => {
+                    let image = queue
+                        .new_image(mev::ImageDesc::new_d2(
+                            width,
+                            height,
+                            mev::PixelFormat::Rgba8Srgb,
+                            mev::ImageUsage::SAMPLED | mev::ImageUsage::TRANSFER_DST,
+                        ))
+                        .map_err(mev::OutOfMemory::from)?;
+
+                    copy.init_image(
+                        mev::PipelineStages::empty(),
+                        mev::PipelineStages::TRANSFER,
+                        &image,
+                    );
+
+                    self.textures.insert(
+                        *id,
+                        EguiArguments {
+                            texture: image.clone(),
+                            sampler,
+                        },
+                    );
+
+                    (image, mev::Offset3::ZERO)
+                }
+                Some([x, y]) => {
+                    let arguments = self
+                        .textures
+                        .get_mut(id)
+                        .expect("Partial update of a texture that was not created");
+                    arguments.sampler = sampler;
+
+                    let image = arguments.texture.clone();
+
+                    // Previous frames may still sample the texture.
+                    copy.sync_image(
+                        mev::PipelineStages::FRAGMENT_SHADER,
+                        mev::PipelineStages::TRANSFER,
+                        &image,
+                    );
+
+                    (image, mev::Offset3::new(x as u32, y as u32, 0))
+                }
+            };
+
+            let staging = queue
+                .new_buffer_init(mev::BufferInitDesc {
+                    data: bytemuck::cast_slice(&data.pixels),
+                    usage: mev::BufferUsage::TRANSFER_SRC,
+                    memory: mev::Memory::Upload,
+                    name: "egui-texture-upload",
+                })
+                .map_err(mev::OutOfMemory::from)?;
+
+            let bytes_per_line = u64::from(width) * 4;
+            copy.copy_buffer_to_image(
+                &staging,
+                0,
+                bytes_per_line,
+                bytes_per_line * u64::from(height),
+                &image,
+                offset,
+                mev::Extent3::new(width, height, 1),
+                0..1,
+                0,
+            );
+
+            copy.sync_image(
+                mev::PipelineStages::TRANSFER,
+                mev::PipelineStages::FRAGMENT_SHADER,
+                &image,
+            );
+        }
+
+        drop(copy);
+        queue.submit_one(encoder.finish()?, false)?;
+        Ok(())
+    }
+
+    /// Writes vertices and indices of the primitives into buffers of the current frame.
+    ///
+    /// Must be called once per frame with the same primitives
+    /// that are passed to [`render`](EguiRenderer::render).
+    /// Buffers are selected with [`mev::PerFrame`],
+    /// so one checkpoint must be submitted per frame.
+    pub fn update_buffers(
+        &mut self,
+        queue: &mev::Queue,
+        primitives: &[egui::ClippedPrimitive],
+    ) -> Result<(), mev::AllocError> {
+        let meshes = || {
+            primitives
+                .iter()
+                .filter_map(|primitive| match &primitive.primitive {
+                    Primitive::Mesh(mesh) => Some(mesh),
+                    Primitive::Callback(_) => None,
+                })
+        };
+
+        let vertex_count: usize = meshes().map(|mesh| mesh.vertices.len()).sum();
+        let index_count: usize = meshes().map(|mesh| mesh.indices.len()).sum();
+
+        let buffers = self.buffers.get_mut(queue);
+
+        let vertices = ensure_buffer(
+            queue,
+            &mut buffers.vertices,
+            (vertex_count * size_of::<Vertex>()) as u64,
+            mev::BufferUsage::VERTEX,
+            "egui-vertices",
+        )?;
+        let indices = ensure_buffer(
+            queue,
+            &mut buffers.indices,
+            (index_count * size_of::<u32>()) as u64,
+            mev::BufferUsage::INDEX,
+            "egui-indices",
+        )?;
+
+        let mut vertex_offset = 0;
+        let mut index_offset = 0;
+        for mesh in meshes() {
+            let vertex_data: &[u8] = bytemuck::cast_slice(&mesh.vertices);
+            let index_data: &[u8] = bytemuck::cast_slice(&mesh.indices);
+
+            // Safety: Buffer of the current frame is not used by pending commands.
+            unsafe {
+                vertices.write_unchecked(vertex_offset, vertex_data);
+                indices.write_unchecked(index_offset, index_data);
+            }
+
+            vertex_offset += vertex_data.len() as u64;
+            index_offset += index_data.len() as u64;
+        }
+
+        self.current = Some((vertices.clone(), indices.clone()));
+        Ok(())
+    }
+
+    /// Draws the primitives.
+    ///
+    /// Render pass must have single color attachment
+    /// of the format the renderer was created for.
+    /// Paint callbacks are skipped.
+    ///
+    /// Viewport is set to the whole screen and scissor is reset to it when done.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`update_buffers`](EguiRenderer::update_buffers) was not called.
+    pub fn render(
+        &self,
+        render: &mut mev::RenderCommandEncoder,
+        primitives: &[egui::ClippedPrimitive],
+        screen: &ScreenDesc,
+    ) {
+        let (vertices, indices) = self
+            .current
+            .as_ref()
+            .expect("`update_buffers` must be called before `render`");
+
+        let [width, height] = screen.size_in_pixels;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let [width_points, height_points] = screen.size_in_points();
+
+        render.with_viewport(
+            mev::Offset3::ZERO,
+            mev::Extent3::new(width as f32, height as f32, 1.0),
+        );
+        render.with_pipeline(&self.pipeline);
+        render.with_constants(&EguiConstants {
+            width: width_points,
+            height: height_points,
+            output_srgb: self.output_srgb as u32,
+        });
+        render.bind_vertex_buffers(0, &[vertices]);
+        render.bind_index_buffer(indices);
+
+        let mut vertex_offset = 0;
+        let mut index_offset = 0;
+
+        for primitive in primitives {
+            let Primitive::Mesh(mesh) = &primitive.primitive else {
+                continue;
+            };
+
+            let vertex_start = vertex_offset;
+            let index_start = index_offset;
+            vertex_offset += mesh.vertices.len() as u32;
+            index_offset += mesh.indices.len() as u32;
+
+            let Some(arguments) = self.textures.get(&mesh.texture_id) else {
+                continue;
+            };
+
+            let Some((offset, extent)) = scissor(primitive.clip_rect, screen) else {
+                continue;
+            };
+
+            render.with_scissor(offset, extent);
+            render.with_arguments(0, arguments);
+            render.draw_indexed(vertex_start as i32, index_start..index_offset, 0..1);
+        }
+
+        render.with_scissor(mev::Offset2::new(0, 0), mev::Extent2::new(width, height));
+    }
+}
+
+/// Returns scissor rectangle in pixels for the clip rectangle in points.
+///
+/// Returns `None` if the rectangle is empty after clamping to the screen.
+fn scissor(
+    clip_rect: egui::Rect,
+    screen: &ScreenDesc,
+) -> Option<(mev::Offset2<i32>, mev::Extent2<u32>)> {
+    let [width, height] = screen.size_in_pixels;
+    let ppp = screen.pixels_per_point;
+
+    let min_x = ((clip_rect.min.x * ppp).round() as u32).min(width);
+    let min_y = ((clip_rect.min.y * ppp).round() as u32).min(height);
+    let max_x = ((clip_rect.max.x * ppp).round() as u32).clamp(min_x, width);
+    let max_y = ((clip_rect.max.y * ppp).round() as u32).clamp(min_y, height);
+
+    if min_x == max_x || min_y == max_y {
+        return None;
+    }
+
+    Some((
+        mev::Offset2::new(min_x as i32, min_y as i32),
+        mev::Extent2::new(max_x - min_x, max_y - min_y),
+    ))
+}
+
+/// Returns buffer with at least `size` bytes, replacing smaller one.
+fn ensure_buffer<'a>(
+    device: &mev::Device,
+    buffer: &'a mut Option<mev::Buffer>,
+    size: u64,
+    usage: mev::BufferUsage,
+    name: &str,
+) -> Result<&'a mut mev::Buffer, mev::AllocError> {
+    // Empty buffers are not allowed.
+    let size = size.max(1024);
+
+    if buffer.as_ref().is_some_and(|buffer| buffer.size() >= size) {
+        return Ok(buffer.as_mut().unwrap());
+    }
+
+    let new = device.new_buffer(mev::BufferDesc {
+        size: size.next_power_of_two(),
+        usage,
+        memory: mev::Memory::Upload,
+        name,
+        shared_across_queues: false,
+    })?;
+
+    Ok(buffer.insert(new))
+}
+
+fn sampler_desc(options: egui::TextureOptions) -> mev::SamplerDesc {
+    let filter = |filter| match filter {
+        egui::TextureFilter::Nearest => mev::Filter::Nearest,
+        egui::TextureFilter::Linear => mev::Filter::Linear,
+    };
+
+    let address_mode = match options.wrap_mode {
+        egui::TextureWrapMode::ClampToEdge => mev::AddressMode::ClampToEdge,
+        egui::TextureWrapMode::Repeat => mev::AddressMode::Repeat,
+        egui::TextureWrapMode::MirroredRepeat => mev::AddressMode::MirrorRepeat,
+    };
+
+    mev::SamplerDesc::builder()
+        .min_filter(filter(options.minification))
+        .mag_filter(filter(options.magnification))
+        .address_mode(address_mode)
+        .build()
+}
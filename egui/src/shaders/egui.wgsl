@@ -0,0 +1,76 @@
+struct VertInput {
+    @location(0)
+    pos: vec2<f32>,
+
+    @location(1)
+    uv: vec2<f32>,
+
+    @location(2)
+    color: vec4<f32>,
+}
+
+struct VertOutput {
+    @builtin(position)
+    position: vec4<f32>,
+
+    @location(0)
+    uv: vec2<f32>,
+
+    @location(1)
+    color: vec4<f32>,
+}
+
+struct Constants {
+    width: f32,
+    height: f32,
+    output_srgb: u32,
+}
+
+var<push_constant> pc: Constants;
+
+@group(0) @binding(0)
+var texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var texture_sampler: sampler;
+
+fn linear_from_gamma(gamma: vec3<f32>) -> vec3<f32> {
+    let cutoff = gamma < vec3<f32>(0.04045);
+    let lower = gamma / vec3<f32>(12.92);
+    let higher = pow((gamma + vec3<f32>(0.055)) / vec3<f32>(1.055), vec3<f32>(2.4));
+    return select(higher, lower, cutoff);
+}
+
+fn gamma_from_linear(linear: vec3<f32>) -> vec3<f32> {
+    let cutoff = linear < vec3<f32>(0.0031308);
+    let lower = linear * vec3<f32>(12.92);
+    let higher = vec3<f32>(1.055) * pow(linear, vec3<f32>(1.0 / 2.4)) - vec3<f32>(0.055);
+    return select(higher, lower, cutoff);
+}
+
+@vertex
+fn vs_main(input: VertInput) -> VertOutput {
+    // Positions are in points with origin in the top-left corner.
+    let position = vec4<f32>(
+        2.0 * input.pos.x / pc.width - 1.0,
+        1.0 - 2.0 * input.pos.y / pc.height,
+        0.0,
+        1.0,
+    );
+    return VertOutput(position, input.uv, input.color);
+}
+
+@fragment
+fn fs_main(input: VertOutput) -> @location(0) vec4<f32> {
+    // Vertex colors are premultiplied sRGB, textures are sampled as linear.
+    let texel = textureSample(texture, texture_sampler, input.uv);
+
+    if pc.output_srgb != 0u {
+        let color = vec4<f32>(linear_from_gamma(input.color.rgb), input.color.a);
+        return color * texel;
+    } else {
+        // Blend in gamma space like egui expects on linear targets.
+        let texel_gamma = vec4<f32>(gamma_from_linear(texel.rgb), texel.a);
+        return input.color * texel_gamma;
+    }
+}
@@ -0,0 +1,249 @@
+//! Renders frames with compute shader only, without opening any render pass.
+//!
+//! If surface supports storage usage for frames of the `Rgba8Unorm` format
+//! compute shader writes directly into the frame.
+//! Otherwise it writes into offscreen image that is then copied into the frame.
+//! Copy doesn't convert formats, so for `Bgra8` frames shader writes
+//! red and blue channels swapped.
+
+use std::time::Instant;
+
+use mev::{Arguments, DeviceRepr};
+use winit::application::ApplicationHandler;
+
+const WORKGROUP_SIZE: u32 = 8;
+
+struct ComputePresentApp {
+    queue: mev::Queue,
+    window: Option<winit::window::Window>,
+    surface: Option<mev::Surface>,
+    direct: bool,
+    offscreen: Option<mev::Image>,
+    pipeline: Option<mev::ComputePipeline>,
+    start: Instant,
+}
+
+impl ApplicationHandler for ComputePresentApp {
+    fn window_event(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        _window_id: winit::window::WindowId,
+        event: winit::event::WindowEvent,
+    ) {
+        match event {
+            winit::event::WindowEvent::CloseRequested => {
+                event_loop.exit();
+            }
+            winit::event::WindowEvent::RedrawRequested => {
+                self.render();
+                self.window.as_ref().unwrap().request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if self.window.is_none() {
+            let window = event_loop
+                .create_window(winit::window::Window::default_attributes())
+                .unwrap();
+            let mut surface = self.queue.new_surface(&window, &window).unwrap();
+
+            let supported = surface.supported_usage().unwrap();
+            if supported.contains(mev::ImageUsage::STORAGE) {
+                surface
+                    .set_usage(mev::ImageUsage::TARGET | mev::ImageUsage::STORAGE)
+                    .unwrap();
+                self.direct = true;
+            } else {
+                surface
+                    .set_usage(mev::ImageUsage::TARGET | mev::ImageUsage::TRANSFER_DST)
+                    .unwrap();
+                self.direct = false;
+            }
+
+            self.window = Some(window);
+            self.surface = Some(surface);
+        }
+
+        self.window.as_ref().unwrap().request_redraw();
+    }
+}
+
+impl ComputePresentApp {
+    fn render(&mut self) {
+        let mut frame = self.surface.as_mut().unwrap().next_frame().unwrap();
+        let extent = frame.image().extent().into_2d();
+
+        // Storage format is fixed by the shader.
+        let direct = self.direct && frame.image().format() == mev::PixelFormat::Rgba8Unorm;
+
+        if self.pipeline.is_none() {
+            let library = self
+                .queue
+                .new_shader_library(mev::LibraryDesc {
+                    name: "main",
+                    input: mev::include_library!(
+                        "shaders/compute_present.wgsl" as mev::ShaderLanguage::Wgsl
+                    ),
+                })
+                .unwrap();
+
+            let pipeline = self
+                .queue
+                .new_compute_pipeline(mev::ComputePipelineDesc {
+                    name: "main",
+                    shader: mev::Shader {
+                        library,
+                        entry: "main".into(),
+                    },
+                    work_group_size: [WORKGROUP_SIZE, WORKGROUP_SIZE, 1],
+                    constants: ComputePresentConstants::SIZE,
+                    arguments: &[ComputePresentArguments::LAYOUT],
+                })
+                .unwrap();
+
+            self.pipeline = Some(pipeline);
+        }
+
+        // Swapchain images and new offscreen image have no content to preserve.
+        let (output, fresh) = if direct {
+            (frame.image().clone(), true)
+        } else {
+            let offscreen_matches = self
+                .offscreen
+                .as_ref()
+                .is_some_and(|image| image.extent().into_2d() == extent);
+
+            if !offscreen_matches {
+                let image = self
+                    .queue
+                    .new_image(mev::ImageDesc {
+                        extent: mev::ImageExtent::D2(extent),
+                        format: mev::PixelFormat::Rgba8Unorm,
+                        usage: mev::ImageUsage::STORAGE | mev::ImageUsage::TRANSFER_SRC,
+                        layers: 1,
+                        levels: 1,
+                        name: "offscreen",
+                        shared_across_queues: false,
+                    })
+                    .unwrap();
+                self.offscreen = Some(image);
+            }
+
+            (self.offscreen.clone().unwrap(), !offscreen_matches)
+        };
+
+        // Copy into the frame keeps bytes as is.
+        let swap_rb = !direct
+            && matches!(
+                frame.image().format(),
+                mev::PixelFormat::Bgra8Unorm | mev::PixelFormat::Bgra8Srgb
+            );
+
+        let pipeline = self.pipeline.as_ref().unwrap();
+
+        let mut encoder = self.queue.new_command_encoder().unwrap();
+        if fresh {
+            encoder.init_image(
+                mev::PipelineStages::empty(),
+                mev::PipelineStages::COMPUTE_SHADER,
+                &output,
+            );
+        } else {
+            // Wait for previous frame to finish reading offscreen image.
+            encoder.sync_image(
+                mev::PipelineStages::TRANSFER,
+                mev::PipelineStages::COMPUTE_SHADER,
+                &output,
+            );
+        }
+        {
+            let mut compute = encoder.compute();
+            compute.with_pipeline(pipeline);
+            compute.with_arguments(
+                0,
+                &ComputePresentArguments {
+                    output: output.clone(),
+                },
+            );
+            compute.with_constants(&ComputePresentConstants {
+                time: self.start.elapsed().as_secs_f32(),
+                width: extent.width(),
+                height: extent.height(),
+                swap_rb: u32::from(swap_rb),
+            });
+            compute.dispatch(mev::Extent3::new(
+                extent.width().div_ceil(WORKGROUP_SIZE),
+                extent.height().div_ceil(WORKGROUP_SIZE),
+                1,
+            ));
+        }
+
+        let last_stage = if direct {
+            mev::PipelineStages::COMPUTE_SHADER
+        } else {
+            encoder.init_image(
+                mev::PipelineStages::empty(),
+                mev::PipelineStages::TRANSFER,
+                frame.image(),
+            );
+            encoder.barrier(
+                mev::PipelineStages::COMPUTE_SHADER,
+                mev::PipelineStages::TRANSFER,
+            );
+
+            encoder.copy().copy_image(&output, frame.image());
+            mev::PipelineStages::TRANSFER
+        };
+
+        self.queue.sync_frame(&mut frame, last_stage);
+        encoder.present(frame, last_stage);
+        let cbuf = encoder.finish().unwrap();
+
+        self.window.as_ref().unwrap().pre_present_notify();
+        self.queue.submit_one(cbuf, true).unwrap();
+    }
+}
+
+fn main() {
+    let instance = mev::Instance::load().expect("Failed to init graphics");
+
+    let (_device, mut queues) = instance
+        .create(mev::DeviceDesc {
+            idx: 0,
+            queues: &[0],
+            features: mev::Features::SURFACE,
+            quirks: mev::Quirks::empty(),
+            allocator: mev::AllocatorConfig::default(),
+        })
+        .unwrap();
+    let queue = queues.pop().unwrap();
+
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let mut app = ComputePresentApp {
+        queue,
+        window: None,
+        surface: None,
+        direct: false,
+        offscreen: None,
+        pipeline: None,
+        start: Instant::now(),
+    };
+
+    let _ = event_loop.run_app(&mut app);
+}
+
+#[derive(mev::Arguments)]
+pub struct ComputePresentArguments {
+    #[mev(storage, shader(compute))]
+    pub output: mev::Image,
+}
+
+#[derive(mev::DeviceRepr)]
+pub struct ComputePresentConstants {
+    pub time: f32,
+    pub width: u32,
+    pub height: u32,
+    pub swap_rb: u32,
+}
@@ -0,0 +1,140 @@
+//! Requests 50 render pipeline variants during a simulated frame loop.
+//!
+//! Pipelines are created on device's background workers,
+//! each frame only polls pending pipelines and never waits for them.
+//! Frame times are printed to show that pipeline creation doesn't cause hitches.
+
+use std::time::{Duration, Instant};
+
+const FORMATS: [mev::PixelFormat; 5] = [
+    mev::PixelFormat::Rgba8Unorm,
+    mev::PixelFormat::Rgba8Srgb,
+    mev::PixelFormat::Bgra8Unorm,
+    mev::PixelFormat::Bgra8Srgb,
+    mev::PixelFormat::Rgba16Float,
+];
+
+const CULLING: [mev::Culling; 3] = [mev::Culling::None, mev::Culling::Front, mev::Culling::Back];
+
+const FRONT_FACES: [mev::FrontFace; 2] =
+    [mev::FrontFace::Clockwise, mev::FrontFace::CounterClockwise];
+
+const VARIANTS: usize = 50;
+
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+fn main() {
+    let instance = match mev::Instance::load() {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("Failed to init graphics: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let (device, _queues) = match instance.create(mev::DeviceDesc {
+        idx: 0,
+        queues: &[0],
+        features: mev::Features::empty(),
+        quirks: mev::Quirks::empty(),
+        allocator: mev::AllocatorConfig::default(),
+    }) {
+        Ok(pair) => pair,
+        Err(err) => {
+            eprintln!("Failed to create device: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let library = device
+        .new_shader_library(mev::LibraryDesc {
+            name: "main",
+            input: mev::include_library!("shaders/triangle.wgsl" as mev::ShaderLanguage::Wgsl),
+        })
+        .unwrap();
+
+    let mut pending = Vec::new();
+    let mut ready = Vec::new();
+    let mut requested = 0;
+    let mut frame = 0;
+    let start = Instant::now();
+
+    while ready.len() < VARIANTS {
+        let frame_start = Instant::now();
+
+        // Request a few variants each frame, as if new materials came into view.
+        for _ in 0..4 {
+            if requested == VARIANTS {
+                break;
+            }
+
+            let format = FORMATS[requested % FORMATS.len()];
+            let culling = CULLING[requested / FORMATS.len() % CULLING.len()];
+            let front_face =
+                FRONT_FACES[requested / (FORMATS.len() * CULLING.len()) % FRONT_FACES.len()];
+            let blend = requested / (FORMATS.len() * CULLING.len() * FRONT_FACES.len()) == 1;
+
+            let name = format!("variant-{requested}");
+            let builder = mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            })
+            .name(&name)
+            .fragment(mev::Shader {
+                library: library.clone(),
+                entry: "fs_main".into(),
+            })
+            .culling(culling)
+            .front_face(front_face)
+            .constants::<WarmupConstants>();
+
+            let builder = if blend {
+                builder.color_target_blend(format, mev::BlendDesc::default())
+            } else {
+                builder.color_target(format)
+            };
+
+            pending.push((
+                requested,
+                device.new_render_pipeline_deferred(builder.build()),
+            ));
+            requested += 1;
+        }
+
+        // Pick up pipelines that are ready without waiting for the rest.
+        pending.retain_mut(|(idx, pending)| match pending.poll() {
+            None => true,
+            Some(Ok(pipeline)) => {
+                ready.push(pipeline);
+                false
+            }
+            Some(Err(err)) => {
+                eprintln!("Failed to create variant {idx}: {err}");
+                std::process::exit(1);
+            }
+        });
+
+        let elapsed = frame_start.elapsed();
+        println!(
+            "frame {frame}: {:.3} ms, {} pending, {} ready",
+            elapsed.as_secs_f64() * 1000.0,
+            pending.len(),
+            ready.len(),
+        );
+
+        std::thread::sleep(FRAME_TIME.saturating_sub(elapsed));
+        frame += 1;
+    }
+
+    println!(
+        "{VARIANTS} variants created in {frame} frames, {:.3} s",
+        start.elapsed().as_secs_f64()
+    );
+}
+
+#[derive(mev::DeviceRepr)]
+pub struct WarmupConstants {
+    pub angle: f32,
+    pub width: u32,
+    pub height: u32,
+}
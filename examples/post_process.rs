@@ -0,0 +1,285 @@
+//! Renders rotating triangle into offscreen image
+//! and applies post-processing effect with a full-screen pass.
+//!
+//! Post-processing pipeline uses vertex shader provided by mev,
+//! it needs no vertex buffers and is drawn with `draw(0..3, 0..1)`.
+
+use std::time::Instant;
+
+use winit::application::ApplicationHandler;
+
+const SCENE_FORMAT: mev::PixelFormat = mev::PixelFormat::Rgba8Unorm;
+
+struct PostProcessApp {
+    queue: mev::Queue,
+    window: Option<winit::window::Window>,
+    surface: Option<mev::Surface>,
+    last_format: Option<mev::PixelFormat>,
+    scene: Option<mev::Image>,
+    scene_pipeline: Option<mev::RenderPipeline>,
+    post_pipeline: Option<mev::RenderPipeline>,
+    start: Instant,
+}
+
+impl ApplicationHandler for PostProcessApp {
+    fn window_event(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        _window_id: winit::window::WindowId,
+        event: winit::event::WindowEvent,
+    ) {
+        match event {
+            winit::event::WindowEvent::CloseRequested => {
+                event_loop.exit();
+            }
+            winit::event::WindowEvent::RedrawRequested => {
+                self.render();
+                self.window.as_ref().unwrap().request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if self.window.is_none() {
+            let window = event_loop
+                .create_window(winit::window::Window::default_attributes())
+                .unwrap();
+            let surface = self.queue.new_surface(&window, &window).unwrap();
+
+            self.window = Some(window);
+            self.surface = Some(surface);
+        }
+
+        self.window.as_ref().unwrap().request_redraw();
+    }
+}
+
+impl PostProcessApp {
+    fn render(&mut self) {
+        let mut frame = self.surface.as_mut().unwrap().next_frame().unwrap();
+        let target_format = frame.image().format();
+        let target_extent = frame.image().extent();
+        let elapsed = self.start.elapsed().as_secs_f32();
+
+        if self.scene_pipeline.is_none() {
+            let library = self
+                .queue
+                .new_shader_library(mev::LibraryDesc {
+                    name: "scene",
+                    input: mev::include_library!(
+                        "shaders/triangle.wgsl" as mev::ShaderLanguage::Wgsl
+                    ),
+                })
+                .unwrap();
+
+            let pipeline = self
+                .queue
+                .new_render_pipeline(
+                    mev::RenderPipelineDesc::builder(mev::Shader {
+                        library: library.clone(),
+                        entry: "vs_main".into(),
+                    })
+                    .name("scene")
+                    .fragment(mev::Shader {
+                        library,
+                        entry: "fs_main".into(),
+                    })
+                    .color_target(SCENE_FORMAT)
+                    .culling(mev::Culling::None)
+                    .constants::<SceneConstants>()
+                    .build(),
+                )
+                .unwrap();
+
+            self.scene_pipeline = Some(pipeline);
+        }
+
+        if self.post_pipeline.is_none() || self.last_format != Some(target_format) {
+            // Full-screen vertex shader is composed with the fragment shader into one library.
+            let library = self
+                .queue
+                .new_shader_library(mev::LibraryDesc {
+                    name: "post",
+                    input: mev::LibraryInput::Sources(vec![
+                        mev::FULLSCREEN_VERTEX_SHADER,
+                        mev::include_shader_source!(
+                            "shaders/post.wgsl" as mev::ShaderLanguage::Wgsl
+                        ),
+                    ]),
+                })
+                .unwrap();
+
+            let pipeline = self
+                .queue
+                .new_render_pipeline(
+                    mev::RenderPipelineDesc::fullscreen(
+                        mev::Shader {
+                            library,
+                            entry: "fs_main".into(),
+                        },
+                        target_format,
+                    )
+                    .name("post")
+                    .arguments::<PostArguments>()
+                    .constants::<PostConstants>()
+                    .build(),
+                )
+                .unwrap();
+
+            self.post_pipeline = Some(pipeline);
+            self.last_format = Some(target_format);
+        }
+
+        let scene_matches = self
+            .scene
+            .as_ref()
+            .is_some_and(|image| image.extent() == target_extent);
+
+        if !scene_matches {
+            let scene = self
+                .queue
+                .new_image(mev::ImageDesc {
+                    extent: target_extent,
+                    format: SCENE_FORMAT,
+                    usage: mev::ImageUsage::TARGET | mev::ImageUsage::SAMPLED,
+                    layers: 1,
+                    levels: 1,
+                    name: "scene",
+                    shared_across_queues: false,
+                })
+                .unwrap();
+
+            self.scene = Some(scene);
+        }
+
+        let scene = self.scene.as_ref().unwrap();
+        let scene_pipeline = self.scene_pipeline.as_ref().unwrap();
+        let post_pipeline = self.post_pipeline.as_ref().unwrap();
+
+        let mut encoder = self.queue.new_command_encoder().unwrap();
+        encoder.init_image(
+            mev::PipelineStages::empty(),
+            mev::PipelineStages::COLOR_OUTPUT,
+            scene,
+        );
+        encoder.init_image(
+            mev::PipelineStages::empty(),
+            mev::PipelineStages::COLOR_OUTPUT,
+            frame.image(),
+        );
+
+        {
+            let mut render = encoder.render(mev::RenderPassDesc {
+                name: "scene",
+                color_attachments: &[
+                    mev::AttachmentDesc::new(scene).clear(mev::ClearColor::DARK_GRAY)
+                ],
+                depth_stencil_attachment: None,
+                flags: mev::RenderPassFlags::empty(),
+                expected_draws: 1,
+            });
+
+            render.with_pipeline(scene_pipeline);
+            render.with_constants(&SceneConstants {
+                angle: elapsed * 0.1,
+                width: target_extent.width(),
+                height: target_extent.height(),
+            });
+            render.draw(0..3, 0..1);
+        }
+
+        encoder.sync_image(
+            mev::PipelineStages::COLOR_OUTPUT,
+            mev::PipelineStages::FRAGMENT_SHADER,
+            scene,
+        );
+
+        {
+            let mut render = encoder.render(mev::RenderPassDesc {
+                name: "post",
+                color_attachments: &[mev::AttachmentDesc::new(frame.image())],
+                depth_stencil_attachment: None,
+                flags: mev::RenderPassFlags::empty(),
+                expected_draws: 1,
+            });
+
+            let (offset, extent) = frame.viewport();
+            render.with_viewport(offset, extent);
+            let (offset, extent) = frame.scissor();
+            render.with_scissor(offset, extent);
+            render.with_pipeline(post_pipeline);
+            render.with_arguments(
+                0,
+                &PostArguments {
+                    scene: scene.clone(),
+                    sampler: (),
+                },
+            );
+            render.with_constants(&PostConstants {
+                strength: 0.5 + 0.5 * elapsed.sin(),
+            });
+
+            // Single triangle covers the whole viewport.
+            render.draw(0..3, 0..1);
+        }
+
+        self.queue
+            .sync_frame(&mut frame, mev::PipelineStages::COLOR_OUTPUT);
+        encoder.present(frame, mev::PipelineStages::COLOR_OUTPUT);
+        let cbuf = encoder.finish().unwrap();
+
+        self.window.as_ref().unwrap().pre_present_notify();
+        self.queue.submit_one(cbuf, true).unwrap();
+    }
+}
+
+fn main() {
+    let instance = mev::Instance::load().expect("Failed to init graphics");
+
+    let (_device, mut queues) = instance
+        .create(mev::DeviceDesc {
+            idx: 0,
+            queues: &[0],
+            features: mev::Features::SURFACE,
+            quirks: mev::Quirks::empty(),
+            allocator: mev::AllocatorConfig::default(),
+        })
+        .unwrap();
+    let queue = queues.pop().unwrap();
+
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let mut app = PostProcessApp {
+        queue,
+        window: None,
+        surface: None,
+        last_format: None,
+        scene: None,
+        scene_pipeline: None,
+        post_pipeline: None,
+        start: Instant::now(),
+    };
+
+    let _ = event_loop.run_app(&mut app);
+}
+
+#[derive(mev::DeviceRepr)]
+pub struct SceneConstants {
+    pub angle: f32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(mev::DeviceRepr)]
+pub struct PostConstants {
+    pub strength: f32,
+}
+
+#[derive(mev::Arguments)]
+pub struct PostArguments {
+    #[mev(sampled, fragment)]
+    pub scene: mev::Image,
+
+    #[mev(fragment, sampler = "linear_clamp")]
+    pub sampler: (),
+}
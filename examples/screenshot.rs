@@ -0,0 +1,286 @@
+//! Renders rotating triangle and saves presented frame to `screenshot.ppm`
+//! when `S` key is pressed.
+//!
+//! Frames are copied into a download buffer right before presenting.
+//! If surface doesn't allow copying from frames, the triangle would have to be
+//! rendered into offscreen image that is copied into the frame and captured instead.
+
+use std::{io::Write, time::Instant};
+
+use winit::application::ApplicationHandler;
+
+struct ScreenshotApp {
+    queue: mev::Queue,
+    window: Option<winit::window::Window>,
+    surface: Option<mev::Surface>,
+    last_format: Option<mev::PixelFormat>,
+    pipeline: Option<mev::RenderPipeline>,
+    capture: bool,
+    start: Instant,
+}
+
+impl ApplicationHandler for ScreenshotApp {
+    fn window_event(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        _window_id: winit::window::WindowId,
+        event: winit::event::WindowEvent,
+    ) {
+        match event {
+            winit::event::WindowEvent::CloseRequested => {
+                event_loop.exit();
+            }
+            winit::event::WindowEvent::KeyboardInput { event, .. }
+                if event.state.is_pressed()
+                    && !event.repeat
+                    && event.physical_key
+                        == winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::KeyS) =>
+            {
+                self.capture = true;
+            }
+            winit::event::WindowEvent::RedrawRequested => {
+                self.render();
+                self.window.as_ref().unwrap().request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if self.window.is_none() {
+            let window = event_loop
+                .create_window(winit::window::Window::default_attributes())
+                .unwrap();
+            let mut surface = self.queue.new_surface(&window, &window).unwrap();
+
+            // Vulkan adds transfer source usage when supported,
+            // Metal requires it to be requested.
+            let supported = surface.supported_usage().unwrap();
+            if supported.contains(mev::ImageUsage::TRANSFER_SRC) {
+                surface
+                    .set_usage(mev::ImageUsage::TARGET | mev::ImageUsage::TRANSFER_SRC)
+                    .unwrap();
+            }
+
+            self.window = Some(window);
+            self.surface = Some(surface);
+        }
+
+        self.window.as_ref().unwrap().request_redraw();
+    }
+}
+
+impl ScreenshotApp {
+    fn render(&mut self) {
+        let mut frame = self.surface.as_mut().unwrap().next_frame().unwrap();
+        let target_format = frame.image().format();
+        let target_extent = frame.image().extent().into_2d();
+        let angle = self.start.elapsed().as_secs_f32() * 0.1;
+
+        if self.pipeline.is_none() || self.last_format != Some(target_format) {
+            let library = self
+                .queue
+                .new_shader_library(mev::LibraryDesc {
+                    name: "main",
+                    input: mev::include_library!(
+                        "shaders/triangle.wgsl" as mev::ShaderLanguage::Wgsl
+                    ),
+                })
+                .unwrap();
+
+            let pipeline = self
+                .queue
+                .new_render_pipeline(
+                    mev::RenderPipelineDesc::builder(mev::Shader {
+                        library: library.clone(),
+                        entry: "vs_main".into(),
+                    })
+                    .name("main")
+                    .fragment(mev::Shader {
+                        library,
+                        entry: "fs_main".into(),
+                    })
+                    .color_target_blend(target_format, mev::BlendDesc::default())
+                    .front_face(mev::FrontFace::Clockwise)
+                    .constants::<TriangleConstants>()
+                    .build(),
+                )
+                .unwrap();
+
+            self.pipeline = Some(pipeline);
+            self.last_format = Some(target_format);
+        }
+
+        let pipeline = self.pipeline.as_ref().unwrap();
+
+        let capture = self.capture
+            && !frame.is_fake()
+            && frame
+                .image()
+                .usage()
+                .contains(mev::ImageUsage::TRANSFER_SRC);
+
+        if self.capture && !capture {
+            eprintln!("Frame can't be captured");
+        }
+        self.capture = false;
+
+        let mut encoder = self.queue.new_command_encoder().unwrap();
+        encoder.init_image(
+            mev::PipelineStages::empty(),
+            mev::PipelineStages::FRAGMENT_SHADER,
+            frame.image(),
+        );
+        {
+            let mut render = encoder.render(mev::RenderPassDesc {
+                name: "main",
+                color_attachments: &[
+                    mev::AttachmentDesc::new(frame.image()).clear(mev::ClearColor::DARK_GRAY)
+                ],
+                depth_stencil_attachment: None,
+                flags: mev::RenderPassFlags::empty(),
+                expected_draws: 0,
+            });
+
+            render.with_pipeline(pipeline);
+            render.with_constants(&TriangleConstants {
+                angle,
+                width: target_extent.width(),
+                height: target_extent.height(),
+            });
+            render.draw(0..3, 0..1);
+        }
+
+        let mut last_stage = mev::PipelineStages::FRAGMENT_SHADER;
+
+        let download = if capture {
+            let bytes_per_line = u64::from(target_extent.width()) * target_format.size() as u64;
+            let size = bytes_per_line * u64::from(target_extent.height());
+
+            let download = self
+                .queue
+                .new_buffer(mev::BufferDesc {
+                    size,
+                    usage: mev::BufferUsage::TRANSFER_DST,
+                    memory: mev::Memory::Download,
+                    name: "screenshot",
+                    shared_across_queues: false,
+                })
+                .unwrap();
+
+            let mut copy = encoder.copy();
+            copy.barrier(
+                mev::PipelineStages::FRAGMENT_SHADER,
+                mev::PipelineStages::TRANSFER,
+            );
+            copy.copy_image_to_buffer(
+                frame.image(),
+                mev::Offset3::ZERO,
+                target_extent.to_3d(),
+                0..1,
+                0,
+                &download,
+                0,
+                bytes_per_line,
+                0,
+            );
+            drop(copy);
+
+            last_stage = mev::PipelineStages::TRANSFER;
+            Some(download)
+        } else {
+            None
+        };
+
+        self.queue.sync_frame(&mut frame, last_stage);
+        encoder.present(frame, last_stage);
+        let cbuf = encoder.finish().unwrap();
+
+        self.window.as_ref().unwrap().pre_present_notify();
+        self.queue.submit_one(cbuf, true).unwrap();
+
+        if let Some(mut download) = download {
+            self.queue.wait_idle().unwrap();
+
+            let mut pixels = vec![0; download.size() as usize];
+
+            // Safety: commands that write the buffer are completed.
+            unsafe {
+                download.read_unchecked(0, &mut pixels);
+            }
+
+            match save_ppm("screenshot.ppm", target_format, target_extent, &pixels) {
+                Ok(()) => println!("Saved screenshot.ppm"),
+                Err(err) => eprintln!("Failed to save screenshot: {err}"),
+            }
+        }
+    }
+}
+
+/// Saves pixels of 8-bit RGBA or BGRA format as binary PPM.
+fn save_ppm(
+    path: &str,
+    format: mev::PixelFormat,
+    extent: mev::Extent2,
+    pixels: &[u8],
+) -> std::io::Result<()> {
+    let bgra = match format {
+        mev::PixelFormat::Rgba8Unorm | mev::PixelFormat::Rgba8Srgb => false,
+        mev::PixelFormat::Bgra8Unorm | mev::PixelFormat::Bgra8Srgb => true,
+        _ => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("unsupported frame format {format:?}"),
+            ))
+        }
+    };
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", extent.width(), extent.height())?;
+
+    for pixel in pixels.chunks_exact(4) {
+        let [r, g, b] = if bgra {
+            [pixel[2], pixel[1], pixel[0]]
+        } else {
+            [pixel[0], pixel[1], pixel[2]]
+        };
+        file.write_all(&[r, g, b])?;
+    }
+
+    file.flush()
+}
+
+fn main() {
+    let instance = mev::Instance::load().expect("Failed to init graphics");
+
+    let (_device, mut queues) = instance
+        .create(mev::DeviceDesc {
+            idx: 0,
+            queues: &[0],
+            features: mev::Features::SURFACE,
+            quirks: mev::Quirks::empty(),
+            allocator: mev::AllocatorConfig::default(),
+        })
+        .unwrap();
+    let queue = queues.pop().unwrap();
+
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let mut app = ScreenshotApp {
+        queue,
+        window: None,
+        surface: None,
+        last_format: None,
+        pipeline: None,
+        capture: false,
+        start: Instant::now(),
+    };
+
+    let _ = event_loop.run_app(&mut app);
+}
+
+#[derive(mev::DeviceRepr)]
+pub struct TriangleConstants {
+    pub angle: f32,
+    pub width: u32,
+    pub height: u32,
+}
@@ -0,0 +1,26 @@
+
+@group(0) @binding(0)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+struct Constants {
+    time: f32,
+    width: u32,
+    height: u32,
+    swap_rb: u32,
+}
+
+var<push_constant> pc: Constants;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= pc.width || id.y >= pc.height) {
+        return;
+    }
+
+    let uv = vec2<f32>(f32(id.x) / f32(pc.width), f32(id.y) / f32(pc.height));
+    var color = vec4<f32>(uv, 0.5 + 0.5 * sin(pc.time), 1.0);
+    if (pc.swap_rb != 0u) {
+        color = color.bgra;
+    }
+    textureStore(output, vec2<i32>(id.xy), color);
+}
@@ -0,0 +1,29 @@
+// Composed after `mev::FULLSCREEN_VERTEX_SHADER`
+// that provides `FullscreenVertexOutput`.
+
+@group(0) @binding(0)
+var scene: texture_2d<f32>;
+
+@group(0) @binding(1)
+var scene_sampler: sampler;
+
+struct Constants {
+    strength: f32,
+}
+
+var<push_constant> pc: Constants;
+
+@fragment
+fn fs_main(input: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // Chromatic aberration grows towards the edges.
+    let offset = (input.uv - vec2<f32>(0.5)) * pc.strength * 0.01;
+    let r = textureSample(scene, scene_sampler, input.uv + offset).r;
+    let g = textureSample(scene, scene_sampler, input.uv).g;
+    let b = textureSample(scene, scene_sampler, input.uv - offset).b;
+
+    // Vignette.
+    let d = length(input.uv - vec2<f32>(0.5));
+    let vignette = 1.0 - smoothstep(0.4, 0.8, d) * pc.strength;
+
+    return vec4<f32>(vec3<f32>(r, g, b) * vignette, 1.0);
+}
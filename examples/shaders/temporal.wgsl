@@ -0,0 +1,30 @@
+@group(0) @binding(0)
+var previous: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+struct Constants {
+    time: f32,
+    history_weight: f32,
+    width: u32,
+    height: u32,
+}
+
+var<push_constant> pc: Constants;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= pc.width || id.y >= pc.height) {
+        return;
+    }
+
+    let aspect = f32(pc.width) / f32(pc.height);
+    let uv = vec2<f32>(f32(id.x) / f32(pc.width) * aspect, f32(id.y) / f32(pc.height));
+    let center = vec2<f32>(0.5 * aspect, 0.5) + 0.3 * vec2<f32>(cos(pc.time), sin(pc.time));
+    let inside = step(length(uv - center), 0.05);
+    let current = vec4<f32>(inside, inside * 0.6, 0.2, 1.0);
+
+    let history = textureLoad(previous, vec2<i32>(id.xy), 0);
+    textureStore(output, vec2<i32>(id.xy), mix(current, history, pc.history_weight));
+}
@@ -0,0 +1,251 @@
+//! Renders moving dot with a trail by blending each frame with the previous one.
+//!
+//! Compute shader mixes the new frame with the history image
+//! and writes result into offscreen image.
+//! Result is copied into the frame and resolved into the history
+//! to be blended with the next frame.
+
+use std::time::Instant;
+
+use mev::{Arguments, DeviceRepr};
+use winit::application::ApplicationHandler;
+
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Weight of the previous frame in the blended result.
+const HISTORY_WEIGHT: f32 = 0.9;
+
+struct TemporalApp {
+    queue: mev::Queue,
+    window: Option<winit::window::Window>,
+    surface: Option<mev::Surface>,
+    output: Option<mev::Image>,
+    history: Option<mev::HistoryImage>,
+    pipeline: Option<mev::ComputePipeline>,
+    start: Instant,
+}
+
+impl ApplicationHandler for TemporalApp {
+    fn window_event(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        _window_id: winit::window::WindowId,
+        event: winit::event::WindowEvent,
+    ) {
+        match event {
+            winit::event::WindowEvent::CloseRequested => {
+                event_loop.exit();
+            }
+            winit::event::WindowEvent::RedrawRequested => {
+                self.render();
+                self.window.as_ref().unwrap().request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if self.window.is_none() {
+            let window = event_loop
+                .create_window(winit::window::Window::default_attributes())
+                .unwrap();
+            let mut surface = self.queue.new_surface(&window, &window).unwrap();
+            surface
+                .set_usage(mev::ImageUsage::TARGET | mev::ImageUsage::TRANSFER_DST)
+                .unwrap();
+
+            self.window = Some(window);
+            self.surface = Some(surface);
+        }
+
+        self.window.as_ref().unwrap().request_redraw();
+    }
+}
+
+impl TemporalApp {
+    fn render(&mut self) {
+        let mut frame = self.surface.as_mut().unwrap().next_frame().unwrap();
+        let extent = frame.image().extent().into_2d();
+
+        if self.pipeline.is_none() {
+            let library = self
+                .queue
+                .new_shader_library(mev::LibraryDesc {
+                    name: "temporal",
+                    input: mev::include_library!(
+                        "shaders/temporal.wgsl" as mev::ShaderLanguage::Wgsl
+                    ),
+                })
+                .unwrap();
+
+            let pipeline = self
+                .queue
+                .new_compute_pipeline(mev::ComputePipelineDesc {
+                    name: "temporal",
+                    shader: mev::Shader {
+                        library,
+                        entry: "main".into(),
+                    },
+                    work_group_size: [WORKGROUP_SIZE, WORKGROUP_SIZE, 1],
+                    constants: Constants::SIZE,
+                    arguments: &[TemporalArguments::LAYOUT],
+                })
+                .unwrap();
+
+            self.pipeline = Some(pipeline);
+        }
+
+        let mut encoder = self.queue.new_command_encoder().unwrap();
+
+        let output_matches = self
+            .output
+            .as_ref()
+            .is_some_and(|image| image.extent().into_2d() == extent);
+
+        if !output_matches {
+            let output = self
+                .queue
+                .new_image(mev::ImageDesc {
+                    extent: mev::ImageExtent::D2(extent),
+                    format: mev::PixelFormat::Rgba8Unorm,
+                    usage: mev::ImageUsage::STORAGE | mev::ImageUsage::TRANSFER_SRC,
+                    layers: 1,
+                    levels: 1,
+                    name: "output",
+                    shared_across_queues: false,
+                })
+                .unwrap();
+
+            let history = mev::HistoryImage::new(
+                &self.queue,
+                mev::ImageDesc {
+                    extent: mev::ImageExtent::D2(extent),
+                    format: mev::PixelFormat::Rgba8Unorm,
+                    usage: mev::ImageUsage::SAMPLED,
+                    layers: 1,
+                    levels: 1,
+                    name: "history",
+                    shared_across_queues: false,
+                },
+            )
+            .unwrap();
+
+            // History has no content until the first resolve,
+            // it is bound with zero weight.
+            for image in history.images() {
+                encoder.init_image(
+                    mev::PipelineStages::empty(),
+                    mev::PipelineStages::COMPUTE_SHADER,
+                    image,
+                );
+            }
+            encoder.init_image(
+                mev::PipelineStages::empty(),
+                mev::PipelineStages::COMPUTE_SHADER,
+                &output,
+            );
+
+            self.output = Some(output);
+            self.history = Some(history);
+        }
+
+        let pipeline = self.pipeline.as_ref().unwrap();
+        let output = self.output.as_ref().unwrap();
+        let history = self.history.as_mut().unwrap();
+
+        {
+            let mut compute = encoder.compute();
+            compute.with_pipeline(pipeline);
+            compute.with_arguments(
+                0,
+                &TemporalArguments {
+                    previous: history.previous().clone(),
+                    output: output.clone(),
+                },
+            );
+            compute.with_constants(&Constants {
+                time: self.start.elapsed().as_secs_f32(),
+                history_weight: if history.has_previous() {
+                    HISTORY_WEIGHT
+                } else {
+                    0.0
+                },
+                width: extent.width(),
+                height: extent.height(),
+            });
+            compute.dispatch(mev::Extent3::new(
+                extent.width().div_ceil(WORKGROUP_SIZE),
+                extent.height().div_ceil(WORKGROUP_SIZE),
+                1,
+            ));
+        }
+
+        encoder.init_image(
+            mev::PipelineStages::empty(),
+            mev::PipelineStages::TRANSFER,
+            frame.image(),
+        );
+
+        {
+            let mut copy = encoder.copy();
+            copy.barrier(
+                mev::PipelineStages::COMPUTE_SHADER,
+                mev::PipelineStages::TRANSFER,
+            );
+
+            // Note that formats of the output image and the frame may differ in channel order.
+            copy.copy_image(output, frame.image());
+
+            // Output becomes the previous image for the next frame.
+            history.resolve(&self.queue, &mut copy, output);
+        }
+
+        self.queue
+            .sync_frame(&mut frame, mev::PipelineStages::TRANSFER);
+        encoder.present(frame, mev::PipelineStages::TRANSFER);
+        let cbuf = encoder.finish().unwrap();
+
+        self.window.as_ref().unwrap().pre_present_notify();
+        self.queue.submit_one(cbuf, true).unwrap();
+    }
+}
+
+fn main() {
+    let instance = mev::Instance::load().expect("Failed to init graphics");
+
+    let (_device, mut queues) = instance
+        .create(mev::DeviceDesc {
+            idx: 0,
+            queues: &[0],
+            features: mev::Features::SURFACE,
+            quirks: mev::Quirks::empty(),
+            allocator: mev::AllocatorConfig::default(),
+        })
+        .unwrap();
+    let queue = queues.pop().unwrap();
+
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let mut app = TemporalApp {
+        queue,
+        window: None,
+        surface: None,
+        output: None,
+        history: None,
+        pipeline: None,
+        start: Instant::now(),
+    };
+
+    let _ = event_loop.run_app(&mut app);
+}
+
+#[derive(mev::Arguments)]
+pub struct TemporalArguments {
+    #[mev(sampled, shader(compute))]
+    pub previous: mev::Image,
+
+    #[mev(storage, shader(compute))]
+    pub output: mev::Image,
+}
+
+// Generated from `Constants` struct declared in the shader.
+mev::shader_interface!("examples/shaders/temporal.wgsl", pub struct Constants);
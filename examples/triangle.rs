@@ -87,6 +87,7 @@ impl TriangleApp {
                         depth_stencil: None,
                         front_face: mev::FrontFace::default(),
                         culling: mev::Culling::Back,
+                        line_width: 1.0,
                     }),
                     arguments: &[],
                     constants: TriangleConstants::SIZE,
@@ -112,10 +113,14 @@ impl TriangleApp {
                     mev::AttachmentDesc::new(frame.image()).clear(mev::ClearColor::DARK_GRAY)
                 ],
                 depth_stencil_attachment: None,
+                flags: mev::RenderPassFlags::empty(),
+                expected_draws: 0,
             });
 
-            render.with_viewport(mev::Offset3::ZERO, target_extent.into_3d().cast_as_f32());
-            render.with_scissor(mev::Offset2::ZERO, target_extent.into_2d());
+            let (offset, extent) = frame.viewport();
+            render.with_viewport(offset, extent);
+            let (offset, extent) = frame.scissor();
+            render.with_scissor(offset, extent);
             render.with_pipeline(pipeline);
             render.with_constants(&TriangleConstants {
                 angle,
@@ -131,7 +136,7 @@ impl TriangleApp {
         let cbuf = encoder.finish().unwrap();
 
         self.window.as_ref().unwrap().pre_present_notify();
-        self.queue.submit([cbuf], true).unwrap();
+        self.queue.submit_one(cbuf, true).unwrap();
     }
 }
 
@@ -145,15 +150,39 @@ fn main() {
         }
     }
 
-    let instance = mev::Instance::load().expect("Failed to init graphics");
+    let instance = match mev::Instance::load() {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("Failed to init graphics: {err}");
+            if err.is_library_not_found() {
+                eprintln!("Graphics drivers seem to be missing, install drivers for your GPU");
+            } else if err.is_unsupported() {
+                eprintln!("Graphics drivers or system are too old, try updating them");
+            } else if !err.missing_layers().is_empty() {
+                eprintln!("Some graphics layers are broken, reinstall or remove them");
+            }
+            std::process::exit(1);
+        }
+    };
 
-    let (_device, mut queues) = instance
-        .create(mev::DeviceDesc {
-            idx: 0,
-            queues: &[0],
-            features: mev::Features::SURFACE,
-        })
-        .unwrap();
+    let result = instance.create(mev::DeviceDesc {
+        idx: 0,
+        queues: &[0],
+        features: mev::Features::SURFACE,
+        quirks: mev::Quirks::empty(),
+        allocator: mev::AllocatorConfig::default(),
+    });
+
+    let (_device, mut queues) = match result {
+        Ok(pair) => pair,
+        Err(err) => {
+            eprintln!("Failed to create device: {err}");
+            if !err.missing_extensions().is_empty() {
+                eprintln!("Try updating graphics drivers");
+            }
+            std::process::exit(1);
+        }
+    };
     let queue = queues.pop().unwrap();
 
     let event_loop = winit::event_loop::EventLoop::new().unwrap();
@@ -1,3 +1,7 @@
+use proc_easy::private::Spanned;
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+
 proc_easy::easy_flags! {
     pub Kind(kind) {
         // Constant(constant),
@@ -20,5 +24,179 @@ proc_easy::easy_attributes! {
     pub struct FieldAttributes {
         pub kind: Option<Kind>,
         pub shaders: Shaders,
+        pub stages: Option<Stages>,
+        pub sampler: Option<SamplerAttr>,
+        pub immutable_sampler: Option<ImmutableSamplerAttr>,
+    }
+}
+
+proc_easy::easy_token!(stages);
+proc_easy::easy_token!(sampler);
+proc_easy::easy_token!(immutable_sampler);
+
+proc_easy::easy_argument_value! {
+    pub struct Stages {
+        pub name: stages,
+        pub value: syn::LitStr,
+    }
+}
+
+proc_easy::easy_argument_value! {
+    pub struct SamplerAttr {
+        pub name: sampler,
+        pub value: syn::LitStr,
+    }
+}
+
+proc_easy::easy_argument_value! {
+    pub struct ImmutableSamplerAttr {
+        pub name: immutable_sampler,
+        pub value: syn::LitStr,
+    }
+}
+
+/// Well-known sampler referenced by the field.
+pub struct FieldSampler {
+    /// Expression of `WellKnownSampler` type.
+    pub sampler: TokenStream,
+
+    /// Sampler is baked into the layout
+    /// with `immutable_sampler = "..."` attribute.
+    pub immutable: bool,
+}
+
+/// Returns well-known sampler if the field is a marker for it
+/// specified with `sampler = "..."` or `immutable_sampler = "..."` attribute.
+pub fn field_sampler(
+    attrs: &FieldAttributes,
+    mev: &TokenStream,
+) -> syn::Result<Option<FieldSampler>> {
+    let (value, immutable) = match (&attrs.sampler, &attrs.immutable_sampler) {
+        (None, None) => return Ok(None),
+        (Some(attr), None) => (&attr.value, false),
+        (None, Some(attr)) => (&attr.value, true),
+        (Some(_), Some(attr)) => {
+            return Err(syn::Error::new(
+                attr.value.span(),
+                "`sampler` and `immutable_sampler` can't be specified together",
+            ))
+        }
+    };
+
+    if attrs.kind.is_some() {
+        return Err(syn::Error::new(
+            value.span(),
+            "argument kind can't be specified for well-known sampler",
+        ));
+    }
+
+    let sampler = match &*value.value() {
+        "linear_clamp" => quote_spanned!(value.span() => #mev::WellKnownSampler::LinearClamp),
+        "nearest_clamp" => quote_spanned!(value.span() => #mev::WellKnownSampler::NearestClamp),
+        "linear_repeat" => quote_spanned!(value.span() => #mev::WellKnownSampler::LinearRepeat),
+        sampler => {
+            return Err(syn::Error::new(
+                value.span(),
+                format!(
+                    "unknown sampler `{sampler}`, expected `linear_clamp`, `nearest_clamp` or `linear_repeat`"
+                ),
+            ))
+        }
+    };
+
+    Ok(Some(FieldSampler { sampler, immutable }))
+}
+
+/// Returns expression of `Option<WellKnownSampler>` type
+/// for `immutable_sampler` field of the argument layout.
+pub fn field_immutable_sampler(sampler: &Option<FieldSampler>) -> TokenStream {
+    match sampler {
+        Some(FieldSampler {
+            sampler,
+            immutable: true,
+        }) => quote!(::core::option::Option::Some(#sampler)),
+        _ => quote!(::core::option::Option::None),
+    }
+}
+
+/// Returns expression of `ShaderStages` type for the field.
+///
+/// Stages are collected from shader flags and `stages = "..."` attribute.
+/// If none are specified, the argument is visible to all stages.
+pub fn field_stages(
+    field: &syn::Field,
+    attrs: &FieldAttributes,
+    mev: &TokenStream,
+) -> syn::Result<TokenStream> {
+    let mut stages = Vec::new();
+
+    for shader in attrs.shaders.flags.iter() {
+        match shader {
+            Shader::Vertex(vertex) => {
+                stages.push(quote_spanned!(vertex.span() => #mev::ShaderStages::VERTEX))
+            }
+            Shader::Fragment(fragment) => {
+                stages.push(quote_spanned!(fragment.span() => #mev::ShaderStages::FRAGMENT))
+            }
+            Shader::Compute(compute) => {
+                stages.push(quote_spanned!(compute.span() => #mev::ShaderStages::COMPUTE))
+            }
+        }
+    }
+
+    if let Some(attr) = &attrs.stages {
+        let value = attr.value.value();
+        for stage in value.split([',', '|']) {
+            let stage = match stage.trim() {
+                "vertex" => quote_spanned!(attr.value.span() => #mev::ShaderStages::VERTEX),
+                "fragment" => quote_spanned!(attr.value.span() => #mev::ShaderStages::FRAGMENT),
+                "compute" => quote_spanned!(attr.value.span() => #mev::ShaderStages::COMPUTE),
+                stage => {
+                    return Err(syn::Error::new(
+                        attr.value.span(),
+                        format!(
+                            "unknown shader stage `{stage}`, expected `vertex`, `fragment` or `compute`"
+                        ),
+                    ))
+                }
+            };
+            stages.push(stage);
+        }
+    }
+
+    if stages.is_empty() {
+        return Ok(quote_spanned!(field.span() => #mev::ShaderStages::all()));
+    }
+
+    Ok(quote!(#mev::ShaderStages::from_bits_truncate(0 #(| #stages.bits())*)))
+}
+
+/// Returns item that warns that the field is visible to all stages
+/// if no stages are specified for it.
+///
+/// Stable proc-macros can't emit warnings,
+/// so generated item uses deprecated constant spanned to the field.
+pub fn field_stages_note(field: &syn::Field, idx: usize, attrs: &FieldAttributes) -> TokenStream {
+    if !attrs.shaders.flags.is_empty() || attrs.stages.is_some() {
+        return TokenStream::new();
+    }
+
+    let name = match &field.ident {
+        Some(ident) => ident.to_string(),
+        None => idx.to_string(),
+    };
+
+    let note = format!(
+        "argument `{name}` is visible to all shader stages, \
+        list stages that use it, e.g. `#[mev(fragment)]` or `#[mev(stages = \"vertex, fragment\")]`"
+    );
+
+    quote_spanned! {field.span() =>
+        const _: () = {
+            #[deprecated(note = #note)]
+            #[allow(non_upper_case_globals)]
+            const mev_argument_stages_not_specified: () = ();
+            mev_argument_stages_not_specified
+        };
     }
 }
@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+
+struct Input {
+    path: syn::LitStr,
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    name: syn::Ident,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<syn::Token![struct]>()?;
+        let name = input.parse()?;
+        if input.peek(syn::Token![;]) {
+            input.parse::<syn::Token![;]>()?;
+        }
+        Ok(Input {
+            path,
+            attrs,
+            vis,
+            name,
+        })
+    }
+}
+
+pub fn shader_interface(input: TokenStream, mev: &TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<Input>(input)?;
+
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .ok_or_else(|| syn::Error::new_spanned(&input.path, "`CARGO_MANIFEST_DIR` is not set"))?;
+    let path = PathBuf::from(manifest_dir).join(input.path.value());
+
+    let source = std::fs::read_to_string(&path).map_err(|err| {
+        syn::Error::new_spanned(
+            &input.path,
+            format!("failed to read shader `{}`: {}", path.display(), err),
+        )
+    })?;
+
+    let module = naga::front::wgsl::parse_str(&source).map_err(|err| {
+        syn::Error::new_spanned(&input.path, err.emit_to_string_with_path(&source, &path))
+    })?;
+
+    let name = input.name.to_string();
+
+    let (members, span) = module
+        .types
+        .iter()
+        .find_map(|(_, ty)| match &ty.inner {
+            naga::TypeInner::Struct { members, span } if ty.name.as_deref() == Some(&name) => {
+                Some((members, *span))
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            let available = module
+                .types
+                .iter()
+                .filter(|(_, ty)| matches!(ty.inner, naga::TypeInner::Struct { .. }))
+                .filter_map(|(_, ty)| ty.name.as_deref())
+                .collect::<Vec<_>>();
+
+            let msg = if available.is_empty() {
+                format!("struct `{}` is not found in `{}`", name, input.path.value())
+            } else {
+                format!(
+                    "struct `{}` is not found in `{}`, available structs: {}",
+                    name,
+                    input.path.value(),
+                    available.join(", ")
+                )
+            };
+            syn::Error::new_spanned(&input.name, msg)
+        })?;
+
+    let mut field_names = Vec::with_capacity(members.len());
+    let mut field_types = Vec::with_capacity(members.len());
+    let mut field_offsets = Vec::with_capacity(members.len());
+    let mut offset_msgs = Vec::with_capacity(members.len());
+
+    for member in members {
+        let member_name = member.name.as_deref().unwrap_or("_");
+
+        let ty = map_type(&module, member.ty, mev).map_err(|ty| {
+            syn::Error::new_spanned(
+                &input.name,
+                format!(
+                    "field `{}.{}` has type `{}` that cannot be represented with `DeviceRepr`",
+                    name, member_name, ty
+                ),
+            )
+        })?;
+
+        let ident = syn::parse_str::<syn::Ident>(member_name)
+            .unwrap_or_else(|_| syn::Ident::new_raw(member_name, Span::call_site()));
+
+        field_names.push(ident);
+        field_types.push(ty);
+        field_offsets.push(member.offset as usize);
+        offset_msgs.push(format!(
+            "`{}.{}` offset does not match WGSL offset {} in `{}`",
+            name,
+            member_name,
+            member.offset,
+            input.path.value()
+        ));
+    }
+
+    let attrs = &input.attrs;
+    let vis = &input.vis;
+    let ident = &input.name;
+    let path_str = path.display().to_string();
+
+    let size_msg = format!(
+        "`{}` layout does not match WGSL struct of {} bytes in `{}`",
+        name,
+        span,
+        input.path.value()
+    );
+    let span = span as usize;
+
+    Ok(quote! {
+        #(#attrs)*
+        #[derive(Clone, Copy, Debug, #mev::DeviceRepr)]
+        #vis struct #ident {
+            #(pub #field_names: #field_types,)*
+        }
+
+        const _: () = {
+            // Rebuild when shader changes.
+            const _: &[u8] = ::core::include_bytes!(#path_str);
+
+            ::core::assert!(
+                <#ident as #mev::for_macro::DeviceRepr>::SIZE == #span,
+                #size_msg
+            );
+
+            #(
+                ::core::assert!(
+                    ::core::mem::offset_of!(
+                        <#ident as #mev::for_macro::DeviceRepr>::Repr,
+                        #field_names
+                    ) == #field_offsets,
+                    #offset_msgs
+                );
+            )*
+        };
+    })
+}
+
+/// Maps WGSL type to Rust type with `DeviceRepr`.
+/// Returns WGSL name of the type if it is not supported.
+fn map_type(
+    module: &naga::Module,
+    ty: naga::Handle<naga::Type>,
+    mev: &TokenStream,
+) -> Result<TokenStream, String> {
+    let ty = &module.types[ty];
+
+    match &ty.inner {
+        naga::TypeInner::Scalar(scalar) | naga::TypeInner::Atomic(scalar) => map_scalar(*scalar),
+        naga::TypeInner::Vector { size, scalar } => {
+            let size = *size as usize;
+            let scalar = map_scalar(*scalar)?;
+            Ok(quote! { #mev::vec<#scalar, #size> })
+        }
+        naga::TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => {
+            let columns = *columns as usize;
+            let rows = *rows as usize;
+            let scalar = map_scalar(*scalar)?;
+            Ok(quote! { #mev::mat<#scalar, #columns, #rows> })
+        }
+        naga::TypeInner::Array {
+            base,
+            size: naga::ArraySize::Constant(size),
+            ..
+        } => {
+            let size = size.get() as usize;
+            let base = map_type(module, *base, mev)?;
+            Ok(quote! { [#base; #size] })
+        }
+        naga::TypeInner::Array {
+            size: naga::ArraySize::Dynamic,
+            ..
+        } => Err("runtime-sized array".to_owned()),
+        naga::TypeInner::Struct { .. } => match &ty.name {
+            // Nested struct must be declared with the same name.
+            Some(name) => {
+                let ident = syn::Ident::new(name, Span::call_site());
+                Ok(quote! { #ident })
+            }
+            None => Err("anonymous struct".to_owned()),
+        },
+        _ => Err(ty.name.clone().unwrap_or_else(|| format!("{:?}", ty.inner))),
+    }
+}
+
+fn map_scalar(scalar: naga::Scalar) -> Result<TokenStream, String> {
+    match (scalar.kind, scalar.width) {
+        (naga::ScalarKind::Float, 4) => Ok(quote! { f32 }),
+        (naga::ScalarKind::Float, 8) => Ok(quote! { f64 }),
+        (naga::ScalarKind::Sint, 4) => Ok(quote! { i32 }),
+        (naga::ScalarKind::Sint, 8) => Ok(quote! { i64 }),
+        (naga::ScalarKind::Uint, 4) => Ok(quote! { u32 }),
+        (naga::ScalarKind::Uint, 8) => Ok(quote! { u64 }),
+        (naga::ScalarKind::Float, 2) => Err("f16".to_owned()),
+        (naga::ScalarKind::Bool, _) => Err("bool".to_owned()),
+        (kind, width) => Err(format!("{:?}{}", kind, width * 8)),
+    }
+}
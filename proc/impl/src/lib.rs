@@ -10,6 +10,8 @@ macro_rules! parse_macro_input {
 }
 
 mod args;
+#[cfg(feature = "shader-interface")]
+mod interface;
 mod repr;
 mod r#match;
 
@@ -46,3 +48,11 @@ pub fn repr_derive(input: TokenStream, mev: &TokenStream) -> TokenStream {
 pub fn match_backend(input: TokenStream, mev: &TokenStream) -> TokenStream {
     r#match::match_backend(input, mev)
 }
+
+#[cfg(feature = "shader-interface")]
+pub fn shader_interface(input: TokenStream, mev: &TokenStream) -> TokenStream {
+    match interface::shader_interface(input, mev) {
+        Ok(tokens) => tokens,
+        Err(err) => err.to_compile_error(),
+    }
+}
@@ -1,6 +1,6 @@
 use proc_easy::{private::Spanned, EasyAttributes};
 use proc_macro2::TokenStream;
-use quote::{quote, quote_spanned};
+use quote::quote;
 use syn;
 
 use crate::args::*;
@@ -31,12 +31,21 @@ pub fn derive(input: &syn::DeriveInput, mev: &TokenStream) -> syn::Result<proc_m
         .map(|field| FieldAttributes::parse(&field.attrs, field.span()))
         .collect::<Result<Vec<_>, _>>()?;
 
+    let field_samplers = field_attrs
+        .iter()
+        .map(|attrs| field_sampler(attrs, mev))
+        .collect::<syn::Result<Vec<_>>>()?;
+
     let field_argument_impls = data
         .fields
         .iter()
         .zip(&field_attrs)
-        .map(|(field, attrs)| {
+        .zip(&field_samplers)
+        .map(|((field, attrs), sampler)| {
             let ty = &field.ty;
+            if sampler.is_some() {
+                return quote::quote!(<#mev::Sampler as #mev::for_macro::ArgumentsField<#mev::for_macro::Automatic>>);
+            }
             match attrs.kind {
                 None => quote::quote!(<#ty as #mev::for_macro::ArgumentsField<#mev::for_macro::Automatic>>),
                 // Some(Kind::Constant(_)) => {
@@ -55,49 +64,26 @@ pub fn derive(input: &syn::DeriveInput, mev: &TokenStream) -> syn::Result<proc_m
         })
         .collect::<Vec<_>>();
 
-    let field_stages = data
+    let field_immutable_samplers = field_samplers
+        .iter()
+        .map(field_immutable_sampler)
+        .collect::<Vec<_>>();
+
+    let field_notes = data
         .fields
         .iter()
         .zip(&field_attrs)
-        .map(|(field, attrs)| {
-            if attrs.shaders.flags.is_empty() {
-                quote_spanned!(field.span() => #mev::ShaderStage::empty())
-            } else {
-                let mut tokens = quote!(0);
-
-                for stage in attrs.shaders.flags.iter() {
-                    match stage {
-                        Shader::Vertex(vertex) => {
-                            if !tokens.is_empty() {
-                                tokens.extend(quote_spanned!(vertex.span() => | ));
-                            }
-                            tokens
-                                .extend(quote_spanned!(vertex.span() => #mev::ShaderStages::VERTEX.bits()))
-                        }
-                        Shader::Fragment(fragment) => {
-                            if !tokens.is_empty() {
-                                tokens.extend(quote_spanned!(fragment.span() => | ));
-                            }
-                            tokens.extend(
-                                quote_spanned!(fragment.span() => #mev::ShaderStages::FRAGMENT.bits()),
-                            )
-                        }
-                        Shader::Compute(compute) => {
-                            if !tokens.is_empty() {
-                                tokens.extend(quote_spanned!(compute.span() => | ));
-                            }
-                            tokens.extend(
-                                quote_spanned!(compute.span() => #mev::ShaderStages::COMPUTE.bits()),
-                            )
-                        }
-                    }
-                }
-
-                quote!(#mev::ShaderStages::from_bits_truncate(#tokens))
-            }
-        })
+        .enumerate()
+        .map(|(idx, (field, attrs))| field_stages_note(field, idx, attrs))
         .collect::<Vec<_>>();
 
+    let field_stages = data
+        .fields
+        .iter()
+        .zip(&field_attrs)
+        .map(|(field, attrs)| field_stages(field, attrs, mev))
+        .collect::<syn::Result<Vec<_>>>()?;
+
     match &data.fields {
         syn::Fields::Unit => {
             return Err(syn::Error::new_spanned(
@@ -113,49 +99,44 @@ pub fn derive(input: &syn::DeriveInput, mev: &TokenStream) -> syn::Result<proc_m
                 .map(|field| field.ident.as_ref().unwrap())
                 .collect::<Vec<_>>();
 
+            // Well-known samplers are taken from the encoder.
+            // Immutable samplers are bound the same way.
+            let field_values = field_names
+                .iter()
+                .zip(&field_samplers)
+                .map(|(name, sampler)| match sampler {
+                    None => quote!(&self.#name),
+                    Some(FieldSampler { sampler, .. }) => {
+                        quote!(&encoder.well_known_sampler(#sampler))
+                    }
+                })
+                .collect::<Vec<_>>();
+
             Ok(quote! {
+                #(#field_notes)*
+
                 impl #mev::for_macro::Arguments for #name {
                     const LAYOUT: #mev::ArgumentGroupLayout<'static> = #mev::ArgumentGroupLayout {
                         arguments: &[#(#mev::ArgumentLayout {
                             kind: #field_argument_impls::KIND,
                             size: #field_argument_impls::SIZE,
                             stages: #field_stages,
+                            immutable_sampler: #field_immutable_samplers,
                         },)*],
                     };
 
                     #[inline(always)]
                     fn bind_render(&self, group: u32, encoder: &mut #mev::RenderCommandEncoder) {
-                        let metal = encoder.metal();
-                        let vertex_bindings = encoder.vertex_bindings();
-                        let fragment_bindings = encoder.fragment_bindings();
-
-                        let mut idx = 0;
-                        #(
-                            if #field_stages.contains(#mev::ShaderStages::VERTEX) {
-                                #field_argument_impls::bind_vertex_argument(&self.#field_names, group, idx, vertex_bindings, metal);
-                            }
-
-                            if #field_stages.contains(#mev::ShaderStages::FRAGMENT) {
-                                #field_argument_impls::bind_fragment_argument(&self.#field_names, group, idx, fragment_bindings, metal);
-                            }
-
-                            idx += 1;
-                        )*
+                        // Arguments are retained by the encoder to re-apply them
+                        // when pipeline with different slots is set.
+                        let arguments = [#((#field_stages, #field_argument_impls::retain(#field_values)),)*];
+                        encoder.bind_arguments_group(group, arguments);
                     }
 
                     #[inline(always)]
                     fn bind_compute(&self, group: u32, encoder: &mut #mev::ComputeCommandEncoder) {
-                        let metal = encoder.metal();
-                        let bindings = encoder.bindings();
-
-                        let mut idx = 0;
-                        #(
-                            if #field_stages.contains(#mev::ShaderStages::COMPUTE) {
-                                #field_argument_impls::bind_compute_argument(&self.#field_names, group, idx, bindings, metal);
-                            }
-
-                            idx += 1;
-                        )*
+                        let arguments = [#((#field_stages, #field_argument_impls::retain(#field_values)),)*];
+                        encoder.bind_arguments_group(group, arguments);
                     }
                 }
             })
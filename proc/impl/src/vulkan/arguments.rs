@@ -1,6 +1,6 @@
 use proc_easy::{private::Spanned, EasyAttributes};
 use proc_macro2::TokenStream;
-use quote::{quote, quote_spanned};
+use quote::quote;
 use syn;
 
 use crate::args::*;
@@ -32,12 +32,21 @@ pub fn derive(input: &syn::DeriveInput, mev: &TokenStream) -> syn::Result<proc_m
         .map(|field| FieldAttributes::parse(&field.attrs, field.span()))
         .collect::<Result<Vec<_>, _>>()?;
 
+    let field_samplers = field_attrs
+        .iter()
+        .map(|attrs| field_sampler(attrs, mev))
+        .collect::<syn::Result<Vec<_>>>()?;
+
     let field_argument_impls = data
         .fields
         .iter()
         .zip(&field_attrs)
-        .map(|(field, attrs)| {
+        .zip(&field_samplers)
+        .map(|((field, attrs), sampler)| {
             let ty = &field.ty;
+            if sampler.is_some() {
+                return quote::quote!(<#mev::Sampler as #mev::for_macro::ArgumentsField<#mev::for_macro::Automatic>>);
+            }
             match attrs.kind {
                 None => quote::quote!(<#ty as #mev::for_macro::ArgumentsField<#mev::for_macro::Automatic>>),
                 // Some(Kind::Constant(_)) => {
@@ -56,51 +65,25 @@ pub fn derive(input: &syn::DeriveInput, mev: &TokenStream) -> syn::Result<proc_m
         })
         .collect::<Vec<_>>();
 
-    let field_stages = data
+    let field_immutable_samplers = field_samplers
+        .iter()
+        .map(field_immutable_sampler)
+        .collect::<Vec<_>>();
+
+    let field_notes = data
         .fields
         .iter()
         .zip(&field_attrs)
-        .map(|(field, attrs)| {
-            if attrs.shaders.flags.is_empty() {
-                quote_spanned!(field.span() => #mev::ShaderStage::empty())
-            } else {
-                let mut tokens = quote!(0);
-
-                for stage in attrs.shaders.flags.iter() {
-                    match stage {
-                        Shader::Vertex(vertex) => {
-                            if !tokens.is_empty() {
-                                tokens.extend(quote_spanned!(vertex.span() => | ));
-                            }
-                            tokens
-                                .extend(quote_spanned!(vertex.span() => #mev::ShaderStages::VERTEX.bits()))
-                        }
-                        Shader::Fragment(fragment) => {
-                            if !tokens.is_empty() {
-                                tokens.extend(quote_spanned!(fragment.span() => | ));
-                            }
-                            tokens.extend(
-                                quote_spanned!(fragment.span() => #mev::ShaderStages::FRAGMENT.bits()),
-                            )
-                        }
-                        Shader::Compute(compute) => {
-                            if !tokens.is_empty() {
-                                tokens.extend(quote_spanned!(compute.span() => | ));
-                            }
-                            tokens.extend(
-                                quote_spanned!(compute.span() => #mev::ShaderStages::COMPUTE.bits()),
-                            )
-                        }
-                    }
-                }
-
-                quote!(#mev::ShaderStages::from_bits_truncate(#tokens))
-            }
-        })
+        .enumerate()
+        .map(|(idx, (field, attrs))| field_stages_note(field, idx, attrs))
         .collect::<Vec<_>>();
 
-    let field_bindings = (0..data.fields.len() as u32).collect::<Vec<_>>();
-    let fields_count = data.fields.len();
+    let field_stages = data
+        .fields
+        .iter()
+        .zip(&field_attrs)
+        .map(|(field, attrs)| field_stages(field, attrs, mev))
+        .collect::<syn::Result<Vec<_>>>()?;
 
     let update_name = quote::format_ident!("MevGenerated{}Update", name);
 
@@ -118,31 +101,85 @@ pub fn derive(input: &syn::DeriveInput, mev: &TokenStream) -> syn::Result<proc_m
                 .iter()
                 .map(|field| field.ident.as_ref().unwrap())
                 .collect::<Vec<_>>();
+
+            // Well-known samplers are taken from the device.
+            // They live as long as the device, so they are not referenced.
+            // Immutable samplers are baked into the set layout
+            // and have no update and template entry.
+            let mut update_names = Vec::new();
+            let mut update_argument_impls = Vec::new();
+            let mut update_bindings = Vec::new();
+            let mut field_updates = Vec::new();
+            let mut field_refs = Vec::new();
+            let mut field_accesses = Vec::new();
+
+            for (binding, ((name, argument_impl), sampler)) in field_names
+                .iter()
+                .zip(&field_argument_impls)
+                .zip(&field_samplers)
+                .enumerate()
+            {
+                match sampler {
+                    None => {
+                        field_updates.push(quote! {
+                            #argument_impl::update(&self.#name)
+                        });
+                        field_refs.push(quote! {
+                            #argument_impl::add_refs(&self.#name, refs);
+                        });
+                        field_accesses.push(quote! {
+                            accesses.add_field(stringify!(#name), #argument_impl::KIND, |refs| {
+                                #argument_impl::add_refs(&self.#name, refs)
+                            });
+                        });
+                    }
+                    Some(FieldSampler {
+                        immutable: true, ..
+                    }) => continue,
+                    Some(FieldSampler { sampler, .. }) => {
+                        field_updates.push(quote! {
+                            #argument_impl::update(
+                                &#mev::Device::well_known_sampler(device, #sampler)
+                                    .expect("Failed to create well-known sampler"),
+                            )
+                        });
+                    }
+                }
+
+                update_names.push(*name);
+                update_argument_impls.push(argument_impl);
+                update_bindings.push(binding as u32);
+            }
+
+            let updates_count = update_names.len();
             Ok(quote! {
+                #(#field_notes)*
+
                 #[doc(hidden)]
                 #[derive(Clone, Copy)]
                 #vis struct #update_name {
-                    #(#field_names: #field_argument_impls::Update,)*
+                    #(#update_names: #update_argument_impls::Update,)*
                 }
 
                 impl #name {
-                    const fn mev_generated_template_entries() -> [#mev::for_macro::DescriptorUpdateTemplateEntry; #fields_count] {
+                    #[allow(unused_variables)]
+                    const fn mev_generated_template_entries() -> [#mev::for_macro::DescriptorUpdateTemplateEntry; #updates_count] {
                         let update = #mev::for_macro::MaybeUninit::<#update_name>::uninit();
                         let ptr = update.as_ptr();
                         [
                             #(
                                 #mev::for_macro::DescriptorUpdateTemplateEntry {
-                                    dst_binding: #field_bindings,
+                                    dst_binding: #update_bindings,
                                     dst_array_element: 0,
                                     descriptor_count: {
-                                        if #field_argument_impls::SIZE > u32::MAX as usize {
+                                        if #update_argument_impls::SIZE > u32::MAX as usize {
                                             panic!("Too many elements in the descriptor array");
                                         }
-                                        #field_argument_impls::SIZE as u32
+                                        #update_argument_impls::SIZE as u32
                                     },
-                                    descriptor_type: #mev::for_macro::descriptor_type(#field_argument_impls::KIND),
-                                    offset: unsafe { #mev::for_macro::addr_of!((*ptr).#field_names).cast::<u8>().offset_from(ptr.cast::<u8>()) as usize },
-                                    stride: #field_argument_impls::STRIDE,
+                                    descriptor_type: #mev::for_macro::descriptor_type(#update_argument_impls::KIND),
+                                    offset: unsafe { #mev::for_macro::addr_of!((*ptr).#update_names).cast::<u8>().offset_from(ptr.cast::<u8>()) as usize },
+                                    stride: #update_argument_impls::STRIDE,
                                 },
                             )*
                         ]
@@ -155,27 +192,38 @@ pub fn derive(input: &syn::DeriveInput, mev: &TokenStream) -> syn::Result<proc_m
                             kind: #field_argument_impls::KIND,
                             size: #field_argument_impls::SIZE,
                             stages: #field_stages,
+                            immutable_sampler: #field_immutable_samplers,
                         },)*],
                     };
 
+                    const FIELDS: &'static [&'static str] = &[#(stringify!(#field_names),)*];
+
                     type Update = #update_name;
 
                     #[inline(always)]
                     fn template_entries() -> &'static [#mev::for_macro::DescriptorUpdateTemplateEntry] {
-                        static ENTRIES: [#mev::for_macro::DescriptorUpdateTemplateEntry; #fields_count] = #name::mev_generated_template_entries();
+                        static ENTRIES: [#mev::for_macro::DescriptorUpdateTemplateEntry; #updates_count] = #name::mev_generated_template_entries();
                         &ENTRIES
                     }
 
                     #[inline(always)]
-                    fn update(&self) -> #update_name {
+                    #[allow(unused_variables)]
+                    fn update(&self, device: &#mev::Device) -> #update_name {
                         #update_name {
-                            #(#field_names: #field_argument_impls::update(&self.#field_names),)*
+                            #(#update_names: #field_updates,)*
                         }
                     }
 
                     #[inline(always)]
+                    #[allow(unused_variables)]
                     fn add_refs(&self, refs: &mut #mev::for_macro::Refs) {
-                        #(#field_argument_impls::add_refs(&self.#field_names, refs);)*
+                        #(#field_refs)*
+                    }
+
+                    #[inline(always)]
+                    #[allow(unused_variables)]
+                    fn add_accesses(&self, accesses: &mut #mev::for_macro::Accesses) {
+                        #(#field_accesses)*
                     }
                 }
             })
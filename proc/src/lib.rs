@@ -1,5 +1,41 @@
 use proc_macro::TokenStream;
 
+/// Derives `Arguments` trait for a struct.
+///
+/// Each field is an argument. Kind of the argument may be overridden with
+/// `uniform`, `sampled` or `storage` flag.
+///
+/// Shader stages that use the argument are listed with `shader(...)`, `shaders(...)`,
+/// bare `vertex`, `fragment`, `compute` flags or `stages = "vertex|fragment"`.
+/// Arguments without stages are visible to all stages,
+/// which may be less efficient on some backends,
+/// and the derive emits a warning suggesting to list them.
+/// Pipeline creation fails if a shader uses an argument not visible to its stage.
+///
+/// Field with `sampler = "linear_clamp"`, `"nearest_clamp"` or `"linear_repeat"`
+/// is a marker of sampler argument bound to the device's well-known sampler.
+/// Type of the marker field is not used, `()` is recommended.
+/// With `immutable_sampler = "..."` instead the sampler is baked into the layout
+/// and is not updated when arguments are bound.
+///
+/// ```ignore
+/// #[derive(mev::Arguments)]
+/// struct Material {
+///     #[mev(sampled, shaders(vertex, fragment))]
+///     albedo: mev::Image,
+///     #[mev(fragment)]
+///     sampler: mev::Sampler,
+///     #[mev(uniform, stages = "vertex, fragment")]
+///     params: mev::Buffer,
+///     #[mev(fragment, sampler = "linear_clamp")]
+///     clamp_sampler: (),
+///     #[mev(fragment, immutable_sampler = "linear_repeat")]
+///     repeat_sampler: (),
+/// }
+/// ```
+///
+/// Generated `Arguments::LAYOUT` constant describes the argument group
+/// and should be used to create pipelines, e.g. `arguments: &[Material::LAYOUT]`.
 #[proc_macro_derive(Arguments, attributes(mev))]
 pub fn arguments_derive(input: TokenStream) -> TokenStream {
     mev_proc_impl::arguments_derive(input.into(), &quote::quote!(mev)).into()
@@ -14,3 +50,23 @@ pub fn repr_derive(input: TokenStream) -> TokenStream {
 pub fn match_backend(input: TokenStream) -> TokenStream {
     mev_proc_impl::match_backend(input.into(), &quote::quote!(mev)).into()
 }
+
+/// Generates struct with `DeviceRepr` from a struct declared in WGSL shader.
+///
+/// Path is relative to the crate's manifest directory.
+/// Fields are mapped to `f32`, `i32`, `u32`, `vec<T, N>`, `mat<T, C, R>` and arrays.
+/// Nested structs are referred by name and must be generated as well.
+/// Compilation fails if the struct is not found, a field type has no mapping
+/// or size of the generated struct differs from the size of WGSL struct.
+///
+/// Generated struct derives `Clone`, `Copy`, `Debug` and `DeviceRepr`,
+/// attributes before `struct` are forwarded.
+///
+/// ```ignore
+/// mev::shader_interface!("shaders/frame.wgsl", pub struct FrameUniforms);
+/// ```
+#[cfg(feature = "shader-interface")]
+#[proc_macro]
+pub fn shader_interface(input: TokenStream) -> TokenStream {
+    mev_proc_impl::shader_interface(input.into(), &quote::quote!(mev)).into()
+}
@@ -0,0 +1,363 @@
+//! Helpers for alignment math and image data layout.
+//!
+//! Backends use these functions internally,
+//! so layouts computed here always match what copy commands expect.
+
+use super::{Extent3, PixelFormat};
+
+/// Rounds `value` up to the nearest multiple of `align`.
+///
+/// `align` must be non-zero.
+/// Returns `None` on overflow.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn checked_align_up(value: usize, align: usize) -> Option<usize> {
+    debug_assert!(align != 0);
+    match value % align {
+        0 => Some(value),
+        rem => value.checked_add(align - rem),
+    }
+}
+
+/// Rounds `value` up to the nearest multiple of `align`.
+///
+/// `align` must be non-zero.
+///
+/// # Panics
+///
+/// Panics if result overflows `usize`.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn align_up(value: usize, align: usize) -> usize {
+    match checked_align_up(value, align) {
+        Some(value) => value,
+        None => panic!("align_up overflow"),
+    }
+}
+
+/// Rounds `value` down to the nearest multiple of `align`.
+///
+/// `align` must be non-zero.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn align_down(value: usize, align: usize) -> usize {
+    debug_assert!(align != 0);
+    value - value % align
+}
+
+/// Checks if `value` is a multiple of `align`.
+///
+/// `align` must be non-zero.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn is_aligned(value: usize, align: usize) -> bool {
+    debug_assert!(align != 0);
+    value % align == 0
+}
+
+/// Rounds `value` up to the nearest multiple of `align`.
+///
+/// `align` must be non-zero.
+/// Returns `None` on overflow.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn checked_align_up_u64(value: u64, align: u64) -> Option<u64> {
+    debug_assert!(align != 0);
+    match value % align {
+        0 => Some(value),
+        rem => value.checked_add(align - rem),
+    }
+}
+
+/// Rounds `value` up to the nearest multiple of `align`.
+///
+/// `align` must be non-zero.
+///
+/// # Panics
+///
+/// Panics if result overflows `u64`.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn align_up_u64(value: u64, align: u64) -> u64 {
+    match checked_align_up_u64(value, align) {
+        Some(value) => value,
+        None => panic!("align_up_u64 overflow"),
+    }
+}
+
+/// Rounds `value` down to the nearest multiple of `align`.
+///
+/// `align` must be non-zero.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn align_down_u64(value: u64, align: u64) -> u64 {
+    debug_assert!(align != 0);
+    value - value % align
+}
+
+/// Checks if `value` is a multiple of `align`.
+///
+/// `align` must be non-zero.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn is_aligned_u64(value: u64, align: u64) -> bool {
+    debug_assert!(align != 0);
+    value % align == 0
+}
+
+/// Returns extent of the mip level `level` of an image with base `extent`.
+///
+/// Each dimension is halved per level and never goes below 1.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn mip_extent(extent: Extent3, level: u32) -> Extent3 {
+    const fn mip(size: u32, level: u32) -> u32 {
+        match size.checked_shr(level) {
+            Some(0) | None => 1,
+            Some(size) => size,
+        }
+    }
+
+    Extent3::new(
+        mip(extent.width(), level),
+        mip(extent.height(), level),
+        mip(extent.depth(), level),
+    )
+}
+
+/// Returns number of mip levels in full mip chain for an image with base `extent`.
+///
+/// Full chain ends with 1x1x1 level.
+#[cfg_attr(feature = "inline-more", inline(always))]
+pub const fn mip_levels_for(extent: Extent3) -> u32 {
+    let mut max = extent.width();
+    if extent.height() > max {
+        max = extent.height();
+    }
+    if extent.depth() > max {
+        max = extent.depth();
+    }
+    if max == 0 {
+        return 1;
+    }
+    u32::BITS - max.leading_zeros()
+}
+
+/// Layout of the image data in a buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ImageDataLayout {
+    /// Number of bytes between starts of consecutive rows.
+    pub bytes_per_line: usize,
+
+    /// Number of bytes between starts of consecutive depth slices.
+    pub bytes_per_plane: usize,
+
+    /// Total number of bytes occupied by the image data.
+    pub total_size: usize,
+}
+
+/// Computes layout of tightly packed image data with rows aligned to `alignment`.
+///
+/// Row pitch is always a multiple of both `alignment` and format's texel size,
+/// so it can be passed to `copy_buffer_to_image` as-is.
+///
+/// Returns `None` if any size overflows `usize`.
+pub fn image_data_layout(
+    format: PixelFormat,
+    extent: Extent3,
+    alignment: usize,
+) -> Option<ImageDataLayout> {
+    // Single texel is a single block until block-compressed formats are supported.
+    let block_size = format.size();
+    let alignment = lcm(alignment.max(1), block_size)?;
+
+    let row = usize::try_from(extent.width())
+        .ok()?
+        .checked_mul(block_size)?;
+    let bytes_per_line = checked_align_up(row, alignment)?;
+    let bytes_per_plane = bytes_per_line.checked_mul(usize::try_from(extent.height()).ok()?)?;
+    let total_size = bytes_per_plane.checked_mul(usize::try_from(extent.depth()).ok()?)?;
+
+    Some(ImageDataLayout {
+        bytes_per_line,
+        bytes_per_plane,
+        total_size,
+    })
+}
+
+const fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+const fn lcm(a: usize, b: usize) -> Option<usize> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_usize() {
+        assert_eq!(align_up(0, 256), 0);
+        assert_eq!(align_up(1, 256), 256);
+        assert_eq!(align_up(256, 256), 256);
+        assert_eq!(align_up(257, 256), 512);
+        assert_eq!(align_up(7, 3), 9);
+
+        assert_eq!(align_down(0, 256), 0);
+        assert_eq!(align_down(255, 256), 0);
+        assert_eq!(align_down(513, 256), 512);
+        assert_eq!(align_down(7, 3), 6);
+
+        assert!(is_aligned(0, 4));
+        assert!(is_aligned(12, 4));
+        assert!(!is_aligned(13, 4));
+        assert!(is_aligned(13, 1));
+    }
+
+    #[test]
+    fn align_usize_overflow() {
+        assert_eq!(checked_align_up(usize::MAX, 1), Some(usize::MAX));
+        assert_eq!(checked_align_up(usize::MAX, 2), None);
+        assert_eq!(checked_align_up(usize::MAX - 254, 256), None);
+        assert_eq!(
+            checked_align_up(usize::MAX - 255, 256),
+            Some(usize::MAX - 255)
+        );
+        assert_eq!(
+            checked_align_up(usize::MAX - 510, 256),
+            Some(usize::MAX - 255)
+        );
+        assert_eq!(align_down(usize::MAX, 256), usize::MAX - 255);
+    }
+
+    #[test]
+    #[should_panic = "align_up overflow"]
+    fn align_up_panics_on_overflow() {
+        align_up(usize::MAX, 16);
+    }
+
+    #[test]
+    fn align_u64() {
+        assert_eq!(align_up_u64(0, 256), 0);
+        assert_eq!(align_up_u64(1, 256), 256);
+        assert_eq!(align_up_u64(1 << 40, 256), 1 << 40);
+        assert_eq!(align_up_u64((1 << 40) + 1, 1 << 20), (1 << 40) + (1 << 20));
+        assert_eq!(align_down_u64((1 << 40) + 1, 1 << 20), 1 << 40);
+        assert!(is_aligned_u64(1 << 40, 1 << 20));
+        assert!(!is_aligned_u64((1 << 40) + 4, 8));
+
+        assert_eq!(checked_align_up_u64(u64::MAX, 1), Some(u64::MAX));
+        assert_eq!(checked_align_up_u64(u64::MAX, 4), None);
+    }
+
+    #[test]
+    #[should_panic = "align_up_u64 overflow"]
+    fn align_up_u64_panics_on_overflow() {
+        align_up_u64(u64::MAX - 1, 4);
+    }
+
+    #[test]
+    fn mip_extents() {
+        let extent = Extent3::new(256, 64, 1);
+        assert_eq!(mip_extent(extent, 0), extent);
+        assert_eq!(mip_extent(extent, 1), Extent3::new(128, 32, 1));
+        assert_eq!(mip_extent(extent, 6), Extent3::new(4, 1, 1));
+        assert_eq!(mip_extent(extent, 8), Extent3::new(1, 1, 1));
+
+        // Levels past the end of the chain stay 1x1x1.
+        assert_eq!(mip_extent(extent, 9), Extent3::new(1, 1, 1));
+        assert_eq!(mip_extent(extent, 32), Extent3::new(1, 1, 1));
+        assert_eq!(mip_extent(extent, u32::MAX), Extent3::new(1, 1, 1));
+
+        // Odd sizes are rounded down.
+        assert_eq!(mip_extent(Extent3::new(5, 3, 7), 1), Extent3::new(2, 1, 3));
+        assert_eq!(
+            mip_extent(Extent3::new(u32::MAX, u32::MAX, u32::MAX), 31),
+            Extent3::new(1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn mip_level_count() {
+        assert_eq!(mip_levels_for(Extent3::new(1, 1, 1)), 1);
+        assert_eq!(mip_levels_for(Extent3::new(2, 1, 1)), 2);
+        assert_eq!(mip_levels_for(Extent3::new(256, 256, 1)), 9);
+        assert_eq!(mip_levels_for(Extent3::new(257, 1, 1)), 9);
+        assert_eq!(mip_levels_for(Extent3::new(1, 1, 1024)), 11);
+        assert_eq!(mip_levels_for(Extent3::new(0, 0, 0)), 1);
+        assert_eq!(mip_levels_for(Extent3::new(u32::MAX, 1, 1)), 32);
+
+        // Last level of the chain is 1x1x1.
+        let extent = Extent3::new(1920, 1080, 1);
+        let levels = mip_levels_for(extent);
+        assert_eq!(mip_extent(extent, levels - 1), Extent3::new(1, 1, 1));
+        assert_ne!(mip_extent(extent, levels - 2), Extent3::new(1, 1, 1));
+    }
+
+    #[test]
+    fn data_layout() {
+        let layout =
+            image_data_layout(PixelFormat::Rgba8Unorm, Extent3::new(3, 2, 1), 256).unwrap();
+        assert_eq!(
+            layout,
+            ImageDataLayout {
+                bytes_per_line: 256,
+                bytes_per_plane: 512,
+                total_size: 512,
+            }
+        );
+
+        let layout = image_data_layout(PixelFormat::R8Unorm, Extent3::new(5, 3, 2), 1).unwrap();
+        assert_eq!(
+            layout,
+            ImageDataLayout {
+                bytes_per_line: 5,
+                bytes_per_plane: 15,
+                total_size: 30,
+            }
+        );
+
+        // Zero alignment is treated as tightly packed.
+        let layout = image_data_layout(PixelFormat::R16Float, Extent3::new(3, 1, 1), 0).unwrap();
+        assert_eq!(layout.bytes_per_line, 6);
+    }
+
+    #[test]
+    fn data_layout_pitch_is_multiple_of_texel() {
+        // 8-byte texels with 12-byte alignment need 24-byte pitch granularity.
+        let layout =
+            image_data_layout(PixelFormat::Rgba16Float, Extent3::new(1, 1, 1), 12).unwrap();
+        assert_eq!(layout.bytes_per_line, 24);
+        assert_eq!(layout.bytes_per_line % 12, 0);
+        assert_eq!(layout.bytes_per_line % PixelFormat::Rgba16Float.size(), 0);
+    }
+
+    #[test]
+    fn data_layout_smallest_mip() {
+        let extent = Extent3::new(1024, 512, 1);
+        let last = mip_extent(extent, mip_levels_for(extent) - 1);
+
+        let layout = image_data_layout(PixelFormat::Rgba32Float, last, 256).unwrap();
+        assert_eq!(
+            layout,
+            ImageDataLayout {
+                bytes_per_line: 256,
+                bytes_per_plane: 256,
+                total_size: 256,
+            }
+        );
+    }
+
+    #[test]
+    fn data_layout_overflow() {
+        let huge = Extent3::new(u32::MAX, u32::MAX, u32::MAX);
+        assert_eq!(image_data_layout(PixelFormat::Rgba32Float, huge, 256), None);
+
+        let wide = Extent3::new(u32::MAX, u32::MAX, 1);
+        assert_eq!(image_data_layout(PixelFormat::Rgba32Float, wide, 256), None);
+
+        // Alignment that overflows when combined with texel size.
+        assert_eq!(
+            image_data_layout(PixelFormat::Rgba16Float, Extent3::new(1, 1, 1), usize::MAX),
+            None
+        );
+    }
+}
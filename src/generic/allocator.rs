@@ -0,0 +1,135 @@
+/// Configuration of device memory allocator.
+///
+/// Default configuration is a reasonable starting point for desktop GPUs.
+/// Linear sub-allocation is disabled by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AllocatorConfig {
+    /// Requests of this size in bytes or larger
+    /// are served by dedicated memory objects.
+    pub dedicated_threshold: u64,
+
+    /// Requests of this size in bytes or larger
+    /// are served by dedicated memory objects if driver prefers so.
+    ///
+    /// Images and buffers of this size in bytes or larger
+    /// for which driver prefers dedicated memory are bound
+    /// to memory objects dedicated to them.
+    pub preferred_dedicated_threshold: u64,
+
+    /// Images and buffers of this size in bytes or larger
+    /// are bound to memory objects dedicated to them.
+    ///
+    /// Resources for which driver requires dedicated memory
+    /// get it regardless of the size.
+    /// Dedicated allocations are reported in [`AllocatorStats`].
+    pub dedicated_resource_threshold: u64,
+
+    /// Transient requests of this size in bytes or larger
+    /// are served by dedicated memory objects.
+    pub transient_dedicated_threshold: u64,
+
+    /// Size in bytes of the first chunk of free-list allocator.
+    pub starting_free_list_chunk: u64,
+
+    /// Upper limit for size in bytes of chunks of free-list allocator.
+    pub final_free_list_chunk: u64,
+
+    /// Minimal size in bytes of buddy allocator blocks.
+    pub minimal_buddy_size: u64,
+
+    /// Initial size in bytes of memory objects of buddy allocator.
+    pub initial_buddy_dedicated_size: u64,
+
+    /// Host-accessible buffers of this size in bytes or smaller
+    /// are sub-allocated linearly from shared chunks of memory.
+    ///
+    /// Chunk is reused once all buffers allocated from it are dropped.
+    /// Buffers referenced by submitted commands are dropped
+    /// no earlier than queue checkpoint observes completion of those commands.
+    /// This avoids fragmentation when many small short-lived buffers are created.
+    ///
+    /// Zero disables linear sub-allocation.
+    pub linear_threshold: u64,
+
+    /// Size in bytes of chunks used for linear sub-allocation.
+    pub linear_chunk_size: u64,
+
+    /// Allocate images and device-only buffers from host-visible memory
+    /// when device memory is exhausted.
+    ///
+    /// Resources in host-visible memory are slower to access by the device,
+    /// but this keeps application running when device memory is over-committed.
+    /// Each fallback is reported with a warning
+    /// and counted in [`AllocatorStats`].
+    pub fallback_to_host_memory: bool,
+
+    /// Upper limit for size in bytes of memory allocated from device-local memory types.
+    ///
+    /// Allocations over the budget fail as if device memory is exhausted,
+    /// so with [`fallback_to_host_memory`](Self::fallback_to_host_memory)
+    /// they are served from host-visible memory instead.
+    /// Useful to simulate devices with less memory.
+    ///
+    /// Not limited by default.
+    pub device_local_budget: u64,
+}
+
+impl Default for AllocatorConfig {
+    fn default() -> Self {
+        AllocatorConfig {
+            dedicated_threshold: 32 * 1024 * 1024,
+            preferred_dedicated_threshold: 1024 * 1024,
+            dedicated_resource_threshold: 32 * 1024 * 1024,
+            transient_dedicated_threshold: 128 * 1024 * 1024,
+            starting_free_list_chunk: 8 * 1024 * 1024,
+            final_free_list_chunk: 128 * 1024 * 1024,
+            minimal_buddy_size: 1024,
+            initial_buddy_dedicated_size: 8 * 1024 * 1024,
+            linear_threshold: 0,
+            linear_chunk_size: 4 * 1024 * 1024,
+            fallback_to_host_memory: false,
+            device_local_budget: u64::MAX,
+        }
+    }
+}
+
+/// Statistics of device memory allocator.
+///
+/// Fields not tracked by the backend are zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AllocatorStats {
+    /// Number of device memory objects currently allocated.
+    pub memory_objects: usize,
+
+    /// Size in bytes of device memory currently allocated.
+    pub reserved_bytes: u64,
+
+    /// Number of memory objects dedicated to a single image or buffer.
+    ///
+    /// See [`AllocatorConfig::dedicated_resource_threshold`].
+    pub dedicated_objects: usize,
+
+    /// Size in bytes of memory objects dedicated to a single image or buffer.
+    pub dedicated_bytes: u64,
+
+    /// Peak size in bytes of device memory allocated since device creation.
+    pub peak_reserved_bytes: u64,
+
+    /// Number of chunks used for linear sub-allocation.
+    /// Includes chunks that buffers moved by compaction are packed into.
+    pub linear_chunks: usize,
+
+    /// Number of buffers sub-allocated linearly.
+    /// Includes buffers moved by compaction.
+    pub linear_buffers: usize,
+
+    /// Number of allocations that fell back to host-visible memory
+    /// since device creation.
+    ///
+    /// See [`AllocatorConfig::fallback_to_host_memory`].
+    pub fallback_allocations: usize,
+
+    /// Size in bytes of allocations that fell back to host-visible memory
+    /// since device creation.
+    pub fallback_bytes: u64,
+}
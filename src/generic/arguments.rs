@@ -1,6 +1,6 @@
 use crate::backend::{ComputeCommandEncoder, RenderCommandEncoder};
 
-use super::ShaderStages;
+use super::{ShaderStages, WellKnownSampler};
 
 
 /// Kind of the shader argument.
@@ -27,6 +27,13 @@ pub enum ArgumentKind {
     /// Argument is sampler.
     /// Read-only object that describes how to sample an image.
     Sampler,
+
+    /// Argument is a bindless table.
+    /// Array of sampled images that is indexed in the shader
+    /// and updated independently of command recording.
+    ///
+    /// Group with a bindless table may not contain other arguments.
+    BindlessTable,
 }
 
 /// Layout of the shader argument slot.
@@ -39,7 +46,17 @@ pub struct ArgumentLayout {
     pub size: usize,
 
     /// Stages that the argument may be used in.
+    ///
+    /// Pipeline creation fails if shader of other stage uses the argument.
     pub stages: ShaderStages,
+
+    /// Sampler baked into the layout.
+    ///
+    /// Must be `None` unless argument kind is [`ArgumentKind::Sampler`].
+    /// Immutable sampler argument is not updated when arguments are bound.
+    /// On Vulkan it is part of the descriptor set layout,
+    /// so layouts with different immutable samplers are distinct.
+    pub immutable_sampler: Option<WellKnownSampler>,
 }
 
 /// Layout of the argument group.
@@ -49,6 +66,33 @@ pub struct ArgumentGroupLayout<'a> {
     pub arguments: &'a [ArgumentLayout],
 }
 
+/// Layout of the argument group retained by a pipeline.
+///
+/// Same as [`ArgumentGroupLayout`] the pipeline was created with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ArgumentGroupInfo {
+    /// Arguments in the group.
+    pub arguments: Vec<ArgumentLayout>,
+}
+
+impl ArgumentGroupInfo {
+    /// Returns layout of the group,
+    /// suitable for creating pipelines with compatible group.
+    pub fn layout(&self) -> ArgumentGroupLayout<'_> {
+        ArgumentGroupLayout {
+            arguments: &self.arguments,
+        }
+    }
+}
+
+impl From<ArgumentGroupLayout<'_>> for ArgumentGroupInfo {
+    fn from(layout: ArgumentGroupLayout<'_>) -> Self {
+        ArgumentGroupInfo {
+            arguments: layout.arguments.to_vec(),
+        }
+    }
+}
+
 /// This is not a part of public API.
 /// It is only public because it is used in the `mev` macro.
 #[doc(hidden)]
@@ -62,6 +106,35 @@ pub trait ArgumentsSealed {}
 /// Use attributes to override default argument kind and specify stages.
 pub trait Arguments: ArgumentsSealed + 'static {
     /// Layout of the argument group defined by the type.
+    ///
+    /// Use it in pipeline descriptors instead of writing layout by hand.
+    ///
+    /// ```no_run
+    /// use mev::Arguments;
+    ///
+    /// #[derive(mev::Arguments)]
+    /// struct Blur {
+    ///     #[mev(sampled, compute)]
+    ///     input: mev::Image,
+    ///     #[mev(storage, compute)]
+    ///     output: mev::Image,
+    /// }
+    ///
+    /// fn blur_pipeline(device: &mev::Device, library: mev::Library) -> mev::ComputePipeline {
+    ///     device
+    ///         .new_compute_pipeline(mev::ComputePipelineDesc {
+    ///             name: "blur",
+    ///             shader: mev::Shader {
+    ///                 library,
+    ///                 entry: "main".into(),
+    ///             },
+    ///             work_group_size: [8, 8, 1],
+    ///             constants: 0,
+    ///             arguments: &[Blur::LAYOUT],
+    ///         })
+    ///         .unwrap()
+    /// }
+    /// ```
     const LAYOUT: ArgumentGroupLayout<'static>;
 
     /// Bind arguments to the command encoder.
@@ -1,9 +1,13 @@
 use std::{
     borrow::Cow,
+    error::Error,
+    fmt,
     ops::{Index, Range, RangeFrom, RangeFull, RangeTo},
 };
 
-use crate::backend::Buffer;
+use crate::backend::{Buffer, CopyCommandEncoder, Device};
+
+use super::AllocError;
 
 bitflags::bitflags! {
     /// Buffer usage flags.
@@ -68,11 +72,32 @@ pub enum Memory {
     Download,
 }
 
+/// Returns largest size of buffer with given usage.
+///
+/// Whole buffers are bound as storage buffers,
+/// so storage buffers are limited by storage buffer range as well.
+pub(crate) fn max_buffer_size(
+    usage: BufferUsage,
+    max_buffer_size: u64,
+    max_storage_buffer_range: u64,
+) -> u64 {
+    if usage.contains(BufferUsage::STORAGE) {
+        max_buffer_size.min(max_storage_buffer_range)
+    } else {
+        max_buffer_size
+    }
+}
+
 /// Description used for buffer creation.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct BufferDesc<'a> {
-    /// Buffer size.
-    pub size: usize,
+    /// Buffer size in bytes.
+    ///
+    /// Size is `u64` on all targets, so sizes computed on the host
+    /// are never truncated before reaching the device.
+    /// Sizes that exceed device limits are reported as
+    /// [`AllocErrorKind::BufferTooLarge`](crate::AllocErrorKind::BufferTooLarge).
+    pub size: u64,
 
     /// Buffer usage flags.
     pub usage: BufferUsage,
@@ -82,6 +107,17 @@ pub struct BufferDesc<'a> {
 
     /// Buffer debug name.
     pub name: &'a str,
+
+    /// Allows using the buffer on queues of different families
+    /// without ownership transfer.
+    ///
+    /// With `false` buffer is owned by single queue family at a time,
+    /// which is the right choice for single-queue applications.
+    /// With `true` buffer is shared concurrently by all queue families of the device.
+    /// Concurrent access may be slower on some hardware.
+    ///
+    /// Metal resources are always shared across queues and this flag is ignored.
+    pub shared_across_queues: bool,
 }
 
 /// Description used for buffer creation with initial contents.
@@ -100,16 +136,81 @@ pub struct BufferInitDesc<'a> {
     pub name: &'a str,
 }
 
+/// Error that can happen when mapping buffer memory.
+///
+/// See [`Buffer::mapped_mut`](crate::Buffer::mapped_mut).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MapError {
+    /// Buffer is allocated with [`Memory::Device`] that host can't access.
+    NotHostVisible,
+
+    /// Buffer is referenced by other handles or by pending commands.
+    Shared,
+
+    /// Not enough host memory or address space to map the buffer.
+    OutOfMemory,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::NotHostVisible => write!(f, "buffer memory is not host-visible"),
+            MapError::Shared => write!(f, "buffer is shared"),
+            MapError::OutOfMemory => write!(f, "out of memory"),
+        }
+    }
+}
+
+impl Error for MapError {}
+
+/// Buffers selected to be moved by compaction.
+///
+/// Created with [`Device::plan_compaction`](crate::Device::plan_compaction)
+/// and executed with [`Queue::execute_compaction`](crate::Queue::execute_compaction).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompactionPlan {
+    pub(crate) buffers: Vec<usize>,
+    pub(crate) bytes: u64,
+    pub(crate) fragmented_bytes: u64,
+}
+
+impl CompactionPlan {
+    /// Returns indices of buffers to move.
+    #[inline(always)]
+    pub fn buffers(&self) -> &[usize] {
+        &self.buffers
+    }
+
+    /// Returns number of bytes to copy.
+    #[inline(always)]
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns number of free bytes in memory regions
+    /// that selected buffers are moved out of.
+    #[inline(always)]
+    pub fn fragmented_bytes(&self) -> u64 {
+        self.fragmented_bytes
+    }
+
+    /// Returns `true` if there's nothing to move.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
+
 /// Trait for types that can be used to index a buffer to get a slice of it.
-/// It is implemented for different range types over `usize`.
+/// It is implemented for different range types over `u64`.
 pub trait BufferIndex {
     /// Returns range for given buffer size.
-    fn range(self, size: usize) -> Range<usize>;
+    fn range(self, size: u64) -> Range<u64>;
 }
 
-impl BufferIndex for Range<usize> {
+impl BufferIndex for Range<u64> {
     #[inline(always)]
-    fn range(self, size: usize) -> Range<usize> {
+    fn range(self, size: u64) -> Range<u64> {
         debug_assert!(self.end <= size, "buffer range out of bounds");
         let end = self.end.min(size);
         let start = self.start.min(end);
@@ -117,18 +218,18 @@ impl BufferIndex for Range<usize> {
     }
 }
 
-impl BufferIndex for RangeFrom<usize> {
+impl BufferIndex for RangeFrom<u64> {
     #[inline(always)]
-    fn range(self, size: usize) -> Range<usize> {
+    fn range(self, size: u64) -> Range<u64> {
         debug_assert!(self.start <= size, "buffer range out of bounds");
         let start = self.start.min(size);
         start..size
     }
 }
 
-impl BufferIndex for RangeTo<usize> {
+impl BufferIndex for RangeTo<u64> {
     #[inline(always)]
-    fn range(self, size: usize) -> Range<usize> {
+    fn range(self, size: u64) -> Range<u64> {
         debug_assert!(self.end <= size, "buffer range out of bounds");
         let end = self.end.min(size);
         0..end
@@ -137,7 +238,7 @@ impl BufferIndex for RangeTo<usize> {
 
 impl BufferIndex for RangeFull {
     #[inline(always)]
-    fn range(self, size: usize) -> Range<usize> {
+    fn range(self, size: u64) -> Range<u64> {
         0..size
     }
 }
@@ -147,8 +248,8 @@ impl BufferIndex for RangeFull {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct BufferSlice<'a> {
     pub(crate) buffer: &'a Buffer,
-    pub(crate) offset: usize,
-    pub(crate) size: usize,
+    pub(crate) offset: u64,
+    pub(crate) size: u64,
 }
 
 impl PartialEq<Buffer> for BufferSlice<'_> {
@@ -172,12 +273,12 @@ impl BufferSlice<'_> {
     }
 
     #[inline(always)]
-    pub fn offset(&self) -> usize {
+    pub fn offset(&self) -> u64 {
         self.offset
     }
 
     #[inline(always)]
-    pub fn size(&self) -> usize {
+    pub fn size(&self) -> u64 {
         self.size
     }
 }
@@ -199,7 +300,7 @@ impl Buffer {
 
     /// Splits buffer into two ranges, from start to `at` and from `at` to end.
     #[inline(always)]
-    pub fn split_at(&self, at: usize) -> (BufferSlice, BufferSlice) {
+    pub fn split_at(&self, at: u64) -> (BufferSlice, BufferSlice) {
         let size = self.size();
         debug_assert!(at <= size);
         let at = at.min(size);
@@ -236,7 +337,7 @@ impl<'a> BufferSlice<'a> {
 
     /// Splits buffer into two ranges, from start to `at` and from `at` to end.
     #[inline(always)]
-    pub fn split_at(&self, at: usize) -> (BufferSlice<'a>, BufferSlice<'a>) {
+    pub fn split_at(&self, at: u64) -> (BufferSlice<'a>, BufferSlice<'a>) {
         let size = self.size();
         debug_assert!(at <= size);
         let at = at.min(size);
@@ -302,3 +403,146 @@ where
         (*self).as_buffer_slice()
     }
 }
+
+/// Buffer that grows on demand preserving its content.
+///
+/// Growing replaces the underlying buffer,
+/// so arguments and bindings that reference the old buffer must be updated.
+///
+/// ```ignore
+/// let mut copy = encoder.copy();
+/// if particles.ensure_capacity(&mut copy, new_size)? {
+///     arguments.particles = particles.buffer().clone();
+/// }
+/// copy.barrier(mev::PipelineStages::TRANSFER, mev::PipelineStages::TRANSFER);
+/// copy.write_buffer_slice(particles.buffer().slice(old_size..new_size), &spawned);
+/// ```
+pub struct GrowableBuffer {
+    device: Device,
+    buffer: Buffer,
+    usage: BufferUsage,
+    memory: Memory,
+    name: Box<str>,
+}
+
+impl GrowableBuffer {
+    /// Factor by which capacity grows at least on reallocation.
+    const GROWTH_FACTOR: u64 = 2;
+
+    /// Initial capacity in bytes of buffer created with zero size.
+    const MIN_CAPACITY: u64 = 64;
+
+    /// Creates new growable buffer with initial capacity of `desc.size` bytes.
+    ///
+    /// Buffers can't be empty, so zero `desc.size` gives
+    /// initial capacity of few bytes instead of failing with
+    /// [`AllocErrorKind::ZeroSizedBuffer`](crate::AllocErrorKind::ZeroSizedBuffer).
+    ///
+    /// Usage is extended with `TRANSFER_SRC` and `TRANSFER_DST`
+    /// to copy content on growth.
+    pub fn new(device: &Device, desc: BufferDesc) -> Result<Self, AllocError> {
+        let usage = desc.usage | BufferUsage::TRANSFER_SRC | BufferUsage::TRANSFER_DST;
+        let size = match desc.size {
+            0 => Self::MIN_CAPACITY,
+            size => size,
+        };
+        let buffer = device.new_buffer(BufferDesc {
+            size,
+            usage,
+            ..desc
+        })?;
+
+        Ok(GrowableBuffer {
+            device: device.clone(),
+            buffer,
+            usage,
+            memory: desc.memory,
+            name: desc.name.into(),
+        })
+    }
+
+    /// Returns current buffer.
+    #[inline(always)]
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Returns current capacity in bytes.
+    #[inline(always)]
+    pub fn capacity(&self) -> u64 {
+        self.buffer.size()
+    }
+
+    /// Returns slice of the whole current buffer.
+    #[inline(always)]
+    pub fn as_slice(&self) -> BufferSlice {
+        self.buffer.as_buffer_slice()
+    }
+
+    /// Ensures that buffer can hold at least `size` bytes.
+    ///
+    /// If capacity is not enough, allocates new buffer
+    /// and records copy of the old content into `encoder`.
+    /// Old buffer is kept alive by the encoder until the copy is complete.
+    ///
+    /// Copy is a transfer command and must be synchronized as such
+    /// with commands that access the buffer before and after it.
+    ///
+    /// Returns `true` if buffer was reallocated
+    /// and references to it must be updated.
+    pub fn ensure_capacity(
+        &mut self,
+        encoder: &mut CopyCommandEncoder,
+        size: u64,
+    ) -> Result<bool, AllocError> {
+        let capacity = self.buffer.size();
+        if size <= capacity {
+            return Ok(false);
+        }
+
+        let new_capacity = size.max(capacity.saturating_mul(Self::GROWTH_FACTOR));
+        let buffer = self.device.new_buffer(BufferDesc {
+            size: new_capacity,
+            usage: self.usage,
+            memory: self.memory,
+            name: &self.name,
+            shared_across_queues: false,
+        })?;
+
+        encoder.copy_buffer(&self.buffer, &buffer);
+        self.buffer = buffer;
+        Ok(true)
+    }
+}
+
+impl AsBufferSlice for GrowableBuffer {
+    #[inline(always)]
+    fn as_buffer_slice(&self) -> BufferSlice {
+        self.buffer.as_buffer_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB4: u64 = 1 << 32;
+
+    #[test]
+    fn max_size_non_storage() {
+        let usage = BufferUsage::UNIFORM | BufferUsage::TRANSFER_DST;
+        assert_eq!(max_buffer_size(usage, 1 << 40, GB4 - 1), 1 << 40);
+        assert_eq!(max_buffer_size(usage, u64::MAX, 0), u64::MAX);
+    }
+
+    #[test]
+    fn max_size_storage() {
+        let usage = BufferUsage::STORAGE | BufferUsage::TRANSFER_SRC;
+        assert_eq!(max_buffer_size(usage, 1 << 40, GB4 - 1), GB4 - 1);
+        assert_eq!(max_buffer_size(usage, GB4, GB4 + 1), GB4);
+        assert_eq!(
+            max_buffer_size(usage, u64::MAX, u64::from(u32::MAX)),
+            u64::from(u32::MAX)
+        );
+    }
+}
@@ -1,7 +1,11 @@
-use crate::{generic::Shader, ArgumentGroupLayout};
+use crate::{
+    generic::{Arguments, DeviceRepr, Shader},
+    ArgumentGroupLayout,
+};
 
 /// Compute pipeline descriptor.
 /// Used to create new compute pipelines.
+#[derive(Debug)]
 pub struct ComputePipelineDesc<'a> {
     /// Name of the compute pipeline.
     /// It can be used for debugging purposes.
@@ -19,3 +23,73 @@ pub struct ComputePipelineDesc<'a> {
     /// Arguments in the pipeline.
     pub arguments: &'a [ArgumentGroupLayout<'a>],
 }
+
+impl<'a> ComputePipelineDesc<'a> {
+    /// Returns builder of compute pipeline descriptor with given shader.
+    ///
+    /// Builder starts with work group size of `[1, 1, 1]`,
+    /// no constants and no arguments.
+    ///
+    /// ```ignore
+    /// let desc = mev::ComputePipelineDesc::builder(shader)
+    ///     .name("blur")
+    ///     .work_group_size([8, 8, 1])
+    ///     .arguments::<BlurArguments>()
+    ///     .build();
+    /// ```
+    pub fn builder(shader: Shader<'a>) -> ComputePipelineBuilder<'a> {
+        ComputePipelineBuilder {
+            desc: ComputePipelineDesc {
+                name: "",
+                shader,
+                work_group_size: [1, 1, 1],
+                constants: 0,
+                arguments: &[],
+            },
+        }
+    }
+}
+
+/// Builder for [`ComputePipelineDesc`].
+///
+/// See [`ComputePipelineDesc::builder`].
+pub struct ComputePipelineBuilder<'a> {
+    desc: ComputePipelineDesc<'a>,
+}
+
+impl<'a> ComputePipelineBuilder<'a> {
+    /// Sets name of the pipeline.
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.desc.name = name;
+        self
+    }
+
+    /// Sets size of the work group.
+    pub fn work_group_size(mut self, work_group_size: [u32; 3]) -> Self {
+        self.desc.work_group_size = work_group_size;
+        self
+    }
+
+    /// Sets size of constants to the size of the given type.
+    pub fn constants<T: DeviceRepr>(mut self) -> Self {
+        self.desc.constants = T::SIZE;
+        self
+    }
+
+    /// Sets single argument group with layout of the given type.
+    pub fn arguments<T: Arguments>(mut self) -> Self {
+        self.desc.arguments = &const { [T::LAYOUT] };
+        self
+    }
+
+    /// Sets argument groups layouts.
+    pub fn argument_layouts(mut self, arguments: &'a [ArgumentGroupLayout<'a>]) -> Self {
+        self.desc.arguments = arguments;
+        self
+    }
+
+    /// Returns built descriptor.
+    pub fn build(self) -> ComputePipelineDesc<'a> {
+        self.desc
+    }
+}
@@ -0,0 +1,429 @@
+//! Human-readable descriptions of created resources.
+//!
+//! Descriptions contain no backend handles,
+//! so they can be pasted into bug reports as is.
+
+use std::fmt;
+
+use super::{
+    ArgumentGroupInfo, BufferUsage, ComputePipelineDesc, ImageExtent, ImageUsage, PixelFormat,
+    PrimitiveTopology, RenderPipelineDesc, ResourceId, SamplerDesc,
+};
+
+/// Description of an image.
+pub(crate) struct ImageSummary<'a> {
+    pub id: ResourceId,
+    pub name: Option<&'a str>,
+    pub extent: ImageExtent,
+    pub format: PixelFormat,
+    pub usage: ImageUsage,
+    pub levels: u32,
+    pub layers: u32,
+}
+
+impl fmt::Debug for ImageSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Image");
+        s.field("id", &format_args!("{}", self.id));
+        if let Some(name) = self.name {
+            s.field("name", &name);
+        }
+        s.field("extent", &self.extent)
+            .field("format", &self.format)
+            .field("usage", &self.usage)
+            .field("levels", &self.levels)
+            .field("layers", &self.layers)
+            .finish()
+    }
+}
+
+/// Description of a buffer.
+pub(crate) struct BufferSummary<'a> {
+    pub id: ResourceId,
+    pub name: Option<&'a str>,
+    pub size: u64,
+    pub usage: Option<BufferUsage>,
+}
+
+impl fmt::Debug for BufferSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Buffer");
+        s.field("id", &format_args!("{}", self.id));
+        if let Some(name) = self.name {
+            s.field("name", &name);
+        }
+        s.field("size", &self.size);
+        if let Some(usage) = self.usage {
+            s.field("usage", &usage);
+        }
+        s.finish()
+    }
+}
+
+/// Description of a sampler.
+pub(crate) struct SamplerSummary<'a> {
+    pub id: ResourceId,
+    pub desc: &'a SamplerDesc,
+}
+
+impl fmt::Debug for SamplerSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sampler")
+            .field("id", &format_args!("{}", self.id))
+            .field("desc", self.desc)
+            .finish()
+    }
+}
+
+enum PipelineKind {
+    Render {
+        vertex_entry: Box<str>,
+        fragment_entry: Option<Box<str>>,
+        topology: PrimitiveTopology,
+        color_formats: Vec<PixelFormat>,
+        depth_format: Option<PixelFormat>,
+    },
+    Compute {
+        entry: Box<str>,
+        work_group_size: [u32; 3],
+    },
+}
+
+/// Parts of pipeline descriptor kept by created pipeline to describe it.
+pub(crate) struct PipelineSummary {
+    name: Box<str>,
+    kind: PipelineKind,
+    arguments: Vec<ArgumentGroupInfo>,
+    constants: usize,
+}
+
+impl PipelineSummary {
+    pub fn render(desc: &RenderPipelineDesc) -> Self {
+        let raster = desc.raster.as_ref();
+        PipelineSummary {
+            name: desc.name.into(),
+            kind: PipelineKind::Render {
+                vertex_entry: (*desc.vertex_shader.entry).into(),
+                fragment_entry: raster
+                    .and_then(|raster| raster.fragment_shader.as_ref())
+                    .map(|shader| (*shader.entry).into()),
+                topology: desc.primitive_topology,
+                color_formats: raster.map_or(Vec::new(), |raster| {
+                    raster.color_targets.iter().map(|ct| ct.format).collect()
+                }),
+                depth_format: raster.and_then(|raster| raster.depth_stencil.map(|ds| ds.format)),
+            },
+            arguments: desc.arguments.iter().copied().map(Into::into).collect(),
+            constants: desc.constants,
+        }
+    }
+
+    pub fn compute(desc: &ComputePipelineDesc) -> Self {
+        PipelineSummary {
+            name: desc.name.into(),
+            kind: PipelineKind::Compute {
+                entry: (*desc.shader.entry).into(),
+                work_group_size: desc.work_group_size,
+            },
+            arguments: desc.arguments.iter().copied().map(Into::into).collect(),
+            constants: desc.constants,
+        }
+    }
+
+    /// Returns layouts of argument groups of the pipeline.
+    pub fn arguments(&self) -> &[ArgumentGroupInfo] {
+        &self.arguments
+    }
+
+    /// Returns size of the constants of the pipeline.
+    pub fn constants(&self) -> usize {
+        self.constants
+    }
+
+    /// Returns description of the pipeline with given identifier.
+    pub fn describe(&self, id: ResourceId) -> String {
+        format!("{:#?}", PipelineDescription { id, summary: self })
+    }
+}
+
+struct PipelineDescription<'a> {
+    id: ResourceId,
+    summary: &'a PipelineSummary,
+}
+
+impl fmt::Debug for PipelineDescription<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = self.summary;
+        let mut s = match &summary.kind {
+            PipelineKind::Render {
+                vertex_entry,
+                fragment_entry,
+                topology,
+                color_formats,
+                depth_format,
+            } => {
+                let mut s = f.debug_struct("RenderPipeline");
+                s.field("id", &format_args!("{}", self.id))
+                    .field("name", &summary.name)
+                    .field("vertex_entry", vertex_entry)
+                    .field("fragment_entry", fragment_entry)
+                    .field("topology", topology)
+                    .field("color_formats", color_formats)
+                    .field("depth_format", depth_format);
+                s
+            }
+            PipelineKind::Compute {
+                entry,
+                work_group_size,
+            } => {
+                let mut s = f.debug_struct("ComputePipeline");
+                s.field("id", &format_args!("{}", self.id))
+                    .field("name", &summary.name)
+                    .field("entry", entry)
+                    .field("work_group_size", work_group_size);
+                s
+            }
+        };
+
+        s.field("arguments", &summary.arguments)
+            .field("constants", &summary.constants)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic::{
+        resource::ResourceIds, AddressMode, ArgumentKind, ArgumentLayout, BufferDesc, Extent2,
+        Filter, ImageDesc, Memory, ShaderStages,
+    };
+
+    #[test]
+    fn image_desc() {
+        let desc = ImageDesc::new_d2(
+            256,
+            128,
+            PixelFormat::Rgba8Srgb,
+            ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST,
+        )
+        .with_name("albedo");
+
+        assert_eq!(
+            format!("{desc:#?}"),
+            r#"ImageDesc {
+    extent: D2(
+        Extent(
+            [
+                256,
+                128,
+            ],
+        ),
+    ),
+    format: Rgba8Srgb,
+    usage: ImageUsage(
+        TRANSFER_DST | SAMPLED,
+    ),
+    layers: 1,
+    levels: 1,
+    name: "albedo",
+    shared_across_queues: false,
+}"#
+        );
+    }
+
+    #[test]
+    fn buffer_desc() {
+        let desc = BufferDesc {
+            size: 1024,
+            usage: BufferUsage::UNIFORM,
+            memory: Memory::Upload,
+            name: "camera",
+            shared_across_queues: false,
+        };
+
+        assert_eq!(
+            format!("{desc:#?}"),
+            r#"BufferDesc {
+    size: 1024,
+    usage: BufferUsage(
+        UNIFORM,
+    ),
+    memory: Upload,
+    name: "camera",
+    shared_across_queues: false,
+}"#
+        );
+    }
+
+    #[test]
+    fn sampler_desc() {
+        let desc = SamplerDesc::builder()
+            .filter(Filter::Linear)
+            .address_mode(AddressMode::ClampToEdge)
+            .anisotropy(8.0)
+            .build();
+
+        assert_eq!(
+            format!("{desc:#?}"),
+            r#"SamplerDesc {
+    min_filter: Linear,
+    mag_filter: Linear,
+    mip_map_mode: Nearest,
+    address_mode: [
+        ClampToEdge,
+        ClampToEdge,
+        ClampToEdge,
+    ],
+    anisotropy: Some(
+        8.0,
+    ),
+    min_lod: 0.0,
+    max_lod: inf,
+    normalized: true,
+}"#
+        );
+    }
+
+    #[test]
+    fn image_summary() {
+        let summary = ImageSummary {
+            id: ResourceIds::new().next(),
+            name: Some("albedo"),
+            extent: ImageExtent::D2(Extent2::new(256, 128)),
+            format: PixelFormat::Rgba8Srgb,
+            usage: ImageUsage::SAMPLED,
+            levels: 1,
+            layers: 1,
+        };
+
+        assert_eq!(
+            format!("{summary:#?}"),
+            r#"Image {
+    id: @0,
+    name: "albedo",
+    extent: D2(
+        Extent(
+            [
+                256,
+                128,
+            ],
+        ),
+    ),
+    format: Rgba8Srgb,
+    usage: ImageUsage(
+        SAMPLED,
+    ),
+    levels: 1,
+    layers: 1,
+}"#
+        );
+    }
+
+    #[test]
+    fn buffer_summary() {
+        let summary = BufferSummary {
+            id: ResourceIds::new().next(),
+            name: None,
+            size: 1024,
+            usage: Some(BufferUsage::UNIFORM),
+        };
+
+        assert_eq!(
+            format!("{summary:#?}"),
+            r#"Buffer {
+    id: @0,
+    size: 1024,
+    usage: BufferUsage(
+        UNIFORM,
+    ),
+}"#
+        );
+    }
+
+    #[test]
+    fn render_pipeline_summary() {
+        let summary = PipelineSummary {
+            name: "sprite".into(),
+            kind: PipelineKind::Render {
+                vertex_entry: "vs_main".into(),
+                fragment_entry: Some("fs_main".into()),
+                topology: PrimitiveTopology::Triangle,
+                color_formats: vec![PixelFormat::Bgra8Srgb],
+                depth_format: Some(PixelFormat::D32Float),
+            },
+            arguments: Vec::new(),
+            constants: 0,
+        };
+
+        assert_eq!(
+            summary.describe(ResourceIds::new().next()),
+            r#"RenderPipeline {
+    id: @0,
+    name: "sprite",
+    vertex_entry: "vs_main",
+    fragment_entry: Some(
+        "fs_main",
+    ),
+    topology: Triangle,
+    color_formats: [
+        Bgra8Srgb,
+    ],
+    depth_format: Some(
+        D32Float,
+    ),
+    arguments: [],
+    constants: 0,
+}"#
+        );
+    }
+
+    #[test]
+    fn compute_pipeline_summary() {
+        let summary = PipelineSummary {
+            name: "blur".into(),
+            kind: PipelineKind::Compute {
+                entry: "main".into(),
+                work_group_size: [8, 8, 1],
+            },
+            arguments: vec![ArgumentGroupInfo {
+                arguments: vec![ArgumentLayout {
+                    kind: ArgumentKind::StorageImage,
+                    size: 1,
+                    stages: ShaderStages::COMPUTE,
+                    immutable_sampler: None,
+                }],
+            }],
+            constants: 8,
+        };
+
+        assert_eq!(
+            summary.describe(ResourceIds::new().next()),
+            r#"ComputePipeline {
+    id: @0,
+    name: "blur",
+    entry: "main",
+    work_group_size: [
+        8,
+        8,
+        1,
+    ],
+    arguments: [
+        ArgumentGroupInfo {
+            arguments: [
+                ArgumentLayout {
+                    kind: StorageImage,
+                    size: 1,
+                    stages: ShaderStages(
+                        COMPUTE,
+                    ),
+                    immutable_sampler: None,
+                },
+            ],
+        },
+    ],
+    constants: 8,
+}"#
+        );
+    }
+}
@@ -8,5 +8,108 @@ bitflags::bitflags! {
         ///
         /// See [`Device::new_surface`](crate::Device::new_surface).
         const SURFACE = 0x0000_0000_0000_0000_0000_0000_0000_0001;
+
+        /// If this feature is enabled, samplers can use anisotropic filtering.
+        ///
+        /// See [`SamplerDesc::anisotropy`](crate::SamplerDesc::anisotropy).
+        const SAMPLER_ANISOTROPY = 0x0000_0000_0000_0000_0000_0000_0000_0002;
+
+        /// If this feature is enabled, blend factors can use second output of fragment shader.
+        ///
+        /// See [`BlendFactor::Src1Color`](crate::BlendFactor::Src1Color).
+        const DUAL_SOURCE_BLEND = 0x0000_0000_0000_0000_0000_0000_0000_0004;
+
+        /// If this feature is enabled, lines can be rasterized with width other than `1.0`.
+        ///
+        /// See [`RasterDesc::line_width`](crate::RasterDesc::line_width).
+        const WIDE_LINES = 0x0000_0000_0000_0000_0000_0000_0000_0008;
+
+        /// If this feature is enabled, acceleration structures can be built
+        /// and pipeline stages for ray tracing can be used in barriers.
+        ///
+        /// See [`PipelineStage::AccelerationStructureBuild`](crate::PipelineStage::AccelerationStructureBuild).
+        const RAY_TRACING = 0x0000_0000_0000_0000_0000_0000_0000_0010;
+
+        /// If this feature is enabled, damage rectangles of presented frames
+        /// are passed to the presentation engine.
+        /// Otherwise damage is ignored and whole frame is presented.
+        ///
+        /// Requires [`Features::SURFACE`].
+        ///
+        /// See [`CommandEncoder::present_with_damage`](crate::CommandEncoder::present_with_damage).
+        const INCREMENTAL_PRESENT = 0x0000_0000_0000_0000_0000_0000_0000_0020;
+
+        /// If this feature is enabled, out-of-bounds buffer accesses in shaders
+        /// are safe: reads return zero and writes are discarded.
+        ///
+        /// On Vulkan `robustBufferAccess` is enabled,
+        /// shaders compiled from source are bounds-checked unless
+        /// `VK_EXT_robustness2` guarantees zero reads.
+        /// On Metal shaders compiled from source are bounds-checked.
+        /// Shaders provided in SPIR-V or MSL are not modified.
+        const ROBUST_BUFFER_ACCESS = 0x0000_0000_0000_0000_0000_0000_0000_0040;
+
+        /// If this feature is enabled, out-of-bounds image loads in shaders
+        /// return zero.
+        ///
+        /// On Vulkan requires `robustImageAccess2` from `VK_EXT_robustness2`,
+        /// which also discards out-of-bounds stores.
+        /// On Metal image loads in shaders compiled from source are bounds-checked.
+        const ROBUST_IMAGE_ACCESS = 0x0000_0000_0000_0000_0000_0000_0000_0080;
+
+        /// If this feature is enabled, shaders can use 64-bit floats.
+        ///
+        /// Not supported on Metal.
+        const SHADER_FLOAT64 = 0x0000_0000_0000_0000_0000_0000_0000_0100;
+
+        /// If this feature is enabled, shaders can use 64-bit integers.
+        const SHADER_INT64 = 0x0000_0000_0000_0000_0000_0000_0000_0200;
+
+        /// If this feature is enabled, fragment and compute shaders
+        /// can use subgroup operations and barriers.
+        ///
+        /// On Vulkan requires all subgroup operations that naga can generate
+        /// to be supported in fragment and compute stages.
+        /// On Metal requires SIMD-group functions of Apple7 or Mac2 GPU family.
+        const SUBGROUP = 0x0000_0000_0000_0000_0000_0000_0000_0400;
+
+        /// If this feature is enabled, devices can create
+        /// [`BindlessTable`](crate::BindlessTable)s and shaders can index
+        /// binding arrays of sampled images with non-uniform indices.
+        ///
+        /// On Vulkan requires descriptor indexing of Vulkan 1.2
+        /// with update-after-bind for sampled images.
+        /// On Metal table is bound to consecutive texture slots
+        /// and its capacity is limited by the number of texture slots.
+        const DESCRIPTOR_INDEXING = 0x0000_0000_0000_0000_0000_0000_0000_0800;
+
+        /// If this feature is enabled, presentation engine signals a fence
+        /// when it stops using resources of presented frame.
+        /// Surfaces use these fences to release retired swapchains.
+        ///
+        /// Enabled automatically with [`Features::SURFACE`] when supported,
+        /// check [`Device::features`](crate::Device::features) to see if it is active.
+        /// Without it, presentation is considered complete
+        /// when the submission that presented the frame is complete,
+        /// which is earlier than the image is actually shown.
+        /// Frame pacing code should use [`Frame::has_present_fence`](crate::Frame::has_present_fence)
+        /// to know which one applies.
+        ///
+        /// On Vulkan requires `VK_EXT_swapchain_maintenance1`,
+        /// it can be disabled with [`Quirks::DISABLE_SWAPCHAIN_MAINTENANCE1`](crate::Quirks::DISABLE_SWAPCHAIN_MAINTENANCE1).
+        /// Not supported on Metal.
+        ///
+        /// Requires [`Features::SURFACE`].
+        const PRESENT_FENCES = 0x0000_0000_0000_0000_0000_0000_0000_1000;
+
+        /// Enables querying fault information when device is lost.
+        ///
+        /// Debug builds and builds with `debug` feature log reported faults,
+        /// including faulting addresses and vendor-specific codes,
+        /// when queue observes [`DeviceError::DeviceLost`](crate::DeviceError::DeviceLost).
+        ///
+        /// On Vulkan requires `VK_EXT_device_fault`.
+        /// Not supported on Metal.
+        const DEVICE_FAULT = 0x0000_0000_0000_0000_0000_0000_0000_2000;
     }
 }
@@ -574,6 +574,163 @@ impl PixelFormat {
             _ => false,
         }
     }
+
+    /// Returns format with the same layout that stores sRGB-encoded values.
+    ///
+    /// Shaders sample and write linear values, encoding is done by the device.
+    /// Returns the format itself if it is already sRGB
+    /// and `None` if there is no sRGB variant.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn srgb_variant(&self) -> Option<PixelFormat> {
+        match self {
+            PixelFormat::R8Unorm | PixelFormat::R8Srgb => Some(PixelFormat::R8Srgb),
+            PixelFormat::Rg8Unorm | PixelFormat::Rg8Srgb => Some(PixelFormat::Rg8Srgb),
+            PixelFormat::Rgb8Unorm | PixelFormat::Rgb8Srgb => Some(PixelFormat::Rgb8Srgb),
+            PixelFormat::Rgba8Unorm | PixelFormat::Rgba8Srgb => Some(PixelFormat::Rgba8Srgb),
+            PixelFormat::Bgr8Unorm | PixelFormat::Bgr8Srgb => Some(PixelFormat::Bgr8Srgb),
+            PixelFormat::Bgra8Unorm | PixelFormat::Bgra8Srgb => Some(PixelFormat::Bgra8Srgb),
+            _ => None,
+        }
+    }
+
+    /// Returns format with the same layout that stores values as is.
+    ///
+    /// Shaders sample and write stored values without conversion.
+    /// Returns the format itself if it is not sRGB.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn linear_variant(&self) -> PixelFormat {
+        match self {
+            PixelFormat::R8Srgb => PixelFormat::R8Unorm,
+            PixelFormat::Rg8Srgb => PixelFormat::Rg8Unorm,
+            PixelFormat::Rgb8Srgb => PixelFormat::Rgb8Unorm,
+            PixelFormat::Rgba8Srgb => PixelFormat::Rgba8Unorm,
+            PixelFormat::Bgr8Srgb => PixelFormat::Bgr8Unorm,
+            PixelFormat::Bgra8Srgb => PixelFormat::Bgra8Unorm,
+            format => *format,
+        }
+    }
+
+    /// Returns number of bits in depth component of the format.
+    /// Zero for formats without depth.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn depth_bits(&self) -> u8 {
+        match self {
+            PixelFormat::D16Unorm | PixelFormat::D16UnormS8Uint => 16,
+            PixelFormat::D24UnormS8Uint => 24,
+            PixelFormat::D32Float | PixelFormat::D32FloatS8Uint => 32,
+            _ => 0,
+        }
+    }
+
+    /// Returns true if formats differ only in sRGB encoding.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn is_srgb_pair(&self, other: PixelFormat) -> bool {
+        *self != other && self.linear_variant() == other.linear_variant()
+    }
+}
+
+bitflags::bitflags! {
+    /// Capabilities of the pixel format on particular device.
+    ///
+    /// Query with `Device::format_caps`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct FormatCaps: u32 {
+        /// Image with this format can be sampled in shaders.
+        const SAMPLED = 0x0000_0001;
+
+        /// Sampled image with this format can be filtered with `Filter::Linear`.
+        const FILTERABLE_LINEAR = 0x0000_0002;
+
+        /// Image with this format can be used as a storage image.
+        const STORAGE = 0x0000_0004;
+
+        /// Storage image with this format supports atomic operations.
+        const STORAGE_ATOMIC = 0x0000_0008;
+
+        /// Image with this format can be used as a color render target.
+        const COLOR_TARGET = 0x0000_0010;
+
+        /// Color render target with this format supports blending.
+        const BLENDABLE = 0x0000_0020;
+
+        /// Image with this format can be used as a depth-stencil render target.
+        const DEPTH_STENCIL_TARGET = 0x0000_0040;
+    }
+}
+
+impl FormatCaps {
+    /// Checks if sampled image with this format can be filtered with `Filter::Linear`.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn filterable_linear(&self) -> bool {
+        self.contains(FormatCaps::FILTERABLE_LINEAR)
+    }
+
+    /// Checks if color render target with this format supports blending.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn blendable(&self) -> bool {
+        self.contains(FormatCaps::BLENDABLE)
+    }
+
+    /// Checks if storage image with this format supports atomic operations.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn storage_atomic(&self) -> bool {
+        self.contains(FormatCaps::STORAGE_ATOMIC)
+    }
+}
+
+/// Requirements for depth format chosen by `Device::select_depth_format`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DepthFormatRequirements {
+    /// Format must have stencil component.
+    pub stencil: bool,
+
+    /// Minimal number of bits in depth component.
+    pub min_depth_bits: u8,
+
+    /// Image with the format must be sampled in shaders.
+    pub sampled: bool,
+}
+
+impl Default for DepthFormatRequirements {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn default() -> Self {
+        DepthFormatRequirements {
+            stencil: false,
+            min_depth_bits: 24,
+            sampled: false,
+        }
+    }
+}
+
+/// Depth formats in order of preference.
+///
+/// Depth-only formats come first, as they are cheaper when stencil is not needed.
+/// `D24UnormS8Uint` precedes `D32FloatS8Uint` because it is smaller,
+/// but it is missing on Apple GPUs and some AMD GPUs.
+const DEPTH_FORMATS: [PixelFormat; 5] = [
+    PixelFormat::D16Unorm,
+    PixelFormat::D32Float,
+    PixelFormat::D24UnormS8Uint,
+    PixelFormat::D16UnormS8Uint,
+    PixelFormat::D32FloatS8Uint,
+];
+
+/// Returns first depth format that satisfies requirements
+/// given capabilities of formats.
+pub(crate) fn find_depth_format(
+    requirements: DepthFormatRequirements,
+    mut caps: impl FnMut(PixelFormat) -> FormatCaps,
+) -> Option<PixelFormat> {
+    let mut required = FormatCaps::DEPTH_STENCIL_TARGET;
+    if requirements.sampled {
+        required |= FormatCaps::SAMPLED;
+    }
+
+    DEPTH_FORMATS.into_iter().find(|format| {
+        format.depth_bits() >= requirements.min_depth_bits
+            && (!requirements.stencil || format.is_stencil())
+            && caps(*format).contains(required)
+    })
 }
 
 /// Format of the vertex attribute.
@@ -749,3 +906,201 @@ pub enum VertexFormat {
     /// 32-bit floating-point number quadruple.
     Float32x4,
 }
+
+impl VertexFormat {
+    /// Returns size of the attribute in bytes.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn size(&self) -> usize {
+        match self {
+            VertexFormat::Uint8
+            | VertexFormat::Sint8
+            | VertexFormat::Unorm8
+            | VertexFormat::Snorm8 => 1,
+            VertexFormat::Uint16
+            | VertexFormat::Sint16
+            | VertexFormat::Unorm16
+            | VertexFormat::Snorm16
+            | VertexFormat::Float16
+            | VertexFormat::Uint8x2
+            | VertexFormat::Sint8x2
+            | VertexFormat::Unorm8x2
+            | VertexFormat::Snorm8x2 => 2,
+            VertexFormat::Uint8x3
+            | VertexFormat::Sint8x3
+            | VertexFormat::Unorm8x3
+            | VertexFormat::Snorm8x3 => 3,
+            VertexFormat::Uint32
+            | VertexFormat::Sint32
+            | VertexFormat::Unorm32
+            | VertexFormat::Snorm32
+            | VertexFormat::Float32
+            | VertexFormat::Uint16x2
+            | VertexFormat::Sint16x2
+            | VertexFormat::Unorm16x2
+            | VertexFormat::Snorm16x2
+            | VertexFormat::Float16x2
+            | VertexFormat::Uint8x4
+            | VertexFormat::Sint8x4
+            | VertexFormat::Unorm8x4
+            | VertexFormat::Snorm8x4 => 4,
+            VertexFormat::Uint16x3
+            | VertexFormat::Sint16x3
+            | VertexFormat::Unorm16x3
+            | VertexFormat::Snorm16x3
+            | VertexFormat::Float16x3 => 6,
+            VertexFormat::Uint32x2
+            | VertexFormat::Sint32x2
+            | VertexFormat::Unorm32x2
+            | VertexFormat::Snorm32x2
+            | VertexFormat::Float32x2
+            | VertexFormat::Uint16x4
+            | VertexFormat::Sint16x4
+            | VertexFormat::Unorm16x4
+            | VertexFormat::Snorm16x4
+            | VertexFormat::Float16x4 => 8,
+            VertexFormat::Uint32x3
+            | VertexFormat::Sint32x3
+            | VertexFormat::Unorm32x3
+            | VertexFormat::Snorm32x3
+            | VertexFormat::Float32x3 => 12,
+            VertexFormat::Uint32x4
+            | VertexFormat::Sint32x4
+            | VertexFormat::Unorm32x4
+            | VertexFormat::Snorm32x4
+            | VertexFormat::Float32x4 => 16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAIRS: [(PixelFormat, PixelFormat); 6] = [
+        (PixelFormat::R8Unorm, PixelFormat::R8Srgb),
+        (PixelFormat::Rg8Unorm, PixelFormat::Rg8Srgb),
+        (PixelFormat::Rgb8Unorm, PixelFormat::Rgb8Srgb),
+        (PixelFormat::Rgba8Unorm, PixelFormat::Rgba8Srgb),
+        (PixelFormat::Bgr8Unorm, PixelFormat::Bgr8Srgb),
+        (PixelFormat::Bgra8Unorm, PixelFormat::Bgra8Srgb),
+    ];
+
+    #[test]
+    fn srgb_pairs() {
+        for (linear, srgb) in PAIRS {
+            assert!(!linear.is_srgb());
+            assert!(srgb.is_srgb());
+
+            assert_eq!(linear.srgb_variant(), Some(srgb));
+            assert_eq!(srgb.srgb_variant(), Some(srgb));
+            assert_eq!(linear.linear_variant(), linear);
+            assert_eq!(srgb.linear_variant(), linear);
+
+            assert!(linear.is_srgb_pair(srgb));
+            assert!(srgb.is_srgb_pair(linear));
+            assert!(!linear.is_srgb_pair(linear));
+            assert!(!srgb.is_srgb_pair(srgb));
+        }
+    }
+
+    #[test]
+    fn no_srgb_variant() {
+        for format in [
+            PixelFormat::R8Snorm,
+            PixelFormat::Rgba16Float,
+            PixelFormat::R32Float,
+            PixelFormat::D32Float,
+        ] {
+            assert_eq!(format.srgb_variant(), None);
+            assert_eq!(format.linear_variant(), format);
+            assert!(!format.is_srgb_pair(PixelFormat::Rgba8Srgb));
+        }
+
+        assert!(!PixelFormat::Rgba8Unorm.is_srgb_pair(PixelFormat::Bgra8Srgb));
+    }
+
+    fn depth(stencil: bool, min_depth_bits: u8, sampled: bool) -> DepthFormatRequirements {
+        DepthFormatRequirements {
+            stencil,
+            min_depth_bits,
+            sampled,
+        }
+    }
+
+    /// Capabilities of typical desktop GPU.
+    fn all_caps(_: PixelFormat) -> FormatCaps {
+        FormatCaps::DEPTH_STENCIL_TARGET | FormatCaps::SAMPLED
+    }
+
+    #[test]
+    fn depth_format_preference() {
+        assert_eq!(
+            find_depth_format(depth(false, 16, false), all_caps),
+            Some(PixelFormat::D16Unorm)
+        );
+        assert_eq!(
+            find_depth_format(DepthFormatRequirements::default(), all_caps),
+            Some(PixelFormat::D32Float)
+        );
+        assert_eq!(
+            find_depth_format(depth(true, 16, false), all_caps),
+            Some(PixelFormat::D24UnormS8Uint)
+        );
+        assert_eq!(
+            find_depth_format(depth(true, 32, false), all_caps),
+            Some(PixelFormat::D32FloatS8Uint)
+        );
+        assert_eq!(find_depth_format(depth(false, 33, false), all_caps), None);
+    }
+
+    #[test]
+    fn depth_format_without_d24() {
+        // Apple GPUs and some AMD GPUs lack `D24UnormS8Uint`.
+        let caps = |format| match format {
+            PixelFormat::D24UnormS8Uint => FormatCaps::empty(),
+            format => all_caps(format),
+        };
+
+        assert_eq!(
+            find_depth_format(depth(true, 24, false), caps),
+            Some(PixelFormat::D32FloatS8Uint)
+        );
+        assert_eq!(
+            find_depth_format(depth(true, 16, false), caps),
+            Some(PixelFormat::D16UnormS8Uint)
+        );
+    }
+
+    #[test]
+    fn depth_format_sampled() {
+        let caps = |format| match format {
+            PixelFormat::D32Float => all_caps(format),
+            _ => FormatCaps::DEPTH_STENCIL_TARGET,
+        };
+
+        assert_eq!(
+            find_depth_format(depth(false, 16, false), caps),
+            Some(PixelFormat::D16Unorm)
+        );
+        assert_eq!(
+            find_depth_format(depth(false, 16, true), caps),
+            Some(PixelFormat::D32Float)
+        );
+        assert_eq!(find_depth_format(depth(true, 16, true), caps), None);
+    }
+
+    #[test]
+    fn depth_format_queries_candidates_only() {
+        let mut queried = Vec::new();
+        let found = find_depth_format(depth(true, 24, false), |format| {
+            queried.push(format);
+            FormatCaps::empty()
+        });
+
+        assert_eq!(found, None);
+        assert_eq!(
+            queried,
+            [PixelFormat::D24UnormS8Uint, PixelFormat::D32FloatS8Uint]
+        );
+    }
+}
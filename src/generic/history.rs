@@ -0,0 +1,142 @@
+use crate::backend::{CopyCommandEncoder, Device, Image, Queue};
+
+use super::{AllocError, ImageDesc, ImageUsage, PipelineStages};
+
+/// Copy of the previous frame's image for temporal effects.
+///
+/// Holds two images, one is read by the current frame
+/// and the other receives the copy of the current frame.
+/// They swap when the next frame resolves its image,
+/// so the image returned by [`previous`](HistoryImage::previous)
+/// is never written by the frame that reads it.
+///
+/// Resolve once per frame, after the last read of the previous image,
+/// and submit one checkpoint per frame.
+///
+/// ```ignore
+/// let mut history = mev::HistoryImage::new(&device, desc)?;
+///
+/// // Every frame.
+/// if history.has_previous() {
+///     compute.with_arguments(0, &TaaArguments {
+///         current: color.clone(),
+///         previous: history.previous().clone(),
+///         output: output.clone(),
+///     });
+///     // ...
+/// }
+/// history.resolve(&queue, &mut encoder.copy(), &output);
+/// queue.submit_one(cbuf, true)?;
+/// ```
+pub struct HistoryImage {
+    images: [Image; 2],
+
+    /// Index of the image with the previous frame's content.
+    read: usize,
+
+    /// Checkpoint index of the queue when image was last resolved.
+    resolved_at: Option<u64>,
+}
+
+impl HistoryImage {
+    /// Creates two images from the description.
+    ///
+    /// `TRANSFER_DST` and `SAMPLED` usages are added.
+    pub fn new(device: &Device, mut desc: ImageDesc) -> Result<Self, AllocError> {
+        desc.usage |= ImageUsage::TRANSFER_DST | ImageUsage::SAMPLED;
+
+        let second = ImageDesc {
+            extent: desc.extent,
+            format: desc.format,
+            usage: desc.usage,
+            layers: desc.layers,
+            levels: desc.levels,
+            name: desc.name,
+            shared_across_queues: desc.shared_across_queues,
+        };
+
+        Ok(HistoryImage {
+            images: [device.new_image(desc)?, device.new_image(second)?],
+            read: 0,
+            resolved_at: None,
+        })
+    }
+
+    /// Returns `true` if the image was resolved at least once.
+    ///
+    /// Previous image has no content before that.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn has_previous(&self) -> bool {
+        self.resolved_at.is_some()
+    }
+
+    /// Returns image with the content of the previous frame.
+    ///
+    /// It is synchronized for reading by any stage of the current frame.
+    /// After [`resolve`](HistoryImage::resolve) it returns
+    /// the image resolved by this frame.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn previous(&self) -> &Image {
+        &self.images[self.read]
+    }
+
+    /// Records the copy of `current` image into the history
+    /// and makes it the previous image for the next frame.
+    ///
+    /// `current` must have the same format and extent as history images,
+    /// `TRANSFER_SRC` usage and be written before the copy.
+    /// Barriers for all stages are recorded before and after the copy.
+    /// First resolve fills both images.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called twice before new checkpoint is submitted to the queue,
+    /// as the image read by the current frame would be overwritten.
+    pub fn resolve(&mut self, queue: &Queue, encoder: &mut CopyCommandEncoder, current: &Image) {
+        assert_eq!(
+            current.format(),
+            self.images[0].format(),
+            "Resolved image must have the same format as history images"
+        );
+        assert_eq!(
+            current.extent(),
+            self.images[0].extent(),
+            "Resolved image must have the same extent as history images"
+        );
+
+        let check_point = queue.check_point_index();
+        assert_ne!(
+            self.resolved_at,
+            Some(check_point),
+            "History image is resolved twice within one frame"
+        );
+
+        let write = 1 - self.read;
+
+        encoder.sync_image(PipelineStages::ALL_NON_RT, PipelineStages::TRANSFER, current);
+
+        if self.resolved_at.is_none() {
+            for image in &self.images {
+                encoder.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, image);
+                encoder.copy_image(current, image);
+                encoder.sync_image(PipelineStages::TRANSFER, PipelineStages::ALL_NON_RT, image);
+            }
+        } else {
+            let dst = &self.images[write];
+            encoder.sync_image(PipelineStages::ALL_NON_RT, PipelineStages::TRANSFER, dst);
+            encoder.copy_image(current, dst);
+            encoder.sync_image(PipelineStages::TRANSFER, PipelineStages::ALL_NON_RT, dst);
+        }
+
+        encoder.sync_image(PipelineStages::TRANSFER, PipelineStages::ALL_NON_RT, current);
+
+        self.read = write;
+        self.resolved_at = Some(check_point);
+    }
+
+    /// Returns both history images.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn images(&self) -> &[Image; 2] {
+        &self.images
+    }
+}
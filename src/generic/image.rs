@@ -4,7 +4,7 @@ use std::{
     ops::{Mul, Range},
 };
 
-use super::{format::PixelFormat, Extent1, Extent2, Extent3};
+use super::{format::PixelFormat, Extent1, Extent2, Extent3, Offset3, OutOfMemory};
 
 /// Image component swizzle.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -211,6 +211,19 @@ impl ImageExtent {
             ImageExtent::D3(e) => e,
         }
     }
+
+    /// Returns extent with each dimension multiplied by `factor`.
+    ///
+    /// Dimension of the extent is preserved.
+    /// See [`Extent::scaled`](super::Extent::scaled) for rounding and clamping rules.
+    #[inline(always)]
+    pub fn scale(&self, factor: f32, min: u32) -> ImageExtent {
+        match self {
+            ImageExtent::D1(e) => ImageExtent::D1(e.scaled(factor, min)),
+            ImageExtent::D2(e) => ImageExtent::D2(e.scaled(factor, min)),
+            ImageExtent::D3(e) => ImageExtent::D3(e.scaled(factor, min)),
+        }
+    }
 }
 
 impl PartialEq<Extent1> for ImageExtent {
@@ -264,6 +277,14 @@ impl From<Extent3> for ImageExtent {
     }
 }
 
+/// Same as [`ImageExtent::into_3d`].
+impl From<ImageExtent> for Extent3 {
+    #[inline(always)]
+    fn from(extent: ImageExtent) -> Self {
+        extent.into_3d()
+    }
+}
+
 bitflags::bitflags! {
     /// Image usage flags.
     /// 
@@ -287,10 +308,22 @@ bitflags::bitflags! {
 
         /// Image can be used as a target for rendering.
         const TARGET = 0x0000_0010;
+
+        /// Image content exists only within a render pass.
+        ///
+        /// May be combined only with [`TARGET`](ImageUsage::TARGET).
+        /// Attachment must not be loaded or stored, see [`RenderPassError`](crate::RenderPassError).
+        /// Good for depth buffers that are not sampled later.
+        ///
+        /// On Apple GPUs image is memoryless and takes no memory.
+        /// On Vulkan image is created with transient attachment usage,
+        /// but memory is still allocated for it.
+        const TRANSIENT = 0x0000_0020;
     }
 }
 
 /// Description used for image creation.
+#[derive(Debug)]
 pub struct ImageDesc<'a> {
     /// Image extent.
     pub extent: ImageExtent,
@@ -309,6 +342,18 @@ pub struct ImageDesc<'a> {
 
     /// Image debug name.
     pub name: &'a str,
+
+    /// Allows using the image on queues of different families
+    /// without ownership transfer.
+    ///
+    /// With `false` image is owned by single queue family at a time,
+    /// which is the right choice for single-queue applications.
+    /// With `true` image is shared concurrently by all queue families of the device.
+    /// Concurrent access may disable image compression and be slower
+    /// on some hardware, so prefer it only for images actually used on several queues.
+    ///
+    /// Metal resources are always shared across queues and this flag is ignored.
+    pub shared_across_queues: bool,
 }
 
 impl<'a> ImageDesc<'a> {
@@ -321,6 +366,7 @@ impl<'a> ImageDesc<'a> {
             layers: 1,
             levels: 1,
             name: "",
+            shared_across_queues: false,
         }
     }
 
@@ -361,6 +407,22 @@ impl<'a> ImageDesc<'a> {
         self
     }
 
+    /// Make image transient.
+    ///
+    /// See [`ImageUsage::TRANSIENT`].
+    pub fn transient(mut self) -> Self {
+        self.usage |= ImageUsage::TRANSIENT;
+        self
+    }
+
+    /// Share image across queues of all families.
+    ///
+    /// See [`ImageDesc::shared_across_queues`].
+    pub fn shared_across_queues(mut self) -> Self {
+        self.shared_across_queues = true;
+        self
+    }
+
     /// Create a new 1D texture description.
     pub const fn new_d1_texture(width: u32, format: PixelFormat) -> Self {
         ImageDesc::new_d1(
@@ -428,6 +490,24 @@ impl<'a> ImageDesc<'a> {
     }
 }
 
+/// Aspects of the image accessible through the view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ViewAspect {
+    /// All aspects of the view format.
+    #[default]
+    Auto,
+
+    /// Only depth aspect of depth or depth-stencil format.
+    ///
+    /// Required to sample depth of combined depth-stencil images.
+    DepthOnly,
+
+    /// Only stencil aspect of stencil or depth-stencil format.
+    ///
+    /// Required to read stencil of combined depth-stencil images.
+    StencilOnly,
+}
+
 /// Description used for image view creation.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ViewDesc {
@@ -451,6 +531,9 @@ pub struct ViewDesc {
 
     /// Image component swizzle.
     pub swizzle: Swizzle,
+
+    /// Aspects of the image accessible through the view.
+    pub aspect: ViewAspect,
 }
 
 impl ViewDesc {
@@ -463,11 +546,21 @@ impl ViewDesc {
             base_level: 0,
             levels: 1,
             swizzle: Swizzle::IDENTITY,
+            aspect: ViewAspect::Auto,
         }
     }
 
     /// Set layers range of the view.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty or inverted.
     pub fn layers(self, range: Range<u32>) -> Self {
+        assert!(
+            range.start < range.end,
+            "View layers range {:?} is empty or inverted",
+            range
+        );
         Self {
             layers: range.end - range.start,
             base_layer: range.start,
@@ -476,7 +569,16 @@ impl ViewDesc {
     }
 
     /// Set mip levels range of the view.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty or inverted.
     pub fn levels(self, range: Range<u32>) -> Self {
+        assert!(
+            range.start < range.end,
+            "View levels range {:?} is empty or inverted",
+            range
+        );
         Self {
             levels: range.end - range.start,
             base_level: range.start,
@@ -488,4 +590,491 @@ impl ViewDesc {
     pub fn swizzle(self, swizzle: Swizzle) -> Self {
         Self { swizzle, ..self }
     }
+
+    /// Set aspects of the image accessible through the view.
+    pub fn aspect(self, aspect: ViewAspect) -> Self {
+        Self { aspect, ..self }
+    }
+
+    /// Checks that the view format has the aspect selected by the view.
+    pub(crate) fn check_aspect(&self, name: &str) -> Result<(), ViewError> {
+        let valid = match self.aspect {
+            ViewAspect::Auto => true,
+            ViewAspect::DepthOnly => self.format.is_depth(),
+            ViewAspect::StencilOnly => self.format.is_stencil(),
+        };
+
+        if !valid {
+            return Err(ViewError::AspectMismatch {
+                image: name.to_owned(),
+                aspect: self.aspect,
+                format: self.format,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the view accesses both depth and stencil aspects.
+    ///
+    /// Such views can't be sampled.
+    #[inline(always)]
+    pub(crate) fn is_combined_depth_stencil(&self) -> bool {
+        self.aspect == ViewAspect::Auto && self.format.is_depth() && self.format.is_stencil()
+    }
+
+    /// Checks that the view fits into the image with given number of layers and levels.
+    pub(crate) fn check_bounds(
+        &self,
+        name: &str,
+        layers: u32,
+        levels: u32,
+    ) -> Result<(), ViewError> {
+        let layers_end = self.base_layer.checked_add(self.layers);
+        if self.layers == 0 || layers_end.map_or(true, |end| end > layers) {
+            return Err(ViewError::LayersOutOfBounds {
+                image: name.to_owned(),
+                base: self.base_layer,
+                count: self.layers,
+                available: layers,
+            });
+        }
+
+        let levels_end = self.base_level.checked_add(self.levels);
+        if self.levels == 0 || levels_end.map_or(true, |end| end > levels) {
+            return Err(ViewError::LevelsOutOfBounds {
+                image: name.to_owned(),
+                base: self.base_level,
+                count: self.levels,
+                available: levels,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Error that can happen when creating an image view.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViewError {
+    /// Not enough memory to create the view.
+    OutOfMemory,
+
+    /// View layers range does not fit into the image.
+    LayersOutOfBounds {
+        /// Debug name of the image.
+        image: String,
+
+        /// Requested base layer.
+        base: u32,
+
+        /// Requested number of layers.
+        count: u32,
+
+        /// Number of layers in the image.
+        available: u32,
+    },
+
+    /// View mip levels range does not fit into the image.
+    LevelsOutOfBounds {
+        /// Debug name of the image.
+        image: String,
+
+        /// Requested base mip level.
+        base: u32,
+
+        /// Requested number of mip levels.
+        count: u32,
+
+        /// Number of mip levels in the image.
+        available: u32,
+    },
+
+    /// Image format has no sRGB variant.
+    NoSrgbVariant {
+        /// Debug name of the image.
+        image: String,
+
+        /// Image format.
+        format: PixelFormat,
+    },
+
+    /// Device doesn't support views with non-identity swizzle.
+    ///
+    /// Only Vulkan portability subset devices may lack the support.
+    SwizzleNotSupported {
+        /// Debug name of the image.
+        image: String,
+    },
+
+    /// Device doesn't support views that reinterpret image format.
+    ///
+    /// Only Vulkan portability subset devices may lack the support.
+    /// sRGB and linear variants of the image format are always supported.
+    FormatNotSupported {
+        /// Debug name of the image.
+        image: String,
+
+        /// Image format.
+        format: PixelFormat,
+
+        /// Requested view format.
+        view_format: PixelFormat,
+    },
+
+    /// Swapchain images can't be viewed with format other than their own.
+    ///
+    /// This includes sRGB and linear variants of the format,
+    /// swapchain should be configured with the format that is going to be rendered.
+    SwapchainFormat {
+        /// Debug name of the image.
+        image: String,
+
+        /// Image format.
+        format: PixelFormat,
+
+        /// Requested view format.
+        view_format: PixelFormat,
+    },
+
+    /// View format doesn't have the selected aspect.
+    AspectMismatch {
+        /// Debug name of the image.
+        image: String,
+
+        /// Requested aspect.
+        aspect: ViewAspect,
+
+        /// View format.
+        format: PixelFormat,
+    },
+}
+
+impl From<OutOfMemory> for ViewError {
+    #[inline(always)]
+    fn from(_: OutOfMemory) -> Self {
+        ViewError::OutOfMemory
+    }
+}
+
+impl fmt::Display for ViewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewError::OutOfMemory => write!(f, "out of memory"),
+            ViewError::LayersOutOfBounds {
+                image,
+                base,
+                count,
+                available,
+            } => write!(
+                f,
+                "view layers {}..{} are out of bounds of image '{}' with {} layers",
+                base,
+                u64::from(*base) + u64::from(*count),
+                image,
+                available
+            ),
+            ViewError::LevelsOutOfBounds {
+                image,
+                base,
+                count,
+                available,
+            } => write!(
+                f,
+                "view levels {}..{} are out of bounds of image '{}' with {} levels",
+                base,
+                u64::from(*base) + u64::from(*count),
+                image,
+                available
+            ),
+            ViewError::NoSrgbVariant { image, format } => write!(
+                f,
+                "image '{}' with format {:?} can't have sRGB view",
+                image, format
+            ),
+            ViewError::SwizzleNotSupported { image } => write!(
+                f,
+                "device doesn't support swizzled views, requested for image '{}'",
+                image
+            ),
+            ViewError::FormatNotSupported {
+                image,
+                format,
+                view_format,
+            } => write!(
+                f,
+                "device doesn't support view with format {:?} of image '{}' with format {:?}",
+                view_format, image, format
+            ),
+            ViewError::SwapchainFormat {
+                image,
+                format,
+                view_format,
+            } => write!(
+                f,
+                "swapchain image '{}' with format {:?} can't have view with format {:?}",
+                image, format, view_format
+            ),
+            ViewError::AspectMismatch {
+                image,
+                aspect,
+                format,
+            } => write!(
+                f,
+                "view of image '{}' with format {:?} can't have aspect {:?}",
+                image, format, aspect
+            ),
+        }
+    }
+}
+
+impl Error for ViewError {}
+
+/// Checks that subresource ranges of a barrier are not empty
+/// and fit into `levels` and `layers` of the image.
+///
+/// # Panics
+///
+/// Panics if ranges are empty or out of image bounds.
+#[track_caller]
+pub(crate) fn check_image_range(
+    levels: &Range<u32>,
+    layers: &Range<u32>,
+    image_levels: u32,
+    image_layers: u32,
+) {
+    assert!(
+        levels.start < levels.end && levels.end <= image_levels,
+        "Levels range {levels:?} is out of image levels 0..{image_levels}",
+    );
+    assert!(
+        layers.start < layers.end && layers.end <= image_layers,
+        "Layers range {layers:?} is out of image layers 0..{image_layers}",
+    );
+}
+
+/// Checks copy between regions of the same image.
+///
+/// Levels and layers are relative to the image, not to a view.
+///
+/// # Panics
+///
+/// Panics if source and destination regions overlap,
+/// as result of such copy is undefined.
+pub(crate) fn check_self_copy(
+    src_level: u32,
+    src_layers: Range<u32>,
+    src_offset: Offset3<u32>,
+    dst_level: u32,
+    dst_layers: Range<u32>,
+    dst_offset: Offset3<u32>,
+    extent: Extent3<u32>,
+) {
+    fn overlap(a: u32, b: u32, len: u32) -> bool {
+        a < b + len && b < a + len
+    }
+
+    let overlapping = src_level == dst_level
+        && src_layers.start < dst_layers.end
+        && dst_layers.start < src_layers.end
+        && overlap(src_offset.x(), dst_offset.x(), extent.width())
+        && overlap(src_offset.y(), dst_offset.y(), extent.height())
+        && overlap(src_offset.z(), dst_offset.z(), extent.depth());
+
+    assert!(
+        !overlapping,
+        "Copy from level {src_level} layers {src_layers:?} at {src_offset:?} \
+        to level {dst_level} layers {dst_layers:?} at {dst_offset:?} \
+        with extent {extent:?} reads and writes the same texels"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_ranges() {
+        let desc = ViewDesc::new(PixelFormat::Rgba8Unorm)
+            .layers(2..5)
+            .levels(1..3);
+
+        assert_eq!(desc.base_layer, 2);
+        assert_eq!(desc.layers, 3);
+        assert_eq!(desc.base_level, 1);
+        assert_eq!(desc.levels, 2);
+
+        let desc = ViewDesc::new(PixelFormat::Rgba8Unorm).levels(u32::MAX - 1..u32::MAX);
+        assert_eq!(desc.base_level, u32::MAX - 1);
+        assert_eq!(desc.levels, 1);
+    }
+
+    #[test]
+    #[should_panic = "View levels range 2..1 is empty or inverted"]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn view_levels_inverted() {
+        let _ = ViewDesc::new(PixelFormat::Rgba8Unorm).levels(2..1);
+    }
+
+    #[test]
+    #[should_panic = "View layers range 3..3 is empty or inverted"]
+    fn view_layers_empty() {
+        let _ = ViewDesc::new(PixelFormat::Rgba8Unorm).layers(3..3);
+    }
+
+    #[test]
+    fn view_aspects() {
+        let combined = ViewDesc::new(PixelFormat::D24UnormS8Uint);
+        assert!(combined.is_combined_depth_stencil());
+        assert_eq!(combined.check_aspect("ds"), Ok(()));
+
+        let depth = combined.aspect(ViewAspect::DepthOnly);
+        assert!(!depth.is_combined_depth_stencil());
+        assert_eq!(depth.check_aspect("ds"), Ok(()));
+
+        let stencil = combined.aspect(ViewAspect::StencilOnly);
+        assert!(!stencil.is_combined_depth_stencil());
+        assert_eq!(stencil.check_aspect("ds"), Ok(()));
+
+        assert!(!ViewDesc::new(PixelFormat::D32Float).is_combined_depth_stencil());
+        assert_eq!(
+            ViewDesc::new(PixelFormat::D32Float)
+                .aspect(ViewAspect::StencilOnly)
+                .check_aspect("depth"),
+            Err(ViewError::AspectMismatch {
+                image: "depth".to_owned(),
+                aspect: ViewAspect::StencilOnly,
+                format: PixelFormat::D32Float,
+            })
+        );
+        assert!(ViewDesc::new(PixelFormat::Rgba8Unorm)
+            .aspect(ViewAspect::DepthOnly)
+            .check_aspect("color")
+            .is_err());
+    }
+
+    #[test]
+    fn view_bounds() {
+        let desc = ViewDesc::new(PixelFormat::Rgba8Unorm)
+            .layers(0..6)
+            .levels(0..4);
+        assert_eq!(desc.check_bounds("cube", 6, 4), Ok(()));
+
+        let desc = ViewDesc::new(PixelFormat::Rgba8Unorm)
+            .layers(5..6)
+            .levels(3..4);
+        assert_eq!(desc.check_bounds("cube", 6, 4), Ok(()));
+
+        let desc = ViewDesc::new(PixelFormat::Rgba8Unorm).layers(4..7);
+        assert_eq!(
+            desc.check_bounds("cube", 6, 4),
+            Err(ViewError::LayersOutOfBounds {
+                image: "cube".to_owned(),
+                base: 4,
+                count: 3,
+                available: 6,
+            })
+        );
+
+        let desc = ViewDesc::new(PixelFormat::Rgba8Unorm).levels(2..5);
+        assert_eq!(
+            desc.check_bounds("cube", 6, 4),
+            Err(ViewError::LevelsOutOfBounds {
+                image: "cube".to_owned(),
+                base: 2,
+                count: 3,
+                available: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn view_bounds_overflow() {
+        // Fields set directly bypass builder checks.
+        let desc = ViewDesc {
+            base_layer: u32::MAX,
+            layers: 2,
+            ..ViewDesc::new(PixelFormat::Rgba8Unorm)
+        };
+        assert!(matches!(
+            desc.check_bounds("image", u32::MAX, 1),
+            Err(ViewError::LayersOutOfBounds { .. })
+        ));
+
+        let desc = ViewDesc {
+            levels: 0,
+            ..ViewDesc::new(PixelFormat::Rgba8Unorm)
+        };
+        assert!(matches!(
+            desc.check_bounds("image", 1, 1),
+            Err(ViewError::LevelsOutOfBounds { .. })
+        ));
+
+        let err = ViewDesc::new(PixelFormat::Rgba8Unorm)
+            .levels(u32::MAX - 1..u32::MAX)
+            .check_bounds("image", 1, 1)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "view levels {}..{} are out of bounds of image 'image' with 1 levels",
+                u32::MAX - 1,
+                u32::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn image_range() {
+        check_image_range(&(0..4), &(0..2), 4, 2);
+        check_image_range(&(3..4), &(1..2), 4, 2);
+    }
+
+    #[test]
+    #[should_panic = "Levels range 2..5 is out of image levels 0..4"]
+    fn image_range_levels_out_of_bounds() {
+        check_image_range(&(2..5), &(0..1), 4, 1);
+    }
+
+    #[test]
+    #[should_panic = "Layers range 1..1 is out of image layers 0..2"]
+    fn image_range_layers_empty() {
+        check_image_range(&(0..1), &(1..1), 1, 2);
+    }
+
+    #[test]
+    fn self_copy_disjoint() {
+        let extent = Extent3::new(4, 4, 1);
+
+        // Next mip level.
+        check_self_copy(0, 0..1, Offset3::ZERO, 1, 0..1, Offset3::ZERO, extent);
+
+        // Next layer.
+        check_self_copy(0, 0..2, Offset3::ZERO, 0, 2..4, Offset3::ZERO, extent);
+
+        // Adjacent region of the same layer.
+        check_self_copy(
+            0,
+            0..1,
+            Offset3::ZERO,
+            0,
+            0..1,
+            Offset3::new(4, 0, 0),
+            extent,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "reads and writes the same texels")]
+    fn self_copy_overlap() {
+        check_self_copy(
+            0,
+            0..2,
+            Offset3::ZERO,
+            0,
+            1..3,
+            Offset3::new(3, 3, 0),
+            Extent3::new(4, 4, 1),
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use super::{feature::Features, queue::QueueFlags};
+use super::{allocator::AllocatorConfig, feature::Features, queue::QueueFlags, quirks::Quirks};
 
 /// Error that can occur when creating an instance.
 /// 
@@ -17,6 +17,29 @@ impl fmt::Display for LoadError {
 
 impl std::error::Error for LoadError {}
 
+impl LoadError {
+    /// Returns `true` if graphics API library is not installed on the system.
+    ///
+    /// Installing or updating graphics drivers may help.
+    pub fn is_library_not_found(&self) -> bool {
+        self.0.is_library_not_found()
+    }
+
+    /// Returns `true` if graphics API version or operating system version
+    /// is too old.
+    pub fn is_unsupported(&self) -> bool {
+        self.0.is_unsupported()
+    }
+
+    /// Returns names of layers that are installed on the system
+    /// but failed to load.
+    ///
+    /// Empty if error is caused by something else.
+    pub fn missing_layers(&self) -> &[String] {
+        self.0.missing_layers()
+    }
+}
+
 /// Error that can occur when creating device from an instance.
 #[derive(Debug)]
 pub struct CreateError(pub(crate) crate::backend::CreateErrorKind);
@@ -30,6 +53,16 @@ impl fmt::Display for CreateError {
 
 impl std::error::Error for CreateError {}
 
+impl CreateError {
+    /// Returns names of extensions required by the device description
+    /// but not supported by the device.
+    ///
+    /// Empty if error is caused by something else.
+    pub fn missing_extensions(&self) -> &[String] {
+        self.0.missing_extensions()
+    }
+}
+
 /// Capabilities of a queue family of specific device.
 #[derive(Clone, Debug)]
 pub struct FamilyCapabilities {
@@ -40,12 +73,52 @@ pub struct FamilyCapabilities {
     pub queue_count: usize,
 }
 
+/// Limits of the specific device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceLimits {
+    /// Maximum number of argument groups a pipeline may use.
+    ///
+    /// On Vulkan this is `maxBoundDescriptorSets`,
+    /// which is as low as 4 on some mobile GPUs.
+    /// On Metal each buffer argument occupies one of 31 buffer slots
+    /// shared by all groups, so fewer groups may fit in practice.
+    ///
+    /// Pipeline creation fails if pipeline uses more groups.
+    pub max_argument_groups: u32,
+
+    /// Maximum size in bytes of a buffer bound as uniform buffer argument.
+    ///
+    /// On Vulkan this is `maxUniformBufferRange`, which is often 64KiB.
+    /// Larger data must be split into multiple buffers or bound as storage.
+    /// Pipeline creation fails if shader declares larger uniform buffer.
+    pub max_uniform_buffer_range: u64,
+
+    /// Maximum size in bytes of a buffer bound as storage buffer argument.
+    ///
+    /// On Vulkan this is `maxStorageBufferRange`, which is 128MiB on some mobile GPUs.
+    /// Larger data must be split into multiple buffers.
+    /// Pipeline creation fails if fixed-size part of shader's storage buffer is larger.
+    pub max_storage_buffer_range: u64,
+
+    /// Maximum capacity of a [`BindlessTable`](crate::BindlessTable).
+    ///
+    /// Zero if device doesn't support [`Features::DESCRIPTOR_INDEXING`].
+    /// Sampled images of other arguments used by the same pipeline stage
+    /// count against the same device limit on Vulkan.
+    /// On Metal shader binding arrays always occupy this many texture slots
+    /// and creating a library with a larger fixed-size binding array fails.
+    pub max_bindless_table_size: u32,
+}
+
 /// Capabilities of the specific device.
 #[derive(Clone, Debug)]
 pub struct DeviceCapabilities {
     /// List of features that are supported by the device.
     pub features: Features,
 
+    /// Limits of the device.
+    pub limits: DeviceLimits,
+
     /// List of queue families capabilities.
     pub families: Vec<FamilyCapabilities>,
 }
@@ -71,4 +144,14 @@ pub struct DeviceDesc<'a> {
     /// 
     /// It should not include features not supported by the device. See [`DeviceCapabilities::features`].
     pub features: Features,
+
+    /// Quirks that should be applied in addition to ones detected for the device.
+    ///
+    /// Use [`Quirks::empty()`] to apply only detected quirks.
+    pub quirks: Quirks,
+
+    /// Configuration of device memory allocator.
+    ///
+    /// Use [`AllocatorConfig::default()`] for default configuration.
+    pub allocator: AllocatorConfig,
 }
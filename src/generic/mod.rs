@@ -1,22 +1,36 @@
 // mod _arguments;
 mod acst;
+pub mod align;
+mod allocator;
 mod arguments;
 mod buffer;
 mod compute_pipeline;
 mod data;
+mod describe;
 mod feature;
 mod format;
+mod history;
 mod image;
 mod instance;
+mod parallel;
+mod per_frame;
 mod queue;
+mod quirks;
 mod render;
 mod render_pipeline;
+mod resource;
 mod sampler;
 mod shader;
 mod stages;
 mod surface;
+#[cfg(feature = "winit")]
+mod winit;
 
-use std::{error::Error, fmt, mem::{ManuallyDrop, MaybeUninit}};
+use std::{
+    error::Error,
+    fmt,
+    mem::{ManuallyDrop, MaybeUninit},
+};
 
 pub use self::{
     acst::{
@@ -24,40 +38,75 @@ pub use self::{
         BlasBuildDesc, BlasDesc, BlasFlags, BlasGeometryDesc, BlasTriangles, TlasBuildDesc,
         TlasDesc, TlasFlags, TlasInstanceDesc,
     },
+    allocator::{AllocatorConfig, AllocatorStats},
     arguments::{
-        ArgumentGroupLayout, ArgumentKind, ArgumentLayout, Arguments, ArgumentsField, Automatic,
+        ArgumentGroupInfo, ArgumentGroupLayout, ArgumentKind, ArgumentLayout, Arguments,
+        ArgumentsField, Automatic,
         /*Constant,*/ Sampled, Storage, Uniform,
     },
-    buffer::{AsBufferSlice, BufferDesc, BufferInitDesc, BufferSlice, BufferUsage, Memory},
-    compute_pipeline::ComputePipelineDesc,
+    buffer::{
+        AsBufferSlice, BufferDesc, BufferIndex, BufferInitDesc, BufferSlice, BufferUsage,
+        CompactionPlan, GrowableBuffer, MapError, Memory,
+    },
+    compute_pipeline::{ComputePipelineBuilder, ComputePipelineDesc},
     data::*,
     feature::Features,
-    format::{PixelFormat, VertexFormat},
-    image::{ComponentSwizzle, ImageDesc, ImageExtent, ImageUsage, Swizzle, ViewDesc},
+    format::{DepthFormatRequirements, FormatCaps, PixelFormat, VertexFormat},
+    history::HistoryImage,
+    image::{
+        ComponentSwizzle, ImageDesc, ImageExtent, ImageUsage, Swizzle, ViewAspect, ViewDesc,
+        ViewError,
+    },
     instance::{
-        Capabilities, CreateError, DeviceCapabilities, DeviceDesc, FamilyCapabilities, LoadError,
+        Capabilities, CreateError, DeviceCapabilities, DeviceDesc, DeviceLimits,
+        FamilyCapabilities, LoadError,
+    },
+    parallel::PipelinePending,
+    per_frame::{PerFrame, FRAMES_IN_FLIGHT},
+    queue::{QueueFlags, QueuePoolConfig, QueueStats, ReusableCommandsError, SubmissionId},
+    quirks::Quirks,
+    render::{
+        AttachmentDesc, AttachmentIndex, ClearColor, ClearDepthStencil, ColorAttachmentDesc,
+        DepthAttachmentDesc, LoadOp, RenderPassDesc, RenderPassError, RenderPassFlags, StoreOp,
     },
-    queue::QueueFlags,
-    render::{AttachmentDesc, ClearColor, ClearDepthStencil, LoadOp, RenderPassDesc, StoreOp},
     render_pipeline::{
         Blend, BlendDesc, BlendFactor, BlendOp, ColorTargetDesc, CompareFunction,
         CreatePipelineError, Culling, DepthStencilDesc, FrontFace, PrimitiveTopology, RasterDesc,
-        RenderPipelineDesc, VertexAttributeDesc, VertexLayoutDesc, VertexStepMode, WriteMask,
+        RenderPipelineBuilder, RenderPipelineDesc, RenderPipelineDescOwned, VertexAttributeDesc,
+        VertexLayoutDesc, VertexStepMode, WriteMask, FULLSCREEN_VERTEX_ENTRY,
+        FULLSCREEN_VERTEX_SHADER,
     },
-    sampler::{AddressMode, Filter, MipMapMode, SamplerDesc},
+    resource::ResourceId,
+    sampler::{AddressMode, Filter, MipMapMode, SamplerBuilder, SamplerDesc, WellKnownSampler},
     shader::{
-        CreateLibraryError, LibraryDesc, LibraryInput, Shader, ShaderLanguage, ShaderSource,
-        ShaderStage, ShaderStages,
+        CreateLibraryError, EntryPoint, LibraryDesc, LibraryInput, Shader, ShaderLanguage,
+        ShaderSource, ShaderStage, ShaderStages,
     },
     stages::{PipelineStage, PipelineStages},
-    surface::SurfaceError,
+    surface::{SuboptimalPolicy, SurfaceError},
 };
 
 pub(crate) use self::{
     arguments::ArgumentsSealed,
-    shader::{parse_shader, ShaderCompileError},
+    buffer::max_buffer_size,
+    describe::{BufferSummary, ImageSummary, PipelineSummary, SamplerSummary},
+    format::find_depth_format,
+    image::{check_image_range, check_self_copy},
+    parallel::{parallel_map, DeferredWorkers},
+    render_pipeline::{check_dual_source_blend, DualSourceBlendError},
+    resource::ResourceIds,
+    shader::{
+        check_argument_stages, check_buffer_ranges, check_entry_point, check_stage_interface,
+        compose_wgsl, parse_shader, reflect_entries, shader_capabilities, ArgumentStageMismatch,
+        BufferRangeExceeded, EntryReflection, ShaderCompileError, SourceFile,
+        StageInterfaceMismatch, UnknownEntryPoint,
+    },
+    surface::{Recreate, SuboptimalTracker},
 };
 
+#[cfg(any(debug_assertions, feature = "debug"))]
+pub(crate) use self::render::check_pipeline_formats;
+
 /// Error that can happen when device's memory is exhausted.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct OutOfMemory;
@@ -70,9 +119,165 @@ impl fmt::Display for OutOfMemory {
 
 impl Error for OutOfMemory {}
 
+/// Reason of resource allocation failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AllocErrorKind {
+    /// Device memory is exhausted.
+    OutOfDeviceMemory,
+
+    /// Host memory is exhausted.
+    OutOfHostMemory,
+
+    /// No memory type is compatible with the resource.
+    /// This may happen when format or usage is not supported.
+    NoCompatibleMemory,
+
+    /// Too many memory objects are allocated.
+    TooManyObjects,
+
+    /// Requested image dimension exceeds device limit.
+    ExceedsLimits {
+        /// Requested dimension.
+        dimension: u32,
+
+        /// Maximum dimension supported by the device.
+        max: u32,
+    },
+
+    /// Requested buffer size is zero.
+    ///
+    /// Zero-sized buffers are not allowed by graphics APIs.
+    ZeroSizedBuffer,
+
+    /// Requested buffer size exceeds device limit.
+    BufferTooLarge {
+        /// Requested size in bytes.
+        size: u64,
+
+        /// Maximum size in bytes supported by the device
+        /// for the requested usage.
+        max: u64,
+    },
+}
+
+impl fmt::Display for AllocErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocErrorKind::OutOfDeviceMemory => write!(f, "out of device memory"),
+            AllocErrorKind::OutOfHostMemory => write!(f, "out of host memory"),
+            AllocErrorKind::NoCompatibleMemory => write!(f, "no compatible memory types"),
+            AllocErrorKind::TooManyObjects => write!(f, "too many objects"),
+            AllocErrorKind::ExceedsLimits { dimension, max } => {
+                write!(f, "dimension {dimension} exceeds device limit {max}")
+            }
+            AllocErrorKind::ZeroSizedBuffer => write!(f, "buffer size is zero"),
+            AllocErrorKind::BufferTooLarge { size, max } => {
+                write!(f, "buffer size {size} exceeds device limit {max}")
+            }
+        }
+    }
+}
+
+/// Resource which allocation failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AllocResource {
+    /// Buffer of requested size in bytes.
+    Buffer { size: u64 },
+
+    /// Image with requested parameters.
+    Image {
+        extent: ImageExtent,
+        format: PixelFormat,
+        layers: u32,
+        levels: u32,
+    },
+}
+
+impl fmt::Display for AllocResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocResource::Buffer { size } => write!(f, "buffer of {size} bytes"),
+            AllocResource::Image {
+                extent,
+                format,
+                layers,
+                levels,
+            } => write!(
+                f,
+                "{format:?} image {}x{}x{} with {layers} layers and {levels} levels",
+                extent.width(),
+                extent.height(),
+                extent.depth()
+            ),
+        }
+    }
+}
+
+/// Error that can happen when buffer or image is created.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AllocError {
+    pub(crate) kind: AllocErrorKind,
+    pub(crate) name: Box<str>,
+    pub(crate) resource: AllocResource,
+}
+
+impl AllocError {
+    pub(crate) fn new(kind: AllocErrorKind, name: &str, resource: AllocResource) -> Self {
+        AllocError {
+            kind,
+            name: name.into(),
+            resource,
+        }
+    }
+
+    /// Returns reason of the failure.
+    #[inline(always)]
+    pub fn kind(&self) -> AllocErrorKind {
+        self.kind
+    }
+
+    /// Returns debug name of the resource.
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns requested resource.
+    #[inline(always)]
+    pub fn resource(&self) -> AllocResource {
+        self.resource
+    }
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to allocate {} '{}': {}",
+            self.resource, self.name, self.kind
+        )
+    }
+}
+
+impl Error for AllocError {}
+
+impl From<AllocError> for OutOfMemory {
+    #[inline(always)]
+    fn from(_: AllocError) -> Self {
+        OutOfMemory
+    }
+}
+
 pub enum DeviceError {
     OutOfMemory,
     DeviceLost,
+
+    /// Frame is presented from the queue of the family
+    /// that does not support presenting to the frame's surface.
+    PresentNotSupported,
+
+    /// Same swapchain image is presented more than once in a single submission.
+    FramePresentedTwice,
 }
 
 impl DeviceError {
@@ -80,6 +285,12 @@ impl DeviceError {
         match self {
             DeviceError::OutOfMemory => OutOfMemory,
             DeviceError::DeviceLost => panic!("device lost"),
+            DeviceError::PresentNotSupported => {
+                panic!("queue family does not support presenting to the surface")
+            }
+            DeviceError::FramePresentedTwice => {
+                panic!("swapchain image is presented twice in one submission")
+            }
         }
     }
 }
@@ -97,6 +308,8 @@ impl fmt::Debug for DeviceError {
         match self {
             DeviceError::OutOfMemory => write!(f, "DeviceError::OutOfMemory"),
             DeviceError::DeviceLost => write!(f, "DeviceError::DeviceLost"),
+            DeviceError::PresentNotSupported => write!(f, "DeviceError::PresentNotSupported"),
+            DeviceError::FramePresentedTwice => write!(f, "DeviceError::FramePresentedTwice"),
         }
     }
 }
@@ -107,6 +320,12 @@ impl fmt::Display for DeviceError {
         match self {
             DeviceError::OutOfMemory => write!(f, "out of memory"),
             DeviceError::DeviceLost => write!(f, "device lost"),
+            DeviceError::PresentNotSupported => {
+                write!(f, "queue family does not support presenting to the surface")
+            }
+            DeviceError::FramePresentedTwice => {
+                write!(f, "swapchain image is presented twice in one submission")
+            }
         }
     }
 }
@@ -344,6 +563,33 @@ impl<T: Copy> Extent3<T> {
     }
 }
 
+impl<const D: usize> Extent<u32, D> {
+    /// Returns extent with each dimension multiplied by `factor`.
+    ///
+    /// Dimensions are rounded to nearest integer, halves are rounded up,
+    /// and clamped to be at least `min`.
+    /// Results that don't fit into `u32` saturate to `u32::MAX`,
+    /// negative and NaN factors give `min`.
+    ///
+    /// Suitable for render targets with dynamic resolution.
+    ///
+    /// ```ignore
+    /// let target = frame.extent().scaled(0.75, 1);
+    /// ```
+    #[inline]
+    pub fn scaled(&self, factor: f32, min: u32) -> Self {
+        // `f64` represents all `u32` values exactly.
+        // Float to integer casts saturate and map NaN to zero.
+        self.map(|d| ((d as f64 * factor as f64).round() as u32).max(min))
+    }
+
+    /// Returns `true` if extent is not larger than `other` in any dimension.
+    #[inline]
+    pub fn fits_within(&self, other: Self) -> bool {
+        self.0.iter().zip(other.0).all(|(&d, o)| d <= o)
+    }
+}
+
 impl<T, const D: usize> Offset<T, D> {
     #[inline]
     pub fn map<U>(self, f: impl FnMut(T) -> U) -> Offset<U, D> {
@@ -400,6 +646,19 @@ impl<T, const D: usize> Extent<T, D> {
     }
 }
 
+/// Axis-aligned 2D rectangle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rect2<O = i32, E = u32> {
+    pub offset: Offset2<O>,
+    pub extent: Extent2<E>,
+}
+
+impl<O, E> Rect2<O, E> {
+    pub const fn new(offset: Offset2<O>, extent: Extent2<E>) -> Self {
+        Rect2 { offset, extent }
+    }
+}
+
 fn array_try_map<T, U, E, const N: usize>(array: [T; N], mut f: impl FnMut(T) -> Result<U, E>) -> Result<[U; N], E> {
     struct PartiallyUsed<T, const N: usize> {
         array: [MaybeUninit<T>; N],
@@ -501,3 +760,66 @@ impl_cast_as! {
     cast_as_f32 f32,
     cast_as_f64 f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_rounds_halves_up() {
+        assert_eq!(Extent2::new(3, 5).scaled(0.5, 1), Extent2::new(2, 3));
+        assert_eq!(
+            Extent2::new(1920, 1080).scaled(0.75, 1),
+            Extent2::new(1440, 810)
+        );
+        assert_eq!(Extent2::new(1, 1).scaled(0.5, 0), Extent2::new(1, 1));
+        assert_eq!(Extent2::new(100, 3).scaled(1.0, 1), Extent2::new(100, 3));
+    }
+
+    #[test]
+    fn scaled_clamps_to_min() {
+        assert_eq!(Extent2::new(640, 480).scaled(0.0, 1), Extent2::new(1, 1));
+        assert_eq!(Extent2::new(640, 480).scaled(0.0, 0), Extent2::new(0, 0));
+        assert_eq!(Extent2::new(640, 480).scaled(-2.0, 8), Extent2::new(8, 8));
+        assert_eq!(
+            Extent2::new(640, 480).scaled(f32::NAN, 4),
+            Extent2::new(4, 4)
+        );
+        assert_eq!(Extent2::new(10, 2).scaled(0.1, 1), Extent2::new(1, 1));
+    }
+
+    #[test]
+    fn scaled_saturates() {
+        assert_eq!(
+            Extent2::new(u32::MAX, 1 << 31).scaled(2.0, 1),
+            Extent2::new(u32::MAX, u32::MAX)
+        );
+        assert_eq!(
+            Extent3::new(u32::MAX, 1, 1).scaled(f32::INFINITY, 1),
+            Extent3::new(u32::MAX, u32::MAX, u32::MAX)
+        );
+        assert_eq!(
+            Extent2::new(u32::MAX, 7).scaled(1.0, 1),
+            Extent2::new(u32::MAX, 7)
+        );
+    }
+
+    #[test]
+    fn fits_within() {
+        let target = Extent2::new(1920, 1080);
+        assert!(target.fits_within(target));
+        assert!(Extent2::new(1280, 720).fits_within(target));
+        assert!(Extent2::new(0, 0).fits_within(target));
+        assert!(!Extent2::new(1921, 1080).fits_within(target));
+        assert!(!Extent2::new(1920, 1081).fits_within(target));
+        assert!(!Extent2::new(2560, 720).fits_within(target));
+
+        assert!(Extent3::new(4, 4, 1).fits_within(Extent3::new(4, 4, 2)));
+        assert!(!Extent3::new(4, 4, 3).fits_within(Extent3::new(4, 4, 2)));
+
+        // Scaled down extent always fits.
+        for factor in [0.25, 0.5, 0.75, 1.0] {
+            assert!(target.scaled(factor, 1).fits_within(target));
+        }
+    }
+}
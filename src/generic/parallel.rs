@@ -0,0 +1,236 @@
+use std::{
+    fmt,
+    num::NonZeroUsize,
+    panic::AssertUnwindSafe,
+    sync::{mpsc, Arc},
+};
+
+use parking_lot::{Condvar, Mutex};
+
+use super::CreatePipelineError;
+
+/// Maps items using scoped worker threads.
+///
+/// Spawns at most one worker per available CPU core.
+/// Workers take items one by one, so that expensive items don't stall others.
+/// Results are returned in the order of items.
+pub(crate) fn parallel_map<T, R>(items: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+{
+    let threads = std::thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(items.len());
+
+    if threads <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let len = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+
+    let mut results = std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let next = queue.lock().next();
+                        let Some((idx, item)) = next else {
+                            break;
+                        };
+                        results.push((idx, f(item)));
+                    }
+                    results
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = Vec::with_capacity(len);
+        for worker in workers {
+            match worker.join() {
+                Ok(worker_results) => results.extend(worker_results),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        }
+        results
+    });
+
+    results.sort_unstable_by_key(|&(idx, _)| idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Background workers for deferred pipeline creation.
+///
+/// Workers are spawned on first use and leave at least half
+/// of CPU cores to the rest of the application.
+/// Workers exit when owning device is dropped.
+pub(crate) struct DeferredWorkers {
+    sender: Mutex<Option<mpsc::Sender<Job>>>,
+}
+
+impl DeferredWorkers {
+    pub fn new() -> Self {
+        DeferredWorkers {
+            sender: Mutex::new(None),
+        }
+    }
+
+    /// Runs `f` on a worker thread and returns handle to its result.
+    pub fn spawn<P>(
+        &self,
+        f: impl FnOnce() -> Result<P, CreatePipelineError> + Send + 'static,
+    ) -> PipelinePending<P>
+    where
+        P: Send + 'static,
+    {
+        let slot = Arc::new(PendingSlot {
+            state: Mutex::new(PendingState::Waiting),
+            ready: Condvar::new(),
+        });
+
+        let completer = Completer { slot: slot.clone() };
+        let job: Job = Box::new(move || completer.complete(f()));
+
+        let mut sender = self.sender.lock();
+        let sender = sender.get_or_insert_with(spawn_workers);
+        if let Err(mpsc::SendError(job)) = sender.send(job) {
+            // All workers are gone, run on this thread instead.
+            job();
+        }
+
+        PipelinePending { slot }
+    }
+}
+
+fn spawn_workers() -> mpsc::Sender<Job> {
+    let threads = std::thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .div_ceil(2);
+
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for idx in 0..threads {
+        let receiver = receiver.clone();
+        let result = std::thread::Builder::new()
+            .name(format!("mev-pipelines-{idx}"))
+            .spawn(move || loop {
+                let job = receiver.lock().recv();
+                let Ok(job) = job else {
+                    break;
+                };
+                // Panic is reported to the waiting `PipelinePending`.
+                let _ = std::panic::catch_unwind(AssertUnwindSafe(job));
+            });
+
+        if let Err(err) = result {
+            tracing::error!("Failed to spawn pipeline worker thread: {err}");
+        }
+    }
+
+    sender
+}
+
+enum PendingState<P> {
+    Waiting,
+    Ready(Result<P, CreatePipelineError>),
+    Taken,
+    Panicked,
+}
+
+struct PendingSlot<P> {
+    state: Mutex<PendingState<P>>,
+    ready: Condvar,
+}
+
+/// Stores result into the slot.
+/// Marks the slot as panicked if dropped without result.
+struct Completer<P> {
+    slot: Arc<PendingSlot<P>>,
+}
+
+impl<P> Completer<P> {
+    fn complete(self, result: Result<P, CreatePipelineError>) {
+        *self.slot.state.lock() = PendingState::Ready(result);
+        self.slot.ready.notify_all();
+    }
+}
+
+impl<P> Drop for Completer<P> {
+    fn drop(&mut self) {
+        let mut state = self.slot.state.lock();
+        if let PendingState::Waiting = *state {
+            *state = PendingState::Panicked;
+            self.slot.ready.notify_all();
+        }
+    }
+}
+
+/// Pipeline that is being created on a background thread.
+///
+/// Returned by [`Device::new_render_pipeline_deferred`](crate::traits::Device::new_render_pipeline_deferred).
+/// Dropping it doesn't cancel creation, created pipeline is dropped instead.
+pub struct PipelinePending<P> {
+    slot: Arc<PendingSlot<P>>,
+}
+
+impl<P> fmt::Debug for PipelinePending<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipelinePending")
+            .field("ready", &self.is_ready())
+            .finish()
+    }
+}
+
+impl<P> PipelinePending<P> {
+    /// Returns `true` if result is ready and not yet taken.
+    pub fn is_ready(&self) -> bool {
+        matches!(*self.slot.state.lock(), PendingState::Ready(_))
+    }
+
+    /// Returns result of pipeline creation if it is ready.
+    ///
+    /// Returns `None` while pipeline is being created
+    /// and after result was taken.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pipeline creation panicked.
+    pub fn poll(&mut self) -> Option<Result<P, CreatePipelineError>> {
+        let mut state = self.slot.state.lock();
+        match std::mem::replace(&mut *state, PendingState::Taken) {
+            PendingState::Ready(result) => Some(result),
+            PendingState::Waiting => {
+                *state = PendingState::Waiting;
+                None
+            }
+            PendingState::Taken => None,
+            PendingState::Panicked => panic!("Deferred pipeline creation panicked"),
+        }
+    }
+
+    /// Blocks current thread until pipeline is created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pipeline creation panicked
+    /// or result was already taken with [`poll`](PipelinePending::poll).
+    pub fn block_on(self) -> Result<P, CreatePipelineError> {
+        let mut state = self.slot.state.lock();
+        loop {
+            match std::mem::replace(&mut *state, PendingState::Taken) {
+                PendingState::Ready(result) => return result,
+                PendingState::Waiting => {
+                    *state = PendingState::Waiting;
+                    self.slot.ready.wait(&mut state);
+                }
+                PendingState::Taken => panic!("Deferred pipeline was already taken"),
+                PendingState::Panicked => panic!("Deferred pipeline creation panicked"),
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::backend::Queue;
+
+use super::array_try_map;
+
+/// Number of frames that may be in flight on a queue.
+///
+/// Commands submitted before the last `FRAMES_IN_FLIGHT - 1` checkpoints
+/// are completed by the device.
+/// Queue blocks on checkpoint submission until that holds.
+pub const FRAMES_IN_FLIGHT: usize = 4;
+
+/// Copies of a resource, one for each frame in flight.
+///
+/// Resources written by host every frame, like uniform buffers,
+/// must not be overwritten while previous frames still read them.
+/// `PerFrame` selects a copy that is not used by pending commands
+/// according to the number of checkpoints submitted to the queue.
+///
+/// Copy returned by [`get`](PerFrame::get) is safe to write
+/// until the next checkpoint is submitted.
+/// Submit one checkpoint per frame, otherwise queue may force one,
+/// see [`Queue::submit`](crate::traits::Queue::submit).
+///
+/// ```ignore
+/// let uniforms = mev::PerFrame::try_new(|_| {
+///     device.new_buffer(mev::BufferDesc {
+///         size: size_of::<Camera>() as u64,
+///         usage: mev::BufferUsage::UNIFORM,
+///         memory: mev::Memory::Shared,
+///         name: "camera",
+///         shared_across_queues: false,
+///     })
+/// })?;
+///
+/// // Every frame.
+/// uniforms
+///     .get_mut(&queue)
+///     .mapped_mut(..)?
+///     .copy_from_slice(bytemuck::bytes_of(&camera));
+/// let buffer = uniforms.get(&queue);
+/// render.with_arguments(0, &CameraArguments { camera: buffer.clone() });
+/// // ...
+/// queue.submit_one(cbuf, true)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct PerFrame<T> {
+    copies: [T; FRAMES_IN_FLIGHT],
+}
+
+impl<T> PerFrame<T> {
+    /// Creates copies with the function called for each frame index.
+    pub fn new(f: impl FnMut(usize) -> T) -> Self {
+        PerFrame {
+            copies: std::array::from_fn(f),
+        }
+    }
+
+    /// Creates copies with the fallible function called for each frame index.
+    pub fn try_new<E>(f: impl FnMut(usize) -> Result<T, E>) -> Result<Self, E> {
+        let copies = array_try_map(std::array::from_fn(|idx| idx), f)?;
+        Ok(PerFrame { copies })
+    }
+
+    /// Returns index of the copy for the current frame of the queue.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn frame_index(queue: &Queue) -> usize {
+        (queue.check_point_index() % FRAMES_IN_FLIGHT as u64) as usize
+    }
+
+    /// Returns copy that is safe to write in the current frame of the queue.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn get(&self, queue: &Queue) -> &T {
+        &self.copies[Self::frame_index(queue)]
+    }
+
+    /// Returns copy that is safe to write in the current frame of the queue.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn get_mut(&mut self, queue: &Queue) -> &mut T {
+        &mut self.copies[Self::frame_index(queue)]
+    }
+
+    /// Returns all copies.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn all(&self) -> &[T; FRAMES_IN_FLIGHT] {
+        &self.copies
+    }
+}
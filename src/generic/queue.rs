@@ -16,3 +16,145 @@ bitflags::bitflags! {
         const TRANSFER = 0x4;
     }
 }
+
+/// Statistics of a queue.
+///
+/// Descriptor pools are used only by backends and devices
+/// that can't push descriptors directly into command buffers.
+/// Otherwise descriptor counters are zero.
+///
+/// Fields not tracked by the backend are zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct QueueStats {
+    /// Number of descriptor pools created since queue creation.
+    pub descriptor_pools_created: u64,
+
+    /// Number of descriptor sets allocated since queue creation.
+    pub descriptor_sets_allocated: u64,
+
+    /// Number of descriptor sets allocated by command buffers
+    /// submitted between the last two checkpoints.
+    ///
+    /// With one checkpoint per frame this is the number of sets allocated per frame.
+    pub descriptor_sets_last_check_point: u64,
+
+    /// Number of checkpoints inserted by the queue
+    /// because too many submissions were made without one.
+    ///
+    /// See [`Queue::submit`](crate::Queue::submit).
+    pub forced_check_points: u64,
+
+    /// Number of command pools owned by the queue.
+    pub command_pools: u64,
+
+    /// Number of command buffers allocated from the driver since queue creation.
+    /// Recycled command buffers are not counted.
+    pub command_buffers_allocated: u64,
+
+    /// Number of free command buffers released by pool trimming since queue creation.
+    ///
+    /// See [`QueuePoolConfig::trim_threshold`].
+    pub command_buffers_trimmed: u64,
+
+    /// Number of command pool resets since queue creation.
+    pub command_pool_resets: u64,
+}
+
+/// Policy of command pools used by a queue to allocate command buffers.
+///
+/// Default configuration suits a renderer that records a few command buffers per frame
+/// and never trims pools.
+/// Workloads that record many command buffers between checkpoints
+/// should raise `max_pools` so command buffers are spread over more pools
+/// and each reset recycles fewer of them.
+///
+/// Backends without command pools ignore this configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct QueuePoolConfig {
+    /// Maximum number of command pools kept by the queue.
+    ///
+    /// When all pools have command buffers in use and the limit is reached,
+    /// command buffers are allocated from the latest pool.
+    ///
+    /// Must be at least 1.
+    pub max_pools: usize,
+
+    /// Number of command buffers allocated at once when a new pool is created.
+    pub preallocate_cbufs: usize,
+
+    /// Maximum number of free command buffers kept by a pool when it is reset.
+    /// Free command buffers above this number are released back to the driver.
+    pub trim_threshold: usize,
+}
+
+impl Default for QueuePoolConfig {
+    fn default() -> Self {
+        QueuePoolConfig {
+            max_pools: 3,
+            preallocate_cbufs: 0,
+            trim_threshold: usize::MAX,
+        }
+    }
+}
+
+/// Identifier of a submission to a queue.
+///
+/// Each submission gets an identifier greater than all previous submissions to the same queue.
+/// Useful to tag GPU work for external tracking, e.g. to report
+/// the last completed submission when device is lost.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubmissionId(u64);
+
+impl SubmissionId {
+    /// Precedes all submissions.
+    ///
+    /// Returned as last completed submission before any submission completes.
+    pub const NONE: Self = SubmissionId(0);
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(crate) const fn new(value: u64) -> Self {
+        SubmissionId(value)
+    }
+
+    /// Returns numeric value of the identifier.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for SubmissionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Error that can happen when creating reusable command encoder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReusableCommandsError {
+    /// Device's memory is exhausted.
+    OutOfMemory,
+
+    /// Backend does not support reusable command buffers.
+    Unsupported,
+}
+
+impl From<OutOfMemory> for ReusableCommandsError {
+    #[inline(always)]
+    fn from(_: OutOfMemory) -> Self {
+        ReusableCommandsError::OutOfMemory
+    }
+}
+
+impl fmt::Display for ReusableCommandsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReusableCommandsError::OutOfMemory => write!(f, "out of memory"),
+            ReusableCommandsError::Unsupported => {
+                write!(f, "reusable command buffers are not supported")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReusableCommandsError {}
@@ -0,0 +1,102 @@
+bitflags::bitflags! {
+    /// Set of driver workarounds applied by the device.
+    ///
+    /// Quirks are detected at device creation from vendor id, device id and driver version
+    /// of the physical device.
+    /// Additional quirks may be forced with [`DeviceDesc::quirks`](crate::DeviceDesc::quirks).
+    ///
+    /// See [`Device::quirks`](crate::Device::quirks).
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Quirks: u32 {
+        /// Descriptor update templates are not used to push descriptors.
+        /// Descriptor writes are pushed directly instead.
+        const AVOID_PUSH_DESCRIPTOR_TEMPLATES = 0x0000_0001;
+
+        /// `VK_EXT_swapchain_maintenance1` is not enabled even if advertised by the device.
+        const DISABLE_SWAPCHAIN_MAINTENANCE1 = 0x0000_0002;
+
+        /// Sampler anisotropy is clamped to `8`.
+        const CLAMP_ANISOTROPY_TO_8 = 0x0000_0004;
+
+        /// `VK_KHR_push_descriptor` is not enabled even if advertised by the device.
+        /// Arguments are bound with descriptor sets allocated from pools instead.
+        const DISABLE_PUSH_DESCRIPTORS = 0x0000_0008;
+    }
+}
+
+/// PCI vendor id of Intel devices.
+const VENDOR_INTEL: u32 = 0x8086;
+
+/// Entry in the quirks database.
+struct QuirkEntry {
+    /// PCI vendor id.
+    vendor_id: u32,
+
+    /// PCI device id.
+    /// Entry matches all devices of the vendor if `None`.
+    device_id: Option<u32>,
+
+    /// Entry matches driver versions below this one.
+    /// Entry matches all driver versions if `None`.
+    ///
+    /// Encoding of driver version is vendor specific.
+    driver_below: Option<u32>,
+
+    quirks: Quirks,
+}
+
+const QUIRKS_DATABASE: &[QuirkEntry] = &[
+    // Intel Windows drivers older than 100.9466 misbehave
+    // when pushing descriptors with update templates.
+    // Windows drivers encode version as `major << 14 | minor`,
+    // Mesa versions are encoded with `VK_MAKE_VERSION` and are never matched.
+    QuirkEntry {
+        vendor_id: VENDOR_INTEL,
+        device_id: None,
+        driver_below: Some((100 << 14) | 9466),
+        quirks: Quirks::AVOID_PUSH_DESCRIPTOR_TEMPLATES,
+    },
+];
+
+impl Quirks {
+    /// Returns quirks known for the device with given vendor id, device id and driver version.
+    pub fn detect(vendor_id: u32, device_id: u32, driver_version: u32) -> Self {
+        let mut quirks = Quirks::empty();
+        for entry in QUIRKS_DATABASE {
+            if entry.vendor_id != vendor_id {
+                continue;
+            }
+            if entry.device_id.is_some_and(|id| id != device_id) {
+                continue;
+            }
+            if entry.driver_below.is_some_and(|v| driver_version >= v) {
+                continue;
+            }
+            quirks |= entry.quirks;
+        }
+        quirks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_intel_driver() {
+        let quirks = Quirks::detect(VENDOR_INTEL, 0x9a49, (100 << 14) | 9000);
+        assert_eq!(quirks, Quirks::AVOID_PUSH_DESCRIPTOR_TEMPLATES);
+    }
+
+    #[test]
+    fn new_intel_driver() {
+        let quirks = Quirks::detect(VENDOR_INTEL, 0x9a49, (101 << 14) | 4000);
+        assert_eq!(quirks, Quirks::empty());
+    }
+
+    #[test]
+    fn other_vendor() {
+        let quirks = Quirks::detect(0x10de, 0x2684, 0);
+        assert_eq!(quirks, Quirks::empty());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::backend::Image;
+use std::fmt;
+
+use crate::{backend::Image, ImageUsage, PixelFormat};
 
 /// Load operation for an attachment.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -58,6 +60,10 @@ impl ClearColor {
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub struct ClearDepthStencil {
     /// Clear value for depth component.
+    ///
+    /// Must be in `0..=1` range and is used as is.
+    /// Usually `1.0` is used, and `0.0` with reverse-Z,
+    /// see [`RenderCommandEncoder::with_depth_range`](crate::RenderCommandEncoder::with_depth_range).
     pub depth: f32,
 
     /// Clear value for stencil component.
@@ -65,6 +71,21 @@ pub struct ClearDepthStencil {
 }
 
 /// Description of an attachment in a render pass.
+///
+/// Attachment refers to a view of the image.
+/// Passing the same image or views with equal descriptors
+/// in different passes reuses the same native view object,
+/// see [`Image::view`](crate::traits::Image::view).
+///
+/// Clear value type selects the kind of attachment,
+/// color clear can't be used for depth-stencil attachment.
+///
+/// ```compile_fail
+/// fn pass(image: &mev::Image) -> mev::RenderPassDesc<'_> {
+///     let depth = mev::AttachmentDesc::clear_color(image, mev::ClearColor::BLACK);
+///     mev::RenderPassDesc::new().depth_stencil_attachment(depth)
+/// }
+/// ```
 #[derive(Clone, Copy)]
 pub struct AttachmentDesc<'a, T> {
     /// Image to use as attachment.
@@ -77,6 +98,31 @@ pub struct AttachmentDesc<'a, T> {
     pub store: StoreOp,
 }
 
+/// Image is described by its identifier, format and extent.
+impl<T: fmt::Debug> fmt::Debug for AttachmentDesc<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttachmentDesc")
+            .field(
+                "image",
+                &format_args!(
+                    "Image {{ id: {}, format: {:?}, extent: {:?} }}",
+                    self.image.id(),
+                    self.image.format(),
+                    self.image.extent()
+                ),
+            )
+            .field("load", &self.load)
+            .field("store", &self.store)
+            .finish()
+    }
+}
+
+/// Description of a color attachment in a render pass.
+pub type ColorAttachmentDesc<'a> = AttachmentDesc<'a, ClearColor>;
+
+/// Description of a depth-stencil attachment in a render pass.
+pub type DepthAttachmentDesc<'a> = AttachmentDesc<'a, ClearDepthStencil>;
+
 impl<'a, T> AttachmentDesc<'a, T> {
     /// Create a new attachment description from image reference.
     pub fn new(image: &'a Image) -> Self {
@@ -87,6 +133,16 @@ impl<'a, T> AttachmentDesc<'a, T> {
         }
     }
 
+    /// Create attachment description that loads and stores the image.
+    pub fn load(image: &'a Image) -> Self {
+        AttachmentDesc::new(image)
+    }
+
+    /// Create attachment description that doesn't load the image.
+    pub fn dont_care(image: &'a Image) -> Self {
+        AttachmentDesc::new(image).no_load()
+    }
+
     /// Set load operation to do not load.
     pub fn no_load(mut self) -> Self {
         self.load = LoadOp::DontCare;
@@ -118,14 +174,62 @@ impl<'a, T> AttachmentDesc<'a, T> {
     }
 }
 
+impl<'a> AttachmentDesc<'a, ClearColor> {
+    /// Create color attachment description that clears the image with specified color.
+    pub fn clear_color(image: &'a Image, color: ClearColor) -> Self {
+        AttachmentDesc::new(image).clear(color)
+    }
+}
+
+impl<'a> AttachmentDesc<'a, ClearDepthStencil> {
+    /// Create depth-stencil attachment description that clears depth with specified value
+    /// and stencil with zero.
+    pub fn clear_depth(image: &'a Image, depth: f32) -> Self {
+        AttachmentDesc::new(image).clear(ClearDepthStencil { depth, stencil: 0 })
+    }
+}
+
 impl<'a, T> From<&'a Image> for AttachmentDesc<'a, T> {
     fn from(image: &'a Image) -> Self {
         AttachmentDesc::new(image)
     }
 }
 
+bitflags::bitflags! {
+    /// Flags to split one render pass across multiple command buffers.
+    ///
+    /// Command buffers can be recorded in parallel, each with a part of the draws.
+    /// First part suspends the pass, middle parts resume and suspend it
+    /// and last part only resumes it.
+    /// All parts must use the same attachments.
+    ///
+    /// Command buffers with the parts must be submitted adjacently and in order
+    /// in one call to [`Queue::submit`](crate::Queue::submit).
+    /// Queue panics if suspended pass is not resumed by the next command buffer.
+    ///
+    /// On Metal the pass is encoded with parallel render command encoder
+    /// in the command buffer of the first part and other parts record into it.
+    /// Parts are executed in the order their passes begin,
+    /// so begin them in submission order, e.g. before spreading encoders across threads.
+    /// The first command buffer can't be submitted before all parts are finished.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct RenderPassFlags: u32 {
+        /// Render pass is continued in the next command buffer.
+        ///
+        /// Nothing may be recorded into the command buffer after the pass.
+        /// Store operations apply only to the last part.
+        const SUSPENDING = 0x1;
+
+        /// Render pass continues the pass suspended in the previous command buffer.
+        ///
+        /// Nothing may be recorded into the command buffer before the pass.
+        /// Load operations apply only to the first part.
+        const RESUMING = 0x2;
+    }
+}
+
 /// Description of a render pass.
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct RenderPassDesc<'a> {
     /// Name of the render pass.
     pub name: &'a str,
@@ -135,6 +239,15 @@ pub struct RenderPassDesc<'a> {
 
     /// Depth-stencil attachment of the render pass.
     pub depth_stencil_attachment: Option<AttachmentDesc<'a, ClearDepthStencil>>,
+
+    /// Flags to split the render pass across command buffers.
+    pub flags: RenderPassFlags,
+
+    /// Expected number of draw calls in the render pass.
+    ///
+    /// Hint to reserve space for references to resources used by the pass.
+    /// Zero if unknown.
+    pub expected_draws: u32,
 }
 
 impl<'a> RenderPassDesc<'a> {
@@ -144,6 +257,8 @@ impl<'a> RenderPassDesc<'a> {
             name: "",
             color_attachments: &[],
             depth_stencil_attachment: None,
+            flags: RenderPassFlags::empty(),
+            expected_draws: 0,
         }
     }
 
@@ -167,4 +282,304 @@ impl<'a> RenderPassDesc<'a> {
         self.depth_stencil_attachment = Some(attachment);
         self
     }
+
+    /// Set flags of the render pass.
+    pub fn flags(mut self, flags: RenderPassFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set expected number of draw calls in the render pass.
+    pub fn expected_draws(mut self, draws: u32) -> Self {
+        self.expected_draws = draws;
+        self
+    }
+
+    /// Checks that attachments can be used as described.
+    ///
+    /// Encoders panic on render passes that fail this check.
+    pub fn validate(&self) -> Result<(), RenderPassError> {
+        for (idx, color) in self.color_attachments.iter().enumerate() {
+            let format = crate::traits::Image::format(color.image);
+            if !format.is_color() {
+                return Err(RenderPassError::FormatMismatch {
+                    attachment: AttachmentIndex::Color(idx),
+                    format,
+                });
+            }
+        }
+
+        if let Some(depth) = &self.depth_stencil_attachment {
+            let format = crate::traits::Image::format(depth.image);
+            if !format.is_depth() && !format.is_stencil() {
+                return Err(RenderPassError::FormatMismatch {
+                    attachment: AttachmentIndex::DepthStencil,
+                    format,
+                });
+            }
+        }
+
+        let colors = self
+            .color_attachments
+            .iter()
+            .enumerate()
+            .map(|(idx, color)| (Some(idx), color.image, color.load.is_load(), color.store));
+        let depth = self
+            .depth_stencil_attachment
+            .iter()
+            .map(|depth| (None, depth.image, depth.load.is_load(), depth.store));
+
+        for (color, image, load, store) in colors.chain(depth) {
+            if !crate::traits::Image::usage(image).contains(ImageUsage::TRANSIENT) {
+                continue;
+            }
+
+            let attachment = match color {
+                Some(idx) => AttachmentIndex::Color(idx),
+                None => AttachmentIndex::DepthStencil,
+            };
+
+            if load {
+                return Err(RenderPassError::TransientLoad { attachment });
+            }
+            if store == StoreOp::Store {
+                return Err(RenderPassError::TransientStore { attachment });
+            }
+            if !self.flags.is_empty() {
+                return Err(RenderPassError::TransientSplit { attachment });
+            }
+        }
+
+        let mut expected = None;
+        for (attachment, image) in self.attachment_images() {
+            let layers = crate::traits::Image::layers(image);
+            match expected {
+                None => expected = Some((attachment, layers)),
+                Some((first, expected)) if expected != layers => {
+                    return Err(RenderPassError::LayerCountMismatch {
+                        attachment,
+                        layers,
+                        first,
+                        expected,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns number of layers rendered by the pass.
+    ///
+    /// All attachments have the same number of layers in valid render pass.
+    pub(crate) fn layers(&self) -> u32 {
+        self.attachment_images()
+            .next()
+            .map_or(1, |(_, image)| crate::traits::Image::layers(image))
+    }
+
+    fn attachment_images(&self) -> impl Iterator<Item = (AttachmentIndex, &'a Image)> + '_ {
+        let colors = self
+            .color_attachments
+            .iter()
+            .enumerate()
+            .map(|(idx, color)| (AttachmentIndex::Color(idx), color.image));
+        let depth = self
+            .depth_stencil_attachment
+            .iter()
+            .map(|depth| (AttachmentIndex::DepthStencil, depth.image));
+        colors.chain(depth)
+    }
+}
+
+/// Panics if render pipeline's target formats don't match attachments of the render pass.
+///
+/// Color formats that differ only in sRGB encoding are accepted,
+/// backends warn about them separately.
+#[cfg(any(debug_assertions, feature = "debug"))]
+pub(crate) fn check_pipeline_formats(
+    color_targets: &[PixelFormat],
+    depth_target: Option<PixelFormat>,
+    color_attachments: &[PixelFormat],
+    depth_attachment: Option<PixelFormat>,
+) {
+    assert_eq!(
+        color_targets.len(),
+        color_attachments.len(),
+        "Render pipeline with color targets {color_targets:?} is used in render pass with color attachments {color_attachments:?}"
+    );
+
+    for (idx, (&target, &attachment)) in color_targets.iter().zip(color_attachments).enumerate() {
+        assert!(
+            target == attachment || target.is_srgb_pair(attachment),
+            "Render pipeline with color target {idx} of format {target:?} is used in render pass with color attachment {idx} of format {attachment:?}"
+        );
+    }
+
+    assert_eq!(
+        depth_target,
+        depth_attachment,
+        "Render pipeline with depth-stencil target {depth_target:?} is used in render pass with depth-stencil attachment {depth_attachment:?}"
+    );
+}
+
+impl<T> LoadOp<T> {
+    fn is_load(&self) -> bool {
+        matches!(self, LoadOp::Load)
+    }
+}
+
+/// Attachment of a render pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttachmentIndex {
+    /// Color attachment at the index.
+    Color(usize),
+
+    /// Depth-stencil attachment.
+    DepthStencil,
+}
+
+impl fmt::Display for AttachmentIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttachmentIndex::Color(idx) => write!(f, "color attachment {idx}"),
+            AttachmentIndex::DepthStencil => write!(f, "depth-stencil attachment"),
+        }
+    }
+}
+
+/// Error in render pass description.
+///
+/// Content of [`ImageUsage::TRANSIENT`] images exists only within a render pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RenderPassError {
+    /// Image format doesn't match the kind of attachment.
+    FormatMismatch {
+        attachment: AttachmentIndex,
+        format: PixelFormat,
+    },
+
+    /// Transient attachment is loaded.
+    TransientLoad { attachment: AttachmentIndex },
+
+    /// Transient attachment is stored.
+    TransientStore { attachment: AttachmentIndex },
+
+    /// Transient attachment is used in a render pass split across command buffers.
+    ///
+    /// Content of transient attachment may not survive between parts of the pass.
+    TransientSplit { attachment: AttachmentIndex },
+
+    /// Attachments have different number of layers.
+    ///
+    /// Layered rendering requires all attachments to have the same number of layers.
+    LayerCountMismatch {
+        attachment: AttachmentIndex,
+        layers: u32,
+        first: AttachmentIndex,
+        expected: u32,
+    },
+}
+
+impl fmt::Display for RenderPassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderPassError::FormatMismatch { attachment, format } => {
+                write!(f, "{attachment} can't have format {format:?}")
+            }
+            RenderPassError::TransientLoad { attachment } => {
+                write!(f, "transient {attachment} can't be loaded")
+            }
+            RenderPassError::TransientStore { attachment } => {
+                write!(f, "transient {attachment} can't be stored")
+            }
+            RenderPassError::TransientSplit { attachment } => {
+                write!(
+                    f,
+                    "transient {attachment} can't be used in a split render pass"
+                )
+            }
+            RenderPassError::LayerCountMismatch {
+                attachment,
+                layers,
+                first,
+                expected,
+            } => write!(
+                f,
+                "{attachment} has {layers} layers while {first} has {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderPassError {}
+
+#[cfg(all(test, any(debug_assertions, feature = "debug")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_formats() {
+        check_pipeline_formats(
+            &[PixelFormat::Rgba8Unorm, PixelFormat::R32Float],
+            Some(PixelFormat::D32Float),
+            &[PixelFormat::Rgba8Unorm, PixelFormat::R32Float],
+            Some(PixelFormat::D32Float),
+        );
+    }
+
+    #[test]
+    fn srgb_pair_accepted() {
+        check_pipeline_formats(
+            &[PixelFormat::Rgba8Srgb],
+            None,
+            &[PixelFormat::Rgba8Unorm],
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "color target 1 of format Rgba16Float")]
+    fn color_format_mismatch() {
+        check_pipeline_formats(
+            &[PixelFormat::Rgba8Unorm, PixelFormat::Rgba16Float],
+            None,
+            &[PixelFormat::Rgba8Unorm, PixelFormat::Rgba8Unorm],
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "color targets")]
+    fn color_count_mismatch() {
+        check_pipeline_formats(
+            &[PixelFormat::Rgba8Unorm],
+            None,
+            &[PixelFormat::Rgba8Unorm, PixelFormat::Rgba8Unorm],
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "depth-stencil target")]
+    fn depth_format_mismatch() {
+        check_pipeline_formats(
+            &[],
+            Some(PixelFormat::D16Unorm),
+            &[],
+            Some(PixelFormat::D32Float),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "depth-stencil target None")]
+    fn missing_depth_target() {
+        check_pipeline_formats(
+            &[PixelFormat::Rgba8Unorm],
+            None,
+            &[PixelFormat::Rgba8Unorm],
+            Some(PixelFormat::D32Float),
+        );
+    }
 }
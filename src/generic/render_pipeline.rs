@@ -1,9 +1,31 @@
 use core::fmt;
-use std::error::Error;
+use std::{borrow::Cow, error::Error};
 
 use crate::backend::CreatePipelineErrorKind;
 
-use super::{arguments::ArgumentGroupLayout, PixelFormat, Shader, VertexFormat};
+use super::{
+    arguments::{ArgumentGroupLayout, ArgumentLayout},
+    Arguments, DeviceRepr, PixelFormat, Shader, ShaderLanguage, ShaderSource, VertexFormat,
+};
+
+/// Name of the vertex entry point in [`FULLSCREEN_VERTEX_SHADER`].
+pub const FULLSCREEN_VERTEX_ENTRY: &str = "fullscreen_vertex";
+
+/// WGSL source of the vertex shader that covers the whole viewport with a single triangle.
+///
+/// Shader reads no vertex buffers, pipeline needs no vertex layouts
+/// and the triangle is drawn with `draw(0..3, 0..1)`.
+/// It outputs texture coordinates at location 0 as `vec2<f32>`,
+/// `(0, 0)` is the top-left corner of the viewport and `(1, 1)` is the bottom-right.
+///
+/// Compose it with WGSL fragment shaders using [`LibraryInput::Sources`](crate::LibraryInput::Sources)
+/// and use [`RenderPipelineDesc::fullscreen`].
+/// Names declared by the shader start with `fullscreen_` and `Fullscreen`.
+pub const FULLSCREEN_VERTEX_SHADER: ShaderSource<'static> = ShaderSource {
+    code: Cow::Borrowed(include_bytes!("shaders/fullscreen.wgsl")),
+    filename: Some("fullscreen.wgsl"),
+    language: ShaderLanguage::Wgsl,
+};
 
 /// Describes single vertex attribute.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -26,9 +48,16 @@ pub enum VertexStepMode {
     Vertex,
 
     /// Advance every `rate` instances.
+    ///
+    /// Rates other than 1 require vertex attribute divisor support on Vulkan.
+    /// Pipeline creation fails if it is not available.
     Instance { rate: u32 },
 
     /// No advancement.
+    /// All vertices and instances read the first element.
+    ///
+    /// Requires zero divisor support on Vulkan.
+    /// Pipeline creation fails if it is not available.
     Constant,
 }
 
@@ -42,6 +71,9 @@ impl Default for VertexStepMode {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct VertexLayoutDesc {
     /// Stride in bytes between vertices in the vertex buffer.
+    ///
+    /// Must not exceed device limit, pipeline creation fails otherwise.
+    /// On Vulkan the limit is `maxVertexInputBindingStride`, on Metal it is 2048.
     pub stride: u32,
 
     /// Specifies when to advance to the next vertex (by `stride` bytes).
@@ -52,9 +84,18 @@ pub struct VertexLayoutDesc {
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum PrimitiveTopology {
     /// Vertex buffer contains list of points that will be rasterized.
+    ///
+    /// Point size is taken from `PointSize` built-in written by the vertex shader,
+    /// e.g. `gl_PointSize` in GLSL.
+    /// If vertex shader does not write it, points are 1 pixel in size.
+    /// WGSL has no such built-in, so points from WGSL shaders are always 1 pixel.
+    /// On Vulkan sizes other than `1.0` require `largePoints` device feature,
+    /// which is enabled when the device supports it.
     Point,
 
     /// Vertex buffer contains list of lines that will be rasterized.
+    ///
+    /// Line width is controlled by [`RasterDesc::line_width`].
     Line,
 
     /// Vertex buffer contains list of triangles that will be rasterized.
@@ -107,7 +148,7 @@ impl Default for BlendDesc {
 }
 
 /// Describes blending option.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Blend {
     /// Blending operation.
     pub op: BlendOp,
@@ -121,6 +162,20 @@ pub struct Blend {
     pub dst: BlendFactor,
 }
 
+/// Spells out blending equation.
+impl fmt::Debug for Blend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Blend { op, src, dst } = *self;
+        match op {
+            BlendOp::Add => write!(f, "src * {src:?} + dst * {dst:?}"),
+            BlendOp::Subtract => write!(f, "src * {src:?} - dst * {dst:?}"),
+            BlendOp::ReverseSubtract => write!(f, "dst * {dst:?} - src * {src:?}"),
+            BlendOp::Min => f.write_str("min(src, dst)"),
+            BlendOp::Max => f.write_str("max(src, dst)"),
+        }
+    }
+}
+
 bitflags::bitflags! {
     /// Mask for color blend write.
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -167,6 +222,28 @@ pub enum BlendFactor {
 
     /// Multiply by minimum of source and 1 - destination alpha.
     SrcAlphaSaturated,
+
+    /// Multiply by second source color.
+    ///
+    /// Requires [`Features::DUAL_SOURCE_BLEND`](crate::Features::DUAL_SOURCE_BLEND)
+    /// and fragment shader that writes second blend source at location 0.
+    /// Can be used only for the first color target.
+    Src1Color,
+
+    /// Multiply by 1 - second source color.
+    ///
+    /// See [`BlendFactor::Src1Color`] for requirements.
+    OneMinusSrc1Color,
+
+    /// Multiply by second source alpha.
+    ///
+    /// See [`BlendFactor::Src1Color`] for requirements.
+    Src1Alpha,
+
+    /// Multiply by 1 - second source alpha.
+    ///
+    /// See [`BlendFactor::Src1Color`] for requirements.
+    OneMinusSrc1Alpha,
     // /// Multiply by constant color.
     // BlendColor,
 
@@ -186,13 +263,67 @@ pub enum BlendOp {
     /// Subtract first value from the second after factor multiplication.
     ReverseSubtract,
 
-    /// Minimum of two values after factor multiplication.
+    /// Minimum of two values.
+    ///
+    /// Blend factors are ignored and should be [`BlendFactor::One`].
     Min,
 
-    /// Maximum of two values after factor multiplication.
+    /// Maximum of two values.
+    ///
+    /// Blend factors are ignored and should be [`BlendFactor::One`].
     Max,
 }
 
+impl BlendFactor {
+    /// Returns `true` if the factor uses second source of the fragment shader.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub const fn is_dual_source(&self) -> bool {
+        matches!(
+            self,
+            BlendFactor::Src1Color
+                | BlendFactor::OneMinusSrc1Color
+                | BlendFactor::Src1Alpha
+                | BlendFactor::OneMinusSrc1Alpha
+        )
+    }
+}
+
+impl Blend {
+    /// Returns `true` if the blending uses second source of the fragment shader.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub const fn is_dual_source(&self) -> bool {
+        self.src.is_dual_source() || self.dst.is_dual_source()
+    }
+
+    /// Returns `true` if blend factors are ignored by the operation
+    /// but are not [`BlendFactor::One`].
+    ///
+    /// Backends disagree on how such factors are treated,
+    /// so they are reported as a warning.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(crate) fn has_ignored_factors(&self) -> bool {
+        matches!(self.op, BlendOp::Min | BlendOp::Max)
+            && (!matches!(self.src, BlendFactor::One) || !matches!(self.dst, BlendFactor::One))
+    }
+}
+
+impl BlendDesc {
+    /// Returns `true` if the blending uses second source of the fragment shader.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub const fn is_dual_source(&self) -> bool {
+        self.color.is_dual_source() || self.alpha.is_dual_source()
+    }
+
+    /// Emits warning if blend factors are ignored by min/max operations.
+    pub(crate) fn warn_ignored_factors(&self, target: usize) {
+        if self.color.has_ignored_factors() || self.alpha.has_ignored_factors() {
+            tracing::warn!(
+                "Color target {target} uses min/max blend operation with factors other than `One`. Factors are ignored"
+            );
+        }
+    }
+}
+
 /// Describes depth-stencil render target.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct DepthStencilDesc {
@@ -262,6 +393,7 @@ pub enum Culling {
 }
 
 /// Describes render pipeline.
+#[derive(Debug)]
 pub struct RenderPipelineDesc<'a> {
     /// Name of the pipeline.
     pub name: &'a str,
@@ -273,6 +405,10 @@ pub struct RenderPipelineDesc<'a> {
     pub vertex_attributes: Vec<VertexAttributeDesc>,
 
     /// Vertex buffer layouts.
+    ///
+    /// May be empty together with attributes if vertex shader has no vertex inputs,
+    /// e.g. generates vertices from vertex index.
+    /// Such pipelines are drawn without binding vertex buffers.
     pub vertex_layouts: Vec<VertexLayoutDesc>,
 
     /// Primitive topology.
@@ -289,6 +425,7 @@ pub struct RenderPipelineDesc<'a> {
 }
 
 /// Describes rasterization options.
+#[derive(Debug)]
 pub struct RasterDesc<'a> {
     /// Fragment shader.
     pub fragment_shader: Option<Shader<'a>>,
@@ -304,6 +441,399 @@ pub struct RasterDesc<'a> {
 
     /// Face culling mode.
     pub culling: Culling,
+
+    /// Width of rasterized lines in pixels.
+    ///
+    /// Widths other than `1.0` require [`Features::WIDE_LINES`](crate::Features::WIDE_LINES)
+    /// and are clamped to the range supported by the device.
+    /// Without the feature `1.0` is used.
+    pub line_width: f32,
+}
+
+/// Invalid use of dual-source blending.
+#[derive(Debug)]
+pub(crate) enum DualSourceBlendError {
+    /// [`Features::DUAL_SOURCE_BLEND`](crate::Features::DUAL_SOURCE_BLEND) is not enabled.
+    NotEnabled,
+
+    /// Dual-source blending is used by color target other than first.
+    InvalidTarget { target: usize },
+
+    /// Fragment shader does not write second blend source.
+    MissingSecondSource,
+}
+
+impl fmt::Display for DualSourceBlendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DualSourceBlendError::NotEnabled => write!(
+                f,
+                "dual-source blending is used but `Features::DUAL_SOURCE_BLEND` is not enabled"
+            ),
+            DualSourceBlendError::InvalidTarget { target } => write!(
+                f,
+                "dual-source blending is used for color target {target}, only first target may use it"
+            ),
+            DualSourceBlendError::MissingSecondSource => write!(
+                f,
+                "dual-source blending is used but fragment shader does not write second blend source"
+            ),
+        }
+    }
+}
+
+/// Checks that color targets use dual-source blending only when it is enabled,
+/// only for the first target and only with fragment shader
+/// that writes second blend source.
+///
+/// `writes_second_source` returns `None` if fragment shader was not reflected.
+/// Such shaders are not checked.
+pub(crate) fn check_dual_source_blend(
+    raster: &RasterDesc,
+    dual_source_enabled: bool,
+    writes_second_source: impl FnOnce(&Shader) -> Option<bool>,
+) -> Result<(), DualSourceBlendError> {
+    let mut dual_source = false;
+
+    for (target, color) in raster.color_targets.iter().enumerate() {
+        if !color.blend.is_some_and(|blend| blend.is_dual_source()) {
+            continue;
+        }
+
+        if !dual_source_enabled {
+            return Err(DualSourceBlendError::NotEnabled);
+        }
+
+        if target != 0 {
+            return Err(DualSourceBlendError::InvalidTarget { target });
+        }
+
+        dual_source = true;
+    }
+
+    if !dual_source {
+        return Ok(());
+    }
+
+    let writes = raster
+        .fragment_shader
+        .as_ref()
+        .map_or(Some(false), writes_second_source);
+
+    if writes == Some(false) {
+        return Err(DualSourceBlendError::MissingSecondSource);
+    }
+
+    Ok(())
+}
+
+impl<'a> RenderPipelineDesc<'a> {
+    /// Returns builder of render pipeline descriptor with given vertex shader.
+    ///
+    /// Builder starts with triangle list topology, no vertex buffers,
+    /// no rasterization, no constants and no arguments.
+    /// Rasterization is enabled by setting fragment shader or any render target.
+    ///
+    /// ```ignore
+    /// let desc = mev::RenderPipelineDesc::builder(vertex_shader)
+    ///     .name("main")
+    ///     .fragment(fragment_shader)
+    ///     .color_target(mev::PixelFormat::Bgra8Srgb)
+    ///     .depth(mev::PixelFormat::D32Float, mev::CompareFunction::LessEqual)
+    ///     .arguments::<MyArguments>()
+    ///     .constants::<MyConstants>()
+    ///     .build();
+    /// ```
+    pub fn builder(vertex_shader: Shader<'a>) -> RenderPipelineBuilder<'a> {
+        RenderPipelineBuilder {
+            desc: RenderPipelineDesc {
+                name: "",
+                vertex_shader,
+                vertex_attributes: Vec::new(),
+                vertex_layouts: Vec::new(),
+                primitive_topology: PrimitiveTopology::Triangle,
+                raster: None,
+                constants: 0,
+                arguments: &[],
+            },
+        }
+    }
+
+    /// Returns builder of full-screen pass pipeline descriptor.
+    ///
+    /// Vertex shader is [`FULLSCREEN_VERTEX_ENTRY`] from the library of the fragment shader,
+    /// so the library must include [`FULLSCREEN_VERTEX_SHADER`].
+    /// Pipeline has no vertex buffers, no culling and single color target without blending.
+    /// Draw it with `draw(0..3, 0..1)`.
+    ///
+    /// ```ignore
+    /// let library = device.new_shader_library(mev::LibraryDesc {
+    ///     name: "post",
+    ///     input: mev::LibraryInput::Sources(vec![
+    ///         mev::FULLSCREEN_VERTEX_SHADER,
+    ///         mev::include_shader_source!("shaders/post.wgsl" as mev::ShaderLanguage::Wgsl),
+    ///     ]),
+    /// })?;
+    ///
+    /// let desc = mev::RenderPipelineDesc::fullscreen(
+    ///     mev::Shader { library, entry: "fs_main".into() },
+    ///     mev::PixelFormat::Bgra8Srgb,
+    /// )
+    /// .name("post")
+    /// .arguments::<PostArguments>()
+    /// .build();
+    /// ```
+    pub fn fullscreen(
+        fragment_shader: Shader<'a>,
+        color_format: PixelFormat,
+    ) -> RenderPipelineBuilder<'a> {
+        let vertex_shader = Shader {
+            library: fragment_shader.library.clone(),
+            entry: Cow::Borrowed(FULLSCREEN_VERTEX_ENTRY),
+        };
+
+        RenderPipelineDesc::builder(vertex_shader)
+            .fragment(fragment_shader)
+            .color_target(color_format)
+            .culling(Culling::None)
+    }
+
+    /// Converts descriptor into owned form that can be sent to other threads.
+    ///
+    /// Name, shader entry points and argument layouts are copied.
+    pub fn into_owned(self) -> RenderPipelineDescOwned {
+        RenderPipelineDescOwned {
+            name: self.name.to_owned(),
+            vertex_shader: self.vertex_shader.into_owned(),
+            vertex_attributes: self.vertex_attributes,
+            vertex_layouts: self.vertex_layouts,
+            primitive_topology: self.primitive_topology,
+            raster: self.raster.map(|raster| RasterDesc {
+                fragment_shader: raster.fragment_shader.map(Shader::into_owned),
+                color_targets: raster.color_targets,
+                depth_stencil: raster.depth_stencil,
+                front_face: raster.front_face,
+                culling: raster.culling,
+                line_width: raster.line_width,
+            }),
+            constants: self.constants,
+            arguments: self
+                .arguments
+                .iter()
+                .map(|group| group.arguments.to_vec())
+                .collect(),
+        }
+    }
+}
+
+/// Owned form of [`RenderPipelineDesc`].
+///
+/// Doesn't borrow anything, so it can be stored or moved to other threads.
+/// Created with [`RenderPipelineDesc::into_owned`].
+#[derive(Debug)]
+pub struct RenderPipelineDescOwned {
+    /// Name of the pipeline.
+    pub name: String,
+
+    /// Vertex shader.
+    pub vertex_shader: Shader<'static>,
+
+    /// Vertex attributes.
+    pub vertex_attributes: Vec<VertexAttributeDesc>,
+
+    /// Vertex buffer layouts.
+    pub vertex_layouts: Vec<VertexLayoutDesc>,
+
+    /// Primitive topology.
+    pub primitive_topology: PrimitiveTopology,
+
+    /// Rasterization options.
+    pub raster: Option<RasterDesc<'static>>,
+
+    /// Size of the shader constants in bytes.
+    pub constants: usize,
+
+    /// Layouts of argument groups used by shaders.
+    pub arguments: Vec<Vec<ArgumentLayout>>,
+}
+
+impl RenderPipelineDescOwned {
+    /// Calls `f` with descriptor borrowing from this owned descriptor.
+    pub fn with_desc<R>(&self, f: impl FnOnce(RenderPipelineDesc<'_>) -> R) -> R {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|arguments| ArgumentGroupLayout { arguments })
+            .collect::<Vec<_>>();
+
+        f(RenderPipelineDesc {
+            name: &self.name,
+            vertex_shader: self.vertex_shader.clone(),
+            vertex_attributes: self.vertex_attributes.clone(),
+            vertex_layouts: self.vertex_layouts.clone(),
+            primitive_topology: self.primitive_topology,
+            raster: self.raster.as_ref().map(|raster| RasterDesc {
+                fragment_shader: raster.fragment_shader.clone(),
+                color_targets: raster.color_targets.clone(),
+                depth_stencil: raster.depth_stencil,
+                front_face: raster.front_face,
+                culling: raster.culling,
+                line_width: raster.line_width,
+            }),
+            constants: self.constants,
+            arguments: &arguments,
+        })
+    }
+}
+
+impl<'a> RasterDesc<'a> {
+    /// Returns rasterization options without fragment shader and targets.
+    ///
+    /// Counter-clockwise faces are front faces and back faces are culled.
+    pub fn new() -> Self {
+        RasterDesc {
+            fragment_shader: None,
+            color_targets: Vec::new(),
+            depth_stencil: None,
+            front_face: FrontFace::CounterClockwise,
+            culling: Culling::Back,
+            line_width: 1.0,
+        }
+    }
+}
+
+impl Default for RasterDesc<'_> {
+    fn default() -> Self {
+        RasterDesc::new()
+    }
+}
+
+/// Builder for [`RenderPipelineDesc`].
+///
+/// See [`RenderPipelineDesc::builder`].
+pub struct RenderPipelineBuilder<'a> {
+    desc: RenderPipelineDesc<'a>,
+}
+
+impl<'a> RenderPipelineBuilder<'a> {
+    /// Sets name of the pipeline.
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.desc.name = name;
+        self
+    }
+
+    /// Sets fragment shader.
+    pub fn fragment(mut self, shader: Shader<'a>) -> Self {
+        self.raster().fragment_shader = Some(shader);
+        self
+    }
+
+    /// Adds color target without blending.
+    pub fn color_target(self, format: PixelFormat) -> Self {
+        self.color_target_desc(ColorTargetDesc {
+            format,
+            blend: None,
+        })
+    }
+
+    /// Adds color target with blending.
+    pub fn color_target_blend(self, format: PixelFormat, blend: BlendDesc) -> Self {
+        self.color_target_desc(ColorTargetDesc {
+            format,
+            blend: Some(blend),
+        })
+    }
+
+    /// Adds color target.
+    pub fn color_target_desc(mut self, desc: ColorTargetDesc) -> Self {
+        self.raster().color_targets.push(desc);
+        self
+    }
+
+    /// Sets depth target with depth test and depth write enabled.
+    pub fn depth(self, format: PixelFormat, compare: CompareFunction) -> Self {
+        self.depth_stencil(DepthStencilDesc {
+            format,
+            write_enabled: true,
+            compare,
+        })
+    }
+
+    /// Sets depth-stencil target.
+    pub fn depth_stencil(mut self, desc: DepthStencilDesc) -> Self {
+        self.raster().depth_stencil = Some(desc);
+        self
+    }
+
+    /// Sets front face winding order.
+    pub fn front_face(mut self, front_face: FrontFace) -> Self {
+        self.raster().front_face = front_face;
+        self
+    }
+
+    /// Sets face culling mode.
+    pub fn culling(mut self, culling: Culling) -> Self {
+        self.raster().culling = culling;
+        self
+    }
+
+    /// Sets width of rasterized lines.
+    pub fn line_width(mut self, line_width: f32) -> Self {
+        self.raster().line_width = line_width;
+        self
+    }
+
+    /// Sets primitive topology.
+    pub fn topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.desc.primitive_topology = topology;
+        self
+    }
+
+    /// Adds vertex buffer layout with attributes read from it.
+    ///
+    /// Buffer index of attributes is set to the index of the added layout.
+    pub fn vertex(
+        mut self,
+        layout: VertexLayoutDesc,
+        attributes: impl IntoIterator<Item = VertexAttributeDesc>,
+    ) -> Self {
+        let buffer_index = self.desc.vertex_layouts.len() as u32;
+        self.desc.vertex_layouts.push(layout);
+        self.desc
+            .vertex_attributes
+            .extend(attributes.into_iter().map(|attribute| VertexAttributeDesc {
+                buffer_index,
+                ..attribute
+            }));
+        self
+    }
+
+    /// Sets size of constants to the size of the given type.
+    pub fn constants<T: DeviceRepr>(mut self) -> Self {
+        self.desc.constants = T::SIZE;
+        self
+    }
+
+    /// Sets single argument group with layout of the given type.
+    pub fn arguments<T: Arguments>(mut self) -> Self {
+        self.desc.arguments = &const { [T::LAYOUT] };
+        self
+    }
+
+    /// Sets argument groups layouts.
+    pub fn argument_layouts(mut self, arguments: &'a [ArgumentGroupLayout<'a>]) -> Self {
+        self.desc.arguments = arguments;
+        self
+    }
+
+    /// Returns built descriptor.
+    pub fn build(self) -> RenderPipelineDesc<'a> {
+        self.desc
+    }
+
+    fn raster(&mut self) -> &mut RasterDesc<'a> {
+        self.desc.raster.get_or_insert_with(RasterDesc::new)
+    }
 }
 
 /// Error during render pipeline creation.
@@ -317,3 +847,120 @@ impl fmt::Display for CreatePipelineError {
 }
 
 impl Error for CreatePipelineError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raster(color_targets: Vec<ColorTargetDesc>) -> RasterDesc<'static> {
+        RasterDesc {
+            fragment_shader: None,
+            color_targets,
+            depth_stencil: None,
+            front_face: FrontFace::default(),
+            culling: Culling::default(),
+            line_width: 1.0,
+        }
+    }
+
+    fn target(blend: Option<BlendDesc>) -> ColorTargetDesc {
+        ColorTargetDesc {
+            format: PixelFormat::Rgba8Unorm,
+            blend,
+        }
+    }
+
+    fn subpixel() -> BlendDesc {
+        BlendDesc {
+            mask: WriteMask::all(),
+            color: Blend {
+                op: BlendOp::Add,
+                src: BlendFactor::One,
+                dst: BlendFactor::OneMinusSrc1Color,
+            },
+            alpha: Blend {
+                op: BlendOp::Add,
+                src: BlendFactor::One,
+                dst: BlendFactor::OneMinusSrcAlpha,
+            },
+        }
+    }
+
+    #[test]
+    fn dual_source_blend() {
+        let single = raster(vec![target(Some(BlendDesc::default())), target(None)]);
+        assert!(check_dual_source_blend(&single, false, |_| None).is_ok());
+
+        let dual = raster(vec![target(Some(subpixel()))]);
+        assert!(matches!(
+            check_dual_source_blend(&dual, false, |_| Some(true)),
+            Err(DualSourceBlendError::NotEnabled)
+        ));
+
+        // Without fragment shader there is no second source.
+        assert!(matches!(
+            check_dual_source_blend(&dual, true, |_| Some(true)),
+            Err(DualSourceBlendError::MissingSecondSource)
+        ));
+
+        let second = raster(vec![target(None), target(Some(subpixel()))]);
+        assert!(matches!(
+            check_dual_source_blend(&second, true, |_| Some(true)),
+            Err(DualSourceBlendError::InvalidTarget { target: 1 })
+        ));
+    }
+
+    #[test]
+    fn blend_equations() {
+        let blend = |op| Blend {
+            op,
+            src: BlendFactor::SrcAlpha,
+            dst: BlendFactor::OneMinusSrcAlpha,
+        };
+
+        assert_eq!(
+            format!("{:?}", blend(BlendOp::Add)),
+            "src * SrcAlpha + dst * OneMinusSrcAlpha"
+        );
+        assert_eq!(
+            format!("{:?}", blend(BlendOp::Subtract)),
+            "src * SrcAlpha - dst * OneMinusSrcAlpha"
+        );
+        assert_eq!(
+            format!("{:?}", blend(BlendOp::ReverseSubtract)),
+            "dst * OneMinusSrcAlpha - src * SrcAlpha"
+        );
+        assert_eq!(format!("{:?}", blend(BlendOp::Min)), "min(src, dst)");
+        assert_eq!(format!("{:?}", blend(BlendOp::Max)), "max(src, dst)");
+    }
+
+    #[test]
+    fn raster_desc() {
+        let raster = raster(vec![target(Some(subpixel()))]);
+
+        assert_eq!(
+            format!("{raster:#?}"),
+            r#"RasterDesc {
+    fragment_shader: None,
+    color_targets: [
+        ColorTargetDesc {
+            format: Rgba8Unorm,
+            blend: Some(
+                BlendDesc {
+                    mask: WriteMask(
+                        RED | GREEN | BLUE | ALPHA,
+                    ),
+                    color: src * One + dst * OneMinusSrc1Color,
+                    alpha: src * One + dst * OneMinusSrcAlpha,
+                },
+            ),
+        },
+    ],
+    depth_stencil: None,
+    front_face: Clockwise,
+    culling: Back,
+    line_width: 1.0,
+}"#
+        );
+    }
+}
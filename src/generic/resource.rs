@@ -0,0 +1,87 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Identifier of a resource.
+///
+/// Buffers, images, samplers and pipelines get identifiers when created.
+/// Identifiers are unique among resources created by the same device
+/// and stay the same for the lifetime of the resource, including all its clones.
+/// Each image view gets its own identifier,
+/// on Vulkan views with equal descriptions are cached and share it,
+/// while [`Image::image_id`](crate::traits::Image::image_id) refers to the image it views.
+///
+/// Unlike handles, identifiers are assigned in creation order,
+/// so they can be used as stable keys in ordered collections.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResourceId(u64);
+
+impl ResourceId {
+    /// Returns numeric value of the identifier.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", self.0)
+    }
+}
+
+/// Source of resource identifiers for a device.
+#[derive(Debug, Default)]
+pub(crate) struct ResourceIds {
+    next: AtomicU64,
+}
+
+impl ResourceIds {
+    pub fn new() -> Self {
+        ResourceIds::default()
+    }
+
+    /// Returns identifier not returned before.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn next(&self) -> ResourceId {
+        ResourceId(self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn unique_across_threads() {
+        const THREADS: usize = 4;
+        const PER_THREAD: usize = 25_000;
+
+        let ids = ResourceIds::new();
+        let all = std::thread::scope(|scope| {
+            let workers = (0..THREADS)
+                .map(|_| scope.spawn(|| (0..PER_THREAD).map(|_| ids.next()).collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect::<HashSet<_>>()
+        });
+
+        assert_eq!(all.len(), THREADS * PER_THREAD);
+        assert_eq!(ids.next().get(), (THREADS * PER_THREAD) as u64);
+    }
+
+    #[test]
+    fn ordered_by_creation() {
+        let ids = ResourceIds::new();
+        let first = ids.next();
+        let second = ids.next();
+        assert!(first < second);
+        assert_eq!(second.to_string(), "@1");
+    }
+}
@@ -52,6 +52,10 @@ pub struct SamplerDesc {
     pub address_mode: [AddressMode; 3],
 
     /// Maximum anisotropy level to use when sampling the texture.
+    ///
+    /// Value is clamped to the range supported by the device, `[1, 16]` at most.
+    /// If [`Features::SAMPLER_ANISOTROPY`](crate::Features::SAMPLER_ANISOTROPY)
+    /// was not enabled, anisotropic filtering is disabled with a warning.
     pub anisotropy: Option<f32>,
 
     /// Minimum level of detail to use when sampling the texture.
@@ -120,3 +124,203 @@ impl Default for SamplerDesc {
         SamplerDesc::new()
     }
 }
+
+impl SamplerDesc {
+    /// Returns builder of sampler descriptor.
+    ///
+    /// Builder starts with [`SamplerDesc::new`].
+    ///
+    /// ```
+    /// let desc = mev::SamplerDesc::builder()
+    ///     .filter(mev::Filter::Linear)
+    ///     .mip_map_mode(mev::MipMapMode::Linear)
+    ///     .address_mode(mev::AddressMode::ClampToEdge)
+    ///     .build();
+    /// ```
+    pub const fn builder() -> SamplerBuilder {
+        SamplerBuilder {
+            desc: SamplerDesc::new(),
+        }
+    }
+}
+
+/// Builder for [`SamplerDesc`].
+///
+/// See [`SamplerDesc::builder`].
+#[derive(Clone, Copy, Debug)]
+pub struct SamplerBuilder {
+    desc: SamplerDesc,
+}
+
+impl SamplerBuilder {
+    /// Sets both minification and magnification filters.
+    pub const fn filter(self, filter: Filter) -> Self {
+        self.min_filter(filter).mag_filter(filter)
+    }
+
+    /// Sets minification filter.
+    pub const fn min_filter(mut self, filter: Filter) -> Self {
+        self.desc.min_filter = filter;
+        self
+    }
+
+    /// Sets magnification filter.
+    pub const fn mag_filter(mut self, filter: Filter) -> Self {
+        self.desc.mag_filter = filter;
+        self
+    }
+
+    /// Sets mip-map mode.
+    pub const fn mip_map_mode(mut self, mip_map_mode: MipMapMode) -> Self {
+        self.desc.mip_map_mode = mip_map_mode;
+        self
+    }
+
+    /// Sets address mode for all dimensions.
+    pub const fn address_mode(mut self, address_mode: AddressMode) -> Self {
+        self.desc.address_mode = [address_mode; 3];
+        self
+    }
+
+    /// Sets address mode for each dimension.
+    pub const fn address_modes(mut self, address_mode: [AddressMode; 3]) -> Self {
+        self.desc.address_mode = address_mode;
+        self
+    }
+
+    /// Sets maximum anisotropy level.
+    pub const fn anisotropy(mut self, anisotropy: f32) -> Self {
+        self.desc.anisotropy = Some(anisotropy);
+        self
+    }
+
+    /// Sets range of levels of detail.
+    pub const fn lod(mut self, min_lod: f32, max_lod: f32) -> Self {
+        self.desc.min_lod = min_lod;
+        self.desc.max_lod = max_lod;
+        self
+    }
+
+    /// Sets whether texture coordinates are normalized.
+    pub const fn normalized(mut self, normalized: bool) -> Self {
+        self.desc.normalized = normalized;
+        self
+    }
+
+    /// Returns built descriptor.
+    pub const fn build(self) -> SamplerDesc {
+        self.desc
+    }
+}
+
+/// Samplers owned by the device.
+///
+/// They are created on first use and live as long as the device.
+/// Arguments may reference them with `#[mev(sampler = "...")]` attribute
+/// instead of carrying sampler field.
+///
+/// With `#[mev(immutable_sampler = "...")]` attribute the sampler
+/// is baked into the layout instead of being bound with the arguments,
+/// which is cheaper on Vulkan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WellKnownSampler {
+    /// Trilinear filtering with coordinates clamped to edge.
+    LinearClamp,
+
+    /// Nearest filtering with coordinates clamped to edge.
+    NearestClamp,
+
+    /// Trilinear filtering with repeated coordinates.
+    LinearRepeat,
+}
+
+impl WellKnownSampler {
+    /// Number of well-known samplers.
+    pub(crate) const COUNT: usize = 3;
+
+    /// Returns index of the sampler in the device cache.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(crate) const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns description of the sampler.
+    pub const fn desc(self) -> SamplerDesc {
+        let (filter, mip_map_mode, address_mode) = match self {
+            WellKnownSampler::LinearClamp => {
+                (Filter::Linear, MipMapMode::Linear, AddressMode::ClampToEdge)
+            }
+            WellKnownSampler::NearestClamp => (
+                Filter::Nearest,
+                MipMapMode::Nearest,
+                AddressMode::ClampToEdge,
+            ),
+            WellKnownSampler::LinearRepeat => {
+                (Filter::Linear, MipMapMode::Linear, AddressMode::Repeat)
+            }
+        };
+
+        SamplerDesc {
+            min_filter: filter,
+            mag_filter: filter,
+            mip_map_mode,
+            address_mode: [address_mode; 3],
+            ..SamplerDesc::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_defaults() {
+        assert_eq!(SamplerDesc::builder().build(), SamplerDesc::new());
+    }
+
+    #[test]
+    fn builder_matches_desc() {
+        let built = SamplerDesc::builder()
+            .min_filter(Filter::Linear)
+            .mip_map_mode(MipMapMode::Linear)
+            .address_modes([
+                AddressMode::ClampToEdge,
+                AddressMode::Repeat,
+                AddressMode::MirrorRepeat,
+            ])
+            .anisotropy(8.0)
+            .lod(1.0, 4.0)
+            .normalized(false)
+            .build();
+
+        let explicit = SamplerDesc {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Nearest,
+            mip_map_mode: MipMapMode::Linear,
+            address_mode: [
+                AddressMode::ClampToEdge,
+                AddressMode::Repeat,
+                AddressMode::MirrorRepeat,
+            ],
+            anisotropy: Some(8.0),
+            min_lod: 1.0,
+            max_lod: 4.0,
+            normalized: false,
+        };
+
+        assert_eq!(built, explicit);
+    }
+
+    #[test]
+    fn builder_filter_and_address_mode() {
+        let built = SamplerDesc::builder()
+            .filter(Filter::Linear)
+            .address_mode(AddressMode::ClampToEdge)
+            .build();
+
+        assert_eq!(built.min_filter, Filter::Linear);
+        assert_eq!(built.mag_filter, Filter::Linear);
+        assert_eq!(built.address_mode, [AddressMode::ClampToEdge; 3]);
+    }
+}
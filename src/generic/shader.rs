@@ -1,13 +1,17 @@
 use std::{borrow::Cow, error::Error, fmt};
 
+use hashbrown::HashMap;
+
 use codespan_reporting::{
     diagnostic::{Diagnostic, Label},
-    files::SimpleFile,
+    files::SimpleFiles,
     term::{self, termcolor::Buffer},
 };
-use naga::FastHashMap;
 
-use crate::{backend::Library, generic::OutOfMemory};
+use crate::{
+    backend::Library,
+    generic::{ArgumentGroupLayout, DeviceLimits, Features, OutOfMemory},
+};
 
 /// Shader stage.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -47,6 +51,13 @@ bitflags::bitflags! {
     }
 }
 
+impl From<ShaderStage> for ShaderStages {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn from(stage: ShaderStage) -> Self {
+        ShaderStages::from_bits_truncate(1 << stage as u32)
+    }
+}
+
 /// Shader language.
 ///
 /// Must be specified when loading shader source.
@@ -116,6 +127,14 @@ macro_rules! include_shader_source {
 pub enum LibraryInput<'a> {
     /// Shader source code.
     Source(ShaderSource<'a>),
+
+    /// Multiple WGSL sources composed into single module.
+    ///
+    /// Sources are concatenated in order, so declarations used by later sources
+    /// should come first, e.g. `common.wgsl`, then `brdf.wgsl`, then material.
+    /// Defining the same module-scope name in two sources is an error.
+    /// Diagnostics refer to the file and line of the source that caused them.
+    Sources(Vec<ShaderSource<'a>>),
 }
 
 /// Convenience macro to include shader library input from a source code file during compilation.
@@ -146,6 +165,35 @@ pub struct Shader<'a> {
     pub entry: Cow<'a, str>,
 }
 
+/// Library is omitted, it contains backend handles only.
+impl fmt::Debug for Shader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shader")
+            .field("entry", &self.entry)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Shader<'_> {
+    /// Returns shader that owns its entry point name.
+    pub fn into_owned(self) -> Shader<'static> {
+        Shader {
+            library: self.library,
+            entry: Cow::Owned(self.entry.into_owned()),
+        }
+    }
+}
+
+/// Entry point of the shader library.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EntryPoint {
+    /// Name of the entry point.
+    pub name: Box<str>,
+
+    /// Stage of the entry point.
+    pub stage: ShaderStage,
+}
+
 /// Error that can occur during library creation.
 #[derive(Debug)]
 pub enum CreateLibraryError {
@@ -184,11 +232,45 @@ impl Error for CreateLibraryError {}
 #[derive(Debug)]
 pub(crate) enum ShaderCompileError {
     NonUtf8(std::str::Utf8Error),
+
+    /// Support for the shader language is disabled by crate features.
+    ShaderLanguageNotEnabled(ShaderLanguage),
+
+    /// SPIR-V code is not a multiple of 4 bytes or has invalid header.
+    InvalidSpirV,
+
+    #[cfg(feature = "shader-spirv")]
     ParseSpirV(naga::front::spv::Error),
+
+    #[cfg(feature = "shader-wgsl")]
     ParseWgsl(naga::front::wgsl::ParseError),
+
+    #[cfg(feature = "shader-glsl")]
     ParseGlsl(naga::front::glsl::ParseErrors),
+
     ValidationFailed,
 
+    /// Only WGSL sources can be composed into single library.
+    NotComposable(ShaderLanguage),
+
+    /// Module-scope name is defined in more than one composed source.
+    DuplicateDefinition {
+        name: String,
+        first: String,
+        second: String,
+    },
+
+    /// Shader is valid but requires capabilities not enabled on the device.
+    MissingCapabilities(naga::valid::Capabilities),
+
+    /// Fixed-size binding array has more elements than bindless table can hold.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    BindingArrayTooLarge {
+        name: String,
+        size: u32,
+        max: u32,
+    },
+
     #[cfg(any(windows, all(unix, not(any(target_os = "macos", target_os = "ios")))))]
     GenSpirV(naga::back::spv::Error),
 
@@ -200,25 +282,332 @@ impl fmt::Display for ShaderCompileError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ShaderCompileError::NonUtf8(err) => write!(f, "non-utf8: {}", err),
+            ShaderCompileError::ShaderLanguageNotEnabled(lang) => match language_feature(*lang) {
+                Some(feature) => write!(
+                    f,
+                    "{} shaders are not supported, enable `{}` feature of `mev`",
+                    lang, feature
+                ),
+                None => write!(f, "{} shaders are not supported by this backend", lang),
+            },
+            ShaderCompileError::InvalidSpirV => write!(f, "invalid SPIR-V header"),
+            #[cfg(feature = "shader-spirv")]
             ShaderCompileError::ParseSpirV(err) => write!(f, "parse SPIR-V: {}", err),
+            #[cfg(feature = "shader-wgsl")]
             ShaderCompileError::ParseWgsl(err) => write!(f, "parse WGSL: {}", err),
+            #[cfg(feature = "shader-glsl")]
             ShaderCompileError::ParseGlsl(err) => write!(f, "parse GLSL: {}", err),
             ShaderCompileError::ValidationFailed => write!(f, "validation failed"),
+            ShaderCompileError::NotComposable(lang) => {
+                write!(
+                    f,
+                    "{} sources cannot be composed, only WGSL is supported",
+                    lang
+                )
+            }
+            ShaderCompileError::DuplicateDefinition {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "`{}` is defined in both `{}` and `{}`",
+                name, first, second
+            ),
+            ShaderCompileError::MissingCapabilities(caps) => {
+                write!(f, "shader requires capabilities not enabled on the device:")?;
+                for (idx, (name, cap)) in caps.iter_names().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    match capability_feature(cap) {
+                        Some(feature) => write!(f, " {name} (enable `Features::{feature}`)")?,
+                        None => write!(f, " {name} (not supported)")?,
+                    }
+                }
+                Ok(())
+            }
             #[cfg(any(windows, all(unix, not(any(target_os = "macos", target_os = "ios")))))]
             ShaderCompileError::GenSpirV(err) => write!(f, "generate SPIR-V: {}", err),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
+            ShaderCompileError::BindingArrayTooLarge { name, size, max } => write!(
+                f,
+                "binding array `{}` has {} elements, bindless table can hold at most {}",
+                name, size, max
+            ),
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
             ShaderCompileError::GenMsl(err) => write!(f, "generate MSL: {}", err),
         }
     }
 }
 
+/// Returns crate feature that enables compilation from the shader language.
+fn language_feature(lang: ShaderLanguage) -> Option<&'static str> {
+    match lang {
+        ShaderLanguage::SpirV => Some("shader-spirv"),
+        ShaderLanguage::Wgsl => Some("shader-wgsl"),
+        ShaderLanguage::Glsl { .. } => Some("shader-glsl"),
+        ShaderLanguage::Msl => None,
+    }
+}
+
+/// Returns name of the device feature that enables naga capability.
+fn capability_feature(cap: naga::valid::Capabilities) -> Option<&'static str> {
+    FEATURE_CAPABILITIES
+        .iter()
+        .find(|(_, _, caps)| caps.contains(cap))
+        .map(|(name, _, _)| *name)
+}
+
+/// Naga capabilities that are enabled by device features.
+const FEATURE_CAPABILITIES: [(&str, Features, naga::valid::Capabilities); 6] = [
+    (
+        "DUAL_SOURCE_BLEND",
+        Features::DUAL_SOURCE_BLEND,
+        naga::valid::Capabilities::DUAL_SOURCE_BLENDING,
+    ),
+    (
+        "RAY_TRACING",
+        Features::RAY_TRACING,
+        naga::valid::Capabilities::RAY_QUERY,
+    ),
+    (
+        "SHADER_FLOAT64",
+        Features::SHADER_FLOAT64,
+        naga::valid::Capabilities::FLOAT64,
+    ),
+    (
+        "SHADER_INT64",
+        Features::SHADER_INT64,
+        naga::valid::Capabilities::SHADER_INT64,
+    ),
+    (
+        "SUBGROUP",
+        Features::SUBGROUP,
+        naga::valid::Capabilities::SUBGROUP.union(naga::valid::Capabilities::SUBGROUP_BARRIER),
+    ),
+    (
+        "DESCRIPTOR_INDEXING",
+        Features::DESCRIPTOR_INDEXING,
+        naga::valid::Capabilities::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+    ),
+];
+
+/// Naga capabilities that no device feature enables.
+const UNSUPPORTED_CAPABILITIES: naga::valid::Capabilities =
+    naga::valid::Capabilities::SUBGROUP_VERTEX_STAGE
+        .union(naga::valid::Capabilities::SHADER_INT64_ATOMIC_MIN_MAX)
+        .union(naga::valid::Capabilities::SHADER_INT64_ATOMIC_ALL_OPS);
+
+/// Returns naga capabilities available to shaders on device with enabled `features`.
+///
+/// `backend` is the set of capabilities the backend can translate,
+/// capabilities gated by device features are removed unless the feature is enabled.
+pub(crate) fn shader_capabilities(
+    backend: naga::valid::Capabilities,
+    features: Features,
+) -> naga::valid::Capabilities {
+    let mut caps = backend - UNSUPPORTED_CAPABILITIES;
+    for (_, feature, feature_caps) in FEATURE_CAPABILITIES {
+        if !features.contains(feature) {
+            caps -= feature_caps;
+        }
+    }
+    caps
+}
+
+/// Finds capabilities missing from `caps` that `module` requires.
+///
+/// Returns `None` if module is invalid regardless of capabilities.
+fn missing_capabilities(
+    module: &naga::Module,
+    caps: naga::valid::Capabilities,
+) -> Option<naga::valid::Capabilities> {
+    let flags = naga::valid::ValidationFlags::all();
+    let all = naga::valid::Capabilities::all();
+
+    naga::valid::Validator::new(flags, all)
+        .validate(module)
+        .ok()?;
+
+    // Capability is required if the module is invalid without it.
+    let mut missing = naga::valid::Capabilities::empty();
+    for cap in (all - caps).iter() {
+        if naga::valid::Validator::new(flags, all - cap)
+            .validate(module)
+            .is_err()
+        {
+            missing |= cap;
+        }
+    }
+    Some(missing)
+}
+
+/// File that constitutes part of the shader code.
+pub(crate) struct SourceFile<'a> {
+    pub name: Cow<'a, str>,
+
+    /// Offset of the file in the shader code.
+    pub start: usize,
+}
+
+impl<'a> SourceFile<'a> {
+    /// Returns file list for shader code with optional name.
+    pub fn single(filename: Option<&'a str>) -> Vec<Self> {
+        filename
+            .map(|name| SourceFile {
+                name: Cow::Borrowed(name),
+                start: 0,
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// WGSL code composed from multiple sources.
+pub(crate) struct ComposedSource<'a> {
+    pub code: String,
+    pub files: Vec<SourceFile<'a>>,
+}
+
+/// Concatenates WGSL sources in order, remembering where each one starts.
+///
+/// Fails if same module-scope name is defined in more than one source.
+pub(crate) fn compose_wgsl<'a>(
+    sources: &[ShaderSource<'a>],
+) -> Result<ComposedSource<'a>, ShaderCompileError> {
+    let mut code = String::new();
+    let mut files = Vec::with_capacity(sources.len());
+    let mut defined = HashMap::<&str, usize>::new();
+
+    for (idx, source) in sources.iter().enumerate() {
+        if source.language != ShaderLanguage::Wgsl {
+            return Err(ShaderCompileError::NotComposable(source.language));
+        }
+
+        let text = std::str::from_utf8(&source.code).map_err(ShaderCompileError::NonUtf8)?;
+
+        let name = match source.filename {
+            Some(filename) => Cow::Borrowed(filename),
+            None => Cow::Owned(format!("<source {}>", idx)),
+        };
+
+        for decl in module_scope_names(text) {
+            if let Some(first) = defined.insert(decl, idx) {
+                if first != idx {
+                    return Err(ShaderCompileError::DuplicateDefinition {
+                        name: decl.to_owned(),
+                        first: files
+                            .get(first)
+                            .map_or_else(String::new, |file: &SourceFile| file.name.to_string()),
+                        second: name.into_owned(),
+                    });
+                }
+            }
+        }
+
+        files.push(SourceFile {
+            name,
+            start: code.len(),
+        });
+        code.push_str(text);
+        if !code.ends_with('\n') {
+            code.push('\n');
+        }
+    }
+
+    Ok(ComposedSource { code, files })
+}
+
+/// Returns names declared at module scope of WGSL source.
+///
+/// This is not a parser, it only skips comments and tracks braces,
+/// which is enough to find declarations in code that naga accepts.
+fn module_scope_names(code: &str) -> Vec<&str> {
+    const KEYWORDS: [&str; 6] = ["fn", "struct", "var", "const", "override", "alias"];
+
+    let bytes = code.as_bytes();
+    let mut names = Vec::new();
+    let mut depth = 0u32;
+    let mut expect_name = false;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        match rest[0] {
+            b'/' if rest.get(1) == Some(&b'/') => {
+                pos += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            }
+            b'/' if rest.get(1) == Some(&b'*') => {
+                let mut nesting = 0u32;
+                while pos < bytes.len() {
+                    if bytes[pos..].starts_with(b"/*") {
+                        nesting += 1;
+                        pos += 2;
+                    } else if bytes[pos..].starts_with(b"*/") {
+                        nesting -= 1;
+                        pos += 2;
+                        if nesting == 0 {
+                            break;
+                        }
+                    } else {
+                        pos += 1;
+                    }
+                }
+            }
+            b'{' => {
+                depth += 1;
+                pos += 1;
+            }
+            b'}' => {
+                depth = depth.saturating_sub(1);
+                pos += 1;
+            }
+            b'<' if expect_name => {
+                // Skip address space of `var<...>`.
+                pos += rest
+                    .iter()
+                    .position(|&b| b == b'>')
+                    .map_or(rest.len(), |p| p + 1);
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                let len = rest
+                    .iter()
+                    .position(|&b| !b.is_ascii_alphanumeric() && b != b'_')
+                    .unwrap_or(rest.len());
+                let ident = &code[pos..pos + len];
+                if expect_name {
+                    names.push(ident);
+                    expect_name = false;
+                } else if depth == 0 && KEYWORDS.contains(&ident) {
+                    expect_name = true;
+                }
+                pos += len;
+            }
+            b if b.is_ascii_whitespace() => pos += 1,
+            _ => {
+                expect_name = false;
+                pos += 1;
+            }
+        }
+    }
+
+    names
+}
+
+/// Parses and validates shader code.
+///
+/// Module is validated with `caps`, see [`shader_capabilities`].
 pub(crate) fn parse_shader<'a>(
     code: &'a [u8],
-    filename: Option<&str>,
+    files: &[SourceFile],
     lang: ShaderLanguage,
+    caps: naga::valid::Capabilities,
 ) -> Result<(naga::Module, naga::valid::ModuleInfo, Option<&'a str>), ShaderCompileError> {
+    #[allow(unused_mut)]
     let mut source_code = None;
     let module = match lang {
+        #[cfg(feature = "shader-spirv")]
         ShaderLanguage::SpirV => {
             naga::front::spv::parse_u8_slice(code, &naga::front::spv::Options::default())
                 .map_err(ShaderCompileError::ParseSpirV)?
@@ -226,18 +615,23 @@ pub(crate) fn parse_shader<'a>(
         ShaderLanguage::Msl => {
             unimplemented!("Compilation from MSL is not supported")
         }
+        #[cfg(feature = "shader-wgsl")]
         ShaderLanguage::Wgsl => {
             let code = std::str::from_utf8(code).map_err(ShaderCompileError::NonUtf8)?;
             source_code = Some(code);
-            naga::front::wgsl::parse_str(code).map_err(ShaderCompileError::ParseWgsl)?
+            naga::front::wgsl::parse_str(code).map_err(|e| {
+                emit_annotated_diagnostic(e.message(), e.labels(), code, files);
+                ShaderCompileError::ParseWgsl(e)
+            })?
         }
+        #[cfg(feature = "shader-glsl")]
         ShaderLanguage::Glsl { stage } => {
             let code = std::str::from_utf8(code).map_err(ShaderCompileError::NonUtf8)?;
             source_code = Some(code);
             naga::front::glsl::Frontend::default()
                 .parse(
                     &naga::front::glsl::Options {
-                        defines: FastHashMap::default(),
+                        defines: naga::FastHashMap::default(),
                         stage: match stage {
                             ShaderStage::Vertex => naga::ShaderStage::Vertex,
                             ShaderStage::Fragment => naga::ShaderStage::Fragment,
@@ -248,20 +642,25 @@ pub(crate) fn parse_shader<'a>(
                 )
                 .map_err(ShaderCompileError::ParseGlsl)?
         }
+        #[allow(unreachable_patterns)]
+        _ => return Err(ShaderCompileError::ShaderLanguageNotEnabled(lang)),
     };
 
     let flags = naga::valid::ValidationFlags::all();
-    let caps = naga::valid::Capabilities::all();
     let info = naga::valid::Validator::new(flags, caps)
         .validate(&module)
         .map_err(|e| {
-            emit_annotated_error(
-                &e,
-                filename.and_then(|filename| {
-                    std::str::from_utf8(code)
-                        .ok()
-                        .map(|source| (filename, source))
-                }),
+            if let Some(missing) =
+                missing_capabilities(&module, caps).filter(|missing| !missing.is_empty())
+            {
+                return ShaderCompileError::MissingCapabilities(missing);
+            }
+
+            emit_annotated_diagnostic(
+                &e.as_inner().to_string(),
+                e.spans().map(|(span, desc)| (*span, desc.as_str())),
+                std::str::from_utf8(code).unwrap_or(""),
+                files,
             );
             ShaderCompileError::ValidationFailed
         })?;
@@ -269,40 +668,878 @@ pub(crate) fn parse_shader<'a>(
     Ok((module, info, source_code))
 }
 
-fn emit_annotated_error<E: std::error::Error>(
-    error: &naga::WithSpan<E>,
-    file: Option<(&str, &str)>,
+/// Information about shader entry point collected from the shader module.
+pub(crate) struct EntryReflection {
+    pub name: Box<str>,
+    pub stage: ShaderStage,
+
+    /// Whether fragment entry point writes second blend source.
+    pub writes_second_blend_source: bool,
+
+    /// Argument bindings used by the entry point as `(group, binding)` pairs.
+    pub bindings: Box<[(u32, u32)]>,
+
+    /// Uniform and storage buffers used by the entry point.
+    pub buffers: Box<[BufferBinding]>,
+
+    /// User-defined inputs of the entry point.
+    pub inputs: Box<[InterfaceVar]>,
+
+    /// User-defined outputs of the entry point.
+    pub outputs: Box<[InterfaceVar]>,
+}
+
+/// Shader entry point is not found in the library.
+#[derive(Debug)]
+pub(crate) struct UnknownEntryPoint {
+    pub requested: String,
+    pub stage: ShaderStage,
+
+    /// Names of the library entry points of the requested stage.
+    pub available: Vec<String>,
+}
+
+impl fmt::Display for UnknownEntryPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entry point `{}` is not found in the library",
+            self.stage, self.requested
+        )?;
+
+        if self.available.is_empty() {
+            write!(f, ", library has no {} entry points", self.stage)
+        } else {
+            write!(f, ", available: {}", self.available.join(", "))
+        }
+    }
+}
+
+/// Checks that the library has entry point with given name and stage.
+///
+/// Libraries that were not reflected have no entry points and are not checked.
+pub(crate) fn check_entry_point(
+    entry_points: &[EntryPoint],
+    entry: &str,
+    stage: ShaderStage,
+) -> Result<(), UnknownEntryPoint> {
+    if entry_points.is_empty()
+        || entry_points
+            .iter()
+            .any(|ep| *ep.name == *entry && ep.stage == stage)
+    {
+        return Ok(());
+    }
+
+    Err(UnknownEntryPoint {
+        requested: entry.to_owned(),
+        stage,
+        available: entry_points
+            .iter()
+            .filter(|ep| ep.stage == stage)
+            .map(|ep| ep.name.to_string())
+            .collect(),
+    })
+}
+
+/// Argument used by the shader is not visible to its stage.
+#[derive(Debug)]
+pub(crate) struct ArgumentStageMismatch {
+    pub group: u32,
+    pub binding: u32,
+    pub stage: ShaderStage,
+}
+
+impl fmt::Display for ArgumentStageMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "argument {} in group {} is used in {} shader but is not visible to {} stage",
+            self.binding, self.group, self.stage, self.stage
+        )
+    }
+}
+
+/// Checks that arguments used by the entry point are visible to its stage.
+///
+/// Arguments that are not in the layouts are not checked.
+pub(crate) fn check_argument_stages(
+    entry: &EntryReflection,
+    arguments: &[ArgumentGroupLayout],
+) -> Result<(), ArgumentStageMismatch> {
+    for &(group, binding) in entry.bindings.iter() {
+        let layout = arguments
+            .get(group as usize)
+            .and_then(|group| group.arguments.get(binding as usize));
+
+        if let Some(layout) = layout {
+            if !layout.stages.contains(ShaderStages::from(entry.stage)) {
+                return Err(ArgumentStageMismatch {
+                    group,
+                    binding,
+                    stage: entry.stage,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Uniform or storage buffer used by the shader.
+#[derive(Clone, Debug)]
+pub(crate) struct BufferBinding {
+    /// Name of the global variable in the shader.
+    pub name: Box<str>,
+    pub group: u32,
+    pub binding: u32,
+    pub uniform: bool,
+
+    /// Minimal size of the buffer in bytes.
+    /// Runtime-sized arrays are counted as one element.
+    pub size: u64,
+}
+
+/// Buffer used by the shader is larger than device can bind.
+#[derive(Debug)]
+pub(crate) struct BufferRangeExceeded {
+    pub name: Box<str>,
+    pub group: u32,
+    pub binding: u32,
+    pub size: u64,
+    pub limit: &'static str,
+    pub max: u64,
+}
+
+impl fmt::Display for BufferRangeExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer `{}` (argument {} in group {}) requires at least {} bytes, exceeding device limit `{}` {}",
+            self.name, self.binding, self.group, self.size, self.limit, self.max
+        )
+    }
+}
+
+/// Checks that buffers used by the entry point fit into buffer range limits.
+pub(crate) fn check_buffer_ranges(
+    entry: &EntryReflection,
+    limits: &DeviceLimits,
+) -> Result<(), BufferRangeExceeded> {
+    for buffer in entry.buffers.iter() {
+        let (limit, max) = match buffer.uniform {
+            true => ("max_uniform_buffer_range", limits.max_uniform_buffer_range),
+            false => ("max_storage_buffer_range", limits.max_storage_buffer_range),
+        };
+
+        if buffer.size > max {
+            return Err(BufferRangeExceeded {
+                name: buffer.name.clone(),
+                group: buffer.group,
+                binding: buffer.binding,
+                size: buffer.size,
+                limit,
+                max,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Type of the value passed between shader stages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct InterfaceType {
+    scalar: naga::Scalar,
+    size: Option<naga::VectorSize>,
+}
+
+impl fmt::Display for InterfaceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scalar = match self.scalar.kind {
+            naga::ScalarKind::Sint => 'i',
+            naga::ScalarKind::Uint => 'u',
+            naga::ScalarKind::Float => 'f',
+            naga::ScalarKind::Bool => return write!(f, "bool"),
+            naga::ScalarKind::AbstractInt | naga::ScalarKind::AbstractFloat => {
+                return write!(f, "abstract")
+            }
+        };
+        let bits = u32::from(self.scalar.width) * 8;
+
+        match self.size {
+            None => write!(f, "{scalar}{bits}"),
+            Some(size) => write!(f, "vec{}<{scalar}{bits}>", size as u8),
+        }
+    }
+}
+
+/// User-defined input or output of the entry point.
+pub(crate) struct InterfaceVar {
+    pub location: u32,
+    pub ty: InterfaceType,
+}
+
+/// Mismatch between vertex shader outputs and fragment shader inputs.
+#[derive(Debug)]
+pub(crate) struct StageInterfaceMismatch {
+    pub location: u32,
+
+    /// Type of the vertex output or `None` if vertex shader doesn't write it.
+    pub vertex_ty: Option<InterfaceType>,
+    pub fragment_ty: InterfaceType,
+}
+
+impl fmt::Display for StageInterfaceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.vertex_ty {
+            None => write!(
+                f,
+                "fragment shader reads `{}` at location {} but vertex shader does not write it",
+                self.fragment_ty, self.location
+            ),
+            Some(vertex_ty) => write!(
+                f,
+                "vertex shader writes `{}` at location {} but fragment shader reads `{}`",
+                vertex_ty, self.location, self.fragment_ty
+            ),
+        }
+    }
+}
+
+/// Checks that every fragment shader input is written by vertex shader
+/// with the same type.
+pub(crate) fn check_stage_interface(
+    vertex: &EntryReflection,
+    fragment: &EntryReflection,
+) -> Result<(), StageInterfaceMismatch> {
+    for input in fragment.inputs.iter() {
+        let output = vertex
+            .outputs
+            .iter()
+            .find(|output| output.location == input.location);
+
+        match output {
+            Some(output) if output.ty == input.ty => {}
+            _ => {
+                return Err(StageInterfaceMismatch {
+                    location: input.location,
+                    vertex_ty: output.map(|output| output.ty),
+                    fragment_ty: input.ty,
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects user-defined locations of the value of type `ty` bound with `binding`.
+/// Structures are flattened into their members.
+fn collect_interface(
+    module: &naga::Module,
+    ty: naga::Handle<naga::Type>,
+    binding: Option<&naga::Binding>,
+    vars: &mut Vec<InterfaceVar>,
 ) {
-    if let Some((filename, source)) = file {
-        let files = SimpleFile::new(filename, source);
-        let config = term::Config::default();
-        let mut writer = Buffer::no_color();
-
-        let diagnostic = Diagnostic::error().with_labels(
-            error
-                .spans()
-                .map(|(span, desc)| {
-                    Label::primary((), span.to_range().unwrap()).with_message(desc.to_owned())
+    match (&module.types[ty].inner, binding) {
+        (naga::TypeInner::Struct { members, .. }, None) => {
+            for member in members {
+                collect_interface(module, member.ty, member.binding.as_ref(), vars);
+            }
+        }
+        (inner, Some(naga::Binding::Location { location, .. })) => {
+            let ty = match *inner {
+                naga::TypeInner::Scalar(scalar) => InterfaceType { scalar, size: None },
+                naga::TypeInner::Vector { size, scalar } => InterfaceType {
+                    scalar,
+                    size: Some(size),
+                },
+                _ => return,
+            };
+            vars.push(InterfaceVar {
+                location: *location,
+                ty,
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Collects information about all entry points of the module.
+pub(crate) fn reflect_entries(
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+) -> Vec<EntryReflection> {
+    let writes_second_source = |binding: &Option<naga::Binding>| {
+        matches!(
+            binding,
+            Some(naga::Binding::Location {
+                second_blend_source: true,
+                ..
+            })
+        )
+    };
+
+    module
+        .entry_points
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let writes_second_blend_source = entry.stage == naga::ShaderStage::Fragment
+                && match &entry.function.result {
+                    None => false,
+                    Some(result) => match &module.types[result.ty].inner {
+                        naga::TypeInner::Struct { members, .. } => members
+                            .iter()
+                            .any(|member| writes_second_source(&member.binding)),
+                        _ => writes_second_source(&result.binding),
+                    },
+                };
+
+            let entry_info = info.get_entry_point(idx);
+            let bindings = module
+                .global_variables
+                .iter()
+                .filter(|(handle, _)| !entry_info[*handle].is_empty())
+                .filter_map(|(_, var)| var.binding.as_ref())
+                .map(|binding| (binding.group, binding.binding))
+                .collect();
+
+            let buffers = module
+                .global_variables
+                .iter()
+                .filter(|(handle, _)| !entry_info[*handle].is_empty())
+                .filter_map(|(_, var)| {
+                    let uniform = match var.space {
+                        naga::AddressSpace::Uniform => true,
+                        naga::AddressSpace::Storage { .. } => false,
+                        _ => return None,
+                    };
+                    let binding = var.binding.as_ref()?;
+
+                    let ty = match module.types[var.ty].inner {
+                        naga::TypeInner::BindingArray { base, .. } => base,
+                        _ => var.ty,
+                    };
+
+                    Some(BufferBinding {
+                        name: var.name.as_deref().unwrap_or("<unnamed>").into(),
+                        group: binding.group,
+                        binding: binding.binding,
+                        uniform,
+                        size: u64::from(module.types[ty].inner.size(module.to_ctx())),
+                    })
                 })
-                .collect(),
+                .collect();
+
+            let mut inputs = Vec::new();
+            for argument in &entry.function.arguments {
+                collect_interface(module, argument.ty, argument.binding.as_ref(), &mut inputs);
+            }
+
+            let mut outputs = Vec::new();
+            if let Some(result) = &entry.function.result {
+                collect_interface(module, result.ty, result.binding.as_ref(), &mut outputs);
+            }
+
+            EntryReflection {
+                name: entry.name.as_str().into(),
+                stage: match entry.stage {
+                    naga::ShaderStage::Vertex => ShaderStage::Vertex,
+                    naga::ShaderStage::Fragment => ShaderStage::Fragment,
+                    naga::ShaderStage::Compute => ShaderStage::Compute,
+                },
+                writes_second_blend_source,
+                bindings,
+                buffers,
+                inputs: inputs.into(),
+                outputs: outputs.into(),
+            }
+        })
+        .collect()
+}
+
+/// Logs error with source code annotations.
+///
+/// See [`annotated_diagnostic`].
+fn emit_annotated_diagnostic<'a>(
+    error: &str,
+    spans: impl Iterator<Item = (naga::Span, &'a str)>,
+    code: &str,
+    files: &[SourceFile],
+) {
+    match annotated_diagnostic(error, spans, code, files) {
+        Some(diagnostic) => tracing::event!(
+            target: "naga",
+            tracing::Level::ERROR,
+            error = error,
+            diagnostic = diagnostic,
+        ),
+        None => tracing::event!(
+            target: "naga",
+            tracing::Level::ERROR,
+            error = error,
+        ),
+    }
+}
+
+/// Renders error with source code annotations.
+///
+/// Spans are relative to the whole `code` and each is reported
+/// against the file from `files` that contains it.
+/// Returns `None` if there are no files to annotate.
+fn annotated_diagnostic<'a>(
+    error: &str,
+    spans: impl Iterator<Item = (naga::Span, &'a str)>,
+    code: &str,
+    files: &[SourceFile],
+) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+
+    let mut db = SimpleFiles::new();
+    let ids = files
+        .iter()
+        .enumerate()
+        .map(|(idx, file)| {
+            let end = files.get(idx + 1).map_or(code.len(), |next| next.start);
+            db.add(&*file.name, code.get(file.start..end).unwrap_or(""))
+        })
+        .collect::<Vec<_>>();
+
+    let config = term::Config::default();
+    let mut writer = Buffer::no_color();
+
+    let diagnostic = Diagnostic::error().with_message(error).with_labels(
+        spans
+            .filter_map(|(span, desc)| {
+                let range = span.to_range()?;
+                let idx = files
+                    .iter()
+                    .rposition(|file| file.start <= range.start)
+                    .unwrap_or(0);
+                let end = files.get(idx + 1).map_or(code.len(), |next| next.start);
+                let start = files[idx].start;
+                let range = range.start - start..range.end.min(end).max(range.start) - start;
+                Some(Label::primary(ids[idx], range).with_message(desc.to_owned()))
+            })
+            .collect(),
+    );
+
+    term::emit(&mut writer, &config, &db, &diagnostic).ok()?;
+    String::from_utf8(writer.into_inner()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic::{ArgumentKind, ArgumentLayout};
+
+    fn entry(stage: ShaderStage, bindings: &[(u32, u32)]) -> EntryReflection {
+        EntryReflection {
+            name: "main".into(),
+            stage,
+            writes_second_blend_source: false,
+            bindings: bindings.into(),
+            buffers: Box::new([]),
+            inputs: Box::new([]),
+            outputs: Box::new([]),
+        }
+    }
+
+    const fn argument(stages: ShaderStages) -> ArgumentLayout {
+        ArgumentLayout {
+            kind: ArgumentKind::SampledImage,
+            size: 1,
+            stages,
+            immutable_sampler: None,
+        }
+    }
+
+    #[test]
+    fn argument_stages() {
+        let group = [
+            argument(ShaderStages::VERTEX),
+            argument(ShaderStages::FRAGMENT),
+        ];
+        let arguments = [ArgumentGroupLayout { arguments: &group }];
+
+        let vertex = entry(ShaderStage::Vertex, &[(0, 0)]);
+        assert!(check_argument_stages(&vertex, &arguments).is_ok());
+
+        let fragment = entry(ShaderStage::Fragment, &[(0, 1)]);
+        assert!(check_argument_stages(&fragment, &arguments).is_ok());
+
+        let fragment = entry(ShaderStage::Fragment, &[(0, 1), (0, 0)]);
+        let err = check_argument_stages(&fragment, &arguments).unwrap_err();
+        assert_eq!((err.group, err.binding), (0, 0));
+        assert_eq!(err.stage, ShaderStage::Fragment);
+        assert_eq!(
+            err.to_string(),
+            "argument 0 in group 0 is used in fragment shader but is not visible to fragment stage"
         );
 
-        term::emit(&mut writer, &config, &files, &diagnostic).expect("cannot write error");
+        // Bindings outside of layouts are not checked.
+        let compute = entry(ShaderStage::Compute, &[(1, 0), (0, 2)]);
+        assert!(check_argument_stages(&compute, &arguments).is_ok());
+    }
 
-        if let Ok(s) = std::str::from_utf8(writer.as_slice()) {
-            tracing::event!(
-                target: "naga",
-                tracing::Level::ERROR,
-                error = error.as_inner().to_string(),
-                diagnostic = s,
-            );
-            return;
+    /// Parses WGSL module and reflects its entry points by name.
+    #[cfg(feature = "shader-wgsl")]
+    fn reflect_wgsl(code: &str) -> HashMap<String, EntryReflection> {
+        let module = naga::front::wgsl::parse_str(code).unwrap();
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .unwrap();
+
+        reflect_entries(&module, &info)
+            .into_iter()
+            .map(|entry| (entry.name.to_string(), entry))
+            .collect()
+    }
+
+    #[cfg(feature = "shader-wgsl")]
+    const INTERFACE: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main() -> VertexOutput {
+    return VertexOutput(vec4<f32>(0.0), vec2<f32>(0.0), vec4<f32>(1.0));
+}
+
+@vertex
+fn vs_position() -> @builtin(position) vec4<f32> {
+    return vec4<f32>(0.0);
+}
+
+@fragment
+fn fs_color(@location(1) color: vec4<f32>) -> @location(0) vec4<f32> {
+    return color;
+}
+
+@fragment
+fn fs_color_vec3(@location(1) color: vec3<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn fs_index(@location(1) @interpolate(flat) index: u32) -> @location(0) vec4<f32> {
+    return vec4<f32>(f32(index));
+}
+"#;
+
+    #[test]
+    #[cfg(feature = "shader-wgsl")]
+    fn buffer_ranges() {
+        let entries = reflect_wgsl(
+            r#"
+struct Lights {
+    count: u32,
+    colors: array<vec4<f32>, 8192>,
+}
+
+@group(0) @binding(0) var<uniform> lights: Lights;
+@group(0) @binding(1) var<storage, read_write> particles: array<vec4<f32>>;
+
+@compute @workgroup_size(1)
+fn main() {
+    particles[0] = lights.colors[lights.count];
+}
+"#,
+        );
+        let entry = &entries["main"];
+
+        let limits = DeviceLimits {
+            max_argument_groups: 4,
+            max_uniform_buffer_range: 1 << 16,
+            max_storage_buffer_range: 1 << 27,
+            max_bindless_table_size: 0,
+        };
+
+        let err = check_buffer_ranges(entry, &limits).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "buffer `lights` (argument 0 in group 0) requires at least 131088 bytes, \
+            exceeding device limit `max_uniform_buffer_range` 65536"
+        );
+
+        // Runtime-sized array counts as a single element.
+        let limits = DeviceLimits {
+            max_uniform_buffer_range: 1 << 20,
+            max_storage_buffer_range: 16,
+            ..limits
+        };
+        assert!(check_buffer_ranges(entry, &limits).is_ok());
+
+        let limits = DeviceLimits {
+            max_storage_buffer_range: 15,
+            ..limits
+        };
+        let err = check_buffer_ranges(entry, &limits).unwrap_err();
+        assert_eq!(&*err.name, "particles");
+        assert_eq!(err.limit, "max_storage_buffer_range");
+    }
+
+    #[test]
+    #[cfg(feature = "shader-wgsl")]
+    fn stage_interface_matches() {
+        let entries = reflect_wgsl(INTERFACE);
+
+        // Unused vertex outputs are allowed.
+        assert!(check_stage_interface(&entries["vs_main"], &entries["fs_color"]).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "shader-wgsl")]
+    fn stage_interface_type_mismatch() {
+        let entries = reflect_wgsl(INTERFACE);
+
+        let err =
+            check_stage_interface(&entries["vs_main"], &entries["fs_color_vec3"]).unwrap_err();
+        assert_eq!(err.location, 1);
+        assert_eq!(
+            err.to_string(),
+            "vertex shader writes `vec4<f32>` at location 1 but fragment shader reads `vec3<f32>`"
+        );
+
+        let err = check_stage_interface(&entries["vs_main"], &entries["fs_index"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "vertex shader writes `vec4<f32>` at location 1 but fragment shader reads `u32`"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "shader-wgsl")]
+    fn stage_interface_missing_output() {
+        let entries = reflect_wgsl(INTERFACE);
+
+        let err = check_stage_interface(&entries["vs_position"], &entries["fs_color"]).unwrap_err();
+        assert!(err.vertex_ty.is_none());
+        assert_eq!(
+            err.to_string(),
+            "fragment shader reads `vec4<f32>` at location 1 but vertex shader does not write it"
+        );
+    }
+
+    fn entry_point(name: &str, stage: ShaderStage) -> EntryPoint {
+        EntryPoint {
+            name: name.into(),
+            stage,
         }
     }
 
-    tracing::event!(
-        target: "naga",
-        tracing::Level::ERROR,
-        error = error.as_inner().to_string(),
-    );
+    #[test]
+    fn entry_point_typo() {
+        let entry_points = [
+            entry_point("vs_main", ShaderStage::Vertex),
+            entry_point("fs_main", ShaderStage::Fragment),
+            entry_point("fs_shadow", ShaderStage::Fragment),
+        ];
+
+        assert!(check_entry_point(&entry_points, "fs_main", ShaderStage::Fragment).is_ok());
+
+        let err = check_entry_point(&entry_points, "fs_mian", ShaderStage::Fragment).unwrap_err();
+        assert_eq!(err.requested, "fs_mian");
+        assert_eq!(err.available, ["fs_main", "fs_shadow"]);
+        assert_eq!(
+            err.to_string(),
+            "fragment entry point `fs_mian` is not found in the library, available: fs_main, fs_shadow"
+        );
+    }
+
+    #[test]
+    fn entry_point_wrong_stage() {
+        let entry_points = [
+            entry_point("vs_main", ShaderStage::Vertex),
+            entry_point("fs_main", ShaderStage::Fragment),
+        ];
+
+        let err = check_entry_point(&entry_points, "fs_main", ShaderStage::Compute).unwrap_err();
+        assert!(err.available.is_empty());
+        assert_eq!(
+            err.to_string(),
+            "compute entry point `fs_main` is not found in the library, library has no compute entry points"
+        );
+    }
+
+    #[test]
+    fn entry_point_not_reflected() {
+        // Libraries without reflection are not checked.
+        assert!(check_entry_point(&[], "main", ShaderStage::Vertex).is_ok());
+    }
+
+    #[test]
+    fn capabilities_follow_features() {
+        let all = naga::valid::Capabilities::all();
+
+        let caps = shader_capabilities(all, Features::empty());
+        assert!(!caps
+            .intersects(naga::valid::Capabilities::SUBGROUP | naga::valid::Capabilities::FLOAT64));
+        assert!(!caps.intersects(UNSUPPORTED_CAPABILITIES));
+
+        let caps = shader_capabilities(all, Features::SUBGROUP);
+        assert!(caps.contains(
+            naga::valid::Capabilities::SUBGROUP | naga::valid::Capabilities::SUBGROUP_BARRIER
+        ));
+        assert!(!caps.contains(naga::valid::Capabilities::FLOAT64));
+
+        // Backend capabilities are not extended by features.
+        let caps = shader_capabilities(naga::valid::Capabilities::empty(), Features::all());
+        assert!(caps.is_empty());
+    }
+
+    #[cfg(feature = "shader-wgsl")]
+    const SUBGROUP_SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read_write> sums: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    sums[id.x] = subgroupAdd(id.x);
+}
+"#;
+
+    #[test]
+    #[cfg(feature = "shader-wgsl")]
+    fn subgroup_requires_feature() {
+        let all = naga::valid::Capabilities::all();
+
+        let err = parse_shader(
+            SUBGROUP_SHADER.as_bytes(),
+            &[],
+            ShaderLanguage::Wgsl,
+            shader_capabilities(all, Features::empty()),
+        )
+        .unwrap_err();
+
+        let ShaderCompileError::MissingCapabilities(missing) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(*missing, naga::valid::Capabilities::SUBGROUP);
+        assert_eq!(
+            err.to_string(),
+            "shader requires capabilities not enabled on the device: SUBGROUP (enable `Features::SUBGROUP`)"
+        );
+
+        assert!(parse_shader(
+            SUBGROUP_SHADER.as_bytes(),
+            &[],
+            ShaderLanguage::Wgsl,
+            shader_capabilities(all, Features::SUBGROUP),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "shader-wgsl")]
+    fn invalid_shader_is_not_missing_capabilities() {
+        // Errors unrelated to capabilities are reported as is.
+        let err = parse_shader(
+            b"@compute @workgroup_size(1) fn main() { let x: u32 = 1.0; }",
+            &[],
+            ShaderLanguage::Wgsl,
+            naga::valid::Capabilities::empty(),
+        )
+        .unwrap_err();
+        assert!(!matches!(err, ShaderCompileError::MissingCapabilities(_)));
+    }
+
+    #[test]
+    #[cfg(feature = "shader-wgsl")]
+    fn diagnostic_points_to_second_file() {
+        let sources = [
+            ShaderSource {
+                code: Cow::Borrowed(b"fn double(x: f32) -> f32 {\n    return x * 2.0;\n}\n"),
+                filename: Some("common.wgsl"),
+                language: ShaderLanguage::Wgsl,
+            },
+            ShaderSource {
+                code: Cow::Borrowed(
+                    b"@compute @workgroup_size(1)\nfn main() {\n    let x = triple(1.0);\n}\n",
+                ),
+                filename: Some("main.wgsl"),
+                language: ShaderLanguage::Wgsl,
+            },
+        ];
+
+        let composed = compose_wgsl(&sources).unwrap();
+        assert_eq!(composed.files.len(), 2);
+        assert_eq!(composed.files[1].start, sources[0].code.len());
+
+        let err = naga::front::wgsl::parse_str(&composed.code).unwrap_err();
+        let diagnostic = annotated_diagnostic(
+            &err.message(),
+            err.labels(),
+            &composed.code,
+            &composed.files,
+        )
+        .unwrap();
+
+        // Line and column are relative to the second file.
+        assert!(diagnostic.contains("main.wgsl:3:13"), "{diagnostic}");
+        assert!(!diagnostic.contains("common.wgsl"), "{diagnostic}");
+    }
+
+    #[test]
+    #[cfg(not(feature = "shader-glsl"))]
+    fn glsl_not_enabled() {
+        let lang = ShaderLanguage::Glsl {
+            stage: ShaderStage::Vertex,
+        };
+        let err = parse_shader(
+            b"void main() {}",
+            &[],
+            lang,
+            naga::valid::Capabilities::empty(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderCompileError::ShaderLanguageNotEnabled(_)
+        ));
+        assert!(err.to_string().contains("`shader-glsl`"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "shader-wgsl"))]
+    fn wgsl_not_enabled() {
+        let err = parse_shader(
+            b"",
+            &[],
+            ShaderLanguage::Wgsl,
+            naga::valid::Capabilities::empty(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderCompileError::ShaderLanguageNotEnabled(_)
+        ));
+        assert!(err.to_string().contains("`shader-wgsl`"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "shader-spirv"))]
+    fn spirv_not_enabled() {
+        let err = parse_shader(
+            &[],
+            &[],
+            ShaderLanguage::SpirV,
+            naga::valid::Capabilities::empty(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderCompileError::ShaderLanguageNotEnabled(_)
+        ));
+        assert!(err.to_string().contains("`shader-spirv`"));
+    }
 }
@@ -0,0 +1,21 @@
+struct FullscreenVertexOutput {
+    @builtin(position)
+    position: vec4<f32>,
+
+    // Texture coordinates, (0, 0) is the top-left corner of the viewport.
+    @location(0)
+    uv: vec2<f32>,
+}
+
+// Emits triangle that covers the whole viewport
+// with vertices at (-1, 1), (3, 1) and (-1, -3) in clip space.
+// Draw with 3 vertices and no vertex buffers.
+@vertex
+fn fullscreen_vertex(@builtin(vertex_index) index: u32) -> FullscreenVertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var output: FullscreenVertexOutput;
+    output.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    output.uv = uv;
+    return output;
+}
@@ -27,6 +27,16 @@ pub enum PipelineStage {
 
     /// Stage at which transfer operations are performed.
     Transfer,
+
+    /// Stage at which acceleration structures are built.
+    ///
+    /// Requires [`Features::RAY_TRACING`](crate::Features::RAY_TRACING).
+    AccelerationStructureBuild,
+
+    /// Stage at which ray tracing shaders are executed.
+    ///
+    /// Requires [`Features::RAY_TRACING`](crate::Features::RAY_TRACING).
+    RayTracingShader,
 }
 
 bitflags::bitflags! {
@@ -53,5 +63,20 @@ bitflags::bitflags! {
         const COMPUTE_SHADER = 1 << PipelineStage::ComputeShader as u32;
         /// Bit for [`Transfer`](PipelineStage::Transfer) stage.
         const TRANSFER = 1 << PipelineStage::Transfer as u32;
+        /// Bit for [`AccelerationStructureBuild`](PipelineStage::AccelerationStructureBuild) stage.
+        const ACCELERATION_STRUCTURE_BUILD = 1 << PipelineStage::AccelerationStructureBuild as u32;
+        /// Bit for [`RayTracingShader`](PipelineStage::RayTracingShader) stage.
+        const RAY_TRACING_SHADER = 1 << PipelineStage::RayTracingShader as u32;
     }
 }
+
+impl PipelineStages {
+    /// All stages except ones that require [`Features::RAY_TRACING`](crate::Features::RAY_TRACING).
+    ///
+    /// Use it instead of [`all`](PipelineStages::all) to synchronize with any preceding
+    /// or following work, as barriers with ray tracing stages
+    /// panic in debug builds when the feature is not enabled.
+    pub const ALL_NON_RT: Self = Self::all()
+        .difference(Self::ACCELERATION_STRUCTURE_BUILD)
+        .difference(Self::RAY_TRACING_SHADER);
+}
@@ -7,6 +7,10 @@ use crate::generic::OutOfMemory;
 pub enum SurfaceError {
     OutOfMemory,
     SurfaceLost,
+
+    /// Window system of the window is not supported by the device,
+    /// or window and display handles do not match.
+    Unsupported,
 }
 
 impl From<OutOfMemory> for SurfaceError {
@@ -22,8 +26,217 @@ impl fmt::Display for SurfaceError {
         match self {
             SurfaceError::OutOfMemory => fmt::Display::fmt(&OutOfMemory, f),
             SurfaceError::SurfaceLost => f.write_str("surface lost"),
+            SurfaceError::Unsupported => f.write_str("window system is not supported"),
         }
     }
 }
 
 impl std::error::Error for SurfaceError {}
+
+/// Policy of recreating swapchain that no longer matches the surface exactly,
+/// e.g. after window resize.
+///
+/// Frames of suboptimal swapchain can still be rendered and presented,
+/// but may be stretched by the presentation engine.
+/// Swapchain that can't be presented to anymore is always recreated immediately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SuboptimalPolicy {
+    /// Recreate swapchain on the next frame after it is reported suboptimal.
+    ///
+    /// Swapchain may be recreated every frame during continuous resize.
+    Immediate,
+
+    /// Recreate swapchain reported suboptimal,
+    /// but not sooner than given number of frames after previous recreation.
+    Cooldown(u32),
+
+    /// Never recreate suboptimal swapchain automatically.
+    ///
+    /// Check [`Surface::is_suboptimal`](crate::traits::Surface::is_suboptimal)
+    /// and call [`Surface::recreate`](crate::traits::Surface::recreate)
+    /// when appropriate, e.g. when resize ends.
+    Manual,
+}
+
+impl Default for SuboptimalPolicy {
+    #[inline(always)]
+    fn default() -> Self {
+        SuboptimalPolicy::Cooldown(10)
+    }
+}
+
+/// Reason to recreate swapchain before acquiring next frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Recreate {
+    /// Swapchain is suboptimal and policy allows recreating it.
+    Suboptimal,
+
+    /// Recreation is requested by user or by changed surface settings.
+    Requested,
+}
+
+/// Tracks suboptimal state of the current swapchain according to the policy.
+pub(crate) struct SuboptimalTracker {
+    policy: SuboptimalPolicy,
+
+    /// Frames left before suboptimal swapchain may be recreated.
+    cooldown: u32,
+    suboptimal: bool,
+    requested: bool,
+}
+
+impl SuboptimalTracker {
+    pub fn new() -> Self {
+        let policy = SuboptimalPolicy::default();
+        SuboptimalTracker {
+            policy,
+            cooldown: Self::policy_cooldown(policy),
+            suboptimal: false,
+            requested: false,
+        }
+    }
+
+    fn policy_cooldown(policy: SuboptimalPolicy) -> u32 {
+        match policy {
+            SuboptimalPolicy::Cooldown(frames) => frames,
+            SuboptimalPolicy::Immediate | SuboptimalPolicy::Manual => 0,
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: SuboptimalPolicy) {
+        self.policy = policy;
+        self.cooldown = self.cooldown.min(Self::policy_cooldown(policy));
+    }
+
+    #[inline(always)]
+    pub fn is_suboptimal(&self) -> bool {
+        self.suboptimal
+    }
+
+    /// Called when swapchain is recreated.
+    pub fn reset(&mut self) {
+        self.cooldown = Self::policy_cooldown(self.policy);
+        self.suboptimal = false;
+        self.requested = false;
+    }
+
+    /// Requests recreation on the next frame regardless of the policy.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Called when swapchain is reported suboptimal.
+    pub fn report_suboptimal(&mut self) {
+        self.suboptimal = true;
+    }
+
+    /// Called before acquiring next frame.
+    /// Returns reason to recreate swapchain first, if any.
+    pub fn next_frame(&mut self) -> Option<Recreate> {
+        if self.requested {
+            return Some(Recreate::Requested);
+        }
+
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return None;
+        }
+
+        match self.policy {
+            SuboptimalPolicy::Manual => None,
+            _ if self.suboptimal => Some(Recreate::Suboptimal),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(policy: SuboptimalPolicy) -> SuboptimalTracker {
+        let mut tracker = SuboptimalTracker::new();
+        tracker.set_policy(policy);
+        tracker.reset();
+        tracker
+    }
+
+    #[test]
+    fn immediate() {
+        let mut tracker = tracker(SuboptimalPolicy::Immediate);
+        assert_eq!(tracker.next_frame(), None);
+
+        tracker.report_suboptimal();
+        assert!(tracker.is_suboptimal());
+        assert_eq!(tracker.next_frame(), Some(Recreate::Suboptimal));
+
+        tracker.reset();
+        assert!(!tracker.is_suboptimal());
+        assert_eq!(tracker.next_frame(), None);
+    }
+
+    #[test]
+    fn cooldown() {
+        let mut tracker = tracker(SuboptimalPolicy::Cooldown(3));
+        tracker.report_suboptimal();
+
+        for _ in 0..3 {
+            assert_eq!(tracker.next_frame(), None);
+        }
+        assert_eq!(tracker.next_frame(), Some(Recreate::Suboptimal));
+
+        // Cooldown restarts after recreation.
+        tracker.reset();
+        tracker.report_suboptimal();
+        for _ in 0..3 {
+            assert_eq!(tracker.next_frame(), None);
+        }
+        assert_eq!(tracker.next_frame(), Some(Recreate::Suboptimal));
+    }
+
+    #[test]
+    fn cooldown_passes_while_optimal() {
+        let mut tracker = tracker(SuboptimalPolicy::Cooldown(3));
+        for _ in 0..5 {
+            assert_eq!(tracker.next_frame(), None);
+        }
+
+        tracker.report_suboptimal();
+        assert_eq!(tracker.next_frame(), Some(Recreate::Suboptimal));
+    }
+
+    #[test]
+    fn manual() {
+        let mut tracker = tracker(SuboptimalPolicy::Manual);
+        tracker.report_suboptimal();
+
+        for _ in 0..100 {
+            assert_eq!(tracker.next_frame(), None);
+        }
+        assert!(tracker.is_suboptimal());
+
+        tracker.request();
+        assert_eq!(tracker.next_frame(), Some(Recreate::Requested));
+    }
+
+    #[test]
+    fn request_ignores_cooldown() {
+        let mut tracker = tracker(SuboptimalPolicy::Cooldown(10));
+        tracker.request();
+        assert_eq!(tracker.next_frame(), Some(Recreate::Requested));
+    }
+
+    #[test]
+    fn shorter_policy_shortens_cooldown() {
+        let mut tracker = SuboptimalTracker::new();
+        tracker.report_suboptimal();
+        assert_eq!(tracker.next_frame(), None);
+
+        tracker.set_policy(SuboptimalPolicy::Cooldown(1));
+        assert_eq!(tracker.next_frame(), None);
+        assert_eq!(tracker.next_frame(), Some(Recreate::Suboptimal));
+
+        tracker.set_policy(SuboptimalPolicy::Immediate);
+        assert_eq!(tracker.next_frame(), Some(Recreate::Suboptimal));
+    }
+}
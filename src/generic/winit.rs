@@ -0,0 +1,97 @@
+//! Conversions between `winit` and `mev` types.
+
+use ::winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use super::{Extent2, Offset2};
+
+impl From<PhysicalSize<u32>> for Extent2<u32> {
+    #[inline(always)]
+    fn from(size: PhysicalSize<u32>) -> Self {
+        Extent2::new(size.width, size.height)
+    }
+}
+
+impl From<Extent2<u32>> for PhysicalSize<u32> {
+    #[inline(always)]
+    fn from(extent: Extent2<u32>) -> Self {
+        PhysicalSize::new(extent.width(), extent.height())
+    }
+}
+
+impl From<PhysicalSize<f32>> for Extent2<f32> {
+    #[inline(always)]
+    fn from(size: PhysicalSize<f32>) -> Self {
+        Extent2::new(size.width, size.height)
+    }
+}
+
+impl From<Extent2<f32>> for PhysicalSize<f32> {
+    #[inline(always)]
+    fn from(extent: Extent2<f32>) -> Self {
+        PhysicalSize::new(extent.width(), extent.height())
+    }
+}
+
+impl From<PhysicalPosition<i32>> for Offset2<i32> {
+    #[inline(always)]
+    fn from(position: PhysicalPosition<i32>) -> Self {
+        Offset2::new(position.x, position.y)
+    }
+}
+
+impl From<Offset2<i32>> for PhysicalPosition<i32> {
+    #[inline(always)]
+    fn from(offset: Offset2<i32>) -> Self {
+        PhysicalPosition::new(offset.x(), offset.y())
+    }
+}
+
+impl From<PhysicalPosition<f32>> for Offset2<f32> {
+    #[inline(always)]
+    fn from(position: PhysicalPosition<f32>) -> Self {
+        Offset2::new(position.x, position.y)
+    }
+}
+
+impl From<Offset2<f32>> for PhysicalPosition<f32> {
+    #[inline(always)]
+    fn from(offset: Offset2<f32>) -> Self {
+        PhysicalPosition::new(offset.x(), offset.y())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_size() {
+        let extent = Extent2::from(PhysicalSize::new(640u32, 480));
+        assert_eq!(extent, Extent2::new(640, 480));
+        assert_eq!(PhysicalSize::from(extent), PhysicalSize::new(640u32, 480));
+
+        let extent = Extent2::from(PhysicalSize::new(640.5f32, 480.25));
+        assert_eq!(extent, Extent2::new(640.5, 480.25));
+        assert_eq!(
+            PhysicalSize::from(extent),
+            PhysicalSize::new(640.5f32, 480.25)
+        );
+    }
+
+    #[test]
+    fn physical_position() {
+        let offset = Offset2::from(PhysicalPosition::new(-10i32, 20));
+        assert_eq!(offset, Offset2::new(-10, 20));
+        assert_eq!(
+            PhysicalPosition::from(offset),
+            PhysicalPosition::new(-10i32, 20)
+        );
+
+        let offset = Offset2::from(PhysicalPosition::new(-10.5f32, 20.75));
+        assert_eq!(offset, Offset2::new(-10.5, 20.75));
+        assert_eq!(
+            PhysicalPosition::from(offset),
+            PhysicalPosition::new(-10.5f32, 20.75)
+        );
+    }
+}
@@ -2,8 +2,22 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 #![allow(warnings)]
 
+#[cfg(all(
+    feature = "shader-spirv-passthrough",
+    not(feature = "shader-spirv"),
+    any(target_os = "macos", target_os = "ios")
+))]
+compile_error!(
+    "`shader-spirv-passthrough` is not available on Metal, \
+     SPIR-V is translated with naga, enable `shader-spirv` feature instead"
+);
+
 mod generic;
-mod traits;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod traits;
+pub mod transfer;
+pub mod upload;
 
 /// Macro that passes-through any tokens inside if chosen backend is Vulkan.
 /// Otherwise, it unwraps to nothing.
@@ -214,6 +228,9 @@ mod private {
 pub use self::{backend::*, generic::*};
 pub use mev_proc::{Arguments, DeviceRepr, match_backend};
 
+#[cfg(feature = "shader-interface")]
+pub use mev_proc::shader_interface;
+
 #[doc(hidden)]
 pub mod for_macro {
     pub use crate::backend::for_macro::*;
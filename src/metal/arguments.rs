@@ -1,6 +1,14 @@
-use crate::generic::{ArgumentGroupLayout, ArgumentKind, ArgumentsSealed};
+use std::sync::Arc;
 
-use super::{shader::Bindings, ComputeCommandEncoder, RenderCommandEncoder};
+use smallvec::SmallVec;
+
+use crate::generic::{
+    ArgumentGroupLayout, ArgumentKind, ArgumentsSealed, PipelineSummary, ShaderStages,
+};
+
+use super::{
+    shader::Bindings, ComputeCommandEncoder, Image, RenderCommandEncoder, MAX_BINDLESS_TABLE_SIZE,
+};
 
 pub trait Arguments: 'static {
     const LAYOUT: ArgumentGroupLayout<'static>;
@@ -27,6 +35,9 @@ where
     }
 }
 
+/// Slot of an argument not used by the stage.
+const UNUSED_SLOT: u8 = 0xff;
+
 fn non_zero_group_no_bindings() -> ! {
     panic!(
         "Attempt to bind non-zero group to a pipeline stage with shader compiled from Metal Shading Language.
@@ -34,70 +45,201 @@ fn non_zero_group_no_bindings() -> ! {
     );
 }
 
+/// Returns slot of the argument in the stage with given bindings.
+/// Returns `None` if the stage doesn't use the argument.
+#[inline(always)]
+fn argument_slot(group: u32, index: u32, bindings: Option<&Bindings>) -> Option<u32> {
+    match bindings {
+        Some(bindings) => {
+            let slot = bindings.groups[group as usize].bindings[index as usize];
+            (slot != UNUSED_SLOT).then_some(slot.into())
+        }
+        None if group == 0 => Some(index),
+        None => non_zero_group_no_bindings(),
+    }
+}
+
+/// Argument retained by encoder while it is bound.
 #[doc(hidden)]
-pub trait ArgumentsField<T>: 'static {
-    const KIND: ArgumentKind;
-    const SIZE: usize;
+pub enum RetainedArgument {
+    Buffer(metal::Buffer),
+    Texture(metal::Texture),
+    Sampler(metal::SamplerState),
 
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef);
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef);
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef);
+    /// Images of bindless table.
+    /// Shared with the table until it is modified.
+    Textures(Arc<Vec<Option<Image>>>),
+}
 
-    #[inline]
-    fn bind_vertex_argument(
-        &self,
-        group: u32,
-        index: u32,
-        bindings: Option<&Bindings>,
-        encoder: &metal::RenderCommandEncoderRef,
-    ) {
-        match bindings {
-            Some(bindings) => {
-                let slot = bindings.groups[group as usize].bindings[index as usize];
-                self.bind_vertex(slot.into(), encoder);
+impl RetainedArgument {
+    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
+        match self {
+            RetainedArgument::Buffer(buffer) => {
+                encoder.set_vertex_buffer(slot.into(), Some(buffer), 0)
+            }
+            RetainedArgument::Texture(texture) => {
+                encoder.set_vertex_texture(slot.into(), Some(texture))
+            }
+            RetainedArgument::Sampler(sampler) => {
+                encoder.set_vertex_sampler_state(slot.into(), Some(sampler))
+            }
+            RetainedArgument::Textures(images) => {
+                encoder.set_vertex_textures(slot.into(), &textures(images))
             }
-            None if group == 0 => self.bind_vertex(index, encoder),
-            None => non_zero_group_no_bindings(),
         }
     }
 
-    #[inline]
-    fn bind_fragment_argument(
-        &self,
-        group: u32,
-        index: u32,
-        bindings: Option<&Bindings>,
-        encoder: &metal::RenderCommandEncoderRef,
-    ) {
-        match bindings {
-            Some(bindings) => {
-                let slot = bindings.groups[group as usize].bindings[index as usize];
-                self.bind_fragment(slot.into(), encoder);
+    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
+        match self {
+            RetainedArgument::Buffer(buffer) => {
+                encoder.set_fragment_buffer(slot.into(), Some(buffer), 0)
+            }
+            RetainedArgument::Texture(texture) => {
+                encoder.set_fragment_texture(slot.into(), Some(texture))
+            }
+            RetainedArgument::Sampler(sampler) => {
+                encoder.set_fragment_sampler_state(slot.into(), Some(sampler))
+            }
+            RetainedArgument::Textures(images) => {
+                encoder.set_fragment_textures(slot.into(), &textures(images))
+            }
+        }
+    }
+
+    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
+        match self {
+            RetainedArgument::Buffer(buffer) => encoder.set_buffer(slot.into(), Some(buffer), 0),
+            RetainedArgument::Texture(texture) => encoder.set_texture(slot.into(), Some(texture)),
+            RetainedArgument::Sampler(sampler) => {
+                encoder.set_sampler_state(slot.into(), Some(sampler))
+            }
+            RetainedArgument::Textures(images) => {
+                encoder.set_textures(slot.into(), &textures(images))
             }
-            None if group == 0 => self.bind_fragment(index, encoder),
-            None => non_zero_group_no_bindings(),
         }
     }
+}
+
+fn textures(
+    images: &[Option<Image>],
+) -> SmallVec<[Option<&metal::TextureRef>; MAX_BINDLESS_TABLE_SIZE as usize]> {
+    images
+        .iter()
+        .map(|image| image.as_ref().map(Image::metal))
+        .collect()
+}
+
+/// Arguments of a group with stages they are visible to.
+pub(super) type GroupArguments = Vec<(ShaderStages, RetainedArgument)>;
+
+/// Argument groups bound in a pass.
+///
+/// Slots are assigned per pipeline, so bound arguments are retained
+/// to re-apply them when new pipeline moves a group to different slots.
+/// Kept by command encoder to reuse storage across passes.
+#[derive(Default)]
+pub(super) struct BoundGroups {
+    groups: Vec<GroupArguments>,
+}
 
-    #[inline]
-    fn bind_compute_argument(
-        &self,
+impl BoundGroups {
+    /// Replaces arguments of the group and returns them.
+    pub fn set(
+        &mut self,
         group: u32,
-        index: u32,
-        bindings: Option<&Bindings>,
-        encoder: &metal::ComputeCommandEncoderRef,
-    ) {
-        match bindings {
-            Some(bindings) => {
-                let slot = bindings.groups[group as usize].bindings[index as usize];
-                self.bind_compute(slot.into(), encoder);
+        arguments: impl IntoIterator<Item = (ShaderStages, RetainedArgument)>,
+    ) -> &GroupArguments {
+        let group = group as usize;
+        if self.groups.len() <= group {
+            self.groups.resize_with(group + 1, Vec::new);
+        }
+
+        let retained = &mut self.groups[group];
+        retained.clear();
+        retained.extend(arguments);
+        retained
+    }
+
+    /// Unbinds groups starting from `first`.
+    pub fn unbind_from(&mut self, first: usize) {
+        for retained in self.groups.iter_mut().skip(first) {
+            retained.clear();
+        }
+    }
+
+    /// Returns bound groups.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &GroupArguments)> {
+        self.groups
+            .iter()
+            .enumerate()
+            .filter(|(_, retained)| !retained.is_empty())
+            .map(|(group, retained)| (group as u32, retained))
+    }
+}
+
+/// Returns number of leading argument groups that stay bound
+/// when pipeline changes from `old` to `new`.
+///
+/// Constants must match for any group to stay bound.
+pub(super) fn compatible_groups(old: &PipelineSummary, new: &PipelineSummary) -> usize {
+    if old.constants() != new.constants() {
+        return 0;
+    }
+
+    old.arguments()
+        .iter()
+        .zip(new.arguments())
+        .take_while(|(old, new)| old == new)
+        .count()
+}
+
+/// Binds arguments of the group to slots of vertex and fragment stages.
+pub(super) fn bind_render_group(
+    group: u32,
+    arguments: &GroupArguments,
+    vertex_bindings: Option<&Bindings>,
+    fragment_bindings: Option<&Bindings>,
+    encoder: &metal::RenderCommandEncoderRef,
+) {
+    for (index, (stages, argument)) in arguments.iter().enumerate() {
+        if stages.contains(ShaderStages::VERTEX) {
+            if let Some(slot) = argument_slot(group, index as u32, vertex_bindings) {
+                argument.bind_vertex(slot, encoder);
+            }
+        }
+        if stages.contains(ShaderStages::FRAGMENT) {
+            if let Some(slot) = argument_slot(group, index as u32, fragment_bindings) {
+                argument.bind_fragment(slot, encoder);
+            }
+        }
+    }
+}
+
+/// Binds arguments of the group to slots of compute stage.
+pub(super) fn bind_compute_group(
+    group: u32,
+    arguments: &GroupArguments,
+    bindings: Option<&Bindings>,
+    encoder: &metal::ComputeCommandEncoderRef,
+) {
+    for (index, (stages, argument)) in arguments.iter().enumerate() {
+        if stages.contains(ShaderStages::COMPUTE) {
+            if let Some(slot) = argument_slot(group, index as u32, bindings) {
+                argument.bind_compute(slot, encoder);
             }
-            None if group == 0 => self.bind_compute(index, encoder),
-            None => non_zero_group_no_bindings(),
         }
     }
 }
 
+#[doc(hidden)]
+pub trait ArgumentsField<T>: 'static {
+    const KIND: ArgumentKind;
+    const SIZE: usize;
+
+    /// Returns argument to bind and retain in the encoder.
+    fn retain(&self) -> RetainedArgument;
+}
+
 impl<T, F> crate::generic::ArgumentsField<T> for F
 where
     T: ArgumentsSealed,
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::generic::{ArgumentKind, Automatic, ImageUsage};
+
+use super::{
+    arguments::{ArgumentsField, RetainedArgument},
+    Image,
+};
+
+/// Table of sampled images bound to consecutive texture slots.
+///
+/// Encoders retain textures when they are bound,
+/// so replaced images need no tracking.
+/// Changes of the table don't affect commands recorded before.
+/// Bound table shares the list of images with encoder
+/// and copies it only when modified while shared.
+pub struct BindlessTable {
+    images: Arc<Vec<Option<Image>>>,
+}
+
+impl BindlessTable {
+    pub(super) fn new(capacity: u32) -> Self {
+        BindlessTable {
+            images: Arc::new(vec![None; capacity as usize]),
+        }
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::BindlessTable for BindlessTable {
+    type Image = Image;
+
+    #[inline(always)]
+    fn capacity(&self) -> u32 {
+        self.images.len() as u32
+    }
+
+    fn set(&mut self, index: u32, image: &Image) {
+        assert!(
+            index < self.capacity(),
+            "Index {index} is out of bounds of bindless table with capacity {}",
+            self.capacity()
+        );
+        debug_assert!(
+            crate::traits::Image::usage(image).contains(ImageUsage::SAMPLED),
+            "Image in bindless table must have SAMPLED usage"
+        );
+
+        Arc::make_mut(&mut self.images)[index as usize] = Some(image.clone());
+    }
+
+    fn clear(&mut self, index: u32) {
+        assert!(
+            index < self.capacity(),
+            "Index {index} is out of bounds of bindless table with capacity {}",
+            self.capacity()
+        );
+
+        Arc::make_mut(&mut self.images)[index as usize] = None;
+    }
+
+    #[inline(always)]
+    fn get(&self, index: u32) -> Option<&Image> {
+        self.images.get(index as usize)?.as_ref()
+    }
+}
+
+impl ArgumentsField<Automatic> for BindlessTable {
+    const KIND: ArgumentKind = ArgumentKind::BindlessTable;
+    const SIZE: usize = 0;
+
+    #[inline(always)]
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Textures(self.images.clone())
+    }
+}
@@ -1,23 +1,30 @@
 use std::{
     fmt,
     hash::{Hash, Hasher},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
 };
 
 use foreign_types::ForeignType;
 
-use crate::generic::{ArgumentKind, Automatic, Storage, Uniform};
+use crate::generic::{
+    ArgumentKind, Automatic, BufferIndex, BufferSummary, MapError, ResourceId, Storage, Uniform,
+};
 
-use super::{arguments::ArgumentsField, out_of_bounds};
+use super::{
+    arguments::{ArgumentsField, RetainedArgument},
+    out_of_bounds,
+};
 
 #[derive(Clone)]
-#[repr(transparent)]
 pub struct Buffer {
     buffer: metal::Buffer,
+    id: ResourceId,
 }
 
 impl Buffer {
-    pub(super) fn new(buffer: metal::Buffer) -> Self {
-        Buffer { buffer }
+    pub(super) fn new(buffer: metal::Buffer, id: ResourceId) -> Self {
+        Buffer { buffer, id }
     }
 
     pub(super) fn metal(&self) -> &metal::BufferRef {
@@ -25,8 +32,66 @@ impl Buffer {
     }
 }
 
+#[hidden_trait::expose]
+impl crate::traits::Resource for Buffer {
+    #[inline(always)]
+    fn id(&self) -> ResourceId {
+        self.id
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{:#?}",
+            BufferSummary {
+                id: self.id,
+                name: None,
+                size: self.buffer.length(),
+                usage: None,
+            }
+        )
+    }
+}
+
 unsafe impl Send for Buffer {}
 
+/// Range of buffer memory mapped for writing from host.
+///
+/// Created with [`Buffer::mapped_mut`].
+/// Notifies Metal about modified range of managed buffers when dropped.
+pub struct MappedSlice<'a> {
+    buffer: &'a mut Buffer,
+    ptr: NonNull<u8>,
+    offset: u64,
+    len: usize,
+}
+
+impl Deref for MappedSlice<'_> {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for MappedSlice<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for MappedSlice<'_> {
+    fn drop(&mut self) {
+        if self.len > 0 && self.buffer.buffer.storage_mode() == metal::MTLStorageMode::Managed {
+            self.buffer.buffer.did_modify_range(metal::NSRange {
+                location: self.offset,
+                length: self.len as _,
+            });
+        }
+    }
+}
+
 impl fmt::Debug for Buffer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Buffer")
@@ -51,9 +116,11 @@ impl Eq for Buffer {}
 
 #[hidden_trait::expose]
 impl crate::traits::Buffer for Buffer {
+    type MappedSlice<'a> = MappedSlice<'a>;
+
     #[inline(always)]
-    fn size(&self) -> usize {
-        self.buffer.length() as usize
+    fn size(&self) -> u64 {
+        self.buffer.length()
     }
 
     #[inline(always)]
@@ -67,14 +134,11 @@ impl crate::traits::Buffer for Buffer {
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
-    unsafe fn write_unchecked(&mut self, offset: usize, data: &[u8]) {
+    unsafe fn write_unchecked(&mut self, offset: u64, data: &[u8]) {
         let length = self.buffer.length();
-        let fits = match (u64::try_from(offset), u64::try_from(data.len())) {
-            (Ok(off), Ok(len)) => match off.checked_add(len) {
-                Some(end) => end <= length,
-                None => false,
-            },
-            _ => false,
+        let fits = match offset.checked_add(data.len() as u64) {
+            Some(end) => end <= length,
+            None => false,
         };
         if !fits {
             out_of_bounds();
@@ -84,50 +148,75 @@ impl crate::traits::Buffer for Buffer {
             ptr.cast::<u8>()
                 .copy_from_nonoverlapping(data.as_ptr(), data.len());
             self.buffer.did_modify_range(metal::NSRange {
-                location: offset as _,
+                location: offset,
                 length: data.len() as _,
             })
         }
     }
-}
-
-impl ArgumentsField<Automatic> for Buffer {
-    const KIND: ArgumentKind = ArgumentKind::UniformBuffer;
-    const SIZE: usize = 1;
 
-    #[inline(always)]
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_vertex_buffer(slot.into(), Some(&self.buffer), 0)
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    unsafe fn read_unchecked(&mut self, offset: u64, data: &mut [u8]) {
+        let length = self.buffer.length();
+        let fits = match offset.checked_add(data.len() as u64) {
+            Some(end) => end <= length,
+            None => false,
+        };
+        if !fits {
+            out_of_bounds();
+        }
+        unsafe {
+            let ptr = self.buffer.contents().add(offset as usize);
+            ptr.cast::<u8>()
+                .copy_to_nonoverlapping(data.as_mut_ptr(), data.len());
+        }
     }
 
-    #[inline(always)]
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_fragment_buffer(slot.into(), Some(&self.buffer), 0)
-    }
+    fn mapped_mut(&mut self, range: impl BufferIndex) -> Result<MappedSlice<'_>, MapError> {
+        if matches!(
+            self.buffer.storage_mode(),
+            metal::MTLStorageMode::Private | metal::MTLStorageMode::Memoryless
+        ) {
+            return Err(MapError::NotHostVisible);
+        }
 
-    #[inline(always)]
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
-        encoder.set_buffer(slot.into(), Some(&self.buffer), 0)
+        if !self.detached() {
+            return Err(MapError::Shared);
+        }
+
+        let range = range.range(self.buffer.length());
+        let offset = range.start;
+        let len = usize::try_from(range.end - range.start).map_err(|_| MapError::OutOfMemory)?;
+
+        let contents = self.buffer.contents().cast::<u8>();
+        let ptr = NonNull::new(contents).ok_or(MapError::NotHostVisible)?;
+        let ptr = unsafe { ptr.add(offset as usize) };
+
+        Ok(MappedSlice {
+            buffer: self,
+            ptr,
+            offset,
+            len,
+        })
     }
 }
 
-impl ArgumentsField<Uniform> for Buffer {
+impl ArgumentsField<Automatic> for Buffer {
     const KIND: ArgumentKind = ArgumentKind::UniformBuffer;
     const SIZE: usize = 1;
 
     #[inline(always)]
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_vertex_buffer(slot.into(), Some(&self.buffer), 0)
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Buffer(self.buffer.clone())
     }
+}
 
-    #[inline(always)]
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_fragment_buffer(slot.into(), Some(&self.buffer), 0)
-    }
+impl ArgumentsField<Uniform> for Buffer {
+    const KIND: ArgumentKind = ArgumentKind::UniformBuffer;
+    const SIZE: usize = 1;
 
     #[inline(always)]
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
-        encoder.set_buffer(slot.into(), Some(&self.buffer), 0)
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Buffer(self.buffer.clone())
     }
 }
 
@@ -136,17 +225,7 @@ impl ArgumentsField<Storage> for Buffer {
     const SIZE: usize = 1;
 
     #[inline(always)]
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_vertex_buffer(slot.into(), Some(&self.buffer), 0)
-    }
-
-    #[inline(always)]
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_fragment_buffer(slot.into(), Some(&self.buffer), 0)
-    }
-
-    #[inline(always)]
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
-        encoder.set_buffer(slot.into(), Some(&self.buffer), 0)
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Buffer(self.buffer.clone())
     }
 }
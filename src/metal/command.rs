@@ -1,29 +1,87 @@
-use std::{marker::PhantomData, ops::Range, sync::Arc};
+use std::{
+    marker::PhantomData,
+    ops::Range,
+    sync::{Arc, Weak},
+};
 
 use metal::{NSRange, NSUInteger};
 use objc::{msg_send, Message};
+use parking_lot::Mutex;
 use smallvec::SmallVec;
 
+#[cfg(any(debug_assertions, feature = "debug"))]
+use crate::generic::PixelFormat;
 use crate::{
     generic::{
-        AccelerationStructureBuildFlags, AccelerationStructurePerformance, Arguments,
-        AsBufferSlice, BlasBuildDesc, BlasGeometryDesc, ClearColor, ClearDepthStencil, DeviceRepr,
-        Extent2, Extent3, LoadOp, Offset2, Offset3, OutOfMemory, PipelineStages, RenderPassDesc,
-        StoreOp, TlasBuildDesc,
+        check_image_range, check_self_copy, AccelerationStructureBuildFlags,
+        AccelerationStructurePerformance, Arguments, AsBufferSlice, BlasBuildDesc,
+        BlasGeometryDesc, ClearColor, ClearDepthStencil, DeviceRepr, Extent2, Extent3, LoadOp,
+        Offset2, Offset3, OutOfMemory, PipelineStages, PipelineSummary, QueueFlags, Rect2,
+        RenderPassDesc, RenderPassFlags, ShaderStages, StoreOp, TlasBuildDesc, WellKnownSampler,
     },
     traits,
 };
 
 use super::{
-    from::TryIntoMetal, out_of_bounds, shader::Bindings, Blas, Buffer, Frame, Image,
-    RenderPipeline, Tlas,
+    arguments::{
+        bind_compute_group, bind_render_group, compatible_groups, BoundGroups, RetainedArgument,
+    },
+    from::TryIntoMetal,
+    out_of_bounds,
+    sampler::WellKnownSamplers,
+    shader::{group_slots_changed, Bindings},
+    Blas, Buffer, ComputePipeline, Frame, Image, RenderPipeline, Sampler, Tlas,
 };
 
+/// Parallel render encoder of a render pass split across command buffers.
+///
+/// Each part of the pass records into its own render encoder created from it.
+/// Encoding ends when the last part is finished.
+pub(super) struct SplitPass {
+    encoder: metal::ParallelRenderCommandEncoder,
+}
+
+impl Drop for SplitPass {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.encoder.end_encoding();
+    }
+}
+
+/// Render pass suspended by one command encoder of the queue
+/// to be resumed by the next one.
+pub(super) type SuspendedPass = Arc<Mutex<Option<Arc<SplitPass>>>>;
+
 pub struct CommandBuffer {
     buffer: metal::CommandBuffer,
+
+    /// Split render pass that began in this command buffer.
+    split_pass: Option<Weak<SplitPass>>,
 }
 
 impl CommandBuffer {
+    /// Checks that render pass split from this command buffer is finished.
+    ///
+    /// Command buffer can't be committed while parallel encoder is not ended.
+    pub(super) fn check_split_pass(&self) {
+        if let Some(split_pass) = &self.split_pass {
+            assert!(
+                split_pass.strong_count() == 0,
+                "Command buffer can't be submitted before all parts of render pass it suspends are finished"
+            );
+        }
+    }
+
+    /// Labels the command buffer in Metal tools and error reports.
+    pub(super) fn set_label(&self, label: &str) {
+        self.buffer.set_label(label);
+    }
+
+    /// Encodes signal of the event with given value after the command buffer completes.
+    pub(super) fn signal(&self, event: &metal::EventRef, value: u64) {
+        self.buffer.encode_signal_event(event, value);
+    }
+
     pub(super) fn commit(self) -> metal::CommandBuffer {
         self.buffer.commit();
         self.buffer
@@ -33,25 +91,125 @@ impl CommandBuffer {
 pub struct CommandEncoder {
     device: metal::Device,
     buffer: metal::CommandBuffer,
+    well_known_samplers: Arc<WellKnownSamplers>,
+
+    /// Shared by all encoders of the queue.
+    suspended: SuspendedPass,
+
+    /// See [`CommandBuffer::split_pass`].
+    split_pass: Option<Weak<SplitPass>>,
+
+    /// Argument groups bound in the current pass.
+    bound_groups: BoundGroups,
 }
 
 impl CommandEncoder {
-    pub(super) fn new(device: metal::Device, buffer: metal::CommandBuffer) -> Self {
-        CommandEncoder { device, buffer }
+    pub(super) fn new(
+        device: metal::Device,
+        buffer: metal::CommandBuffer,
+        well_known_samplers: Arc<WellKnownSamplers>,
+        suspended: SuspendedPass,
+    ) -> Self {
+        CommandEncoder {
+            device,
+            buffer,
+            well_known_samplers,
+            suspended,
+            split_pass: None,
+            bound_groups: BoundGroups::default(),
+        }
+    }
+}
+
+/// Reusable command buffers are not supported by Metal backend yet.
+/// This type can't be constructed.
+#[derive(Clone)]
+pub enum ReusableCommandBuffer {}
+
+#[hidden_trait::expose]
+impl crate::traits::ReusableCommandBuffer for ReusableCommandBuffer {
+    fn references_swapchain(&self) -> bool {
+        match *self {}
+    }
+}
+
+/// Reusable command buffers are not supported by Metal backend yet.
+/// This type can't be constructed.
+pub struct ReusableCommandEncoder {
+    never: std::convert::Infallible,
+}
+
+impl std::ops::Deref for ReusableCommandEncoder {
+    type Target = CommandEncoder;
+
+    fn deref(&self) -> &CommandEncoder {
+        match self.never {}
+    }
+}
+
+impl std::ops::DerefMut for ReusableCommandEncoder {
+    fn deref_mut(&mut self) -> &mut CommandEncoder {
+        match self.never {}
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::ReusableCommandEncoder for ReusableCommandEncoder {
+    type CommandEncoder = CommandEncoder;
+    type ReusableCommandBuffer = ReusableCommandBuffer;
+
+    fn finish(self) -> Result<ReusableCommandBuffer, OutOfMemory> {
+        match self.never {}
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::SyncCommandEncoder for CommandEncoder {
+    type Image = Image;
+
     #[inline(always)]
     fn barrier(&mut self, _after: PipelineStages, _before: PipelineStages) {}
 
     #[inline(always)]
     fn init_image(&mut self, _after: PipelineStages, _before: PipelineStages, _image: &Image) {}
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image(&mut self, _after: PipelineStages, _before: PipelineStages, _image: &Image) {}
+
+    #[inline(always)]
+    fn init_image_range(
+        &mut self,
+        _after: PipelineStages,
+        _before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        sync_image_range(image, levels, layers);
+    }
+
+    #[inline(always)]
+    fn sync_image_range(
+        &mut self,
+        _after: PipelineStages,
+        _before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        sync_image_range(image, levels, layers);
+    }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::CommandEncoder for CommandEncoder {
+    type Frame = Frame;
+    type CommandBuffer = CommandBuffer;
+    type CopyCommandEncoder<'a> = CopyCommandEncoder<'a>;
+    type AccelerationStructureCommandEncoder<'a> = AccelerationStructureCommandEncoder<'a>;
+    type ComputeCommandEncoder<'a> = ComputeCommandEncoder<'a>;
+    type RenderCommandEncoder<'a> = RenderCommandEncoder<'a>;
+
     #[inline(always)]
     fn copy(&mut self) -> CopyCommandEncoder {
         let encoder = self.buffer.new_blit_command_encoder();
@@ -67,19 +225,38 @@ impl crate::traits::CommandEncoder for CommandEncoder {
         let encoder = self.buffer.new_compute_command_encoder();
         ComputeCommandEncoder {
             device: &mut self.device,
+            well_known_samplers: &self.well_known_samplers,
             encoder: encoder.to_owned(),
+            pipeline: None,
             bindings: None,
+            bound_groups: &mut self.bound_groups,
             workgroup_size: None,
             _marker: PhantomData,
         }
     }
 
     fn render(&mut self, desc: RenderPassDesc) -> RenderCommandEncoder<'_> {
+        if let Err(err) = desc.validate() {
+            panic!("Invalid render pass \"{}\": {err}", desc.name);
+        }
+
+        // Metal can't continue render pass in another command buffer.
+        // Split pass is encoded with parallel render encoder in the command buffer of the first part.
+        // Other parts record into render encoders created from it, so they execute in order
+        // their passes begin, not in order of submission.
+        // Store actions are known only when the last part begins.
+        let resuming = desc.flags.contains(RenderPassFlags::RESUMING);
+        let suspending = desc.flags.contains(RenderPassFlags::SUSPENDING);
+        let store_action = |store| match store {
+            _ if suspending && !resuming => metal::MTLStoreAction::Unknown,
+            StoreOp::Store => metal::MTLStoreAction::Store,
+            StoreOp::DontCare => metal::MTLStoreAction::DontCare,
+        };
+
         let mdesc = metal::RenderPassDescriptor::new();
         let color_attachments = mdesc.color_attachments();
         for (idx, color) in desc.color_attachments.iter().enumerate() {
             let format = color.image.format();
-            debug_assert!(format.is_color());
 
             let attachment = metal::RenderPassColorAttachmentDescriptor::new();
             attachment.set_texture(Some(color.image.metal()));
@@ -96,10 +273,7 @@ impl crate::traits::CommandEncoder for CommandEncoder {
                 }
                 LoadOp::DontCare => metal::MTLLoadAction::DontCare,
             });
-            attachment.set_store_action(match color.store {
-                StoreOp::Store => metal::MTLStoreAction::Store,
-                StoreOp::DontCare => metal::MTLStoreAction::DontCare,
-            });
+            attachment.set_store_action(store_action(color.store));
             attachment.set_level(0);
             attachment.set_slice(0);
             color_attachments.set_object_at(idx as _, Some(&attachment));
@@ -107,7 +281,6 @@ impl crate::traits::CommandEncoder for CommandEncoder {
 
         if let Some(depth) = desc.depth_stencil_attachment {
             let format = depth.image.format();
-            debug_assert!(format.is_depth() || format.is_stencil());
 
             if format.is_depth() {
                 let attachment = mdesc.depth_attachment().unwrap();
@@ -120,10 +293,7 @@ impl crate::traits::CommandEncoder for CommandEncoder {
                     }
                     LoadOp::DontCare => metal::MTLLoadAction::DontCare,
                 });
-                attachment.set_store_action(match depth.store {
-                    StoreOp::Store => metal::MTLStoreAction::Store,
-                    StoreOp::DontCare => metal::MTLStoreAction::DontCare,
-                });
+                attachment.set_store_action(store_action(depth.store));
                 attachment.set_level(0);
                 attachment.set_slice(0);
             }
@@ -138,24 +308,103 @@ impl crate::traits::CommandEncoder for CommandEncoder {
                     }
                     LoadOp::DontCare => metal::MTLLoadAction::DontCare,
                 });
-                attachment.set_store_action(match depth.store {
-                    StoreOp::Store => metal::MTLStoreAction::Store,
-                    StoreOp::DontCare => metal::MTLStoreAction::DontCare,
-                });
+                attachment.set_store_action(store_action(depth.store));
                 attachment.set_level(0);
                 attachment.set_slice(0);
             }
         }
 
-        let encoder = self.buffer.new_render_command_encoder(&mdesc);
+        // Metal starts with viewport covering the smallest attachment.
+        let mut extent = Extent2::new(u32::MAX, u32::MAX);
+        for color in desc.color_attachments.iter() {
+            let color_extent = color.image.extent().expect_2d();
+            extent = Extent2::new(
+                extent.width().min(color_extent.width()),
+                extent.height().min(color_extent.height()),
+            );
+        }
+        if let Some(depth) = desc.depth_stencil_attachment {
+            let depth_extent = depth.image.extent().expect_2d();
+            extent = Extent2::new(
+                extent.width().min(depth_extent.width()),
+                extent.height().min(depth_extent.height()),
+            );
+        }
+
+        // Layered rendering selects layer with `@builtin(layer)` output.
+        let layers = desc.layers();
+        if layers > 1 {
+            mdesc.set_render_target_array_length(layers as _);
+        }
+
+        let (encoder, split_pass) = if resuming {
+            let Some(split_pass) = self.suspended.lock().take() else {
+                panic!(
+                    "Render pass \"{}\" resumes pass that was not suspended",
+                    desc.name
+                );
+            };
+
+            if !suspending {
+                set_split_pass_store_actions(&split_pass.encoder, &desc);
+            }
+
+            let encoder = split_pass.encoder.render_command_encoder().to_owned();
+            (encoder, Some(split_pass))
+        } else if suspending {
+            let parallel = self.buffer.new_parallel_render_command_encoder(&mdesc);
+            let split_pass = Arc::new(SplitPass {
+                encoder: parallel.to_owned(),
+            });
+            self.split_pass = Some(Arc::downgrade(&split_pass));
+
+            let encoder = split_pass.encoder.render_command_encoder().to_owned();
+            (encoder, Some(split_pass))
+        } else {
+            let encoder = self.buffer.new_render_command_encoder(&mdesc);
+            (encoder.to_owned(), None)
+        };
+
+        if suspending {
+            let mut suspended = self.suspended.lock();
+            assert!(
+                suspended.is_none(),
+                "Render pass \"{}\" is suspended before previously suspended pass is resumed",
+                desc.name
+            );
+            *suspended = split_pass.clone();
+        }
+
         RenderCommandEncoder {
-            encoder: encoder.to_owned(),
+            well_known_samplers: &self.well_known_samplers,
+            encoder,
+            split_pass,
+            viewport: metal::MTLViewport {
+                originX: 0.0,
+                originY: 0.0,
+                width: extent.width().into(),
+                height: extent.height().into(),
+                znear: 0.0,
+                zfar: 1.0,
+            },
             primitive: metal::MTLPrimitiveType::Triangle,
             index_buffer: None,
             index_buffer_offset: 0,
+            pipeline: None,
             vertex_bindings: None,
             fragment_bindings: None,
+            bound_groups: &mut self.bound_groups,
             vertex_buffers_count: 0,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            color_formats: desc
+                .color_attachments
+                .iter()
+                .map(|color| color.image.format())
+                .collect(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            depth_format: desc
+                .depth_stencil_attachment
+                .map(|depth| depth.image.format()),
             _marker: PhantomData,
         }
     }
@@ -175,10 +424,28 @@ impl crate::traits::CommandEncoder for CommandEncoder {
         self.buffer.present_drawable(frame.drawable());
     }
 
+    #[inline(always)]
+    fn present_with_damage(&mut self, frame: Frame, after: PipelineStages, _damage: &[Rect2]) {
+        // Core Animation has no damage hints for drawables.
+        self.present(frame, after);
+    }
+
+    #[inline(always)]
+    fn capabilities(&self) -> QueueFlags {
+        // All Metal queues support all commands.
+        QueueFlags::GRAPHICS | QueueFlags::COMPUTE | QueueFlags::TRANSFER
+    }
+
+    #[inline(always)]
+    fn checkpoint(&mut self, _marker: u32) {
+        // Breadcrumbs are not supported.
+    }
+
     #[inline(always)]
     fn finish(self) -> Result<CommandBuffer, OutOfMemory> {
         Ok(CommandBuffer {
             buffer: self.buffer,
+            split_pass: self.split_pass,
         })
     }
 }
@@ -198,28 +465,120 @@ impl Drop for CopyCommandEncoder<'_> {
 
 #[hidden_trait::expose]
 impl crate::traits::SyncCommandEncoder for CopyCommandEncoder<'_> {
+    type Image = Image;
+
     #[inline(always)]
     fn barrier(&mut self, _after: PipelineStages, _before: PipelineStages) {}
 
     #[inline(always)]
     fn init_image(&mut self, _after: PipelineStages, _before: PipelineStages, _image: &Image) {}
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image(&mut self, _after: PipelineStages, _before: PipelineStages, _image: &Image) {}
+
+    #[inline(always)]
+    fn init_image_range(
+        &mut self,
+        _after: PipelineStages,
+        _before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        sync_image_range(image, levels, layers);
+    }
+
+    #[inline(always)]
+    fn sync_image_range(
+        &mut self,
+        _after: PipelineStages,
+        _before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        sync_image_range(image, levels, layers);
+    }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
+    type Buffer = Buffer;
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn copy_image_to_buffer(
+        &mut self,
+        src: &Image,
+        offset: Offset3<u32>,
+        extent: Extent3<u32>,
+        layers: Range<u32>,
+        level: u32,
+        dst: &Buffer,
+        start: u64,
+        bytes_per_line: u64,
+        bytes_per_plane: u64,
+    ) {
+        debug_assert!(
+            extent.width() > 0 && extent.height() > 0 && extent.depth() > 0,
+            "copy_image_to_buffer: extent {extent:?} is empty"
+        );
+        debug_assert!(layers.end > layers.start);
+        debug_assert!(layers.end == layers.start + 1);
+
+        // Zero pitch means tightly packed data.
+        let bytes_per_line = match bytes_per_line {
+            0 => u64::from(extent.width()) * src.format().size() as u64,
+            _ => bytes_per_line,
+        };
+        let bytes_per_plane = match bytes_per_plane {
+            0 if extent.depth() > 1 => bytes_per_line * u64::from(extent.height()),
+            _ => bytes_per_plane,
+        };
+
+        self.encoder.copy_from_texture_to_buffer(
+            src.metal(),
+            layers.start as NSUInteger,
+            level as NSUInteger,
+            metal::MTLOrigin {
+                x: offset.x() as NSUInteger,
+                y: offset.y() as NSUInteger,
+                z: offset.z() as NSUInteger,
+            },
+            metal::MTLSize {
+                width: extent.width() as NSUInteger,
+                height: extent.height() as NSUInteger,
+                depth: extent.depth() as NSUInteger,
+            },
+            dst.metal(),
+            start as NSUInteger,
+            bytes_per_line as NSUInteger,
+            bytes_per_plane as NSUInteger,
+            metal::MTLBlitOption::empty(),
+        );
+
+        // Make managed memory content visible to the host.
+        if dst.metal().storage_mode() == metal::MTLStorageMode::Managed {
+            self.encoder.synchronize_resource(dst.metal());
+        }
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn copy_buffer_to_image(
         &mut self,
         src: &Buffer,
-        start: usize,
-        bytes_per_line: usize,
-        bytes_per_plane: usize,
+        start: u64,
+        bytes_per_line: u64,
+        bytes_per_plane: u64,
         dst: &Image,
         offset: Offset3<u32>,
         extent: Extent3<u32>,
         layers: Range<u32>,
         level: u32,
     ) {
+        debug_assert!(
+            extent.width() > 0 && extent.height() > 0 && extent.depth() > 0,
+            "copy_buffer_to_image: extent {extent:?} is empty"
+        );
         debug_assert!(layers.end > layers.start);
         debug_assert!(layers.end == layers.start + 1);
 
@@ -245,6 +604,30 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         );
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn copy_image(&mut self, src: &Image, dst: &Image) {
+        let src_extent = src.extent().into_3d();
+        let dst_extent = dst.extent().into_3d();
+        let extent = Extent3::new(
+            src_extent.width().min(dst_extent.width()),
+            src_extent.height().min(dst_extent.height()),
+            src_extent.depth().min(dst_extent.depth()),
+        );
+
+        self.copy_image_region(
+            src,
+            0,
+            0,
+            Offset3::ZERO,
+            dst,
+            0,
+            0,
+            Offset3::ZERO,
+            extent,
+            1,
+        );
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn copy_image_region(
         &mut self,
@@ -259,8 +642,31 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         extent: Extent3<u32>,
         layers: u32,
     ) {
+        debug_assert!(
+            extent.width() > 0 && extent.height() > 0 && extent.depth() > 0 && layers > 0,
+            "copy_image_region: extent {extent:?} with {layers} layers is empty"
+        );
         use objc::{sel, sel_impl};
 
+        if src.image_id() == dst.image_id() {
+            // Views share texture with the image, translate levels and layers to it.
+            let src_level = src.metal().parent_relative_level() as u32 + src_level;
+            let dst_level = dst.metal().parent_relative_level() as u32 + dst_level;
+            let src_base_layer = src.metal().parent_relative_slice() as u32 + src_base_layer;
+            let dst_base_layer = dst.metal().parent_relative_slice() as u32 + dst_base_layer;
+            check_self_copy(
+                src_level,
+                src_base_layer..src_base_layer + layers,
+                src_offset,
+                dst_level,
+                dst_base_layer..dst_base_layer + layers,
+                dst_offset,
+                extent,
+            );
+
+            // Successive copies within the texture are ordered by Metal's hazard tracking.
+        }
+
         // If copying entire slices, use optimized method
         if src_offset == Offset3::ZERO
             && dst_offset == Offset3::ZERO
@@ -311,9 +717,31 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         }
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn copy_buffer(&mut self, src: impl AsBufferSlice, dst: impl AsBufferSlice) {
+        let src = src.as_buffer_slice();
+        let dst = dst.as_buffer_slice();
+
+        let size = src.size.min(dst.size);
+        if size == 0 {
+            return;
+        }
+
+        self.encoder.copy_from_buffer(
+            src.buffer.metal(),
+            src.offset as NSUInteger,
+            dst.buffer.metal(),
+            dst.offset as NSUInteger,
+            size as NSUInteger,
+        );
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn fill_buffer(&mut self, slice: impl AsBufferSlice, byte: u8) {
         let slice = slice.as_buffer_slice();
+        if slice.size == 0 {
+            return;
+        }
 
         self.encoder.fill_buffer(
             slice.buffer.metal(),
@@ -332,9 +760,14 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         }
 
         let slice = slice.as_buffer_slice();
-        if data.len() > slice.size {
+        if data.len() as u64 > slice.size {
             out_of_bounds();
         }
+        assert!(
+            slice.offset % 4 == 0,
+            "Buffer write offset {} is not a multiple of 4",
+            slice.offset
+        );
 
         let staged = self.device.new_buffer_with_data(
             data.as_ptr().cast(),
@@ -365,8 +798,15 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
 
 pub struct ComputeCommandEncoder<'a> {
     device: &'a mut metal::DeviceRef,
+    well_known_samplers: &'a WellKnownSamplers,
     encoder: metal::ComputeCommandEncoder,
+
+    /// Layout of the current pipeline.
+    pipeline: Option<Arc<PipelineSummary>>,
     bindings: Option<Arc<Bindings>>,
+
+    /// Bound argument groups, re-applied when slots change with pipeline.
+    bound_groups: &'a mut BoundGroups,
     workgroup_size: Option<[u32; 3]>,
     _marker: PhantomData<&'a mut CommandBuffer>,
 }
@@ -383,30 +823,109 @@ impl ComputeCommandEncoder<'_> {
     pub fn metal(&self) -> &metal::ComputeCommandEncoderRef {
         &self.encoder
     }
+
+    /// Binds arguments group and retains it
+    /// to re-apply when pipeline with different slots is set.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn bind_arguments_group(
+        &mut self,
+        group: u32,
+        arguments: impl IntoIterator<Item = (ShaderStages, RetainedArgument)>,
+    ) {
+        let arguments = self.bound_groups.set(group, arguments);
+        bind_compute_group(group, arguments, self.bindings.as_deref(), &self.encoder);
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn well_known_sampler(&self, kind: WellKnownSampler) -> Sampler {
+        self.well_known_samplers.get(kind)
+    }
 }
 
 impl Drop for ComputeCommandEncoder<'_> {
     #[inline(always)]
     fn drop(&mut self) {
         self.encoder.end_encoding();
+        self.bound_groups.unbind_from(0);
     }
 }
 
 #[hidden_trait::expose]
 impl traits::SyncCommandEncoder for ComputeCommandEncoder<'_> {
+    type Image = Image;
+
     #[inline(always)]
     fn barrier(&mut self, _after: PipelineStages, _before: PipelineStages) {}
 
     #[inline(always)]
     fn init_image(&mut self, _after: PipelineStages, _before: PipelineStages, _image: &Image) {}
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image(&mut self, _after: PipelineStages, _before: PipelineStages, _image: &Image) {}
+
+    #[inline(always)]
+    fn init_image_range(
+        &mut self,
+        _after: PipelineStages,
+        _before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        sync_image_range(image, levels, layers);
+    }
+
+    #[inline(always)]
+    fn sync_image_range(
+        &mut self,
+        _after: PipelineStages,
+        _before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        sync_image_range(image, levels, layers);
+    }
 }
 
 #[hidden_trait::expose]
 impl traits::ComputeCommandEncoder for ComputeCommandEncoder<'_> {
+    type ComputePipeline = ComputePipeline;
+
+    #[inline(always)]
+    fn capabilities(&self) -> QueueFlags {
+        QueueFlags::GRAPHICS | QueueFlags::COMPUTE | QueueFlags::TRANSFER
+    }
+
     #[inline(always)]
     fn with_pipeline(&mut self, pipeline: &crate::backend::ComputePipeline) {
         self.encoder.set_compute_pipeline_state(pipeline.metal());
-        self.bindings = pipeline.bindings();
+        let bindings = pipeline.bindings();
+
+        // Groups with different layouts must be bound again.
+        if let Some(old) = &self.pipeline {
+            self.bound_groups
+                .unbind_from(compatible_groups(old, pipeline.summary()));
+        }
+
+        // Slots are assigned per pipeline,
+        // arguments bound before are moved to new slots.
+        for (group, arguments) in self.bound_groups.iter() {
+            let idx = group as usize;
+            if group_slots_changed(self.bindings.as_deref(), bindings.as_deref(), idx) {
+                bind_compute_group(
+                    group,
+                    arguments,
+                    Bindings::for_group(bindings.as_deref(), idx),
+                    &self.encoder,
+                );
+            }
+        }
+
+        self.pipeline = Some(pipeline.summary().clone());
+        self.bindings = bindings;
         self.workgroup_size = pipeline.workgroup_size();
     }
 
@@ -424,8 +943,18 @@ impl traits::ComputeCommandEncoder for ComputeCommandEncoder<'_> {
             .set_bytes(0, data_bytes.len() as NSUInteger, data_bytes.as_ptr() as _);
     }
 
+    #[inline(always)]
+    fn set_auto_barrier(&mut self, _enabled: bool) {
+        // Metal tracks hazards between dispatches of serial encoder.
+    }
+
     #[inline(always)]
     fn dispatch(&mut self, groups: Extent3) {
+        if groups.width() == 0 || groups.height() == 0 || groups.depth() == 0 {
+            // Dispatching no work groups is a no-op
+            return;
+        }
+
         let group_size = self.workgroup_size.unwrap_or([1, 1, 1]);
 
         self.encoder.dispatch_thread_groups(
@@ -444,13 +973,36 @@ impl traits::ComputeCommandEncoder for ComputeCommandEncoder<'_> {
 }
 
 pub struct RenderCommandEncoder<'a> {
+    well_known_samplers: &'a WellKnownSamplers,
     encoder: metal::RenderCommandEncoder,
+
+    /// Parallel encoder that `encoder` was created from
+    /// if render pass is split across command buffers.
+    /// Dropped after `encoder` ends.
+    split_pass: Option<Arc<SplitPass>>,
+
+    /// Current viewport, depth range is changed without changing rectangle.
+    viewport: metal::MTLViewport,
     primitive: metal::MTLPrimitiveType,
     index_buffer: Option<metal::Buffer>,
     index_buffer_offset: NSUInteger,
+
+    /// Layout of the current pipeline.
+    pipeline: Option<Arc<PipelineSummary>>,
     vertex_bindings: Option<Arc<Bindings>>,
     fragment_bindings: Option<Arc<Bindings>>,
+
+    /// Bound argument groups, re-applied when slots change with pipeline.
+    bound_groups: &'a mut BoundGroups,
     vertex_buffers_count: u32,
+
+    /// Formats of color attachments of the render pass.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    color_formats: SmallVec<[PixelFormat; 4]>,
+
+    /// Format of depth-stencil attachment of the render pass.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    depth_format: Option<PixelFormat>,
     _marker: PhantomData<&'a mut CommandBuffer>,
 }
 
@@ -472,29 +1024,141 @@ impl RenderCommandEncoder<'_> {
     pub fn metal(&self) -> &metal::RenderCommandEncoderRef {
         &self.encoder
     }
+
+    /// Binds arguments group and retains it
+    /// to re-apply when pipeline with different slots is set.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn bind_arguments_group(
+        &mut self,
+        group: u32,
+        arguments: impl IntoIterator<Item = (ShaderStages, RetainedArgument)>,
+    ) {
+        let arguments = self.bound_groups.set(group, arguments);
+        bind_render_group(
+            group,
+            arguments,
+            self.vertex_bindings.as_deref(),
+            self.fragment_bindings.as_deref(),
+            &self.encoder,
+        );
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn well_known_sampler(&self, kind: WellKnownSampler) -> Sampler {
+        self.well_known_samplers.get(kind)
+    }
 }
 
 impl Drop for RenderCommandEncoder<'_> {
     #[inline(always)]
     fn drop(&mut self) {
         self.encoder.end_encoding();
+        self.bound_groups.unbind_from(0);
+        self.split_pass = None;
+    }
+}
+
+/// Sets store actions of the split render pass from the description of its last part.
+fn set_split_pass_store_actions(
+    encoder: &metal::ParallelRenderCommandEncoderRef,
+    desc: &RenderPassDesc,
+) {
+    use objc::{sel, sel_impl};
+
+    let store_action = |store| match store {
+        StoreOp::Store => metal::MTLStoreAction::Store,
+        StoreOp::DontCare => metal::MTLStoreAction::DontCare,
+    };
+
+    for (idx, color) in desc.color_attachments.iter().enumerate() {
+        let action = store_action(color.store);
+        let idx = idx as NSUInteger;
+        unsafe {
+            let () = msg_send![encoder, setColorStoreAction: action atIndex: idx];
+        }
+    }
+
+    if let Some(depth) = desc.depth_stencil_attachment {
+        let action = store_action(depth.store);
+        let format = depth.image.format();
+        if format.is_depth() {
+            unsafe {
+                let () = msg_send![encoder, setDepthStoreAction: action];
+            }
+        }
+        if format.is_stencil() {
+            unsafe {
+                let () = msg_send![encoder, setStencilStoreAction: action];
+            }
+        }
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
+    type RenderPipeline = RenderPipeline;
+
+    #[inline(always)]
+    fn capabilities(&self) -> QueueFlags {
+        QueueFlags::GRAPHICS | QueueFlags::COMPUTE | QueueFlags::TRANSFER
+    }
+
     #[inline(always)]
     fn with_pipeline(&mut self, pipeline: &RenderPipeline) {
+        // Encoder keeps viewport and scissor when pipeline state changes.
         self.encoder.set_render_pipeline_state(pipeline.metal());
         self.primitive = pipeline.primitive();
-        self.vertex_bindings = pipeline.vertex_bindings();
-        self.fragment_bindings = pipeline.fragment_bindings();
+        let vertex_bindings = pipeline.vertex_bindings();
+        let fragment_bindings = pipeline.fragment_bindings();
+
+        // Groups with different layouts must be bound again.
+        if let Some(old) = &self.pipeline {
+            self.bound_groups
+                .unbind_from(compatible_groups(old, pipeline.summary()));
+        }
+
+        // Slots are assigned per pipeline,
+        // arguments bound before are moved to new slots.
+        for (group, arguments) in self.bound_groups.iter() {
+            let idx = group as usize;
+            if group_slots_changed(
+                self.vertex_bindings.as_deref(),
+                vertex_bindings.as_deref(),
+                idx,
+            ) || group_slots_changed(
+                self.fragment_bindings.as_deref(),
+                fragment_bindings.as_deref(),
+                idx,
+            ) {
+                bind_render_group(
+                    group,
+                    arguments,
+                    Bindings::for_group(vertex_bindings.as_deref(), idx),
+                    Bindings::for_group(fragment_bindings.as_deref(), idx),
+                    &self.encoder,
+                );
+            }
+        }
+
+        self.pipeline = Some(pipeline.summary().clone());
+        self.vertex_bindings = vertex_bindings;
+        self.fragment_bindings = fragment_bindings;
         self.vertex_buffers_count = pipeline.vertex_buffers_count();
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        crate::generic::check_pipeline_formats(
+            pipeline.color_formats(),
+            pipeline.depth_format(),
+            &self.color_formats,
+            self.depth_format,
+        );
     }
 
     #[inline(always)]
     fn with_viewport(&mut self, offset: Offset3<f32>, extent: Extent3<f32>) {
-        let viewport = metal::MTLViewport {
+        self.viewport = metal::MTLViewport {
             originX: offset.x().into(),
             originY: offset.y().into(),
             width: extent.width().into(),
@@ -502,7 +1166,20 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
             znear: offset.z().into(),
             zfar: (offset.z() + extent.depth()).into(),
         };
-        self.encoder.set_viewport(viewport);
+        self.encoder.set_viewport(self.viewport);
+    }
+
+    #[inline(always)]
+    fn with_depth_range(&mut self, near: f32, far: f32) {
+        // Metal allows `znear > zfar`, reversed range needs no special handling.
+        debug_assert!(
+            (0.0..=1.0).contains(&near) && (0.0..=1.0).contains(&far),
+            "Depth range {near}..{far} is outside of 0..=1"
+        );
+
+        self.viewport.znear = near.into();
+        self.viewport.zfar = far.into();
+        self.encoder.set_viewport(self.viewport);
     }
 
     #[inline(always)]
@@ -555,6 +1232,10 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
     /// Bind vertex buffer to the current pipeline.
     #[cfg_attr(feature = "inline-more", inline)]
     fn bind_vertex_buffers(&mut self, start: u32, buffers: &[(impl AsBufferSlice)]) {
+        if buffers.is_empty() {
+            return;
+        }
+
         let (buffers, offsets) = buffers
             .iter()
             .map(|slice| {
@@ -679,6 +1360,9 @@ pub struct AccelerationStructureCommandEncoder<'a> {
 impl crate::traits::AccelerationStructureCommandEncoder
     for AccelerationStructureCommandEncoder<'_>
 {
+    type Blas = Blas;
+    type Tlas = Tlas;
+
     fn build_blas(&mut self, blas: &Blas, desc: BlasBuildDesc, scratch: impl AsBufferSlice) {
         use objc::{
             class, msg_send,
@@ -705,7 +1389,7 @@ impl crate::traits::AccelerationStructureCommandEncoder
                     mdesc.set_opaque(triangles.opaque);
 
                     let mut count =
-                        (triangles.vertices.size / triangles.vertex_stride) as NSUInteger;
+                        (triangles.vertices.size / triangles.vertex_stride as u64) as NSUInteger;
 
                     if let Some(indices) = triangles.indices {
                         mdesc.set_index_type(metal::MTLIndexType::UInt32);
@@ -737,7 +1421,7 @@ impl crate::traits::AccelerationStructureCommandEncoder
                     mdesc.set_opaque(aabbs.opaque);
 
                     mdesc.set_bounding_box_count(
-                        (aabbs.boxes.size / aabbs.box_stride) as NSUInteger,
+                        (aabbs.boxes.size / aabbs.box_stride as u64) as NSUInteger,
                     );
                     unsafe {
                         msg_send![mdesc, setBoundingBoxStride: (aabbs.box_stride as NSUInteger)]
@@ -759,3 +1443,11 @@ impl crate::traits::AccelerationStructureCommandEncoder
 
     fn build_tlas(&mut self, tlas: &Tlas, desc: TlasBuildDesc, scratch: impl AsBufferSlice) {}
 }
+
+/// Metal tracks hazards of resources automatically
+/// and has no image layouts, so subresource barriers only validate ranges.
+#[inline(always)]
+#[track_caller]
+fn sync_image_range(image: &Image, levels: Range<u32>, layers: Range<u32>) {
+    check_image_range(&levels, &layers, image.levels(), image.layers());
+}
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use crate::generic::{ArgumentGroupInfo, PipelineSummary, ResourceId};
+
 use super::shader::Bindings;
 
 #[derive(Clone)]
@@ -7,6 +9,8 @@ pub struct ComputePipeline {
     state: metal::ComputePipelineState,
     bindings: Option<Arc<Bindings>>,
     workgroup_size: Option<[u32; 3]>,
+    id: ResourceId,
+    summary: Arc<PipelineSummary>,
 }
 
 unsafe impl Send for ComputePipeline {}
@@ -18,11 +22,15 @@ impl ComputePipeline {
         state: metal::ComputePipelineState,
         bindings: Option<Arc<Bindings>>,
         workgroup_size: Option<[u32; 3]>,
+        id: ResourceId,
+        summary: PipelineSummary,
     ) -> Self {
         ComputePipeline {
             state,
             bindings,
             workgroup_size,
+            id,
+            summary: Arc::new(summary),
         }
     }
 
@@ -36,8 +44,38 @@ impl ComputePipeline {
         self.bindings.clone()
     }
 
+    #[inline(always)]
+    pub(super) fn summary(&self) -> &Arc<PipelineSummary> {
+        &self.summary
+    }
+
     #[inline(always)]
     pub(super) fn workgroup_size(&self) -> Option<[u32; 3]> {
         self.workgroup_size
     }
 }
+
+#[hidden_trait::expose]
+impl crate::traits::Resource for ComputePipeline {
+    #[inline(always)]
+    fn id(&self) -> ResourceId {
+        self.id
+    }
+
+    fn describe(&self) -> String {
+        self.summary.describe(self.id)
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Pipeline for ComputePipeline {
+    #[inline(always)]
+    fn argument_groups(&self) -> &[ArgumentGroupInfo] {
+        self.summary.arguments()
+    }
+
+    #[inline(always)]
+    fn constants_size(&self) -> usize {
+        self.summary.constants()
+    }
+}
@@ -3,7 +3,7 @@ use std::{fmt, sync::Arc};
 use core_graphics_types::{base::CGFloat, geometry::CGRect};
 use foreign_types::ForeignType;
 use hashbrown::HashMap;
-use metal::{CAMetalLayer, NSUInteger, SamplerDescriptor};
+use metal::CAMetalLayer;
 use objc::{
     class, msg_send,
     runtime::{Object, BOOL, YES},
@@ -18,24 +18,40 @@ use raw_window_handle::{
 
 use crate::{
     generic::{
-        parse_shader, ArgumentKind, BlasDesc, BufferDesc, BufferInitDesc, ComputePipelineDesc,
-        CreateLibraryError, CreatePipelineError, ImageDesc, ImageExtent, LibraryDesc, LibraryInput,
-        Memory, OutOfMemory, RenderPipelineDesc, SamplerDesc, ShaderCompileError, ShaderLanguage,
-        SurfaceError, TlasDesc, VertexStepMode,
+        check_argument_stages, check_buffer_ranges, check_dual_source_blend, check_entry_point,
+        check_stage_interface, compose_wgsl, find_depth_format, max_buffer_size, parallel_map,
+        parse_shader, reflect_entries, shader_capabilities, AllocError, AllocErrorKind,
+        AllocResource, AllocatorStats, ArgumentGroupLayout, ArgumentKind, BlasDesc, BufferDesc,
+        BufferInitDesc, BufferUsage, CompactionPlan, ComputePipelineDesc, CreateLibraryError,
+        CreatePipelineError, DeferredWorkers, DepthFormatRequirements, DeviceLimits,
+        FamilyCapabilities, Features, FormatCaps, ImageDesc, ImageExtent, ImageUsage, LibraryDesc,
+        LibraryInput, Memory, OutOfMemory, PipelinePending, PipelineSummary, PixelFormat,
+        PrimitiveTopology, QueueFlags, Quirks, RenderPipelineDesc, ResourceId, ResourceIds,
+        SamplerDesc, Shader, ShaderCompileError, ShaderLanguage, ShaderStage, SourceFile,
+        SurfaceError, TlasDesc, VertexStepMode, WellKnownSampler,
     },
     Extent3,
 };
 
 use super::{
+    bindless::BindlessTable,
     from::{IntoMetal, TryIntoMetal},
-    shader::{Bindings, EntryPointData},
+    sampler::{new_sampler, WellKnownSamplers},
+    shader::{compile_options, Bindings, EntryPointData},
     Blas, Buffer, ComputePipeline, CreatePipelineErrorKind, Image, Library, RenderPipeline,
-    Sampler, Surface, Tlas, MAX_VERTEX_BUFFERS,
+    Sampler, Surface, Tlas, MAX_ARGUMENT_GROUPS, MAX_BINDLESS_TABLE_SIZE, MAX_STORAGE_BUFFER_RANGE,
+    MAX_TEXTURE_DIMENSION_2D, MAX_TEXTURE_DIMENSION_3D, MAX_VERTEX_BUFFERS,
+    MAX_VERTEX_BUFFER_STRIDE,
 };
 
 #[derive(Clone)]
 pub struct Device {
     device: metal::Device,
+    features: Features,
+    quirks: Quirks,
+    well_known_samplers: Arc<WellKnownSamplers>,
+    resource_ids: Arc<ResourceIds>,
+    deferred: Arc<DeferredWorkers>,
 }
 
 unsafe impl Sync for Device {}
@@ -58,12 +74,30 @@ impl PartialEq for Device {
 impl Eq for Device {}
 
 impl Device {
-    pub(super) fn new(device: metal::Device, queues: usize) -> Self {
-        Device { device }
+    pub(super) fn new(
+        device: metal::Device,
+        queues: usize,
+        features: Features,
+        quirks: Quirks,
+    ) -> Self {
+        let resource_ids = Arc::new(ResourceIds::new());
+        let well_known_samplers = Arc::new(WellKnownSamplers::new(
+            device.clone(),
+            resource_ids.clone(),
+            quirks,
+        ));
+        Device {
+            device,
+            features,
+            quirks,
+            well_known_samplers,
+            resource_ids,
+            deferred: Arc::new(DeferredWorkers::new()),
+        }
     }
 
     pub(super) fn set_last_cbuf(device: metal::Device, queues: usize) -> Self {
-        Device { device }
+        Device::new(device, queues, Features::empty(), Quirks::empty())
     }
 }
 
@@ -72,15 +106,150 @@ impl Device {
     pub(crate) fn metal(&self) -> &metal::DeviceRef {
         self.device.as_ref()
     }
+
+    /// Returns identifier for a new resource.
+    #[inline(always)]
+    pub(super) fn new_resource_id(&self) -> ResourceId {
+        self.resource_ids.next()
+    }
+
+    /// Largest buffer that can be created with given usage.
+    fn max_buffer_size(&self, usage: BufferUsage) -> u64 {
+        max_buffer_size(
+            usage,
+            self.device.max_buffer_length(),
+            MAX_STORAGE_BUFFER_RANGE,
+        )
+    }
+
+    /// Checks that buffer of given size and usage can be created on this device.
+    fn check_buffer_size(
+        &self,
+        size: u64,
+        usage: BufferUsage,
+        name: &str,
+    ) -> Result<(), AllocError> {
+        if size == 0 {
+            return Err(AllocError::new(
+                AllocErrorKind::ZeroSizedBuffer,
+                name,
+                AllocResource::Buffer { size },
+            ));
+        }
+
+        let max = self.max_buffer_size(usage);
+        if size > max {
+            return Err(AllocError::new(
+                AllocErrorKind::BufferTooLarge { size, max },
+                name,
+                AllocResource::Buffer { size },
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates image description and builds texture descriptor for it.
+    fn texture_descriptor(&self, desc: &ImageDesc) -> Result<metal::TextureDescriptor, AllocError> {
+        assert!(
+            !desc.usage.contains(ImageUsage::TRANSIENT)
+                || (desc.usage - ImageUsage::TRANSIENT) == ImageUsage::TARGET,
+            "Transient image may only be used as render target"
+        );
+
+        let (dimension, max) = match desc.extent {
+            ImageExtent::D1(extent) => (extent.width(), MAX_TEXTURE_DIMENSION_2D),
+            ImageExtent::D2(extent) => (
+                extent.width().max(extent.height()),
+                MAX_TEXTURE_DIMENSION_2D,
+            ),
+            ImageExtent::D3(extent) => (
+                extent.width().max(extent.height()).max(extent.depth()),
+                MAX_TEXTURE_DIMENSION_3D,
+            ),
+        };
+
+        if dimension > max {
+            return Err(AllocError::new(
+                AllocErrorKind::ExceedsLimits { dimension, max },
+                desc.name,
+                AllocResource::Image {
+                    extent: desc.extent,
+                    format: desc.format,
+                    layers: desc.layers,
+                    levels: desc.levels,
+                },
+            ));
+        }
+
+        let mdesc = metal::TextureDescriptor::new();
+        mdesc.set_pixel_format(desc.format.try_into_metal().unwrap());
+        match desc.extent {
+            // Non-array textures can't have more than one layer.
+            ImageExtent::D1(extent) if desc.layers > 1 => {
+                mdesc.set_texture_type(metal::MTLTextureType::D1Array);
+                mdesc.set_width(extent.width() as _);
+            }
+            ImageExtent::D1(extent) => {
+                mdesc.set_texture_type(metal::MTLTextureType::D1);
+                mdesc.set_width(extent.width() as _);
+            }
+            ImageExtent::D2(extent) if desc.layers > 1 => {
+                mdesc.set_texture_type(metal::MTLTextureType::D2Array);
+                mdesc.set_width(extent.width() as _);
+                mdesc.set_height(extent.height() as _);
+            }
+            ImageExtent::D2(extent) => {
+                mdesc.set_texture_type(metal::MTLTextureType::D2);
+                mdesc.set_width(extent.width() as _);
+                mdesc.set_height(extent.height() as _);
+            }
+            ImageExtent::D3(extent) => {
+                mdesc.set_texture_type(metal::MTLTextureType::D3);
+                mdesc.set_width(extent.width() as _);
+                mdesc.set_height(extent.height() as _);
+                mdesc.set_depth(extent.depth() as _);
+            }
+        }
+        mdesc.set_mipmap_level_count(desc.levels as _);
+        mdesc.set_array_length(desc.layers as _);
+        mdesc.set_sample_count(1);
+        mdesc.set_usage(desc.usage.into_metal());
+
+        // Memoryless textures are supported only by Apple GPUs.
+        if desc.usage.contains(ImageUsage::TRANSIENT)
+            && self.device.supports_family(metal::MTLGPUFamily::Apple1)
+        {
+            mdesc.set_storage_mode(metal::MTLStorageMode::Memoryless);
+        } else {
+            mdesc.set_storage_mode(metal::MTLStorageMode::Private);
+        }
+
+        Ok(mdesc)
+    }
+
+    #[inline(always)]
+    pub(super) fn well_known_samplers(&self) -> &Arc<WellKnownSamplers> {
+        &self.well_known_samplers
+    }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::Device for Device {
+    type Library = Library;
+    type ComputePipeline = ComputePipeline;
+    type RenderPipeline = RenderPipeline;
+    type Buffer = Buffer;
+    type Image = Image;
+    type Sampler = Sampler;
+    type Surface = Surface;
+    type Blas = Blas;
+    type Tlas = Tlas;
+    type BindlessTable = BindlessTable;
+
     fn new_shader_library(&self, desc: LibraryDesc) -> Result<Library, CreateLibraryError> {
         match desc.input {
             LibraryInput::Source(source) => {
-                let options = metal::CompileOptions::new();
-                options.set_language_version(metal::MTLLanguageVersion::V2_2);
+                let options = compile_options();
 
                 match source.language {
                     ShaderLanguage::Msl => {
@@ -97,8 +266,14 @@ impl crate::traits::Device for Device {
                     }
 
                     src => {
-                        let compiled = compile_shader(&source.code, source.filename, src)
-                            .map_err(|err| CreateLibraryError::CompileError(err))?;
+                        let compiled = compile_shader(
+                            &source.code,
+                            &SourceFile::single(source.filename),
+                            src,
+                            bounds_check_policies(self.features),
+                            shader_capabilities(MSL_CAPABILITIES, self.features),
+                        )
+                        .map_err(|err| CreateLibraryError::CompileError(err))?;
 
                         let library = self
                             .device
@@ -108,10 +283,32 @@ impl crate::traits::Device for Device {
                         Ok(Library::with_entry_point_data(
                             library,
                             compiled.entry_point_data,
+                            compiled.point_code,
                         ))
                     }
                 }
             }
+            LibraryInput::Sources(sources) => {
+                let composed = compose_wgsl(&sources)?;
+                let compiled = compile_shader(
+                    composed.code.as_bytes(),
+                    &composed.files,
+                    ShaderLanguage::Wgsl,
+                    bounds_check_policies(self.features),
+                    shader_capabilities(MSL_CAPABILITIES, self.features),
+                )?;
+
+                let library = self
+                    .device
+                    .new_library_with_source(&compiled.code, &compile_options())
+                    .unwrap();
+
+                Ok(Library::with_entry_point_data(
+                    library,
+                    compiled.entry_point_data,
+                    compiled.point_code,
+                ))
+            }
         }
     }
 
@@ -119,6 +316,15 @@ impl crate::traits::Device for Device {
         &self,
         desc: ComputePipelineDesc,
     ) -> Result<ComputePipeline, CreatePipelineError> {
+        check_entry_stage(&desc.shader, ShaderStage::Compute)?;
+        check_argument_groups(desc.arguments)?;
+        check_shader_argument_stages(&desc.shader, desc.arguments)?;
+
+        let limits = self.limits();
+        check_shader_buffer_ranges(&desc.shader, &limits)?;
+
+        let summary = PipelineSummary::compute(&desc);
+
         let mdesc = metal::ComputePipelineDescriptor::new();
         mdesc.set_label(desc.name);
 
@@ -141,6 +347,8 @@ impl crate::traits::Device for Device {
             pipeline,
             desc.shader.library.get_bindings(&desc.shader.entry),
             desc.shader.library.get_workgroup_size(&desc.shader.entry),
+            self.new_resource_id(),
+            summary,
         ))
     }
 
@@ -148,14 +356,63 @@ impl crate::traits::Device for Device {
         &self,
         desc: RenderPipelineDesc,
     ) -> Result<RenderPipeline, CreatePipelineError> {
+        check_entry_stage(&desc.vertex_shader, ShaderStage::Vertex)?;
+        check_argument_groups(desc.arguments)?;
+        check_shader_argument_stages(&desc.vertex_shader, desc.arguments)?;
+
+        let limits = self.limits();
+        check_shader_buffer_ranges(&desc.vertex_shader, &limits)?;
+
+        let summary = PipelineSummary::render(&desc);
+
+        if let Some(raster) = &desc.raster {
+            check_dual_source_blend(
+                raster,
+                self.features.contains(Features::DUAL_SOURCE_BLEND),
+                |shader| shader.library.writes_second_blend_source(&shader.entry),
+            )
+            .map_err(|err| CreatePipelineError(CreatePipelineErrorKind::DualSourceBlend(err)))?;
+        }
+
+        if let Some(fragment_shader) = desc
+            .raster
+            .as_ref()
+            .and_then(|raster| raster.fragment_shader.as_ref())
+        {
+            check_entry_stage(fragment_shader, ShaderStage::Fragment)?;
+            check_shader_argument_stages(fragment_shader, desc.arguments)?;
+            check_shader_buffer_ranges(fragment_shader, &limits)?;
+
+            let vertex = desc
+                .vertex_shader
+                .library
+                .reflect_entry(&desc.vertex_shader.entry);
+            let fragment = fragment_shader
+                .library
+                .reflect_entry(&fragment_shader.entry);
+
+            // Libraries compiled from MSL are not reflected and are not checked.
+            if let (Some(vertex), Some(fragment)) = (vertex, fragment) {
+                check_stage_interface(vertex, fragment).map_err(|err| {
+                    CreatePipelineError(CreatePipelineErrorKind::StageInterfaceMismatch(err))
+                })?;
+            }
+        }
+
         let mdesc = metal::RenderPipelineDescriptor::new();
         mdesc.set_label(desc.name);
 
-        let vertex_function = desc
-            .vertex_shader
-            .library
-            .get_function(&desc.vertex_shader.entry)
-            .ok_or_else(|| CreatePipelineError(CreatePipelineErrorKind::InvalidShaderEntry))?;
+        let vertex_function = match desc.primitive_topology {
+            PrimitiveTopology::Point => desc
+                .vertex_shader
+                .library
+                .get_point_function(&self.device, &desc.vertex_shader.entry),
+            _ => desc
+                .vertex_shader
+                .library
+                .get_function(&desc.vertex_shader.entry),
+        }
+        .ok_or_else(|| CreatePipelineError(CreatePipelineErrorKind::InvalidShaderEntry))?;
 
         mdesc.set_vertex_function(Some(&vertex_function));
 
@@ -193,6 +450,16 @@ impl crate::traits::Device for Device {
 
         let layouts = vertex_desc.layouts();
         for (idx, vertex_layout) in desc.vertex_layouts.iter().enumerate() {
+            if vertex_layout.stride > MAX_VERTEX_BUFFER_STRIDE {
+                return Err(CreatePipelineError(
+                    CreatePipelineErrorKind::VertexStrideTooLarge {
+                        buffer: idx as u32,
+                        stride: vertex_layout.stride,
+                        limit: MAX_VERTEX_BUFFER_STRIDE,
+                    },
+                ));
+            }
+
             let layout_desc = metal::VertexBufferLayoutDescriptor::new();
             layout_desc.set_stride(vertex_layout.stride as _);
             match vertex_layout.step_mode {
@@ -204,6 +471,8 @@ impl crate::traits::Device for Device {
                     layout_desc.set_step_function(metal::MTLVertexStepFunction::PerInstance)
                 }
                 VertexStepMode::Constant => {
+                    // Constant step function requires zero step rate.
+                    layout_desc.set_step_rate(0);
                     layout_desc.set_step_function(metal::MTLVertexStepFunction::Constant)
                 }
             }
@@ -221,10 +490,37 @@ impl crate::traits::Device for Device {
             attributes.set_object_at(idx as _, Some(&attribute_desc));
         }
 
-        mdesc.set_vertex_descriptor(Some(&vertex_desc));
+        // Vertex shader without stage input may be used with no vertex buffers,
+        // e.g. full-screen triangle generated from vertex index.
+        if !desc.vertex_layouts.is_empty() || !desc.vertex_attributes.is_empty() {
+            mdesc.set_vertex_descriptor(Some(&vertex_desc));
+        }
         mdesc.set_input_primitive_topology(desc.primitive_topology.into_metal());
 
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        let color_formats = desc.raster.as_ref().map_or(Vec::new(), |raster| {
+            raster
+                .color_targets
+                .iter()
+                .map(|color| color.format)
+                .collect()
+        });
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        let depth_format = desc
+            .raster
+            .as_ref()
+            .and_then(|raster| raster.depth_stencil.as_ref())
+            .map(|depth| depth.format);
+
         if let Some(raster) = desc.raster {
+            if raster.line_width != 1.0 {
+                tracing::warn!(
+                    "Line width {} requires `Features::WIDE_LINES` which is not supported on Metal, using 1.0",
+                    raster.line_width
+                );
+            }
+
             if let Some(fragment_shader) = raster.fragment_shader {
                 let fragment_function = fragment_shader
                     .library
@@ -244,6 +540,9 @@ impl crate::traits::Device for Device {
                 color_attachment.set_pixel_format(color_desc.format.try_into_metal().unwrap());
 
                 if let Some(blend_desc) = &color_desc.blend {
+                    #[cfg(any(debug_assertions, feature = "debug"))]
+                    blend_desc.warn_ignored_factors(idx);
+
                     color_attachment.set_blending_enabled(true);
                     color_attachment.set_write_mask(blend_desc.mask.into_metal());
                     color_attachment.set_rgb_blend_operation(blend_desc.color.op.into_metal());
@@ -285,95 +584,92 @@ impl crate::traits::Device for Device {
             vertex_bindings,
             fragment_bindings,
             vertex_buffers_count as u32,
+            self.new_resource_id(),
+            summary,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            color_formats,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            depth_format,
         ))
     }
 
-    fn new_buffer(&self, desc: BufferDesc) -> Result<Buffer, OutOfMemory> {
-        let mut options = metal::MTLResourceOptions::empty();
+    fn new_compute_pipelines_parallel(
+        &self,
+        descs: Vec<ComputePipelineDesc>,
+    ) -> Vec<Result<ComputePipeline, CreatePipelineError>> {
+        parallel_map(descs, |desc| self.new_compute_pipeline(desc))
+    }
 
-        match desc.memory {
-            Memory::Device => options |= metal::MTLResourceOptions::StorageModePrivate,
-            Memory::Shared => options |= metal::MTLResourceOptions::StorageModeShared,
-            Memory::Upload => {
-                options |= metal::MTLResourceOptions::StorageModeManaged
-                    | metal::MTLResourceOptions::CPUCacheModeWriteCombined
-            }
-            Memory::Download => options |= metal::MTLResourceOptions::StorageModeManaged,
-        }
+    fn new_render_pipelines_parallel(
+        &self,
+        descs: Vec<RenderPipelineDesc>,
+    ) -> Vec<Result<RenderPipeline, CreatePipelineError>> {
+        parallel_map(descs, |desc| self.new_render_pipeline(desc))
+    }
 
-        let buffer = self.device.new_buffer(desc.size as _, options);
-        Ok(Buffer::new(buffer))
+    fn new_render_pipeline_deferred(
+        &self,
+        desc: RenderPipelineDesc,
+    ) -> PipelinePending<RenderPipeline> {
+        let desc = desc.into_owned();
+        let device = self.clone();
+        self.deferred
+            .spawn(move || desc.with_desc(|desc| device.new_render_pipeline(desc)))
     }
 
-    fn new_buffer_init(&self, desc: BufferInitDesc) -> Result<Buffer, OutOfMemory> {
-        let Ok(len) = u64::try_from(desc.data.len()) else {
-            return Err(OutOfMemory);
-        };
+    fn new_buffer(&self, desc: BufferDesc) -> Result<Buffer, AllocError> {
+        self.check_buffer_size(desc.size, desc.usage, desc.name)?;
 
-        let mut options = metal::MTLResourceOptions::empty();
+        let options = buffer_options(desc.memory);
 
-        match desc.memory {
-            Memory::Device => options |= metal::MTLResourceOptions::StorageModePrivate,
-            Memory::Shared => options |= metal::MTLResourceOptions::StorageModeShared,
-            Memory::Upload => {
-                options |= metal::MTLResourceOptions::StorageModeManaged
-                    | metal::MTLResourceOptions::CPUCacheModeWriteCombined
-            }
-            Memory::Download => options |= metal::MTLResourceOptions::StorageModeManaged,
-        }
+        let buffer = self.device.new_buffer(desc.size, options);
+        Ok(Buffer::new(buffer, self.new_resource_id()))
+    }
+
+    fn new_buffer_init(&self, desc: BufferInitDesc) -> Result<Buffer, AllocError> {
+        let len = desc.data.len() as u64;
+        self.check_buffer_size(len, desc.usage, desc.name)?;
+
+        let options = buffer_options(desc.memory);
 
         let buffer = self
             .device
             .new_buffer_with_data(desc.data.as_ptr().cast(), len, options);
-        Ok(Buffer::new(buffer))
+        Ok(Buffer::new(buffer, self.new_resource_id()))
     }
 
-    fn new_image(&self, desc: ImageDesc) -> Result<Image, OutOfMemory> {
-        let mdesc = metal::TextureDescriptor::new();
-        mdesc.set_pixel_format(desc.format.try_into_metal().unwrap());
-        match desc.extent {
-            ImageExtent::D1(extent) => {
-                mdesc.set_texture_type(metal::MTLTextureType::D1);
-                mdesc.set_width(extent.width() as _);
-            }
-            ImageExtent::D2(extent) => {
-                mdesc.set_texture_type(metal::MTLTextureType::D2);
-                mdesc.set_width(extent.width() as _);
-                mdesc.set_height(extent.height() as _);
-            }
-            ImageExtent::D3(extent) => {
-                mdesc.set_texture_type(metal::MTLTextureType::D3);
-                mdesc.set_width(extent.width() as _);
-                mdesc.set_height(extent.height() as _);
-                mdesc.set_depth(extent.depth() as _);
-            }
-        }
-        mdesc.set_mipmap_level_count(desc.levels as _);
-        mdesc.set_array_length(desc.layers as _);
-        mdesc.set_sample_count(1);
-        mdesc.set_usage(desc.usage.into_metal());
-        mdesc.set_storage_mode(metal::MTLStorageMode::Private);
-
+    fn new_image(&self, desc: ImageDesc) -> Result<Image, AllocError> {
+        let mdesc = self.texture_descriptor(&desc)?;
         let texture = self.device.new_texture(&mdesc);
-        Ok(Image::new(texture))
+        Ok(Image::new(texture, self.new_resource_id()))
     }
 
     fn new_sampler(&self, desc: SamplerDesc) -> Result<Sampler, OutOfMemory> {
-        let mdesc = SamplerDescriptor::new();
-        mdesc.set_min_filter(desc.min_filter.into_metal());
-        mdesc.set_mag_filter(desc.mag_filter.into_metal());
-        mdesc.set_mip_filter(desc.mip_map_mode.into_metal());
-        mdesc.set_address_mode_s(desc.address_mode[0].into_metal());
-        mdesc.set_address_mode_t(desc.address_mode[1].into_metal());
-        mdesc.set_address_mode_r(desc.address_mode[2].into_metal());
-        if let Some(anisotropy) = desc.anisotropy {
-            mdesc.set_max_anisotropy((anisotropy as NSUInteger).clamp(1, 16));
-        }
-        mdesc.set_lod_min_clamp(desc.min_lod);
-        mdesc.set_lod_max_clamp(desc.max_lod);
-        mdesc.set_normalized_coordinates(desc.normalized);
-        let state = self.device.new_sampler(&mdesc);
-        Ok(Sampler::new(state))
+        Ok(new_sampler(
+            &self.device,
+            self.new_resource_id(),
+            desc,
+            self.quirks,
+        ))
+    }
+
+    fn well_known_sampler(&self, kind: WellKnownSampler) -> Result<Sampler, OutOfMemory> {
+        Ok(self.well_known_samplers.get(kind))
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn linear_clamp_sampler(&self) -> Result<Sampler, OutOfMemory> {
+        self.well_known_sampler(WellKnownSampler::LinearClamp)
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn nearest_clamp_sampler(&self) -> Result<Sampler, OutOfMemory> {
+        self.well_known_sampler(WellKnownSampler::NearestClamp)
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn linear_repeat_sampler(&self) -> Result<Sampler, OutOfMemory> {
+        self.well_known_sampler(WellKnownSampler::LinearRepeat)
     }
 
     fn new_surface(
@@ -391,17 +687,24 @@ impl crate::traits::Device for Device {
             (RawWindowHandle::UiKit(handle), RawDisplayHandle::UiKit(_)) => unsafe {
                 let layer = layer_from_view(handle.ui_view.cast().as_ptr());
                 layer.set_device(&self.device);
-                Ok(Surface::new(layer, std::ptr::null_mut()))
+                Ok(Surface::new(
+                    layer,
+                    std::ptr::null_mut(),
+                    self.resource_ids.clone(),
+                ))
             },
             (RawWindowHandle::AppKit(handle), RawDisplayHandle::AppKit(_)) => unsafe {
                 let layer = layer_from_view(handle.ns_view.cast().as_ptr());
                 layer.set_device(&self.device);
-                Ok(Surface::new(layer, handle.ns_view.cast().as_ptr()))
+                Ok(Surface::new(
+                    layer,
+                    handle.ns_view.cast().as_ptr(),
+                    self.resource_ids.clone(),
+                ))
             },
-            (RawWindowHandle::UiKit(_), _) | (RawWindowHandle::AppKit(_), _) => {
-                panic!("Mismatched window and display type")
-            }
-            _ => unreachable!("Unsupported window type for the metal backend"),
+            // Mismatched window and display types
+            // or window system not supported by the metal backend.
+            _ => Err(SurfaceError::Unsupported),
         }
     }
 
@@ -421,6 +724,180 @@ impl crate::traits::Device for Device {
         Ok(Tlas::new(tlas))
     }
 
+    fn new_bindless_table(&self, capacity: u32) -> Result<BindlessTable, OutOfMemory> {
+        assert!(
+            self.features.contains(Features::DESCRIPTOR_INDEXING),
+            "Bindless tables require `Features::DESCRIPTOR_INDEXING`"
+        );
+        assert!(
+            capacity <= MAX_BINDLESS_TABLE_SIZE,
+            "Bindless table capacity {capacity} exceeds device limit {MAX_BINDLESS_TABLE_SIZE}"
+        );
+        Ok(BindlessTable::new(capacity))
+    }
+
+    fn features(&self) -> Features {
+        self.features
+    }
+
+    fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    fn limits(&self) -> DeviceLimits {
+        // Metal limits buffer arguments only by maximum buffer length.
+        let max_buffer_length = self.device.max_buffer_length();
+        DeviceLimits {
+            max_argument_groups: MAX_ARGUMENT_GROUPS,
+            max_uniform_buffer_range: max_buffer_length,
+            max_storage_buffer_range: max_buffer_length.min(MAX_STORAGE_BUFFER_RANGE),
+            max_bindless_table_size: if self.features.contains(Features::DESCRIPTOR_INDEXING) {
+                MAX_BINDLESS_TABLE_SIZE
+            } else {
+                0
+            },
+        }
+    }
+
+    fn image_allocation_size(&self, desc: &ImageDesc) -> Result<u64, AllocError> {
+        let mdesc = self.texture_descriptor(desc)?;
+
+        // Memoryless textures take no memory.
+        if mdesc.storage_mode() == metal::MTLStorageMode::Memoryless {
+            return Ok(0);
+        }
+
+        Ok(self.device.heap_texture_size_and_align(&mdesc).size)
+    }
+
+    fn buffer_allocation_size(&self, desc: &BufferDesc) -> u64 {
+        if desc.size > self.max_buffer_size(desc.usage) {
+            return desc.size;
+        }
+
+        // Heaps don't support managed storage mode, shared buffers are laid out the same way.
+        let mut options = buffer_options(desc.memory);
+        if options.contains(metal::MTLResourceOptions::StorageModeManaged) {
+            options.remove(metal::MTLResourceOptions::StorageModeManaged);
+            options |= metal::MTLResourceOptions::StorageModeShared;
+        }
+
+        self.device
+            .heap_buffer_size_and_align(desc.size, options)
+            .size
+    }
+
+    fn allocator_stats(&self) -> AllocatorStats {
+        // Metal allocates memory for each resource, only total size is known.
+        AllocatorStats {
+            reserved_bytes: self.device.current_allocated_size() as u64,
+            ..AllocatorStats::default()
+        }
+    }
+
+    fn trim_memory(&self) -> u64 {
+        // Metal allocates memory for each resource and frees it with the resource.
+        0
+    }
+
+    fn plan_compaction(&self, _buffers: &[Buffer]) -> CompactionPlan {
+        // Metal allocates memory for each resource, there's nothing to compact.
+        CompactionPlan::default()
+    }
+
+    fn format_caps(&self, format: PixelFormat) -> FormatCaps {
+        if TryIntoMetal::<metal::MTLPixelFormat>::try_into_metal(format).is_none() {
+            return FormatCaps::empty();
+        }
+
+        if format == PixelFormat::D24UnormS8Uint && !self.device.d24_s8_supported() {
+            // Apple GPUs don't support packed 24-bit depth.
+            return FormatCaps::empty();
+        }
+
+        let apple = self.device.supports_family(metal::MTLGPUFamily::Apple2);
+        let mac = self.device.supports_family(metal::MTLGPUFamily::Mac2);
+
+        if matches!(format, PixelFormat::R8Srgb | PixelFormat::Rg8Srgb) && !apple {
+            // Only Apple GPUs have one and two channel sRGB formats.
+            return FormatCaps::empty();
+        }
+
+        if format.is_depth() || format.is_stencil() {
+            return FormatCaps::SAMPLED | FormatCaps::DEPTH_STENCIL_TARGET;
+        }
+
+        let mut caps = FormatCaps::SAMPLED | FormatCaps::COLOR_TARGET;
+
+        if !format.is_srgb() {
+            // sRGB formats can't be written from shaders.
+            caps |= FormatCaps::STORAGE;
+        }
+
+        match format {
+            PixelFormat::R8Uint
+            | PixelFormat::R8Sint
+            | PixelFormat::R16Uint
+            | PixelFormat::R16Sint
+            | PixelFormat::Rg8Uint
+            | PixelFormat::Rg8Sint
+            | PixelFormat::Rg16Uint
+            | PixelFormat::Rg16Sint
+            | PixelFormat::Rg32Uint
+            | PixelFormat::Rg32Sint
+            | PixelFormat::Rgba8Uint
+            | PixelFormat::Rgba8Sint
+            | PixelFormat::Rgba16Uint
+            | PixelFormat::Rgba16Sint
+            | PixelFormat::Rgba32Uint
+            | PixelFormat::Rgba32Sint => {}
+            PixelFormat::R32Uint | PixelFormat::R32Sint => {
+                // Texture atomics need Apple6 or Mac2 family.
+                if mac || self.device.supports_family(metal::MTLGPUFamily::Apple6) {
+                    caps |= FormatCaps::STORAGE_ATOMIC;
+                }
+            }
+            PixelFormat::R32Float | PixelFormat::Rg32Float | PixelFormat::Rgba32Float => {
+                let filtering: BOOL =
+                    unsafe { msg_send![self.device, supports32BitFloatFiltering] };
+                if filtering == YES {
+                    caps |= FormatCaps::FILTERABLE_LINEAR;
+                }
+
+                // Apple GPUs before Apple7 blend only single channel 32-bit floats.
+                if mac
+                    || format == PixelFormat::R32Float
+                    || self.device.supports_family(metal::MTLGPUFamily::Apple7)
+                {
+                    caps |= FormatCaps::BLENDABLE;
+                }
+            }
+            _ => caps |= FormatCaps::FILTERABLE_LINEAR | FormatCaps::BLENDABLE,
+        }
+
+        caps
+    }
+
+    fn select_depth_format(&self, requirements: DepthFormatRequirements) -> Option<PixelFormat> {
+        find_depth_format(requirements, |format| self.format_caps(format))
+    }
+
+    fn describe(&self, resource: &impl crate::traits::Resource) -> String {
+        resource.describe()
+    }
+
+    fn family_capabilities(&self, family: u32) -> FamilyCapabilities {
+        assert_eq!(family, 0, "Only one queue family is supported");
+        FamilyCapabilities {
+            queue_flags: QueueFlags::GRAPHICS | QueueFlags::COMPUTE | QueueFlags::TRANSFER,
+            queue_count: 32,
+        }
+    }
+
+    fn supports_present(&self, family: u32, _surface: &Surface) -> bool {
+        family == 0
+    }
+
     // fn wait_idle(&self) -> Result<(), OutOfMemory> {
     //     Ok(())
     // }
@@ -467,17 +944,101 @@ extern "C" {
     static kCAGravityTopLeft: *mut Object;
 }
 
+fn buffer_options(memory: Memory) -> metal::MTLResourceOptions {
+    match memory {
+        Memory::Device => metal::MTLResourceOptions::StorageModePrivate,
+        Memory::Shared => metal::MTLResourceOptions::StorageModeShared,
+        Memory::Upload => {
+            metal::MTLResourceOptions::StorageModeManaged
+                | metal::MTLResourceOptions::CPUCacheModeWriteCombined
+        }
+        Memory::Download => metal::MTLResourceOptions::StorageModeManaged,
+    }
+}
+
+/// Checks that pipeline doesn't use more argument groups than there are buffer slots.
+fn check_argument_groups(arguments: &[ArgumentGroupLayout]) -> Result<(), CreatePipelineError> {
+    if arguments.len() > MAX_ARGUMENT_GROUPS as usize {
+        return Err(CreatePipelineError(
+            CreatePipelineErrorKind::TooManyArgumentGroups {
+                requested: arguments.len() as u32,
+                max: MAX_ARGUMENT_GROUPS,
+            },
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the library has shader entry point of the given stage.
+fn check_entry_stage(shader: &Shader, stage: ShaderStage) -> Result<(), CreatePipelineError> {
+    check_entry_point(shader.library.entry_points(), &shader.entry, stage)
+        .map_err(|err| CreatePipelineError(CreatePipelineErrorKind::UnknownEntryPoint(err)))
+}
+
+/// Checks that arguments used by the shader are visible to its stage.
+///
+/// Libraries compiled from MSL are not reflected and are not checked.
+fn check_shader_argument_stages(
+    shader: &Shader,
+    arguments: &[ArgumentGroupLayout],
+) -> Result<(), CreatePipelineError> {
+    match shader.library.reflect_entry(&shader.entry) {
+        None => Ok(()),
+        Some(entry) => check_argument_stages(entry, arguments).map_err(|err| {
+            CreatePipelineError(CreatePipelineErrorKind::ArgumentStageMismatch(err))
+        }),
+    }
+}
+
+/// Checks that buffers used by the shader fit into buffer range limits of the device.
+///
+/// Libraries compiled from MSL are not reflected and are not checked.
+fn check_shader_buffer_ranges(
+    shader: &Shader,
+    limits: &DeviceLimits,
+) -> Result<(), CreatePipelineError> {
+    match shader.library.reflect_entry(&shader.entry) {
+        None => Ok(()),
+        Some(entry) => check_buffer_ranges(entry, limits)
+            .map_err(|err| CreatePipelineError(CreatePipelineErrorKind::BufferRangeExceeded(err))),
+    }
+}
+
 struct CompiledMetalShader {
     code: String,
     entry_point_data: HashMap<String, EntryPointData>,
+
+    /// Code with point size written by vertex shaders.
+    /// `None` if there are no vertex shaders.
+    point_code: Option<String>,
+}
+
+/// Returns bounds check policies that provide robustness requested by features.
+fn bounds_check_policies(features: Features) -> naga::proc::BoundsCheckPolicies {
+    let mut policies = naga::proc::BoundsCheckPolicies::default();
+    if features.contains(Features::ROBUST_BUFFER_ACCESS) {
+        policies.buffer = naga::proc::BoundsCheckPolicy::ReadZeroSkipWrite;
+    }
+    if features.contains(Features::ROBUST_IMAGE_ACCESS) {
+        policies.image_load = naga::proc::BoundsCheckPolicy::ReadZeroSkipWrite;
+    }
+    policies
 }
 
+/// Naga capabilities that MSL backend can generate.
+/// Capabilities gated by device features are removed by [`shader_capabilities`].
+const MSL_CAPABILITIES: naga::valid::Capabilities = naga::valid::Capabilities::all()
+    .difference(naga::valid::Capabilities::FLOAT64)
+    .difference(naga::valid::Capabilities::CULL_DISTANCE);
+
 fn compile_shader(
     code: &[u8],
-    filename: Option<&str>,
+    files: &[SourceFile],
     lang: ShaderLanguage,
+    bounds_check_policies: naga::proc::BoundsCheckPolicies,
+    caps: naga::valid::Capabilities,
 ) -> Result<CompiledMetalShader, ShaderCompileError> {
-    let (module, info, _source_code) = parse_shader(code, filename, lang)?;
+    let (module, info, _source_code) = parse_shader(code, files, lang, caps)?;
 
     let mut options = naga::back::msl::Options {
         lang_version: (2, 4),
@@ -485,13 +1046,18 @@ fn compile_shader(
         inline_samplers: Vec::new(),
         spirv_cross_compatibility: false,
         fake_missing_bindings: false,
-        bounds_check_policies: Default::default(),
+        bounds_check_policies,
         zero_initialize_workgroup_memory: false,
     };
 
     let mut entry_point_data = HashMap::new();
+    let mut reflections = reflect_entries(&module, &info).into_iter();
 
     for (i, entry) in module.entry_points.iter().enumerate() {
+        let reflection = reflections
+            .next()
+            .expect("Reflection is collected for each entry point");
+
         let mut bindings = Bindings::new();
         let mut map = naga::back::msl::EntryPointResources::default();
         map.sizes_buffer = Some(30);
@@ -579,6 +1145,38 @@ fn compile_shader(
                                 bindings.insert(binding, next_texture_slot);
                                 next_texture_slot += 1;
                             }
+                            naga::TypeInner::BindingArray { base, size }
+                                if matches!(
+                                    module.types[base].inner,
+                                    naga::TypeInner::Image { .. }
+                                ) =>
+                            {
+                                // Bindless table occupies consecutive texture slots.
+                                // Smaller arrays reserve the same slots,
+                                // larger ones would overlap textures bound after them.
+                                if let naga::ArraySize::Constant(size) = size {
+                                    if size.get() > MAX_BINDLESS_TABLE_SIZE {
+                                        return Err(ShaderCompileError::BindingArrayTooLarge {
+                                            name: global_variable.name.clone().unwrap_or_default(),
+                                            size: size.get(),
+                                            max: MAX_BINDLESS_TABLE_SIZE,
+                                        });
+                                    }
+                                }
+
+                                map.resources.insert(
+                                    binding.clone(),
+                                    naga::back::msl::BindTarget {
+                                        buffer: None,
+                                        texture: Some(next_texture_slot),
+                                        sampler: None,
+                                        binding_array_size: Some(MAX_BINDLESS_TABLE_SIZE),
+                                        mutable: false,
+                                    },
+                                );
+                                bindings.insert(binding, next_texture_slot);
+                                next_texture_slot += MAX_BINDLESS_TABLE_SIZE as u8;
+                            }
                             _ => {}
                         }
                     }
@@ -595,6 +1193,7 @@ fn compile_shader(
                 bindings: Arc::new(bindings),
                 workgroup_size: entry.workgroup_size,
                 name: Ok(String::new()),
+                reflection: Arc::new(reflection),
             },
         );
     }
@@ -611,6 +1210,31 @@ fn compile_shader(
     )
     .map_err(ShaderCompileError::GenMsl)?;
 
+    // Metal requires point size to be written for point topology
+    // and rejects it for other topologies.
+    // Generate separate code for pipelines with point topology.
+    let has_vertex_shaders = module
+        .entry_points
+        .iter()
+        .any(|entry| entry.stage == naga::ShaderStage::Vertex);
+
+    let point_code = if has_vertex_shaders {
+        let (point_code, _) = naga::back::msl::write_string(
+            &module,
+            &info,
+            &options,
+            &naga::back::msl::PipelineOptions {
+                allow_and_force_point_size: true,
+                vertex_pulling_transform: true,
+                vertex_buffer_mappings: vec![],
+            },
+        )
+        .map_err(ShaderCompileError::GenMsl)?;
+        Some(point_code)
+    } else {
+        None
+    };
+
     // eprintln!("{}", code);
     // eprintln!("{:?}", translation.entry_point_names);
 
@@ -622,5 +1246,6 @@ fn compile_shader(
     Ok(CompiledMetalShader {
         code,
         entry_point_data,
+        point_code,
     })
 }
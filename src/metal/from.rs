@@ -271,6 +271,8 @@ impl TryFromMetal<metal::MTLPixelFormat> for PixelFormat {
             // metal::MTLPixelFormat::Depth16Unorm_Stencil8 => PixelFormat::D16UnormS8Uint,
             metal::MTLPixelFormat::Depth24Unorm_Stencil8 => PixelFormat::D24UnormS8Uint,
             metal::MTLPixelFormat::Depth32Float_Stencil8 => PixelFormat::D32FloatS8Uint,
+            metal::MTLPixelFormat::X24_Stencil8 => PixelFormat::D24UnormS8Uint,
+            metal::MTLPixelFormat::X32_Stencil8 => PixelFormat::D32FloatS8Uint,
             _ => return None,
         })
     }
@@ -456,6 +458,10 @@ impl MetalFrom<BlendFactor> for metal::MTLBlendFactor {
             BlendFactor::DstAlpha => metal::MTLBlendFactor::DestinationAlpha,
             BlendFactor::OneMinusDstAlpha => metal::MTLBlendFactor::OneMinusDestinationAlpha,
             BlendFactor::SrcAlphaSaturated => metal::MTLBlendFactor::SourceAlphaSaturated,
+            BlendFactor::Src1Color => metal::MTLBlendFactor::Source1Color,
+            BlendFactor::OneMinusSrc1Color => metal::MTLBlendFactor::OneMinusSource1Color,
+            BlendFactor::Src1Alpha => metal::MTLBlendFactor::Source1Alpha,
+            BlendFactor::OneMinusSrc1Alpha => metal::MTLBlendFactor::OneMinusSource1Alpha,
             // BlendFactor::BlendColor => metal::MTLBlendFactor::BlendColor,
             // BlendFactor::OneMinusBlendColor => metal::MTLBlendFactor::OneMinusBlendColor,
         }
@@ -9,13 +9,14 @@ use metal::MTLTextureType;
 use crate::{
     generic::{
         ArgumentKind, Automatic, ComponentSwizzle, Extent1, Extent2, Extent3, ImageExtent,
-        OutOfMemory, PixelFormat, Sampled, Storage, Swizzle, ViewDesc,
+        ImageSummary, OutOfMemory, PixelFormat, ResourceId, Sampled, Storage, Swizzle, ViewAspect,
+        ViewDesc, ViewError,
     },
     ImageUsage,
 };
 
 use super::{
-    arguments::ArgumentsField,
+    arguments::{ArgumentsField, RetainedArgument},
     from::{MetalInto, TryIntoMetal, TryMetalInto},
     Device,
 };
@@ -23,6 +24,10 @@ use super::{
 #[derive(Clone, Debug)]
 pub struct Image {
     texture: metal::Texture,
+    id: ResourceId,
+
+    /// Identifier of the image this view belongs to.
+    image_id: ResourceId,
 }
 
 impl PartialEq for Image {
@@ -43,8 +48,21 @@ unsafe impl Send for Image {}
 unsafe impl Sync for Image {}
 
 impl Image {
-    pub(super) fn new(texture: metal::Texture) -> Self {
-        Image { texture }
+    pub(super) fn new(texture: metal::Texture, id: ResourceId) -> Self {
+        Image {
+            texture,
+            id,
+            image_id: id,
+        }
+    }
+
+    /// Returns new view of the same image with given texture.
+    fn new_view(&self, texture: metal::Texture, device: &Device) -> Self {
+        Image {
+            texture,
+            id: device.new_resource_id(),
+            image_id: self.image_id,
+        }
     }
 
     pub(super) fn metal(&self) -> &metal::TextureRef {
@@ -52,8 +70,38 @@ impl Image {
     }
 }
 
+#[hidden_trait::expose]
+impl crate::traits::Resource for Image {
+    #[inline(always)]
+    fn id(&self) -> ResourceId {
+        self.id
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{:#?}",
+            ImageSummary {
+                id: self.id,
+                name: None,
+                extent: self.extent(),
+                format: self.format(),
+                usage: self.usage(),
+                levels: self.levels(),
+                layers: self.layers(),
+            }
+        )
+    }
+}
+
 #[hidden_trait::expose]
 impl crate::traits::Image for Image {
+    type Device = Device;
+
+    #[inline(always)]
+    fn image_id(&self) -> ResourceId {
+        self.image_id
+    }
+
     fn format(&self) -> PixelFormat {
         self.texture.pixel_format().expect_metal_into()
     }
@@ -91,20 +139,48 @@ impl crate::traits::Image for Image {
     }
 
     fn usage(&self) -> ImageUsage {
-        self.texture.usage().metal_into()
+        let usage: ImageUsage = self.texture.usage().metal_into();
+        if self.texture.storage_mode() == metal::MTLStorageMode::Memoryless {
+            // Memoryless textures can't be copied.
+            (usage | ImageUsage::TRANSIENT) - (ImageUsage::TRANSFER_SRC | ImageUsage::TRANSFER_DST)
+        } else {
+            usage
+        }
     }
 
-    fn view(&self, _device: &Device, desc: ViewDesc) -> Result<Image, OutOfMemory> {
+    fn view(&self, device: &Device, desc: ViewDesc) -> Result<Image, ViewError> {
         use foreign_types::{ForeignType, ForeignTypeRef};
         use objc::*;
 
-        let pixel_format = desc.format.expect_into_metal();
+        desc.check_bounds(self.texture.label(), self.layers(), self.levels())?;
+        desc.check_aspect(self.texture.label())?;
+
+        // Drawable textures are framebuffer-only and can't be reinterpreted.
+        if self.texture.framebuffer_only() && desc.format != self.format() {
+            return Err(ViewError::SwapchainFormat {
+                image: self.texture.label().to_owned(),
+                format: self.format(),
+                view_format: desc.format,
+            });
+        }
+
+        // Sampling depth-stencil texture reads depth.
+        // Stencil is read through a view with stencil-only format.
+        let pixel_format = match (desc.aspect, desc.format) {
+            (ViewAspect::StencilOnly, PixelFormat::D24UnormS8Uint) => {
+                metal::MTLPixelFormat::X24_Stencil8
+            }
+            (ViewAspect::StencilOnly, PixelFormat::D32FloatS8Uint) => {
+                metal::MTLPixelFormat::X32_Stencil8
+            }
+            _ => desc.format.expect_into_metal(),
+        };
         let root_texture = self.texture.parent_texture().unwrap_or(&self.texture);
 
         if desc.swizzle == Swizzle::IDENTITY {
             if desc.base_layer == 0 && desc.base_level == 0 {
-                let texture = root_texture.new_texture_view(desc.format.expect_into_metal());
-                Ok(Image { texture })
+                let texture = root_texture.new_texture_view(pixel_format);
+                Ok(self.new_view(texture, device))
             } else {
                 let base_layer = self.texture.parent_relative_slice() as u32 + desc.base_layer;
                 let base_level = self.texture.mipmap_level_count() as u32 + desc.base_level;
@@ -115,7 +191,7 @@ impl crate::traits::Image for Image {
                     metal::NSRange::new(base_level.into(), desc.levels.into()),
                     metal::NSRange::new(base_layer.into(), desc.layers.into()),
                 );
-                Ok(Image { texture })
+                Ok(self.new_view(texture, device))
             }
         } else {
             let base_layer = self.texture.parent_relative_slice() as u32 + desc.base_layer;
@@ -135,10 +211,33 @@ impl crate::traits::Image for Image {
                 ]
             };
 
-            Ok(Image { texture })
+            Ok(self.new_view(texture, device))
         }
     }
 
+    fn srgb_view(&self, device: &Device) -> Result<Image, ViewError> {
+        let format = self.format();
+        let Some(srgb) = format.srgb_variant() else {
+            return Err(ViewError::NoSrgbVariant {
+                image: self.texture.label().to_owned(),
+                format,
+            });
+        };
+
+        // sRGB and linear variants don't require `PixelFormatView` usage.
+        let desc = ViewDesc::new(srgb)
+            .layers(0..self.layers())
+            .levels(0..self.levels());
+        self.view(device, desc)
+    }
+
+    fn linear_view(&self, device: &Device) -> Result<Image, ViewError> {
+        let desc = ViewDesc::new(self.format().linear_variant())
+            .layers(0..self.layers())
+            .levels(0..self.levels());
+        self.view(device, desc)
+    }
+
     fn detached(&self) -> bool {
         use foreign_types::ForeignType;
         use metal::NSUInteger;
@@ -202,18 +301,8 @@ impl ArgumentsField<Automatic> for Image {
     const SIZE: usize = 1;
 
     #[inline(always)]
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_vertex_texture(slot.into(), Some(&self.texture));
-    }
-
-    #[inline(always)]
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_fragment_texture(slot.into(), Some(&self.texture));
-    }
-
-    #[inline(always)]
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
-        encoder.set_texture(slot.into(), Some(&self.texture));
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Texture(self.texture.clone())
     }
 }
 
@@ -222,18 +311,8 @@ impl ArgumentsField<Sampled> for Image {
     const SIZE: usize = 1;
 
     #[inline(always)]
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_vertex_texture(slot.into(), Some(&self.texture));
-    }
-
-    #[inline(always)]
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_fragment_texture(slot.into(), Some(&self.texture));
-    }
-
-    #[inline(always)]
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
-        encoder.set_texture(slot.into(), Some(&self.texture));
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Texture(self.texture.clone())
     }
 }
 
@@ -242,17 +321,7 @@ impl ArgumentsField<Storage> for Image {
     const SIZE: usize = 1;
 
     #[inline(always)]
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_vertex_texture(slot.into(), Some(&self.texture));
-    }
-
-    #[inline(always)]
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_fragment_texture(slot.into(), Some(&self.texture));
-    }
-
-    #[inline(always)]
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
-        encoder.set_texture(slot.into(), Some(&self.texture));
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Texture(self.texture.clone())
     }
 }
@@ -1,19 +1,57 @@
-use std::{convert::Infallible, fmt};
+use std::fmt;
 
 use crate::generic::{
-    Capabilities, CreateError, DeviceCapabilities, DeviceDesc, FamilyCapabilities, Features,
-    LoadError, QueueFlags,
+    Capabilities, CreateError, DeviceCapabilities, DeviceDesc, DeviceLimits, FamilyCapabilities,
+    Features, LoadError, QueueFlags,
 };
 
-use super::{Device, Queue};
+use super::{
+    Device, Queue, MAX_ARGUMENT_GROUPS, MAX_BINDLESS_TABLE_SIZE, MAX_STORAGE_BUFFER_RANGE,
+};
+
+#[derive(Debug)]
+pub(crate) enum LoadErrorKind {
+    /// No Metal device is available.
+    Unsupported,
+}
+
+impl LoadErrorKind {
+    pub(crate) fn is_library_not_found(&self) -> bool {
+        false
+    }
+
+    pub(crate) fn is_unsupported(&self) -> bool {
+        matches!(self, LoadErrorKind::Unsupported)
+    }
+
+    pub(crate) fn missing_layers(&self) -> &[String] {
+        &[]
+    }
+}
 
-pub(crate) type LoadErrorKind = Infallible;
+impl fmt::Display for LoadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadErrorKind::Unsupported => write!(
+                f,
+                "Metal is not supported on this system, \
+                 Metal-capable GPU and newer operating system version are required"
+            ),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub(crate) enum CreateErrorKind {
     FailedToCreateDevice,
 }
 
+impl CreateErrorKind {
+    pub(crate) fn missing_extensions(&self) -> &[String] {
+        &[]
+    }
+}
+
 impl fmt::Display for CreateErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -33,10 +71,50 @@ impl Instance {
     where
         Self: Sized,
     {
+        // Metal is not available on old systems and systems without Metal-capable GPU.
+        let device =
+            metal::Device::system_default().ok_or(LoadError(LoadErrorKind::Unsupported))?;
+
+        // Metal doesn't guarantee robust resource access,
+        // it is provided by bounds checks in shaders translated by naga.
+        let mut features = Features::SAMPLER_ANISOTROPY
+            | Features::DUAL_SOURCE_BLEND
+            | Features::ROBUST_BUFFER_ACCESS
+            | Features::ROBUST_IMAGE_ACCESS
+            | Features::SHADER_INT64;
+
+        if device.supports_raytracing() {
+            features |= Features::RAY_TRACING;
+        }
+
+        // SIMD-group functions are available on Apple7 and Mac2 families.
+        if device.supports_family(metal::MTLGPUFamily::Apple7)
+            || device.supports_family(metal::MTLGPUFamily::Mac2)
+        {
+            features |= Features::SUBGROUP;
+        }
+
+        // Bindless table needs 128 texture slots of Apple4 and Mac2 families.
+        let mut max_bindless_table_size = 0;
+        if device.supports_family(metal::MTLGPUFamily::Apple4)
+            || device.supports_family(metal::MTLGPUFamily::Mac2)
+        {
+            features |= Features::DESCRIPTOR_INDEXING;
+            max_bindless_table_size = MAX_BINDLESS_TABLE_SIZE;
+        }
+
         Ok(Instance {
             capabilities: Capabilities {
                 devices: vec![DeviceCapabilities {
-                    features: Features::empty(),
+                    features,
+                    limits: DeviceLimits {
+                        max_argument_groups: MAX_ARGUMENT_GROUPS,
+                        max_uniform_buffer_range: device.max_buffer_length(),
+                        max_storage_buffer_range: device
+                            .max_buffer_length()
+                            .min(MAX_STORAGE_BUFFER_RANGE),
+                        max_bindless_table_size,
+                    },
                     families: vec![FamilyCapabilities {
                         queue_flags: QueueFlags::GRAPHICS
                             | QueueFlags::COMPUTE
@@ -57,6 +135,9 @@ impl fmt::Debug for Instance {
 
 #[hidden_trait::expose]
 impl crate::traits::Instance for Instance {
+    type Device = Device;
+    type Queue = Queue;
+
     fn capabilities(&self) -> &Capabilities {
         &self.capabilities
     }
@@ -70,7 +151,9 @@ impl crate::traits::Instance for Instance {
         let device = metal::Device::system_default()
             .ok_or(CreateError(CreateErrorKind::FailedToCreateDevice))?;
 
-        let device = Device::new(device, info.queues.len());
+        // Vendor and driver are not known, only forced quirks are applied.
+        // Metal manages resource memory itself, allocator configuration is ignored.
+        let device = Device::new(device, info.queues.len(), info.features, info.quirks);
 
         let queues = (0..info.queues.len())
             .map(|_| Queue::new(device.clone(), device.metal().new_command_queue()))
@@ -1,5 +1,6 @@
 mod acst;
 mod arguments;
+mod bindless;
 mod buffer;
 mod command;
 mod compute_pipeline;
@@ -15,10 +16,11 @@ mod surface;
 
 pub use self::{
     acst::{Blas, Tlas},
-    buffer::Buffer,
+    bindless::BindlessTable,
+    buffer::{Buffer, MappedSlice},
     command::{
         AccelerationStructureCommandEncoder, CommandBuffer, CommandEncoder, ComputeCommandEncoder,
-        CopyCommandEncoder, RenderCommandEncoder,
+        CopyCommandEncoder, RenderCommandEncoder, ReusableCommandBuffer, ReusableCommandEncoder,
     },
     compute_pipeline::ComputePipeline,
     device::Device,
@@ -46,6 +48,27 @@ fn out_of_bounds() -> ! {
 
 const MAX_VERTEX_BUFFERS: u32 = 31;
 
+/// Buffer arguments of all groups share buffer slots,
+/// so number of groups is limited by number of slots.
+const MAX_ARGUMENT_GROUPS: u32 = MAX_VERTEX_BUFFERS;
+
+/// Largest storage buffer that shaders can access whole.
+/// Shaders translated by naga receive buffer sizes as 32-bit values.
+const MAX_STORAGE_BUFFER_RANGE: u64 = u32::MAX as u64;
+
+/// Bindless table occupies this many texture slots in shaders.
+/// Leaves half of 128 texture slots of Apple4 and Mac2 families to other arguments.
+const MAX_BINDLESS_TABLE_SIZE: u32 = 64;
+
+/// Maximum 1D and 2D texture size on Apple GPU families 3 and later.
+const MAX_TEXTURE_DIMENSION_2D: u32 = 16384;
+
+/// Maximum 3D texture size.
+const MAX_TEXTURE_DIMENSION_3D: u32 = 2048;
+
+/// Maximum stride of vertex buffer layout in Metal feature set tables.
+const MAX_VERTEX_BUFFER_STRIDE: u32 = 2048;
+
 pub mod for_macro {
     pub use crate::generic::DeviceRepr;
 
@@ -1,15 +1,34 @@
-use std::{fmt, ops::Deref};
+use std::{collections::VecDeque, fmt, ops::Deref};
 
 use foreign_types::ForeignType;
 
-use crate::generic::{DeviceError, OutOfMemory, PipelineStages};
+use crate::generic::{
+    CompactionPlan, DeviceError, OutOfMemory, PipelineStages, QueueFlags, QueuePoolConfig,
+    QueueStats, ReusableCommandsError, SubmissionId, FRAMES_IN_FLIGHT,
+};
 
-use super::{CommandBuffer, CommandEncoder, Device, Frame};
+use super::{
+    command::SuspendedPass, Buffer, CommandBuffer, CommandEncoder, Device, Frame,
+    ReusableCommandBuffer, ReusableCommandEncoder,
+};
 
 pub struct Queue {
     device: Device,
     queue: metal::CommandQueue,
     last_cbuf: Option<metal::CommandBuffer>,
+
+    /// Last command buffers submitted with checkpoints that may be pending.
+    check_points: VecDeque<metal::CommandBuffer>,
+    check_point_index: u64,
+
+    /// Latest submission made to the queue.
+    last_submission: u64,
+
+    /// Signaled with index of each submission when it completes.
+    timeline: metal::SharedEvent,
+
+    /// Render pass suspended by the last command encoder that recorded a split pass.
+    suspended: SuspendedPass,
 }
 
 unsafe impl Send for Queue {}
@@ -26,11 +45,74 @@ impl fmt::Debug for Queue {
 
 impl Queue {
     pub(super) fn new(device: Device, queue: metal::CommandQueue) -> Self {
+        let timeline = device.metal().new_shared_event();
         Queue {
             device,
             queue,
             last_cbuf: None,
+            check_points: VecDeque::new(),
+            check_point_index: 0,
+            last_submission: 0,
+            timeline,
+            suspended: SuspendedPass::default(),
+        }
+    }
+
+    /// Waits for the earliest checkpoint when too many frames are in flight.
+    ///
+    /// Checkpoint is counted even if nothing was committed yet, same as on Vulkan.
+    fn check_point(&mut self) {
+        self.check_point_index += 1;
+
+        let Some(last_cbuf) = &self.last_cbuf else {
+            return;
+        };
+
+        self.check_points.push_back(last_cbuf.clone());
+
+        if self.check_points.len() >= FRAMES_IN_FLIGHT {
+            if let Some(cbuf) = self.check_points.pop_front() {
+                cbuf.wait_until_completed();
+            }
+        }
+    }
+
+    /// Commits command buffers, labeling them if label is provided.
+    ///
+    /// Last command buffer signals the timeline with index of the submission.
+    fn commit<I>(&mut self, command_buffers: I, label: Option<&str>) -> SubmissionId
+    where
+        I: IntoIterator<Item = CommandBuffer>,
+    {
+        self.last_submission += 1;
+
+        let mut last = None;
+        for cbuf in command_buffers {
+            cbuf.check_split_pass();
+            if let Some(label) = label {
+                cbuf.set_label(label);
+            }
+            if let Some(prev) = last.replace(cbuf) {
+                prev.commit();
+            }
         }
+
+        let last_cbuf = match last {
+            Some(cbuf) => {
+                cbuf.signal(&self.timeline, self.last_submission);
+                cbuf.commit()
+            }
+            None => {
+                // Empty submission still has to advance the timeline.
+                let cbuf = self.queue.new_command_buffer().to_owned();
+                cbuf.encode_signal_event(&self.timeline, self.last_submission);
+                cbuf.commit();
+                cbuf
+            }
+        };
+
+        self.last_cbuf = Some(last_cbuf);
+        SubmissionId::new(self.last_submission)
     }
 }
 
@@ -45,6 +127,13 @@ impl Deref for Queue {
 
 #[hidden_trait::expose]
 impl crate::traits::Queue for Queue {
+    type Device = Device;
+    type CommandEncoder = CommandEncoder;
+    type CommandBuffer = CommandBuffer;
+    type ReusableCommandEncoder = ReusableCommandEncoder;
+    type ReusableCommandBuffer = ReusableCommandBuffer;
+    type Frame = Frame;
+
     fn device(&self) -> &Device {
         &self.device
     }
@@ -53,29 +142,119 @@ impl crate::traits::Queue for Queue {
         0
     }
 
+    fn flags(&self) -> QueueFlags {
+        QueueFlags::GRAPHICS | QueueFlags::COMPUTE | QueueFlags::TRANSFER
+    }
+
+    fn stats(&self) -> QueueStats {
+        // Metal binds arguments without descriptor pools.
+        QueueStats::default()
+    }
+
+    #[inline(always)]
+    fn set_pool_config(&mut self, config: QueuePoolConfig) {
+        assert!(
+            config.max_pools > 0,
+            "Queue must be allowed to have a command pool"
+        );
+        // Metal command buffers are not allocated from pools.
+    }
+
+    #[inline(always)]
+    fn pending_resource_estimate(&self) -> usize {
+        // Metal command buffers retain resources themselves.
+        0
+    }
+
+    #[inline(always)]
+    fn check_point_index(&self) -> u64 {
+        self.check_point_index
+    }
+
     fn new_command_encoder(&mut self) -> Result<CommandEncoder, OutOfMemory> {
         Ok(CommandEncoder::new(
             self.device.metal().to_owned(),
             self.queue.new_command_buffer().to_owned(),
+            self.device.well_known_samplers().clone(),
+            self.suspended.clone(),
         ))
     }
 
-    fn submit<I>(&mut self, command_buffers: I, _check_point: bool) -> Result<(), DeviceError>
+    fn submit<I>(
+        &mut self,
+        command_buffers: I,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>
     where
         I: IntoIterator<Item = CommandBuffer>,
     {
-        let last_cbuf = command_buffers
-            .into_iter()
-            .map(CommandBuffer::commit)
-            .last();
+        let id = self.commit(command_buffers, None);
 
-        if let Some(last_cbuf) = last_cbuf {
-            self.last_cbuf = Some(last_cbuf);
+        if check_point {
+            self.check_point();
         }
 
+        Ok(id)
+    }
+
+    /// Label is set on Metal command buffers of the submission.
+    fn submit_labeled<I>(
+        &mut self,
+        command_buffers: I,
+        label: &str,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>
+    where
+        I: IntoIterator<Item = CommandBuffer>,
+    {
+        let id = self.commit(command_buffers, Some(label));
+
+        if check_point {
+            self.check_point();
+        }
+
+        Ok(id)
+    }
+
+    fn submit_one(
+        &mut self,
+        command_buffer: CommandBuffer,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError> {
+        let id = self.commit(std::iter::once(command_buffer), None);
+
+        if check_point {
+            self.check_point();
+        }
+
+        Ok(id)
+    }
+
+    fn last_completed_submission(&self) -> SubmissionId {
+        SubmissionId::new(self.timeline.signaled_value())
+    }
+
+    /// Metal command buffers complete in order,
+    /// so waits for the latest submission if the requested one is not complete.
+    fn wait_submission(&mut self, id: SubmissionId) -> Result<(), DeviceError> {
+        assert!(
+            id.get() <= self.last_submission,
+            "Submission {id} was not made to this queue"
+        );
+
+        if self.timeline.signaled_value() < id.get() {
+            if let Some(last_cbuf) = &self.last_cbuf {
+                last_cbuf.wait_until_completed();
+            }
+        }
         Ok(())
     }
 
+    #[inline(always)]
+    fn last_breadcrumbs(&self) -> Vec<u32> {
+        Vec::new()
+    }
+
     /// Drop command buffers without submitting them to the queue.
     fn drop_command_buffer<I>(&mut self, command_buffers: I)
     where
@@ -84,12 +263,41 @@ impl crate::traits::Queue for Queue {
         command_buffers.into_iter().for_each(drop);
     }
 
+    fn new_command_encoder_reusable(
+        &mut self,
+    ) -> Result<ReusableCommandEncoder, ReusableCommandsError> {
+        Err(ReusableCommandsError::Unsupported)
+    }
+
+    fn submit_reusable(
+        &mut self,
+        command_buffer: &ReusableCommandBuffer,
+        _check_point: bool,
+    ) -> Result<SubmissionId, DeviceError> {
+        match *command_buffer {}
+    }
+
+    fn execute_compaction(
+        &mut self,
+        plan: &CompactionPlan,
+        _buffers: &mut [Buffer],
+    ) -> Result<(), DeviceError> {
+        debug_assert!(plan.is_empty(), "Metal plans no compaction");
+        Ok(())
+    }
+
     fn sync_frame(&mut self, _frame: &mut Frame, _before: PipelineStages) {}
 
-    fn wait_idle(&self) -> Result<(), OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), DeviceError> {
         if let Some(last_cbuf) = &self.last_cbuf {
             last_cbuf.wait_until_completed();
         }
         Ok(())
     }
+
+    /// Metal command buffers release resources on completion.
+    #[inline(always)]
+    fn reclaim(&mut self) -> Result<(), OutOfMemory> {
+        Ok(())
+    }
 }
@@ -1,5 +1,12 @@
 use std::{fmt, sync::Arc};
 
+#[cfg(any(debug_assertions, feature = "debug"))]
+use crate::generic::PixelFormat;
+use crate::generic::{
+    ArgumentGroupInfo, ArgumentStageMismatch, BufferRangeExceeded, DualSourceBlendError,
+    PipelineSummary, ResourceId, StageInterfaceMismatch, UnknownEntryPoint,
+};
+
 use super::shader::Bindings;
 
 #[derive(Clone)]
@@ -9,6 +16,16 @@ pub struct RenderPipeline {
     vertex_bindings: Option<Arc<Bindings>>,
     fragment_bindings: Option<Arc<Bindings>>,
     vertex_buffers_count: u32,
+    id: ResourceId,
+    summary: Arc<PipelineSummary>,
+
+    /// Formats of color targets.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    color_formats: Arc<[PixelFormat]>,
+
+    /// Format of depth-stencil target.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    depth_format: Option<PixelFormat>,
 }
 
 unsafe impl Send for RenderPipeline {}
@@ -21,6 +38,10 @@ impl RenderPipeline {
         vertex_bindings: Option<Arc<Bindings>>,
         fragment_bindings: Option<Arc<Bindings>>,
         vertex_buffers_count: u32,
+        id: ResourceId,
+        summary: PipelineSummary,
+        #[cfg(any(debug_assertions, feature = "debug"))] color_formats: Vec<PixelFormat>,
+        #[cfg(any(debug_assertions, feature = "debug"))] depth_format: Option<PixelFormat>,
     ) -> Self {
         RenderPipeline {
             state,
@@ -28,6 +49,12 @@ impl RenderPipeline {
             vertex_bindings,
             fragment_bindings,
             vertex_buffers_count,
+            id,
+            summary: Arc::new(summary),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            color_formats: color_formats.into(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            depth_format,
         }
     }
 
@@ -47,15 +74,68 @@ impl RenderPipeline {
         self.fragment_bindings.clone()
     }
 
+    pub(super) fn summary(&self) -> &Arc<PipelineSummary> {
+        &self.summary
+    }
+
     pub(super) fn vertex_buffers_count(&self) -> u32 {
         self.vertex_buffers_count
     }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn color_formats(&self) -> &[PixelFormat] {
+        &self.color_formats
+    }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn depth_format(&self) -> Option<PixelFormat> {
+        self.depth_format
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Resource for RenderPipeline {
+    #[inline(always)]
+    fn id(&self) -> ResourceId {
+        self.id
+    }
+
+    fn describe(&self) -> String {
+        self.summary.describe(self.id)
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Pipeline for RenderPipeline {
+    #[inline(always)]
+    fn argument_groups(&self) -> &[ArgumentGroupInfo] {
+        self.summary.arguments()
+    }
+
+    #[inline(always)]
+    fn constants_size(&self) -> usize {
+        self.summary.constants()
+    }
 }
 
 #[derive(Debug)]
 pub enum CreatePipelineErrorKind {
     InvalidShaderEntry,
     FailedToBuildPipeline(String),
+    TooManyArgumentGroups {
+        requested: u32,
+        max: u32,
+    },
+    VertexStrideTooLarge {
+        buffer: u32,
+        stride: u32,
+        limit: u32,
+    },
+    ArgumentStageMismatch(ArgumentStageMismatch),
+    BufferRangeExceeded(BufferRangeExceeded),
+    DualSourceBlend(DualSourceBlendError),
+    StageInterfaceMismatch(StageInterfaceMismatch),
+    UnknownEntryPoint(UnknownEntryPoint),
 }
 
 impl fmt::Display for CreatePipelineErrorKind {
@@ -67,6 +147,31 @@ impl fmt::Display for CreatePipelineErrorKind {
             CreatePipelineErrorKind::FailedToBuildPipeline(err) => {
                 write!(f, "Failed to build pipeline: {}", err)
             }
+            CreatePipelineErrorKind::TooManyArgumentGroups { requested, max } => {
+                write!(
+                    f,
+                    "Pipeline uses {} argument groups, at most {} are supported",
+                    requested, max
+                )
+            }
+            CreatePipelineErrorKind::DualSourceBlend(err) => fmt::Display::fmt(err, f),
+            CreatePipelineErrorKind::ArgumentStageMismatch(mismatch) => {
+                fmt::Display::fmt(mismatch, f)
+            }
+            CreatePipelineErrorKind::BufferRangeExceeded(err) => fmt::Display::fmt(err, f),
+            CreatePipelineErrorKind::VertexStrideTooLarge {
+                buffer,
+                stride,
+                limit,
+            } => write!(
+                f,
+                "Vertex buffer {} stride {} exceeds Metal limit {}",
+                buffer, stride, limit
+            ),
+            CreatePipelineErrorKind::StageInterfaceMismatch(mismatch) => {
+                fmt::Display::fmt(mismatch, f)
+            }
+            CreatePipelineErrorKind::UnknownEntryPoint(err) => fmt::Display::fmt(err, f),
         }
     }
 }
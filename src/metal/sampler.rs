@@ -1,15 +1,127 @@
-use crate::generic::{ArgumentKind, Automatic};
+use std::sync::Arc;
 
-use super::arguments::ArgumentsField;
+use metal::{NSUInteger, SamplerDescriptor};
+use parking_lot::Mutex;
+
+use crate::generic::{
+    ArgumentKind, Automatic, Quirks, ResourceId, ResourceIds, SamplerDesc, SamplerSummary,
+    WellKnownSampler,
+};
+
+use super::{
+    arguments::{ArgumentsField, RetainedArgument},
+    from::IntoMetal,
+};
 
 #[derive(Clone)]
 pub struct Sampler {
     sampler: metal::SamplerState,
+    id: ResourceId,
+    desc: SamplerDesc,
 }
 
 impl Sampler {
-    pub(super) fn new(sampler: metal::SamplerState) -> Self {
-        Self { sampler }
+    pub(super) fn new(sampler: metal::SamplerState, id: ResourceId, desc: SamplerDesc) -> Self {
+        Self { sampler, id, desc }
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Resource for Sampler {
+    #[inline(always)]
+    fn id(&self) -> ResourceId {
+        self.id
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{:#?}",
+            SamplerSummary {
+                id: self.id,
+                desc: &self.desc,
+            }
+        )
+    }
+}
+
+pub(super) fn new_sampler(
+    device: &metal::DeviceRef,
+    id: ResourceId,
+    desc: SamplerDesc,
+    quirks: Quirks,
+) -> Sampler {
+    let mdesc = SamplerDescriptor::new();
+    mdesc.set_min_filter(desc.min_filter.into_metal());
+    mdesc.set_mag_filter(desc.mag_filter.into_metal());
+    mdesc.set_mip_filter(desc.mip_map_mode.into_metal());
+    mdesc.set_address_mode_s(desc.address_mode[0].into_metal());
+    mdesc.set_address_mode_t(desc.address_mode[1].into_metal());
+    mdesc.set_address_mode_r(desc.address_mode[2].into_metal());
+    if let Some(anisotropy) = desc.anisotropy {
+        mdesc.set_max_anisotropy(clamp_anisotropy(anisotropy, quirks) as NSUInteger);
+    }
+    mdesc.set_lod_min_clamp(desc.min_lod);
+    mdesc.set_lod_max_clamp(desc.max_lod);
+    mdesc.set_normalized_coordinates(desc.normalized);
+    let state = device.new_sampler(&mdesc);
+    Sampler::new(state, id, desc)
+}
+
+/// Clamps sampler anisotropy to `[1, 16]` supported by Metal,
+/// upper bound is lowered to `8` by [`Quirks::CLAMP_ANISOTROPY_TO_8`].
+fn clamp_anisotropy(anisotropy: f32, quirks: Quirks) -> f32 {
+    let max = if quirks.contains(Quirks::CLAMP_ANISOTROPY_TO_8) {
+        8.0
+    } else {
+        16.0
+    };
+    let clamped = anisotropy.clamp(1.0, max);
+    if clamped != anisotropy {
+        tracing::warn!(
+            "Sampler anisotropy {anisotropy} is out of supported range [1, {max}], clamping to {clamped}"
+        );
+    }
+    clamped
+}
+
+/// Samplers shared by the device and its command encoders.
+/// Created on first use and kept until device is dropped.
+pub(super) struct WellKnownSamplers {
+    device: metal::Device,
+    resource_ids: Arc<ResourceIds>,
+    quirks: Quirks,
+    samplers: Mutex<[Option<Sampler>; WellKnownSampler::COUNT]>,
+}
+
+unsafe impl Sync for WellKnownSamplers {}
+unsafe impl Send for WellKnownSamplers {}
+
+impl WellKnownSamplers {
+    pub(super) fn new(
+        device: metal::Device,
+        resource_ids: Arc<ResourceIds>,
+        quirks: Quirks,
+    ) -> Self {
+        WellKnownSamplers {
+            device,
+            resource_ids,
+            quirks,
+            samplers: Mutex::new([const { None }; WellKnownSampler::COUNT]),
+        }
+    }
+
+    pub(super) fn get(&self, kind: WellKnownSampler) -> Sampler {
+        let mut samplers = self.samplers.lock();
+        samplers[kind.index()]
+            .get_or_insert_with(|| {
+                new_sampler(
+                    &self.device,
+                    self.resource_ids.next(),
+                    kind.desc(),
+                    self.quirks,
+                )
+            })
+            .clone()
     }
 }
 
@@ -17,16 +129,23 @@ impl ArgumentsField<Automatic> for Sampler {
     const KIND: ArgumentKind = ArgumentKind::Sampler;
     const SIZE: usize = 1;
 
-    fn bind_vertex(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_vertex_sampler_state(slot.into(), Some(&self.sampler));
-    }
     #[inline(always)]
-    fn bind_fragment(&self, slot: u32, encoder: &metal::RenderCommandEncoderRef) {
-        encoder.set_fragment_sampler_state(slot.into(), Some(&self.sampler));
+    fn retain(&self) -> RetainedArgument {
+        RetainedArgument::Sampler(self.sampler.clone())
     }
+}
 
-    #[inline(always)]
-    fn bind_compute(&self, slot: u32, encoder: &metal::ComputeCommandEncoderRef) {
-        encoder.set_sampler_state(slot.into(), Some(&self.sampler));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anisotropy_clamped() {
+        assert_eq!(clamp_anisotropy(4.0, Quirks::empty()), 4.0);
+        assert_eq!(clamp_anisotropy(16.0, Quirks::empty()), 16.0);
+        assert_eq!(clamp_anisotropy(32.0, Quirks::empty()), 16.0);
+        assert_eq!(clamp_anisotropy(0.5, Quirks::empty()), 1.0);
+        assert_eq!(clamp_anisotropy(16.0, Quirks::CLAMP_ANISOTROPY_TO_8), 8.0);
+        assert_eq!(clamp_anisotropy(4.0, Quirks::CLAMP_ANISOTROPY_TO_8), 4.0);
     }
 }
@@ -1,8 +1,12 @@
-use std::{borrow::Cow, fmt, sync::Arc};
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::{Arc, OnceLock},
+};
 
 use hashbrown::HashMap;
 
-use crate::generic::{Shader, ShaderCompileError};
+use crate::generic::{EntryPoint, EntryReflection, Shader, ShaderCompileError};
 
 #[derive(Clone, Copy, Debug, PartialEq, Hash)]
 pub struct GroupBindings {
@@ -21,14 +25,29 @@ pub struct Bindings {
 }
 
 impl Bindings {
+    /// Bindings of a stage that uses no arguments.
+    pub const UNUSED: Self = Bindings::new();
+
     #[inline(always)]
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Bindings {
             groups: [GroupBindings::INVALID; 16],
             push_constants: None,
         }
     }
 
+    /// Returns bindings to re-apply arguments group to a stage.
+    ///
+    /// Stage without bindings uses argument indices as slots for group 0
+    /// and has no slots for other groups.
+    #[inline(always)]
+    pub(super) fn for_group(bindings: Option<&Self>, group: usize) -> Option<&Self> {
+        match bindings {
+            None if group != 0 => Some(&Self::UNUSED),
+            bindings => bindings,
+        }
+    }
+
     #[inline(always)]
     pub fn insert(&mut self, binding: naga::ResourceBinding, slot: u8) {
         self.groups[binding.group as usize].bindings[binding.binding as usize] = slot;
@@ -40,25 +59,66 @@ impl Bindings {
     }
 }
 
+/// Checks if arguments group bound with `old` bindings
+/// must be bound again to be visible to the stage with `new` bindings.
+///
+/// Groups not used by the new stage are left as is.
+#[inline(always)]
+pub(super) fn group_slots_changed(
+    old: Option<&Bindings>,
+    new: Option<&Bindings>,
+    group: usize,
+) -> bool {
+    match (old, new) {
+        (_, Some(new)) if new.groups[group] == GroupBindings::INVALID => false,
+        (Some(old), Some(new)) => old.groups[group] != new.groups[group],
+        (None, Some(_)) => true,
+        (Some(_), None) => group == 0,
+        (None, None) => false,
+    }
+}
+
 #[derive(Clone)]
 pub(super) struct EntryPointData {
     pub bindings: Arc<Bindings>,
     pub workgroup_size: [u32; 3],
     pub name: Result<String, naga::back::msl::EntryPointError>,
+    pub reflection: Arc<EntryReflection>,
+}
+
+/// Options used to compile MSL code.
+pub(super) fn compile_options() -> metal::CompileOptions {
+    let options = metal::CompileOptions::new();
+    options.set_language_version(metal::MTLLanguageVersion::V2_2);
+    options
+}
+
+/// Variant of the library used for point topology.
+/// Compiled on first use.
+struct PointLibrary {
+    code: String,
+    library: OnceLock<Option<metal::Library>>,
 }
 
 #[derive(Clone)]
 pub struct Library {
     library: metal::Library,
     entry_point_data: HashMap<String, EntryPointData>,
+    entry_points: Arc<[EntryPoint]>,
+    point: Option<Arc<PointLibrary>>,
 }
 
+unsafe impl Send for Library {}
+unsafe impl Sync for Library {}
+
 impl Library {
     #[cfg_attr(feature = "inline-more", inline)]
     pub(super) fn new(library: metal::Library) -> Self {
         Library {
             library,
             entry_point_data: HashMap::new(),
+            entry_points: Arc::new([]),
+            point: None,
         }
     }
 
@@ -66,10 +126,27 @@ impl Library {
     pub(super) fn with_entry_point_data(
         library: metal::Library,
         entry_point_data: HashMap<String, EntryPointData>,
+        point_code: Option<String>,
     ) -> Self {
+        let mut entry_points = entry_point_data
+            .iter()
+            .map(|(name, ep)| EntryPoint {
+                name: name.as_str().into(),
+                stage: ep.reflection.stage,
+            })
+            .collect::<Vec<_>>();
+        entry_points.sort_by(|a, b| a.name.cmp(&b.name));
+
         Library {
             library,
             entry_point_data,
+            entry_points: entry_points.into(),
+            point: point_code.map(|code| {
+                Arc::new(PointLibrary {
+                    code,
+                    library: OnceLock::new(),
+                })
+            }),
         }
     }
 
@@ -84,12 +161,56 @@ impl Library {
         }
     }
 
+    /// Returns vertex function that writes point size.
+    /// Must be used for pipelines with point topology.
+    pub(super) fn get_point_function(
+        &self,
+        device: &metal::DeviceRef,
+        entry: &str,
+    ) -> Option<metal::Function> {
+        let Some(point) = &self.point else {
+            // Library from MSL source is used as is.
+            return self.get_function(entry);
+        };
+
+        let library = point.library.get_or_init(|| {
+            match device.new_library_with_source(&point.code, &compile_options()) {
+                Ok(library) => Some(library),
+                Err(err) => {
+                    tracing::error!(
+                        "Failed to compile point topology variant of the library: {err}"
+                    );
+                    None
+                }
+            }
+        });
+
+        let name = self.entry_point_data.get(entry)?.name.as_ref().ok()?;
+        library.as_ref()?.get_function(name, None).ok()
+    }
+
     #[cfg_attr(feature = "inline-more", inline)]
     pub(super) fn get_bindings(&self, entry: &str) -> Option<Arc<Bindings>> {
         let ep = self.entry_point_data.get(entry)?;
         Some(ep.bindings.clone())
     }
 
+    /// Returns reflected entry point.
+    /// Returns `None` if library was created from MSL or entry is not found.
+    #[cfg_attr(feature = "inline-more", inline)]
+    pub(super) fn reflect_entry(&self, entry: &str) -> Option<&EntryReflection> {
+        let ep = self.entry_point_data.get(entry)?;
+        Some(&ep.reflection)
+    }
+
+    /// Returns whether fragment entry point writes second blend source.
+    /// Returns `None` if library was not reflected or entry is not found.
+    #[cfg_attr(feature = "inline-more", inline)]
+    pub(super) fn writes_second_blend_source(&self, entry: &str) -> Option<bool> {
+        let entry = self.reflect_entry(entry)?;
+        Some(entry.writes_second_blend_source)
+    }
+
     #[cfg_attr(feature = "inline-more", inline)]
     pub(super) fn get_workgroup_size(&self, entry: &str) -> Option<[u32; 3]> {
         let ep = self.entry_point_data.get(entry)?;
@@ -105,4 +226,9 @@ impl crate::traits::Library for Library {
             entry: Cow::Borrowed(entry),
         }
     }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn entry_points(&self) -> &[EntryPoint] {
+        &self.entry_points
+    }
 }
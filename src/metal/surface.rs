@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, ops::RangeInclusive, sync::Arc};
 
 use core_graphics_types::{
     base::CGFloat,
@@ -6,16 +6,20 @@ use core_graphics_types::{
 };
 use objc::{msg_send, runtime::Object, sel, sel_impl};
 
-use crate::generic::{PipelineStages, SurfaceError};
+use crate::generic::{
+    Extent2, Extent3, ImageUsage, Offset2, Offset3, PipelineStages, ResourceIds, SuboptimalPolicy,
+    SuboptimalTracker, SurfaceError,
+};
 
 use super::{Image, Queue};
 
-const SUBOPTIMAL_RETIRE_COOLDOWN: u64 = 10;
-
 pub struct Surface {
     layer: metal::MetalLayer,
     view: *mut objc::runtime::Object,
-    suboptimal_retire_cooldown: u64,
+    resource_ids: Arc<ResourceIds>,
+
+    /// Decides when to resize drawables to match the view.
+    suboptimal: SuboptimalTracker,
 }
 
 unsafe impl Sync for Surface {}
@@ -32,7 +36,11 @@ impl Drop for Surface {
 }
 
 impl Surface {
-    pub(super) fn new(layer: metal::MetalLayer, view: *mut Object) -> Self {
+    pub(super) fn new(
+        layer: metal::MetalLayer,
+        view: *mut Object,
+        resource_ids: Arc<ResourceIds>,
+    ) -> Self {
         if !view.is_null() {
             unsafe {
                 let () = msg_send![view, retain];
@@ -42,7 +50,31 @@ impl Surface {
         Surface {
             layer,
             view,
-            suboptimal_retire_cooldown: SUBOPTIMAL_RETIRE_COOLDOWN,
+            resource_ids,
+            suboptimal: SuboptimalTracker::new(),
+        }
+    }
+
+    /// Returns drawable size matching the view, if it differs from the current one.
+    fn view_drawable_size(&self) -> Option<CGSize> {
+        if self.view.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let draw_size = self.layer.drawable_size();
+
+            let scale = window_scale_factor(self.view);
+            let size = view_size(self.view);
+
+            if draw_size.width != size.width * scale || draw_size.height != size.height * scale {
+                Some(CGSize {
+                    width: size.width * scale,
+                    height: size.height * scale,
+                })
+            } else {
+                None
+            }
         }
     }
 }
@@ -67,28 +99,66 @@ unsafe fn view_size(view: *mut Object) -> CGSize {
 
 #[hidden_trait::expose]
 impl crate::traits::Surface for Surface {
+    type Frame = Frame;
+
+    fn supported_usage(&self) -> Result<ImageUsage, SurfaceError> {
+        Ok(ImageUsage::TARGET
+            | ImageUsage::SAMPLED
+            | ImageUsage::STORAGE
+            | ImageUsage::TRANSFER_SRC
+            | ImageUsage::TRANSFER_DST)
+    }
+
+    fn set_usage(&mut self, usage: ImageUsage) -> Result<(), SurfaceError> {
+        // Drawables that are only rendered to can be optimized for display.
+        self.layer.set_framebuffer_only(ImageUsage::TARGET.contains(usage));
+        Ok(())
+    }
+
+    fn supported_image_count(&self) -> Result<RangeInclusive<u32>, SurfaceError> {
+        // `CAMetalLayer` allows only 2 or 3 drawables.
+        Ok(2..=3)
+    }
+
+    fn set_image_count(&mut self, count: u32) -> Result<(), SurfaceError> {
+        assert!(
+            (2..=3).contains(&count),
+            "Surface does not support {} frame images. Supported range is 2..=3",
+            count
+        );
+        self.layer.set_maximum_drawable_count(count as _);
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn image_count(&self) -> u32 {
+        self.layer.maximum_drawable_count() as u32
+    }
+
+    fn set_suboptimal_policy(&mut self, policy: SuboptimalPolicy) {
+        self.suboptimal.set_policy(policy);
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn is_suboptimal(&self) -> bool {
+        self.suboptimal.is_suboptimal()
+    }
+
+    fn recreate(&mut self) {
+        self.suboptimal.request();
+    }
+
     fn next_frame(&mut self) -> Result<Frame, SurfaceError> {
-        if self.suboptimal_retire_cooldown == 0 {
-            if !self.view.is_null() {
-                unsafe {
-                    let draw_size = self.layer.drawable_size();
-
-                    let scale = window_scale_factor(self.view);
-                    let size = view_size(self.view);
-
-                    if draw_size.width != size.width * scale
-                        || draw_size.height != size.height * scale
-                    {
-                        self.layer.set_drawable_size(CGSize {
-                            width: size.width * scale,
-                            height: size.height * scale,
-                        });
-                        self.suboptimal_retire_cooldown = SUBOPTIMAL_RETIRE_COOLDOWN;
-                    }
-                }
+        let new_size = self.view_drawable_size();
+        if new_size.is_some() {
+            self.suboptimal.report_suboptimal();
+        }
+
+        if self.suboptimal.next_frame().is_some() {
+            if let Some(size) = new_size {
+                self.layer.set_drawable_size(size);
             }
-        } else {
-            self.suboptimal_retire_cooldown -= 1;
+            self.suboptimal.reset();
         }
 
         let drawable = self
@@ -96,7 +166,7 @@ impl crate::traits::Surface for Surface {
             .next_drawable()
             .ok_or(SurfaceError::SurfaceLost)?;
 
-        let image = Image::new(drawable.texture().to_owned());
+        let image = Image::new(drawable.texture().to_owned(), self.resource_ids.next());
         Ok(Frame {
             drawable: drawable.to_owned(),
             image,
@@ -118,8 +188,34 @@ impl Frame {
 
 #[hidden_trait::expose]
 impl crate::traits::Frame for Frame {
+    type Image = Image;
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn image(&self) -> &Image {
         &self.image
     }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn is_fake(&self) -> bool {
+        false
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn viewport(&self) -> (Offset3<f32>, Extent3<f32>) {
+        let extent = self.image.extent().into_2d();
+        (
+            Offset3::ZERO,
+            Extent3::new(extent.width() as f32, extent.height() as f32, 1.0),
+        )
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn scissor(&self) -> (Offset2<i32>, Extent2<u32>) {
+        (Offset2::ZERO, self.image.extent().into_2d())
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn has_present_fence(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,434 @@
+//! Utilities for image comparison in test suites.
+//!
+//! Read rendered images back with [`read_image_to_rgba8`]
+//! and compare them with reference images using [`compare_images`]
+//! or [`assert_images_match!`](crate::assert_images_match).
+//!
+//! Images written on mismatch are 8-bit RGBA PNG files.
+
+use std::{fmt, io, path::Path};
+
+use crate::{
+    BufferDesc, BufferUsage, DeviceError, Extent2, Image, Memory, Offset3, PipelineStages,
+    PixelFormat, Queue,
+};
+
+/// Directory where images are written when assertion fails
+/// and no directory is specified.
+pub const DEFAULT_DIFF_DIR: &str = "target/mev-image-diff";
+
+/// Reads first layer of the base level of the 2D image
+/// and converts it to tightly packed 8-bit RGBA pixels.
+///
+/// Supported formats are `Rgba8Unorm`, `Rgba8Srgb`, `Bgra8Unorm`, `Bgra8Srgb`
+/// and `Rgba16Float`.
+/// sRGB values are returned as stored, without conversion to linear.
+/// Float values are clamped to `0..=1`.
+///
+/// Image must be created with `ImageUsage::TRANSFER_SRC` and be initialized.
+/// Blocks until the queue is idle.
+///
+/// # Panics
+///
+/// Panics if image format is not supported.
+pub fn read_image_to_rgba8(queue: &mut Queue, image: &Image) -> Result<Vec<u8>, DeviceError> {
+    let format = image.format();
+    assert!(
+        matches!(
+            format,
+            PixelFormat::Rgba8Unorm
+                | PixelFormat::Rgba8Srgb
+                | PixelFormat::Bgra8Unorm
+                | PixelFormat::Bgra8Srgb
+                | PixelFormat::Rgba16Float
+        ),
+        "Image format {format:?} is not supported for readback"
+    );
+
+    let extent = image.extent().into_2d();
+    let bytes_per_line = u64::from(extent.width()) * format.size() as u64;
+    let size = bytes_per_line * u64::from(extent.height());
+    let host_size = usize::try_from(size).map_err(|_| DeviceError::OutOfMemory)?;
+
+    let mut download = queue
+        .new_buffer(BufferDesc {
+            size,
+            usage: BufferUsage::TRANSFER_DST,
+            memory: Memory::Download,
+            name: "mev-testing-readback",
+            shared_across_queues: false,
+        })
+        .map_err(|_| DeviceError::OutOfMemory)?;
+
+    let mut encoder = queue.new_command_encoder()?;
+    let mut copy = encoder.copy();
+    copy.sync_image(PipelineStages::ALL_NON_RT, PipelineStages::TRANSFER, image);
+    copy.copy_image_to_buffer(
+        image,
+        Offset3::ZERO,
+        extent.to_3d(),
+        0..1,
+        0,
+        &download,
+        0,
+        bytes_per_line,
+        0,
+    );
+    drop(copy);
+    queue.submit_one(encoder.finish()?, true)?;
+    queue.wait_idle()?;
+
+    let mut data = vec![0; host_size];
+
+    // Safety: commands that write the buffer are completed.
+    unsafe {
+        download.read_unchecked(0, &mut data);
+    }
+
+    let pixels = match format {
+        PixelFormat::Rgba8Unorm | PixelFormat::Rgba8Srgb => data,
+        PixelFormat::Bgra8Unorm | PixelFormat::Bgra8Srgb => {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            data
+        }
+        PixelFormat::Rgba16Float => data
+            .chunks_exact(2)
+            .map(|bits| unorm_to_u8(f16_to_f32(u16::from_ne_bytes([bits[0], bits[1]]))))
+            .collect(),
+        _ => unreachable!(),
+    };
+
+    Ok(pixels)
+}
+
+/// Result of image comparison.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageDiff {
+    /// Maximum absolute difference of a channel value.
+    pub max_error: u8,
+
+    /// Mean absolute difference of channel values.
+    pub mean_error: f64,
+
+    /// Number of pixels with any channel differing by more than tolerance.
+    pub differing_pixels: usize,
+
+    /// Total number of compared pixels.
+    pub pixels: usize,
+}
+
+impl ImageDiff {
+    /// Returns `true` if no pixel differs by more than tolerance.
+    pub fn matches(&self) -> bool {
+        self.differing_pixels == 0
+    }
+}
+
+impl fmt::Display for ImageDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} pixels differ, max channel error {}, mean channel error {:.3}",
+            self.differing_pixels, self.pixels, self.max_error, self.mean_error
+        )
+    }
+}
+
+/// Compares two images of tightly packed 8-bit RGBA pixels.
+///
+/// Pixel differs if any of its channels differs by more than `tolerance`.
+///
+/// # Panics
+///
+/// Panics if images have different sizes or size is not a multiple of 4.
+pub fn compare_images(actual: &[u8], expected: &[u8], tolerance: u8) -> ImageDiff {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "Compared images must have the same size"
+    );
+    assert_eq!(actual.len() % 4, 0, "Images must consist of RGBA pixels");
+
+    let mut max_error = 0;
+    let mut total_error = 0u64;
+    let mut differing_pixels = 0;
+
+    for (a, e) in actual.chunks_exact(4).zip(expected.chunks_exact(4)) {
+        let mut differs = false;
+        for (&a, &e) in a.iter().zip(e) {
+            let error = a.abs_diff(e);
+            max_error = max_error.max(error);
+            total_error += u64::from(error);
+            differs |= error > tolerance;
+        }
+        differing_pixels += usize::from(differs);
+    }
+
+    let mean_error = if actual.is_empty() {
+        0.0
+    } else {
+        total_error as f64 / actual.len() as f64
+    };
+
+    ImageDiff {
+        max_error,
+        mean_error,
+        differing_pixels,
+        pixels: actual.len() / 4,
+    }
+}
+
+/// Returns image of absolute channel differences of two images
+/// of tightly packed 8-bit RGBA pixels.
+///
+/// Alpha of the result is opaque.
+///
+/// # Panics
+///
+/// Panics if images have different sizes or size is not a multiple of 4.
+pub fn diff_image(actual: &[u8], expected: &[u8]) -> Vec<u8> {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "Compared images must have the same size"
+    );
+    assert_eq!(actual.len() % 4, 0, "Images must consist of RGBA pixels");
+
+    actual
+        .chunks_exact(4)
+        .zip(expected.chunks_exact(4))
+        .flat_map(|(a, e)| {
+            [
+                a[0].abs_diff(e[0]),
+                a[1].abs_diff(e[1]),
+                a[2].abs_diff(e[2]),
+                255,
+            ]
+        })
+        .collect()
+}
+
+/// Writes tightly packed 8-bit RGBA pixels as PNG.
+pub fn write_png(path: impl AsRef<Path>, extent: Extent2, pixels: &[u8]) -> io::Result<()> {
+    assert_eq!(
+        pixels.len(),
+        extent.width() as usize * extent.height() as usize * 4,
+        "Pixels don't match image extent"
+    );
+
+    let file = io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, extent.width(), extent.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Compares images and panics with comparison stats if they don't match.
+///
+/// On mismatch writes `actual.png`, `expected.png` and `diff.png` to `dir`.
+///
+/// Use [`assert_images_match!`](crate::assert_images_match) instead of calling this directly.
+#[track_caller]
+pub fn assert_images_match(
+    actual: &[u8],
+    expected: &[u8],
+    extent: Extent2,
+    tolerance: u8,
+    dir: &Path,
+) {
+    let diff = compare_images(actual, expected, tolerance);
+    if diff.matches() {
+        return;
+    }
+
+    let written = std::fs::create_dir_all(dir)
+        .and_then(|()| write_png(dir.join("actual.png"), extent, actual))
+        .and_then(|()| write_png(dir.join("expected.png"), extent, expected))
+        .and_then(|()| write_png(dir.join("diff.png"), extent, &diff_image(actual, expected)));
+
+    match written {
+        Ok(()) => panic!(
+            "Images don't match with tolerance {tolerance}: {diff}. Images are written to {}",
+            dir.display()
+        ),
+        Err(err) => panic!(
+            "Images don't match with tolerance {tolerance}: {diff}. Failed to write images to {}: {err}",
+            dir.display()
+        ),
+    }
+}
+
+/// Asserts that two images of tightly packed 8-bit RGBA pixels match.
+///
+/// Pixel matches if none of its channels differs by more than tolerance.
+/// On mismatch writes `actual.png`, `expected.png` and `diff.png`
+/// to the directory, [`DEFAULT_DIFF_DIR`](crate::testing::DEFAULT_DIFF_DIR) by default,
+/// and panics with the comparison stats.
+///
+/// ```ignore
+/// let actual = mev::testing::read_image_to_rgba8(&mut queue, &target)?;
+/// mev::assert_images_match!(actual, expected, extent, 2);
+/// mev::assert_images_match!(actual, expected, extent, 2, "target/diff/shadows");
+/// ```
+#[macro_export]
+macro_rules! assert_images_match {
+    ($actual:expr, $expected:expr, $extent:expr, $tolerance:expr $(,)?) => {
+        $crate::assert_images_match!(
+            $actual,
+            $expected,
+            $extent,
+            $tolerance,
+            $crate::testing::DEFAULT_DIFF_DIR
+        )
+    };
+    ($actual:expr, $expected:expr, $extent:expr, $tolerance:expr, $dir:expr $(,)?) => {
+        $crate::testing::assert_images_match(
+            &$actual[..],
+            &$expected[..],
+            $extent,
+            $tolerance,
+            ::std::path::Path::new(&$dir),
+        )
+    };
+}
+
+/// Converts normalized value to 8-bit value, clamping it to `0..=1`.
+fn unorm_to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Converts bits of half-precision float to single-precision float.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = u32::from(bits >> 15) << 31;
+    let exponent = u32::from((bits >> 10) & 0x1f);
+    let mantissa = u32::from(bits & 0x3ff);
+
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal half is normal single.
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x3ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mantissa << 13)
+        }
+        (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+
+    f32::from_bits(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_images() {
+        let image = [10, 20, 30, 255, 40, 50, 60, 128];
+        let diff = compare_images(&image, &image, 0);
+        assert!(diff.matches());
+        assert_eq!(
+            diff,
+            ImageDiff {
+                max_error: 0,
+                mean_error: 0.0,
+                differing_pixels: 0,
+                pixels: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn tolerance() {
+        let expected = [100, 100, 100, 255, 100, 100, 100, 255];
+        let actual = [102, 100, 100, 255, 100, 100, 97, 255];
+
+        let diff = compare_images(&actual, &expected, 2);
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.max_error, 3);
+        assert_eq!(diff.mean_error, 5.0 / 8.0);
+        assert!(!diff.matches());
+
+        assert!(compare_images(&actual, &expected, 3).matches());
+    }
+
+    #[test]
+    fn alpha_is_compared() {
+        let diff = compare_images(&[0, 0, 0, 0], &[0, 0, 0, 255], 0);
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.max_error, 255);
+    }
+
+    #[test]
+    fn empty_images() {
+        let diff = compare_images(&[], &[], 0);
+        assert!(diff.matches());
+        assert_eq!(diff.pixels, 0);
+        assert_eq!(diff.mean_error, 0.0);
+    }
+
+    #[test]
+    #[should_panic = "same size"]
+    fn size_mismatch() {
+        compare_images(&[0; 8], &[0; 4], 0);
+    }
+
+    #[test]
+    fn diff_is_opaque() {
+        let actual = [10, 200, 30, 0, 0, 0, 0, 0];
+        let expected = [20, 100, 30, 255, 0, 0, 0, 0];
+        assert_eq!(
+            diff_image(&actual, &expected),
+            [10, 100, 0, 255, 0, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let extent = Extent2::new(2, 1);
+        let pixels = [255, 0, 0, 255, 0, 128, 255, 64];
+        let path = std::env::temp_dir().join("mev-testing-round-trip.png");
+        write_png(&path, extent, &pixels).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let mut reader = png::Decoder::new(file).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut decoded).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(&decoded[..info.buffer_size()], pixels);
+    }
+
+    #[test]
+    fn half_floats() {
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert!(f16_to_f32(0x8000).is_sign_negative());
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xbc00), -1.0);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0x7bff), 65504.0);
+
+        // Smallest subnormal and largest subnormal.
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x03ff), 1023.0 * 2f32.powi(-24));
+
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(f16_to_f32(0xfc00), f32::NEG_INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn unorm_clamps() {
+        assert_eq!(unorm_to_u8(-1.0), 0);
+        assert_eq!(unorm_to_u8(0.5), 128);
+        assert_eq!(unorm_to_u8(2.0), 255);
+    }
+}
@@ -1,107 +1,529 @@
+//! Traits implemented by the types of the backend.
+//!
+//! Types of the backend chosen at compile time are re-exported from the crate root
+//! and have all trait methods available as inherent methods,
+//! so these traits don't need to be imported to use them.
+//!
+//! The traits allow writing code that is generic over device types,
+//! for example to substitute a mock device in tests.
+//! They are not sealed and may be implemented outside of this crate.
+//! Note that descriptors like [`RenderPipelineDesc`] and [`BufferSlice`]
+//! still refer to the types of the current backend.
+//!
+//! ```ignore
+//! use mev::traits::Device;
+//!
+//! fn new_uniform_buffer<D: Device>(device: &D, size: u64) -> Result<D::Buffer, mev::AllocError> {
+//!     device.new_buffer(mev::BufferDesc {
+//!         size,
+//!         usage: mev::BufferUsage::UNIFORM,
+//!         memory: mev::Memory::Shared,
+//!         name: "uniforms",
+//!         shared_across_queues: false,
+//!     })
+//! }
+//!
+//! // Compiles with both Vulkan and Metal backends.
+//! let buffer: mev::Buffer = new_uniform_buffer(&device, 256)?;
+//! ```
+//!
+//! # Empty operations
+//!
+//! Operations on empty ranges behave the same on all backends.
+//!
+//! | Operation | Behavior |
+//! |-----------|----------|
+//! | [`Device::new_buffer`] and [`Device::new_buffer_init`] with zero size | Fail with [`AllocErrorKind::ZeroSizedBuffer`](crate::AllocErrorKind::ZeroSizedBuffer) |
+//! | [`RenderCommandEncoder::draw`] and [`RenderCommandEncoder::draw_indexed`] with empty range | Skipped, nothing is recorded |
+//! | [`ComputeCommandEncoder::dispatch`] with zero groups in any dimension | Skipped, nothing is recorded |
+//! | [`RenderCommandEncoder::bind_vertex_buffers`] with no slices | No-op |
+//! | [`CopyCommandEncoder::copy_buffer`] and [`CopyCommandEncoder::fill_buffer`] on empty slice | No-op |
+//! | [`CopyCommandEncoder::write_buffer_raw`] with empty data | No-op |
+//! | Image copies with zero extent or no layers | Debug assertion naming the call |
+
 use std::{
     fmt::Debug,
     hash::Hash,
-    ops::{Deref, Range},
+    ops::{Deref, DerefMut, Range, RangeInclusive},
 };
 
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 
 use crate::{
     generic::{
-        Arguments, AsBufferSlice, BlasBuildDesc, BlasDesc, BufferDesc, BufferInitDesc, BufferSlice,
-        Capabilities, ComputePipelineDesc, CreateError, CreateLibraryError, CreatePipelineError,
-        DeviceDesc, DeviceError, DeviceRepr, Extent2, Extent3, ImageDesc, ImageExtent, LibraryDesc,
-        Offset2, Offset3, OutOfMemory, PipelineStages, PixelFormat, RenderPassDesc,
-        RenderPipelineDesc, SamplerDesc, SurfaceError, TlasBuildDesc, TlasDesc, ViewDesc,
+        AllocError, AllocatorStats, ArgumentGroupInfo, Arguments, AsBufferSlice, BlasBuildDesc,
+        BlasDesc, BufferDesc, BufferIndex, BufferInitDesc, BufferSlice, Capabilities,
+        CompactionPlan, ComputePipelineDesc, CreateError, CreateLibraryError, CreatePipelineError,
+        DepthFormatRequirements, DeviceDesc, DeviceError, DeviceLimits, DeviceRepr, EntryPoint,
+        Extent2, Extent3, FamilyCapabilities, Features, FormatCaps, ImageDesc, ImageExtent,
+        LibraryDesc, MapError, Offset2, Offset3, OutOfMemory, PipelinePending, PipelineStages,
+        PixelFormat, QueueFlags, QueuePoolConfig, QueueStats, Quirks, Rect2, RenderPassDesc,
+        RenderPipelineDesc, ResourceId, ReusableCommandsError, SamplerDesc, SubmissionId,
+        SuboptimalPolicy, SurfaceError, TlasBuildDesc, TlasDesc, ViewDesc, ViewError,
+        WellKnownSampler,
     },
     ImageUsage, Shader,
 };
 
 pub trait Instance: Debug + Send + Sync + 'static {
+    type Device: Device;
+    type Queue: Queue<Device = Self::Device>;
+
     fn capabilities(&self) -> &Capabilities;
-    fn create(
-        &self,
-        info: DeviceDesc,
-    ) -> Result<(crate::backend::Device, Vec<crate::backend::Queue>), CreateError>;
+    fn create(&self, info: DeviceDesc) -> Result<(Self::Device, Vec<Self::Queue>), CreateError>;
 }
 
+/// Device creates resources and pipelines.
+///
+/// Device is cheap to clone and can be shared between threads.
+/// All `new_*` methods may be called concurrently from multiple threads.
+/// Internal caches are locked only to look up and insert objects,
+/// so pipelines created on multiple threads don't serialize on each other.
 pub trait Device: Clone + Debug + Eq + Send + Sync + 'static {
+    type Library: Library;
+    type ComputePipeline;
+    type RenderPipeline;
+    type Buffer: Buffer;
+    type Image: Image<Device = Self>;
+    type Sampler;
+    type Surface: Surface;
+    type Blas;
+    type Tlas;
+    type BindlessTable: BindlessTable<Image = Self::Image>;
+
     /// Create a new shader library.
-    fn new_shader_library(
-        &self,
-        desc: LibraryDesc,
-    ) -> Result<crate::backend::Library, CreateLibraryError>;
+    fn new_shader_library(&self, desc: LibraryDesc) -> Result<Self::Library, CreateLibraryError>;
 
     /// Create a new compute pipeline.
     fn new_compute_pipeline(
         &self,
         desc: ComputePipelineDesc,
-    ) -> Result<crate::backend::ComputePipeline, CreatePipelineError>;
+    ) -> Result<Self::ComputePipeline, CreatePipelineError>;
 
     /// Create a new render pipeline.
     fn new_render_pipeline(
         &self,
         desc: RenderPipelineDesc,
-    ) -> Result<crate::backend::RenderPipeline, CreatePipelineError>;
+    ) -> Result<Self::RenderPipeline, CreatePipelineError>;
+
+    /// Create multiple compute pipelines in parallel.
+    ///
+    /// Pipelines are created on scoped worker threads,
+    /// at most one per available CPU core.
+    /// Results are returned in the order of descriptors.
+    fn new_compute_pipelines_parallel(
+        &self,
+        descs: Vec<ComputePipelineDesc>,
+    ) -> Vec<Result<Self::ComputePipeline, CreatePipelineError>>;
+
+    /// Create multiple render pipelines in parallel.
+    ///
+    /// Pipelines are created on scoped worker threads,
+    /// at most one per available CPU core.
+    /// Results are returned in the order of descriptors.
+    fn new_render_pipelines_parallel(
+        &self,
+        descs: Vec<RenderPipelineDesc>,
+    ) -> Vec<Result<Self::RenderPipeline, CreatePipelineError>>;
+
+    /// Create a new render pipeline on a background thread.
+    ///
+    /// Descriptor is converted into [`RenderPipelineDescOwned`](crate::RenderPipelineDescOwned)
+    /// and pipeline is created on one of device's background workers.
+    /// Use this to request pipeline variants during frame loop
+    /// without stalling frame on shader translation and driver compilation.
+    fn new_render_pipeline_deferred(
+        &self,
+        desc: RenderPipelineDesc,
+    ) -> PipelinePending<Self::RenderPipeline>;
 
     /// Create a new buffer with uninitialized contents.
-    fn new_buffer(&self, desc: BufferDesc) -> Result<crate::backend::Buffer, OutOfMemory>;
+    ///
+    /// Fails with [`AllocErrorKind::BufferTooLarge`](crate::AllocErrorKind::BufferTooLarge)
+    /// if size exceeds maximum buffer size of the device.
+    /// Buffers with [`BufferUsage::STORAGE`](crate::BufferUsage::STORAGE)
+    /// are also limited by maximum storage buffer range.
+    /// Fails with [`AllocErrorKind::ZeroSizedBuffer`](crate::AllocErrorKind::ZeroSizedBuffer)
+    /// if size is zero.
+    fn new_buffer(&self, desc: BufferDesc) -> Result<Self::Buffer, AllocError>;
 
     /// Create a new buffer and initialize it with the given data.
-    fn new_buffer_init(&self, desc: BufferInitDesc) -> Result<crate::backend::Buffer, OutOfMemory>;
+    fn new_buffer_init(&self, desc: BufferInitDesc) -> Result<Self::Buffer, AllocError>;
 
     /// Create a new image.
-    fn new_image(&self, desc: ImageDesc) -> Result<crate::backend::Image, OutOfMemory>;
+    fn new_image(&self, desc: ImageDesc) -> Result<Self::Image, AllocError>;
 
     /// Create a new sampler.
-    fn new_sampler(&self, desc: SamplerDesc) -> Result<crate::backend::Sampler, OutOfMemory>;
+    fn new_sampler(&self, desc: SamplerDesc) -> Result<Self::Sampler, OutOfMemory>;
+
+    /// Returns well-known sampler owned by the device.
+    ///
+    /// Sampler is created on first request and cached for the lifetime of the device.
+    /// Well-known samplers don't count towards device limit of sampler objects.
+    fn well_known_sampler(&self, kind: WellKnownSampler) -> Result<Self::Sampler, OutOfMemory>;
+
+    /// Returns sampler with trilinear filtering and coordinates clamped to edge.
+    ///
+    /// See [`WellKnownSampler::LinearClamp`].
+    fn linear_clamp_sampler(&self) -> Result<Self::Sampler, OutOfMemory>;
+
+    /// Returns sampler with nearest filtering and coordinates clamped to edge.
+    ///
+    /// See [`WellKnownSampler::NearestClamp`].
+    fn nearest_clamp_sampler(&self) -> Result<Self::Sampler, OutOfMemory>;
+
+    /// Returns sampler with trilinear filtering and repeated coordinates.
+    ///
+    /// See [`WellKnownSampler::LinearRepeat`].
+    fn linear_repeat_sampler(&self) -> Result<Self::Sampler, OutOfMemory>;
 
     /// Create a new surface associated with given window.
+    ///
+    /// Returns [`SurfaceError::Unsupported`] if window system
+    /// is not supported by the device
+    /// or window and display handles belong to different window systems.
     fn new_surface(
         &self,
         window: &impl HasWindowHandle,
         display: &impl HasDisplayHandle,
-    ) -> Result<crate::backend::Surface, SurfaceError>;
+    ) -> Result<Self::Surface, SurfaceError>;
 
     /// Create a new bottom-level acceleration structure.
-    fn new_blas(&self, desc: BlasDesc) -> Result<crate::backend::Blas, OutOfMemory>;
+    fn new_blas(&self, desc: BlasDesc) -> Result<Self::Blas, OutOfMemory>;
 
     /// Create a new top-level acceleration structure.
-    fn new_tlas(&self, desc: TlasDesc) -> Result<crate::backend::Tlas, OutOfMemory>;
+    fn new_tlas(&self, desc: TlasDesc) -> Result<Self::Tlas, OutOfMemory>;
+
+    /// Create a new bindless table of sampled images with given capacity.
+    ///
+    /// Requires [`Features::DESCRIPTOR_INDEXING`].
+    /// Capacity must not exceed [`DeviceLimits::max_bindless_table_size`].
+    fn new_bindless_table(&self, capacity: u32) -> Result<Self::BindlessTable, OutOfMemory>;
+
+    /// Returns driver workarounds applied by this device.
+    ///
+    /// Includes quirks detected for the device and quirks forced
+    /// with [`DeviceDesc::quirks`].
+    fn quirks(&self) -> Quirks;
+
+    /// Returns features enabled for the device.
+    ///
+    /// Same as [`DeviceDesc::features`] the device was created with,
+    /// plus [`Features::PRESENT_FENCES`] if it was enabled automatically.
+    fn features(&self) -> Features;
+
+    /// Returns limits of the device.
+    ///
+    /// Same as [`DeviceCapabilities::limits`](crate::DeviceCapabilities::limits)
+    /// of the device.
+    fn limits(&self) -> DeviceLimits;
+
+    /// Returns statistics of device memory allocator.
+    ///
+    /// Useful to diagnose memory usage and fragmentation.
+    fn allocator_stats(&self) -> AllocatorStats;
+
+    /// Releases device memory objects that no longer hold any resources.
+    ///
+    /// Allocator keeps freed memory for reuse,
+    /// so reserved memory does not shrink when resources are dropped.
+    /// Call this at a quiet point, e.g. after unloading a level,
+    /// once resources are dropped and queues observed completion of their use
+    /// by waiting idle or submitting checkpoints.
+    /// It is cheap when there is nothing to release.
+    ///
+    /// Returns number of bytes released,
+    /// [`AllocatorStats::reserved_bytes`] decreases by this amount.
+    fn trim_memory(&self) -> u64;
+
+    /// Returns number of bytes of device memory an image with given description would take.
+    ///
+    /// Nothing is created or allocated.
+    /// Useful for budgeting memory before creating resources.
+    /// Actual allocation may take more due to alignment of memory blocks.
+    fn image_allocation_size(&self, desc: &ImageDesc) -> Result<u64, AllocError>;
+
+    /// Returns number of bytes of device memory a buffer with given description would take.
+    ///
+    /// Nothing is created or allocated.
+    /// Returns `desc.size` if buffer can't be created with the description.
+    fn buffer_allocation_size(&self, desc: &BufferDesc) -> u64;
+
+    /// Selects buffers that should be moved to new memory to reduce fragmentation.
+    ///
+    /// Only buffers in [`Memory::Device`](crate::Memory::Device)
+    /// created with both [`BufferUsage::TRANSFER_SRC`](crate::BufferUsage::TRANSFER_SRC)
+    /// and [`BufferUsage::TRANSFER_DST`](crate::BufferUsage::TRANSFER_DST) can be moved.
+    /// Buffer must be [`detached`](Buffer::detached),
+    /// i.e. not cloned and not referenced by pending submissions.
+    ///
+    /// Movable buffers are selected if memory region they occupy
+    /// is less than half used by all resources allocated from it.
+    /// Buffers in densely used regions are left in place.
+    ///
+    /// Plan refers to buffers by index in the slice
+    /// and must be executed with the same slice.
+    fn plan_compaction(&self, buffers: &[Self::Buffer]) -> CompactionPlan;
+
+    /// Returns capabilities of the pixel format on this device.
+    fn format_caps(&self, format: PixelFormat) -> FormatCaps;
+
+    /// Returns preferred depth format that satisfies requirements on this device.
+    ///
+    /// Formats are checked with [`Device::format_caps`]
+    /// for depth-stencil target and, if requested, sampled support.
+    /// Returns `None` if no depth format satisfies requirements.
+    fn select_depth_format(&self, requirements: DepthFormatRequirements) -> Option<PixelFormat>;
+
+    /// Returns human-readable description of the resource created by this device.
+    ///
+    /// See [`Resource::describe`].
+    fn describe(&self, resource: &impl Resource) -> String;
+
+    /// Returns capabilities of the queue family with given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `family` is not an index of queue family of the device.
+    fn family_capabilities(&self, family: u32) -> FamilyCapabilities;
+
+    /// Checks if queues of the family with given index can present to the surface.
+    fn supports_present(&self, family: u32, surface: &Self::Surface) -> bool;
 }
 
-pub trait Queue: Deref<Target = crate::backend::Device> + Debug + Send + Sync + 'static {
+/// Queue submits command buffers to the device.
+///
+/// # Threading
+///
+/// Queue is `Send` and `Sync`, but submission requires `&mut self`,
+/// so one thread owns the queue and submits.
+/// Command encoders and command buffers are `Send` but not `Sync`.
+/// They may be created and recorded on worker threads
+/// and finished command buffers sent to the owning thread, e.g. over a channel:
+///
+/// ```ignore
+/// let (tx, rx) = std::sync::mpsc::channel();
+///
+/// let mut encoder = queue.new_command_encoder()?;
+/// std::thread::spawn(move || {
+///     // Record commands.
+///     tx.send(encoder.finish().unwrap()).unwrap();
+/// });
+///
+/// let cbuf = rx.recv().unwrap();
+/// queue.submit([cbuf], true)?;
+/// ```
+///
+/// Command buffers must be submitted to the queue their encoders were created from.
+///
+/// # Debugging
+///
+/// In debug builds and with `debug` feature Vulkan backend reports resources
+/// kept alive by submitted commands with `Queue::pending_resources`
+/// and by command buffers with `CommandBuffer::referenced_resources`.
+/// These methods are not part of this trait and don't exist on Metal,
+/// where resources are retained by the Metal runtime instead of the backend.
+pub trait Queue: Deref<Target = <Self as Queue>::Device> + Debug + Send + Sync + 'static {
+    type Device: Device;
+    type CommandEncoder: CommandEncoder<
+            Image = <Self::Device as Device>::Image,
+            CommandBuffer = Self::CommandBuffer,
+            Frame = Self::Frame,
+        > + Send
+        + 'static;
+    type CommandBuffer: Send + 'static;
+    type ReusableCommandEncoder: ReusableCommandEncoder<
+        CommandEncoder = Self::CommandEncoder,
+        ReusableCommandBuffer = Self::ReusableCommandBuffer,
+    >;
+    type ReusableCommandBuffer: ReusableCommandBuffer;
+    type Frame: Frame<Image = <Self::Device as Device>::Image>;
+
     /// Get the device associated with this queue.
-    fn device(&self) -> &crate::backend::Device;
+    fn device(&self) -> &Self::Device;
 
     /// Get the queue family index.
     fn family(&self) -> u32;
 
+    /// Get the flags of the queue family.
+    fn flags(&self) -> QueueFlags;
+
+    /// Get statistics of the queue.
+    fn stats(&self) -> QueueStats;
+
+    /// Sets policy of command pools used to allocate command buffers.
+    ///
+    /// Takes effect for command encoders created afterwards.
+    /// Pools above the new `max_pools` are kept but no new pools are created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_pools` is zero.
+    fn set_pool_config(&mut self, config: QueuePoolConfig);
+
+    /// Returns estimated number of resources kept alive by submitted commands.
+    ///
+    /// Resources are released when checkpoint after their submission completes.
+    /// Engines may use this to insert checkpoints more often.
+    /// Backends that don't track resources return zero.
+    fn pending_resource_estimate(&self) -> usize;
+
+    /// Returns number of checkpoints submitted to the queue, including forced ones.
+    ///
+    /// Used by [`PerFrame`](crate::PerFrame) to select a copy
+    /// that is not used by pending commands.
+    fn check_point_index(&self) -> u64;
+
     /// Create a new command encoder associated with this queue.
     /// The encoder must be submitted to the queue it was created from.
-    fn new_command_encoder(&mut self) -> Result<crate::backend::CommandEncoder, OutOfMemory>;
+    fn new_command_encoder(&mut self) -> Result<Self::CommandEncoder, OutOfMemory>;
 
     /// Submit command buffers to the queue.
     ///
     /// If `check_point` is `true`, inserts a checkpoint into queue and check previous checkpoints.
     /// Checkpoints are required for resource reclamation.
-    fn submit<I>(&mut self, command_buffers: I, check_point: bool) -> Result<(), DeviceError>
+    ///
+    /// Queue may insert checkpoint even if `check_point` is `false`
+    /// when too many submissions were made without one,
+    /// otherwise resources would never be released.
+    /// Vulkan backend forces checkpoint every 64 submissions without one,
+    /// see [`QueueStats::forced_check_points`].
+    ///
+    /// Returns identifier of the submission.
+    fn submit<I>(
+        &mut self,
+        command_buffers: I,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>
     where
-        I: IntoIterator<Item = crate::backend::CommandBuffer>;
+        I: IntoIterator<Item = Self::CommandBuffer>;
+
+    /// Submit command buffers to the queue with a label.
+    ///
+    /// Same as [`submit`](Queue::submit), label is kept until the submission completes
+    /// and is reported if device is lost before that.
+    fn submit_labeled<I>(
+        &mut self,
+        command_buffers: I,
+        label: &str,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>
+    where
+        I: IntoIterator<Item = Self::CommandBuffer>;
+
+    /// Submit single command buffer to the queue.
+    ///
+    /// Same as [`submit`](Queue::submit) with one command buffer,
+    /// but skips collecting command buffers from iterator.
+    fn submit_one(
+        &mut self,
+        command_buffer: Self::CommandBuffer,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>;
+
+    /// Returns identifier of the latest submission known to be complete.
+    ///
+    /// Completion is tracked at checkpoints,
+    /// so submissions without checkpoint are known to be complete
+    /// only when a later checkpoint completes.
+    /// Returns [`SubmissionId::NONE`] if no submission is known to be complete.
+    fn last_completed_submission(&self) -> SubmissionId;
+
+    /// Waits until submission with given identifier is complete.
+    ///
+    /// Resources used by complete submissions are released
+    /// if the submissions are followed by a checkpoint.
+    /// May wait for later submissions too, e.g. when the submission has no checkpoint
+    /// and backend can't track it separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the submission was not made to this queue.
+    fn wait_submission(&mut self, id: SubmissionId) -> Result<(), DeviceError>;
+
+    /// Returns markers written by [`CommandEncoder::checkpoint`]
+    /// in commands submitted to this queue, from oldest to most recent.
+    ///
+    /// Only the latest 64 markers are kept.
+    /// Intended to be called after [`DeviceError::DeviceLost`]
+    /// or when the queue is idle, as markers of running commands may be missing.
+    /// Always empty on Metal.
+    fn last_breadcrumbs(&self) -> Vec<u32>;
 
     /// Drop command buffers without submitting them to the queue.
     fn drop_command_buffer<I>(&mut self, command_buffers: I)
     where
-        I: IntoIterator<Item = crate::backend::CommandBuffer>;
+        I: IntoIterator<Item = Self::CommandBuffer>;
+
+    /// Create a new command encoder that produces reusable command buffer.
+    ///
+    /// Reusable command buffer can be submitted any number of times
+    /// with [`Queue::submit_reusable`].
+    /// It keeps all referenced resources alive for as long as it lives.
+    ///
+    /// Frames can't be presented from reusable command buffers.
+    /// Command buffer that references swapchain images
+    /// must be dropped and recorded again when the swapchain is recreated,
+    /// see [`ReusableCommandBuffer::references_swapchain`].
+    ///
+    /// Returns [`ReusableCommandsError::Unsupported`] if backend can't reuse command buffers.
+    fn new_command_encoder_reusable(
+        &mut self,
+    ) -> Result<Self::ReusableCommandEncoder, ReusableCommandsError>;
+
+    /// Submit reusable command buffer to the queue.
+    ///
+    /// Command buffer may be submitted again while previous submission is still executing.
+    ///
+    /// If `check_point` is `true`, inserts a checkpoint into queue and check previous checkpoints.
+    /// Checkpoints are required for resource reclamation.
+    fn submit_reusable(
+        &mut self,
+        command_buffer: &Self::ReusableCommandBuffer,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>;
+
+    /// Moves buffers selected by the plan to newly allocated memory.
+    ///
+    /// Buffers are packed back to back into fresh memory chunks.
+    /// Content is copied on this queue and buffers are replaced in place.
+    /// Old memory is released when the copy completes.
+    /// Commands submitted to this queue afterwards see the copied content.
+    /// Other queues must be synchronized by the user.
+    ///
+    /// Selected buffers that are no longer movable, e.g. cloned since planning, are skipped.
+    /// If memory can't be allocated, no buffers are moved.
+    fn execute_compaction(
+        &mut self,
+        plan: &CompactionPlan,
+        buffers: &mut [<Self::Device as Device>::Buffer],
+    ) -> Result<(), DeviceError>;
 
     /// Synchronize the access to the frame resources.
-    fn sync_frame(&mut self, frame: &mut crate::backend::Frame, before: PipelineStages);
+    fn sync_frame(&mut self, frame: &mut Self::Frame, before: PipelineStages);
 
     /// Wait for all operations on the queue to complete.
-    fn wait_idle(&self) -> Result<(), OutOfMemory>;
+    ///
+    /// After this call queue holds no references to resources used by submitted commands,
+    /// so resources dropped afterwards are destroyed immediately.
+    /// Other queues of the device keep their references until they are waited or reclaimed.
+    ///
+    /// Intended for teardown, e.g. when unloading a level,
+    /// use checkpoints to release resources during regular frames.
+    ///
+    /// Returns [`DeviceError::DeviceLost`] if the device is lost while waiting.
+    fn wait_idle(&mut self) -> Result<(), DeviceError>;
+
+    /// Releases references to resources used by completed commands without waiting.
+    ///
+    /// Commands are tracked per checkpoint, so resources of commands submitted
+    /// after the last checkpoint are not released.
+    fn reclaim(&mut self) -> Result<(), OutOfMemory>;
 }
 
 pub trait SyncCommandEncoder {
+    type Image: Image;
+
     /// Synchronizes the access to the resources.
     /// Commands in `before` stages of subsequent commands will be
     /// executed only after commands in `after` stages of previous commands
@@ -113,68 +535,282 @@ pub trait SyncCommandEncoder {
     /// executed only after commands in `after` stages of previous commands
     /// are finished.
     /// Image content is discarded.
-    fn init_image(
+    ///
+    /// This must be used to initialize the image before first use.
+    /// Use [`sync_image`](SyncCommandEncoder::sync_image) to synchronize
+    /// access to the image that contains data.
+    fn init_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Self::Image);
+
+    /// Synchronizes the access to the image.
+    /// Commands in `before` stages of subsequent commands will be
+    /// executed only after commands in `after` stages of previous commands
+    /// are finished.
+    /// Image content is preserved.
+    ///
+    /// Image must be initialized with [`init_image`](SyncCommandEncoder::init_image) before.
+    fn sync_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Self::Image);
+
+    /// Same as [`init_image`](SyncCommandEncoder::init_image)
+    /// but affects only given mip levels and array layers of the image.
+    ///
+    /// Ranges are relative to the image view's base level and layer.
+    /// Debug builds track initialization of whole images only,
+    /// so re-initialization of a range is not reported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ranges are empty or out of image bounds.
+    fn init_image_range(
+        &mut self,
+        after: PipelineStages,
+        before: PipelineStages,
+        image: &Self::Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    );
+
+    /// Same as [`sync_image`](SyncCommandEncoder::sync_image)
+    /// but affects only given mip levels and array layers of the image.
+    ///
+    /// Ranges are relative to the image view's base level and layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if ranges are empty or out of image bounds.
+    fn sync_image_range(
         &mut self,
         after: PipelineStages,
         before: PipelineStages,
-        image: &crate::backend::Image,
+        image: &Self::Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
     );
 }
 
 pub trait CommandEncoder: SyncCommandEncoder {
+    type Frame: Frame<Image = Self::Image>;
+    type CommandBuffer;
+    type CopyCommandEncoder<'a>: CopyCommandEncoder<Image = Self::Image>
+    where
+        Self: 'a;
+    type AccelerationStructureCommandEncoder<'a>: AccelerationStructureCommandEncoder
+    where
+        Self: 'a;
+    type ComputeCommandEncoder<'a>: ComputeCommandEncoder<Image = Self::Image>
+    where
+        Self: 'a;
+    type RenderCommandEncoder<'a>: RenderCommandEncoder
+    where
+        Self: 'a;
+
     /// Presents the frame to the surface.
-    fn present(&mut self, frame: crate::backend::Frame, after: PipelineStages);
+    ///
+    /// Frame may be presented from any queue whose family supports the surface,
+    /// see [`Device::supports_present`].
+    /// Graphics stages in `after` are ignored on queues without graphics capability.
+    /// Submitting to a queue of a family that can't present the frame
+    /// fails with [`DeviceError::PresentNotSupported`].
+    ///
+    /// Frame's image must not be used by commands recorded after this call,
+    /// as the image is transitioned to present layout and returned to the swapchain.
+    /// Debug builds panic on such use in render passes and copies.
+    /// Submission that presents the same swapchain image twice
+    /// fails with [`DeviceError::FramePresentedTwice`].
+    fn present(&mut self, frame: Self::Frame, after: PipelineStages);
+
+    /// Presents the frame to the surface, hinting that only `damage` rectangles
+    /// changed since the frame previously presented from the same surface.
+    ///
+    /// Presentation engine may use damage to reduce composition work.
+    /// Rectangles are clamped to the frame image.
+    /// Damage is ignored unless
+    /// [`Features::INCREMENTAL_PRESENT`](crate::Features::INCREMENTAL_PRESENT) is enabled,
+    /// so whole frame must be rendered in any case.
+    fn present_with_damage(&mut self, frame: Self::Frame, after: PipelineStages, damage: &[Rect2]);
+
+    /// Returns capabilities of the queue this encoder records commands for.
+    ///
+    /// Library code may use this to check that encoder received from the user
+    /// can record commands it needs.
+    fn capabilities(&self) -> QueueFlags;
+
+    /// Records breadcrumb `marker` that is written
+    /// after all previously recorded commands complete.
+    ///
+    /// After [`DeviceError::DeviceLost`] markers may be read with
+    /// [`Queue::last_breadcrumbs`] to find the last pass that finished before the hang.
+    /// Not to be confused with queue checkpoints that track submission completion.
+    ///
+    /// Each marker waits for previous commands, so they should be placed between passes
+    /// and not between every command.
+    /// No-op on Metal and in encoders that record reusable command buffers.
+    fn checkpoint(&mut self, marker: u32);
 
     /// Finishes encoding and returns the command buffer.
-    fn finish(self) -> Result<crate::backend::CommandBuffer, OutOfMemory>;
+    fn finish(self) -> Result<Self::CommandBuffer, OutOfMemory>;
 
     /// Returns encoder for copy commands.
-    fn copy(&mut self) -> crate::backend::CopyCommandEncoder<'_>;
+    fn copy(&mut self) -> Self::CopyCommandEncoder<'_>;
 
-    fn acceleration_structure(&mut self)
-        -> crate::backend::AccelerationStructureCommandEncoder<'_>;
+    /// Returns encoder for acceleration structure commands.
+    ///
+    /// Queue must have [`QueueFlags::COMPUTE`] capability.
+    fn acceleration_structure(&mut self) -> Self::AccelerationStructureCommandEncoder<'_>;
 
-    fn compute(&mut self) -> crate::backend::ComputeCommandEncoder<'_>;
+    /// Returns encoder for compute commands.
+    ///
+    /// Queue must have [`QueueFlags::COMPUTE`] capability.
+    fn compute(&mut self) -> Self::ComputeCommandEncoder<'_>;
 
     /// Starts rendering and returns encoder for render commands.
-    fn render(&mut self, desc: RenderPassDesc) -> crate::backend::RenderCommandEncoder<'_>;
+    ///
+    /// Queue must have [`QueueFlags::GRAPHICS`] capability.
+    fn render(&mut self, desc: RenderPassDesc) -> Self::RenderCommandEncoder<'_>;
+}
+
+pub trait ReusableCommandEncoder:
+    DerefMut<Target = <Self as ReusableCommandEncoder>::CommandEncoder>
+{
+    type CommandEncoder: CommandEncoder;
+    type ReusableCommandBuffer: ReusableCommandBuffer;
+
+    /// Finishes encoding and returns reusable command buffer.
+    fn finish(self) -> Result<Self::ReusableCommandBuffer, OutOfMemory>;
+}
+
+pub trait ReusableCommandBuffer: Clone + Send + Sync + 'static {
+    /// Returns `true` if command buffer references any swapchain image.
+    ///
+    /// Such command buffer becomes invalid when swapchain is recreated
+    /// and must be recorded again.
+    fn references_swapchain(&self) -> bool;
 }
 
 pub trait ComputeCommandEncoder: SyncCommandEncoder {
+    type ComputePipeline;
+
+    /// Returns capabilities of the queue this encoder records commands for.
+    fn capabilities(&self) -> QueueFlags;
+
     /// Sets the current compute pipeline.
-    fn with_pipeline(&mut self, pipeline: &crate::backend::ComputePipeline);
+    ///
+    /// Argument group `N` bound before stays bound with the new pipeline
+    /// if layouts of groups `0..=N` and constants are identical
+    /// in both pipelines.
+    /// Other groups must be bound again before dispatch.
+    /// Vulkan backend panics on dispatch with unbound group in debug builds.
+    fn with_pipeline(&mut self, pipeline: &Self::ComputePipeline);
 
     /// Sets arguments group for the current pipeline.
+    ///
+    /// Bound argument groups must not read and write overlapping
+    /// subresources of the same image, e.g. sample a mip level
+    /// while storing into it.
+    /// Such accesses must be split into separate dispatches
+    /// with a barrier between them.
+    /// Vulkan backend panics on dispatch with such groups bound in debug builds,
+    /// naming the two conflicting fields.
+    /// Metal backend doesn't check this.
     fn with_arguments(&mut self, group: u32, arguments: &impl Arguments);
 
     /// Sets constants for the current pipeline.
     fn with_constants(&mut self, constants: &impl DeviceRepr);
 
+    /// Enables or disables automatic barriers between dispatches.
+    ///
+    /// When enabled, encoder records which resources are bound
+    /// as storage (written) and as other kinds (read),
+    /// and inserts compute to compute barrier before dispatch
+    /// that accesses resources written by previous dispatches
+    /// or writes resources read by them.
+    ///
+    /// Tracking is limited to this encoder.
+    /// Barriers with other encoders are still required.
+    ///
+    /// Disabled by default.
+    fn set_auto_barrier(&mut self, enabled: bool);
+
     /// Dispatches compute work.
+    ///
+    /// Skipped if number of groups is zero in any dimension.
     fn dispatch(&mut self, groups: Extent3);
 }
 
 pub trait CopyCommandEncoder: SyncCommandEncoder {
+    type Buffer: Buffer;
+
+    /// Copies first layer of the base level of `src` image into `dst` image.
+    /// Copied region is clamped to the smaller of the two images.
+    ///
+    /// Images must have formats of the same size.
+    /// This is the way to present an offscreen image
+    /// when frames can't be written as storage images.
+    /// Frame images must be configured with `ImageUsage::TRANSFER_DST` usage.
+    fn copy_image(&mut self, src: &Self::Image, dst: &Self::Image);
+
+    /// Copies data from `src` buffer slice into `dst` buffer slice.
+    /// Copied size is the smaller of the two slices.
+    ///
+    /// Slices must not overlap if they belong to the same buffer.
+    fn copy_buffer(&mut self, src: impl AsBufferSlice, dst: impl AsBufferSlice);
+
     /// Fills the buffer slice with the given byte.
+    ///
+    /// Does nothing if slice is empty.
     fn fill_buffer(&mut self, slice: impl AsBufferSlice, byte: u8);
 
     /// Writes data to the buffer.
+    ///
+    /// Data may be of any size, but slice offset must be a multiple of 4.
+    ///
+    /// Writes are not ordered against other commands, including copies and other writes.
+    /// Use [`SyncCommandEncoder::barrier`] with [`PipelineStages::TRANSFER`]
+    /// between a write and commands recorded before or after it
+    /// that access the same range of the buffer.
+    /// Debug builds may warn when written buffer was used
+    /// in a previous pass without a barrier in between.
+    ///
+    /// # Panics
+    ///
+    /// Panics if data does not fit into the slice
+    /// or slice offset is not a multiple of 4.
     fn write_buffer_raw(&mut self, slice: impl AsBufferSlice, data: &[u8]);
 
     /// Writes data to the buffer.
+    ///
+    /// See [`CopyCommandEncoder::write_buffer_raw`].
     fn write_buffer(&mut self, slice: impl AsBufferSlice, data: &impl bytemuck::Pod);
 
     /// Writes data to the buffer.
+    ///
+    /// See [`CopyCommandEncoder::write_buffer_raw`].
     fn write_buffer_slice(&mut self, slice: impl AsBufferSlice, data: &[impl bytemuck::Pod]);
 
+    /// Copies pixels from src image to dst buffer.
+    ///
+    /// Pixels are written to the buffer starting from `start` offset.
+    /// Zero `bytes_per_line` and `bytes_per_plane` mean tightly packed pixels.
+    fn copy_image_to_buffer(
+        &mut self,
+        src: &Self::Image,
+        offset: Offset3<u32>,
+        extent: Extent3<u32>,
+        layers: Range<u32>,
+        level: u32,
+        dst: &Self::Buffer,
+        start: u64,
+        bytes_per_line: u64,
+        bytes_per_plane: u64,
+    );
+
     /// Copies pixels from src image to dst image.
     fn copy_buffer_to_image(
         &mut self,
-        src: &crate::backend::Buffer,
-        start: usize,
-        bytes_per_line: usize,
-        bytes_per_plane: usize,
-        dst: &crate::backend::Image,
+        src: &Self::Buffer,
+        start: u64,
+        bytes_per_line: u64,
+        bytes_per_plane: u64,
+        dst: &Self::Image,
         offset: Offset3<u32>,
         extent: Extent3<u32>,
         layers: Range<u32>,
@@ -182,13 +818,22 @@ pub trait CopyCommandEncoder: SyncCommandEncoder {
     );
 
     /// Copies pixels from src image to dst image.
+    ///
+    /// `src` and `dst` may be the same image or views of it,
+    /// e.g. to build mip chain by copying each level into the next one.
+    /// Successive copies within the same image in this encoder
+    /// are synchronized automatically, so each copy sees results of previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if copy within the same image reads and writes the same texels.
     fn copy_image_region(
         &mut self,
-        src: &crate::backend::Image,
+        src: &Self::Image,
         src_level: u32,
         src_base_layer: u32,
         src_offset: Offset3<u32>,
-        dst: &crate::backend::Image,
+        dst: &Self::Image,
         dst_level: u32,
         dst_base_layer: u32,
         dst_offset: Offset3<u32>,
@@ -198,58 +843,238 @@ pub trait CopyCommandEncoder: SyncCommandEncoder {
 }
 
 pub trait RenderCommandEncoder {
+    type RenderPipeline;
+
+    /// Returns capabilities of the queue this encoder records commands for.
+    fn capabilities(&self) -> QueueFlags;
+
     /// Sets the current render pipeline.
-    fn with_pipeline(&mut self, pipeline: &crate::backend::RenderPipeline);
+    ///
+    /// Viewport and scissor are not affected by pipeline changes,
+    /// values set before are used for draws with the new pipeline.
+    ///
+    /// Argument groups stay bound under the same rule as
+    /// [`ComputeCommandEncoder::with_pipeline`].
+    fn with_pipeline(&mut self, pipeline: &Self::RenderPipeline);
 
+    /// Sets viewport for following draws.
+    ///
+    /// `offset.z` and `offset.z + extent.depth` are minimum and maximum depth.
+    /// Prefer [`with_depth_range`](RenderCommandEncoder::with_depth_range)
+    /// for reversed depth ranges.
+    ///
+    /// Viewport persists until changed or encoder is dropped,
+    /// including across [`with_pipeline`](RenderCommandEncoder::with_pipeline) calls.
+    /// Render pass starts with viewport covering the whole render area
+    /// with depth range `0..1`.
     fn with_viewport(&mut self, offset: Offset3<f32>, extent: Extent3<f32>);
 
+    /// Sets depth range of the viewport for following draws.
+    ///
+    /// Depth at near plane is mapped to `near` and depth at far plane to `far`.
+    /// Both must be in `0..=1` range.
+    /// `near` may be greater than `far`, e.g. `with_depth_range(1.0, 0.0)` for reverse-Z.
+    /// Reverse-Z also needs [`CompareFunction::Greater`](crate::CompareFunction::Greater)
+    /// or [`CompareFunction::GreaterEqual`](crate::CompareFunction::GreaterEqual) depth test
+    /// and depth attachment cleared to `0.0`.
+    ///
+    /// Rectangle of the viewport is not changed.
+    fn with_depth_range(&mut self, near: f32, far: f32);
+
+    /// Sets scissor rectangle for following draws.
+    ///
+    /// Scissor persists until changed or encoder is dropped,
+    /// including across [`with_pipeline`](RenderCommandEncoder::with_pipeline) calls.
+    /// Render pass starts with scissor covering the whole render area.
     fn with_scissor(&mut self, offset: Offset2<i32>, extent: Extent2<u32>);
 
     /// Sets arguments group for the current pipeline.
+    ///
+    /// Bound argument groups must not read and write overlapping
+    /// subresources of the same image.
+    /// See [`ComputeCommandEncoder::with_arguments`].
     fn with_arguments(&mut self, group: u32, arguments: &impl Arguments);
 
     /// Sets constants for the current pipeline.
     fn with_constants(&mut self, constants: &impl DeviceRepr);
 
     /// Bind vertex buffer to the current pipeline.
+    ///
+    /// Does nothing if `slices` is empty.
     fn bind_vertex_buffers(&mut self, start: u32, slices: &[impl AsBufferSlice]);
 
     /// Bind index buffer to the current pipeline.
     fn bind_index_buffer(&mut self, slice: impl AsBufferSlice);
 
     /// Draws primitives.
+    ///
+    /// Skipped if either range is empty.
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>);
 
     /// Draws primitives with indices.
+    ///
+    /// Skipped if either range is empty.
     fn draw_indexed(&mut self, vertex_offset: i32, indices: Range<u32>, instances: Range<u32>);
 }
 
 pub trait AccelerationStructureCommandEncoder {
-    fn build_blas(
-        &mut self,
-        blas: &crate::backend::Blas,
-        desc: BlasBuildDesc,
-        scratch: impl AsBufferSlice,
-    );
+    type Blas;
+    type Tlas;
 
-    fn build_tlas(
-        &mut self,
-        tlas: &crate::backend::Tlas,
-        desc: TlasBuildDesc,
-        scratch: impl AsBufferSlice,
-    );
+    fn build_blas(&mut self, blas: &Self::Blas, desc: BlasBuildDesc, scratch: impl AsBufferSlice);
+
+    fn build_tlas(&mut self, tlas: &Self::Tlas, desc: TlasBuildDesc, scratch: impl AsBufferSlice);
 }
 
 pub trait Surface: Send + Sync + 'static {
+    type Frame: Frame;
+
     /// Acquires next frame from the surface.
-    fn next_frame(&mut self) -> Result<crate::backend::Frame, SurfaceError>;
+    fn next_frame(&mut self) -> Result<Self::Frame, SurfaceError>;
+
+    /// Returns usages that frame images of this surface may have.
+    ///
+    /// `ImageUsage::TARGET` is always supported.
+    fn supported_usage(&self) -> Result<ImageUsage, SurfaceError>;
+
+    /// Sets usage of frame images.
+    /// Takes effect when next frame is acquired.
+    ///
+    /// Use `ImageUsage::STORAGE` to write frames from compute shaders
+    /// or `ImageUsage::TRANSFER_DST` to copy offscreen image into frames.
+    ///
+    /// On Vulkan frames also get `ImageUsage::TRANSFER_SRC`
+    /// if surface supports it, so presented frames can be captured.
+    /// On Metal it must be requested explicitly.
+    /// Check effective usage with `frame.image().usage()`.
+    /// If frames can't be copied from, render into offscreen image,
+    /// copy it into the frame and capture the offscreen image instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if usage is not a subset of `supported_usage`.
+    fn set_usage(&mut self, usage: ImageUsage) -> Result<(), SurfaceError>;
+
+    /// Returns range of frame image counts supported by the surface.
+    ///
+    /// Upper bound is `u32::MAX` if surface has no limit.
+    fn supported_image_count(&self) -> Result<RangeInclusive<u32>, SurfaceError>;
+
+    /// Sets desired number of frame images.
+    /// Takes effect when next frame is acquired.
+    ///
+    /// Fewer images reduce latency, more images allow CPU to run ahead.
+    /// Default is 3.
+    ///
+    /// If surface limits change later, e.g. on resize,
+    /// the count is clamped to new limits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if count is not in `supported_image_count` range.
+    fn set_image_count(&mut self, count: u32) -> Result<(), SurfaceError>;
+
+    /// Returns number of frame images actually used by the surface.
+    ///
+    /// Returns 0 if no frames were acquired yet.
+    fn image_count(&self) -> u32;
+
+    /// Sets policy of recreating suboptimal swapchain.
+    ///
+    /// Default is [`SuboptimalPolicy::Cooldown(10)`](SuboptimalPolicy::Cooldown).
+    fn set_suboptimal_policy(&mut self, policy: SuboptimalPolicy);
+
+    /// Returns `true` if frames of the current swapchain were reported suboptimal,
+    /// e.g. window was resized since the swapchain was created.
+    /// Fake frames are always suboptimal.
+    ///
+    /// Reset when swapchain is recreated.
+    fn is_suboptimal(&self) -> bool;
+
+    /// Requests swapchain recreation.
+    /// Takes effect when next frame is acquired.
+    ///
+    /// Intended for [`SuboptimalPolicy::Manual`],
+    /// but works with any policy.
+    fn recreate(&mut self);
 }
 
 pub trait Frame: Send + Sync + 'static {
-    fn image(&self) -> &crate::backend::Image;
+    type Image: Image;
+
+    /// Returns image of the frame.
+    fn image(&self) -> &Self::Image;
+
+    /// Returns `true` if the frame is not backed by a swapchain image.
+    ///
+    /// Fake frames are returned while surface can't be presented to,
+    /// e.g. when window is minimized.
+    /// Content of fake frames is never shown,
+    /// so rendering into them may be skipped.
+    /// Syncing and presenting fake frames is valid and does nothing.
+    fn is_fake(&self) -> bool;
+
+    /// Returns viewport covering the whole frame
+    /// with depth range `0..1`.
+    ///
+    /// Viewport origin is the top-left corner and Y axis points down
+    /// on all backends.
+    /// Shaders are translated so that positive Y in clip space points up,
+    /// so no Y-flip is required for this viewport.
+    fn viewport(&self) -> (Offset3<f32>, Extent3<f32>);
+
+    /// Returns scissor rectangle covering the whole frame.
+    fn scissor(&self) -> (Offset2<i32>, Extent2<u32>);
+
+    /// Returns `true` if presentation of this frame signals a present fence.
+    ///
+    /// Otherwise completion of presentation is approximated
+    /// by completion of the submission that presented the frame.
+    /// Fake frames never have present fence.
+    ///
+    /// See [`Features::PRESENT_FENCES`].
+    fn has_present_fence(&self) -> bool;
+}
+
+/// Resource created by the device.
+///
+/// Implemented by buffers, images, samplers and pipelines.
+pub trait Resource {
+    /// Returns identifier of the resource.
+    ///
+    /// Identifiers are unique per device and stable for the lifetime of the resource.
+    /// Ordered by creation, so suitable as deterministic sort keys.
+    fn id(&self) -> ResourceId;
+
+    /// Returns human-readable multi-line description of the resource.
+    ///
+    /// Includes identifier, name if any and parameters the resource was created with.
+    /// Never includes backend handles, so it is stable across runs
+    /// and suitable for logs and bug reports.
+    fn describe(&self) -> String;
+}
+
+/// Render or compute pipeline created by the device.
+pub trait Pipeline: Resource {
+    /// Returns layouts of argument groups the pipeline was created with.
+    ///
+    /// Group at index `N` is bound with `with_arguments(N, ...)`.
+    /// Use [`ArgumentGroupInfo::layout`] to create pipelines
+    /// with compatible groups.
+    fn argument_groups(&self) -> &[ArgumentGroupInfo];
+
+    /// Returns size in bytes of the constants the pipeline was created with.
+    fn constants_size(&self) -> usize;
 }
 
-pub trait Image: Clone + Debug + Eq + Hash + Send + Sync + 'static {
+pub trait Image: Resource + Clone + Debug + Eq + Hash + Send + Sync + 'static {
+    type Device: Device;
+
+    /// Returns identifier of the image this view belongs to.
+    ///
+    /// Equals [`Resource::id`] for images that are not views.
+    fn image_id(&self) -> ResourceId;
+
     /// Returns the pixel format of the image.
     fn format(&self) -> PixelFormat;
 
@@ -266,11 +1091,33 @@ pub trait Image: Clone + Debug + Eq + Hash + Send + Sync + 'static {
     fn usage(&self) -> ImageUsage;
 
     /// Returns new image that is a view into this image.
-    fn view(
-        &self,
-        device: &crate::backend::Device,
-        desc: ViewDesc,
-    ) -> Result<crate::backend::Image, OutOfMemory>;
+    ///
+    /// On Vulkan views are cached per image.
+    /// Views with equal descriptors share the same `VkImageView`
+    /// for as long as the image lives,
+    /// so render passes that use such views as attachments across frames
+    /// don't create new objects and benefit from driver caching.
+    fn view(&self, device: &Self::Device, desc: ViewDesc) -> Result<Self, ViewError>;
+
+    /// Returns view of the image with sRGB variant of its format.
+    ///
+    /// Shaders sample linear values decoded from stored sRGB values
+    /// and values written by render passes are encoded.
+    /// Use for color textures authored in sRGB, like most PNG images.
+    ///
+    /// Swapchain images can't be reinterpreted
+    /// and return [`ViewError::SwapchainFormat`] unless already in sRGB format.
+    fn srgb_view(&self, device: &Self::Device) -> Result<Self, ViewError>;
+
+    /// Returns view of the image with linear variant of its format.
+    ///
+    /// Shaders sample and write stored values without conversion.
+    /// Use for data textures like normal maps and for sRGB-encoded images
+    /// when shader encodes values itself.
+    ///
+    /// Swapchain images can't be reinterpreted
+    /// and return [`ViewError::SwapchainFormat`] unless already in linear format.
+    fn linear_view(&self, device: &Self::Device) -> Result<Self, ViewError>;
 
     /// Returns `true` if the image is not shared,
     /// meaning that there are no other references to the image
@@ -284,9 +1131,14 @@ pub trait Image: Clone + Debug + Eq + Hash + Send + Sync + 'static {
     fn detached(&self) -> bool;
 }
 
-pub trait Buffer: Clone + Debug + Eq + Hash + Send + Sync + 'static {
+pub trait Buffer: Resource + Clone + Debug + Eq + Hash + Send + Sync + 'static {
+    /// Guard of the buffer memory mapped with [`Buffer::mapped_mut`].
+    type MappedSlice<'a>: DerefMut<Target = [u8]>
+    where
+        Self: 'a;
+
     /// Returns the size of the buffer in bytes.
-    fn size(&self) -> usize;
+    fn size(&self) -> u64;
 
     /// Returns `true` if the buffer is not shared,
     /// meaning that there are no other references to the buffer
@@ -308,10 +1160,81 @@ pub trait Buffer: Clone + Debug + Eq + Hash + Send + Sync + 'static {
     ///
     /// Use [`CommandEncoder::write_buffer`] to update
     /// buffer in a bit safer way.
-    unsafe fn write_unchecked(&mut self, offset: usize, data: &[u8]);
+    unsafe fn write_unchecked(&mut self, offset: u64, data: &[u8]);
+
+    /// Read data from the buffer.
+    ///
+    /// Buffer must be allocated with host-visible memory,
+    /// typically [`Memory::Download`](crate::Memory::Download).
+    ///
+    /// # Safety
+    ///
+    /// Calling this function is unsafe because
+    /// Other threads or GPU may access the same buffer region.
+    ///
+    /// Commands that write the buffer must be completed,
+    /// e.g. by waiting for the queue to become idle.
+    unsafe fn read_unchecked(&mut self, offset: u64, data: &mut [u8]);
+
+    /// Maps range of the buffer memory to write it from host.
+    ///
+    /// Returned guard dereferences to bytes of the range.
+    /// When guard is dropped, non-coherent memory is flushed,
+    /// so commands submitted afterwards see written bytes.
+    ///
+    /// Buffer must be allocated with host-visible memory,
+    /// i.e. not [`Memory::Device`](crate::Memory::Device),
+    /// and must be [`detached`](Buffer::detached).
+    /// Exclusive borrow prevents other writes from host through this handle,
+    /// and detached buffer is not referenced by pending commands of this crate.
+    /// Still, device accesses are not synchronized with host writes.
+    /// Buffer written every frame should have a copy per frame in flight,
+    /// see [`PerFrame`](crate::PerFrame).
+    fn mapped_mut(&mut self, range: impl BufferIndex) -> Result<Self::MappedSlice<'_>, MapError>;
 }
 
-pub trait Library {
+/// Table of sampled images that shaders index at runtime.
+///
+/// Table is used as a field of [`Arguments`] structure
+/// that has no other fields and binds to a runtime-sized binding array
+/// of sampled textures in the shader.
+/// Unset elements must not be accessed by the shader.
+///
+/// Elements that pending command buffers don't access may be updated at any time.
+/// Replaced images are kept alive until those command buffers complete.
+/// Shader accesses of the images are not tracked by the encoder
+/// and require explicit barriers after writes.
+///
+/// On Metal table is bound to consecutive texture slots
+/// and changes don't affect commands recorded before.
+pub trait BindlessTable: Send + Sync + 'static {
+    type Image: Image;
+
+    /// Returns capacity of the table.
+    fn capacity(&self) -> u32;
+
+    /// Puts image into the table at given index.
+    ///
+    /// Image must have [`ImageUsage::SAMPLED`] usage.
+    /// Panics if index is out of bounds.
+    fn set(&mut self, index: u32, image: &Self::Image);
+
+    /// Removes image from the table at given index.
+    ///
+    /// Panics if index is out of bounds.
+    fn clear(&mut self, index: u32);
+
+    /// Returns image at given index.
+    fn get(&self, index: u32) -> Option<&Self::Image>;
+}
+
+pub trait Library: Send + Sync {
     /// Returns shader entry point.
     fn entry<'a>(&self, entry: &'a str) -> Shader<'a>;
+
+    /// Returns entry points of the library.
+    ///
+    /// Empty if the library was not reflected,
+    /// e.g. when created from shading language native to the backend.
+    fn entry_points(&self) -> &[EntryPoint];
 }
@@ -0,0 +1,231 @@
+//! Copying images between devices.
+//!
+//! Images can't be shared between devices directly.
+//! Functions in this module copy image content through host memory:
+//! image is downloaded on the source device, waited for,
+//! and then uploaded to the destination device.
+//!
+//! This is slow but works with any pair of devices.
+
+use crate::{
+    Buffer, BufferDesc, BufferUsage, DeviceError, Image, Memory, Offset3, PipelineStages, Queue,
+    SubmissionId,
+};
+
+/// Maximum number of staging buffers kept in each pool.
+/// Oldest buffers are dropped when the limit is exceeded.
+const MAX_POOLED_BUFFERS: usize = 4;
+
+/// Staging buffers reused by image transfers between two devices.
+///
+/// Must be used with the same pair of devices,
+/// as staging buffers belong to them.
+pub struct ImageTransfer {
+    download: Vec<Buffer>,
+    upload: Vec<Buffer>,
+    data: Vec<u8>,
+}
+
+/// Guard of the image transfer.
+///
+/// Commands submitted to the destination queue after the transfer
+/// see the copied image content.
+/// Use [`TransferGuard::wait`] to wait until the transfer is completed.
+#[must_use]
+pub struct TransferGuard {
+    submission: SubmissionId,
+}
+
+impl TransferGuard {
+    /// Returns submission to the destination queue that copies the image.
+    pub fn submission(&self) -> SubmissionId {
+        self.submission
+    }
+
+    /// Waits until the destination image is ready.
+    ///
+    /// `queue` must be the destination queue of the transfer.
+    pub fn wait(self, queue: &mut Queue) -> Result<(), DeviceError> {
+        queue.wait_submission(self.submission)
+    }
+}
+
+impl ImageTransfer {
+    pub fn new() -> Self {
+        ImageTransfer {
+            download: Vec::new(),
+            upload: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Copies first layer of the base level of `src` image
+    /// owned by `src_queue` device into `dst` image owned by `dst_queue` device.
+    ///
+    /// Images must have the same format and extent.
+    /// `src` must be created with `ImageUsage::TRANSFER_SRC`
+    /// and `dst` with `ImageUsage::TRANSFER_DST` usage.
+    /// Both images must be initialized.
+    ///
+    /// Blocks until the source image is downloaded.
+    pub fn copy(
+        &mut self,
+        src_queue: &mut Queue,
+        src: &Image,
+        dst_queue: &mut Queue,
+        dst: &Image,
+    ) -> Result<TransferGuard, DeviceError> {
+        assert_eq!(
+            src.format(),
+            dst.format(),
+            "Images must have the same format"
+        );
+        assert_eq!(
+            src.extent(),
+            dst.extent(),
+            "Images must have the same extent"
+        );
+
+        let extent = src.extent().into_3d();
+        let bytes_per_line = u64::from(extent.width()) * src.format().size() as u64;
+        let bytes_per_plane = bytes_per_line * u64::from(extent.height());
+        let size = bytes_per_plane * u64::from(extent.depth());
+
+        // Image data passes through host memory.
+        let host_size = usize::try_from(size).map_err(|_| DeviceError::OutOfMemory)?;
+
+        let mut download = pick_buffer(
+            &mut self.download,
+            src_queue,
+            size,
+            BufferUsage::TRANSFER_DST,
+            Memory::Download,
+            "mev-transfer-download",
+        )?;
+
+        let mut encoder = src_queue.new_command_encoder()?;
+        let mut copy = encoder.copy();
+        copy.sync_image(PipelineStages::ALL_NON_RT, PipelineStages::TRANSFER, src);
+        copy.copy_image_to_buffer(
+            src,
+            Offset3::ZERO,
+            extent,
+            0..1,
+            0,
+            &download,
+            0,
+            bytes_per_line,
+            bytes_per_plane,
+        );
+        drop(copy);
+        let submission = src_queue.submit_one(encoder.finish()?, true)?;
+
+        // Waiting releases references held by the submission,
+        // so the buffer is detached and can be read.
+        src_queue.wait_submission(submission)?;
+        debug_assert!(download.detached());
+
+        self.data.resize(host_size, 0);
+
+        // Safety: commands that write the buffer are completed.
+        unsafe {
+            download.read_unchecked(0, &mut self.data);
+        }
+        return_buffer(&mut self.download, download);
+
+        // Release uploads of previous transfers that are complete.
+        dst_queue.reclaim()?;
+
+        let mut upload = pick_buffer(
+            &mut self.upload,
+            dst_queue,
+            size,
+            BufferUsage::TRANSFER_SRC,
+            Memory::Upload,
+            "mev-transfer-upload",
+        )?;
+
+        // Safety: buffer is not used by the device.
+        unsafe {
+            upload.write_unchecked(0, &self.data);
+        }
+
+        let mut encoder = dst_queue.new_command_encoder()?;
+        let mut copy = encoder.copy();
+        copy.sync_image(PipelineStages::ALL_NON_RT, PipelineStages::TRANSFER, dst);
+        copy.copy_buffer_to_image(
+            &upload,
+            0,
+            bytes_per_line,
+            bytes_per_plane,
+            dst,
+            Offset3::ZERO,
+            extent,
+            0..1,
+            0,
+        );
+        copy.sync_image(PipelineStages::TRANSFER, PipelineStages::ALL_NON_RT, dst);
+        drop(copy);
+
+        // Submission keeps the buffer alive until it completes,
+        // it is picked again once detached.
+        let submission = dst_queue.submit_one(encoder.finish()?, true)?;
+        return_buffer(&mut self.upload, upload);
+
+        Ok(TransferGuard { submission })
+    }
+}
+
+/// Copies first layer of the base level of `src` image
+/// owned by `src_queue` device into `dst` image owned by `dst_queue` device.
+///
+/// Staging buffers are not reused between calls.
+/// Use [`ImageTransfer`] to copy images repeatedly.
+///
+/// See [`ImageTransfer::copy`] for details.
+pub fn copy_image_between_devices(
+    src_queue: &mut Queue,
+    src: &Image,
+    dst_queue: &mut Queue,
+    dst: &Image,
+) -> Result<TransferGuard, DeviceError> {
+    ImageTransfer::new().copy(src_queue, src, dst_queue, dst)
+}
+
+/// Takes unused buffer large enough from the pool
+/// or creates new one.
+fn pick_buffer(
+    pool: &mut Vec<Buffer>,
+    queue: &Queue,
+    size: u64,
+    usage: BufferUsage,
+    memory: Memory,
+    name: &str,
+) -> Result<Buffer, DeviceError> {
+    if let Some(idx) = pool
+        .iter()
+        .position(|buffer| buffer.size() >= size && buffer.detached())
+    {
+        return Ok(pool.remove(idx));
+    }
+
+    let buffer = queue
+        .new_buffer(BufferDesc {
+            size,
+            usage,
+            memory,
+            name,
+            shared_across_queues: false,
+        })
+        .map_err(|_| DeviceError::OutOfMemory)?;
+    Ok(buffer)
+}
+
+/// Returns buffer to the pool.
+/// Drops the oldest buffer if the pool is full.
+fn return_buffer(pool: &mut Vec<Buffer>, buffer: Buffer) {
+    if pool.len() >= MAX_POOLED_BUFFERS {
+        pool.remove(0);
+    }
+    pool.push(buffer);
+}
@@ -0,0 +1,158 @@
+//! Creating images with content generated on the host.
+//!
+//! Useful for small procedural textures like noise, gradients and lookup tables
+//! that don't come from asset files.
+
+use std::mem::size_of;
+
+use crate::{
+    generic::align::{image_data_layout, mip_extent},
+    BufferInitDesc, BufferUsage, DeviceError, Image, ImageDesc, ImageUsage, Memory, Offset3,
+    OutOfMemory, PipelineStages, Queue,
+};
+
+/// Alignment of rows in the staging buffer.
+const ROW_ALIGNMENT: usize = 256;
+
+/// Coordinate of a texel passed to the generating function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TexelCoord {
+    /// Mip level of the texel.
+    pub level: u32,
+
+    /// Array layer of the texel.
+    pub layer: u32,
+
+    /// Coordinate within the mip level.
+    pub x: u32,
+
+    /// Coordinate within the mip level.
+    /// Always 0 for 1D images.
+    pub y: u32,
+
+    /// Coordinate within the mip level.
+    /// Always 0 for 1D and 2D images.
+    pub z: u32,
+}
+
+/// Creates an image and fills every texel of every layer and mip level
+/// with the value returned by `f`.
+///
+/// `f` is called in row-major order, level by level and layer by layer.
+/// `T` must have the size of the format's texel.
+/// Image is created with `TRANSFER_DST` usage added
+/// and is ready for use by commands submitted to the queue afterwards.
+///
+/// ```ignore
+/// let gradient = mev::upload::new_image_with(
+///     &mut queue,
+///     mev::ImageDesc::new(
+///         mev::ImageExtent::D2(mev::Extent2::new(256, 1)),
+///         mev::PixelFormat::Rgba8Unorm,
+///         mev::ImageUsage::SAMPLED,
+///     ),
+///     |texel| [texel.x as u8, 0, 255 - texel.x as u8, 255],
+/// )?;
+/// ```
+///
+/// # Panics
+///
+/// Panics if size of `T` doesn't match the texel size of the format.
+pub fn new_image_with<T: bytemuck::Pod>(
+    queue: &mut Queue,
+    mut desc: ImageDesc,
+    mut f: impl FnMut(TexelCoord) -> T,
+) -> Result<Image, DeviceError> {
+    assert_eq!(
+        size_of::<T>(),
+        desc.format.size(),
+        "Texel type size doesn't match texel size of format {:?}",
+        desc.format
+    );
+
+    desc.usage |= ImageUsage::TRANSFER_DST;
+
+    let extent = desc.extent.into_3d();
+    let layers = desc.layers;
+
+    // Regions follow each other.
+    // Region size is a multiple of the row pitch, so every region starts aligned.
+    let mut regions = Vec::with_capacity(desc.levels as usize);
+    let mut data = Vec::new();
+
+    for level in 0..desc.levels {
+        let level_extent = mip_extent(extent, level);
+        let layout = image_data_layout(desc.format, level_extent, ROW_ALIGNMENT)
+            .ok_or(DeviceError::OutOfMemory)?;
+
+        regions.push((data.len(), layout, level_extent));
+
+        for layer in 0..layers {
+            let start = data.len();
+            data.resize(start + layout.total_size, 0);
+
+            for z in 0..level_extent.depth() {
+                for y in 0..level_extent.height() {
+                    let line = start
+                        + z as usize * layout.bytes_per_plane
+                        + y as usize * layout.bytes_per_line;
+
+                    for x in 0..level_extent.width() {
+                        let texel = f(TexelCoord {
+                            level,
+                            layer,
+                            x,
+                            y,
+                            z,
+                        });
+
+                        let offset = line + x as usize * size_of::<T>();
+                        data[offset..][..size_of::<T>()]
+                            .copy_from_slice(bytemuck::bytes_of(&texel));
+                    }
+                }
+            }
+        }
+    }
+
+    let image = queue.new_image(desc).map_err(OutOfMemory::from)?;
+
+    let upload = queue
+        .new_buffer_init(BufferInitDesc {
+            data: &data,
+            usage: BufferUsage::TRANSFER_SRC,
+            memory: Memory::Upload,
+            name: "mev-upload",
+        })
+        .map_err(OutOfMemory::from)?;
+
+    let mut encoder = queue.new_command_encoder()?;
+    let mut copy = encoder.copy();
+    copy.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, &image);
+
+    for (level, (start, layout, level_extent)) in regions.into_iter().enumerate() {
+        let layer_size = layout.total_size as u64;
+
+        for layer in 0..layers {
+            copy.copy_buffer_to_image(
+                &upload,
+                start as u64 + layer_size * u64::from(layer),
+                layout.bytes_per_line as u64,
+                layout.bytes_per_plane as u64,
+                &image,
+                Offset3::ZERO,
+                level_extent,
+                layer..layer + 1,
+                level as u32,
+            );
+        }
+    }
+
+    copy.sync_image(PipelineStages::TRANSFER, PipelineStages::ALL_NON_RT, &image);
+    drop(copy);
+
+    // Queue keeps the staging buffer alive until the copy is complete.
+    queue.submit_one(encoder.finish()?, false)?;
+
+    Ok(image)
+}
@@ -30,6 +30,18 @@ pub(super) fn access_for_stage(stage: PipelineStage) -> ash::vk::AccessFlags {
         PipelineStage::Transfer => {
             ash::vk::AccessFlags::TRANSFER_READ | ash::vk::AccessFlags::TRANSFER_WRITE
         }
+        PipelineStage::AccelerationStructureBuild => {
+            ash::vk::AccessFlags::ACCELERATION_STRUCTURE_READ_KHR
+                | ash::vk::AccessFlags::ACCELERATION_STRUCTURE_WRITE_KHR
+                | ash::vk::AccessFlags::SHADER_READ
+                | ash::vk::AccessFlags::TRANSFER_READ
+                | ash::vk::AccessFlags::TRANSFER_WRITE
+        }
+        PipelineStage::RayTracingShader => {
+            ash::vk::AccessFlags::ACCELERATION_STRUCTURE_READ_KHR
+                | ash::vk::AccessFlags::SHADER_READ
+                | ash::vk::AccessFlags::SHADER_WRITE
+        }
     }
 }
 
@@ -60,5 +72,18 @@ pub(super) fn access_for_stages(stages: PipelineStages) -> ash::vk::AccessFlags
     if stages.contains(PipelineStages::TRANSFER) {
         access |= ash::vk::AccessFlags::TRANSFER_READ | ash::vk::AccessFlags::TRANSFER_WRITE;
     }
+    if stages.contains(PipelineStages::ACCELERATION_STRUCTURE_BUILD) {
+        // Builds read geometry and instance buffers and may copy structures.
+        access |= ash::vk::AccessFlags::ACCELERATION_STRUCTURE_READ_KHR
+            | ash::vk::AccessFlags::ACCELERATION_STRUCTURE_WRITE_KHR
+            | ash::vk::AccessFlags::SHADER_READ
+            | ash::vk::AccessFlags::TRANSFER_READ
+            | ash::vk::AccessFlags::TRANSFER_WRITE;
+    }
+    if stages.contains(PipelineStages::RAY_TRACING_SHADER) {
+        access |= ash::vk::AccessFlags::ACCELERATION_STRUCTURE_READ_KHR
+            | ash::vk::AccessFlags::SHADER_READ
+            | ash::vk::AccessFlags::SHADER_WRITE;
+    }
     access
 }
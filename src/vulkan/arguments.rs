@@ -1,7 +1,13 @@
-use crate::generic::{ArgumentGroupLayout, ArgumentKind, ArgumentsSealed};
+use smallvec::SmallVec;
+
+use crate::generic::{ArgumentGroupLayout, ArgumentKind, ArgumentsSealed, Quirks};
 
 use super::{
     command::{ComputeCommandEncoder, RenderCommandEncoder},
+    descriptors::DescriptorPools,
+    device::Device,
+    hazards::Accesses,
+    layout::PipelineLayout,
     refs::Refs,
 };
 
@@ -9,6 +15,9 @@ use super::{
 pub trait Arguments: 'static {
     const LAYOUT: ArgumentGroupLayout<'static>;
 
+    /// Names of the fields, indexed by binding.
+    const FIELDS: &'static [&'static str];
+
     /// Descriptor updates matching template created from this type.
     type Update: Copy;
 
@@ -17,10 +26,16 @@ pub trait Arguments: 'static {
     fn template_entries() -> &'static [ash::vk::DescriptorUpdateTemplateEntry];
 
     /// Fill descriptor update template with the value.
-    fn update(&self) -> Self::Update;
+    ///
+    /// `device` provides well-known samplers referenced by the arguments.
+    fn update(&self, device: &Device) -> Self::Update;
 
     /// Add references to descriptors into the `Refs` object.
     fn add_refs(&self, refs: &mut Refs);
+
+    /// Add references to descriptors into the `Accesses` object
+    /// according to argument kinds.
+    fn add_accesses(&self, accesses: &mut Accesses);
 }
 
 impl<T> ArgumentsSealed for T where T: Arguments {}
@@ -32,33 +47,34 @@ where
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn bind_render(&self, group: u32, encoder: &mut RenderCommandEncoder) {
-        let Some(layout) = encoder.current_layout() else {
+        let handle = encoder.handle();
+        let (device, layout, descriptors) = encoder.binding_parts();
+        let Some(layout) = layout else {
             panic!("Argument binding requires a pipeline to be bound to the encoder");
         };
 
-        let device = encoder.device();
+        let update = self.update(device);
 
-        let Ok(template) = device.get_descriptor_update_template::<Self>(
-            Self::template_entries(),
+        bind_descriptor_set::<Self>(
+            device,
+            descriptors,
+            handle,
             ash::vk::PipelineBindPoint::GRAPHICS,
             layout,
             group,
-        ) else {
-            panic!("Failed to create descriptor update template");
-        };
+            &update,
+        );
 
-        let update = self.update();
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            let mut accesses = Accesses::new();
+            self.add_accesses(&mut accesses);
+            encoder.aliases_mut().bind(group, accesses.images());
 
-        unsafe {
-            device
-                .push_descriptor()
-                .cmd_push_descriptor_set_with_template(
-                    encoder.handle(),
-                    template,
-                    layout.handle(),
-                    group,
-                    &update as *const _ as *const _,
-                )
+            let reads = accesses.reads();
+            encoder
+                .device()
+                .validate_sampled_filtering(reads.images(), reads.samplers());
         }
 
         self.add_refs(encoder.refs_mut());
@@ -66,36 +82,235 @@ where
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn bind_compute(&self, group: u32, encoder: &mut ComputeCommandEncoder) {
-        let Some(layout) = encoder.current_layout() else {
+        let handle = encoder.handle();
+        let (device, layout, descriptors) = encoder.binding_parts();
+        let Some(layout) = layout else {
             panic!("Argument binding requires a pipeline to be bound to the encoder");
         };
 
-        let device = encoder.device();
+        let update = self.update(device);
 
-        let Ok(template) = device.get_descriptor_update_template::<Self>(
-            Self::template_entries(),
+        bind_descriptor_set::<Self>(
+            device,
+            descriptors,
+            handle,
             ash::vk::PipelineBindPoint::COMPUTE,
             layout,
             group,
-        ) else {
-            panic!("Failed to create descriptor update template");
+            &update,
+        );
+
+        let track_hazards = encoder.hazards_mut().is_some();
+        if track_hazards || cfg!(any(debug_assertions, feature = "debug")) {
+            let mut accesses = Accesses::new();
+            self.add_accesses(&mut accesses);
+
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            {
+                encoder.aliases_mut().bind(group, accesses.images());
+
+                let reads = accesses.reads();
+                encoder
+                    .device()
+                    .validate_sampled_filtering(reads.images(), reads.samplers());
+            }
+
+            if let Some(hazards) = encoder.hazards_mut() {
+                hazards.bind(group, &accesses);
+            }
+        }
+
+        self.add_refs(encoder.refs_mut());
+    }
+}
+
+/// Checks that buffers bound to the group don't exceed buffer range limits of the device.
+/// Binding larger buffer is undefined behavior.
+#[cfg(any(debug_assertions, feature = "debug"))]
+fn validate_buffer_ranges<T: Arguments>(device: &Device, group: u32, data: *const u8) {
+    let limits = device.limits();
+
+    for entry in T::template_entries() {
+        let (max, limit) = match entry.descriptor_type {
+            ash::vk::DescriptorType::UNIFORM_BUFFER => {
+                (limits.max_uniform_buffer_range, "maxUniformBufferRange")
+            }
+            ash::vk::DescriptorType::STORAGE_BUFFER => {
+                (limits.max_storage_buffer_range, "maxStorageBufferRange")
+            }
+            _ => continue,
         };
 
-        let update = self.update();
+        for idx in 0..entry.descriptor_count as usize {
+            let info = unsafe {
+                data.add(entry.offset + idx * entry.stride)
+                    .cast::<ash::vk::DescriptorBufferInfo>()
+                    .read_unaligned()
+            };
 
-        unsafe {
-            device
-                .push_descriptor()
-                .cmd_push_descriptor_set_with_template(
-                    encoder.handle(),
-                    template,
+            assert!(
+                info.range <= max,
+                "Buffer of {} bytes is bound to field `{}` in group {}, exceeding device limit `{}` {}",
+                info.range,
+                T::FIELDS[entry.dst_binding as usize],
+                group,
+                limit,
+                max,
+            );
+        }
+    }
+}
+
+/// Binds descriptors of the arguments group.
+///
+/// Descriptors are pushed if device supports push descriptors.
+/// Descriptor update template is used unless device applies
+/// [`Quirks::AVOID_PUSH_DESCRIPTOR_TEMPLATES`],
+/// in which case descriptor writes are built from template entries.
+///
+/// Otherwise descriptor set is allocated from the encoder's pools,
+/// written with the template and bound.
+///
+/// Group with bindless table binds the table's descriptor set.
+#[cfg_attr(feature = "inline-more", inline(always))]
+fn bind_descriptor_set<T: Arguments>(
+    device: &Device,
+    descriptors: &mut DescriptorPools,
+    command_buffer: ash::vk::CommandBuffer,
+    bind: ash::vk::PipelineBindPoint,
+    layout: &PipelineLayout,
+    group: u32,
+    update: &T::Update,
+) {
+    let data = update as *const T::Update as *const u8;
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    validate_buffer_ranges::<T>(device, group, data);
+
+    if let [arg] = T::LAYOUT.arguments {
+        if arg.kind == ArgumentKind::BindlessTable {
+            // Update of the table is its descriptor set.
+            let set = unsafe {
+                data.add(T::template_entries()[0].offset)
+                    .cast::<ash::vk::DescriptorSet>()
+                    .read_unaligned()
+            };
+
+            unsafe {
+                device.ash().cmd_bind_descriptor_sets(
+                    command_buffer,
+                    bind,
                     layout.handle(),
                     group,
-                    &update as *const _ as *const _,
-                )
+                    &[set],
+                    &[],
+                );
+            }
+            return;
         }
+    }
 
-        self.add_refs(encoder.refs_mut());
+    let Some(push_descriptor) = device.push_descriptor() else {
+        let Ok(set) = descriptors.allocate(
+            device.ash(),
+            layout.set_layout(group as usize).handle(),
+            layout.group_layout(group as usize),
+        ) else {
+            panic!("Failed to allocate descriptor set");
+        };
+
+        // Set of immutable samplers needs no writes.
+        if !T::template_entries().is_empty() {
+            let Ok(template) = device.get_descriptor_update_template::<T>(
+                T::template_entries(),
+                bind,
+                layout,
+                group,
+            ) else {
+                panic!("Failed to create descriptor update template");
+            };
+
+            unsafe {
+                device
+                    .ash()
+                    .update_descriptor_set_with_template(set, template, data.cast());
+            }
+        }
+
+        unsafe {
+            device.ash().cmd_bind_descriptor_sets(
+                command_buffer,
+                bind,
+                layout.handle(),
+                group,
+                &[set],
+                &[],
+            );
+        }
+        return;
+    };
+
+    // Group of immutable samplers has nothing to push.
+    if T::template_entries().is_empty() {
+        return;
+    }
+
+    if device
+        .quirks()
+        .contains(Quirks::AVOID_PUSH_DESCRIPTOR_TEMPLATES)
+    {
+        // Each template entry references array of descriptor infos
+        // that are tightly packed in the update data.
+        let writes: SmallVec<[ash::vk::WriteDescriptorSet; 8]> = T::template_entries()
+            .iter()
+            .filter(|entry| entry.descriptor_count > 0)
+            .map(|entry| {
+                let info = unsafe { data.add(entry.offset) };
+
+                let mut write = ash::vk::WriteDescriptorSet::default()
+                    .dst_binding(entry.dst_binding)
+                    .dst_array_element(entry.dst_array_element)
+                    .descriptor_type(entry.descriptor_type);
+                write.descriptor_count = entry.descriptor_count;
+
+                match entry.descriptor_type {
+                    ash::vk::DescriptorType::UNIFORM_BUFFER
+                    | ash::vk::DescriptorType::STORAGE_BUFFER => {
+                        write.p_buffer_info = info.cast();
+                    }
+                    _ => {
+                        write.p_image_info = info.cast();
+                    }
+                }
+                write
+            })
+            .collect();
+
+        unsafe {
+            push_descriptor.cmd_push_descriptor_set(
+                command_buffer,
+                bind,
+                layout.handle(),
+                group,
+                &writes,
+            )
+        }
+    } else {
+        let Ok(template) =
+            device.get_descriptor_update_template::<T>(T::template_entries(), bind, layout, group)
+        else {
+            panic!("Failed to create descriptor update template");
+        };
+
+        unsafe {
+            push_descriptor.cmd_push_descriptor_set_with_template(
+                command_buffer,
+                template,
+                layout.handle(),
+                group,
+                data.cast(),
+            )
+        }
     }
 }
 
@@ -132,5 +347,6 @@ pub const fn descriptor_type(kind: ArgumentKind) -> ash::vk::DescriptorType {
         ArgumentKind::StorageBuffer => ash::vk::DescriptorType::STORAGE_BUFFER,
         ArgumentKind::SampledImage => ash::vk::DescriptorType::SAMPLED_IMAGE,
         ArgumentKind::StorageImage => ash::vk::DescriptorType::STORAGE_IMAGE,
+        ArgumentKind::BindlessTable => ash::vk::DescriptorType::SAMPLED_IMAGE,
     }
 }
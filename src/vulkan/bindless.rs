@@ -0,0 +1,193 @@
+use std::{
+    mem::size_of,
+    sync::{Arc, OnceLock},
+};
+
+use ash::vk;
+use parking_lot::Mutex;
+
+use crate::generic::{ArgumentKind, Automatic, ImageUsage, Sampled};
+
+use super::{
+    arguments::ArgumentsField,
+    device::{DeviceOwned, WeakDevice},
+    layout::DescriptorSetLayout,
+    refs::Refs,
+    Image,
+};
+
+struct Inner {
+    owner: WeakDevice,
+    pool: vk::DescriptorPool,
+    set: vk::DescriptorSet,
+    _layout: DescriptorSetLayout,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Set is freed with the pool.
+        self.owner.drop_descriptor_pool(self.pool);
+    }
+}
+
+/// Images removed from the table while command buffers may still use them.
+///
+/// Command buffers reference the generation that was current when the table was bound.
+/// Each generation keeps the next one alive,
+/// so images removed after binding live until the command buffer is completed.
+#[derive(Default)]
+struct Generation {
+    retired: Mutex<Vec<Image>>,
+    next: OnceLock<Arc<Generation>>,
+}
+
+/// Reference to the bindless table kept by command buffers.
+#[derive(Clone)]
+pub(super) struct BindlessTableRef {
+    _inner: Arc<Inner>,
+    _generation: Arc<Generation>,
+}
+
+pub struct BindlessTable {
+    inner: Arc<Inner>,
+    images: Vec<Option<Image>>,
+    generation: Arc<Generation>,
+}
+
+impl Drop for BindlessTable {
+    fn drop(&mut self) {
+        // Pending command buffers may still sample images of the table.
+        if Arc::strong_count(&self.generation) > 1 {
+            let images = self.images.drain(..).flatten();
+            self.generation.retired.lock().extend(images);
+        }
+    }
+}
+
+impl DeviceOwned for BindlessTable {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn owner(&self) -> &WeakDevice {
+        &self.inner.owner
+    }
+}
+
+impl BindlessTable {
+    pub(super) fn new(
+        owner: WeakDevice,
+        pool: vk::DescriptorPool,
+        set: vk::DescriptorSet,
+        layout: DescriptorSetLayout,
+        capacity: u32,
+    ) -> Self {
+        BindlessTable {
+            inner: Arc::new(Inner {
+                owner,
+                pool,
+                set,
+                _layout: layout,
+            }),
+            images: vec![None; capacity as usize],
+            generation: Arc::new(Generation::default()),
+        }
+    }
+
+    /// Keeps removed image alive while command buffers that bound the table are pending.
+    fn retire(&mut self, image: Image) {
+        if Arc::strong_count(&self.generation) == 1 {
+            // No command buffer references images of current generation.
+            return;
+        }
+
+        self.generation.retired.lock().push(image);
+
+        // Command buffers bound from now on don't need retired images.
+        let next = Arc::new(Generation::default());
+        let _ = self.generation.next.set(next.clone());
+        self.generation = next;
+    }
+
+    fn reference(&self) -> BindlessTableRef {
+        BindlessTableRef {
+            _inner: self.inner.clone(),
+            _generation: self.generation.clone(),
+        }
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::BindlessTable for BindlessTable {
+    type Image = Image;
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn capacity(&self) -> u32 {
+        self.images.len() as u32
+    }
+
+    fn set(&mut self, index: u32, image: &Image) {
+        assert!(
+            index < self.capacity(),
+            "Index {index} is out of bounds of bindless table with capacity {}",
+            self.capacity()
+        );
+        debug_assert!(
+            crate::traits::Image::usage(image).contains(ImageUsage::SAMPLED),
+            "Image in bindless table must have SAMPLED usage"
+        );
+
+        if let Some(device) = self.inner.owner.upgrade() {
+            let info = <Image as ArgumentsField<Sampled>>::update(image);
+            let write = vk::WriteDescriptorSet::default()
+                .dst_set(self.inner.set)
+                .dst_binding(0)
+                .dst_array_element(index)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .image_info(std::slice::from_ref(&info));
+
+            unsafe {
+                device.ash().update_descriptor_sets(&[write], &[]);
+            }
+        }
+
+        if let Some(old) = self.images[index as usize].replace(image.clone()) {
+            self.retire(old);
+        }
+    }
+
+    fn clear(&mut self, index: u32) {
+        assert!(
+            index < self.capacity(),
+            "Index {index} is out of bounds of bindless table with capacity {}",
+            self.capacity()
+        );
+
+        // Partially bound descriptor may stay invalid while shader doesn't access it.
+        if let Some(old) = self.images[index as usize].take() {
+            self.retire(old);
+        }
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn get(&self, index: u32) -> Option<&Image> {
+        self.images.get(index as usize)?.as_ref()
+    }
+}
+
+impl ArgumentsField<Automatic> for BindlessTable {
+    const KIND: ArgumentKind = ArgumentKind::BindlessTable;
+    const SIZE: usize = 0;
+    const OFFSET: usize = 0;
+    const STRIDE: usize = size_of::<vk::DescriptorSet>();
+
+    /// Table is bound as persistent descriptor set instead of being pushed.
+    type Update = vk::DescriptorSet;
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn update(&self) -> vk::DescriptorSet {
+        self.inner.set
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn add_refs(&self, refs: &mut Refs) {
+        refs.add_bindless_table(self.reference());
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use ash::vk;
+use parking_lot::Mutex;
+
+use crate::generic::{BufferDesc, BufferUsage, Memory};
+
+use super::{Buffer, Device};
+
+/// Number of breadcrumbs kept in the buffer.
+/// Older breadcrumbs are overwritten.
+const SLOTS: u32 = 64;
+
+/// Size of the slot in bytes.
+/// Each slot contains sequence number and marker.
+const SLOT_SIZE: u64 = 8;
+
+/// Host-visible buffer that command buffers write checkpoint markers into.
+///
+/// Shared by the queue and encoders it creates.
+/// Buffer is allocated on first checkpoint.
+pub(super) struct Breadcrumbs {
+    buffer: Mutex<Option<Buffer>>,
+
+    /// Sequence number of the last recorded checkpoint.
+    /// Zero marks slots that were never written.
+    sequence: AtomicU32,
+}
+
+impl Breadcrumbs {
+    pub fn new() -> Self {
+        Breadcrumbs {
+            buffer: Mutex::new(None),
+            sequence: AtomicU32::new(0),
+        }
+    }
+
+    /// Records command that writes `marker` into the next slot
+    /// after all previously recorded commands complete.
+    ///
+    /// Sequence number is assigned here and not on submission,
+    /// so command buffers must not be submitted more than once.
+    pub fn record(&self, device: &Device, cbuf: vk::CommandBuffer, marker: u32) {
+        let mut buffer = self.buffer.lock();
+
+        if buffer.is_none() {
+            let result = device.new_buffer(BufferDesc {
+                size: SLOTS as u64 * SLOT_SIZE,
+                usage: BufferUsage::TRANSFER_DST,
+                memory: Memory::Download,
+                name: "mev.breadcrumbs",
+                shared_across_queues: false,
+            });
+
+            match result {
+                Ok(new) => *buffer = Some(new),
+                Err(err) => {
+                    tracing::warn!("Failed to allocate breadcrumb buffer: {err}");
+                    return;
+                }
+            }
+        }
+
+        let buffer = buffer.as_ref().unwrap();
+
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let offset = (sequence % SLOTS) as u64 * SLOT_SIZE;
+
+        let data = [sequence, marker];
+
+        unsafe {
+            device.ash().cmd_pipeline_barrier(
+                cbuf,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[],
+            );
+
+            device.ash().cmd_update_buffer(
+                cbuf,
+                buffer.handle(),
+                offset,
+                bytemuck::cast_slice(&data),
+            );
+        }
+    }
+
+    /// Reads markers from the buffer ordered from oldest to most recent.
+    ///
+    /// Commands that write the buffer must be complete or the device lost.
+    pub fn read(&self) -> Vec<u32> {
+        let mut buffer = self.buffer.lock();
+        let Some(buffer) = &mut *buffer else {
+            return Vec::new();
+        };
+
+        let mut slots = [[0u32; 2]; SLOTS as usize];
+        match buffer.mapped_mut(..) {
+            Ok(mapped) => bytemuck::cast_slice_mut(&mut slots).copy_from_slice(&mapped),
+            Err(err) => {
+                tracing::warn!("Failed to read breadcrumb buffer: {err}");
+                return Vec::new();
+            }
+        }
+
+        let mut written = slots
+            .iter()
+            .filter(|[sequence, _]| *sequence != 0)
+            .copied()
+            .collect::<Vec<_>>();
+
+        written.sort_by_key(|[sequence, _]| *sequence);
+        written.into_iter().map(|[_, marker]| marker).collect()
+    }
+}
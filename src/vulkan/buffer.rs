@@ -2,25 +2,44 @@ use core::fmt;
 use std::{
     hash::{Hash, Hasher},
     mem::{size_of, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
     sync::Arc,
 };
 
 use ash::vk;
 use gpu_alloc::MemoryBlock;
 
-use crate::generic::{ArgumentKind, Automatic, BufferUsage, Storage, Uniform};
+use crate::generic::{
+    ArgumentKind, Automatic, BufferIndex, BufferSummary, BufferUsage, MapError, Memory, ResourceId,
+    Storage, Uniform,
+};
 
 use super::{
     arguments::ArgumentsField,
     device::{DeviceOwned, WeakDevice},
+    linear::LinearBlock,
     refs::Refs,
 };
 
+/// Memory bound to the buffer.
+pub(super) enum BufferMemory {
+    /// Block allocated by general purpose allocator.
+    Block(MemoryBlock<(vk::DeviceMemory, usize)>),
+
+    /// Sub-allocation of linear allocator.
+    Linear(LinearBlock),
+}
+
 struct Inner {
     owner: WeakDevice,
-    size: usize,
+    id: ResourceId,
+    name: Box<str>,
+    size: u64,
     usage: BufferUsage,
-    block: ManuallyDrop<MemoryBlock<(vk::DeviceMemory, usize)>>,
+    shared_across_queues: bool,
+    memory_kind: Memory,
+    memory: ManuallyDrop<BufferMemory>,
     idx: usize,
 }
 
@@ -55,8 +74,8 @@ impl fmt::Debug for Buffer {
 
 impl Drop for Inner {
     fn drop(&mut self) {
-        let block = unsafe { ManuallyDrop::take(&mut self.block) };
-        self.owner.drop_buffer(self.idx, block);
+        let memory = unsafe { ManuallyDrop::take(&mut self.memory) };
+        self.owner.drop_buffer(self.idx, memory);
     }
 }
 
@@ -70,19 +89,27 @@ impl DeviceOwned for Buffer {
 impl Buffer {
     pub(super) fn new(
         owner: WeakDevice,
+        id: ResourceId,
+        name: &str,
         handle: vk::Buffer,
-        size: usize,
+        size: u64,
         usage: BufferUsage,
-        block: MemoryBlock<(vk::DeviceMemory, usize)>,
+        shared_across_queues: bool,
+        memory_kind: Memory,
+        memory: BufferMemory,
         idx: usize,
     ) -> Self {
         Buffer {
             handle,
             inner: Arc::new(Inner {
                 owner,
+                id,
+                name: name.into(),
                 size,
                 usage,
-                block: ManuallyDrop::new(block),
+                shared_across_queues,
+                memory_kind,
+                memory: ManuallyDrop::new(memory),
                 idx,
             }),
         }
@@ -92,12 +119,131 @@ impl Buffer {
     pub fn handle(&self) -> vk::Buffer {
         self.handle
     }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn usage(&self) -> BufferUsage {
+        self.inner.usage
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn shared_across_queues(&self) -> bool {
+        self.inner.shared_across_queues
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn memory(&self) -> &BufferMemory {
+        &self.inner.memory
+    }
+
+    /// Returns `true` if buffer can be moved by compaction.
+    ///
+    /// Device memory is sub-allocated linearly only for buffers
+    /// packed by previous compaction, so they may be moved again.
+    pub(super) fn movable(&self) -> bool {
+        self.detached()
+            && self.inner.memory_kind == Memory::Device
+            && self
+                .inner
+                .usage
+                .contains(BufferUsage::TRANSFER_SRC | BufferUsage::TRANSFER_DST)
+    }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    /// Flushes and unmaps range mapped by [`Buffer::mapped_mut`].
+    fn unmap(&mut self, offset: u64, len: usize) {
+        let inner = Arc::get_mut(&mut self.inner).unwrap();
+        let Some(device) = inner.owner.upgrade() else {
+            return;
+        };
+
+        let result = match &mut *inner.memory {
+            BufferMemory::Block(block) => unsafe {
+                let result = block.flush_range(device.inner(), offset, len as u64);
+                block.unmap(device.inner());
+                result
+            },
+            BufferMemory::Linear(block) => unsafe {
+                device.unmap_linear(block, offset, len as u64)
+            },
+        };
+
+        if let Err(err) = result {
+            tracing::error!("Failed to flush buffer '{}' memory: {err}", inner.name);
+        }
+    }
+}
+
+/// Range of buffer memory mapped for writing from host.
+///
+/// Created with [`Buffer::mapped_mut`].
+/// Flushes written memory when dropped.
+pub struct MappedSlice<'a> {
+    buffer: &'a mut Buffer,
+    ptr: NonNull<u8>,
+    offset: u64,
+    len: usize,
+}
+
+impl Deref for MappedSlice<'_> {
+    type Target = [u8];
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for MappedSlice<'_> {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for MappedSlice<'_> {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            self.buffer.unmap(self.offset, self.len);
+        }
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Resource for Buffer {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn id(&self) -> ResourceId {
+        self.inner.id
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{:#?}",
+            BufferSummary {
+                id: self.inner.id,
+                name: Some(&self.inner.name),
+                size: self.inner.size,
+                usage: Some(self.inner.usage),
+            }
+        )
+    }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::Buffer for Buffer {
+    type MappedSlice<'a> = MappedSlice<'a>;
+
     #[cfg_attr(feature = "inline-more", inline(always))]
-    fn size(&self) -> usize {
+    fn size(&self) -> u64 {
         self.inner.size
     }
 
@@ -108,18 +254,73 @@ impl crate::traits::Buffer for Buffer {
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
-    unsafe fn write_unchecked(&mut self, offset: usize, data: &[u8]) {
+    unsafe fn write_unchecked(&mut self, offset: u64, data: &[u8]) {
         let inner = Arc::get_mut(&mut self.inner).unwrap();
         if let Some(device) = inner.owner.upgrade() {
-            unsafe {
-                let ptr = inner
-                    .block
-                    .map(device.inner(), offset as u64, data.len())
-                    .unwrap();
-                std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), data.len());
+            match &mut *inner.memory {
+                BufferMemory::Block(block) => unsafe {
+                    block.write_bytes(device.inner(), offset, data).unwrap();
+                },
+                BufferMemory::Linear(block) => unsafe {
+                    device.write_linear(block, offset, data);
+                },
             }
         }
     }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    unsafe fn read_unchecked(&mut self, offset: u64, data: &mut [u8]) {
+        let inner = Arc::get_mut(&mut self.inner).unwrap();
+        if let Some(device) = inner.owner.upgrade() {
+            match &mut *inner.memory {
+                BufferMemory::Block(block) => unsafe {
+                    block.read_bytes(device.inner(), offset, data).unwrap();
+                },
+                BufferMemory::Linear(block) => unsafe {
+                    device.read_linear(block, offset, data);
+                },
+            }
+        }
+    }
+
+    fn mapped_mut(&mut self, range: impl BufferIndex) -> Result<MappedSlice<'_>, MapError> {
+        if self.inner.memory_kind == Memory::Device {
+            return Err(MapError::NotHostVisible);
+        }
+
+        let range = range.range(self.inner.size);
+        let offset = range.start;
+        let len = usize::try_from(range.end - range.start).map_err(|_| MapError::OutOfMemory)?;
+
+        let inner = Arc::get_mut(&mut self.inner).ok_or(MapError::Shared)?;
+
+        let ptr = if len == 0 {
+            NonNull::dangling()
+        } else {
+            let Some(device) = inner.owner.upgrade() else {
+                // Memory is freed with the device.
+                return Err(MapError::NotHostVisible);
+            };
+
+            let result = match &mut *inner.memory {
+                BufferMemory::Block(block) => unsafe { block.map(device.inner(), offset, len) },
+                BufferMemory::Linear(block) => unsafe { device.map_linear(block, offset) },
+            };
+
+            result.map_err(|err| match err {
+                gpu_alloc::MapError::NonHostVisible => MapError::NotHostVisible,
+                gpu_alloc::MapError::AlreadyMapped => MapError::Shared,
+                _ => MapError::OutOfMemory,
+            })?
+        };
+
+        Ok(MappedSlice {
+            buffer: self,
+            ptr,
+            offset,
+            len,
+        })
+    }
 }
 
 impl ArgumentsField<Automatic> for Buffer {
@@ -154,7 +355,7 @@ impl ArgumentsField<Uniform> for Buffer {
         vk::DescriptorBufferInfo {
             buffer: self.handle,
             offset: 0,
-            range: self.inner.size as u64,
+            range: self.inner.size,
         }
     }
 
@@ -177,7 +378,7 @@ impl ArgumentsField<Storage> for Buffer {
         vk::DescriptorBufferInfo {
             buffer: self.handle,
             offset: 0,
-            range: self.inner.size as u64,
+            range: self.inner.size,
         }
     }
 
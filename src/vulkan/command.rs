@@ -1,25 +1,49 @@
-use std::ops::Range;
+use std::{
+    ops::{Deref, DerefMut, Range},
+    sync::Arc,
+};
 
 use ash::vk;
 use smallvec::SmallVec;
 
 use crate::generic::{
-    Arguments, AsBufferSlice, BlasBuildDesc, ClearColor, ClearDepthStencil, DeviceRepr, Extent2,
-    Extent3, LoadOp, Offset2, Offset3, OutOfMemory, PipelineStages, RenderPassDesc, StoreOp,
-    TlasBuildDesc,
+    align, check_image_range, check_self_copy, Arguments, AsBufferSlice, BlasBuildDesc, ClearColor,
+    ClearDepthStencil, DeviceRepr, Extent2, Extent3, Features, LoadOp, Offset2, Offset3,
+    OutOfMemory, PipelineStages, PixelFormat, QueueFlags, Rect2, RenderPassDesc, RenderPassFlags,
+    ResourceId, StoreOp, TlasBuildDesc,
 };
 
 use super::{
-    access::access_for_stages, format_aspect, from::IntoAsh, handle_host_oom,
-    layout::PipelineLayout, refs::Refs, unexpected_error, Blas, Buffer, ComputePipeline, Device,
-    Frame, Image, RenderPipeline, Tlas,
+    access::access_for_stages, breadcrumbs::Breadcrumbs, descriptors::DescriptorPools,
+    format_aspect, from::IntoAsh, handle_host_oom, hazards::HazardTracker, layout::PipelineLayout,
+    refs::Refs, unexpected_error, Blas, Buffer, ComputePipeline, Device, Frame, Image,
+    RenderPipeline, Tlas,
 };
+#[cfg(any(debug_assertions, feature = "debug"))]
+use super::{hazards::AliasTracker, refs::RefsReport};
 
 pub struct CommandBuffer {
     pub(super) handle: vk::CommandBuffer,
     pub(super) pool: vk::CommandPool,
     pub(super) present: SmallVec<[Frame; 2]>,
     pub(super) refs: Refs,
+    pub(super) descriptors: DescriptorPools,
+
+    /// First render pass resumes pass suspended by previous command buffer.
+    pub(super) resumes_pass: bool,
+
+    /// Last render pass is suspended to be resumed by next command buffer.
+    pub(super) suspends_pass: bool,
+}
+
+impl CommandBuffer {
+    /// Returns report of resources referenced by this command buffer.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn referenced_resources(&self) -> RefsReport {
+        let mut report = RefsReport::default();
+        self.refs.report_into(&mut report);
+        report
+    }
 }
 
 pub struct CommandEncoder {
@@ -28,14 +52,49 @@ pub struct CommandEncoder {
     pool: vk::CommandPool,
     present: SmallVec<[Frame; 2]>,
     refs: Refs,
+
+    /// Descriptor pools for devices without push descriptors.
+    descriptors: DescriptorPools,
+    reusable: bool,
+
+    /// At least one render pass was recorded.
+    began_pass: bool,
+
+    /// See [`CommandBuffer::resumes_pass`].
+    resumes_pass: bool,
+
+    /// See [`CommandBuffer::suspends_pass`].
+    suspends_pass: bool,
+
+    /// Flags of the queue family the encoder records commands for.
+    flags: QueueFlags,
+
+    /// Breadcrumb buffer of the queue.
+    breadcrumbs: Arc<Breadcrumbs>,
+
+    /// Buffer of the queue with every byte value at its own offset.
+    /// Unaligned tails of buffer writes are copied from it.
+    byte_table: Buffer,
 }
 
+/// Stages that are not supported by queues without graphics capability.
+const GRAPHICS_STAGES: PipelineStages = PipelineStages::VERTEX_INPUT
+    .union(PipelineStages::VERTEX_SHADER)
+    .union(PipelineStages::EARLY_FRAGMENT_TEST)
+    .union(PipelineStages::FRAGMENT_SHADER)
+    .union(PipelineStages::LATE_FRAGMENT_TEST)
+    .union(PipelineStages::COLOR_OUTPUT);
+
 impl CommandEncoder {
     pub(super) fn new(
         device: Device,
         handle: vk::CommandBuffer,
         pool: vk::CommandPool,
         refs: Refs,
+        descriptors: DescriptorPools,
+        flags: QueueFlags,
+        breadcrumbs: Arc<Breadcrumbs>,
+        byte_table: Buffer,
     ) -> Self {
         CommandEncoder {
             device,
@@ -43,28 +102,283 @@ impl CommandEncoder {
             pool,
             present: SmallVec::new(),
             refs,
+            descriptors,
+            reusable: false,
+            began_pass: false,
+            resumes_pass: false,
+            suspends_pass: false,
+            flags,
+            breadcrumbs,
+            byte_table,
+        }
+    }
+}
+
+/// Owns command pool and the command buffer allocated from it.
+/// Both are destroyed when last reference is dropped.
+pub(super) struct ReusableCommandBufferInner {
+    device: Device,
+    handle: vk::CommandBuffer,
+    pool: vk::CommandPool,
+    refs: Refs,
+
+    /// Descriptor sets are used by every submission,
+    /// so pools live as long as the command buffer.
+    descriptors: DescriptorPools,
+}
+
+impl ReusableCommandBufferInner {
+    pub(super) fn refs(&self) -> &Refs {
+        &self.refs
+    }
+}
+
+impl Drop for ReusableCommandBufferInner {
+    fn drop(&mut self) {
+        // Queue keeps a reference until the submission is complete.
+        // Destroying the pool frees the command buffer.
+        unsafe {
+            self.device.ash().destroy_command_pool(self.pool, None);
+            self.descriptors.destroy(self.device.ash());
+        }
+    }
+}
+
+/// Command buffer that can be submitted multiple times.
+#[derive(Clone)]
+pub struct ReusableCommandBuffer {
+    inner: Arc<ReusableCommandBufferInner>,
+}
+
+impl ReusableCommandBuffer {
+    /// Returns report of resources referenced by this command buffer.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn referenced_resources(&self) -> RefsReport {
+        let mut report = RefsReport::default();
+        self.inner.refs.report_into(&mut report);
+        report
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn handle(&self) -> vk::CommandBuffer {
+        self.inner.handle
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn inner(&self) -> &Arc<ReusableCommandBufferInner> {
+        &self.inner
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::ReusableCommandBuffer for ReusableCommandBuffer {
+    fn references_swapchain(&self) -> bool {
+        self.inner.refs.images().iter().any(Image::is_swapchain)
+    }
+}
+
+/// Encoder that records reusable command buffer.
+///
+/// Dereferences to regular [`CommandEncoder`] to record commands.
+pub struct ReusableCommandEncoder {
+    encoder: CommandEncoder,
+}
+
+impl ReusableCommandEncoder {
+    /// Creates encoder for the command buffer allocated from the dedicated pool.
+    /// Command buffer must be in recording state.
+    pub(super) fn new(
+        device: Device,
+        handle: vk::CommandBuffer,
+        pool: vk::CommandPool,
+        flags: QueueFlags,
+        breadcrumbs: Arc<Breadcrumbs>,
+        byte_table: Buffer,
+    ) -> Self {
+        let mut encoder = CommandEncoder::new(
+            device,
+            handle,
+            pool,
+            Refs::new(),
+            DescriptorPools::default(),
+            flags,
+            breadcrumbs,
+            byte_table,
+        );
+        encoder.reusable = true;
+        ReusableCommandEncoder { encoder }
+    }
+}
+
+impl Deref for ReusableCommandEncoder {
+    type Target = CommandEncoder;
+
+    #[inline(always)]
+    fn deref(&self) -> &CommandEncoder {
+        &self.encoder
+    }
+}
+
+impl DerefMut for ReusableCommandEncoder {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut CommandEncoder {
+        &mut self.encoder
+    }
+}
+
+impl Drop for ReusableCommandEncoder {
+    fn drop(&mut self) {
+        if self.encoder.pool != vk::CommandPool::null() {
+            // Encoder was dropped without finishing.
+            unsafe {
+                let device = self.encoder.device.ash();
+                device.destroy_command_pool(self.encoder.pool, None);
+                self.encoder.descriptors.destroy(device);
+            }
         }
     }
 }
 
+#[hidden_trait::expose]
+impl crate::traits::ReusableCommandEncoder for ReusableCommandEncoder {
+    type CommandEncoder = CommandEncoder;
+    type ReusableCommandBuffer = ReusableCommandBuffer;
+
+    fn finish(mut self) -> Result<ReusableCommandBuffer, OutOfMemory> {
+        let encoder = &mut self.encoder;
+        debug_assert!(encoder.present.is_empty());
+        assert!(
+            !encoder.resumes_pass && !encoder.suspends_pass,
+            "Reusable command buffer can't contain part of split render pass"
+        );
+
+        let result = unsafe { encoder.device.ash().end_command_buffer(encoder.handle) };
+        result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
+            _ => unexpected_error(err),
+        })?;
+
+        // Pool ownership moves to the command buffer.
+        let pool = std::mem::replace(&mut encoder.pool, vk::CommandPool::null());
+
+        Ok(ReusableCommandBuffer {
+            inner: Arc::new(ReusableCommandBufferInner {
+                device: encoder.device.clone(),
+                handle: encoder.handle,
+                pool,
+                refs: std::mem::replace(&mut encoder.refs, Refs::new()),
+                descriptors: std::mem::take(&mut encoder.descriptors),
+            }),
+        })
+    }
+}
+
 #[hidden_trait::expose]
 impl crate::traits::SyncCommandEncoder for CommandEncoder {
+    type Image = Image;
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn barrier(&mut self, after: PipelineStages, before: PipelineStages) {
         barrier(&self.device, self.handle, after, before);
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.refs.barrier();
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn init_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Image) {
-        image_barrier(&self.device, self.handle, after, before, image);
+        self.init_image_range(after, before, image, 0..image.levels(), 0..image.layers());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Image) {
+        self.sync_image_range(after, before, image, 0..image.levels(), 0..image.layers());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn init_image_range(
+        &mut self,
+        after: PipelineStages,
+        before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        mark_initialized(image, &levels, &layers);
+
+        image_barrier(
+            &self.device,
+            self.handle,
+            after,
+            before,
+            image,
+            levels,
+            layers,
+            true,
+        );
+        self.refs.add_image(image.clone());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image_range(
+        &mut self,
+        after: PipelineStages,
+        before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        image_barrier(
+            &self.device,
+            self.handle,
+            after,
+            before,
+            image,
+            levels,
+            layers,
+            false,
+        );
         self.refs.add_image(image.clone());
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::CommandEncoder for CommandEncoder {
+    type Frame = Frame;
+    type CommandBuffer = CommandBuffer;
+    type CopyCommandEncoder<'a> = CopyCommandEncoder<'a>;
+    type AccelerationStructureCommandEncoder<'a> = AccelerationStructureCommandEncoder<'a>;
+    type ComputeCommandEncoder<'a> = ComputeCommandEncoder<'a>;
+    type RenderCommandEncoder<'a> = RenderCommandEncoder<'a>;
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn present(&mut self, frame: Frame, after: PipelineStages) {
+        assert!(
+            !self.reusable,
+            "Frames can't be presented from reusable command buffers"
+        );
+
+        // Fake frames are not shown, dropping them is enough.
+        if !frame.is_real() {
+            return;
+        }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            frame.image().check_not_presented("present");
+            frame.image().set_presented(true);
+        }
+
+        // Frames may be presented from compute queues
+        // that don't support graphics stages.
+        let after = if self.flags.contains(QueueFlags::GRAPHICS) {
+            after
+        } else {
+            after.difference(GRAPHICS_STAGES)
+        };
+
         unsafe {
             self.device.ash().cmd_pipeline_barrier(
                 self.handle,
@@ -89,10 +403,40 @@ impl crate::traits::CommandEncoder for CommandEncoder {
             )
         }
 
-        self.refs.add_image(frame.image().clone());
+        // Frame keeps the image alive until the command buffer is submitted.
+        // Swapchain owns the image after that.
         self.present.push(frame);
     }
 
+    fn present_with_damage(&mut self, frame: Frame, after: PipelineStages, damage: &[Rect2]) {
+        let mut frame = frame;
+
+        let features = self.device.features();
+        if features.contains(Features::INCREMENTAL_PRESENT) {
+            let extent = frame.image().extent().expect_2d();
+            frame.damage.clear();
+            frame
+                .damage
+                .extend(damage.iter().filter_map(|rect| clamp_damage(rect, extent)));
+        }
+
+        self.present(frame, after);
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn capabilities(&self) -> QueueFlags {
+        self.flags
+    }
+
+    fn checkpoint(&mut self, marker: u32) {
+        // Sequence number is baked into commands at record time,
+        // so markers from resubmitted buffers would be ordered incorrectly.
+        if self.reusable {
+            return;
+        }
+        self.breadcrumbs.record(&self.device, self.handle, marker);
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn finish(self) -> Result<CommandBuffer, OutOfMemory> {
         let result = unsafe { self.device.ash().end_command_buffer(self.handle) };
@@ -107,6 +451,9 @@ impl crate::traits::CommandEncoder for CommandEncoder {
             pool: self.pool,
             present: self.present,
             refs: self.refs,
+            descriptors: self.descriptors,
+            resumes_pass: self.resumes_pass,
+            suspends_pass: self.suspends_pass,
         })
     }
 
@@ -114,15 +461,31 @@ impl crate::traits::CommandEncoder for CommandEncoder {
     fn copy(&mut self) -> CopyCommandEncoder<'_> {
         CopyCommandEncoder {
             device: self.device.clone(),
+            #[cfg(feature = "trace-gpu")]
+            span: tracing::info_span!(target: "mev", "mev.encoder", kind = "copy"),
             handle: self.handle,
             refs: &mut self.refs,
+            byte_table: &self.byte_table,
+            last_self_copy: None,
         }
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn acceleration_structure(&mut self) -> AccelerationStructureCommandEncoder<'_> {
+        debug_assert!(
+            self.flags.contains(QueueFlags::COMPUTE),
+            "Acceleration structure commands require queue with compute capability, encoder's queue has {:?}",
+            self.flags
+        );
+
         AccelerationStructureCommandEncoder {
             device: self.device.clone(),
+            #[cfg(feature = "trace-gpu")]
+            span: tracing::info_span!(
+                target: "mev",
+                "mev.encoder",
+                kind = "acceleration_structure"
+            ),
             handle: self.handle,
             refs: &mut self.refs,
         }
@@ -130,15 +493,42 @@ impl crate::traits::CommandEncoder for CommandEncoder {
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn compute(&mut self) -> ComputeCommandEncoder<'_> {
+        debug_assert!(
+            self.flags.contains(QueueFlags::COMPUTE),
+            "Compute commands require queue with compute capability, encoder's queue has {:?}",
+            self.flags
+        );
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.refs.begin_pass();
+
         ComputeCommandEncoder {
             device: self.device.clone(),
+            #[cfg(feature = "trace-gpu")]
+            span: tracing::info_span!(target: "mev", "mev.encoder", kind = "compute"),
             handle: self.handle,
             refs: &mut self.refs,
+            descriptors: &mut self.descriptors,
             current_layout: None,
+            hazards: None,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            aliases: AliasTracker::default(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            bound_groups: 0,
+            flags: self.flags,
         }
     }
 
     fn render(&mut self, desc: RenderPassDesc) -> RenderCommandEncoder<'_> {
+        debug_assert!(
+            self.flags.contains(QueueFlags::GRAPHICS),
+            "Render commands require queue with graphics capability, encoder's queue has {:?}",
+            self.flags
+        );
+        if let Err(err) = desc.validate() {
+            panic!("Invalid render pass \"{}\": {err}", desc.name);
+        }
+
         let mut extent = vk::Extent2D {
             width: u32::MAX,
             height: u32::MAX,
@@ -147,7 +537,6 @@ impl crate::traits::CommandEncoder for CommandEncoder {
         let mut color_attachments = Vec::with_capacity(desc.color_attachments.len());
         for color in desc.color_attachments.iter() {
             let format = color.image.format();
-            debug_assert!(format.is_color());
 
             let color_extent: ash::vk::Extent2D = color.image.extent().expect_2d().into_ash();
             extent.width = extent.width.min(color_extent.width);
@@ -155,8 +544,12 @@ impl crate::traits::CommandEncoder for CommandEncoder {
 
             let mut attachment = vk::RenderingAttachmentInfo::default();
 
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            color.image.check_not_presented("render pass");
+
             self.refs.add_image(color.image.clone());
 
+            // Views are cached in image data, equal views yield the same handle across passes.
             attachment.image_view = color.image.view_handle();
             attachment.image_layout = vk::ImageLayout::GENERAL;
             attachment.load_op = match color.load {
@@ -178,14 +571,34 @@ impl crate::traits::CommandEncoder for CommandEncoder {
             color_attachments.push(attachment);
         }
 
-        let mut info = vk::RenderingInfo::default().color_attachments(&color_attachments);
+        let mut flags = vk::RenderingFlags::empty();
+        if desc.flags.contains(RenderPassFlags::SUSPENDING) {
+            flags |= vk::RenderingFlags::SUSPENDING;
+        }
+        if desc.flags.contains(RenderPassFlags::RESUMING) {
+            flags |= vk::RenderingFlags::RESUMING;
+
+            // Pass suspended in this command buffer is resumed in place.
+            if !self.suspends_pass {
+                assert!(
+                    !self.began_pass,
+                    "Render pass suspended by previous command buffer must be resumed by the first pass"
+                );
+                self.resumes_pass = true;
+            }
+        }
+        self.began_pass = true;
+        self.suspends_pass = desc.flags.contains(RenderPassFlags::SUSPENDING);
+
+        let mut info = vk::RenderingInfo::default()
+            .flags(flags)
+            .color_attachments(&color_attachments);
 
         let depth_attachment;
         let stencil_attachment;
 
         if let Some(depth) = desc.depth_stencil_attachment {
             let format = depth.image.format();
-            debug_assert!(format.is_depth() || format.is_stencil());
 
             let depth_extent: ash::vk::Extent2D = depth.image.extent().expect_2d().into_ash();
             extent.width = extent.width.min(depth_extent.width);
@@ -240,6 +653,12 @@ impl crate::traits::CommandEncoder for CommandEncoder {
             }
         }
 
+        let viewport = vk::Viewport::default()
+            .width(extent.width as f32)
+            .height(extent.height as f32)
+            .min_depth(0.0)
+            .max_depth(1.0);
+
         unsafe {
             self.device.ash().cmd_begin_rendering(
                 self.handle,
@@ -248,24 +667,79 @@ impl crate::traits::CommandEncoder for CommandEncoder {
                         offset: vk::Offset2D { x: 0, y: 0 },
                         extent,
                     })
-                    .layer_count(1),
-            )
+                    .layer_count(desc.layers()),
+            );
+
+            // Dynamic state is undefined until set.
+            // Start with viewport and scissor covering render area, same as Metal does.
+            self.device
+                .ash()
+                .cmd_set_viewport(self.handle, 0, &[viewport]);
+            self.device.ash().cmd_set_scissor(
+                self.handle,
+                0,
+                &[vk::Rect2D {
+                    offset: vk::Offset2D { x: 0, y: 0 },
+                    extent,
+                }],
+            );
         }
 
+        self.refs.with_capacity_hint(desc.expected_draws as usize);
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.refs.begin_pass();
+
         RenderCommandEncoder {
             device: self.device.clone(),
+            #[cfg(feature = "trace-gpu")]
+            span: tracing::info_span!(
+                target: "mev",
+                "mev.encoder",
+                kind = "render",
+                name = desc.name,
+                color_attachments = desc.color_attachments.len(),
+            ),
             handle: self.handle,
             current_layout: None,
             refs: &mut self.refs,
+            descriptors: &mut self.descriptors,
+            viewport,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            aliases: AliasTracker::default(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            bound_groups: 0,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            color_formats: desc
+                .color_attachments
+                .iter()
+                .map(|color| color.image.format())
+                .collect(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            depth_format: desc
+                .depth_stencil_attachment
+                .map(|depth| depth.image.format()),
+            flags: self.flags,
         }
     }
 }
 
 pub struct ComputeCommandEncoder<'a> {
     device: Device,
+    #[cfg(feature = "trace-gpu")]
+    span: tracing::Span,
     handle: vk::CommandBuffer,
     refs: &'a mut Refs,
+    descriptors: &'a mut DescriptorPools,
     current_layout: Option<PipelineLayout>,
+    hazards: Option<HazardTracker>,
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    aliases: AliasTracker,
+
+    /// Mask of argument groups bound and not disturbed by pipeline changes.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    bound_groups: u64,
+    flags: QueueFlags,
 }
 
 impl ComputeCommandEncoder<'_> {
@@ -284,28 +758,136 @@ impl ComputeCommandEncoder<'_> {
         self.current_layout.as_ref()
     }
 
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn refs(&self) -> &Refs {
+        &self.refs
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     pub(super) fn refs_mut(&mut self) -> &mut Refs {
         &mut self.refs
     }
+
+    /// Returns parts of the encoder required to bind arguments.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn binding_parts(
+        &mut self,
+    ) -> (&Device, Option<&PipelineLayout>, &mut DescriptorPools) {
+        (
+            &self.device,
+            self.current_layout.as_ref(),
+            &mut *self.descriptors,
+        )
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn hazards_mut(&mut self) -> Option<&mut HazardTracker> {
+        self.hazards.as_mut()
+    }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn aliases_mut(&mut self) -> &mut AliasTracker {
+        &mut self.aliases
+    }
+}
+
+#[cfg(any(debug_assertions, feature = "debug"))]
+impl Drop for ComputeCommandEncoder<'_> {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn drop(&mut self) {
+        self.refs.end_pass();
+    }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::SyncCommandEncoder for ComputeCommandEncoder<'_> {
+    type Image = Image;
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn barrier(&mut self, after: PipelineStages, before: PipelineStages) {
         barrier(&self.device, self.handle, after, before);
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.refs.barrier();
+
+        if after.contains(PipelineStages::COMPUTE_SHADER)
+            && before.contains(PipelineStages::COMPUTE_SHADER)
+        {
+            if let Some(hazards) = &mut self.hazards {
+                hazards.reset();
+            }
+        }
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn init_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Image) {
-        image_barrier(&self.device, self.handle, after, before, image);
+        self.init_image_range(after, before, image, 0..image.levels(), 0..image.layers());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Image) {
+        self.sync_image_range(after, before, image, 0..image.levels(), 0..image.layers());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn init_image_range(
+        &mut self,
+        after: PipelineStages,
+        before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        mark_initialized(image, &levels, &layers);
+
+        image_barrier(
+            &self.device,
+            self.handle,
+            after,
+            before,
+            image,
+            levels,
+            layers,
+            true,
+        );
+        self.refs.add_image(image.clone());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image_range(
+        &mut self,
+        after: PipelineStages,
+        before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        image_barrier(
+            &self.device,
+            self.handle,
+            after,
+            before,
+            image,
+            levels,
+            layers,
+            false,
+        );
         self.refs.add_image(image.clone());
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::ComputeCommandEncoder for ComputeCommandEncoder<'_> {
+    type ComputePipeline = ComputePipeline;
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn capabilities(&self) -> QueueFlags {
+        self.flags
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn with_pipeline(&mut self, pipeline: &ComputePipeline) {
         unsafe {
@@ -315,13 +897,28 @@ impl crate::traits::ComputeCommandEncoder for ComputeCommandEncoder<'_> {
                 pipeline.handle(),
             );
         }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        switch_layout(
+            &mut self.bound_groups,
+            self.current_layout.as_ref(),
+            pipeline.layout(),
+        );
         self.current_layout = Some(pipeline.layout().clone());
         self.refs.add_compute_pipeline(pipeline.clone());
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.aliases.reset();
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn with_arguments(&mut self, group: u32, arguments: &impl Arguments) {
         arguments.bind_compute(group, self);
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            self.bound_groups |= 1 << group;
+        }
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
@@ -336,15 +933,45 @@ impl crate::traits::ComputeCommandEncoder for ComputeCommandEncoder<'_> {
             self.device.ash().cmd_push_constants(
                 self.handle,
                 layout.handle(),
-                ash::vk::ShaderStageFlags::ALL,
+                layout.constants_stages(),
                 0,
                 bytemuck::bytes_of(&data),
             )
         }
     }
 
+    fn set_auto_barrier(&mut self, enabled: bool) {
+        match (enabled, &self.hazards) {
+            (true, None) => self.hazards = Some(HazardTracker::default()),
+            (false, Some(_)) => self.hazards = None,
+            _ => {}
+        }
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn dispatch(&mut self, groups: Extent3) {
+        if groups.width() == 0 || groups.height() == 0 || groups.depth() == 0 {
+            // Dispatching no work groups is a no-op
+            return;
+        }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        check_bound_groups(self.bound_groups, self.current_layout.as_ref(), "dispatch");
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.aliases.check();
+
+        if let Some(hazards) = &mut self.hazards {
+            if hazards.dispatch() {
+                barrier(
+                    &self.device,
+                    self.handle,
+                    PipelineStages::COMPUTE_SHADER,
+                    PipelineStages::COMPUTE_SHADER,
+                );
+            }
+        }
+
         unsafe {
             self.device.ash().cmd_dispatch(
                 self.handle,
@@ -358,9 +985,30 @@ impl crate::traits::ComputeCommandEncoder for ComputeCommandEncoder<'_> {
 
 pub struct RenderCommandEncoder<'a> {
     device: Device,
+    #[cfg(feature = "trace-gpu")]
+    span: tracing::Span,
     handle: vk::CommandBuffer,
     refs: &'a mut Refs,
+    descriptors: &'a mut DescriptorPools,
     current_layout: Option<PipelineLayout>,
+
+    /// Current viewport, depth range is changed without changing rectangle.
+    viewport: vk::Viewport,
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    aliases: AliasTracker,
+
+    /// Mask of argument groups bound and not disturbed by pipeline changes.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    bound_groups: u64,
+
+    /// Formats of color attachments of the render pass.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    color_formats: SmallVec<[PixelFormat; 4]>,
+
+    /// Format of depth-stencil attachment of the render pass.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    depth_format: Option<PixelFormat>,
+    flags: QueueFlags,
 }
 
 impl RenderCommandEncoder<'_> {
@@ -379,23 +1027,59 @@ impl RenderCommandEncoder<'_> {
         self.current_layout.as_ref()
     }
 
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn refs(&self) -> &Refs {
+        &self.refs
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     pub(super) fn refs_mut(&mut self) -> &mut Refs {
         &mut self.refs
     }
+
+    /// Returns parts of the encoder required to bind arguments.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn binding_parts(
+        &mut self,
+    ) -> (&Device, Option<&PipelineLayout>, &mut DescriptorPools) {
+        (
+            &self.device,
+            self.current_layout.as_ref(),
+            &mut *self.descriptors,
+        )
+    }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn aliases_mut(&mut self) -> &mut AliasTracker {
+        &mut self.aliases
+    }
 }
 
 impl Drop for RenderCommandEncoder<'_> {
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn drop(&mut self) {
         unsafe { self.device.ash().cmd_end_rendering(self.handle) }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.refs.end_pass();
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
+    type RenderPipeline = RenderPipeline;
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn capabilities(&self) -> QueueFlags {
+        self.flags
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn with_pipeline(&mut self, pipeline: &RenderPipeline) {
+        // All render pipelines declare viewport and scissor as dynamic state,
+        // so values set before are kept by Vulkan.
         unsafe {
             self.device.ash().cmd_bind_pipeline(
                 self.handle,
@@ -403,24 +1087,58 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
                 pipeline.handle(),
             );
         }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        switch_layout(
+            &mut self.bound_groups,
+            self.current_layout.as_ref(),
+            pipeline.layout(),
+        );
         self.current_layout = Some(pipeline.layout().clone());
         self.refs.add_render_pipeline(pipeline.clone());
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            crate::generic::check_pipeline_formats(
+                pipeline.color_formats(),
+                pipeline.depth_format(),
+                &self.color_formats,
+                self.depth_format,
+            );
+            self.device
+                .check_srgb_mismatch(pipeline.color_formats(), &self.color_formats);
+        }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.aliases.reset();
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn with_viewport(&mut self, offset: Offset3<f32>, extent: Extent3<f32>) {
+        self.viewport = ash::vk::Viewport::default()
+            .x(offset.x())
+            .y(offset.y())
+            .width(extent.width())
+            .height(extent.height());
+        self.with_depth_range(offset.z(), offset.z() + extent.depth());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn with_depth_range(&mut self, near: f32, far: f32) {
+        // Values outside of `0..=1` require VK_EXT_depth_range_unrestricted.
+        // Reversed range is allowed.
+        debug_assert!(
+            (0.0..=1.0).contains(&near) && (0.0..=1.0).contains(&far),
+            "Depth range {near}..{far} is outside of 0..=1"
+        );
+
+        self.viewport.min_depth = near;
+        self.viewport.max_depth = far;
+
         unsafe {
-            self.device.ash().cmd_set_viewport(
-                self.handle,
-                0,
-                &[ash::vk::Viewport::default()
-                    .x(offset.x())
-                    .y(offset.y())
-                    .width(extent.width())
-                    .height(extent.height())
-                    .min_depth(offset.z())
-                    .max_depth(extent.depth())],
-            );
+            self.device
+                .ash()
+                .cmd_set_viewport(self.handle, 0, &[self.viewport]);
         }
     }
 
@@ -446,6 +1164,11 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn with_arguments(&mut self, group: u32, arguments: &impl Arguments) {
         arguments.bind_render(group, self);
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            self.bound_groups |= 1 << group;
+        }
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
@@ -460,7 +1183,7 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
             self.device.ash().cmd_push_constants(
                 self.handle,
                 layout.handle(),
-                ash::vk::ShaderStageFlags::ALL,
+                layout.constants_stages(),
                 0,
                 bytemuck::bytes_of(&data),
             )
@@ -469,12 +1192,16 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn bind_vertex_buffers(&mut self, start: u32, slices: &[impl AsBufferSlice]) {
+        if slices.is_empty() {
+            return;
+        }
+
         let mut handles = smallvec::SmallVec::<[_; 8]>::with_capacity(slices.len());
         let mut offsets = smallvec::SmallVec::<[_; 8]>::with_capacity(slices.len());
         for slice in slices.iter() {
             let slice: crate::generic::BufferSlice = slice.as_buffer_slice();
             handles.push(slice.buffer.handle());
-            offsets.push(slice.offset as u64);
+            offsets.push(slice.offset);
             self.refs.add_buffer(slice.buffer.clone());
         }
 
@@ -492,7 +1219,7 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
             self.device.ash().cmd_bind_index_buffer(
                 self.handle,
                 slice.buffer.handle(),
-                slice.offset as u64,
+                slice.offset,
                 vk::IndexType::UINT32,
             )
         }
@@ -501,6 +1228,21 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        if vertices.end <= vertices.start {
+            // Rendering no vertices is a no-op
+            return;
+        }
+        if instances.end <= instances.start {
+            // Rendering no instances is a no-op
+            return;
+        }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        check_bound_groups(self.bound_groups, self.current_layout.as_ref(), "draw");
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.aliases.check();
+
         unsafe {
             self.device.ash().cmd_draw(
                 self.handle,
@@ -514,6 +1256,25 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn draw_indexed(&mut self, vertex_offset: i32, indices: Range<u32>, instances: Range<u32>) {
+        if indices.end <= indices.start {
+            // Rendering no indices is a no-op
+            return;
+        }
+        if instances.end <= instances.start {
+            // Rendering no instances is a no-op
+            return;
+        }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        check_bound_groups(
+            self.bound_groups,
+            self.current_layout.as_ref(),
+            "draw_indexed",
+        );
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.aliases.check();
+
         unsafe {
             self.device.ash().cmd_draw_indexed(
                 self.handle,
@@ -529,44 +1290,216 @@ impl crate::traits::RenderCommandEncoder for RenderCommandEncoder<'_> {
 
 pub struct CopyCommandEncoder<'a> {
     device: Device,
+    #[cfg(feature = "trace-gpu")]
+    span: tracing::Span,
     handle: vk::CommandBuffer,
     refs: &'a mut Refs,
+
+    /// See [`CommandEncoder::byte_table`].
+    byte_table: &'a Buffer,
+
+    /// Image of the last copy within single image.
+    /// Next copy within the same image waits for it.
+    last_self_copy: Option<ResourceId>,
 }
 
 #[hidden_trait::expose]
 impl crate::traits::SyncCommandEncoder for CopyCommandEncoder<'_> {
+    type Image = Image;
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn barrier(&mut self, after: PipelineStages, before: PipelineStages) {
         barrier(&self.device, self.handle, after, before);
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.refs.barrier();
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn init_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Image) {
-        image_barrier(&self.device, self.handle, after, before, image);
+        self.init_image_range(after, before, image, 0..image.levels(), 0..image.layers());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image(&mut self, after: PipelineStages, before: PipelineStages, image: &Image) {
+        self.sync_image_range(after, before, image, 0..image.levels(), 0..image.layers());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn init_image_range(
+        &mut self,
+        after: PipelineStages,
+        before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        mark_initialized(image, &levels, &layers);
+
+        image_barrier(
+            &self.device,
+            self.handle,
+            after,
+            before,
+            image,
+            levels,
+            layers,
+            true,
+        );
+        self.refs.add_image(image.clone());
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn sync_image_range(
+        &mut self,
+        after: PipelineStages,
+        before: PipelineStages,
+        image: &Image,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        image_barrier(
+            &self.device,
+            self.handle,
+            after,
+            before,
+            image,
+            levels,
+            layers,
+            false,
+        );
         self.refs.add_image(image.clone());
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
+    type Buffer = Buffer;
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn copy_image_to_buffer(
+        &mut self,
+        src: &Image,
+        offset: Offset3<u32>,
+        extent: Extent3<u32>,
+        layers: Range<u32>,
+        level: u32,
+        dst: &Buffer,
+        start: u64,
+        bytes_per_line: u64,
+        bytes_per_plane: u64,
+    ) {
+        debug_assert!(
+            extent.width() > 0 && extent.height() > 0 && extent.depth() > 0,
+            "copy_image_to_buffer: extent {extent:?} is empty"
+        );
+        let texel_size = src.format().size() as u64;
+        debug_assert!(align::is_aligned_u64(bytes_per_line, texel_size));
+        debug_assert!(
+            bytes_per_line == 0 || bytes_per_line >= u64::from(extent.width()) * texel_size,
+            "bytes_per_line is too small for the copied region"
+        );
+
+        // Zero pitch means tightly packed data.
+        let texel_per_line = bytes_per_line / texel_size;
+        let lines_per_plane = match bytes_per_line {
+            0 => 0,
+            _ => {
+                debug_assert!(align::is_aligned_u64(bytes_per_plane, bytes_per_line));
+                bytes_per_plane / bytes_per_line
+            }
+        };
+        debug_assert!(
+            lines_per_plane == 0 || lines_per_plane >= u64::from(extent.height()),
+            "bytes_per_plane is too small for the copied region"
+        );
+
+        let buffer_row_length = u32::try_from(texel_per_line).expect("bytes_per_line is too large");
+        let buffer_image_height =
+            u32::try_from(lines_per_plane).expect("bytes_per_plane is too large");
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        src.check_not_presented("copy");
+
+        self.refs.add_image(src.clone());
+        self.refs.add_buffer(dst.clone());
+
+        unsafe {
+            self.device.ash().cmd_copy_image_to_buffer(
+                self.handle,
+                src.handle(),
+                ash::vk::ImageLayout::GENERAL,
+                dst.handle(),
+                &[vk::BufferImageCopy {
+                    buffer_offset: start,
+                    buffer_row_length,
+                    buffer_image_height,
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: format_aspect(src.format()),
+                        mip_level: src.base_level() + level,
+                        base_array_layer: src.base_layer() + layers.start,
+                        layer_count: layers.end - layers.start,
+                    },
+                    image_offset: vk::Offset3D {
+                        x: offset.x() as i32,
+                        y: offset.y() as i32,
+                        z: offset.z() as i32,
+                    },
+                    image_extent: vk::Extent3D {
+                        width: extent.width(),
+                        height: extent.height(),
+                        depth: extent.depth(),
+                    },
+                }],
+            )
+        }
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn copy_buffer_to_image(
         &mut self,
         src: &Buffer,
-        start: usize,
-        bytes_per_line: usize,
-        bytes_per_plane: usize,
+        start: u64,
+        bytes_per_line: u64,
+        bytes_per_plane: u64,
         dst: &Image,
         offset: Offset3<u32>,
         extent: Extent3<u32>,
         layers: Range<u32>,
         level: u32,
     ) {
-        let texel_size = dst.format().size();
-        debug_assert_eq!(bytes_per_line % texel_size, 0);
-        debug_assert_eq!(bytes_per_plane % texel_size, 0);
+        debug_assert!(
+            extent.width() > 0 && extent.height() > 0 && extent.depth() > 0,
+            "copy_buffer_to_image: extent {extent:?} is empty"
+        );
+        let texel_size = dst.format().size() as u64;
+        debug_assert!(align::is_aligned_u64(bytes_per_line, texel_size));
+        debug_assert!(
+            bytes_per_line == 0 || bytes_per_line >= u64::from(extent.width()) * texel_size,
+            "bytes_per_line is too small for the copied region"
+        );
+
+        // Zero pitch means tightly packed data.
         let texel_per_line = bytes_per_line / texel_size;
-        let texel_per_plane = bytes_per_plane / texel_size;
+        let lines_per_plane = match bytes_per_line {
+            0 => 0,
+            _ => {
+                debug_assert!(align::is_aligned_u64(bytes_per_plane, bytes_per_line));
+                bytes_per_plane / bytes_per_line
+            }
+        };
+        debug_assert!(
+            lines_per_plane == 0 || lines_per_plane >= u64::from(extent.height()),
+            "bytes_per_plane is too small for the copied region"
+        );
+
+        let buffer_row_length = u32::try_from(texel_per_line).expect("bytes_per_line is too large");
+        let buffer_image_height =
+            u32::try_from(lines_per_plane).expect("bytes_per_plane is too large");
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        dst.check_not_presented("copy");
 
         self.refs.add_buffer(src.clone());
         self.refs.add_image(dst.clone());
@@ -578,9 +1511,9 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
                 dst.handle(),
                 ash::vk::ImageLayout::GENERAL,
                 &[vk::BufferImageCopy {
-                    buffer_offset: start as u64,
-                    buffer_row_length: texel_per_line as u32,
-                    buffer_image_height: texel_per_plane as u32,
+                    buffer_offset: start,
+                    buffer_row_length,
+                    buffer_image_height,
                     image_subresource: vk::ImageSubresourceLayers {
                         aspect_mask: format_aspect(dst.format()),
                         mip_level: dst.base_level() + level,
@@ -602,6 +1535,30 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         }
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn copy_image(&mut self, src: &Image, dst: &Image) {
+        let src_extent = src.extent().into_3d();
+        let dst_extent = dst.extent().into_3d();
+        let extent = Extent3::new(
+            src_extent.width().min(dst_extent.width()),
+            src_extent.height().min(dst_extent.height()),
+            src_extent.depth().min(dst_extent.depth()),
+        );
+
+        self.copy_image_region(
+            src,
+            0,
+            0,
+            Offset3::ZERO,
+            dst,
+            0,
+            0,
+            Offset3::ZERO,
+            extent,
+            1,
+        );
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn copy_image_region(
         &mut self,
@@ -616,6 +1573,47 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         extent: Extent3<u32>,
         layers: u32,
     ) {
+        debug_assert!(
+            extent.width() > 0 && extent.height() > 0 && extent.depth() > 0 && layers > 0,
+            "copy_image_region: extent {extent:?} with {layers} layers is empty"
+        );
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            src.check_not_presented("copy");
+            dst.check_not_presented("copy");
+        }
+
+        if src.image_id() == dst.image_id() {
+            let src_base_layer = src.base_layer() + src_base_layer;
+            let dst_base_layer = dst.base_layer() + dst_base_layer;
+            check_self_copy(
+                src.base_level() + src_level,
+                src_base_layer..src_base_layer + layers,
+                src_offset,
+                dst.base_level() + dst_level,
+                dst_base_layer..dst_base_layer + layers,
+                dst_offset,
+                extent,
+            );
+
+            // Successive copies within the image, like building mip chain level by level,
+            // usually read what previous copy has written.
+            // Source and destination may be views of any parts of the image,
+            // so memory barrier covers the whole image.
+            if self.last_self_copy == Some(src.image_id()) {
+                barrier(
+                    &self.device,
+                    self.handle,
+                    PipelineStages::TRANSFER,
+                    PipelineStages::TRANSFER,
+                );
+
+                #[cfg(any(debug_assertions, feature = "debug"))]
+                self.refs.barrier();
+            }
+            self.last_self_copy = Some(src.image_id());
+        }
+
         self.refs.add_image(src.clone());
         self.refs.add_image(dst.clone());
         unsafe {
@@ -628,7 +1626,7 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
                 &[vk::ImageCopy {
                     src_subresource: vk::ImageSubresourceLayers {
                         aspect_mask: format_aspect(src.format()),
-                        mip_level: src.base_level(),
+                        mip_level: src.base_level() + src_level,
                         base_array_layer: src.base_layer() + src_base_layer,
                         layer_count: layers,
                     },
@@ -639,7 +1637,7 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
                     },
                     dst_subresource: vk::ImageSubresourceLayers {
                         aspect_mask: format_aspect(dst.format()),
-                        mip_level: dst.base_level(),
+                        mip_level: dst.base_level() + dst_level,
                         base_array_layer: dst.base_layer() + dst_base_layer,
                         layer_count: layers,
                     },
@@ -658,9 +1656,39 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         }
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn copy_buffer(&mut self, src: impl AsBufferSlice, dst: impl AsBufferSlice) {
+        let src = src.as_buffer_slice();
+        let dst = dst.as_buffer_slice();
+
+        let size = src.size.min(dst.size);
+        if size == 0 {
+            return;
+        }
+
+        self.refs.add_buffer(src.buffer.clone());
+        self.refs.add_buffer(dst.buffer.clone());
+
+        unsafe {
+            self.device.ash().cmd_copy_buffer(
+                self.handle,
+                src.buffer.handle(),
+                dst.buffer.handle(),
+                &[vk::BufferCopy {
+                    src_offset: src.offset,
+                    dst_offset: dst.offset,
+                    size,
+                }],
+            );
+        }
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn fill_buffer(&mut self, slice: impl AsBufferSlice, byte: u8) {
         let slice = slice.as_buffer_slice();
+        if slice.size == 0 {
+            return;
+        }
 
         self.refs.add_buffer(slice.buffer.clone());
 
@@ -670,8 +1698,8 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
             self.device.ash().cmd_fill_buffer(
                 self.handle,
                 slice.buffer.handle(),
-                slice.offset as u64,
-                slice.size as u64,
+                slice.offset,
+                slice.size,
                 data,
             );
         }
@@ -684,35 +1712,68 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
         }
 
         let slice = slice.as_buffer_slice();
-        assert!(slice.size >= data.len());
+        assert!(
+            slice.size >= data.len() as u64,
+            "Data of size {} does not fit into buffer slice of size {}",
+            data.len(),
+            slice.size
+        );
+        assert!(
+            slice.offset % 4 == 0,
+            "Buffer write offset {} is not a multiple of 4",
+            slice.offset
+        );
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        if self.refs.used_in_pass(slice.buffer) {
+            tracing::warn!(
+                "Buffer '{}' is written after it was used in a pass without a barrier in between. The write is not ordered against the pass",
+                slice.buffer.name()
+            );
+        }
 
         self.refs.add_buffer(slice.buffer.clone());
 
         const CHUNK_SIZE: usize = 65536;
 
-        let full_chunks = data.len() / CHUNK_SIZE;
+        // `vkCmdUpdateBuffer` requires size to be a multiple of 4.
+        let (body, tail) = data.split_at(data.len() & !3);
 
-        for i in 0..full_chunks {
+        for (i, chunk) in body.chunks(CHUNK_SIZE).enumerate() {
             unsafe {
                 self.device.ash().cmd_update_buffer(
                     self.handle,
                     slice.buffer.handle(),
-                    (slice.offset + i * CHUNK_SIZE) as u64,
-                    &data[i * CHUNK_SIZE..(i + 1) * CHUNK_SIZE],
+                    slice.offset + (i * CHUNK_SIZE) as u64,
+                    chunk,
                 )
             }
         }
 
-        let remainder = data.len() % CHUNK_SIZE;
-        if remainder > 0 {
+        if !tail.is_empty() {
+            // Copy commands have no size alignment requirements,
+            // so each byte of unaligned remainder is copied from the byte table.
+            let offset = slice.offset + body.len() as u64;
+            let regions = tail
+                .iter()
+                .enumerate()
+                .map(|(i, &byte)| vk::BufferCopy {
+                    src_offset: byte as u64,
+                    dst_offset: offset + i as u64,
+                    size: 1,
+                })
+                .collect::<SmallVec<[_; 3]>>();
+
             unsafe {
-                self.device.ash().cmd_update_buffer(
+                self.device.ash().cmd_copy_buffer(
                     self.handle,
+                    self.byte_table.handle(),
                     slice.buffer.handle(),
-                    (slice.offset + full_chunks * CHUNK_SIZE) as u64,
-                    &data[full_chunks * CHUNK_SIZE..],
-                )
+                    &regions,
+                );
             }
+
+            self.refs.add_buffer(self.byte_table.clone());
         }
     }
 
@@ -730,6 +1791,8 @@ impl crate::traits::CopyCommandEncoder for CopyCommandEncoder<'_> {
 
 pub struct AccelerationStructureCommandEncoder<'a> {
     device: Device,
+    #[cfg(feature = "trace-gpu")]
+    span: tracing::Span,
     handle: vk::CommandBuffer,
     refs: &'a mut Refs,
 }
@@ -738,6 +1801,9 @@ pub struct AccelerationStructureCommandEncoder<'a> {
 impl crate::traits::AccelerationStructureCommandEncoder
     for AccelerationStructureCommandEncoder<'_>
 {
+    type Blas = Blas;
+    type Tlas = Tlas;
+
     fn build_blas(&mut self, blas: &Blas, desc: BlasBuildDesc, scratch: impl AsBufferSlice) {
         todo!();
     }
@@ -747,6 +1813,51 @@ impl crate::traits::AccelerationStructureCommandEncoder
     }
 }
 
+/// Checks that ray tracing stages are used only when the feature is enabled.
+#[cfg_attr(feature = "inline-more", inline(always))]
+fn debug_check_stages(device: &Device, stages: PipelineStages) {
+    debug_assert!(
+        device.features().contains(Features::RAY_TRACING)
+            || !stages.intersects(
+                PipelineStages::ACCELERATION_STRUCTURE_BUILD | PipelineStages::RAY_TRACING_SHADER
+            ),
+        "Ray tracing stages require `Features::RAY_TRACING`"
+    );
+}
+
+/// Keeps only argument groups that stay bound after switching pipeline layout.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[cfg_attr(feature = "inline-more", inline(always))]
+fn switch_layout(bound_groups: &mut u64, old: Option<&PipelineLayout>, new: &PipelineLayout) {
+    let compatible = match old {
+        None => 0,
+        Some(old) => old.compatible_groups(new),
+    };
+
+    if compatible < 64 {
+        *bound_groups &= (1 << compatible) - 1;
+    }
+}
+
+/// Checks that all non-empty groups of the current pipeline layout are bound.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[cfg_attr(feature = "inline-more", inline(always))]
+fn check_bound_groups(bound_groups: u64, layout: Option<&PipelineLayout>, call: &str) {
+    let Some(layout) = layout else {
+        panic!("`{call}` requires a pipeline to be bound to the encoder");
+    };
+
+    for group in 0..layout.groups_count() {
+        if !layout.group_layout(group).is_empty() && bound_groups & (1 << group) == 0 {
+            panic!(
+                "`{call}` requires arguments group {group} to be bound. \
+                Arguments are kept across pipeline changes only if layouts of this and preceding groups \
+                and constants are identical"
+            );
+        }
+    }
+}
+
 #[cfg_attr(feature = "inline-more", inline(always))]
 fn barrier(
     device: &Device,
@@ -754,6 +1865,8 @@ fn barrier(
     after: PipelineStages,
     before: PipelineStages,
 ) {
+    debug_check_stages(device, after | before);
+
     unsafe {
         device.ash().cmd_pipeline_barrier(
             handle,
@@ -769,6 +1882,18 @@ fn barrier(
     }
 }
 
+/// Marks image as initialized if the whole image is initialized.
+///
+/// Initialization is tracked per image, not per subresource,
+/// so partial initialization is not tracked.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[cfg_attr(feature = "inline-more", inline(always))]
+fn mark_initialized(image: &Image, levels: &Range<u32>, layers: &Range<u32>) {
+    if *levels == (0..image.levels()) && *layers == (0..image.layers()) {
+        image.mark_initialized();
+    }
+}
+
 #[cfg_attr(feature = "inline-more", inline(always))]
 fn image_barrier(
     device: &Device,
@@ -776,7 +1901,13 @@ fn image_barrier(
     after: PipelineStages,
     before: PipelineStages,
     image: &Image,
+    levels: Range<u32>,
+    layers: Range<u32>,
+    discard: bool,
 ) {
+    debug_check_stages(device, after | before);
+    check_image_range(&levels, &layers, image.levels(), image.layers());
+
     let mut aspect_mask = ash::vk::ImageAspectFlags::empty();
     if image.format().is_color() {
         aspect_mask |= ash::vk::ImageAspectFlags::COLOR;
@@ -799,16 +1930,94 @@ fn image_barrier(
             &[ash::vk::ImageMemoryBarrier::default()
                 .src_access_mask(access_for_stages(after))
                 .dst_access_mask(access_for_stages(before))
-                .old_layout(ash::vk::ImageLayout::UNDEFINED)
+                .old_layout(if discard {
+                    ash::vk::ImageLayout::UNDEFINED
+                } else {
+                    ash::vk::ImageLayout::GENERAL
+                })
                 .new_layout(ash::vk::ImageLayout::GENERAL)
                 .image(image.handle())
                 .subresource_range(vk::ImageSubresourceRange {
                     aspect_mask,
-                    base_mip_level: 0,
-                    level_count: image.levels(),
-                    base_array_layer: 0,
-                    layer_count: image.layers(),
+                    base_mip_level: image.base_level() + levels.start,
+                    level_count: levels.end - levels.start,
+                    base_array_layer: image.base_layer() + layers.start,
+                    layer_count: layers.end - layers.start,
                 })],
         )
     }
 }
+
+/// Clamps damage rectangle to the frame extent.
+/// Returns `None` if nothing is left.
+fn clamp_damage(rect: &Rect2, extent: Extent2) -> Option<vk::RectLayerKHR> {
+    let x0 = i64::from(rect.offset.x()).clamp(0, extent.width().into());
+    let y0 = i64::from(rect.offset.y()).clamp(0, extent.height().into());
+    let x1 = (i64::from(rect.offset.x()) + i64::from(rect.extent.width()))
+        .clamp(0, extent.width().into());
+    let y1 = (i64::from(rect.offset.y()) + i64::from(rect.extent.height()))
+        .clamp(0, extent.height().into());
+
+    if x0 == x1 || y0 == y1 {
+        return None;
+    }
+
+    Some(vk::RectLayerKHR {
+        offset: vk::Offset2D {
+            x: x0 as i32,
+            y: y0 as i32,
+        },
+        extent: vk::Extent2D {
+            width: (x1 - x0) as u32,
+            height: (y1 - y0) as u32,
+        },
+        layer: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rect2 {
+        Rect2::new(Offset2::new(x, y), Extent2::new(width, height))
+    }
+
+    fn damage(x: i32, y: i32, width: u32, height: u32) -> Option<(i32, i32, u32, u32)> {
+        clamp_damage(&rect(x, y, width, height), Extent2::new(100, 50)).map(|rect| {
+            (
+                rect.offset.x,
+                rect.offset.y,
+                rect.extent.width,
+                rect.extent.height,
+            )
+        })
+    }
+
+    #[test]
+    fn damage_inside() {
+        assert_eq!(damage(10, 10, 20, 20), Some((10, 10, 20, 20)));
+        assert_eq!(damage(0, 0, 100, 50), Some((0, 0, 100, 50)));
+    }
+
+    #[test]
+    fn damage_clamped() {
+        assert_eq!(damage(-10, -5, 20, 20), Some((0, 0, 10, 15)));
+        assert_eq!(damage(90, 40, 20, 20), Some((90, 40, 10, 10)));
+        assert_eq!(damage(-10, -10, 200, 200), Some((0, 0, 100, 50)));
+        assert_eq!(
+            damage(i32::MAX, 0, u32::MAX, 10),
+            None,
+            "offset beyond extent"
+        );
+        assert_eq!(damage(i32::MIN, 0, u32::MAX, 10), Some((0, 0, 100, 10)));
+    }
+
+    #[test]
+    fn damage_outside() {
+        assert_eq!(damage(100, 0, 10, 10), None);
+        assert_eq!(damage(0, 50, 10, 10), None);
+        assert_eq!(damage(-20, 0, 10, 10), None);
+        assert_eq!(damage(10, 10, 0, 10), None);
+    }
+}
@@ -2,15 +2,17 @@ use std::sync::Arc;
 
 use ash::vk;
 
-use crate::generic::OutOfMemory;
+use crate::generic::{ArgumentGroupInfo, OutOfMemory, PipelineSummary, ResourceId};
 
 use super::{device::WeakDevice, layout::PipelineLayout, shader::Library};
 
 struct Inner {
     owner: WeakDevice,
+    id: ResourceId,
     layout: PipelineLayout,
     idx: usize,
     shader_library: Library,
+    summary: PipelineSummary,
 }
 
 impl Drop for Inner {
@@ -29,23 +31,33 @@ pub struct ComputePipeline {
 impl ComputePipeline {
     pub(super) fn new(
         owner: WeakDevice,
+        id: ResourceId,
         handle: vk::Pipeline,
         idx: usize,
         layout: PipelineLayout,
         shader_library: Library,
+        summary: PipelineSummary,
     ) -> Self {
         ComputePipeline {
             handle,
             layout: layout.handle(),
             inner: Arc::new(Inner {
                 owner,
+                id,
                 layout,
                 idx,
                 shader_library,
+                summary,
             }),
         }
     }
 
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
     pub(super) fn handle(&self) -> vk::Pipeline {
         self.handle
     }
@@ -54,3 +66,28 @@ impl ComputePipeline {
         &self.inner.layout
     }
 }
+
+#[hidden_trait::expose]
+impl crate::traits::Resource for ComputePipeline {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn id(&self) -> ResourceId {
+        self.inner.id
+    }
+
+    fn describe(&self) -> String {
+        self.inner.summary.describe(self.inner.id)
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Pipeline for ComputePipeline {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn argument_groups(&self) -> &[ArgumentGroupInfo] {
+        self.inner.summary.arguments()
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn constants_size(&self) -> usize {
+        self.inner.summary.constants()
+    }
+}
@@ -0,0 +1,272 @@
+//! Descriptor sets for devices without push descriptors.
+//!
+//! Sets are transient: they are allocated when arguments are bound,
+//! written once before binding and never touched again.
+//! They are freed all at once by resetting their pools
+//! when submission that used them is complete.
+//! Therefore update-after-bind is not required.
+
+use ash::vk;
+
+use crate::generic::{ArgumentLayout, OutOfMemory};
+
+use super::{arguments::descriptor_type, handle_host_oom, unexpected_error};
+
+/// Number of sets in the first pool of the chain.
+const INITIAL_POOL_SETS: u32 = 64;
+
+/// Sizes of pools stop doubling at this number of sets.
+const MAX_POOL_SETS: u32 = 16384;
+
+/// Number of descriptors of each type reserved per set.
+const DESCRIPTORS_PER_SET: u32 = 4;
+
+/// Descriptor types that arguments may use.
+const DESCRIPTOR_TYPES: [vk::DescriptorType; 5] = [
+    vk::DescriptorType::SAMPLER,
+    vk::DescriptorType::UNIFORM_BUFFER,
+    vk::DescriptorType::STORAGE_BUFFER,
+    vk::DescriptorType::SAMPLED_IMAGE,
+    vk::DescriptorType::STORAGE_IMAGE,
+];
+
+/// Counters of descriptor pools chain.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct DescriptorCounters {
+    /// Number of pools created.
+    pub pools_created: u64,
+
+    /// Number of sets allocated.
+    pub sets_allocated: u64,
+}
+
+/// Returns number of sets for the pool that follows pool with `last` sets.
+fn next_pool_sets(last: Option<u32>) -> u32 {
+    match last {
+        None => INITIAL_POOL_SETS,
+        Some(sets) => (sets * 2).min(MAX_POOL_SETS),
+    }
+}
+
+/// Returns descriptor counts for pool with `sets` sets
+/// that fits at least one set with `arguments`.
+fn pool_sizes(sets: u32, arguments: &[ArgumentLayout]) -> [vk::DescriptorPoolSize; 5] {
+    DESCRIPTOR_TYPES.map(|ty| {
+        let required = arguments
+            .iter()
+            .filter(|arg| descriptor_type(arg.kind) == ty)
+            .map(|arg| u32::try_from(arg.size).expect("Too many descriptors in array"))
+            .sum::<u32>();
+
+        vk::DescriptorPoolSize {
+            ty,
+            descriptor_count: (sets * DESCRIPTORS_PER_SET).max(required),
+        }
+    })
+}
+
+/// Chain of descriptor pools that belongs to a command buffer.
+///
+/// Sets are allocated from the current pool.
+/// When it is exhausted or fragmented, allocation continues from the next one,
+/// creating a new pool with doubled sizes when the chain ends.
+/// All pools are reset at once when the command buffer is complete
+/// and the chain is reused for another command buffer.
+///
+/// Chain is empty until first set is allocated,
+/// so devices with push descriptors never create pools.
+#[derive(Default)]
+pub(super) struct DescriptorPools {
+    /// Pools with number of sets each one is created for.
+    pools: Vec<(vk::DescriptorPool, u32)>,
+
+    /// Index of the pool to allocate from.
+    current: usize,
+
+    /// Counters since last call to `take_counters`.
+    counters: DescriptorCounters,
+}
+
+impl DescriptorPools {
+    /// Allocates descriptor set with given layout.
+    ///
+    /// `arguments` must be the arguments of the layout.
+    /// They are used to size a new pool so that the set fits into it.
+    pub fn allocate(
+        &mut self,
+        device: &ash::Device,
+        layout: vk::DescriptorSetLayout,
+        arguments: &[ArgumentLayout],
+    ) -> Result<vk::DescriptorSet, OutOfMemory> {
+        loop {
+            let fresh = if self.current == self.pools.len() {
+                self.create_pool(device, arguments)?;
+                true
+            } else {
+                false
+            };
+
+            let (pool, _) = self.pools[self.current];
+
+            let mut set = vk::DescriptorSet::null();
+            let result = unsafe {
+                (device.fp_v1_0().allocate_descriptor_sets)(
+                    device.handle(),
+                    &vk::DescriptorSetAllocateInfo::default()
+                        .descriptor_pool(pool)
+                        .set_layouts(std::slice::from_ref(&layout)),
+                    &mut set,
+                )
+            };
+
+            match result {
+                vk::Result::SUCCESS => {
+                    self.counters.sets_allocated += 1;
+                    return Ok(set);
+                }
+                vk::Result::ERROR_FRAGMENTED_POOL | vk::Result::ERROR_OUT_OF_POOL_MEMORY
+                    if !fresh =>
+                {
+                    // Continue with the next pool.
+                    self.current += 1;
+                }
+                vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => return Err(OutOfMemory),
+                err => unexpected_error(err),
+            }
+        }
+    }
+
+    /// Appends new pool to the chain.
+    /// Pool is twice as large as the last one and fits set with `arguments`.
+    #[cold]
+    fn create_pool(
+        &mut self,
+        device: &ash::Device,
+        arguments: &[ArgumentLayout],
+    ) -> Result<(), OutOfMemory> {
+        let sets = next_pool_sets(self.pools.last().map(|&(_, sets)| sets));
+        let sizes = pool_sizes(sets, arguments);
+
+        let result = unsafe {
+            device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default()
+                    .max_sets(sets)
+                    .pool_sizes(&sizes),
+                None,
+            )
+        };
+
+        let pool = result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY | vk::Result::ERROR_FRAGMENTATION => OutOfMemory,
+            _ => unexpected_error(err),
+        })?;
+
+        self.pools.push((pool, sets));
+        self.counters.pools_created += 1;
+        Ok(())
+    }
+
+    /// Returns counters accumulated since last call and resets them.
+    pub fn take_counters(&mut self) -> DescriptorCounters {
+        std::mem::take(&mut self.counters)
+    }
+
+    /// Frees all sets allocated from the chain.
+    ///
+    /// # Safety
+    ///
+    /// Device must be the one that created the pools.
+    /// Command buffers that use allocated sets must not be pending.
+    pub unsafe fn reset(&mut self, device: &ash::Device) {
+        for &(pool, _) in &self.pools[..self.pools.len().min(self.current + 1)] {
+            // Resetting descriptor pool can't fail.
+            let result = unsafe {
+                device.reset_descriptor_pool(pool, vk::DescriptorPoolResetFlags::empty())
+            };
+            if let Err(err) = result {
+                unexpected_error(err);
+            }
+        }
+        self.current = 0;
+    }
+
+    /// Destroys all pools of the chain.
+    ///
+    /// # Safety
+    ///
+    /// Device must be the one that created the pools.
+    /// Command buffers that use allocated sets must not be pending.
+    pub unsafe fn destroy(&mut self, device: &ash::Device) {
+        for (pool, _) in self.pools.drain(..) {
+            unsafe {
+                device.destroy_descriptor_pool(pool, None);
+            }
+        }
+        self.current = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generic::{ArgumentKind, ShaderStages};
+
+    use super::*;
+
+    /// Number of pools a chain grows to for given number of sets.
+    fn pools_for(sets: u32) -> usize {
+        let mut pools = Vec::new();
+        let mut capacity = 0;
+        while capacity < sets {
+            let next = next_pool_sets(pools.last().copied());
+            capacity += next;
+            pools.push(next);
+        }
+        pools.len()
+    }
+
+    #[test]
+    fn pool_growth() {
+        assert_eq!(next_pool_sets(None), INITIAL_POOL_SETS);
+        assert_eq!(next_pool_sets(Some(64)), 128);
+        assert_eq!(next_pool_sets(Some(MAX_POOL_SETS)), MAX_POOL_SETS);
+        assert_eq!(next_pool_sets(Some(MAX_POOL_SETS / 2 + 1)), MAX_POOL_SETS);
+    }
+
+    #[test]
+    fn pool_chain_10k_sets() {
+        // 64 + 128 + ... + 8192 = 16320 sets.
+        assert_eq!(pools_for(10_000), 8);
+        assert_eq!(pools_for(64), 1);
+        assert_eq!(pools_for(65), 2);
+    }
+
+    #[test]
+    fn pool_fits_large_set() {
+        let arguments = [
+            ArgumentLayout {
+                kind: ArgumentKind::SampledImage,
+                size: 1000,
+                stages: ShaderStages::FRAGMENT,
+                immutable_sampler: None,
+            },
+            ArgumentLayout {
+                kind: ArgumentKind::BindlessTable,
+                size: 500,
+                stages: ShaderStages::FRAGMENT,
+                immutable_sampler: None,
+            },
+        ];
+
+        let sizes = pool_sizes(INITIAL_POOL_SETS, &arguments);
+        for size in sizes {
+            let expected = if size.ty == vk::DescriptorType::SAMPLED_IMAGE {
+                1500
+            } else {
+                INITIAL_POOL_SETS * DESCRIPTORS_PER_SET
+            };
+            assert_eq!(size.descriptor_count, expected, "{:?}", size.ty);
+        }
+    }
+}
@@ -2,8 +2,12 @@ use std::{
     any::TypeId,
     ffi, fmt,
     hash::{Hash, Hasher},
+    ops::Range,
     ptr::NonNull,
-    sync::{Arc, Weak},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Weak,
+    },
 };
 
 use ash::vk::{self, Handle};
@@ -15,30 +19,42 @@ use slab::Slab;
 use smallvec::SmallVec;
 
 use crate::generic::{
-    parse_shader, BlasDesc, BufferDesc, BufferInitDesc, ComputePipelineDesc, CreateLibraryError,
-    CreatePipelineError, Features, ImageDesc, ImageExtent, LibraryDesc, LibraryInput, Memory,
-    OutOfMemory, PrimitiveTopology, RenderPipelineDesc, SamplerDesc, ShaderCompileError,
-    ShaderLanguage, SurfaceError, Swizzle, TlasDesc, VertexStepMode, ViewDesc,
+    check_argument_stages, check_buffer_ranges, check_dual_source_blend, check_entry_point,
+    check_stage_interface, compose_wgsl, find_depth_format, max_buffer_size, parallel_map,
+    parse_shader, reflect_entries, shader_capabilities, AllocError, AllocErrorKind, AllocResource,
+    AllocatorStats, ArgumentGroupLayout, ArgumentKind, ArgumentLayout, BlasDesc, BufferDesc,
+    BufferInitDesc, BufferUsage, CompactionPlan, ComputePipelineDesc, CreateLibraryError,
+    CreatePipelineError, DeferredWorkers, DepthFormatRequirements, DeviceError, DeviceLimits,
+    EntryReflection, FamilyCapabilities, Features, Filter, FormatCaps, ImageDesc, ImageExtent,
+    ImageUsage, LibraryDesc, LibraryInput, Memory, OutOfMemory, PipelinePending, PipelineSummary,
+    PixelFormat, PrimitiveTopology, Quirks, RasterDesc, RenderPipelineDesc, ResourceId,
+    ResourceIds, SamplerDesc, Shader, ShaderCompileError, ShaderLanguage, ShaderStage,
+    ShaderStages, SourceFile, SurfaceError, Swizzle, TlasDesc, VertexStepMode, ViewAspect,
+    ViewDesc, ViewError, WellKnownSampler,
 };
 
 use super::{
     arguments::descriptor_type,
-    buffer::Buffer,
-    format_aspect,
+    bindless::BindlessTable,
+    buffer::{Buffer, BufferMemory},
     from::{IntoAsh, TryIntoAsh},
     handle_host_oom,
     image::Image,
-    instance::InstanceGuard,
+    instance::{InstanceGuard, PlatformSurface},
     layout::{
         DescriptorSetLayout, DescriptorSetLayoutDesc, PipelineLayout, PipelineLayoutDesc,
         WeakDescriptorSetLayout, WeakPipelineLayout,
     },
+    linear::{LinearAllocator, LinearBlock},
+    map_device_error,
     // queue::PendingEpochs,
-    render_pipeline::RenderPipeline,
+    refs::Refs,
+    render_pipeline::{CreatePipelineErrorKind, RenderPipeline},
     sampler::WeakSampler,
     shader::Library,
     surface::Surface,
     unexpected_error,
+    view_aspect,
     Blas,
     ComputePipeline,
     Sampler,
@@ -68,7 +84,39 @@ impl gpu_alloc::MemoryDevice<(vk::DeviceMemory, usize)> for DeviceInner {
             info = info.push_next(&mut info_flags);
         }
 
+        let dedicated = *self.pending_dedicated.lock();
+        let mut info_dedicated;
+
+        if let Some(resource) = dedicated {
+            info_dedicated = match resource {
+                DedicatedResource::Buffer(buffer) => {
+                    vk::MemoryDedicatedAllocateInfo::default().buffer(buffer)
+                }
+                DedicatedResource::Image(image) => {
+                    vk::MemoryDedicatedAllocateInfo::default().image(image)
+                }
+            };
+            info = info.push_next(&mut info_dedicated);
+        }
+
+        let device_local = self.device_local_types & (1 << memory_type) != 0;
+        if device_local {
+            let reserved = self
+                .device_local_reserved
+                .fetch_add(size, Ordering::Relaxed);
+            if reserved.saturating_add(size) > self.device_local_budget {
+                self.device_local_reserved
+                    .fetch_sub(size, Ordering::Relaxed);
+                return Err(gpu_alloc::OutOfMemory::OutOfDeviceMemory);
+            }
+        }
+
         let result = unsafe { self.device.allocate_memory(&info, None) };
+        if result.is_err() && device_local {
+            self.device_local_reserved
+                .fetch_sub(size, Ordering::Relaxed);
+        }
+
         let memory = match result {
             Ok(memory) => memory,
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
@@ -81,7 +129,17 @@ impl gpu_alloc::MemoryDevice<(vk::DeviceMemory, usize)> for DeviceInner {
             Err(err) => unexpected_error(err),
         };
 
-        let idx = self.memory.lock().insert(memory);
+        let idx = self.memory.lock().insert(MemoryObject {
+            memory,
+            size,
+            used: 0,
+            dedicated: dedicated.is_some(),
+            device_local,
+        });
+
+        let reserved = self.reserved.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_reserved.fetch_max(reserved, Ordering::Relaxed);
+
         Ok((memory, idx))
     }
 
@@ -91,7 +149,12 @@ impl gpu_alloc::MemoryDevice<(vk::DeviceMemory, usize)> for DeviceInner {
             self.device.free_memory(memory.0, None);
         }
 
-        self.memory.lock().remove(memory.1);
+        let object = self.memory.lock().remove(memory.1);
+        self.reserved.fetch_sub(object.size, Ordering::Relaxed);
+        if object.device_local {
+            self.device_local_reserved
+                .fetch_sub(object.size, Ordering::Relaxed);
+        }
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
@@ -181,6 +244,92 @@ impl gpu_alloc::MemoryDevice<(vk::DeviceMemory, usize)> for DeviceInner {
     }
 }
 
+/// Memory regions occupied less than this are compacted.
+const COMPACTION_OCCUPANCY_PERCENT: u64 = 50;
+
+/// Buffers moved by compaction are packed into chunks up to this size.
+/// Larger buffers get a chunk each.
+const PACKED_CHUNK_SIZE: u64 = 256 << 20;
+
+/// Region of memory that buffers are allocated from.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MemoryRegion {
+    /// Memory object of general purpose allocator.
+    Object(usize),
+
+    /// Chunk of linear allocator.
+    Chunk(usize),
+}
+
+/// Memory object allocated by the device.
+pub(super) struct MemoryObject {
+    pub memory: vk::DeviceMemory,
+
+    /// Size of the memory object in bytes.
+    pub size: u64,
+
+    /// Bytes occupied by blocks allocated from the memory object.
+    pub used: u64,
+
+    /// Memory object is dedicated to a single resource.
+    pub dedicated: bool,
+
+    /// Memory object is allocated from device-local memory type.
+    pub device_local: bool,
+}
+
+/// General purpose allocator that accounts bytes
+/// occupied by blocks in each memory object.
+///
+/// Occupancy is used to find fragmented memory objects for compaction.
+pub(super) struct Allocator {
+    inner: gpu_alloc::GpuAllocator<(vk::DeviceMemory, usize)>,
+}
+
+impl Allocator {
+    pub fn new(inner: gpu_alloc::GpuAllocator<(vk::DeviceMemory, usize)>) -> Self {
+        Allocator { inner }
+    }
+
+    pub unsafe fn alloc(
+        &mut self,
+        device: &DeviceInner,
+        request: gpu_alloc::Request,
+    ) -> Result<MemoryBlock<(vk::DeviceMemory, usize)>, gpu_alloc::AllocationError> {
+        let block = unsafe { self.inner.alloc(device, request)? };
+        device.memory.lock()[block.memory().1].used += block.size();
+        Ok(block)
+    }
+
+    pub unsafe fn alloc_with_dedicated(
+        &mut self,
+        device: &DeviceInner,
+        request: gpu_alloc::Request,
+        dedicated: gpu_alloc::Dedicated,
+    ) -> Result<MemoryBlock<(vk::DeviceMemory, usize)>, gpu_alloc::AllocationError> {
+        let block = unsafe {
+            self.inner
+                .alloc_with_dedicated(device, request, dedicated)?
+        };
+        device.memory.lock()[block.memory().1].used += block.size();
+        Ok(block)
+    }
+
+    pub unsafe fn dealloc(
+        &mut self,
+        device: &DeviceInner,
+        block: MemoryBlock<(vk::DeviceMemory, usize)>,
+    ) {
+        // Account before memory object may be freed.
+        device.memory.lock()[block.memory().1].used -= block.size();
+        unsafe { self.inner.dealloc(device, block) }
+    }
+
+    pub unsafe fn cleanup(&mut self, device: &DeviceInner) {
+        unsafe { self.inner.cleanup(device) }
+    }
+}
+
 struct DescriptorUpdateTemplateEntries {
     entries: Vec<ash::vk::DescriptorUpdateTemplateEntry>,
 }
@@ -227,6 +376,82 @@ impl Hash for DescriptorUpdateTemplateEntries {
     }
 }
 
+/// Source of `VK_KHR_maintenance4` functions.
+pub(super) enum Maintenance4 {
+    /// Promoted to core in Vulkan 1.3.
+    Core,
+    Khr(ash::khr::maintenance4::Device),
+}
+
+/// Support of vertex attribute divisors.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct VertexDivisor {
+    /// Instance step rate other than 1 is supported.
+    pub rate: bool,
+
+    /// Instance step rate 0 is supported.
+    /// Used for constant vertex attributes.
+    pub zero: bool,
+}
+
+/// Limitations of device that implements portability subset, e.g. MoltenVK.
+///
+/// Subset features that mev never uses, e.g. triangle fans, events
+/// or sampler LOD bias, are not tracked.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PortabilitySubset {
+    /// Vertex attributes may extend past the binding stride.
+    pub vertex_attribute_access_beyond_stride: bool,
+
+    /// Vertex binding strides must be multiple of this value.
+    pub min_vertex_input_binding_stride_alignment: u32,
+
+    /// Image views may have non-identity swizzle.
+    pub image_view_format_swizzle: bool,
+
+    /// Image views may have format with different components or bits than the image.
+    pub image_view_format_reinterpretation: bool,
+}
+
+impl PortabilitySubset {
+    /// Fully conformant device without limitations.
+    pub const CONFORMANT: Self = PortabilitySubset {
+        vertex_attribute_access_beyond_stride: true,
+        min_vertex_input_binding_stride_alignment: 1,
+        image_view_format_swizzle: true,
+        image_view_format_reinterpretation: true,
+    };
+
+    /// Checks that the view of the image with `format` can be created.
+    ///
+    /// Views with sRGB or linear variant of the image format don't reinterpret it.
+    pub fn check_view(
+        &self,
+        name: &str,
+        format: PixelFormat,
+        desc: &ViewDesc,
+    ) -> Result<(), ViewError> {
+        if !self.image_view_format_swizzle && desc.swizzle != Swizzle::IDENTITY {
+            return Err(ViewError::SwizzleNotSupported {
+                image: name.to_owned(),
+            });
+        }
+
+        if !self.image_view_format_reinterpretation
+            && desc.format != format
+            && !format.is_srgb_pair(desc.format)
+        {
+            return Err(ViewError::FormatNotSupported {
+                image: name.to_owned(),
+                format,
+                view_format: desc.format,
+            });
+        }
+
+        Ok(())
+    }
+}
+
 pub(super) struct DeviceInner {
     _guard: Arc<InstanceGuard>,
     device: ash::Device,
@@ -234,33 +459,110 @@ pub(super) struct DeviceInner {
     physical_device: vk::PhysicalDevice,
     version: Version,
     families: Vec<u32>,
+    family_capabilities: Vec<FamilyCapabilities>,
     features: Features,
+    quirks: Quirks,
     properties: ash::vk::PhysicalDeviceProperties,
 
-    memory: Mutex<Slab<vk::DeviceMemory>>,
+    /// Maximum size of a buffer.
+    /// `u64::MAX` if device doesn't report the limit.
+    max_buffer_size: u64,
+
+    vertex_divisor: VertexDivisor,
+
+    /// Only devices with portability subset have limitations.
+    portability: PortabilitySubset,
+
+    /// Number of descriptors in set layouts of bindless tables.
+    /// Zero unless descriptor indexing is enabled.
+    max_bindless_table_size: u32,
+
+    /// Bounds check policies for shaders translated by naga.
+    bounds_check_policies: naga::proc::BoundsCheckPolicies,
+
+    /// Allocated memory objects.
+    memory: Mutex<Slab<MemoryObject>>,
+    reserved: AtomicU64,
+    peak_reserved: AtomicU64,
+
+    /// Images and buffers of this size or larger get dedicated memory
+    /// if driver prefers so.
+    preferred_dedicated_threshold: u64,
+
+    /// Images and buffers of this size or larger get dedicated memory.
+    dedicated_resource_threshold: u64,
+
+    /// Resource that memory being allocated is dedicated to.
+    /// Set only while `allocator` is locked.
+    pending_dedicated: Mutex<Option<DedicatedResource>>,
+
+    /// Allocate from host-visible memory when device memory is exhausted.
+    fallback_to_host_memory: bool,
+    fallback_allocations: AtomicUsize,
+    fallback_bytes: AtomicU64,
+
+    /// Mask of device-local memory types.
+    device_local_types: u32,
+
+    /// Allocations from device-local memory types over this size fail.
+    device_local_budget: u64,
+    device_local_reserved: AtomicU64,
+
     buffers: Mutex<Slab<vk::Buffer>>,
     images: Mutex<Slab<vk::Image>>,
     image_views: Mutex<Slab<vk::ImageView>>,
     samplers: Mutex<HashMap<SamplerDesc, WeakSampler>>,
 
+    /// Well-known samplers are kept alive until device is dropped.
+    well_known_samplers: Mutex<[Option<Sampler>; WellKnownSampler::COUNT]>,
+
     libraries: Mutex<Slab<vk::ShaderModule>>,
     set_layouts: Mutex<HashMap<DescriptorSetLayoutDesc, WeakDescriptorSetLayout>>,
     pipeline_layouts: Mutex<HashMap<PipelineLayoutDesc, WeakPipelineLayout>>,
     pipelines: Mutex<Slab<vk::Pipeline>>,
 
-    allocator: Mutex<gpu_alloc::GpuAllocator<(vk::DeviceMemory, usize)>>,
+    /// Identifiers of resources created by the device.
+    resource_ids: ResourceIds,
+
+    /// Workers creating deferred pipelines.
+    deferred: DeferredWorkers,
+
+    /// Unsignaled binary semaphores without pending operations ready for reuse.
+    /// Destroyed with the device.
+    semaphores: Mutex<Vec<vk::Semaphore>>,
+
+    /// Locked before `allocator` when both are required.
+    linear: Mutex<LinearAllocator>,
+    allocator: Mutex<Allocator>,
 
     // # Extensions
-    push_descriptor: ash::khr::push_descriptor::Device,
+    push_descriptor: Option<ash::khr::push_descriptor::Device>,
+    maintenance4: Option<Maintenance4>,
+
+    /// Timeline semaphores are enabled.
+    timeline_semaphore: bool,
+
+    /// Images can list formats of their views with `VK_KHR_image_format_list`.
+    image_format_list: bool,
+
     surface: Option<ash::khr::surface::Instance>,
     swapchain: Option<ash::khr::swapchain::Device>,
     swapchain_maintenance1: Option<ash::ext::swapchain_maintenance1::Device>,
+    platform_surface: PlatformSurface,
 
-    #[cfg(target_os = "windows")]
-    win32_surface: Option<ash::khr::win32_surface::Instance>,
+    /// Loaded when `Features::DEVICE_FAULT` is enabled.
+    device_fault: Option<ash::ext::device_fault::Device>,
 
     #[cfg(any(debug_assertions, feature = "debug"))]
     debug_utils: Option<ash::ext::debug_utils::Device>,
+
+    /// Formats already reported as sampled with linear filter while not filterable.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    reported_unfilterable: Mutex<hashbrown::HashSet<PixelFormat>>,
+
+    /// Pairs of pipeline target and attachment formats already reported as sRGB mismatch.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    reported_srgb_mismatch: Mutex<hashbrown::HashSet<(PixelFormat, PixelFormat)>>,
 }
 
 impl Drop for DeviceInner {
@@ -287,9 +589,9 @@ impl Drop for DeviceInner {
             }
         }
 
-        for memory in self.memory.get_mut().drain() {
+        for object in self.memory.get_mut().drain() {
             unsafe {
-                self.device.free_memory(memory, None);
+                self.device.free_memory(object.memory, None);
             }
         }
 
@@ -337,6 +639,12 @@ impl Drop for DeviceInner {
             }
         }
 
+        for semaphore in self.semaphores.get_mut().drain(..) {
+            unsafe {
+                self.device.destroy_semaphore(semaphore, None);
+            }
+        }
+
         unsafe {
             self.device.destroy_device(None);
         }
@@ -355,9 +663,17 @@ impl WeakDevice {
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
-    pub fn drop_buffer(&self, idx: usize, block: MemoryBlock<(vk::DeviceMemory, usize)>) {
+    pub fn drop_buffer(&self, idx: usize, memory: BufferMemory) {
         if let Some(inner) = self.inner.upgrade() {
-            unsafe { inner.allocator.lock().dealloc(&*inner, block) }
+            match memory {
+                BufferMemory::Block(block) => unsafe {
+                    inner.allocator.lock().dealloc(&*inner, block)
+                },
+                BufferMemory::Linear(block) => unsafe {
+                    let mut linear = inner.linear.lock();
+                    linear.dealloc(&*inner, &mut inner.allocator.lock(), block)
+                },
+            }
 
             let mut buffers = inner.buffers.lock();
             let buffer = buffers.remove(idx);
@@ -500,6 +816,15 @@ impl WeakDevice {
         }
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn drop_descriptor_pool(&self, pool: vk::DescriptorPool) {
+        if let Some(inner) = self.inner.upgrade() {
+            unsafe {
+                inner.device.destroy_descriptor_pool(pool, None);
+            }
+        }
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     pub fn drop_library(&self, idx: usize) {
         if let Some(inner) = self.inner.upgrade() {
@@ -548,15 +873,32 @@ impl Device {
         physical_device: vk::PhysicalDevice,
         device: ash::Device,
         families: Vec<u32>,
+        family_capabilities: Vec<FamilyCapabilities>,
         features: Features,
+        quirks: Quirks,
         properties: ash::vk::PhysicalDeviceProperties,
+        max_buffer_size: u64,
+        vertex_divisor: VertexDivisor,
+        portability: PortabilitySubset,
+        max_bindless_table_size: u32,
+        bounds_check_policies: naga::proc::BoundsCheckPolicies,
         allocator: gpu_alloc::GpuAllocator<(vk::DeviceMemory, usize)>,
+        linear: LinearAllocator,
+        fallback_to_host_memory: bool,
+        device_local_types: u32,
+        device_local_budget: u64,
+        preferred_dedicated_threshold: u64,
+        dedicated_resource_threshold: u64,
         // epochs: Vec<Arc<PendingEpochs>>,
-        push_descriptor: ash::khr::push_descriptor::Device,
+        push_descriptor: Option<ash::khr::push_descriptor::Device>,
+        maintenance4: Option<Maintenance4>,
+        timeline_semaphore: bool,
+        image_format_list: bool,
         surface: Option<ash::khr::surface::Instance>,
-        #[cfg(target_os = "windows")] win32_surface: Option<ash::khr::win32_surface::Instance>,
+        platform_surface: PlatformSurface,
         swapchain: Option<ash::khr::swapchain::Device>,
         swapchain_maintenance1: Option<ash::ext::swapchain_maintenance1::Device>,
+        device_fault: Option<ash::ext::device_fault::Device>,
         #[cfg(any(debug_assertions, feature = "debug"))] debug_utils: Option<
             ash::ext::debug_utils::Device,
         >,
@@ -569,26 +911,57 @@ impl Device {
                 physical_device,
                 version,
                 families,
+                family_capabilities,
                 features,
+                quirks,
                 properties,
+                max_buffer_size,
+                vertex_divisor,
+                portability,
+                max_bindless_table_size,
+                bounds_check_policies,
                 memory: Mutex::new(Slab::with_capacity(64)),
+                reserved: AtomicU64::new(0),
+                peak_reserved: AtomicU64::new(0),
+                preferred_dedicated_threshold,
+                dedicated_resource_threshold,
+                pending_dedicated: Mutex::new(None),
+                fallback_to_host_memory,
+                fallback_allocations: AtomicUsize::new(0),
+                fallback_bytes: AtomicU64::new(0),
+                device_local_types,
+                device_local_budget,
+                device_local_reserved: AtomicU64::new(0),
                 buffers: Mutex::new(Slab::with_capacity(1024)),
                 images: Mutex::new(Slab::with_capacity(1024)),
                 image_views: Mutex::new(Slab::with_capacity(1024)),
                 samplers: Mutex::new(HashMap::with_capacity(64)),
+                well_known_samplers: Mutex::new([const { None }; WellKnownSampler::COUNT]),
                 libraries: Mutex::new(Slab::with_capacity(64)),
                 set_layouts: Mutex::new(HashMap::with_capacity(256)),
                 pipeline_layouts: Mutex::new(HashMap::with_capacity(64)),
                 pipelines: Mutex::new(Slab::with_capacity(128)),
-                allocator: Mutex::new(allocator),
+                resource_ids: ResourceIds::new(),
+                deferred: DeferredWorkers::new(),
+                semaphores: Mutex::new(Vec::new()),
+                linear: Mutex::new(linear),
+                allocator: Mutex::new(Allocator::new(allocator)),
                 push_descriptor,
+                maintenance4,
+                timeline_semaphore,
+                image_format_list,
                 surface,
-                win32_surface,
                 swapchain,
                 swapchain_maintenance1,
+                platform_surface,
+                device_fault,
                 // epochs,
                 #[cfg(any(debug_assertions, feature = "debug"))]
                 debug_utils,
+                #[cfg(any(debug_assertions, feature = "debug"))]
+                reported_unfilterable: Mutex::new(hashbrown::HashSet::new()),
+                #[cfg(any(debug_assertions, feature = "debug"))]
+                reported_srgb_mismatch: Mutex::new(hashbrown::HashSet::new()),
             }),
         }
     }
@@ -618,6 +991,12 @@ impl Device {
         self.is(owned.owner())
     }
 
+    /// Returns identifier for a new resource.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn new_resource_id(&self) -> ResourceId {
+        self.inner.resource_ids.next()
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     pub(super) fn weak(&self) -> WeakDevice {
         WeakDevice {
@@ -625,9 +1004,320 @@ impl Device {
         }
     }
 
+    /// Allocates memory block for a resource.
+    ///
+    /// Memory object is dedicated to the `dedicated` resource if specified.
+    /// Falls back to host-visible memory if device memory is exhausted
+    /// and fallback is enabled.
+    unsafe fn alloc_block(
+        &self,
+        name: &str,
+        usage: gpu_alloc::UsageFlags,
+        requirements: vk::MemoryRequirements,
+        dedicated: Option<DedicatedResource>,
+    ) -> Result<MemoryBlock<(vk::DeviceMemory, usize)>, gpu_alloc::AllocationError> {
+        let fallback = self.inner.fallback_to_host_memory
+            && usage.contains(gpu_alloc::UsageFlags::FAST_DEVICE_ACCESS);
+
+        let mut memory_types = requirements.memory_type_bits;
+
+        // Allocator tries other memory types by itself when device-local ones are exhausted.
+        // Keep it to device-local memory so that fallback is reported.
+        if fallback && memory_types & self.inner.device_local_types != 0 {
+            memory_types &= self.inner.device_local_types;
+        }
+
+        let request = gpu_alloc::Request {
+            size: requirements.size,
+            align_mask: requirements.alignment - 1,
+            usage,
+            memory_types,
+        };
+
+        let result = match dedicated {
+            None => unsafe { self.inner.allocator.lock().alloc(&*self.inner, request) },
+            Some(resource) => {
+                let mut allocator = self.inner.allocator.lock();
+                *self.inner.pending_dedicated.lock() = Some(resource);
+                let result = unsafe {
+                    allocator.alloc_with_dedicated(
+                        &*self.inner,
+                        request,
+                        gpu_alloc::Dedicated::Required,
+                    )
+                };
+                *self.inner.pending_dedicated.lock() = None;
+                result
+            }
+        };
+
+        match result {
+            Err(gpu_alloc::AllocationError::OutOfDeviceMemory) if fallback => {
+                let memory_types = requirements.memory_type_bits & !self.inner.device_local_types;
+                if memory_types == 0 {
+                    // All memory is device-local on unified memory architectures.
+                    return Err(gpu_alloc::AllocationError::OutOfDeviceMemory);
+                }
+
+                let fallback = gpu_alloc::Request {
+                    usage: gpu_alloc::UsageFlags::HOST_ACCESS,
+                    memory_types,
+                    ..request
+                };
+
+                let Ok(block) =
+                    (unsafe { self.inner.allocator.lock().alloc(&*self.inner, fallback) })
+                else {
+                    return Err(gpu_alloc::AllocationError::OutOfDeviceMemory);
+                };
+
+                tracing::warn!(
+                    "Out of device memory, '{}' ({} bytes) is allocated from host-visible memory",
+                    name,
+                    requirements.size
+                );
+
+                self.inner
+                    .fallback_allocations
+                    .fetch_add(1, Ordering::Relaxed);
+                self.inner
+                    .fallback_bytes
+                    .fetch_add(requirements.size, Ordering::Relaxed);
+
+                Ok(block)
+            }
+            result => result,
+        }
+    }
+
+    /// Creates buffers like `sources` and binds them to new memory chunks
+    /// where they are packed back to back.
+    ///
+    /// Returns `None` for sources that need dedicated memory, they are not packed.
+    /// Either all other buffers are created or none.
+    pub(super) fn pack_buffers(
+        &self,
+        sources: &[&Buffer],
+    ) -> Result<Vec<Option<Buffer>>, OutOfMemory> {
+        let destroy = |handles: &[(usize, vk::Buffer, vk::MemoryRequirements)]| {
+            for &(_, handle, _) in handles {
+                unsafe { self.inner.device.destroy_buffer(handle, None) }
+            }
+        };
+
+        let mut handles = Vec::with_capacity(sources.len());
+        for (idx, source) in sources.iter().enumerate() {
+            let desc = BufferDesc {
+                size: source.size(),
+                usage: source.usage(),
+                memory: Memory::Device,
+                name: source.name(),
+                shared_across_queues: source.shared_across_queues(),
+            };
+
+            let result = unsafe {
+                self.inner
+                    .device
+                    .create_buffer(&self.buffer_create_info(&desc), None)
+            };
+
+            let handle = match result {
+                Ok(handle) => handle,
+                Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => handle_host_oom(),
+                Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                    destroy(&handles);
+                    return Err(OutOfMemory);
+                }
+                Err(err) => unexpected_error(err),
+            };
+
+            let (requirements, dedicated) = unsafe { self.buffer_memory_requirements(handle) };
+            if dedicated.is_some() {
+                unsafe { self.inner.device.destroy_buffer(handle, None) };
+                continue;
+            }
+
+            handles.push((idx, handle, requirements));
+        }
+
+        // Buffers that share a chunk must allow the same memory types.
+        handles.sort_by_key(|&(_, _, requirements)| requirements.memory_type_bits);
+        let requirements = handles.iter().map(|&(_, _, r)| r).collect::<Vec<_>>();
+
+        let mut packed = sources.iter().map(|_| None).collect::<Vec<_>>();
+
+        for range in pack_ranges(&requirements) {
+            let result = {
+                let mut linear = self.inner.linear.lock();
+                unsafe {
+                    linear.alloc_packed(
+                        &self.inner,
+                        &mut self.inner.allocator.lock(),
+                        memory_to_usage_flags(Memory::Device),
+                        requirements[range.start].memory_type_bits,
+                        &requirements[range.clone()],
+                    )
+                }
+            };
+
+            // Buffers packed so far are dropped with their memory.
+            let Ok(blocks) = result else {
+                destroy(&handles[range.start..]);
+                return Err(OutOfMemory);
+            };
+
+            // Buffers own their memory before binding,
+            // so they are freed on drop if binding fails.
+            let mut bindings = Vec::with_capacity(range.len());
+            for (&(idx, handle, _), block) in handles[range.clone()].iter().zip(blocks) {
+                let (memory, offset) = self.inner.linear.lock().memory(&block);
+                bindings.push((idx, handle, memory, offset));
+
+                let source = sources[idx];
+                let buffer_idx = self.inner.buffers.lock().insert(handle);
+
+                packed[idx] = Some(Buffer::new(
+                    self.weak(),
+                    // Packed buffer replaces the source and keeps its identifier.
+                    source.id(),
+                    source.name(),
+                    handle,
+                    source.size(),
+                    source.usage(),
+                    source.shared_across_queues(),
+                    Memory::Device,
+                    BufferMemory::Linear(block),
+                    buffer_idx,
+                ));
+            }
+
+            for (idx, handle, memory, offset) in bindings {
+                let result =
+                    unsafe { self.inner.device.bind_buffer_memory(handle, memory, offset) };
+                match result {
+                    Ok(()) => {}
+                    Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => handle_host_oom(),
+                    Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                        destroy(&handles[range.end..]);
+                        return Err(OutOfMemory);
+                    }
+                    Err(err) => unexpected_error(err),
+                }
+
+                #[cfg(any(debug_assertions, feature = "debug"))]
+                self.set_object_name(handle, sources[idx].name());
+            }
+        }
+
+        Ok(packed)
+    }
+
+    /// Returns memory requirements of the buffer
+    /// and the buffer if it should get dedicated memory.
+    unsafe fn buffer_memory_requirements(
+        &self,
+        buffer: vk::Buffer,
+    ) -> (vk::MemoryRequirements, Option<DedicatedResource>) {
+        if self.inner.version < Version::V1_1 {
+            let requirements = unsafe { self.inner.device.get_buffer_memory_requirements(buffer) };
+            return (requirements, None);
+        }
+
+        let mut dedicated = vk::MemoryDedicatedRequirements::default();
+        let mut requirements = vk::MemoryRequirements2::default().push_next(&mut dedicated);
+        unsafe {
+            self.inner.device.get_buffer_memory_requirements2(
+                &vk::BufferMemoryRequirementsInfo2::default().buffer(buffer),
+                &mut requirements,
+            );
+        }
+        let requirements = requirements.memory_requirements;
+
+        let dedicated = self
+            .wants_dedicated(requirements.size, &dedicated)
+            .then_some(DedicatedResource::Buffer(buffer));
+        (requirements, dedicated)
+    }
+
+    /// Returns memory requirements of the image
+    /// and the image if it should get dedicated memory.
+    unsafe fn image_memory_requirements(
+        &self,
+        image: vk::Image,
+    ) -> (vk::MemoryRequirements, Option<DedicatedResource>) {
+        if self.inner.version < Version::V1_1 {
+            let requirements = unsafe { self.inner.device.get_image_memory_requirements(image) };
+            return (requirements, None);
+        }
+
+        let mut dedicated = vk::MemoryDedicatedRequirements::default();
+        let mut requirements = vk::MemoryRequirements2::default().push_next(&mut dedicated);
+        unsafe {
+            self.inner.device.get_image_memory_requirements2(
+                &vk::ImageMemoryRequirementsInfo2::default().image(image),
+                &mut requirements,
+            );
+        }
+        let requirements = requirements.memory_requirements;
+
+        let dedicated = self
+            .wants_dedicated(requirements.size, &dedicated)
+            .then_some(DedicatedResource::Image(image));
+        (requirements, dedicated)
+    }
+
+    /// Checks if resource should get dedicated memory.
+    fn wants_dedicated(&self, size: u64, dedicated: &vk::MemoryDedicatedRequirements) -> bool {
+        wants_dedicated(
+            size,
+            dedicated,
+            self.inner.preferred_dedicated_threshold,
+            self.inner.dedicated_resource_threshold,
+        )
+    }
+
+    /// Writes bytes to linearly sub-allocated buffer memory.
+    pub(super) unsafe fn write_linear(&self, block: &LinearBlock, offset: u64, data: &[u8]) {
+        let mut linear = self.inner.linear.lock();
+        unsafe { linear.write_bytes(&self.inner, block, offset, data) }.unwrap();
+    }
+
+    /// Reads bytes from linearly sub-allocated buffer memory.
+    pub(super) unsafe fn read_linear(&self, block: &LinearBlock, offset: u64, data: &mut [u8]) {
+        let mut linear = self.inner.linear.lock();
+        unsafe { linear.read_bytes(&self.inner, block, offset, data) }.unwrap();
+    }
+
+    /// Maps range of linearly sub-allocated buffer memory.
+    pub(super) unsafe fn map_linear(
+        &self,
+        block: &LinearBlock,
+        offset: u64,
+    ) -> Result<NonNull<u8>, gpu_alloc::MapError> {
+        let mut linear = self.inner.linear.lock();
+        unsafe { linear.map(&self.inner, block, offset) }
+    }
+
+    /// Flushes and unmaps range mapped with [`Device::map_linear`].
+    pub(super) unsafe fn unmap_linear(
+        &self,
+        block: &LinearBlock,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), gpu_alloc::MapError> {
+        let mut linear = self.inner.linear.lock();
+        unsafe { linear.unmap(&self.inner, block, offset, size, true) }
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn push_descriptor(&self) -> Option<&ash::khr::push_descriptor::Device> {
+        self.inner.push_descriptor.as_ref()
+    }
+
+    /// Returns `true` if timeline semaphores are enabled.
     #[cfg_attr(feature = "inline-more", inline(always))]
-    pub fn push_descriptor(&self) -> &ash::khr::push_descriptor::Device {
-        &self.inner.push_descriptor
+    pub(super) fn timeline_semaphore(&self) -> bool {
+        self.inner.timeline_semaphore
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
@@ -647,6 +1337,11 @@ impl Device {
         self.inner.swapchain_maintenance1.as_ref()
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn device_fault(&self) -> Option<&ash::ext::device_fault::Device> {
+        self.inner.device_fault.as_ref()
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     pub(super) fn physical_device(&self) -> vk::PhysicalDevice {
         self.inner.physical_device
@@ -657,6 +1352,169 @@ impl Device {
         &self.inner.families
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn portability(&self) -> &PortabilitySubset {
+        &self.inner.portability
+    }
+
+    /// Returns sharing mode and queue families for a new resource.
+    ///
+    /// Concurrent sharing is used only when device has queues of several families,
+    /// otherwise exclusive sharing is equivalent and faster.
+    fn sharing_mode(&self, shared_across_queues: bool) -> (vk::SharingMode, &[u32]) {
+        if shared_across_queues && self.inner.families.len() > 1 {
+            (vk::SharingMode::CONCURRENT, &self.inner.families)
+        } else {
+            (vk::SharingMode::EXCLUSIVE, &[])
+        }
+    }
+
+    /// Largest buffer that can be created with given usage.
+    fn max_buffer_size(&self, usage: BufferUsage) -> u64 {
+        let limits = &self.inner.properties.limits;
+        max_buffer_size(
+            usage,
+            self.inner.max_buffer_size,
+            u64::from(limits.max_storage_buffer_range),
+        )
+    }
+
+    fn buffer_create_info(&self, desc: &BufferDesc) -> vk::BufferCreateInfo<'_> {
+        let (sharing_mode, families) = self.sharing_mode(desc.shared_across_queues);
+
+        vk::BufferCreateInfo::default()
+            .size(desc.size)
+            .sharing_mode(sharing_mode)
+            .queue_family_indices(families)
+            .usage(desc.usage.into_ash())
+    }
+
+    /// Returns formats of views that image with given format may have.
+    ///
+    /// Images with sRGB pair formats are created with mutable format
+    /// and list both formats, so drivers can keep compression enabled for them.
+    /// Empty if the format has no pair or `VK_KHR_image_format_list` is not available.
+    fn image_view_formats(&self, format: PixelFormat) -> SmallVec<[vk::Format; 2]> {
+        let mut formats = SmallVec::new();
+        if let Some(srgb) = format.srgb_variant() {
+            if self.inner.image_format_list {
+                formats.push(format.linear_variant().try_into_ash().unwrap());
+                formats.push(srgb.try_into_ash().unwrap());
+            }
+        }
+        formats
+    }
+
+    /// Validates image description and builds create info for it.
+    ///
+    /// Formats from [`image_view_formats`](Device::image_view_formats)
+    /// must be chained to it if there are any.
+    fn image_create_info(
+        &self,
+        desc: &ImageDesc,
+    ) -> Result<vk::ImageCreateInfo<'_>, AllocErrorKind> {
+        assert!(
+            !desc.usage.contains(ImageUsage::TRANSIENT)
+                || (desc.usage - ImageUsage::TRANSIENT) == ImageUsage::TARGET,
+            "Transient image may only be used as render target"
+        );
+
+        let limits = &self.inner.properties.limits;
+        let (dimension, max) = match desc.extent {
+            ImageExtent::D1(extent) => (extent.width(), limits.max_image_dimension1_d),
+            ImageExtent::D2(extent) => (
+                extent.width().max(extent.height()),
+                limits.max_image_dimension2_d,
+            ),
+            ImageExtent::D3(extent) => (
+                extent.width().max(extent.height()).max(extent.depth()),
+                limits.max_image_dimension3_d,
+            ),
+        };
+
+        if dimension > max {
+            return Err(AllocErrorKind::ExceedsLimits { dimension, max });
+        }
+
+        // Allows sRGB and linear views of the image.
+        let mut flags = vk::ImageCreateFlags::empty();
+        if desc.format.srgb_variant().is_some() {
+            flags |= vk::ImageCreateFlags::MUTABLE_FORMAT;
+        }
+
+        let (sharing_mode, families) = self.sharing_mode(desc.shared_across_queues);
+
+        Ok(vk::ImageCreateInfo::default()
+            .flags(flags)
+            .image_type(desc.extent.into_ash())
+            .format(desc.format.try_into_ash().expect("Unsupported format"))
+            .extent(desc.extent.into_ash())
+            .array_layers(desc.layers)
+            .mip_levels(desc.levels)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage((desc.usage, desc.format).into_ash())
+            .sharing_mode(sharing_mode)
+            .queue_family_indices(families)
+            .initial_layout(vk::ImageLayout::UNDEFINED))
+    }
+
+    /// Warns once per format when image that can't be filtered linearly
+    /// is bound in the same arguments group with a linear sampler.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn validate_sampled_filtering(&self, images: &[Image], samplers: &[Sampler]) {
+        let linear = samplers.iter().any(|sampler| {
+            let desc = sampler.desc();
+            desc.min_filter == Filter::Linear || desc.mag_filter == Filter::Linear
+        });
+
+        if !linear {
+            return;
+        }
+
+        for image in images {
+            if !image.usage().contains(ImageUsage::SAMPLED) {
+                continue;
+            }
+
+            let format = image.format();
+            if self.format_caps(format).filterable_linear() {
+                continue;
+            }
+
+            if self.inner.reported_unfilterable.lock().insert(format) {
+                tracing::warn!(
+                    "Image with format {:?} is bound together with a linear sampler, but the format does not support linear filtering on this device. Use `Filter::Nearest` or a filterable format instead",
+                    format
+                );
+            }
+        }
+    }
+
+    /// Warns once per format pair when pipeline renders into attachment
+    /// which format differs from the pipeline's target only in sRGB encoding.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn check_srgb_mismatch(&self, targets: &[PixelFormat], attachments: &[PixelFormat]) {
+        for (&target, &attachment) in targets.iter().zip(attachments) {
+            if !target.is_srgb_pair(attachment) {
+                continue;
+            }
+
+            if self
+                .inner
+                .reported_srgb_mismatch
+                .lock()
+                .insert((target, attachment))
+            {
+                tracing::warn!(
+                    "Render pipeline with color target {:?} is used with attachment {:?}. Values are likely gamma-corrected twice or not at all. Use attachment format the pipeline was created for, `Image::srgb_view` or `Image::linear_view`",
+                    target,
+                    attachment
+                );
+            }
+        }
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     #[cfg(any(debug_assertions, feature = "debug"))]
     fn set_object_name<T: Handle>(&self, handle: T, name: &str) {
@@ -674,11 +1532,41 @@ impl Device {
         }
     }
 
-    #[cfg_attr(feature = "inline-more", inline(always))]
-    fn new_sampler_slow(&self, count: usize, desc: SamplerDesc) -> Result<Sampler, OutOfMemory> {
-        if self.inner.properties.limits.max_sampler_allocation_count as usize <= count {
-            return Err(OutOfMemory);
-        }
+    /// Returns sampler from the cache or creates new one.
+    ///
+    /// If `exempt` is true, sampler is created regardless of the sampler count limit.
+    fn cached_sampler(&self, desc: SamplerDesc, exempt: bool) -> Result<Sampler, OutOfMemory> {
+        let mut samplers = self.inner.samplers.lock();
+        let len = if exempt { 0 } else { samplers.len() };
+        match samplers.entry(desc) {
+            hashbrown::hash_map::Entry::Occupied(mut entry) => match entry.get().upgrade() {
+                Some(sampler) => Ok(sampler),
+                None => {
+                    let sampler = self.new_sampler_slow(len, desc)?;
+                    entry.insert(sampler.downgrade());
+                    Ok(sampler)
+                }
+            },
+            hashbrown::hash_map::Entry::Vacant(entry) => {
+                let sampler = self.new_sampler_slow(len, desc)?;
+                entry.insert(sampler.downgrade());
+                Ok(sampler)
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn new_sampler_slow(&self, count: usize, desc: SamplerDesc) -> Result<Sampler, OutOfMemory> {
+        if self.inner.properties.limits.max_sampler_allocation_count as usize <= count {
+            return Err(OutOfMemory);
+        }
+
+        let anisotropy = sampler_anisotropy(
+            desc.anisotropy,
+            self.inner.features,
+            self.inner.properties.limits.max_sampler_anisotropy,
+            self.inner.quirks,
+        );
 
         let result = unsafe {
             self.ash().create_sampler(
@@ -689,8 +1577,8 @@ impl Device {
                     .address_mode_u(desc.address_mode[0].into_ash())
                     .address_mode_v(desc.address_mode[1].into_ash())
                     .address_mode_w(desc.address_mode[2].into_ash())
-                    .anisotropy_enable(desc.anisotropy.is_some())
-                    .max_anisotropy(desc.anisotropy.unwrap_or(0.0))
+                    .anisotropy_enable(anisotropy.is_some())
+                    .max_anisotropy(anisotropy.unwrap_or(1.0))
                     .unnormalized_coordinates(!desc.normalized),
                 None,
             )
@@ -702,72 +1590,178 @@ impl Device {
             _ => unexpected_error(err),
         })?;
 
-        Ok(Sampler::new(self.weak(), handle, desc))
+        Ok(Sampler::new(
+            self.weak(),
+            self.new_resource_id(),
+            handle,
+            desc,
+        ))
     }
 
     fn new_set_layout_slow(
         &self,
-        desc: DescriptorSetLayoutDesc,
-    ) -> Result<DescriptorSetLayout, OutOfMemory> {
+        desc: &DescriptorSetLayoutDesc,
+    ) -> Result<ash::vk::DescriptorSetLayout, OutOfMemory> {
+        // Well-known samplers live as long as the device,
+        // so layout doesn't need to keep them alive.
+        let immutable_samplers = desc
+            .arguments
+            .iter()
+            .map(|arg| match arg.immutable_sampler {
+                None => Ok(None),
+                Some(kind) => {
+                    debug_assert_eq!(arg.kind, ArgumentKind::Sampler);
+                    debug_assert_eq!(arg.size, 1);
+                    Ok(Some(self.well_known_sampler(kind)?.handle()))
+                }
+            })
+            .collect::<Result<Vec<_>, OutOfMemory>>()?;
+
+        // Bindless table is a persistent set updated after bind.
+        // Its binding is the same for every group,
+        // so pipelines are compatible with the set of any table.
+        let bindless = desc
+            .arguments
+            .iter()
+            .any(|arg| arg.kind == ArgumentKind::BindlessTable);
+
+        if bindless {
+            assert_eq!(
+                desc.arguments.len(),
+                1,
+                "Argument group with bindless table may not contain other arguments"
+            );
+            assert!(
+                self.inner.max_bindless_table_size > 0,
+                "Bindless tables require `Features::DESCRIPTOR_INDEXING`"
+            );
+
+            let binding = ash::vk::DescriptorSetLayoutBinding::default()
+                .binding(0)
+                .descriptor_count(self.inner.max_bindless_table_size)
+                .descriptor_type(ash::vk::DescriptorType::SAMPLED_IMAGE)
+                .stage_flags(ShaderStages::all().into_ash());
+
+            let binding_flags = [ash::vk::DescriptorBindingFlags::PARTIALLY_BOUND
+                | ash::vk::DescriptorBindingFlags::UPDATE_AFTER_BIND
+                | ash::vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING
+                | ash::vk::DescriptorBindingFlags::VARIABLE_DESCRIPTOR_COUNT];
+
+            let mut flags_info = ash::vk::DescriptorSetLayoutBindingFlagsCreateInfo::default()
+                .binding_flags(&binding_flags);
+
+            let result = unsafe {
+                self.ash().create_descriptor_set_layout(
+                    &ash::vk::DescriptorSetLayoutCreateInfo::default()
+                        .flags(ash::vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL)
+                        .bindings(std::slice::from_ref(&binding))
+                        .push_next(&mut flags_info),
+                    None,
+                )
+            };
+
+            return result.map_err(|err| match err {
+                ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+                ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
+                _ => unexpected_error(err),
+            });
+        }
+
         let bindings = desc
             .arguments
             .iter()
+            .zip(&immutable_samplers)
             .enumerate()
-            .map(|(idx, arg)| {
-                ash::vk::DescriptorSetLayoutBinding::default()
-                    .binding(u32::try_from(idx).expect("Too many descriptor bindings"))
-                    .descriptor_count(
-                        u32::try_from(arg.size).expect("Too many descriptors in array"),
-                    )
-                    .descriptor_type(descriptor_type(arg.kind))
-                    .stage_flags(arg.stages.into_ash())
+            .map(|(idx, (arg, immutable_sampler))| {
+                let binding = set_layout_binding(idx, arg);
+
+                match immutable_sampler {
+                    None => binding,
+                    Some(sampler) => binding.immutable_samplers(std::slice::from_ref(sampler)),
+                }
             })
             .collect::<Vec<_>>();
 
+        let flags = if self.inner.push_descriptor.is_some() {
+            ash::vk::DescriptorSetLayoutCreateFlags::PUSH_DESCRIPTOR_KHR
+        } else {
+            ash::vk::DescriptorSetLayoutCreateFlags::empty()
+        };
+
         let result = unsafe {
             self.ash().create_descriptor_set_layout(
                 &ash::vk::DescriptorSetLayoutCreateInfo::default()
-                    .flags(ash::vk::DescriptorSetLayoutCreateFlags::PUSH_DESCRIPTOR_KHR)
+                    .flags(flags)
                     .bindings(&bindings),
                 None,
             )
         };
 
-        let handle = result.map_err(|err| match err {
+        result.map_err(|err| match err {
             ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
             ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
             _ => unexpected_error(err),
-        })?;
-        Ok(DescriptorSetLayout::new(self.weak(), handle, desc))
+        })
     }
 
+    /// Returns set layout from the cache or creates new one.
+    ///
+    /// Cache is not locked while the layout is created,
+    /// so layouts can be created from multiple threads in parallel.
     fn new_set_layout(
         &self,
         desc: DescriptorSetLayoutDesc,
     ) -> Result<DescriptorSetLayout, OutOfMemory> {
+        let cached = self
+            .inner
+            .set_layouts
+            .lock()
+            .get(&desc)
+            .and_then(|weak| weak.upgrade());
+
+        if let Some(set_layout) = cached {
+            return Ok(set_layout);
+        }
+
+        let handle = self.new_set_layout_slow(&desc)?;
+
         let mut set_layouts = self.inner.set_layouts.lock();
 
-        match set_layouts.entry(desc) {
-            hashbrown::hash_map::Entry::Occupied(entry) => match entry.get().upgrade() {
-                Some(set_layout) => Ok(set_layout.clone()),
-                None => {
-                    let set_layout = self.new_set_layout_slow(entry.key().clone())?;
-                    entry.replace_entry(set_layout.downgrade());
-                    Ok(set_layout)
-                }
-            },
-            hashbrown::hash_map::Entry::Vacant(entry) => {
-                let set_layout = self.new_set_layout_slow(entry.key().clone())?;
-                entry.insert(set_layout.downgrade());
-                Ok(set_layout)
+        if let Some(set_layout) = set_layouts.get(&desc).and_then(|weak| weak.upgrade()) {
+            // Same layout was created by another thread in the meantime.
+            drop(set_layouts);
+            unsafe {
+                self.ash().destroy_descriptor_set_layout(handle, None);
             }
+            return Ok(set_layout);
+        }
+
+        let set_layout = DescriptorSetLayout::new(self.weak(), handle, desc.clone());
+        set_layouts.insert(desc, set_layout.downgrade());
+        Ok(set_layout)
+    }
+
+    /// Checks that pipeline doesn't use more argument groups than device can bind.
+    fn check_argument_groups(
+        &self,
+        arguments: &[ArgumentGroupLayout],
+    ) -> Result<(), CreatePipelineError> {
+        let max = self.inner.properties.limits.max_bound_descriptor_sets;
+        if arguments.len() > max as usize {
+            return Err(CreatePipelineError(
+                CreatePipelineErrorKind::TooManyArgumentGroups {
+                    requested: arguments.len() as u32,
+                    max,
+                },
+            ));
         }
+        Ok(())
     }
 
     fn new_pipeline_layout_slow(
         &self,
-        desc: PipelineLayoutDesc,
-    ) -> Result<PipelineLayout, OutOfMemory> {
+        desc: &PipelineLayoutDesc,
+    ) -> Result<(ash::vk::PipelineLayout, Vec<DescriptorSetLayout>), OutOfMemory> {
         let set_layouts = desc
             .groups
             .iter()
@@ -789,7 +1783,7 @@ impl Device {
 
         if desc.constants > 0 {
             push_constant_ranges = ash::vk::PushConstantRange::default()
-                .stage_flags(ash::vk::ShaderStageFlags::ALL)
+                .stage_flags(desc.constants_stages)
                 .size((desc.constants as u32 + 3) & !3);
 
             info = info.push_constant_ranges(std::slice::from_ref(&push_constant_ranges));
@@ -801,27 +1795,41 @@ impl Device {
             ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
             _ => unexpected_error(err),
         })?;
-        Ok(PipelineLayout::new(self.weak(), handle, desc, set_layouts))
+        Ok((handle, set_layouts))
     }
 
+    /// Returns pipeline layout from the cache or creates new one.
+    ///
+    /// Cache is not locked while the layout is created,
+    /// so layouts can be created from multiple threads in parallel.
     fn new_pipeline_layout(&self, desc: PipelineLayoutDesc) -> Result<PipelineLayout, OutOfMemory> {
+        let cached = self
+            .inner
+            .pipeline_layouts
+            .lock()
+            .get(&desc)
+            .and_then(|weak| weak.upgrade());
+
+        if let Some(pipeline_layout) = cached {
+            return Ok(pipeline_layout);
+        }
+
+        let (handle, set_layouts) = self.new_pipeline_layout_slow(&desc)?;
+
         let mut pipeline_layouts = self.inner.pipeline_layouts.lock();
 
-        match pipeline_layouts.entry(desc) {
-            hashbrown::hash_map::Entry::Occupied(entry) => match entry.get().upgrade() {
-                Some(pipeline_layout) => Ok(pipeline_layout.clone()),
-                None => {
-                    let pipeline_layout = self.new_pipeline_layout_slow(entry.key().clone())?;
-                    entry.replace_entry(pipeline_layout.downgrade());
-                    Ok(pipeline_layout)
-                }
-            },
-            hashbrown::hash_map::Entry::Vacant(entry) => {
-                let pipeline_layout = self.new_pipeline_layout_slow(entry.key().clone())?;
-                entry.insert(pipeline_layout.downgrade());
-                Ok(pipeline_layout)
+        if let Some(pipeline_layout) = pipeline_layouts.get(&desc).and_then(|weak| weak.upgrade()) {
+            // Same layout was created by another thread in the meantime.
+            drop(pipeline_layouts);
+            unsafe {
+                self.ash().destroy_pipeline_layout(handle, None);
             }
+            return Ok(pipeline_layout);
         }
+
+        let pipeline_layout = PipelineLayout::new(self.weak(), handle, desc.clone(), set_layouts);
+        pipeline_layouts.insert(desc, pipeline_layout.downgrade());
+        Ok(pipeline_layout)
     }
 
     #[doc(hidden)]
@@ -839,20 +1847,21 @@ impl Device {
         {
             hashbrown::hash_map::Entry::Occupied(entry) => Ok(*entry.get()),
             hashbrown::hash_map::Entry::Vacant(entry) => {
-                let result = unsafe {
-                    self.ash().create_descriptor_update_template(
-                        &ash::vk::DescriptorUpdateTemplateCreateInfo::default()
-                            .template_type(
-                                ash::vk::DescriptorUpdateTemplateType::PUSH_DESCRIPTORS_KHR,
-                            )
-                            .pipeline_bind_point(bind)
-                            .pipeline_layout(layout.handle())
-                            .descriptor_update_entries(entries)
-                            .set(set),
-                        None,
-                    )
+                let info = ash::vk::DescriptorUpdateTemplateCreateInfo::default()
+                    .pipeline_bind_point(bind)
+                    .pipeline_layout(layout.handle())
+                    .descriptor_update_entries(entries)
+                    .set(set);
+
+                let info = if self.inner.push_descriptor.is_some() {
+                    info.template_type(ash::vk::DescriptorUpdateTemplateType::PUSH_DESCRIPTORS_KHR)
+                } else {
+                    info.template_type(ash::vk::DescriptorUpdateTemplateType::DESCRIPTOR_SET)
+                        .descriptor_set_layout(layout.set_layout(set as usize).handle())
                 };
 
+                let result = unsafe { self.ash().create_descriptor_update_template(&info, None) };
+
                 let template = result.map_err(|err| match err {
                     ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
                     ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
@@ -873,6 +1882,14 @@ impl Device {
         view_type: vk::ImageViewType,
         desc: ViewDesc,
     ) -> Result<(ash::vk::ImageView, usize), OutOfMemory> {
+        // Non-array views can't have more than one layer.
+        // Layered rendering requires array views of all layers.
+        let view_type = match view_type {
+            vk::ImageViewType::TYPE_1D if desc.layers > 1 => vk::ImageViewType::TYPE_1D_ARRAY,
+            vk::ImageViewType::TYPE_2D if desc.layers > 1 => vk::ImageViewType::TYPE_2D_ARRAY,
+            view_type => view_type,
+        };
+
         let result = unsafe {
             self.inner.device.create_image_view(
                 &vk::ImageViewCreateInfo::default()
@@ -881,7 +1898,7 @@ impl Device {
                     .format(desc.format.try_into_ash().unwrap())
                     .subresource_range(
                         vk::ImageSubresourceRange::default()
-                            .aspect_mask(format_aspect(desc.format))
+                            .aspect_mask(view_aspect(&desc))
                             .base_mip_level(desc.base_level)
                             .level_count(desc.levels)
                             .base_array_layer(desc.base_layer)
@@ -916,14 +1933,14 @@ impl Device {
         })
     }
 
-    pub(super) fn get_fence_status(&self, fence: vk::Fence) -> Result<bool, OutOfMemory> {
-        match unsafe { self.ash().get_fence_status(fence) } {
-            Ok(true) => Ok(true),
-            Ok(false) => Ok(false),
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(handle_host_oom()),
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory),
-            Err(err) => Err(unexpected_error(err)),
-        }
+    pub(super) fn wait_fences(&self, fences: &[vk::Fence]) -> Result<(), DeviceError> {
+        let result = unsafe { self.ash().wait_for_fences(fences, true, u64::MAX) };
+        result.map_err(map_device_error)
+    }
+
+    pub(super) fn get_fence_status(&self, fence: vk::Fence) -> Result<bool, DeviceError> {
+        let result = unsafe { self.ash().get_fence_status(fence) };
+        result.map_err(map_device_error)
     }
 
     pub(super) fn reset_fences(&self, fences: &[vk::Fence]) -> Result<(), OutOfMemory> {
@@ -935,72 +1952,153 @@ impl Device {
         }
     }
 
-    /// Wait for all operations on the device to complete.
-    pub(super) fn wait_idle(&self) -> Result<(), OutOfMemory> {
-        let result = unsafe { self.inner.device.device_wait_idle() };
+    /// Returns binary semaphore from the pool or creates new one.
+    pub(super) fn new_semaphore(&self) -> Result<vk::Semaphore, OutOfMemory> {
+        if let Some(semaphore) = self.inner.semaphores.lock().pop() {
+            return Ok(semaphore);
+        }
 
-        let result = result.map_err(|err| match err {
-            ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-            ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
-            ash::vk::Result::ERROR_DEVICE_LOST => unimplemented!("Device lost"),
+        let result = unsafe {
+            self.ash()
+                .create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
+        };
+
+        result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
             _ => unexpected_error(err),
-        });
+        })
+    }
+
+    /// Creates a new timeline semaphore with zero initial value.
+    ///
+    /// Timeline semaphores must be enabled.
+    pub(super) fn new_timeline_semaphore(&self) -> Result<vk::Semaphore, OutOfMemory> {
+        debug_assert!(self.inner.timeline_semaphore);
+
+        let mut type_info = vk::SemaphoreTypeCreateInfo::default()
+            .semaphore_type(vk::SemaphoreType::TIMELINE)
+            .initial_value(0);
+
+        let result = unsafe {
+            self.ash().create_semaphore(
+                &vk::SemaphoreCreateInfo::default().push_next(&mut type_info),
+                None,
+            )
+        };
+
+        result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
+            _ => unexpected_error(err),
+        })
+    }
+
+    /// Returns semaphores to the pool.
+    ///
+    /// Semaphores must be unsignaled and have no pending operations.
+    pub(super) fn recycle_semaphores(&self, semaphores: impl IntoIterator<Item = vk::Semaphore>) {
+        self.inner.semaphores.lock().extend(semaphores);
+    }
 
-        result
+    /// Wait for all operations on the device to complete.
+    pub(super) fn wait_idle(&self) -> Result<(), DeviceError> {
+        let result = unsafe { self.inner.device.device_wait_idle() };
+        result.map_err(map_device_error)
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::Device for Device {
+    type Library = Library;
+    type ComputePipeline = ComputePipeline;
+    type RenderPipeline = RenderPipeline;
+    type Buffer = Buffer;
+    type Image = Image;
+    type Sampler = Sampler;
+    type Surface = Surface;
+    type Blas = Blas;
+    type Tlas = Tlas;
+    type BindlessTable = BindlessTable;
+
     fn new_shader_library(&self, desc: LibraryDesc) -> Result<Library, CreateLibraryError> {
         let me = &*self.inner;
-        match desc.input {
-            LibraryInput::Source(source) => {
-                let compiled: Box<[u32]>;
-                let code = match source.language {
-                    ShaderLanguage::SpirV => unsafe {
-                        let (left, words, right) = source.code.align_to::<u32>();
-
-                        if left.is_empty() && right.is_empty() {
-                            words
-                        } else {
-                            let mut code = &*source.code;
-                            let mut words = Vec::with_capacity(code.len() / 4);
-
-                            while let [a, b, c, d, tail @ ..] = code {
-                                words.push(u32::from_ne_bytes([*a, *b, *c, *d]));
-                                code = tail;
-                            }
-
-                            compiled = words.into();
-                            &*compiled
+        let compiled: Box<[u32]>;
+        let mut entries = None;
+        let code = match &desc.input {
+            LibraryInput::Source(source) => match source.language {
+                #[cfg(not(any(feature = "shader-spirv", feature = "shader-spirv-passthrough")))]
+                ShaderLanguage::SpirV => {
+                    return Err(ShaderCompileError::ShaderLanguageNotEnabled(
+                        ShaderLanguage::SpirV,
+                    )
+                    .into());
+                }
+                #[cfg(any(feature = "shader-spirv", feature = "shader-spirv-passthrough"))]
+                ShaderLanguage::SpirV => unsafe {
+                    validate_spirv_header(&source.code)?;
+                    entries = reflect_spirv(&source.code);
+
+                    let (left, words, right) = source.code.align_to::<u32>();
+
+                    if left.is_empty() && right.is_empty() {
+                        words
+                    } else {
+                        let mut code = &*source.code;
+                        let mut words = Vec::with_capacity(code.len() / 4);
+
+                        while let [a, b, c, d, tail @ ..] = code {
+                            words.push(u32::from_ne_bytes([*a, *b, *c, *d]));
+                            code = tail;
                         }
-                    },
-                    _ => {
-                        compiled = compile_shader(&source.code, source.filename, source.language)?;
+
+                        compiled = words.into();
                         &*compiled
                     }
-                };
-                let result = unsafe {
-                    me.device.create_shader_module(
-                        &vk::ShaderModuleCreateInfo::default().code(code),
-                        None,
-                    )
-                };
-                let module = result.map_err(|err| match err {
-                    vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-                    vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => CreateLibraryError::OutOfMemory,
-                    _ => unexpected_error(err),
-                })?;
+                },
+                _ => {
+                    let (words, reflected) = compile_shader(
+                        &source.code,
+                        &SourceFile::single(source.filename),
+                        source.language,
+                        me.bounds_check_policies,
+                        shader_capabilities(SPIRV_CAPABILITIES, me.features),
+                    )?;
+                    compiled = words;
+                    entries = Some(reflected);
+                    &*compiled
+                }
+            },
+            LibraryInput::Sources(sources) => {
+                let composed = compose_wgsl(sources)?;
+                let (words, reflected) = compile_shader(
+                    composed.code.as_bytes(),
+                    &composed.files,
+                    ShaderLanguage::Wgsl,
+                    me.bounds_check_policies,
+                    shader_capabilities(SPIRV_CAPABILITIES, me.features),
+                )?;
+                compiled = words;
+                entries = Some(reflected);
+                &*compiled
+            }
+        };
+        let result = unsafe {
+            me.device
+                .create_shader_module(&vk::ShaderModuleCreateInfo::default().code(code), None)
+        };
+        let module = result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => CreateLibraryError::OutOfMemory,
+            _ => unexpected_error(err),
+        })?;
 
-                let idx = self.inner.libraries.lock().insert(module);
+        let idx = self.inner.libraries.lock().insert(module);
 
-                #[cfg(any(debug_assertions, feature = "debug"))]
-                self.set_object_name(module, desc.name);
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        self.set_object_name(module, desc.name);
 
-                Ok(Library::new(self.weak(), module, idx))
-            }
-        }
+        Ok(Library::new(self.weak(), module, idx, entries))
     }
 
     /// Create a new render pipeline.
@@ -1008,6 +2106,17 @@ impl crate::traits::Device for Device {
         &self,
         desc: ComputePipelineDesc,
     ) -> Result<ComputePipeline, CreatePipelineError> {
+        trace_timed_span!("mev.new_compute_pipeline", name = desc.name);
+
+        check_entry_stage(&desc.shader, ShaderStage::Compute)?;
+        self.check_argument_groups(desc.arguments)?;
+        check_shader_argument_stages(&desc.shader, desc.arguments)?;
+
+        let limits = self.limits();
+        check_shader_buffer_ranges(&desc.shader, &limits)?;
+
+        let summary = PipelineSummary::compute(&desc);
+
         let layout_desc = PipelineLayoutDesc {
             groups: desc
                 .arguments
@@ -1015,6 +2124,7 @@ impl crate::traits::Device for Device {
                 .map(|group| group.arguments.to_vec())
                 .collect(),
             constants: desc.constants,
+            constants_stages: constants_stages(vk::PipelineBindPoint::COMPUTE, false),
         };
 
         let layout = self
@@ -1057,10 +2167,12 @@ impl crate::traits::Device for Device {
 
         Ok(ComputePipeline::new(
             self.weak(),
+            self.new_resource_id(),
             pipeline,
             idx,
             layout,
             desc.shader.library,
+            summary,
         ))
     }
 
@@ -1068,6 +2180,25 @@ impl crate::traits::Device for Device {
         &self,
         desc: RenderPipelineDesc,
     ) -> Result<RenderPipeline, CreatePipelineError> {
+        trace_timed_span!("mev.new_render_pipeline", name = desc.name);
+
+        check_entry_stage(&desc.vertex_shader, ShaderStage::Vertex)?;
+        self.check_argument_groups(desc.arguments)?;
+        check_shader_argument_stages(&desc.vertex_shader, desc.arguments)?;
+
+        let limits = self.limits();
+        check_shader_buffer_ranges(&desc.vertex_shader, &limits)?;
+        if let Some(raster) = &desc.raster {
+            if let Some(fragment_shader) = &raster.fragment_shader {
+                check_entry_stage(fragment_shader, ShaderStage::Fragment)?;
+                check_shader_argument_stages(fragment_shader, desc.arguments)?;
+                check_shader_buffer_ranges(fragment_shader, &limits)?;
+                check_stage_interfaces(&desc.vertex_shader, fragment_shader)?;
+            }
+        }
+
+        let summary = PipelineSummary::render(&desc);
+
         let layout_desc = PipelineLayoutDesc {
             groups: desc
                 .arguments
@@ -1075,6 +2206,16 @@ impl crate::traits::Device for Device {
                 .map(|group| group.arguments.to_vec())
                 .collect(),
             constants: desc.constants,
+            constants_stages: constants_stages(
+                vk::PipelineBindPoint::GRAPHICS,
+                matches!(
+                    desc.raster,
+                    Some(RasterDesc {
+                        fragment_shader: Some(_),
+                        ..
+                    })
+                ),
+            ),
         };
 
         let layout = self
@@ -1093,27 +2234,98 @@ impl crate::traits::Device for Device {
             })
             .collect::<Vec<_>>();
 
-        let vertex_bindings = desc
-            .vertex_layouts
-            .iter()
-            .enumerate()
-            .map(|(idx, attr)| vk::VertexInputBindingDescription {
-                binding: idx as u32,
-                stride: attr.stride,
-                input_rate: match attr.step_mode {
-                    VertexStepMode::Vertex => vk::VertexInputRate::VERTEX,
-                    VertexStepMode::Instance { rate: 1 } => vk::VertexInputRate::INSTANCE,
-                    VertexStepMode::Instance { rate } => {
-                        panic!(
-                            "Instance vertex step mode with rate {rate} is not supported on Vulkan"
-                        )
-                    }
-                    VertexStepMode::Constant => {
-                        panic!("Constant vertex step mode is not supported on Vulkan")
-                    }
-                },
-            })
-            .collect::<Vec<_>>();
+        let max_stride = self.inner.properties.limits.max_vertex_input_binding_stride;
+        let vertex_divisor = self.inner.vertex_divisor;
+
+        let mut vertex_bindings = Vec::with_capacity(desc.vertex_layouts.len());
+        let mut vertex_divisors = Vec::new();
+
+        for (idx, layout) in desc.vertex_layouts.iter().enumerate() {
+            let binding = idx as u32;
+
+            if layout.stride > max_stride {
+                return Err(CreatePipelineError(
+                    CreatePipelineErrorKind::VertexStrideTooLarge {
+                        buffer: binding,
+                        stride: layout.stride,
+                        limit: max_stride,
+                    },
+                ));
+            }
+
+            let input_rate = match layout.step_mode {
+                VertexStepMode::Vertex => vk::VertexInputRate::VERTEX,
+                VertexStepMode::Instance { rate: 1 } => vk::VertexInputRate::INSTANCE,
+                VertexStepMode::Instance { rate } if rate != 0 && vertex_divisor.rate => {
+                    vertex_divisors.push(vk::VertexInputBindingDivisorDescriptionKHR {
+                        binding,
+                        divisor: rate,
+                    });
+                    vk::VertexInputRate::INSTANCE
+                }
+                VertexStepMode::Constant if vertex_divisor.zero => {
+                    // Instance rate with divisor 0 reads the same element for all instances.
+                    vertex_divisors.push(vk::VertexInputBindingDivisorDescriptionKHR {
+                        binding,
+                        divisor: 0,
+                    });
+                    vk::VertexInputRate::INSTANCE
+                }
+                step_mode => {
+                    return Err(CreatePipelineError(
+                        CreatePipelineErrorKind::UnsupportedVertexStepMode {
+                            buffer: binding,
+                            step_mode,
+                        },
+                    ));
+                }
+            };
+
+            let portability = &self.inner.portability;
+            if layout.stride % portability.min_vertex_input_binding_stride_alignment != 0 {
+                return Err(CreatePipelineError(
+                    CreatePipelineErrorKind::VertexStrideMisaligned {
+                        buffer: binding,
+                        stride: layout.stride,
+                        alignment: portability.min_vertex_input_binding_stride_alignment,
+                    },
+                ));
+            }
+
+            if !portability.vertex_attribute_access_beyond_stride {
+                let beyond = desc.vertex_attributes.iter().enumerate().find(|(_, attr)| {
+                    attr.buffer_index == binding
+                        && attr.offset as usize + attr.format.size() > layout.stride as usize
+                });
+
+                if let Some((attribute, _)) = beyond {
+                    return Err(CreatePipelineError(
+                        CreatePipelineErrorKind::VertexAttributeBeyondStride {
+                            attribute: attribute as u32,
+                            buffer: binding,
+                            stride: layout.stride,
+                        },
+                    ));
+                }
+            }
+
+            vertex_bindings.push(vk::VertexInputBindingDescription {
+                binding,
+                stride: layout.stride,
+                input_rate,
+            });
+        }
+
+        let mut vertex_divisor_state = vk::PipelineVertexInputDivisorStateCreateInfoKHR::default()
+            .vertex_binding_divisors(&vertex_divisors);
+
+        let mut vertex_input_state = vk::PipelineVertexInputStateCreateInfo::default()
+            .vertex_attribute_descriptions(&vertex_attributes)
+            .vertex_binding_descriptions(&vertex_bindings);
+
+        if !vertex_divisors.is_empty() {
+            vertex_input_state = vertex_input_state.push_next(&mut vertex_divisor_state);
+        }
 
         let vertex_shader_name;
         let fragment_shader_name;
@@ -1135,6 +2347,38 @@ impl crate::traits::Device for Device {
         let vertex_library = desc.vertex_shader.library;
         let mut fragment_library = None;
 
+        if let Some(raster) = &desc.raster {
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            for (idx, color) in raster.color_targets.iter().enumerate() {
+                if let Some(blend) = &color.blend {
+                    blend.warn_ignored_factors(idx);
+                }
+            }
+
+            check_dual_source_blend(
+                raster,
+                self.inner.features.contains(Features::DUAL_SOURCE_BLEND),
+                |shader| shader.library.writes_second_blend_source(&shader.entry),
+            )
+            .map_err(|err| CreatePipelineError(CreatePipelineErrorKind::DualSourceBlend(err)))?;
+        }
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        let color_formats = desc.raster.as_ref().map_or(Vec::new(), |raster| {
+            raster
+                .color_targets
+                .iter()
+                .map(|color| color.format)
+                .collect()
+        });
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        let depth_format = desc
+            .raster
+            .as_ref()
+            .and_then(|raster| raster.depth_stencil.as_ref())
+            .map(|depth| depth.format);
+
         if let Some(raster) = desc.raster {
             if let Some(fragment_shader) = raster.fragment_shader {
                 stages.push(
@@ -1151,13 +2395,19 @@ impl crate::traits::Device for Device {
                 fragment_library = Some(fragment_shader.library);
             }
 
+            let line_width = effective_line_width(
+                raster.line_width,
+                self.inner.features.contains(Features::WIDE_LINES),
+                self.inner.properties.limits.line_width_range,
+            );
+
             raster_state = raster_state
                 .depth_clamp_enable(false)
                 .rasterizer_discard_enable(false)
                 .polygon_mode(vk::PolygonMode::FILL)
                 .cull_mode(raster.culling.into_ash())
                 .front_face(raster.front_face.into_ash())
-                .line_width(1.0);
+                .line_width(line_width);
 
             if let Some(depth) = &raster.depth_stencil {
                 depth_state = depth_state
@@ -1205,11 +2455,7 @@ impl crate::traits::Device for Device {
                 std::slice::from_ref(
                     &create_info
                         .stages(&stages)
-                        .vertex_input_state(
-                            &vk::PipelineVertexInputStateCreateInfo::default()
-                                .vertex_attribute_descriptions(&vertex_attributes)
-                                .vertex_binding_descriptions(&vertex_bindings),
-                        )
+                        .vertex_input_state(&vertex_input_state)
                         .input_assembly_state(
                             &vk::PipelineInputAssemblyStateCreateInfo::default().topology(
                                 match desc.primitive_topology {
@@ -1276,57 +2522,113 @@ impl crate::traits::Device for Device {
 
         Ok(RenderPipeline::new(
             self.weak(),
+            self.new_resource_id(),
             pipeline,
             idx,
             layout,
             vertex_library,
             fragment_library,
+            summary,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            color_formats,
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            depth_format,
         ))
     }
 
-    fn new_buffer(&self, desc: BufferDesc) -> Result<Buffer, OutOfMemory> {
-        let size = u64::try_from(desc.size).map_err(|_| OutOfMemory)?;
+    fn new_compute_pipelines_parallel(
+        &self,
+        descs: Vec<ComputePipelineDesc>,
+    ) -> Vec<Result<ComputePipeline, CreatePipelineError>> {
+        parallel_map(descs, |desc| self.new_compute_pipeline(desc))
+    }
+
+    fn new_render_pipelines_parallel(
+        &self,
+        descs: Vec<RenderPipelineDesc>,
+    ) -> Vec<Result<RenderPipeline, CreatePipelineError>> {
+        parallel_map(descs, |desc| self.new_render_pipeline(desc))
+    }
+
+    fn new_render_pipeline_deferred(
+        &self,
+        desc: RenderPipelineDesc,
+    ) -> PipelinePending<RenderPipeline> {
+        let desc = desc.into_owned();
+        let device = self.clone();
+        self.inner
+            .deferred
+            .spawn(move || desc.with_desc(|desc| device.new_render_pipeline(desc)))
+    }
+
+    fn new_buffer(&self, desc: BufferDesc) -> Result<Buffer, AllocError> {
+        trace_span!("mev.new_buffer", name = desc.name, size = desc.size);
+
+        let alloc_error =
+            |kind| AllocError::new(kind, desc.name, AllocResource::Buffer { size: desc.size });
+
+        if desc.size == 0 {
+            return Err(alloc_error(AllocErrorKind::ZeroSizedBuffer));
+        }
+
+        let max = self.max_buffer_size(desc.usage);
+        if desc.size > max {
+            return Err(alloc_error(AllocErrorKind::BufferTooLarge {
+                size: desc.size,
+                max,
+            }));
+        }
 
         let buffer = unsafe {
-            self.inner.device.create_buffer(
-                &vk::BufferCreateInfo::default()
-                    .size(size)
-                    .sharing_mode(vk::SharingMode::EXCLUSIVE)
-                    .usage(desc.usage.into_ash()),
-                None,
-            )
+            self.inner
+                .device
+                .create_buffer(&self.buffer_create_info(&desc), None)
         }
         .map_err(|err| match err {
             vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                alloc_error(AllocErrorKind::OutOfDeviceMemory)
+            }
             err => unexpected_error(err),
         })?;
 
-        let requirements = unsafe { self.inner.device.get_buffer_memory_requirements(buffer) };
-        let align_mask = requirements.alignment - 1;
+        let (requirements, dedicated) = unsafe { self.buffer_memory_requirements(buffer) };
+        let usage = memory_to_usage_flags(desc.memory);
 
-        let block = unsafe {
-            self.inner.allocator.lock().alloc(
-                &*self.inner,
-                gpu_alloc::Request {
-                    size: requirements.size,
-                    align_mask,
-                    usage: memory_to_usage_flags(desc.memory),
-                    memory_types: requirements.memory_type_bits,
-                },
-            )
-        }
-        .map_err(|err| match err {
-            gpu_alloc::AllocationError::OutOfDeviceMemory => OutOfMemory,
-            gpu_alloc::AllocationError::OutOfHostMemory => handle_host_oom(),
-            gpu_alloc::AllocationError::NoCompatibleMemoryTypes => OutOfMemory,
-            gpu_alloc::AllocationError::TooManyObjects => OutOfMemory,
+        let allocated = if dedicated.is_none()
+            && self.inner.linear.lock().accepts(usage, requirements.size)
+        {
+            let mut linear = self.inner.linear.lock();
+            unsafe {
+                linear.alloc(
+                    &self.inner,
+                    &mut self.inner.allocator.lock(),
+                    usage,
+                    requirements,
+                )
+            }
+            .map(|block| {
+                let (memory, offset) = linear.memory(&block);
+                (BufferMemory::Linear(block), memory, offset)
+            })
+        } else {
+            unsafe { self.alloc_block(desc.name, usage, requirements, dedicated) }.map(|block| {
+                let (memory, offset) = (block.memory().0, block.offset());
+                (BufferMemory::Block(block), memory, offset)
+            })
+        };
+
+        let (memory, raw_memory, offset) = allocated.map_err(|err| {
+            unsafe {
+                self.inner.device.destroy_buffer(buffer, None);
+            }
+            alloc_error(alloc_error_kind(err))
         })?;
 
         let result = unsafe {
             self.inner
                 .device
-                .bind_buffer_memory(buffer, block.memory().0, block.offset())
+                .bind_buffer_memory(buffer, raw_memory, offset)
         };
 
         match result {
@@ -1336,33 +2638,55 @@ impl crate::traits::Device for Device {
 
                 let idx = self.inner.buffers.lock().insert(buffer);
 
-                let buffer = Buffer::new(self.weak(), buffer, desc.size, desc.usage, block, idx);
+                let buffer = Buffer::new(
+                    self.weak(),
+                    self.new_resource_id(),
+                    desc.name,
+                    buffer,
+                    desc.size,
+                    desc.usage,
+                    desc.shared_across_queues,
+                    desc.memory,
+                    memory,
+                    idx,
+                );
                 Ok(buffer)
             }
             Err(err) => {
                 unsafe {
-                    self.inner.allocator.lock().dealloc(&*self.inner, block);
+                    match memory {
+                        BufferMemory::Block(block) => {
+                            self.inner.allocator.lock().dealloc(&*self.inner, block)
+                        }
+                        BufferMemory::Linear(block) => {
+                            let mut linear = self.inner.linear.lock();
+                            linear.dealloc(&self.inner, &mut self.inner.allocator.lock(), block)
+                        }
+                    }
 
                     self.ash().destroy_buffer(buffer, None);
                 }
 
                 match err {
                     vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-                    vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => Err(OutOfMemory),
+                    vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                        Err(alloc_error(AllocErrorKind::OutOfDeviceMemory))
+                    }
                     _ => unexpected_error(err),
                 }
             }
         }
     }
 
-    fn new_buffer_init(&self, desc: BufferInitDesc<'_>) -> Result<Buffer, OutOfMemory> {
+    fn new_buffer_init(&self, desc: BufferInitDesc<'_>) -> Result<Buffer, AllocError> {
         assert!(!matches!(desc.memory, Memory::Device));
 
         let mut buffer = self.new_buffer(BufferDesc {
-            size: desc.data.len(),
+            size: desc.data.len() as u64,
             usage: desc.usage,
             memory: desc.memory,
             name: desc.name,
+            shared_across_queues: false,
         })?;
 
         // Safety: Buffer is not user anywhere
@@ -1373,55 +2697,63 @@ impl crate::traits::Device for Device {
         Ok(buffer)
     }
 
-    fn new_image(&self, desc: ImageDesc) -> Result<Image, OutOfMemory> {
-        let image = unsafe {
-            self.inner.device.create_image(
-                &vk::ImageCreateInfo::default()
-                    .image_type(desc.extent.into_ash())
-                    .format(desc.format.try_into_ash().expect("Unsupported format"))
-                    .extent(desc.extent.into_ash())
-                    .array_layers(desc.layers)
-                    .mip_levels(desc.levels)
-                    .samples(vk::SampleCountFlags::TYPE_1)
-                    .tiling(vk::ImageTiling::OPTIMAL)
-                    .usage((desc.usage, desc.format).into_ash())
-                    .initial_layout(vk::ImageLayout::UNDEFINED),
-                None,
-            )
-        }
-        .map_err(|err| match err {
-            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
-            err => unexpected_error(err),
-        })?;
-
-        let requirements = unsafe { self.inner.device.get_image_memory_requirements(image) };
-        let align_mask = requirements.alignment - 1;
+    fn new_image(&self, desc: ImageDesc) -> Result<Image, AllocError> {
+        trace_span!(
+            "mev.new_image",
+            name = desc.name,
+            extent = ?desc.extent,
+            format = ?desc.format,
+            layers = desc.layers,
+            levels = desc.levels,
+        );
 
-        let result = unsafe {
-            self.inner.allocator.lock().alloc(
-                &*self.inner,
-                gpu_alloc::Request {
-                    size: requirements.size,
-                    align_mask,
-                    usage: memory_to_usage_flags(Memory::Device),
-                    memory_types: requirements.memory_type_bits,
+        let alloc_error = |kind| {
+            AllocError::new(
+                kind,
+                desc.name,
+                AllocResource::Image {
+                    extent: desc.extent,
+                    format: desc.format,
+                    layers: desc.layers,
+                    levels: desc.levels,
                 },
             )
         };
 
-        let block = match result {
-            Ok(block) => block,
+        let view_formats = self.image_view_formats(desc.format);
+        let mut format_list = vk::ImageFormatListCreateInfo::default().view_formats(&view_formats);
+        let mut info = self.image_create_info(&desc).map_err(alloc_error)?;
+        if !view_formats.is_empty() {
+            info = info.push_next(&mut format_list);
+        }
+
+        let image =
+            unsafe { self.inner.device.create_image(&info, None) }.map_err(|err| match err {
+                vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                    alloc_error(AllocErrorKind::OutOfDeviceMemory)
+                }
+                err => unexpected_error(err),
+            })?;
+
+        let (requirements, dedicated) = unsafe { self.image_memory_requirements(image) };
+
+        let result = unsafe {
+            self.alloc_block(
+                desc.name,
+                memory_to_usage_flags(Memory::Device),
+                requirements,
+                dedicated,
+            )
+        };
+
+        let block = match result {
+            Ok(block) => block,
             Err(err) => {
                 unsafe {
                     self.inner.device.destroy_image(image, None);
                 }
-                match err {
-                    gpu_alloc::AllocationError::OutOfDeviceMemory => return Err(OutOfMemory),
-                    gpu_alloc::AllocationError::OutOfHostMemory => handle_host_oom(),
-                    gpu_alloc::AllocationError::NoCompatibleMemoryTypes => return Err(OutOfMemory),
-                    gpu_alloc::AllocationError::TooManyObjects => return Err(OutOfMemory),
-                }
+                return Err(alloc_error(alloc_error_kind(err)));
             }
         };
 
@@ -1439,7 +2771,9 @@ impl crate::traits::Device for Device {
 
             match err {
                 vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => return Err(OutOfMemory),
+                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                    return Err(alloc_error(AllocErrorKind::OutOfDeviceMemory))
+                }
                 _ => unexpected_error(err),
             }
         }
@@ -1454,6 +2788,7 @@ impl crate::traits::Device for Device {
                 base_level: 0,
                 levels: desc.levels,
                 swizzle: Swizzle::IDENTITY,
+                aspect: ViewAspect::Auto,
             },
         );
 
@@ -1465,7 +2800,7 @@ impl crate::traits::Device for Device {
                     self.inner.allocator.lock().dealloc(&*self.inner, block);
                 }
 
-                return Err(OutOfMemory);
+                return Err(alloc_error(AllocErrorKind::OutOfDeviceMemory));
             }
         };
 
@@ -1476,6 +2811,8 @@ impl crate::traits::Device for Device {
 
         let image = Image::new(
             self.weak(),
+            self.new_resource_id(),
+            desc.name,
             image,
             view,
             view_idx,
@@ -1491,23 +2828,33 @@ impl crate::traits::Device for Device {
     }
 
     fn new_sampler(&self, desc: SamplerDesc) -> Result<Sampler, OutOfMemory> {
-        let mut samplers = self.inner.samplers.lock();
-        let len = samplers.len();
-        match samplers.entry(desc) {
-            hashbrown::hash_map::Entry::Occupied(entry) => match entry.get().upgrade() {
-                Some(sampler) => Ok(sampler),
-                None => {
-                    let sampler = self.new_sampler_slow(len, desc)?;
-                    entry.replace_entry(sampler.downgrade());
-                    Ok(sampler)
-                }
-            },
-            hashbrown::hash_map::Entry::Vacant(entry) => {
-                let sampler = self.new_sampler_slow(len, desc)?;
-                entry.insert(sampler.downgrade());
-                Ok(sampler)
-            }
+        self.cached_sampler(desc, false)
+    }
+
+    fn well_known_sampler(&self, kind: WellKnownSampler) -> Result<Sampler, OutOfMemory> {
+        let mut well_known = self.inner.well_known_samplers.lock();
+        if let Some(sampler) = &well_known[kind.index()] {
+            return Ok(sampler.clone());
         }
+
+        let sampler = self.cached_sampler(kind.desc(), true)?;
+        well_known[kind.index()] = Some(sampler.clone());
+        Ok(sampler)
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn linear_clamp_sampler(&self) -> Result<Sampler, OutOfMemory> {
+        self.well_known_sampler(WellKnownSampler::LinearClamp)
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn nearest_clamp_sampler(&self) -> Result<Sampler, OutOfMemory> {
+        self.well_known_sampler(WellKnownSampler::NearestClamp)
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn linear_repeat_sampler(&self) -> Result<Sampler, OutOfMemory> {
+        self.well_known_sampler(WellKnownSampler::LinearRepeat)
     }
 
     fn new_surface(
@@ -1528,86 +2875,140 @@ impl crate::traits::Device for Device {
             .display_handle()
             .map_err(|_| SurfaceError::SurfaceLost)?;
 
-        match (window.as_raw(), display.as_raw()) {
+        let result = match (window.as_raw(), display.as_raw()) {
             #[cfg(target_os = "windows")]
             (RawWindowHandle::Win32(window), RawDisplayHandle::Windows(_)) => {
-                let win32_surface = me.win32_surface.as_ref().unwrap();
-                let result = unsafe {
+                let Some(win32_surface) = &me.platform_surface.win32 else {
+                    return Err(SurfaceError::Unsupported);
+                };
+                unsafe {
                     win32_surface.create_win32_surface(
                         &ash::vk::Win32SurfaceCreateInfoKHR::default()
                             // .hinstance(hinstance)
                             .hwnd(window.hwnd.get() as _),
                         None,
                     )
+                }
+            }
+            #[cfg(all(unix, not(target_os = "android")))]
+            (RawWindowHandle::Xlib(window), RawDisplayHandle::Xlib(display)) => {
+                let (Some(xlib_surface), Some(dpy)) = (&me.platform_surface.xlib, display.display)
+                else {
+                    return Err(SurfaceError::Unsupported);
                 };
-                let surface = result.map_err(|err| match err {
-                    vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-                    vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
-                    err => unexpected_error(err),
-                })?;
-
-                let result = unsafe {
-                    self.surface()
-                        .get_physical_device_surface_formats(self.physical_device(), surface)
+                unsafe {
+                    xlib_surface.create_xlib_surface(
+                        &ash::vk::XlibSurfaceCreateInfoKHR::default()
+                            .dpy(dpy.as_ptr() as _)
+                            .window(window.window),
+                        None,
+                    )
+                }
+            }
+            #[cfg(all(unix, not(target_os = "android")))]
+            (RawWindowHandle::Xcb(window), RawDisplayHandle::Xcb(display)) => {
+                let (Some(xcb_surface), Some(connection)) =
+                    (&me.platform_surface.xcb, display.connection)
+                else {
+                    return Err(SurfaceError::Unsupported);
                 };
-                let formats = result.map_err(|err| match err {
-                    ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-                    ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
-                    ash::vk::Result::ERROR_SURFACE_LOST_KHR => SurfaceError::SurfaceLost,
-                    _ => unexpected_error(err),
-                })?;
-
-                let result = unsafe {
-                    self.surface()
-                        .get_physical_device_surface_present_modes(self.physical_device(), surface)
+                unsafe {
+                    xcb_surface.create_xcb_surface(
+                        &ash::vk::XcbSurfaceCreateInfoKHR::default()
+                            .connection(connection.as_ptr())
+                            .window(window.window.get()),
+                        None,
+                    )
+                }
+            }
+            #[cfg(all(unix, not(target_os = "android")))]
+            (RawWindowHandle::Wayland(window), RawDisplayHandle::Wayland(display)) => {
+                let Some(wayland_surface) = &me.platform_surface.wayland else {
+                    return Err(SurfaceError::Unsupported);
                 };
-                let modes = result.map_err(|err| match err {
-                    ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-                    ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
-                    ash::vk::Result::ERROR_SURFACE_LOST_KHR => SurfaceError::SurfaceLost,
-                    _ => unexpected_error(err),
+                unsafe {
+                    wayland_surface.create_wayland_surface(
+                        &ash::vk::WaylandSurfaceCreateInfoKHR::default()
+                            .display(display.display.as_ptr())
+                            .surface(window.surface.as_ptr()),
+                        None,
+                    )
+                }
+            }
+            #[cfg(target_os = "android")]
+            (RawWindowHandle::AndroidNdk(window), RawDisplayHandle::Android(_)) => {
+                let Some(android_surface) = &me.platform_surface.android else {
+                    return Err(SurfaceError::Unsupported);
+                };
+                unsafe {
+                    android_surface.create_android_surface(
+                        &ash::vk::AndroidSurfaceCreateInfoKHR::default()
+                            .window(window.a_native_window.as_ptr()),
+                        None,
+                    )
+                }
+            }
+            // Mismatched window and display types
+            // or window system not supported on this platform.
+            _ => return Err(SurfaceError::Unsupported),
+        };
+
+        let surface = result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
+            err => unexpected_error(err),
+        })?;
+
+        let result = unsafe {
+            self.surface()
+                .get_physical_device_surface_formats(self.physical_device(), surface)
+        };
+        let formats = result.map_err(|err| match err {
+            ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
+            ash::vk::Result::ERROR_SURFACE_LOST_KHR => SurfaceError::SurfaceLost,
+            _ => unexpected_error(err),
+        })?;
+
+        let result = unsafe {
+            self.surface()
+                .get_physical_device_surface_present_modes(self.physical_device(), surface)
+        };
+        let modes = result.map_err(|err| match err {
+            ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
+            ash::vk::Result::ERROR_SURFACE_LOST_KHR => SurfaceError::SurfaceLost,
+            _ => unexpected_error(err),
+        })?;
+
+        let family_supports =
+            self.queue_families()
+                .iter()
+                .try_fold(Vec::new(), |mut supports, &idx| {
+                    let result = unsafe {
+                        self.surface().get_physical_device_surface_support(
+                            self.physical_device(),
+                            idx,
+                            surface,
+                        )
+                    };
+                    let support = result.map_err(|err| match err {
+                        ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+                        ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
+                        ash::vk::Result::ERROR_SURFACE_LOST_KHR => SurfaceError::SurfaceLost,
+                        _ => unexpected_error(err),
+                    })?;
+                    supports.push(support);
+                    Ok::<_, SurfaceError>(supports)
                 })?;
 
-                let family_supports =
-                    self.queue_families()
-                        .iter()
-                        .try_fold(Vec::new(), |mut supports, &idx| {
-                            let result = unsafe {
-                                self.surface().get_physical_device_surface_support(
-                                    self.physical_device(),
-                                    idx,
-                                    surface,
-                                )
-                            };
-                            let support = result.map_err(|err| match err {
-                                ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-                                ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
-                                    SurfaceError::OutOfMemory
-                                }
-                                ash::vk::Result::ERROR_SURFACE_LOST_KHR => {
-                                    SurfaceError::SurfaceLost
-                                }
-                                _ => unexpected_error(err),
-                            })?;
-                            supports.push(support);
-                            Ok::<_, SurfaceError>(supports)
-                        })?;
-
-                Ok(Surface::new(
-                    self.clone(),
-                    surface,
-                    formats,
-                    modes,
-                    family_supports,
-                ))
-            }
-            (RawWindowHandle::Win32(_), _) => {
-                panic!("Mismatched window and display type")
-            }
-            _ => {
-                unreachable!("Unsupported window type for this platform")
-            }
-        }
+        Ok(Surface::new(
+            self.clone(),
+            surface,
+            formats,
+            modes,
+            family_supports,
+        ))
     }
 
     /// Create a new bottom-level acceleration structure.
@@ -1619,6 +3020,583 @@ impl crate::traits::Device for Device {
     fn new_tlas(&self, desc: TlasDesc) -> Result<Tlas, OutOfMemory> {
         todo!()
     }
+
+    fn new_bindless_table(&self, capacity: u32) -> Result<BindlessTable, OutOfMemory> {
+        assert!(
+            self.inner.features.contains(Features::DESCRIPTOR_INDEXING),
+            "Bindless tables require `Features::DESCRIPTOR_INDEXING`"
+        );
+        assert!(
+            capacity <= self.inner.max_bindless_table_size,
+            "Bindless table capacity {capacity} exceeds device limit {}",
+            self.inner.max_bindless_table_size
+        );
+
+        let layout = self.new_set_layout(DescriptorSetLayoutDesc {
+            arguments: vec![ArgumentLayout {
+                kind: ArgumentKind::BindlessTable,
+                size: 0,
+                stages: ShaderStages::all(),
+                immutable_sampler: None,
+            }],
+        })?;
+
+        let pool_size = vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::SAMPLED_IMAGE,
+            descriptor_count: capacity.max(1),
+        };
+
+        let result = unsafe {
+            self.ash().create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default()
+                    .flags(vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
+                    .max_sets(1)
+                    .pool_sizes(std::slice::from_ref(&pool_size)),
+                None,
+            )
+        };
+
+        let pool = result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
+            vk::Result::ERROR_FRAGMENTATION_EXT => OutOfMemory,
+            _ => unexpected_error(err),
+        })?;
+
+        let counts = [capacity];
+        let mut variable_count = vk::DescriptorSetVariableDescriptorCountAllocateInfo::default()
+            .descriptor_counts(&counts);
+
+        let result = unsafe {
+            self.ash().allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(pool)
+                    .set_layouts(&[layout.handle()])
+                    .push_next(&mut variable_count),
+            )
+        };
+
+        let set = match result {
+            Ok(sets) => sets[0],
+            Err(err) => {
+                unsafe {
+                    self.ash().destroy_descriptor_pool(pool, None);
+                }
+                return Err(match err {
+                    vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+                    vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
+                    _ => unexpected_error(err),
+                });
+            }
+        };
+
+        Ok(BindlessTable::new(self.weak(), pool, set, layout, capacity))
+    }
+
+    fn format_caps(&self, format: PixelFormat) -> FormatCaps {
+        let Some(format) = format.try_into_ash() else {
+            return FormatCaps::empty();
+        };
+
+        let properties = unsafe {
+            self.inner
+                .instance
+                .get_physical_device_format_properties(self.inner.physical_device, format)
+        };
+
+        let features = properties.optimal_tiling_features;
+        let mut caps = FormatCaps::empty();
+
+        if features.contains(vk::FormatFeatureFlags::SAMPLED_IMAGE) {
+            caps |= FormatCaps::SAMPLED;
+        }
+        if features.contains(vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR) {
+            caps |= FormatCaps::FILTERABLE_LINEAR;
+        }
+        if features.contains(vk::FormatFeatureFlags::STORAGE_IMAGE) {
+            caps |= FormatCaps::STORAGE;
+        }
+        if features.contains(vk::FormatFeatureFlags::STORAGE_IMAGE_ATOMIC) {
+            caps |= FormatCaps::STORAGE_ATOMIC;
+        }
+        if features.contains(vk::FormatFeatureFlags::COLOR_ATTACHMENT) {
+            caps |= FormatCaps::COLOR_TARGET;
+        }
+        if features.contains(vk::FormatFeatureFlags::COLOR_ATTACHMENT_BLEND) {
+            caps |= FormatCaps::BLENDABLE;
+        }
+        if features.contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT) {
+            caps |= FormatCaps::DEPTH_STENCIL_TARGET;
+        }
+
+        caps
+    }
+
+    fn select_depth_format(&self, requirements: DepthFormatRequirements) -> Option<PixelFormat> {
+        find_depth_format(requirements, |format| self.format_caps(format))
+    }
+
+    fn describe(&self, resource: &impl crate::traits::Resource) -> String {
+        resource.describe()
+    }
+
+    fn features(&self) -> Features {
+        self.inner.features
+    }
+
+    fn quirks(&self) -> Quirks {
+        self.inner.quirks
+    }
+
+    fn limits(&self) -> DeviceLimits {
+        let limits = &self.inner.properties.limits;
+        DeviceLimits {
+            max_argument_groups: limits.max_bound_descriptor_sets,
+            max_uniform_buffer_range: u64::from(limits.max_uniform_buffer_range),
+            max_storage_buffer_range: u64::from(limits.max_storage_buffer_range),
+            max_bindless_table_size: self.inner.max_bindless_table_size,
+        }
+    }
+
+    fn image_allocation_size(&self, desc: &ImageDesc) -> Result<u64, AllocError> {
+        let alloc_error = |kind| {
+            AllocError::new(
+                kind,
+                desc.name,
+                AllocResource::Image {
+                    extent: desc.extent,
+                    format: desc.format,
+                    layers: desc.layers,
+                    levels: desc.levels,
+                },
+            )
+        };
+
+        let view_formats = self.image_view_formats(desc.format);
+        let mut format_list = vk::ImageFormatListCreateInfo::default().view_formats(&view_formats);
+        let mut info = self.image_create_info(desc).map_err(alloc_error)?;
+        if !view_formats.is_empty() {
+            info = info.push_next(&mut format_list);
+        }
+
+        let requirements = match &self.inner.maintenance4 {
+            Some(maintenance4) => {
+                let query = vk::DeviceImageMemoryRequirements::default().create_info(&info);
+                let mut requirements = vk::MemoryRequirements2::default();
+                unsafe {
+                    match maintenance4 {
+                        Maintenance4::Core => self
+                            .inner
+                            .device
+                            .get_device_image_memory_requirements(&query, &mut requirements),
+                        Maintenance4::Khr(device) => {
+                            device.get_device_image_memory_requirements(&query, &mut requirements)
+                        }
+                    }
+                }
+                requirements.memory_requirements
+            }
+            None => {
+                // Throwaway image is never bound to memory.
+                let image = unsafe { self.inner.device.create_image(&info, None) }.map_err(
+                    |err| match err {
+                        vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+                        vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                            alloc_error(AllocErrorKind::OutOfDeviceMemory)
+                        }
+                        err => unexpected_error(err),
+                    },
+                )?;
+
+                unsafe {
+                    let requirements = self.inner.device.get_image_memory_requirements(image);
+                    self.inner.device.destroy_image(image, None);
+                    requirements
+                }
+            }
+        };
+
+        Ok(requirements.size)
+    }
+
+    fn buffer_allocation_size(&self, desc: &BufferDesc) -> u64 {
+        if desc.size > self.max_buffer_size(desc.usage) {
+            return desc.size;
+        }
+
+        let info = self.buffer_create_info(desc);
+
+        let requirements = match &self.inner.maintenance4 {
+            Some(maintenance4) => {
+                let query = vk::DeviceBufferMemoryRequirements::default().create_info(&info);
+                let mut requirements = vk::MemoryRequirements2::default();
+                unsafe {
+                    match maintenance4 {
+                        Maintenance4::Core => self
+                            .inner
+                            .device
+                            .get_device_buffer_memory_requirements(&query, &mut requirements),
+                        Maintenance4::Khr(device) => {
+                            device.get_device_buffer_memory_requirements(&query, &mut requirements)
+                        }
+                    }
+                }
+                requirements.memory_requirements
+            }
+            None => {
+                // Throwaway buffer is never bound to memory.
+                match unsafe { self.inner.device.create_buffer(&info, None) } {
+                    Ok(buffer) => unsafe {
+                        let requirements = self.inner.device.get_buffer_memory_requirements(buffer);
+                        self.inner.device.destroy_buffer(buffer, None);
+                        requirements
+                    },
+                    Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => handle_host_oom(),
+                    Err(_) => return desc.size,
+                }
+            }
+        };
+
+        requirements.size
+    }
+
+    fn allocator_stats(&self) -> AllocatorStats {
+        let linear = self.inner.linear.lock();
+        let memory = self.inner.memory.lock();
+        let (dedicated_objects, dedicated_bytes) = memory
+            .iter()
+            .filter(|(_, object)| object.dedicated)
+            .fold((0, 0), |(count, bytes), (_, object)| {
+                (count + 1, bytes + object.size)
+            });
+
+        AllocatorStats {
+            memory_objects: memory.len(),
+            dedicated_objects,
+            dedicated_bytes,
+            reserved_bytes: self.inner.reserved.load(Ordering::Relaxed),
+            peak_reserved_bytes: self.inner.peak_reserved.load(Ordering::Relaxed),
+            linear_chunks: linear.chunks(),
+            linear_buffers: linear.live(),
+            fallback_allocations: self.inner.fallback_allocations.load(Ordering::Relaxed),
+            fallback_bytes: self.inner.fallback_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn trim_memory(&self) -> u64 {
+        let before = self.inner.reserved.load(Ordering::Relaxed);
+
+        let mut linear = self.inner.linear.lock();
+        let mut allocator = self.inner.allocator.lock();
+
+        // Safety: blocks are allocated from the same allocator with the same device.
+        unsafe {
+            linear.trim(&self.inner, &mut allocator);
+            allocator.cleanup(&*self.inner);
+        }
+
+        drop(allocator);
+        drop(linear);
+
+        let after = self.inner.reserved.load(Ordering::Relaxed);
+        let released = before.saturating_sub(after);
+
+        if released > 0 {
+            tracing::debug!("Released {released} bytes of unused device memory");
+        }
+        released
+    }
+
+    fn plan_compaction(&self, buffers: &[Buffer]) -> CompactionPlan {
+        let linear = self.inner.linear.lock();
+        let memory = self.inner.memory.lock();
+
+        // Movable buffers grouped by memory region they occupy
+        // with bytes occupied in the region and its size.
+        let mut regions = HashMap::<_, (u64, u64, Vec<usize>)>::new();
+
+        for (idx, buffer) in buffers.iter().enumerate() {
+            if !buffer.movable() {
+                continue;
+            }
+
+            let (region, used, size) = match buffer.memory() {
+                BufferMemory::Block(block) => {
+                    let object = &memory[block.memory().1];
+                    (
+                        MemoryRegion::Object(block.memory().1),
+                        object.used,
+                        object.size,
+                    )
+                }
+                BufferMemory::Linear(block) => {
+                    let (chunk, used, size) = linear.occupancy(block);
+                    (MemoryRegion::Chunk(chunk), used, size)
+                }
+            };
+
+            regions
+                .entry(region)
+                .or_insert((used, size, Vec::new()))
+                .2
+                .push(idx);
+        }
+
+        let mut plan = CompactionPlan::default();
+        for (used, size, indices) in regions.into_values() {
+            if !fragmented(used, size) {
+                continue;
+            }
+
+            plan.fragmented_bytes += size - used;
+            for idx in indices {
+                plan.bytes += buffers[idx].size();
+                plan.buffers.push(idx);
+            }
+        }
+
+        plan.buffers.sort_unstable();
+        plan
+    }
+
+    fn family_capabilities(&self, family: u32) -> FamilyCapabilities {
+        match self.inner.family_capabilities.get(family as usize) {
+            Some(caps) => caps.clone(),
+            None => panic!(
+                "Queue family {family} is out of range, device has {} families",
+                self.inner.family_capabilities.len()
+            ),
+        }
+    }
+
+    fn supports_present(&self, family: u32, surface: &Surface) -> bool {
+        if let Some(idx) = self.queue_families().iter().position(|&f| f == family) {
+            return surface.family_supports()[idx];
+        }
+
+        let result = unsafe {
+            self.surface().get_physical_device_surface_support(
+                self.physical_device(),
+                family,
+                surface.handle(),
+            )
+        };
+
+        match result {
+            Ok(support) => support,
+            Err(ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY) => handle_host_oom(),
+            Err(ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY)
+            | Err(ash::vk::Result::ERROR_SURFACE_LOST_KHR) => false,
+            Err(err) => unexpected_error(err),
+        }
+    }
+}
+
+/// Returns stages of the pipeline with given bind point
+/// and whether it has fragment shader.
+/// Constants are visible to these stages.
+fn constants_stages(bind_point: vk::PipelineBindPoint, fragment: bool) -> vk::ShaderStageFlags {
+    match bind_point {
+        vk::PipelineBindPoint::COMPUTE => vk::ShaderStageFlags::COMPUTE,
+        _ if fragment => vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+        _ => vk::ShaderStageFlags::VERTEX,
+    }
+}
+
+/// Returns anisotropy to create sampler with, `None` disables anisotropic filtering.
+///
+/// Requested anisotropy is ignored unless `Features::SAMPLER_ANISOTROPY` is enabled.
+fn sampler_anisotropy(
+    anisotropy: Option<f32>,
+    features: Features,
+    limit: f32,
+    quirks: Quirks,
+) -> Option<f32> {
+    match anisotropy {
+        None => None,
+        Some(_) if !features.contains(Features::SAMPLER_ANISOTROPY) => {
+            tracing::warn!(
+                "Sampler anisotropy is requested but `Features::SAMPLER_ANISOTROPY` is not enabled, disabling anisotropic filtering"
+            );
+            None
+        }
+        Some(anisotropy) => Some(clamp_anisotropy(anisotropy, limit, quirks)),
+    }
+}
+
+/// Clamps sampler anisotropy to the device limit,
+/// which is lowered to `8` by [`Quirks::CLAMP_ANISOTROPY_TO_8`].
+fn clamp_anisotropy(anisotropy: f32, limit: f32, quirks: Quirks) -> f32 {
+    let mut max = limit;
+    if quirks.contains(Quirks::CLAMP_ANISOTROPY_TO_8) {
+        max = max.min(8.0);
+    }
+    let clamped = anisotropy.clamp(1.0, max);
+    if clamped != anisotropy {
+        tracing::warn!(
+            "Sampler anisotropy {anisotropy} is out of supported range [1, {max}], clamping to {clamped}"
+        );
+    }
+    clamped
+}
+
+/// Returns line width that device can rasterize.
+///
+/// Without `Features::WIDE_LINES` only `1.0` is allowed,
+/// otherwise width is clamped to the supported range.
+fn effective_line_width(line_width: f32, wide_lines: bool, range: [f32; 2]) -> f32 {
+    if line_width == 1.0 {
+        return 1.0;
+    }
+
+    if !wide_lines {
+        tracing::warn!(
+            "Line width {line_width} requires `Features::WIDE_LINES` which is not enabled, using 1.0"
+        );
+        return 1.0;
+    }
+
+    let [min, max] = range;
+    let clamped = line_width.clamp(min, max);
+    if clamped != line_width {
+        tracing::warn!(
+            "Line width {line_width} is out of supported range [{min}, {max}], clamping to {clamped}"
+        );
+    }
+    clamped
+}
+
+/// Returns descriptor set layout binding for the argument at index `idx`.
+fn set_layout_binding(
+    idx: usize,
+    arg: &ArgumentLayout,
+) -> ash::vk::DescriptorSetLayoutBinding<'static> {
+    ash::vk::DescriptorSetLayoutBinding::default()
+        .binding(u32::try_from(idx).expect("Too many descriptor bindings"))
+        .descriptor_count(u32::try_from(arg.size).expect("Too many descriptors in array"))
+        .descriptor_type(descriptor_type(arg.kind))
+        .stage_flags(arg.stages.into_ash())
+}
+
+/// Checks that the library has shader entry point of the given stage.
+fn check_entry_stage(shader: &Shader, stage: ShaderStage) -> Result<(), CreatePipelineError> {
+    check_entry_point(shader.library.entry_points(), &shader.entry, stage)
+        .map_err(|err| CreatePipelineError(CreatePipelineErrorKind::UnknownEntryPoint(err)))
+}
+
+/// Checks that arguments used by the shader are visible to its stage.
+///
+/// Shaders that were not reflected are not checked.
+fn check_shader_argument_stages(
+    shader: &Shader,
+    arguments: &[ArgumentGroupLayout],
+) -> Result<(), CreatePipelineError> {
+    match shader.library.reflect_entry(&shader.entry) {
+        None => Ok(()),
+        Some(entry) => check_argument_stages(entry, arguments).map_err(|err| {
+            CreatePipelineError(CreatePipelineErrorKind::ArgumentStageMismatch(err))
+        }),
+    }
+}
+
+/// Checks that buffers used by the shader fit into buffer range limits of the device.
+///
+/// Shaders that were not reflected are not checked.
+fn check_shader_buffer_ranges(
+    shader: &Shader,
+    limits: &DeviceLimits,
+) -> Result<(), CreatePipelineError> {
+    match shader.library.reflect_entry(&shader.entry) {
+        None => Ok(()),
+        Some(entry) => check_buffer_ranges(entry, limits)
+            .map_err(|err| CreatePipelineError(CreatePipelineErrorKind::BufferRangeExceeded(err))),
+    }
+}
+
+/// Checks that fragment shader inputs match vertex shader outputs.
+///
+/// Shaders that were not reflected are not checked.
+fn check_stage_interfaces(
+    vertex_shader: &Shader,
+    fragment_shader: &Shader,
+) -> Result<(), CreatePipelineError> {
+    let vertex = vertex_shader.library.reflect_entry(&vertex_shader.entry);
+    let fragment = fragment_shader
+        .library
+        .reflect_entry(&fragment_shader.entry);
+
+    if let (Some(vertex), Some(fragment)) = (vertex, fragment) {
+        check_stage_interface(vertex, fragment).map_err(|err| {
+            CreatePipelineError(CreatePipelineErrorKind::StageInterfaceMismatch(err))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Resource that memory object is dedicated to.
+#[derive(Clone, Copy)]
+enum DedicatedResource {
+    Buffer(vk::Buffer),
+    Image(vk::Image),
+}
+
+/// Returns `true` if resource of `size` bytes should get dedicated memory.
+///
+/// Driver preference is followed only for resources
+/// of `preferred_threshold` bytes or larger,
+/// small resources that drivers often flag are sub-allocated.
+fn wants_dedicated(
+    size: u64,
+    dedicated: &vk::MemoryDedicatedRequirements,
+    preferred_threshold: u64,
+    resource_threshold: u64,
+) -> bool {
+    dedicated.requires_dedicated_allocation != 0
+        || (dedicated.prefers_dedicated_allocation != 0 && size >= preferred_threshold)
+        || size >= resource_threshold
+}
+
+/// Returns `true` if memory region with `used` bytes of `size` should be compacted.
+fn fragmented(used: u64, size: u64) -> bool {
+    used * 100 < size * COMPACTION_OCCUPANCY_PERCENT
+}
+
+/// Splits requirements sorted by memory types into ranges
+/// that are packed into one chunk each.
+///
+/// Chunk holds buffers that allow the same memory types
+/// and is limited by [`PACKED_CHUNK_SIZE`] unless it holds one buffer.
+fn pack_ranges(requirements: &[vk::MemoryRequirements]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+
+    for (idx, r) in requirements.iter().enumerate() {
+        if idx > start
+            && (r.memory_type_bits != requirements[start].memory_type_bits
+                || size + r.size > PACKED_CHUNK_SIZE)
+        {
+            ranges.push(start..idx);
+            start = idx;
+            size = 0;
+        }
+        size += r.size;
+    }
+
+    if start < requirements.len() {
+        ranges.push(start..requirements.len());
+    }
+    ranges
+}
+
+fn alloc_error_kind(err: gpu_alloc::AllocationError) -> AllocErrorKind {
+    match err {
+        gpu_alloc::AllocationError::OutOfDeviceMemory => AllocErrorKind::OutOfDeviceMemory,
+        gpu_alloc::AllocationError::OutOfHostMemory => AllocErrorKind::OutOfHostMemory,
+        gpu_alloc::AllocationError::NoCompatibleMemoryTypes => AllocErrorKind::NoCompatibleMemory,
+        gpu_alloc::AllocationError::TooManyObjects => AllocErrorKind::TooManyObjects,
+    }
 }
 
 fn memory_to_usage_flags(memory: Memory) -> gpu_alloc::UsageFlags {
@@ -1630,19 +3608,81 @@ fn memory_to_usage_flags(memory: Memory) -> gpu_alloc::UsageFlags {
     }
 }
 
+/// Checks that SPIR-V code consists of whole words and starts with valid header.
+fn validate_spirv_header(code: &[u8]) -> Result<(), ShaderCompileError> {
+    const MAGIC: u32 = 0x0723_0203;
+    const HEADER_SIZE: usize = 20;
+
+    if code.len() % 4 != 0 || code.len() < HEADER_SIZE {
+        return Err(ShaderCompileError::InvalidSpirV);
+    }
+
+    let magic = u32::from_ne_bytes([code[0], code[1], code[2], code[3]]);
+    if magic != MAGIC {
+        return Err(ShaderCompileError::InvalidSpirV);
+    }
+
+    Ok(())
+}
+
+/// Reflects entry points of SPIR-V module to validate pipelines.
+///
+/// With `shader-spirv-passthrough` feature alone modules are never reflected.
+#[cfg(not(feature = "shader-spirv"))]
+fn reflect_spirv(_code: &[u8]) -> Option<Box<[EntryReflection]>> {
+    None
+}
+
+/// Reflects entry points of SPIR-V module to validate pipelines.
+///
+/// SPIR-V is passed to the driver as is, so modules that naga can't parse
+/// are still accepted, just not reflected.
+#[cfg(feature = "shader-spirv")]
+fn reflect_spirv(code: &[u8]) -> Option<Box<[EntryReflection]>> {
+    let module = match naga::front::spv::parse_u8_slice(code, &naga::front::spv::Options::default())
+    {
+        Ok(module) => module,
+        Err(err) => {
+            tracing::info!("SPIR-V module is not reflected: {err}");
+            return None;
+        }
+    };
+
+    let flags = naga::valid::ValidationFlags::empty();
+    let caps = naga::valid::Capabilities::all();
+    match naga::valid::Validator::new(flags, caps).validate(&module) {
+        Ok(info) => Some(reflect_entries(&module, &info).into()),
+        Err(err) => {
+            tracing::info!("SPIR-V module is not reflected: {err}");
+            None
+        }
+    }
+}
+
+/// Naga capabilities that SPIR-V backend can generate.
+/// Capabilities gated by device features are removed by [`shader_capabilities`].
+const SPIRV_CAPABILITIES: naga::valid::Capabilities = naga::valid::Capabilities::all();
+
 pub(crate) fn compile_shader(
     code: &[u8],
-    filename: Option<&str>,
+    files: &[SourceFile],
     lang: ShaderLanguage,
-) -> Result<Box<[u32]>, ShaderCompileError> {
-    let (module, info, source_code) = parse_shader(code, filename, lang)?;
+    bounds_check_policies: naga::proc::BoundsCheckPolicies,
+    caps: naga::valid::Capabilities,
+) -> Result<(Box<[u32]>, Box<[EntryReflection]>), ShaderCompileError> {
+    let (module, info, source_code) = parse_shader(code, files, lang, caps)?;
+    let filename = files.first().map(|file| &*file.name);
+    let entries = reflect_entries(&module, &info).into();
 
     let options = naga::back::spv::Options {
         lang_version: (1, 3),
-        flags: naga::back::spv::WriterFlags::ADJUST_COORDINATE_SPACE,
+        // Point size must be written for point topology.
+        // It is ignored for other topologies.
+        flags: naga::back::spv::WriterFlags::ADJUST_COORDINATE_SPACE
+            | naga::back::spv::WriterFlags::FORCE_POINT_SIZE,
         binding_map: naga::back::spv::BindingMap::default(),
         capabilities: None,
-        bounds_check_policies: naga::proc::BoundsCheckPolicies::default(),
+        bounds_check_policies,
         zero_initialize_workgroup_memory: naga::back::spv::ZeroInitializeWorkgroupMemoryMode::None,
         debug_info: match source_code {
             None => None,
@@ -1657,5 +3697,243 @@ pub(crate) fn compile_shader(
         .map(|vec| vec.into())
         .map_err(ShaderCompileError::GenSpirV)?;
 
-    Ok(words)
+    Ok((words, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spirv_header() {
+        let mut code = [0u8; 20];
+        code[..4].copy_from_slice(&0x0723_0203u32.to_ne_bytes());
+        assert!(validate_spirv_header(&code).is_ok());
+
+        assert!(validate_spirv_header(&code[..16]).is_err());
+        assert!(validate_spirv_header(&[code.as_slice(), &[0]].concat()).is_err());
+
+        code[0] ^= 0xff;
+        assert!(validate_spirv_header(&code).is_err());
+    }
+
+    fn requirements(size: u64, memory_type_bits: u32) -> vk::MemoryRequirements {
+        vk::MemoryRequirements {
+            size,
+            alignment: 256,
+            memory_type_bits,
+        }
+    }
+
+    #[test]
+    fn dedicated_thresholds() {
+        let config = crate::generic::AllocatorConfig::default();
+        let wants = |size, requires, prefers| {
+            let dedicated = vk::MemoryDedicatedRequirements {
+                requires_dedicated_allocation: requires as vk::Bool32,
+                prefers_dedicated_allocation: prefers as vk::Bool32,
+                ..Default::default()
+            };
+            wants_dedicated(
+                size,
+                &dedicated,
+                config.preferred_dedicated_threshold,
+                config.dedicated_resource_threshold,
+            )
+        };
+
+        // 4K render target that driver prefers to be dedicated.
+        let target = 3840 * 2160 * 4;
+        assert!(target < config.dedicated_resource_threshold);
+        assert!(wants(target, false, true));
+        assert!(!wants(target, false, false));
+
+        // Small uniform buffer is sub-allocated even if driver prefers otherwise.
+        let uniform = 16 * 1024;
+        assert!(!wants(uniform, false, true));
+        assert!(wants(uniform, true, false));
+
+        assert!(wants(config.dedicated_resource_threshold, false, false));
+        assert!(wants(config.preferred_dedicated_threshold, false, true));
+        assert!(!wants(
+            config.preferred_dedicated_threshold - 1,
+            false,
+            true
+        ));
+    }
+
+    #[test]
+    fn fragmentation_threshold() {
+        assert!(fragmented(0, 1024));
+        assert!(fragmented(511, 1024));
+        assert!(!fragmented(512, 1024));
+        assert!(!fragmented(1024, 1024));
+    }
+
+    #[test]
+    fn pack_ranges_split() {
+        assert!(pack_ranges(&[]).is_empty());
+
+        let mb = 1 << 20;
+        let ranges = pack_ranges(&[
+            requirements(mb, 0b01),
+            requirements(mb, 0b01),
+            requirements(mb, 0b10),
+            requirements(PACKED_CHUNK_SIZE, 0b10),
+            requirements(2 * PACKED_CHUNK_SIZE, 0b10),
+            requirements(mb, 0b10),
+        ]);
+        assert_eq!(ranges, [0..2, 2..3, 3..4, 4..5, 5..6]);
+    }
+
+    #[test]
+    fn constants_stages_match_pipeline() {
+        assert_eq!(
+            constants_stages(vk::PipelineBindPoint::COMPUTE, false),
+            vk::ShaderStageFlags::COMPUTE
+        );
+        assert_eq!(
+            constants_stages(vk::PipelineBindPoint::GRAPHICS, false),
+            vk::ShaderStageFlags::VERTEX
+        );
+        assert_eq!(
+            constants_stages(vk::PipelineBindPoint::GRAPHICS, true),
+            vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT
+        );
+    }
+
+    #[test]
+    fn portability_views() {
+        let rgba = PixelFormat::Rgba8Unorm;
+        let subset = PortabilitySubset {
+            vertex_attribute_access_beyond_stride: false,
+            min_vertex_input_binding_stride_alignment: 4,
+            image_view_format_swizzle: false,
+            image_view_format_reinterpretation: false,
+        };
+
+        let swizzled = ViewDesc::new(rgba).swizzle(Swizzle::RRRR);
+        assert_eq!(
+            subset.check_view("image", rgba, &swizzled),
+            Err(ViewError::SwizzleNotSupported {
+                image: "image".to_owned()
+            })
+        );
+        assert!(PortabilitySubset::CONFORMANT
+            .check_view("image", rgba, &swizzled)
+            .is_ok());
+
+        // sRGB twin doesn't reinterpret the format.
+        assert!(subset
+            .check_view("image", rgba, &ViewDesc::new(PixelFormat::Rgba8Srgb))
+            .is_ok());
+
+        let reinterpreted = ViewDesc::new(PixelFormat::R32Uint);
+        assert_eq!(
+            subset.check_view("image", rgba, &reinterpreted),
+            Err(ViewError::FormatNotSupported {
+                image: "image".to_owned(),
+                format: rgba,
+                view_format: PixelFormat::R32Uint,
+            })
+        );
+        assert!(PortabilitySubset::CONFORMANT
+            .check_view("image", rgba, &reinterpreted)
+            .is_ok());
+    }
+
+    #[test]
+    fn fragment_only_binding() {
+        let binding = set_layout_binding(
+            1,
+            &ArgumentLayout {
+                kind: ArgumentKind::SampledImage,
+                size: 1,
+                stages: ShaderStages::FRAGMENT,
+                immutable_sampler: None,
+            },
+        );
+
+        assert_eq!(binding.binding, 1);
+        assert_eq!(binding.descriptor_count, 1);
+        assert_eq!(binding.descriptor_type, vk::DescriptorType::SAMPLED_IMAGE);
+        assert_eq!(binding.stage_flags, vk::ShaderStageFlags::FRAGMENT);
+    }
+
+    #[test]
+    fn vertex_compute_binding() {
+        let binding = set_layout_binding(
+            0,
+            &ArgumentLayout {
+                kind: ArgumentKind::StorageBuffer,
+                size: 4,
+                stages: ShaderStages::VERTEX | ShaderStages::COMPUTE,
+                immutable_sampler: None,
+            },
+        );
+
+        assert_eq!(binding.descriptor_count, 4);
+        assert_eq!(binding.descriptor_type, vk::DescriptorType::STORAGE_BUFFER);
+        assert_eq!(
+            binding.stage_flags,
+            vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::COMPUTE
+        );
+    }
+
+    #[test]
+    fn line_width() {
+        let range = [1.0, 8.0];
+
+        assert_eq!(effective_line_width(1.0, false, range), 1.0);
+        assert_eq!(effective_line_width(4.0, false, range), 1.0);
+        assert_eq!(effective_line_width(4.0, true, range), 4.0);
+        assert_eq!(effective_line_width(16.0, true, range), 8.0);
+        assert_eq!(effective_line_width(0.5, true, range), 1.0);
+    }
+
+    #[test]
+    fn anisotropy_quirk() {
+        assert_eq!(clamp_anisotropy(16.0, 16.0, Quirks::empty()), 16.0);
+        assert_eq!(
+            clamp_anisotropy(16.0, 16.0, Quirks::CLAMP_ANISOTROPY_TO_8),
+            8.0
+        );
+        assert_eq!(
+            clamp_anisotropy(4.0, 16.0, Quirks::CLAMP_ANISOTROPY_TO_8),
+            4.0
+        );
+        assert_eq!(
+            clamp_anisotropy(16.0, 2.0, Quirks::CLAMP_ANISOTROPY_TO_8),
+            2.0
+        );
+        assert_eq!(clamp_anisotropy(0.0, 16.0, Quirks::empty()), 1.0);
+    }
+
+    #[test]
+    fn anisotropy_without_feature() {
+        let anisotropy =
+            |requested| sampler_anisotropy(requested, Features::empty(), 16.0, Quirks::empty());
+        assert_eq!(anisotropy(None), None);
+        assert_eq!(anisotropy(Some(4.0)), None);
+        assert_eq!(anisotropy(Some(16.0)), None);
+    }
+
+    #[test]
+    fn anisotropy_clamped_to_limit() {
+        let anisotropy = |requested, limit| {
+            sampler_anisotropy(
+                requested,
+                Features::SAMPLER_ANISOTROPY,
+                limit,
+                Quirks::empty(),
+            )
+        };
+        assert_eq!(anisotropy(None, 16.0), None);
+        assert_eq!(anisotropy(Some(4.0), 16.0), Some(4.0));
+        assert_eq!(anisotropy(Some(16.0), 16.0), Some(16.0));
+        assert_eq!(anisotropy(Some(32.0), 16.0), Some(16.0));
+        assert_eq!(anisotropy(Some(16.0), 4.0), Some(4.0));
+        assert_eq!(anisotropy(Some(0.5), 16.0), Some(1.0));
+        assert_eq!(anisotropy(Some(-1.0), 16.0), Some(1.0));
+    }
 }
@@ -363,6 +363,9 @@ impl AshFrom<(ImageUsage, PixelFormat)> for vk::ImageUsageFlags {
                 result |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
             }
         }
+        if usage.contains(ImageUsage::TRANSIENT) {
+            result |= vk::ImageUsageFlags::TRANSIENT_ATTACHMENT;
+        }
         result
     }
 }
@@ -388,6 +391,9 @@ impl FromAsh<vk::ImageUsageFlags> for ImageUsage {
         ) {
             result |= ImageUsage::TARGET;
         }
+        if usage.contains(vk::ImageUsageFlags::TRANSIENT_ATTACHMENT) {
+            result |= ImageUsage::TRANSIENT;
+        }
         result
     }
 }
@@ -488,6 +494,10 @@ impl AshFrom<BlendFactor> for vk::BlendFactor {
             BlendFactor::DstAlpha => vk::BlendFactor::DST_ALPHA,
             BlendFactor::OneMinusDstAlpha => vk::BlendFactor::ONE_MINUS_DST_ALPHA,
             BlendFactor::SrcAlphaSaturated => vk::BlendFactor::SRC_ALPHA_SATURATE,
+            BlendFactor::Src1Color => vk::BlendFactor::SRC1_COLOR,
+            BlendFactor::OneMinusSrc1Color => vk::BlendFactor::ONE_MINUS_SRC1_COLOR,
+            BlendFactor::Src1Alpha => vk::BlendFactor::SRC1_ALPHA,
+            BlendFactor::OneMinusSrc1Alpha => vk::BlendFactor::ONE_MINUS_SRC1_ALPHA,
             // BlendFactor::BlendColor => vk::BlendFactor::CONSTANT_COLOR,
             // BlendFactor::OneMinusBlendColor => vk::BlendFactor::ONE_MINUS_CONSTANT_COLOR,
         }
@@ -593,6 +603,10 @@ impl AshFrom<PipelineStage> for ash::vk::PipelineStageFlags {
             PipelineStage::ColorOutput => ash::vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
             PipelineStage::ComputeShader => ash::vk::PipelineStageFlags::COMPUTE_SHADER,
             PipelineStage::Transfer => ash::vk::PipelineStageFlags::TRANSFER,
+            PipelineStage::AccelerationStructureBuild => {
+                ash::vk::PipelineStageFlags::ACCELERATION_STRUCTURE_BUILD_KHR
+            }
+            PipelineStage::RayTracingShader => ash::vk::PipelineStageFlags::RAY_TRACING_SHADER_KHR,
         }
     }
 }
@@ -610,6 +624,8 @@ impl AshFrom<PipelineStages> for ash::vk::PipelineStageFlags {
             COLOR_OUTPUT => COLOR_ATTACHMENT_OUTPUT,
             COMPUTE_SHADER => COMPUTE_SHADER,
             TRANSFER => TRANSFER,
+            ACCELERATION_STRUCTURE_BUILD => ACCELERATION_STRUCTURE_BUILD_KHR,
+            RAY_TRACING_SHADER => RAY_TRACING_SHADER_KHR,
         ], stages)
     }
 }
@@ -0,0 +1,427 @@
+#[cfg(any(debug_assertions, feature = "debug"))]
+use std::ops::Range;
+
+use ash::vk;
+use hashbrown::HashSet;
+use smallvec::SmallVec;
+
+use crate::generic::ArgumentKind;
+
+use super::refs::Refs;
+
+/// Resources referenced by arguments, split by access kind.
+pub struct Accesses {
+    reads: Refs,
+    writes: Refs,
+
+    /// Images referenced by each field.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    images: Vec<ImageAccess>,
+}
+
+impl Accesses {
+    pub fn new() -> Self {
+        Accesses {
+            reads: Refs::new(),
+            writes: Refs::new(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            images: Vec::new(),
+        }
+    }
+
+    /// Adds references of the argument field.
+    ///
+    /// Storage arguments are considered written by the shader,
+    /// all others are only read.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn add_field(
+        &mut self,
+        name: &'static str,
+        kind: ArgumentKind,
+        add: impl FnOnce(&mut Refs),
+    ) {
+        let write = matches!(
+            kind,
+            ArgumentKind::StorageBuffer | ArgumentKind::StorageImage
+        );
+        let refs = if write {
+            &mut self.writes
+        } else {
+            &mut self.reads
+        };
+
+        // Refs keep each image once,
+        // so images of the field are collected separately.
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            let mut field = Refs::new();
+            add(&mut field);
+
+            self.images
+                .extend(field.images().iter().map(|image| ImageAccess {
+                    field: name,
+                    image: image.handle(),
+                    name: image.name().into(),
+                    levels: image.base_level()..image.base_level() + image.levels(),
+                    layers: image.base_layer()..image.base_layer() + image.layers(),
+                    write,
+                }));
+
+            refs.append(&field);
+        }
+
+        #[cfg(not(any(debug_assertions, feature = "debug")))]
+        {
+            add(refs);
+            let _ = name;
+        }
+    }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn images(&self) -> &[ImageAccess] {
+        &self.images
+    }
+
+    /// Resources that are only read.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn reads(&self) -> &Refs {
+        &self.reads
+    }
+}
+
+/// Image subresources accessed by an argument field.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[derive(Clone)]
+pub(super) struct ImageAccess {
+    field: &'static str,
+    image: vk::Image,
+    name: Box<str>,
+    levels: Range<u32>,
+    layers: Range<u32>,
+    write: bool,
+}
+
+#[cfg(any(debug_assertions, feature = "debug"))]
+impl ImageAccess {
+    /// Checks if one access reads and another writes same subresources.
+    fn conflicts(&self, other: &Self) -> bool {
+        self.write != other.write
+            && self.image == other.image
+            && self.levels.start < other.levels.end
+            && other.levels.start < self.levels.end
+            && self.layers.start < other.layers.end
+            && other.layers.start < self.layers.end
+    }
+}
+
+/// Validates that currently bound argument groups don't read
+/// and write the same image subresources.
+///
+/// Shaders may observe stale data in such case,
+/// accesses must be split into separate commands with a barrier between them.
+///
+/// Groups are checked on draw or dispatch,
+/// so groups may be rebound in any order between commands.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[derive(Default)]
+pub(super) struct AliasTracker {
+    groups: Vec<Vec<ImageAccess>>,
+
+    /// Bound groups were checked since last change.
+    checked: bool,
+}
+
+#[cfg(any(debug_assertions, feature = "debug"))]
+impl AliasTracker {
+    /// Replaces image accesses of the argument group.
+    pub fn bind(&mut self, group: u32, images: &[ImageAccess]) {
+        let group = group as usize;
+        if self.groups.len() <= group {
+            self.groups.resize_with(group + 1, Vec::new);
+        }
+        self.groups[group].clear();
+        self.groups[group].extend_from_slice(images);
+        self.checked = false;
+    }
+
+    /// Checks accesses of all bound groups.
+    /// Called before draw or dispatch.
+    ///
+    /// Panics if any two accesses conflict.
+    pub fn check(&mut self) {
+        if self.checked {
+            return;
+        }
+
+        let accesses = self
+            .groups
+            .iter()
+            .enumerate()
+            .flat_map(|(g, accesses)| accesses.iter().map(move |access| (g, access)));
+
+        for (idx, (group, access)) in accesses.clone().enumerate() {
+            for (other_group, other) in accesses.clone().take(idx) {
+                if access.conflicts(other) {
+                    let (read, read_group, write, write_group) = if access.write {
+                        (other, other_group, access, group)
+                    } else {
+                        (access, group, other, other_group)
+                    };
+
+                    panic!(
+                        "Image '{}' is read by field `{}` of argument group {} \
+                         and written by field `{}` of argument group {} \
+                         with overlapping levels and layers. \
+                         Reads may observe stale data, \
+                         split the accesses into separate commands with a barrier between them",
+                        access.name, read.field, read_group, write.field, write_group,
+                    );
+                }
+            }
+        }
+
+        self.checked = true;
+    }
+
+    /// Forgets all bound groups.
+    /// Called when pipeline is bound.
+    pub fn reset(&mut self) {
+        self.groups.clear();
+        self.checked = false;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Resource {
+    Buffer(vk::Buffer),
+    Image(vk::Image),
+}
+
+fn resources(refs: &Refs) -> impl Iterator<Item = Resource> + '_ {
+    let buffers = refs.buffers().iter().map(|b| Resource::Buffer(b.handle()));
+    let images = refs.images().iter().map(|i| Resource::Image(i.handle()));
+    buffers.chain(images)
+}
+
+#[derive(Default)]
+struct GroupAccesses {
+    reads: SmallVec<[Resource; 4]>,
+    writes: SmallVec<[Resource; 4]>,
+}
+
+/// Tracks resources accessed by dispatches within one compute encoder
+/// to insert barriers only where previous dispatches may conflict.
+#[derive(Default)]
+pub(super) struct HazardTracker {
+    /// Accesses of currently bound argument groups.
+    groups: Vec<GroupAccesses>,
+
+    /// Resources read since last barrier.
+    read: HashSet<Resource>,
+
+    /// Resources written since last barrier.
+    written: HashSet<Resource>,
+}
+
+impl HazardTracker {
+    /// Replaces accesses of the argument group.
+    pub fn bind(&mut self, group: u32, accesses: &Accesses) {
+        self.bind_resources(
+            group,
+            resources(&accesses.reads),
+            resources(&accesses.writes),
+        );
+    }
+
+    fn bind_resources(
+        &mut self,
+        group: u32,
+        reads: impl Iterator<Item = Resource>,
+        writes: impl Iterator<Item = Resource>,
+    ) {
+        let group = group as usize;
+        if self.groups.len() <= group {
+            self.groups.resize_with(group + 1, GroupAccesses::default);
+        }
+
+        let group = &mut self.groups[group];
+        group.reads.clear();
+        group.reads.extend(reads);
+        group.writes.clear();
+        group.writes.extend(writes);
+    }
+
+    /// Forgets accesses of previous dispatches.
+    /// Called when barrier is recorded.
+    pub fn reset(&mut self) {
+        self.read.clear();
+        self.written.clear();
+    }
+
+    /// Records accesses of the next dispatch.
+    ///
+    /// Returns `true` if barrier must be recorded before it.
+    /// That is when it reads resources written by previous dispatches
+    /// or writes resources accessed by them.
+    pub fn dispatch(&mut self) -> bool {
+        let hazard = self.groups.iter().any(|group| {
+            group.reads.iter().any(|r| self.written.contains(r))
+                || group
+                    .writes
+                    .iter()
+                    .any(|r| self.written.contains(r) || self.read.contains(r))
+        });
+
+        if hazard {
+            self.reset();
+        }
+
+        for group in &self.groups {
+            self.read.extend(group.reads.iter().copied());
+            self.written.extend(group.writes.iter().copied());
+        }
+
+        hazard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ash::vk::Handle;
+
+    use super::*;
+
+    fn buffer(raw: u64) -> Resource {
+        Resource::Buffer(vk::Buffer::from_raw(raw))
+    }
+
+    fn bind(tracker: &mut HazardTracker, group: u32, reads: &[Resource], writes: &[Resource]) {
+        tracker.bind_resources(group, reads.iter().copied(), writes.iter().copied());
+    }
+
+    #[test]
+    fn read_after_write() {
+        let mut tracker = HazardTracker::default();
+
+        bind(&mut tracker, 0, &[buffer(1)], &[buffer(2)]);
+        assert!(!tracker.dispatch());
+
+        bind(&mut tracker, 0, &[buffer(2)], &[buffer(3)]);
+        assert!(tracker.dispatch());
+
+        bind(&mut tracker, 0, &[buffer(3)], &[buffer(4)]);
+        assert!(tracker.dispatch());
+    }
+
+    #[test]
+    fn independent_dispatches() {
+        let mut tracker = HazardTracker::default();
+
+        bind(&mut tracker, 0, &[buffer(1)], &[buffer(2)]);
+        assert!(!tracker.dispatch());
+
+        bind(&mut tracker, 0, &[buffer(1)], &[buffer(3)]);
+        assert!(!tracker.dispatch());
+
+        // Same dispatch repeated writes the same buffer again.
+        assert!(tracker.dispatch());
+    }
+
+    #[test]
+    fn write_after_read() {
+        let mut tracker = HazardTracker::default();
+
+        bind(&mut tracker, 0, &[buffer(1)], &[buffer(2)]);
+        assert!(!tracker.dispatch());
+
+        bind(&mut tracker, 0, &[], &[buffer(1)]);
+        assert!(tracker.dispatch());
+    }
+
+    #[test]
+    fn hazard_across_groups() {
+        let mut tracker = HazardTracker::default();
+
+        bind(&mut tracker, 0, &[], &[buffer(1)]);
+        bind(&mut tracker, 1, &[buffer(2)], &[]);
+        assert!(!tracker.dispatch());
+
+        bind(&mut tracker, 0, &[], &[buffer(3)]);
+        bind(&mut tracker, 1, &[buffer(1)], &[]);
+        assert!(tracker.dispatch());
+    }
+
+    #[test]
+    fn reset_by_barrier() {
+        let mut tracker = HazardTracker::default();
+
+        bind(&mut tracker, 0, &[], &[buffer(1)]);
+        assert!(!tracker.dispatch());
+
+        tracker.reset();
+
+        bind(&mut tracker, 0, &[buffer(1)], &[buffer(2)]);
+        assert!(!tracker.dispatch());
+    }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    fn access(field: &'static str, level: u32, write: bool) -> ImageAccess {
+        ImageAccess {
+            field,
+            image: vk::Image::from_raw(1),
+            name: "pyramid".into(),
+            levels: level..level + 1,
+            layers: 0..1,
+            write,
+        }
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    fn alias_disjoint_levels() {
+        let mut tracker = AliasTracker::default();
+        tracker.bind(0, &[access("src", 0, false)]);
+        tracker.bind(1, &[access("dst", 1, true)]);
+        tracker.check();
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[should_panic(expected = "read by field `src` of argument group 0 \
+                               and written by field `dst` of argument group 1")]
+    fn alias_across_groups() {
+        let mut tracker = AliasTracker::default();
+        tracker.bind(0, &[access("src", 0, false)]);
+        tracker.bind(1, &[access("dst", 0, true)]);
+        tracker.check();
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[should_panic(expected = "read by field `src` of argument group 0 \
+                               and written by field `dst` of argument group 0")]
+    fn alias_within_group() {
+        let mut tracker = AliasTracker::default();
+        tracker.bind(0, &[access("dst", 2, true), access("src", 2, false)]);
+        tracker.check();
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    fn alias_rebind_before_check() {
+        let mut tracker = AliasTracker::default();
+        tracker.bind(0, &[access("src", 0, false)]);
+        tracker.bind(1, &[access("dst", 1, true)]);
+        tracker.check();
+
+        // Each rebound group conflicts with the stale other group
+        // until it is rebound too.
+        tracker.bind(1, &[access("dst", 0, true)]);
+        tracker.bind(0, &[access("src", 1, false)]);
+        tracker.check();
+
+        tracker.bind(1, &[access("dst", 1, true)]);
+        tracker.bind(0, &[access("src", 0, false)]);
+        tracker.check();
+    }
+}
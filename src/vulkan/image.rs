@@ -2,17 +2,23 @@ use core::fmt;
 use std::{
     hash::{Hash, Hasher},
     mem::{size_of, ManuallyDrop},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Arc,
+    },
 };
 
+#[cfg(any(debug_assertions, feature = "debug"))]
+use std::sync::atomic::AtomicU8;
+
 use ash::vk;
 use gpu_alloc::MemoryBlock;
 use hashbrown::{hash_map::Entry, HashMap};
 use parking_lot::Mutex;
 
 use crate::generic::{
-    ArgumentKind, Automatic, ImageExtent, ImageUsage, OutOfMemory, PixelFormat, Sampled, Storage,
-    Swizzle, ViewDesc,
+    ArgumentKind, Automatic, ImageExtent, ImageSummary, ImageUsage, OutOfMemory, PixelFormat,
+    ResourceId, Sampled, Storage, Swizzle, ViewAspect, ViewDesc, ViewError,
 };
 
 use super::{
@@ -28,25 +34,60 @@ enum Flavor {
         block: ManuallyDrop<MemoryBlock<(vk::DeviceMemory, usize)>>,
         idx: usize,
     },
-    Swapchain,
+    Swapchain {
+        /// Set when a queue submits wait for acquire semaphore of the image's frame.
+        /// Cleared when the image is acquired again.
+        acquire_waited: Arc<AtomicBool>,
+    },
 }
 
 // Contains actual `vk::Image`
 struct ImageData {
     owner: WeakDevice,
+    id: ResourceId,
+    name: Box<str>,
     format: PixelFormat,
     usage: ImageUsage,
     extent: ImageExtent,
     layers: u32,
     levels: u32,
     flavor: Flavor,
-    views: Mutex<HashMap<ViewDesc, (vk::ImageView, usize)>>,
+    /// All views created for the image, including the default one.
+    /// Views are never destroyed before the image, so handles stay stable
+    /// and can be relied upon by render passes across frames.
+    views: Mutex<HashMap<ViewDesc, CachedView>>,
+
+    /// Tracks whether `init_image` was called for the image.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    init_state: AtomicU8,
+
+    /// Set when present of the swapchain image is recorded.
+    /// Cleared when the image is acquired again.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    presented: AtomicBool,
 }
 
+/// View of the image cached by its description.
+#[derive(Clone, Copy)]
+struct CachedView {
+    handle: vk::ImageView,
+    idx: usize,
+
+    /// Identifier shared by all `Image`s with this view.
+    id: ResourceId,
+}
+
+#[cfg(any(debug_assertions, feature = "debug"))]
+const IMAGE_UNINIT: u8 = 0;
+#[cfg(any(debug_assertions, feature = "debug"))]
+const IMAGE_INIT: u8 = 1;
+#[cfg(any(debug_assertions, feature = "debug"))]
+const IMAGE_INIT_WARNED: u8 = 2;
+
 impl Drop for ImageData {
     fn drop(&mut self) {
         self.owner
-            .drop_image_views(self.views.get_mut().values().map(|(_, idx)| *idx));
+            .drop_image_views(self.views.get_mut().values().map(|view| view.idx));
 
         if let Flavor::Device { block, idx } = &mut self.flavor {
             self.owner
@@ -57,6 +98,9 @@ impl Drop for ImageData {
 
 struct Inner {
     data: Arc<ImageData>,
+
+    /// Identifier of the view.
+    id: ResourceId,
     desc: ViewDesc,
     usage: ImageUsage,
     extent: ImageExtent,
@@ -107,6 +151,8 @@ impl DeviceOwned for Image {
 impl Image {
     fn build(
         owner: WeakDevice,
+        id: ResourceId,
+        name: &str,
         handle: vk::Image,
         view: vk::ImageView,
         view_idx: usize,
@@ -125,10 +171,18 @@ impl Image {
             base_level: 0,
             levels,
             swizzle: Swizzle::IDENTITY,
+            aspect: ViewAspect::Auto,
         };
 
         let mut views = HashMap::new();
-        views.insert(desc, (view, view_idx));
+        views.insert(
+            desc,
+            CachedView {
+                handle: view,
+                idx: view_idx,
+                id,
+            },
+        );
 
         Image {
             handle,
@@ -136,6 +190,8 @@ impl Image {
             inner: Arc::new(Inner {
                 data: Arc::new(ImageData {
                     owner: owner.clone(),
+                    id,
+                    name: name.into(),
                     extent,
                     format,
                     usage,
@@ -143,7 +199,12 @@ impl Image {
                     levels,
                     flavor,
                     views: Mutex::new(views),
+                    #[cfg(any(debug_assertions, feature = "debug"))]
+                    init_state: AtomicU8::new(IMAGE_UNINIT),
+                    #[cfg(any(debug_assertions, feature = "debug"))]
+                    presented: AtomicBool::new(false),
                 }),
+                id,
                 desc,
                 extent,
                 usage,
@@ -154,6 +215,8 @@ impl Image {
 
     pub(super) fn new(
         owner: WeakDevice,
+        id: ResourceId,
+        name: &str,
         handle: vk::Image,
         view: vk::ImageView,
         view_idx: usize,
@@ -167,6 +230,8 @@ impl Image {
     ) -> Self {
         Image::build(
             owner,
+            id,
+            name,
             handle,
             view,
             view_idx,
@@ -184,6 +249,7 @@ impl Image {
 
     pub(super) fn from_swapchain_image(
         owner: WeakDevice,
+        id: ResourceId,
         handle: vk::Image,
         view: vk::ImageView,
         view_idx: usize,
@@ -193,6 +259,8 @@ impl Image {
     ) -> Self {
         Image::build(
             owner,
+            id,
+            "swapchain",
             handle,
             view,
             view_idx,
@@ -201,12 +269,30 @@ impl Image {
             usage,
             1,
             1,
-            Flavor::Swapchain,
+            Flavor::Swapchain {
+                acquire_waited: Arc::new(AtomicBool::new(false)),
+            },
         )
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
-    pub(super) fn get_view(&self, device: &Device, desc: ViewDesc) -> Result<Image, OutOfMemory> {
+    pub(super) fn get_view(&self, device: &Device, desc: ViewDesc) -> Result<Image, ViewError> {
+        desc.check_bounds(
+            &self.inner.data.name,
+            self.inner.desc.layers,
+            self.inner.desc.levels,
+        )?;
+        desc.check_aspect(&self.inner.data.name)?;
+
+        // Swapchain images are created without mutable format.
+        if self.is_swapchain() && desc.format != self.inner.data.format {
+            return Err(ViewError::SwapchainFormat {
+                image: self.inner.data.name.to_string(),
+                format: self.inner.data.format,
+                view_format: desc.format,
+            });
+        }
+
         let desc = ViewDesc {
             base_layer: desc.base_layer + self.inner.desc.base_layer,
             base_level: desc.base_level + self.inner.desc.base_level,
@@ -218,19 +304,30 @@ impl Image {
         }
 
         let view = match self.inner.data.views.lock().entry(desc) {
-            Entry::Occupied(entry) => entry.get().0,
+            Entry::Occupied(entry) => *entry.get(),
             Entry::Vacant(entry) => {
-                let (view, idx) =
+                device.portability().check_view(
+                    &self.inner.data.name,
+                    self.inner.data.format,
+                    &desc,
+                )?;
+
+                let (handle, idx) =
                     device.new_image_view(self.handle, self.inner.extent.into_ash(), desc)?;
-                entry.insert((view, idx)).0
+                *entry.insert(CachedView {
+                    handle,
+                    idx,
+                    id: device.new_resource_id(),
+                })
             }
         };
 
         Ok(Image {
             handle: self.handle,
-            view,
+            view: view.handle,
             inner: Arc::new(Inner {
                 data: self.inner.data.clone(),
+                id: view.id,
                 desc,
                 extent: self.inner.extent,
                 usage: self.inner.usage,
@@ -249,6 +346,93 @@ impl Image {
         self.view
     }
 
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn name(&self) -> &str {
+        &self.inner.data.name
+    }
+
+    /// Returns number of references to the image memory, including all views.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner.data)
+    }
+
+    /// Marks image as initialized.
+    /// Warns once if image is initialized again and its content is discarded.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn mark_initialized(&self) {
+        if self.is_swapchain() {
+            // Swapchain images are initialized each frame.
+            return;
+        }
+
+        let state = &self.inner.data.init_state;
+        if let Err(IMAGE_INIT) = state.compare_exchange(IMAGE_UNINIT, IMAGE_INIT, Relaxed, Relaxed)
+        {
+            if state
+                .compare_exchange(IMAGE_INIT, IMAGE_INIT_WARNED, Relaxed, Relaxed)
+                .is_ok()
+            {
+                tracing::warn!(
+                    "Image '{}' is initialized again and its content is discarded. Use `sync_image` to preserve the content",
+                    self.inner.data.name
+                );
+            }
+        }
+    }
+
+    /// Marks swapchain image as presented or acquired again.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn set_presented(&self, presented: bool) {
+        self.inner.data.presented.store(presented, Relaxed);
+    }
+
+    /// Panics if present of the image was recorded and the image wasn't acquired since.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub(super) fn check_not_presented(&self, usage: &str) {
+        assert!(
+            !self.inner.data.presented.load(Relaxed),
+            "Swapchain image is used by {usage} after its frame was presented. \
+            `present` transitions the image to present layout and returns it to the swapchain, \
+            so all commands that use the frame's image must be recorded before the frame is presented"
+        );
+    }
+
+    /// Returns `true` if image is owned by a swapchain.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn is_swapchain(&self) -> bool {
+        matches!(self.inner.data.flavor, Flavor::Swapchain { .. })
+    }
+
+    /// Returns flag that is set when a queue submits wait
+    /// for acquire semaphore of the swapchain image's frame.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn acquire_waited_flag(&self) -> Option<&Arc<AtomicBool>> {
+        match &self.inner.data.flavor {
+            Flavor::Swapchain { acquire_waited } => Some(acquire_waited),
+            Flavor::Device { .. } => None,
+        }
+    }
+
+    /// Clears the flag when the swapchain image is acquired again.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn reset_acquire_waited(&self) {
+        if let Some(flag) = self.acquire_waited_flag() {
+            flag.store(false, Relaxed);
+        }
+    }
+
+    /// Returns `true` if a queue submitted wait
+    /// for acquire semaphore of the swapchain image's frame.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn acquire_waited(&self) -> bool {
+        self.acquire_waited_flag()
+            .map_or(false, |flag| flag.load(Relaxed))
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     pub(super) fn base_layer(&self) -> u32 {
         self.inner.desc.base_layer
@@ -260,8 +444,38 @@ impl Image {
     }
 }
 
+#[hidden_trait::expose]
+impl crate::traits::Resource for Image {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn id(&self) -> ResourceId {
+        self.inner.id
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{:#?}",
+            ImageSummary {
+                id: self.inner.id,
+                name: Some(&self.inner.data.name),
+                extent: self.extent(),
+                format: self.format(),
+                usage: self.usage(),
+                levels: self.levels(),
+                layers: self.layers(),
+            }
+        )
+    }
+}
+
 #[hidden_trait::expose]
 impl crate::traits::Image for Image {
+    type Device = Device;
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn image_id(&self) -> ResourceId {
+        self.inner.data.id
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn format(&self) -> PixelFormat {
         self.inner.desc.format
@@ -288,10 +502,42 @@ impl crate::traits::Image for Image {
     }
 
     #[cfg_attr(feature = "inline-more", inline(always))]
-    fn view(&self, device: &Device, desc: ViewDesc) -> Result<Image, OutOfMemory> {
+    fn view(&self, device: &Device, desc: ViewDesc) -> Result<Image, ViewError> {
         self.get_view(device, desc)
     }
 
+    fn srgb_view(&self, device: &Device) -> Result<Image, ViewError> {
+        let format = self.inner.desc.format;
+        let Some(srgb) = format.srgb_variant() else {
+            return Err(ViewError::NoSrgbVariant {
+                image: self.inner.data.name.to_string(),
+                format,
+            });
+        };
+
+        self.get_view(
+            device,
+            ViewDesc {
+                format: srgb,
+                base_layer: 0,
+                base_level: 0,
+                ..self.inner.desc
+            },
+        )
+    }
+
+    fn linear_view(&self, device: &Device) -> Result<Image, ViewError> {
+        self.get_view(
+            device,
+            ViewDesc {
+                format: self.inner.desc.format.linear_variant(),
+                base_layer: 0,
+                base_level: 0,
+                ..self.inner.desc
+            },
+        )
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn detached(&self) -> bool {
         // If strong is 1, it cannot be changed by another thread if called owns
@@ -332,6 +578,13 @@ impl ArgumentsField<Sampled> for Image {
 
     #[cfg_attr(feature = "inline-more", inline(always))]
     fn update(&self) -> vk::DescriptorImageInfo {
+        debug_assert!(
+            !self.inner.desc.is_combined_depth_stencil(),
+            "Image '{}' with format {:?} can't be sampled through a view with both depth and stencil aspects, create view with `ViewAspect::DepthOnly` or `ViewAspect::StencilOnly`",
+            self.inner.data.name,
+            self.inner.desc.format
+        );
+
         vk::DescriptorImageInfo {
             sampler: vk::Sampler::null(),
             image_view: self.view,
@@ -12,11 +12,17 @@ use hashbrown::HashMap;
 use khr::get_physical_device_properties2;
 
 use crate::generic::{
-    Capabilities, CreateError, DeviceCapabilities, DeviceDesc, FamilyCapabilities, Features,
-    LoadError, OutOfMemory,
+    Capabilities, CreateError, DeviceCapabilities, DeviceDesc, DeviceLimits, FamilyCapabilities,
+    Features, LoadError, OutOfMemory, Quirks,
 };
 
-use super::{device::Device, from::*, handle_host_oom, unexpected_error, Queue, Version};
+use super::{
+    device::{Device, Maintenance4, PortabilitySubset, VertexDivisor},
+    from::*,
+    handle_host_oom,
+    linear::LinearAllocator,
+    unexpected_error, Queue, Version,
+};
 
 macro_rules! extension_name {
     ($name:literal) => {
@@ -51,11 +57,49 @@ pub struct Instance {
     debug_utils: Option<ash::ext::debug_utils::Instance>,
 
     surface: Option<ash::khr::surface::Instance>,
+    platform_surface: PlatformSurface,
+}
 
+/// Window system specific surface extensions.
+/// Only extensions supported by the instance are loaded.
+#[derive(Clone, Default)]
+pub(super) struct PlatformSurface {
     #[cfg(target_os = "windows")]
-    win32_surface: Option<ash::khr::win32_surface::Instance>,
+    pub win32: Option<ash::khr::win32_surface::Instance>,
+
+    #[cfg(all(unix, not(target_os = "android")))]
+    pub xlib: Option<ash::khr::xlib_surface::Instance>,
+
+    #[cfg(all(unix, not(target_os = "android")))]
+    pub xcb: Option<ash::khr::xcb_surface::Instance>,
+
+    #[cfg(all(unix, not(target_os = "android")))]
+    pub wayland: Option<ash::khr::wayland_surface::Instance>,
+
+    #[cfg(target_os = "android")]
+    pub android: Option<ash::khr::android_surface::Instance>,
 }
 
+#[cfg(target_os = "windows")]
+const PLATFORM_SURFACE_EXTENSIONS: &[&str] = &["VK_KHR_win32_surface"];
+
+#[cfg(all(unix, not(target_os = "android")))]
+const PLATFORM_SURFACE_EXTENSIONS: &[&str] = &[
+    "VK_KHR_xlib_surface",
+    "VK_KHR_xcb_surface",
+    "VK_KHR_wayland_surface",
+];
+
+#[cfg(target_os = "android")]
+const PLATFORM_SURFACE_EXTENSIONS: &[&str] = &["VK_KHR_android_surface"];
+
+/// Upper bound of bindless table capacity.
+///
+/// Descriptors of the table layout count against per-stage limits
+/// that are shared with other sampled images of the pipeline,
+/// so table doesn't take the whole limit on devices that report huge ones.
+const MAX_BINDLESS_TABLE_SIZE: u32 = 1 << 16;
+
 impl fmt::Debug for Instance {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Instance")
@@ -70,16 +114,65 @@ impl fmt::Debug for Instance {
 #[derive(Debug)]
 pub(crate) enum LoadErrorKind {
     OutOfMemory,
-    LoadingError(ash::LoadingError),
+    LibraryNotFound(ash::LoadingError),
+
+    /// Drivers support Vulkan version older than required.
+    /// `found` is `None` if no driver supports `required` version
+    /// and supported version is unknown.
+    UnsupportedApiVersion {
+        found: Option<Version>,
+        required: Version,
+    },
+    MissingLayers(Vec<String>),
     InitializationFailed,
 }
 
+impl LoadErrorKind {
+    pub(crate) fn is_library_not_found(&self) -> bool {
+        matches!(self, LoadErrorKind::LibraryNotFound(_))
+    }
+
+    pub(crate) fn is_unsupported(&self) -> bool {
+        matches!(self, LoadErrorKind::UnsupportedApiVersion { .. })
+    }
+
+    pub(crate) fn missing_layers(&self) -> &[String] {
+        match self {
+            LoadErrorKind::MissingLayers(names) => names,
+            _ => &[],
+        }
+    }
+}
+
 impl fmt::Display for LoadErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LoadErrorKind::LoadingError(err) => {
-                write!(f, "failed to load Vulkan entry points: {}", err)
-            }
+            LoadErrorKind::LibraryNotFound(err) => write!(
+                f,
+                "Vulkan library is not found ({err}), install or update graphics drivers"
+            ),
+            LoadErrorKind::UnsupportedApiVersion {
+                found: Some(found),
+                required,
+            } => write!(
+                f,
+                "installed Vulkan drivers support Vulkan {found}, \
+                 at least Vulkan {required} is required, update graphics drivers"
+            ),
+            LoadErrorKind::UnsupportedApiVersion {
+                found: None,
+                required,
+            } => write!(
+                f,
+                "installed Vulkan drivers don't support Vulkan {required}, \
+                 update graphics drivers"
+            ),
+            LoadErrorKind::MissingLayers(names) => write!(
+                f,
+                "failed to load Vulkan layers: {}, \
+                 reinstall them or remove them from the system",
+                names.join(", ")
+            ),
             LoadErrorKind::OutOfMemory => write!(f, "{OutOfMemory}"),
             LoadErrorKind::InitializationFailed => write!(f, "initialization failed"),
         }
@@ -92,6 +185,16 @@ pub(crate) enum CreateErrorKind {
     InitializationFailed,
     TooManyObjects,
     DeviceLost,
+    MissingExtensions(Vec<String>),
+}
+
+impl CreateErrorKind {
+    pub(crate) fn missing_extensions(&self) -> &[String] {
+        match self {
+            CreateErrorKind::MissingExtensions(names) => names,
+            _ => &[],
+        }
+    }
 }
 
 impl fmt::Display for CreateErrorKind {
@@ -101,6 +204,12 @@ impl fmt::Display for CreateErrorKind {
             CreateErrorKind::InitializationFailed => write!(f, "initialization failed"),
             CreateErrorKind::TooManyObjects => write!(f, "too many objects"),
             CreateErrorKind::DeviceLost => write!(f, "device lost"),
+            CreateErrorKind::MissingExtensions(names) => write!(
+                f,
+                "graphics driver doesn't support required extensions: {}, \
+                 update graphics drivers",
+                names.join(", ")
+            ),
         }
     }
 }
@@ -123,6 +232,20 @@ unsafe fn find_extension<'a>(
     })
 }
 
+/// Oldest Vulkan version supported by the backend.
+const MIN_API_VERSION: Version = Version::V1_0;
+
+/// Checks that Vulkan version reported by the loader is supported.
+fn check_api_version(found: Version) -> Result<(), LoadErrorKind> {
+    if found < MIN_API_VERSION {
+        return Err(LoadErrorKind::UnsupportedApiVersion {
+            found: Some(found),
+            required: MIN_API_VERSION,
+        });
+    }
+    Ok(())
+}
+
 fn engine_version() -> u32 {
     let major = env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap();
     let minor = env!("CARGO_PKG_VERSION_MINOR").parse().unwrap();
@@ -138,8 +261,8 @@ impl Instance {
         // This call is unsafe and cannot be made completely safe.
         // It loads dynamic library and some function pointers.
         // The library must behave correctly in order for the rest of the code to be safe.
-        let entry =
-            unsafe { Entry::load() }.map_err(|err| LoadError(LoadErrorKind::LoadingError(err)))?;
+        let entry = unsafe { Entry::load() }
+            .map_err(|err| LoadError(LoadErrorKind::LibraryNotFound(err)))?;
 
         // Collect instance layers and extensions.
 
@@ -153,6 +276,7 @@ impl Instance {
             })?;
 
         let mut enabled_layer_names = Vec::new();
+        let mut enabled_layers: Vec<&str> = Vec::new();
 
         let extensions = unsafe { entry.enumerate_instance_extension_properties(None) }.map_err(
             |err| match err {
@@ -170,6 +294,7 @@ impl Instance {
         #[cfg(any(debug_assertions, feature = "debug"))]
         if let Some(layer) = unsafe { find_layer(&layers, "VK_LAYER_KHRONOS_validation") } {
             enabled_layer_names.push(layer.layer_name.as_ptr());
+            enabled_layers.push("VK_LAYER_KHRONOS_validation");
         }
 
         #[cfg(any(debug_assertions, feature = "debug"))]
@@ -182,14 +307,19 @@ impl Instance {
         }
 
         let mut has_surface = false;
+        let mut platform_surface_extensions = Vec::new();
         if let Some(surface_extension) = unsafe { find_extension(&extensions, "VK_KHR_surface") } {
-            #[cfg(target_os = "windows")]
-            let name = "VK_KHR_win32_surface";
+            // Enable all window systems available on the platform.
+            for &name in PLATFORM_SURFACE_EXTENSIONS {
+                if let Some(platform_extension) = unsafe { find_extension(&extensions, name) } {
+                    enabled_extension_names.push(platform_extension.extension_name.as_ptr());
+                    platform_surface_extensions.push(name);
+                }
+            }
 
-            if let Some(platform_extension) = unsafe { find_extension(&extensions, name) } {
+            if !platform_surface_extensions.is_empty() {
                 has_surface = true;
                 enabled_extension_names.push(surface_extension.extension_name.as_ptr());
-                enabled_extension_names.push(platform_extension.extension_name.as_ptr());
 
                 if let Some(surface_maintenance1) =
                     unsafe { find_extension(&extensions, "VK_EXT_surface_maintenance1") }
@@ -215,6 +345,8 @@ impl Instance {
             patch: vk::api_version_patch(api_version),
         };
 
+        check_api_version(version).map_err(LoadError)?;
+
         let mut has_physical_device_properties2 = false;
         if version < Version::V1_1 {
             if let Some(extension) =
@@ -225,11 +357,21 @@ impl Instance {
             }
         }
 
+        // Portability drivers, e.g. MoltenVK, are enumerated only when requested.
+        let mut instance_flags = vk::InstanceCreateFlags::empty();
+        if let Some(extension) =
+            unsafe { find_extension(&extensions, "VK_KHR_portability_enumeration") }
+        {
+            enabled_extension_names.push(extension.extension_name.as_ptr());
+            instance_flags |= vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR;
+        }
+
         // Create the Vulkan instance.
 
         let result = unsafe {
             entry.create_instance(
                 &vk::InstanceCreateInfo::default()
+                    .flags(instance_flags)
                     .application_info(
                         &vk::ApplicationInfo::default()
                             .api_version(api_version)
@@ -260,9 +402,17 @@ impl Instance {
             vk::Result::ERROR_INITIALIZATION_FAILED => {
                 LoadError(LoadErrorKind::InitializationFailed)
             }
-            vk::Result::ERROR_LAYER_NOT_PRESENT => unreachable!("Layers were checked"),
+            // Layer is enumerated but its library failed to load.
+            vk::Result::ERROR_LAYER_NOT_PRESENT => LoadError(LoadErrorKind::MissingLayers(
+                enabled_layers.iter().map(|name| name.to_string()).collect(),
+            )),
             vk::Result::ERROR_EXTENSION_NOT_PRESENT => unreachable!("Extensions were checked"),
-            vk::Result::ERROR_INCOMPATIBLE_DRIVER => unreachable!("Version was checked"),
+            vk::Result::ERROR_INCOMPATIBLE_DRIVER => {
+                LoadError(LoadErrorKind::UnsupportedApiVersion {
+                    found: None,
+                    required: version,
+                })
+            }
             err => unexpected_error(err),
         })?;
 
@@ -277,15 +427,39 @@ impl Instance {
 
         // Init surface extension functions
         let mut surface = None;
-
-        #[cfg(target_os = "windows")]
-        let mut win32_surface = None;
+        let mut platform_surface = PlatformSurface::default();
         if has_surface {
             surface = Some(ash::khr::surface::Instance::new(&entry, &instance));
 
-            #[cfg(target_os = "windows")]
-            {
-                win32_surface = Some(ash::khr::win32_surface::Instance::new(&entry, &instance));
+            for name in platform_surface_extensions {
+                match name {
+                    #[cfg(target_os = "windows")]
+                    "VK_KHR_win32_surface" => {
+                        platform_surface.win32 =
+                            Some(ash::khr::win32_surface::Instance::new(&entry, &instance));
+                    }
+                    #[cfg(all(unix, not(target_os = "android")))]
+                    "VK_KHR_xlib_surface" => {
+                        platform_surface.xlib =
+                            Some(ash::khr::xlib_surface::Instance::new(&entry, &instance));
+                    }
+                    #[cfg(all(unix, not(target_os = "android")))]
+                    "VK_KHR_xcb_surface" => {
+                        platform_surface.xcb =
+                            Some(ash::khr::xcb_surface::Instance::new(&entry, &instance));
+                    }
+                    #[cfg(all(unix, not(target_os = "android")))]
+                    "VK_KHR_wayland_surface" => {
+                        platform_surface.wayland =
+                            Some(ash::khr::wayland_surface::Instance::new(&entry, &instance));
+                    }
+                    #[cfg(target_os = "android")]
+                    "VK_KHR_android_surface" => {
+                        platform_surface.android =
+                            Some(ash::khr::android_surface::Instance::new(&entry, &instance));
+                    }
+                    _ => unreachable!(),
+                }
             }
         }
 
@@ -316,6 +490,13 @@ impl Instance {
             let mut features11 = vk::PhysicalDeviceVulkan11Features::default();
             let mut features12 = vk::PhysicalDeviceVulkan12Features::default();
             let mut features13 = vk::PhysicalDeviceVulkan13Features::default();
+            let mut robustness2 = vk::PhysicalDeviceRobustness2FeaturesEXT::default();
+            let mut device_fault = vk::PhysicalDeviceFaultFeaturesEXT::default();
+
+            let has_robustness2 =
+                unsafe { find_extension(&extensions, "VK_EXT_robustness2") }.is_some();
+            let has_device_fault =
+                unsafe { find_extension(&extensions, "VK_EXT_device_fault") }.is_some();
 
             if version < Version::V1_1 {
                 if get_physical_device_properties2.is_some() {
@@ -335,11 +516,37 @@ impl Instance {
                 if version >= Version::V1_3 {
                     features = features.push_next(&mut features13);
                 }
+                if has_robustness2 {
+                    features = features.push_next(&mut robustness2);
+                }
+                if has_device_fault {
+                    features = features.push_next(&mut device_fault);
+                }
                 unsafe {
                     instance.get_physical_device_features2(device, &mut features);
                 }
             }
 
+            let sampler_anisotropy = features.features.sampler_anisotropy != 0;
+            let dual_src_blend = features.features.dual_src_blend != 0;
+            let wide_lines = features.features.wide_lines != 0;
+            let robust_buffer_access = features.features.robust_buffer_access != 0;
+            let shader_float64 = features.features.shader_float64 != 0;
+            let shader_int64 = features.features.shader_int64 != 0;
+            let robust_image_access = robustness2.robust_image_access2 != 0;
+            let device_fault = device_fault.device_fault != 0;
+
+            // Bindless tables are variable-sized partially bound arrays
+            // of sampled images updated after bind.
+            let descriptor_indexing = version >= Version::V1_2
+                && features12.descriptor_indexing != 0
+                && features12.shader_sampled_image_array_non_uniform_indexing != 0
+                && features12.descriptor_binding_sampled_image_update_after_bind != 0
+                && features12.descriptor_binding_update_unused_while_pending != 0
+                && features12.descriptor_binding_partially_bound != 0
+                && features12.descriptor_binding_variable_descriptor_count != 0
+                && features12.runtime_descriptor_array != 0;
+
             if version < Version::V1_1 {
                 if unsafe { find_extension(&extensions, "VK_KHR_descriptor_update_template") }
                     .is_none()
@@ -370,16 +577,55 @@ impl Instance {
             //     }
             // }
 
-            if unsafe { find_extension(&extensions, "VK_KHR_push_descriptor") }.is_none() {
-                // Skip devices that don't support push descriptors.
-                continue;
+            let mut features = Features::empty();
+
+            if sampler_anisotropy {
+                features |= Features::SAMPLER_ANISOTROPY;
             }
 
-            let mut features = Features::empty();
+            if dual_src_blend {
+                features |= Features::DUAL_SOURCE_BLEND;
+            }
+
+            if wide_lines {
+                features |= Features::WIDE_LINES;
+            }
+
+            if robust_buffer_access {
+                features |= Features::ROBUST_BUFFER_ACCESS;
+            }
+
+            if robust_image_access {
+                features |= Features::ROBUST_IMAGE_ACCESS;
+            }
+
+            if device_fault {
+                features |= Features::DEVICE_FAULT;
+            }
+
+            if shader_float64 {
+                features |= Features::SHADER_FLOAT64;
+            }
+
+            if shader_int64 {
+                features |= Features::SHADER_INT64;
+            }
 
             if has_surface {
                 if unsafe { find_extension(&extensions, "VK_KHR_swapchain") }.is_some() {
                     features |= Features::SURFACE;
+
+                    if unsafe { find_extension(&extensions, "VK_KHR_incremental_present") }
+                        .is_some()
+                    {
+                        features |= Features::INCREMENTAL_PRESENT;
+                    }
+
+                    if unsafe { find_extension(&extensions, "VK_EXT_swapchain_maintenance1") }
+                        .is_some()
+                    {
+                        features |= Features::PRESENT_FENCES;
+                    }
                 }
             }
 
@@ -433,8 +679,44 @@ impl Instance {
                     .collect()
             };
 
+            let limits = &properties.properties.limits;
+            let mut limits = DeviceLimits {
+                max_argument_groups: limits.max_bound_descriptor_sets,
+                max_uniform_buffer_range: u64::from(limits.max_uniform_buffer_range),
+                max_storage_buffer_range: u64::from(limits.max_storage_buffer_range),
+                max_bindless_table_size: 0,
+            };
+
+            if descriptor_indexing {
+                features |= Features::DESCRIPTOR_INDEXING;
+                limits.max_bindless_table_size = properties12
+                    .max_per_stage_descriptor_update_after_bind_sampled_images
+                    .min(properties12.max_descriptor_set_update_after_bind_sampled_images)
+                    .min(MAX_BINDLESS_TABLE_SIZE);
+            }
+
+            // Naga may generate any subgroup operation, all of them must be supported.
+            let subgroup_stages = vk::ShaderStageFlags::FRAGMENT | vk::ShaderStageFlags::COMPUTE;
+            let subgroup_operations = vk::SubgroupFeatureFlags::BASIC
+                | vk::SubgroupFeatureFlags::VOTE
+                | vk::SubgroupFeatureFlags::ARITHMETIC
+                | vk::SubgroupFeatureFlags::BALLOT
+                | vk::SubgroupFeatureFlags::SHUFFLE
+                | vk::SubgroupFeatureFlags::SHUFFLE_RELATIVE;
+            if version >= Version::V1_1
+                && properties11
+                    .subgroup_supported_stages
+                    .contains(subgroup_stages)
+                && properties11
+                    .subgroup_supported_operations
+                    .contains(subgroup_operations)
+            {
+                features |= Features::SUBGROUP;
+            }
+
             device_caps.push(DeviceCapabilities {
-                features: Features::empty(),
+                features,
+                limits,
                 families,
             })
         }
@@ -454,20 +736,27 @@ impl Instance {
             #[cfg(any(debug_assertions, feature = "debug"))]
             debug_utils,
             surface,
-
-            #[cfg(target_os = "windows")]
-            win32_surface,
+            platform_surface,
         })
     }
 }
 
 #[hidden_trait::expose]
 impl crate::traits::Instance for Instance {
+    type Device = Device;
+    type Queue = Queue;
+
     fn capabilities(&self) -> &Capabilities {
         &self.capabilities
     }
 
     fn create(&self, desc: DeviceDesc) -> Result<(Device, Vec<Queue>), CreateError> {
+        trace_timed_span!(
+            "mev.create_device",
+            idx = desc.idx,
+            queues = desc.queues.len()
+        );
+
         let physical_device = self.devices[desc.idx];
         let device_caps = &self.capabilities.devices[desc.idx];
 
@@ -530,8 +819,42 @@ impl crate::traits::Instance for Instance {
                 .get_physical_device_memory_properties(physical_device)
         };
 
+        let device_local_types = memory_properties.memory_types
+            [..memory_properties.memory_type_count as usize]
+            .iter()
+            .enumerate()
+            .filter(|(_, memory_type)| {
+                memory_type
+                    .property_flags
+                    .contains(vk::MemoryPropertyFlags::DEVICE_LOCAL)
+            })
+            .fold(0u32, |mask, (idx, _)| mask | (1 << idx));
+
+        // `maxBufferSize` is reported since Vulkan 1.3.
+        // Older devices don't declare the limit.
+        let max_buffer_size = if self.version >= Version::V1_3 {
+            let mut properties13 = vk::PhysicalDeviceVulkan13Properties::default();
+            unsafe {
+                self.instance.get_physical_device_properties2(
+                    physical_device,
+                    &mut vk::PhysicalDeviceProperties2::default().push_next(&mut properties13),
+                );
+            }
+            properties13.max_buffer_size
+        } else {
+            u64::MAX
+        };
+
         let allocator = gpu_alloc::GpuAllocator::<(vk::DeviceMemory, usize)>::new(
-            gpu_alloc::Config::i_am_prototyping(),
+            gpu_alloc::Config {
+                dedicated_threshold: desc.allocator.dedicated_threshold,
+                preferred_dedicated_threshold: desc.allocator.preferred_dedicated_threshold,
+                transient_dedicated_threshold: desc.allocator.transient_dedicated_threshold,
+                starting_free_list_chunk: desc.allocator.starting_free_list_chunk,
+                final_free_list_chunk: desc.allocator.final_free_list_chunk,
+                minimal_buddy_size: desc.allocator.minimal_buddy_size,
+                initial_buddy_dedicated_size: desc.allocator.initial_buddy_dedicated_size,
+            },
             gpu_alloc::DeviceProperties {
                 max_memory_allocation_count: properties.limits.max_memory_allocation_count,
                 max_memory_allocation_size: u64::max_value(), // FIXME: Can query this information if instance is v1.1
@@ -577,20 +900,362 @@ impl crate::traits::Instance for Instance {
             // features13.synchronization2 = 1;
         }
 
-        enabled_extension_names.push(extension_name!("VK_KHR_push_descriptor"));
+        let quirks = Quirks::detect(
+            properties.vendor_id,
+            properties.device_id,
+            properties.driver_version,
+        ) | desc.quirks;
+
+        if !quirks.is_empty() {
+            tracing::info!("Applying driver quirks: {quirks:?}");
+        }
+
+        // Without push descriptors arguments are bound
+        // with descriptor sets allocated from pools.
+        let extension = if quirks.contains(Quirks::DISABLE_PUSH_DESCRIPTORS) {
+            None
+        } else {
+            unsafe { find_extension(&extensions, "VK_KHR_push_descriptor") }
+        };
+
+        let has_push_descriptor = match extension {
+            None => false,
+            Some(extension) => {
+                enabled_extension_names.push(extension.extension_name.as_ptr());
+                true
+            }
+        };
+
+        // Allows querying memory requirements without creating resources.
+        let mut maintenance4_features = vk::PhysicalDeviceMaintenance4Features::default();
+        let mut maintenance4_core = false;
+        let mut maintenance4_khr = false;
+
+        if self.version >= Version::V1_1 {
+            let extension = unsafe { find_extension(&extensions, "VK_KHR_maintenance4") };
+
+            if self.version >= Version::V1_3 || extension.is_some() {
+                let mut supported = vk::PhysicalDeviceMaintenance4Features::default();
+                let mut query = vk::PhysicalDeviceFeatures2::default().push_next(&mut supported);
+                unsafe {
+                    self.instance
+                        .get_physical_device_features2(physical_device, &mut query);
+                }
+
+                if supported.maintenance4 != 0 {
+                    if self.version >= Version::V1_3 {
+                        features13.maintenance4 = 1;
+                        maintenance4_core = true;
+                    } else if let Some(extension) = extension {
+                        enabled_extension_names.push(extension.extension_name.as_ptr());
+                        maintenance4_features.maintenance4 = 1;
+                        maintenance4_khr = true;
+                    }
+                }
+            }
+        }
+
+        // Images with sRGB and linear views list both formats,
+        // so drivers can keep compression enabled for them.
+        let image_format_list = if self.version >= Version::V1_2 {
+            true
+        } else if let Some(extension) =
+            unsafe { find_extension(&extensions, "VK_KHR_image_format_list") }
+        {
+            enabled_extension_names.push(extension.extension_name.as_ptr());
+            true
+        } else {
+            false
+        };
+
+        // Queues track completion of every submission with a timeline semaphore.
+        // Without it checkpoints are tracked with fences.
+        let mut timeline_semaphore = false;
+
+        if self.version >= Version::V1_2 {
+            let mut supported = vk::PhysicalDeviceVulkan12Features::default();
+            let mut query = vk::PhysicalDeviceFeatures2::default().push_next(&mut supported);
+            unsafe {
+                self.instance
+                    .get_physical_device_features2(physical_device, &mut query);
+            }
+
+            if supported.timeline_semaphore != 0 {
+                features12.timeline_semaphore = 1;
+                timeline_semaphore = true;
+            }
+        }
+
+        if desc.features.contains(Features::SAMPLER_ANISOTROPY) {
+            assert!(
+                device_caps.features.contains(Features::SAMPLER_ANISOTROPY),
+                "Sampler anisotropy is not supported by the device"
+            );
+            features.features.sampler_anisotropy = 1;
+        }
+
+        if desc.features.contains(Features::DUAL_SOURCE_BLEND) {
+            assert!(
+                device_caps.features.contains(Features::DUAL_SOURCE_BLEND),
+                "Dual-source blending is not supported by the device"
+            );
+            features.features.dual_src_blend = 1;
+        }
+
+        if desc.features.contains(Features::WIDE_LINES) {
+            assert!(
+                device_caps.features.contains(Features::WIDE_LINES),
+                "Wide lines are not supported by the device"
+            );
+            features.features.wide_lines = 1;
+        }
+
+        // Without `largePoints` only point size of `1.0` written by vertex shaders is supported.
+        // It has no cost and is always enabled when available.
+        let supported = unsafe { self.instance.get_physical_device_features(physical_device) };
+        if supported.large_points != 0 {
+            features.features.large_points = 1;
+        }
+
+        if desc.features.contains(Features::SHADER_FLOAT64) {
+            assert!(
+                device_caps.features.contains(Features::SHADER_FLOAT64),
+                "64-bit floats in shaders are not supported by the device"
+            );
+            features.features.shader_float64 = 1;
+        }
+
+        if desc.features.contains(Features::SHADER_INT64) {
+            assert!(
+                device_caps.features.contains(Features::SHADER_INT64),
+                "64-bit integers in shaders are not supported by the device"
+            );
+            features.features.shader_int64 = 1;
+        }
+
+        // Subgroup operations are core in Vulkan 1.1 and need no enabling.
+        if desc.features.contains(Features::SUBGROUP) {
+            assert!(
+                device_caps.features.contains(Features::SUBGROUP),
+                "Subgroup operations are not supported by the device"
+            );
+        }
+
+        let mut max_bindless_table_size = 0;
+        if desc.features.contains(Features::DESCRIPTOR_INDEXING) {
+            assert!(
+                device_caps.features.contains(Features::DESCRIPTOR_INDEXING),
+                "Descriptor indexing is not supported by the device"
+            );
+            features12.descriptor_indexing = 1;
+            features12.shader_sampled_image_array_non_uniform_indexing = 1;
+            features12.descriptor_binding_sampled_image_update_after_bind = 1;
+            features12.descriptor_binding_update_unused_while_pending = 1;
+            features12.descriptor_binding_partially_bound = 1;
+            features12.descriptor_binding_variable_descriptor_count = 1;
+            features12.runtime_descriptor_array = 1;
+            max_bindless_table_size = device_caps.limits.max_bindless_table_size;
+        }
+
+        let mut robustness2_features = vk::PhysicalDeviceRobustness2FeaturesEXT::default();
+        let mut robustness2 = false;
+
+        if desc.features.contains(Features::ROBUST_BUFFER_ACCESS) {
+            assert!(
+                device_caps
+                    .features
+                    .contains(Features::ROBUST_BUFFER_ACCESS),
+                "Robust buffer access is not supported by the device"
+            );
+            features.features.robust_buffer_access = 1;
+        }
+
+        if self.version >= Version::V1_1
+            && desc
+                .features
+                .intersects(Features::ROBUST_BUFFER_ACCESS | Features::ROBUST_IMAGE_ACCESS)
+        {
+            if let Some(extension) = unsafe { find_extension(&extensions, "VK_EXT_robustness2") } {
+                let mut supported = vk::PhysicalDeviceRobustness2FeaturesEXT::default();
+                let mut query = vk::PhysicalDeviceFeatures2::default().push_next(&mut supported);
+                unsafe {
+                    self.instance
+                        .get_physical_device_features2(physical_device, &mut query);
+                }
+
+                if desc.features.contains(Features::ROBUST_BUFFER_ACCESS) {
+                    // Makes out-of-bounds reads return zero, same as bounds checks in shaders.
+                    robustness2_features.robust_buffer_access2 = supported.robust_buffer_access2;
+                }
+
+                if desc.features.contains(Features::ROBUST_IMAGE_ACCESS) {
+                    robustness2_features.robust_image_access2 = supported.robust_image_access2;
+                }
+
+                if robustness2_features.robust_buffer_access2 != 0
+                    || robustness2_features.robust_image_access2 != 0
+                {
+                    robustness2 = true;
+                    enabled_extension_names.push(extension.extension_name.as_ptr());
+                }
+            }
+        }
+
+        if desc.features.contains(Features::ROBUST_IMAGE_ACCESS) {
+            assert!(
+                robustness2_features.robust_image_access2 != 0,
+                "Robust image access is not supported by the device"
+            );
+        }
+
+        // Shaders translated by naga are bounds-checked unless device
+        // guarantees that out-of-bounds reads return zero.
+        let mut bounds_check_policies = naga::proc::BoundsCheckPolicies::default();
+        if desc.features.contains(Features::ROBUST_BUFFER_ACCESS)
+            && robustness2_features.robust_buffer_access2 == 0
+        {
+            bounds_check_policies.buffer = naga::proc::BoundsCheckPolicy::ReadZeroSkipWrite;
+        }
+
+        let mut vertex_divisor_features =
+            vk::PhysicalDeviceVertexAttributeDivisorFeaturesKHR::default();
+        let mut vertex_divisor = VertexDivisor::default();
+
+        if self.version >= Version::V1_1 {
+            // KHR extension is the promoted EXT one with identical structures.
+            let extension = unsafe {
+                find_extension(&extensions, "VK_KHR_vertex_attribute_divisor")
+                    .or_else(|| find_extension(&extensions, "VK_EXT_vertex_attribute_divisor"))
+            };
+
+            if let Some(extension) = extension {
+                let mut query =
+                    vk::PhysicalDeviceFeatures2::default().push_next(&mut vertex_divisor_features);
+                unsafe {
+                    self.instance
+                        .get_physical_device_features2(physical_device, &mut query);
+                }
+
+                vertex_divisor.rate =
+                    vertex_divisor_features.vertex_attribute_instance_rate_divisor != 0;
+                vertex_divisor.zero =
+                    vertex_divisor_features.vertex_attribute_instance_rate_zero_divisor != 0;
+
+                if vertex_divisor.rate || vertex_divisor.zero {
+                    enabled_extension_names.push(extension.extension_name.as_ptr());
+                }
+            }
+        }
+
+        // Portability subset must be enabled when device exposes it.
+        // Such device is not fully conformant and supports only features listed in the subset.
+        let mut portability_features = vk::PhysicalDevicePortabilitySubsetFeaturesKHR::default();
+        let mut portability_properties =
+            vk::PhysicalDevicePortabilitySubsetPropertiesKHR::default();
+        let mut portability_subset = false;
+        let mut portability = PortabilitySubset::CONFORMANT;
+
+        if let Some(extension) = unsafe { find_extension(&extensions, "VK_KHR_portability_subset") }
+        {
+            // Features are unknown without Vulkan 1.1 and all of them are assumed missing.
+            if self.version >= Version::V1_1 {
+                let mut query =
+                    vk::PhysicalDeviceFeatures2::default().push_next(&mut portability_features);
+                let mut properties_query =
+                    vk::PhysicalDeviceProperties2::default().push_next(&mut portability_properties);
+                unsafe {
+                    self.instance
+                        .get_physical_device_features2(physical_device, &mut query);
+                    self.instance
+                        .get_physical_device_properties2(physical_device, &mut properties_query);
+                }
+            }
+
+            portability = PortabilitySubset {
+                vertex_attribute_access_beyond_stride: portability_features
+                    .vertex_attribute_access_beyond_stride
+                    != 0,
+                min_vertex_input_binding_stride_alignment: portability_properties
+                    .min_vertex_input_binding_stride_alignment
+                    .max(1),
+                image_view_format_swizzle: portability_features.image_view_format_swizzle != 0,
+                image_view_format_reinterpretation: portability_features
+                    .image_view_format_reinterpretation
+                    != 0,
+            };
+
+            portability_subset = true;
+            enabled_extension_names.push(extension.extension_name.as_ptr());
+        }
 
         let mut has_swapchain_maintenance1 = false;
         if desc.features.contains(Features::SURFACE) {
             enabled_extension_names.push(extension_name!("VK_KHR_swapchain"));
 
-            if let Some(extension) =
+            let extension = if quirks.contains(Quirks::DISABLE_SWAPCHAIN_MAINTENANCE1) {
+                None
+            } else {
                 unsafe { find_extension(&extensions, "VK_EXT_swapchain_maintenance1") }
-            {
+            };
+
+            if let Some(extension) = extension {
                 has_swapchain_maintenance1 = true;
                 enabled_extension_names.push(extension.extension_name.as_ptr());
             }
         }
 
+        if desc.features.contains(Features::PRESENT_FENCES) {
+            assert!(
+                has_swapchain_maintenance1,
+                "Present fences are not supported by the device or disabled by quirks"
+            );
+        }
+
+        // Present fences are enabled whenever supported.
+        let mut enabled_features = desc.features;
+        if has_swapchain_maintenance1 {
+            enabled_features |= Features::PRESENT_FENCES;
+        }
+
+        if desc.features.contains(Features::INCREMENTAL_PRESENT) {
+            assert!(
+                desc.features.contains(Features::SURFACE),
+                "Incremental present requires surface feature"
+            );
+            assert!(
+                device_caps.features.contains(Features::INCREMENTAL_PRESENT),
+                "Incremental present is not supported by the device"
+            );
+            enabled_extension_names.push(extension_name!("VK_KHR_incremental_present"));
+        }
+
+        let mut device_fault_features = vk::PhysicalDeviceFaultFeaturesEXT::default();
+        if desc.features.contains(Features::DEVICE_FAULT) {
+            assert!(
+                device_caps.features.contains(Features::DEVICE_FAULT),
+                "Device fault reporting is not supported by the device"
+            );
+            device_fault_features.device_fault = 1;
+            enabled_extension_names.push(extension_name!("VK_EXT_device_fault"));
+        }
+
+        let missing_extensions = enabled_extension_names
+            .iter()
+            .map(|&name| unsafe { CStr::from_ptr(name) })
+            .filter(|name| {
+                !extensions.iter().any(|extension| unsafe {
+                    CStr::from_ptr(extension.extension_name.as_ptr()) == *name
+                })
+            })
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+
+        if !missing_extensions.is_empty() {
+            return Err(CreateError(CreateErrorKind::MissingExtensions(
+                missing_extensions,
+            )));
+        }
+
         let mut info = vk::DeviceCreateInfo::default()
             .enabled_extension_names(&enabled_extension_names)
             .queue_create_infos(&queue_create_infos);
@@ -607,6 +1272,22 @@ impl crate::traits::Instance for Instance {
             if self.version >= Version::V1_3 {
                 info = info.push_next(&mut features13);
             }
+            if vertex_divisor.rate || vertex_divisor.zero {
+                info = info.push_next(&mut vertex_divisor_features);
+            }
+            if robustness2 {
+                info = info.push_next(&mut robustness2_features);
+            }
+            if desc.features.contains(Features::DEVICE_FAULT) {
+                info = info.push_next(&mut device_fault_features);
+            }
+            if maintenance4_khr {
+                info = info.push_next(&mut maintenance4_features);
+            }
+            if portability_subset {
+                // Enable everything the subset supports.
+                info = info.push_next(&mut portability_features);
+            }
         }
 
         let result = unsafe { self.instance.create_device(physical_device, &info, None) };
@@ -632,7 +1313,24 @@ impl crate::traits::Instance for Instance {
             .contains(Features::SURFACE)
             .then(|| ash::khr::swapchain::Device::new(&self.instance, &device));
 
-        let push_descriptor = ash::khr::push_descriptor::Device::new(&self.instance, &device);
+        let push_descriptor = has_push_descriptor
+            .then(|| ash::khr::push_descriptor::Device::new(&self.instance, &device));
+
+        let device_fault = desc
+            .features
+            .contains(Features::DEVICE_FAULT)
+            .then(|| ash::ext::device_fault::Device::new(&self.instance, &device));
+
+        let maintenance4 = if maintenance4_core {
+            Some(Maintenance4::Core)
+        } else if maintenance4_khr {
+            Some(Maintenance4::Khr(ash::khr::maintenance4::Device::new(
+                &self.instance,
+                &device,
+            )))
+        } else {
+            None
+        };
 
         #[cfg(any(debug_assertions, feature = "debug"))]
         let debug_utils = self
@@ -650,15 +1348,34 @@ impl crate::traits::Instance for Instance {
                 .iter()
                 .map(|info| info.queue_family_index)
                 .collect(),
-            desc.features,
+            device_caps.families.clone(),
+            enabled_features,
+            quirks,
             properties,
+            max_buffer_size,
+            vertex_divisor,
+            portability,
+            max_bindless_table_size,
+            bounds_check_policies,
             allocator,
+            LinearAllocator::new(
+                desc.allocator.linear_threshold,
+                desc.allocator.linear_chunk_size,
+            ),
+            desc.allocator.fallback_to_host_memory,
+            device_local_types,
+            desc.allocator.device_local_budget,
+            desc.allocator.preferred_dedicated_threshold,
+            desc.allocator.dedicated_resource_threshold,
             push_descriptor,
+            maintenance4,
+            timeline_semaphore,
+            image_format_list,
             self.surface.clone(),
-            #[cfg(target_os = "windows")]
-            self.win32_surface.clone(),
+            self.platform_surface.clone(),
             swapchain,
             swapchain_maintenance1,
+            device_fault,
             #[cfg(any(debug_assertions, feature = "debug"))]
             debug_utils,
         );
@@ -674,12 +1391,9 @@ impl crate::traits::Instance for Instance {
             let queue = unsafe { device.ash().get_device_queue(family, *counter) };
             *counter += 1;
 
-            queues.push(Queue::new(
-                device.clone(),
-                queue,
-                family_caps.queue_flags,
-                family,
-            ));
+            let queue = Queue::new(device.clone(), queue, family_caps.queue_flags, family)
+                .map_err(|OutOfMemory| CreateError(CreateErrorKind::OutOfMemory))?;
+            queues.push(queue);
         }
 
         Ok((device, queues))
@@ -774,3 +1488,76 @@ pub fn memory_properties_from_ash(
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_version_supported() {
+        assert!(check_api_version(Version::V1_0).is_ok());
+        assert!(check_api_version(Version::V1_3).is_ok());
+    }
+
+    #[test]
+    fn api_version_too_old() {
+        let found = Version {
+            major: 0,
+            minor: 9,
+            patch: 0,
+        };
+        let err = check_api_version(found).unwrap_err();
+        assert!(err.is_unsupported());
+        assert!(matches!(
+            err,
+            LoadErrorKind::UnsupportedApiVersion {
+                found: Some(v),
+                required: Version::V1_0,
+            } if v == found
+        ));
+        assert_eq!(
+            err.to_string(),
+            "installed Vulkan drivers support Vulkan 0.9.0, \
+             at least Vulkan 1.0.0 is required, update graphics drivers"
+        );
+    }
+
+    #[test]
+    fn incompatible_driver() {
+        let err = LoadErrorKind::UnsupportedApiVersion {
+            found: None,
+            required: Version::V1_2,
+        };
+        assert!(err.is_unsupported());
+        assert_eq!(
+            err.to_string(),
+            "installed Vulkan drivers don't support Vulkan 1.2.0, update graphics drivers"
+        );
+    }
+
+    #[test]
+    fn missing_layers() {
+        let err = LoadErrorKind::MissingLayers(vec!["VK_LAYER_KHRONOS_validation".to_owned()]);
+        assert_eq!(err.missing_layers(), ["VK_LAYER_KHRONOS_validation"]);
+        assert!(!err.is_unsupported());
+        assert!(err.to_string().contains("VK_LAYER_KHRONOS_validation"));
+
+        let err = LoadErrorKind::InitializationFailed;
+        assert!(err.missing_layers().is_empty());
+    }
+
+    #[test]
+    fn missing_extensions() {
+        let err = CreateErrorKind::MissingExtensions(vec![
+            "VK_KHR_swapchain".to_owned(),
+            "VK_KHR_dynamic_rendering".to_owned(),
+        ]);
+        assert_eq!(
+            err.missing_extensions(),
+            ["VK_KHR_swapchain", "VK_KHR_dynamic_rendering"]
+        );
+        assert!(err
+            .to_string()
+            .contains("VK_KHR_swapchain, VK_KHR_dynamic_rendering"));
+    }
+}
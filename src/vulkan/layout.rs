@@ -90,6 +90,9 @@ impl DescriptorSetLayout {
 pub(super) struct PipelineLayoutDesc {
     pub groups: Vec<Vec<ArgumentLayout>>,
     pub constants: usize,
+
+    /// Stages of the pipeline that access constants.
+    pub constants_stages: ash::vk::ShaderStageFlags,
 }
 
 struct PipelineLayoutInner {
@@ -108,6 +111,7 @@ impl Drop for PipelineLayoutInner {
             PipelineLayoutDesc {
                 groups: Vec::new(),
                 constants: 0,
+                constants_stages: ash::vk::ShaderStageFlags::empty(),
             },
         );
         self.owner
@@ -192,6 +196,43 @@ impl PipelineLayout {
         &self.inner.desc.groups[idx]
     }
 
+    pub fn set_layout(&self, idx: usize) -> &DescriptorSetLayout {
+        &self.inner.set_layouts[idx]
+    }
+
+    pub fn constants_stages(&self) -> ash::vk::ShaderStageFlags {
+        self.inner.desc.constants_stages
+    }
+
+    pub fn groups_count(&self) -> usize {
+        self.inner.desc.groups.len()
+    }
+
+    /// Returns number of leading groups that stay bound
+    /// when switching from `self` to `other`.
+    ///
+    /// Descriptor set layouts are cached by device,
+    /// so identical group layouts share the handle.
+    /// Constants layout must match for any group to stay bound.
+    pub fn compatible_groups(&self, other: &PipelineLayout) -> usize {
+        if Arc::ptr_eq(&self.inner, &other.inner) {
+            return self.inner.set_layouts.len();
+        }
+
+        if self.inner.desc.constants != other.inner.desc.constants
+            || self.inner.desc.constants_stages != other.inner.desc.constants_stages
+        {
+            return 0;
+        }
+
+        self.inner
+            .set_layouts
+            .iter()
+            .zip(&other.inner.set_layouts)
+            .take_while(|(a, b)| a.handle == b.handle)
+            .count()
+    }
+
     pub fn templates(
         &self,
     ) -> &Mutex<HashMap<(TypeId, ash::vk::PipelineBindPoint, u32), ash::vk::DescriptorUpdateTemplate>>
@@ -0,0 +1,390 @@
+use std::ptr::NonNull;
+
+use ash::vk;
+use gpu_alloc::MemoryBlock;
+use hashbrown::HashMap;
+use slab::Slab;
+
+use super::device::{Allocator, DeviceInner};
+
+type Block = MemoryBlock<(vk::DeviceMemory, usize)>;
+
+/// Minimal alignment of chunks.
+const CHUNK_ALIGN_MASK: u64 = 255;
+
+struct Chunk {
+    block: Block,
+
+    /// Offset of free space in the chunk.
+    offset: u64,
+
+    /// Number of live sub-allocations.
+    live: usize,
+
+    /// Bytes occupied by live sub-allocations.
+    used: u64,
+
+    /// Pointer to the whole chunk while any sub-allocation is mapped.
+    mapped: Option<NonNull<u8>>,
+
+    /// Number of sub-allocations currently mapped.
+    map_count: usize,
+
+    key: (gpu_alloc::UsageFlags, u32),
+}
+
+// Mapped pointer is only used to access memory of sub-allocations
+// by owners of corresponding buffers.
+unsafe impl Send for Chunk {}
+
+/// Memory of buffer sub-allocated by [`LinearAllocator`].
+pub(super) struct LinearBlock {
+    chunk: usize,
+    offset: u64,
+    size: u64,
+}
+
+/// Sub-allocates small host-accessible buffers from large chunks.
+///
+/// Chunks are bump-allocated and reset once all buffers allocated from them are dropped.
+/// Buffers are kept alive by command buffers until queue checkpoint
+/// observes their completion, so reset chunks are not used by the device.
+///
+/// Also owns chunks that buffers moved by compaction are packed into.
+/// Those are never current and are freed with the last buffer.
+pub(super) struct LinearAllocator {
+    threshold: u64,
+    chunk_size: u64,
+    chunks: Slab<Chunk>,
+    current: HashMap<(gpu_alloc::UsageFlags, u32), usize>,
+    live: usize,
+}
+
+impl LinearAllocator {
+    pub fn new(threshold: u64, chunk_size: u64) -> Self {
+        LinearAllocator {
+            threshold: threshold.min(chunk_size),
+            chunk_size,
+            chunks: Slab::new(),
+            current: HashMap::new(),
+            live: 0,
+        }
+    }
+
+    /// Checks if request should be sub-allocated linearly.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn accepts(&self, usage: gpu_alloc::UsageFlags, size: u64) -> bool {
+        usage.contains(gpu_alloc::UsageFlags::HOST_ACCESS) && size <= self.threshold
+    }
+
+    /// Number of chunks currently allocated.
+    pub fn chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Number of live sub-allocations.
+    pub fn live(&self) -> usize {
+        self.live
+    }
+
+    pub unsafe fn alloc(
+        &mut self,
+        device: &DeviceInner,
+        allocator: &mut Allocator,
+        usage: gpu_alloc::UsageFlags,
+        requirements: vk::MemoryRequirements,
+    ) -> Result<LinearBlock, gpu_alloc::AllocationError> {
+        let key = (usage, requirements.memory_type_bits);
+        let align_mask = requirements.alignment - 1;
+
+        if let Some(&idx) = self.current.get(&key) {
+            let chunk = &mut self.chunks[idx];
+
+            // Align absolute offset in the memory object.
+            let base = chunk.block.offset();
+            let offset = ((base + chunk.offset + align_mask) & !align_mask) - base;
+
+            if offset + requirements.size <= chunk.block.size() {
+                chunk.offset = offset + requirements.size;
+                chunk.live += 1;
+                chunk.used += requirements.size;
+                self.live += 1;
+                return Ok(LinearBlock {
+                    chunk: idx,
+                    offset,
+                    size: requirements.size,
+                });
+            }
+
+            // Chunk is exhausted, it is freed when last buffer is dropped.
+            self.current.remove(&key);
+            if chunk.live == 0 {
+                let chunk = self.chunks.remove(idx);
+                unsafe { allocator.dealloc(device, chunk.block) };
+            }
+        }
+
+        let block = unsafe {
+            allocator.alloc(
+                device,
+                gpu_alloc::Request {
+                    size: self.chunk_size,
+                    align_mask: align_mask | CHUNK_ALIGN_MASK,
+                    usage,
+                    memory_types: requirements.memory_type_bits,
+                },
+            )?
+        };
+
+        let idx = self.chunks.insert(Chunk {
+            block,
+            offset: requirements.size,
+            live: 1,
+            used: requirements.size,
+            mapped: None,
+            map_count: 0,
+            key,
+        });
+        self.current.insert(key, idx);
+        self.live += 1;
+
+        Ok(LinearBlock {
+            chunk: idx,
+            offset: 0,
+            size: requirements.size,
+        })
+    }
+
+    /// Allocates chunk that fits all `requirements`
+    /// and sub-allocates them back to back in the same order.
+    ///
+    /// `memory_types` must be allowed by all requirements.
+    pub unsafe fn alloc_packed(
+        &mut self,
+        device: &DeviceInner,
+        allocator: &mut Allocator,
+        usage: gpu_alloc::UsageFlags,
+        memory_types: u32,
+        requirements: &[vk::MemoryRequirements],
+    ) -> Result<Vec<LinearBlock>, gpu_alloc::AllocationError> {
+        let (offsets, size) = pack(requirements);
+        let align_mask = requirements
+            .iter()
+            .fold(CHUNK_ALIGN_MASK, |mask, r| mask | (r.alignment - 1));
+
+        let block = unsafe {
+            allocator.alloc(
+                device,
+                gpu_alloc::Request {
+                    size,
+                    align_mask,
+                    usage,
+                    memory_types,
+                },
+            )?
+        };
+
+        let idx = self.chunks.insert(Chunk {
+            block,
+            offset: size,
+            live: requirements.len(),
+            used: requirements.iter().map(|r| r.size).sum(),
+            mapped: None,
+            map_count: 0,
+            key: (usage, memory_types),
+        });
+        self.live += requirements.len();
+
+        Ok(offsets
+            .into_iter()
+            .zip(requirements)
+            .map(|(offset, r)| LinearBlock {
+                chunk: idx,
+                offset,
+                size: r.size,
+            })
+            .collect())
+    }
+
+    pub unsafe fn dealloc(
+        &mut self,
+        device: &DeviceInner,
+        allocator: &mut Allocator,
+        block: LinearBlock,
+    ) {
+        self.live -= 1;
+
+        let chunk = &mut self.chunks[block.chunk];
+        chunk.live -= 1;
+        chunk.used -= block.size;
+        if chunk.live > 0 {
+            return;
+        }
+
+        if self.current.get(&chunk.key) == Some(&block.chunk) {
+            // Reuse current chunk from the start.
+            chunk.offset = 0;
+        } else {
+            let chunk = self.chunks.remove(block.chunk);
+            unsafe { allocator.dealloc(device, chunk.block) };
+        }
+    }
+
+    /// Frees current chunks that have no live sub-allocations.
+    pub unsafe fn trim(&mut self, device: &DeviceInner, allocator: &mut Allocator) {
+        let chunks = &mut self.chunks;
+        self.current.retain(|_, &mut idx| {
+            if chunks[idx].live > 0 {
+                return true;
+            }
+            let chunk = chunks.remove(idx);
+            unsafe { allocator.dealloc(device, chunk.block) };
+            false
+        });
+    }
+
+    /// Returns index of the chunk of the sub-allocation,
+    /// bytes occupied in the chunk and its size.
+    pub fn occupancy(&self, block: &LinearBlock) -> (usize, u64, u64) {
+        let chunk = &self.chunks[block.chunk];
+        (block.chunk, chunk.used, chunk.block.size())
+    }
+
+    /// Returns memory object and offset of the sub-allocation.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub fn memory(&self, block: &LinearBlock) -> (vk::DeviceMemory, u64) {
+        let chunk = &self.chunks[block.chunk];
+        (chunk.block.memory().0, chunk.block.offset() + block.offset)
+    }
+
+    /// Maps range of the sub-allocation.
+    ///
+    /// Chunk stays mapped until all its mapped sub-allocations are unmapped.
+    pub unsafe fn map(
+        &mut self,
+        device: &DeviceInner,
+        block: &LinearBlock,
+        offset: u64,
+    ) -> Result<NonNull<u8>, gpu_alloc::MapError> {
+        let chunk = &mut self.chunks[block.chunk];
+        let base = match chunk.mapped {
+            Some(ptr) => ptr,
+            None => {
+                let size = chunk.block.size() as usize;
+                let ptr = unsafe { chunk.block.map(device, 0, size)? };
+                chunk.mapped = Some(ptr);
+                ptr
+            }
+        };
+        chunk.map_count += 1;
+        Ok(unsafe { base.add((block.offset + offset) as usize) })
+    }
+
+    /// Unmaps range of the sub-allocation mapped with [`LinearAllocator::map`].
+    /// Flushes the range first if `flush` is `true`.
+    pub unsafe fn unmap(
+        &mut self,
+        device: &DeviceInner,
+        block: &LinearBlock,
+        offset: u64,
+        size: u64,
+        flush: bool,
+    ) -> Result<(), gpu_alloc::MapError> {
+        let chunk = &mut self.chunks[block.chunk];
+        let result = if flush {
+            unsafe { chunk.block.flush_range(device, block.offset + offset, size) }
+        } else {
+            Ok(())
+        };
+
+        chunk.map_count -= 1;
+        if chunk.map_count == 0 {
+            chunk.mapped = None;
+            unsafe { chunk.block.unmap(device) };
+        }
+        result
+    }
+
+    pub unsafe fn write_bytes(
+        &mut self,
+        device: &DeviceInner,
+        block: &LinearBlock,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), gpu_alloc::MapError> {
+        unsafe {
+            let ptr = self.map(device, block, offset)?;
+            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), data.len());
+            self.unmap(device, block, offset, data.len() as u64, true)
+        }
+    }
+
+    pub unsafe fn read_bytes(
+        &mut self,
+        device: &DeviceInner,
+        block: &LinearBlock,
+        offset: u64,
+        data: &mut [u8],
+    ) -> Result<(), gpu_alloc::MapError> {
+        unsafe {
+            let ptr = self.map(device, block, offset)?;
+            let chunk = &mut self.chunks[block.chunk];
+            let result =
+                chunk
+                    .block
+                    .invalidate_range(device, block.offset + offset, data.len() as u64);
+            if result.is_ok() {
+                std::ptr::copy_nonoverlapping(ptr.as_ptr(), data.as_mut_ptr(), data.len());
+            }
+            self.unmap(device, block, offset, data.len() as u64, false)?;
+            result
+        }
+    }
+}
+
+/// Returns offsets of `requirements` placed back to back and total size.
+fn pack(requirements: &[vk::MemoryRequirements]) -> (Vec<u64>, u64) {
+    let mut size = 0;
+    let offsets = requirements
+        .iter()
+        .map(|r| {
+            let align_mask = r.alignment - 1;
+            let offset = (size + align_mask) & !align_mask;
+            size = offset + r.size;
+            offset
+        })
+        .collect();
+    (offsets, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirements(size: u64, alignment: u64) -> vk::MemoryRequirements {
+        vk::MemoryRequirements {
+            size,
+            alignment,
+            memory_type_bits: !0,
+        }
+    }
+
+    #[test]
+    fn pack_aligned() {
+        let (offsets, size) = pack(&[
+            requirements(100, 16),
+            requirements(1000, 256),
+            requirements(4, 4),
+            requirements(64, 64),
+        ]);
+        assert_eq!(offsets, [0, 256, 1256, 1280]);
+        assert_eq!(size, 1344);
+    }
+
+    #[test]
+    fn pack_tight() {
+        let (offsets, size) = pack(&[requirements(256, 256); 4]);
+        assert_eq!(offsets, [0, 256, 512, 768]);
+        assert_eq!(size, 1024);
+    }
+}
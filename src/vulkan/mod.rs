@@ -2,17 +2,53 @@ use std::{alloc::Layout, fmt};
 
 use ash::vk;
 
+/// Enters `tracing` span until the end of the scope if `trace-gpu` feature is enabled.
+/// Expands to nothing otherwise, fields are not evaluated.
+macro_rules! trace_span {
+    ($name:literal $(, $($field:tt)*)?) => {
+        #[cfg(feature = "trace-gpu")]
+        let _trace_span = tracing::info_span!(target: "mev", $name $(, $($field)*)?).entered();
+    };
+}
+
+/// Same as [`trace_span!`] but records `duration_us` field when scope ends.
+macro_rules! trace_timed_span {
+    ($name:literal $(, $($field:tt)*)?) => {
+        #[cfg(feature = "trace-gpu")]
+        let _trace_span = crate::backend::TimedSpan::new(tracing::info_span!(
+            target: "mev",
+            $name,
+            duration_us = tracing::field::Empty
+            $(, $($field)*)?
+        ));
+    };
+}
+
+/// Emits `tracing` event if `trace-gpu` feature is enabled.
+/// Expands to nothing otherwise, fields are not evaluated.
+macro_rules! trace_event {
+    ($name:literal, $($field:tt)+) => {
+        #[cfg(feature = "trace-gpu")]
+        tracing::event!(name: $name, target: "mev", tracing::Level::INFO, { $($field)+ });
+    };
+}
+
 mod access;
 mod acst;
 mod arguments;
+mod bindless;
+mod breadcrumbs;
 mod buffer;
 mod command;
 mod compute_pipeline;
+mod descriptors;
 mod device;
 mod from;
+mod hazards;
 mod image;
 mod instance;
 mod layout;
+mod linear;
 mod queue;
 mod refs;
 mod render_pipeline;
@@ -20,14 +56,15 @@ mod sampler;
 mod shader;
 mod surface;
 
-use crate::generic::{DeviceError, OutOfMemory, PixelFormat};
+use crate::generic::{DeviceError, OutOfMemory, PixelFormat, ViewAspect, ViewDesc};
 
 pub use self::{
     acst::{Blas, Tlas},
-    buffer::Buffer,
+    bindless::BindlessTable,
+    buffer::{Buffer, MappedSlice},
     command::{
         AccelerationStructureCommandEncoder, CommandBuffer, CommandEncoder, ComputeCommandEncoder,
-        CopyCommandEncoder, RenderCommandEncoder,
+        CopyCommandEncoder, RenderCommandEncoder, ReusableCommandBuffer, ReusableCommandEncoder,
     },
     compute_pipeline::ComputePipeline,
     device::Device,
@@ -40,11 +77,39 @@ pub use self::{
     surface::{Frame, Surface},
 };
 
+#[cfg(any(debug_assertions, feature = "debug"))]
+pub use self::refs::{RefsReport, ResourceKind, ResourceReport};
+
 pub(crate) use self::{
     instance::{CreateErrorKind, LoadErrorKind},
     render_pipeline::CreatePipelineErrorKind,
 };
 
+/// Entered span that records its duration when dropped.
+#[cfg(feature = "trace-gpu")]
+struct TimedSpan {
+    span: tracing::span::EnteredSpan,
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "trace-gpu")]
+impl TimedSpan {
+    fn new(span: tracing::Span) -> Self {
+        TimedSpan {
+            span: span.entered(),
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "trace-gpu")]
+impl Drop for TimedSpan {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed().as_micros() as u64;
+        self.span.record("duration_us", duration);
+    }
+}
+
 #[track_caller]
 fn handle_host_oom() -> ! {
     std::alloc::handle_alloc_error(Layout::new::<()>())
@@ -116,6 +181,16 @@ fn format_aspect(format: PixelFormat) -> vk::ImageAspectFlags {
     aspect
 }
 
+#[cfg_attr(feature = "inline-more", inline(always))]
+fn view_aspect(desc: &ViewDesc) -> vk::ImageAspectFlags {
+    let aspect = format_aspect(desc.format);
+    match desc.aspect {
+        ViewAspect::Auto => aspect,
+        ViewAspect::DepthOnly => aspect & vk::ImageAspectFlags::DEPTH,
+        ViewAspect::StencilOnly => aspect & vk::ImageAspectFlags::STENCIL,
+    }
+}
+
 #[track_caller]
 fn map_oom(err: vk::Result) -> OutOfMemory {
     match err {
@@ -139,6 +214,7 @@ pub mod for_macro {
 
     pub use super::{
         arguments::{descriptor_type, Arguments, ArgumentsField},
+        hazards::Accesses,
         refs::Refs,
     };
     pub use ash::vk::DescriptorUpdateTemplateEntry;
@@ -1,14 +1,30 @@
-use std::{collections::VecDeque, fmt, ops::Deref, sync::Arc};
+use std::{
+    collections::VecDeque,
+    fmt,
+    ops::{Deref, Range},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use ash::{ext::swapchain_maintenance1, prelude::VkResult, vk};
 use parking_lot::Mutex;
 use smallvec::SmallVec;
 
-use crate::generic::{DeviceError, OutOfMemory, PipelineStages, QueueFlags};
+use crate::generic::{
+    AsBufferSlice, BufferInitDesc, BufferUsage, CompactionPlan, DeviceError, Features, Memory,
+    OutOfMemory, PipelineStages, QueueFlags, QueuePoolConfig, QueueStats, ReusableCommandsError,
+    SubmissionId, FRAMES_IN_FLIGHT,
+};
 
+#[cfg(any(debug_assertions, feature = "debug"))]
+use super::refs::RefsReport;
 use super::{
-    device::Device, from::IntoAsh, handle_host_oom, map_device_error, map_oom, refs::Refs,
-    surface::Frame, unexpected_error, CommandBuffer, CommandEncoder,
+    breadcrumbs::Breadcrumbs, buffer::Buffer, command::ReusableCommandBufferInner,
+    descriptors::DescriptorPools, device::Device, from::IntoAsh, handle_host_oom, map_device_error,
+    map_oom, refs::Refs, surface::Frame, unexpected_error, CommandBuffer, CommandEncoder,
+    ReusableCommandBuffer, ReusableCommandEncoder,
 };
 
 /// Maximum number of pending epochs to keep in queue.
@@ -17,13 +33,13 @@ use super::{
 ///
 /// The number is chosen to minimize waiting (ideally epoch would be already complete when it's recycled)
 /// and to minimize memory usage (epoch contains resources that are not released until it's complete).
-const MAX_EPOCHS: usize = 3;
+///
+/// Together with the epoch being filled it makes [`FRAMES_IN_FLIGHT`].
+const MAX_EPOCHS: usize = FRAMES_IN_FLIGHT - 1;
 
-/// Maximum number of command pools to keep in queue.
-/// When new command buffer is needed it will allocate from the oldest used pool if it was reset.
-/// Otherwise it will create a new pool if number of pools is less than this limit.
-/// Otherwise it will keep using last pool
-const MAX_POOLS: usize = 3;
+/// Number of submissions without checkpoint after which checkpoint is forced.
+/// Without checkpoints epochs never complete and resources are never released.
+const MAX_SUBMISSIONS_WITHOUT_CHECK_POINT: u32 = 64;
 
 unsafe fn deallocate_cbuf(
     cbuf: vk::CommandBuffer,
@@ -36,6 +52,127 @@ unsafe fn deallocate_cbuf(
     pool.deallocate(cbuf);
 }
 
+/// Returns whether all submissions of the epoch are complete.
+///
+/// Uses queue's timeline semaphore if there is one, and epoch's fence otherwise.
+fn epoch_complete(device: &ash::Device, timeline: vk::Semaphore, epoch: &Epoch) -> VkResult<bool> {
+    if timeline == vk::Semaphore::null() {
+        unsafe { device.get_fence_status(epoch.fence) }
+    } else {
+        let value = unsafe { device.get_semaphore_counter_value(timeline) }?;
+        Ok(value >= epoch.last_submission)
+    }
+}
+
+/// Waits until all submissions of the epoch are complete.
+fn wait_epoch(device: &ash::Device, timeline: vk::Semaphore, epoch: &Epoch) -> VkResult<()> {
+    if timeline == vk::Semaphore::null() {
+        unsafe { device.wait_for_fences(&[epoch.fence], true, !0) }
+    } else {
+        unsafe {
+            device.wait_semaphores(
+                &vk::SemaphoreWaitInfo::default()
+                    .semaphores(&[timeline])
+                    .values(&[epoch.last_submission]),
+                !0,
+            )
+        }
+    }
+}
+
+/// Checks that render passes suspended by command buffers
+/// are resumed by the next command buffer in the submission.
+fn check_split_passes(command_buffers: &[CommandBuffer]) {
+    let mut suspended = false;
+    for cbuf in command_buffers {
+        assert_eq!(
+            suspended, cbuf.resumes_pass,
+            "Command buffer that suspends render pass must be followed by command buffer that resumes it"
+        );
+        suspended = cbuf.suspends_pass;
+    }
+    assert!(
+        !suspended,
+        "Last submitted command buffer must not suspend render pass"
+    );
+}
+
+/// Checks whether any swapchain image is presented more than once.
+///
+/// Takes swapchain and image index of each presented frame.
+fn presents_twice(frames: impl IntoIterator<Item = (vk::SwapchainKHR, u32)>) -> bool {
+    let mut presented = SmallVec::<[(vk::SwapchainKHR, u32); 4]>::new();
+    for key in frames {
+        if presented.contains(&key) {
+            return true;
+        }
+        presented.push(key);
+    }
+    false
+}
+
+/// Queries fault information of the lost device and formats it for the log.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[cold]
+fn query_device_fault(
+    device: &ash::Device,
+    device_fault: &ash::ext::device_fault::Device,
+) -> VkResult<String> {
+    use std::{ffi::CStr, fmt::Write};
+
+    let get_device_fault_info = device_fault.fp().get_device_fault_info_ext;
+
+    let mut counts = vk::DeviceFaultCountsEXT::default();
+    unsafe { get_device_fault_info(device.handle(), &mut counts, std::ptr::null_mut()) }
+        .result()?;
+
+    let mut addresses =
+        vec![vk::DeviceFaultAddressInfoEXT::default(); counts.address_info_count as usize];
+    let mut vendor_infos =
+        vec![vk::DeviceFaultVendorInfoEXT::default(); counts.vendor_info_count as usize];
+
+    // Vendor binary is not requested.
+    counts.vendor_binary_size = 0;
+
+    let mut info = vk::DeviceFaultInfoEXT {
+        p_address_infos: addresses.as_mut_ptr(),
+        p_vendor_infos: vendor_infos.as_mut_ptr(),
+        ..Default::default()
+    };
+
+    match unsafe { get_device_fault_info(device.handle(), &mut counts, &mut info) } {
+        vk::Result::SUCCESS | vk::Result::INCOMPLETE => {}
+        err => return Err(err),
+    }
+
+    addresses.truncate(counts.address_info_count as usize);
+    vendor_infos.truncate(counts.vendor_info_count as usize);
+
+    let description = unsafe { CStr::from_ptr(info.description.as_ptr()) };
+    let mut report = description.to_string_lossy().into_owned();
+
+    for address in &addresses {
+        let _ = write!(
+            report,
+            "\n  {:?} at 0x{:x} (precision 0x{:x})",
+            address.address_type, address.reported_address, address.address_precision
+        );
+    }
+
+    for vendor in &vendor_infos {
+        let description = unsafe { CStr::from_ptr(vendor.description.as_ptr()) };
+        let _ = write!(
+            report,
+            "\n  vendor fault \"{}\" code 0x{:x} data 0x{:x}",
+            description.to_string_lossy(),
+            vendor.vendor_fault_code,
+            vendor.vendor_fault_data
+        );
+    }
+
+    Ok(report)
+}
+
 pub struct Pool {
     free_cbufs: Vec<vk::CommandBuffer>,
     pool: vk::CommandPool,
@@ -43,7 +180,11 @@ pub struct Pool {
 }
 
 impl Pool {
-    fn allocate(&mut self, device: &ash::Device) -> Result<vk::CommandBuffer, OutOfMemory> {
+    fn allocate(
+        &mut self,
+        device: &ash::Device,
+        stats: &mut QueueStats,
+    ) -> Result<vk::CommandBuffer, OutOfMemory> {
         if let Some(cbuf) = self.free_cbufs.last() {
             unsafe {
                 device.begin_command_buffer(
@@ -75,6 +216,7 @@ impl Pool {
             vk::Result::SUCCESS => {}
             err => return Err(map_oom(err)),
         }
+        stats.command_buffers_allocated += 1;
 
         let result = unsafe {
             device.begin_command_buffer(
@@ -97,16 +239,56 @@ impl Pool {
         self.free_cbufs.push(cbuf);
         self.allocated -= 1;
     }
+
+    /// Releases free command buffers above `threshold`.
+    /// Returns number of released command buffers.
+    ///
+    /// # Safety
+    ///
+    /// Device must be the same device that created the pool.
+    unsafe fn trim(&mut self, device: &ash::Device, threshold: usize) -> usize {
+        if self.free_cbufs.len() <= threshold {
+            return 0;
+        }
+
+        // Safety: free command buffers are not in use.
+        unsafe {
+            device.free_command_buffers(self.pool, &self.free_cbufs[threshold..]);
+        }
+
+        let count = self.free_cbufs.len() - threshold;
+        self.free_cbufs.truncate(threshold);
+        count
+    }
 }
 
 /// Epoch contains resource references
-/// and fence that must be signaled before references can be dropped.
+/// that can be dropped when all submissions of the epoch are complete.
+///
+/// Completion is tracked by queue's timeline semaphore when available.
+/// Otherwise the epoch's fence is signaled by the submission that closes the epoch.
 struct Epoch {
+    /// Null when queue has timeline semaphore.
     fence: vk::Fence,
     refs: Vec<Refs>,
 
     /// Contains owning command pool handle for each command buffer in the epoch.
     cbufs: Vec<(vk::CommandBuffer, vk::CommandPool)>,
+
+    /// Reusable command buffers submitted in the epoch.
+    reusable: Vec<Arc<ReusableCommandBufferInner>>,
+
+    /// Descriptor pool chains of command buffers in the epoch.
+    descriptors: Vec<DescriptorPools>,
+
+    /// Latest submission in the epoch.
+    /// Complete when the epoch's fence is signaled
+    /// or queue's timeline reaches this value.
+    last_submission: u64,
+
+    /// Labels of submissions in the epoch.
+    /// Kept until the epoch is recycled.
+    labels: Vec<(SubmissionId, Box<str>)>,
 }
 
 impl Epoch {
@@ -123,16 +305,47 @@ impl Epoch {
             device.destroy_fence(self.fence, None);
         }
 
+        self.reusable.clear();
+
         for (cbuf, pool) in self.cbufs.drain(..) {
             // Safety: caller must ensure pool exists.
             unsafe {
                 deallocate_cbuf(cbuf, pool, pools);
             }
         }
+
+        for mut descriptors in self.descriptors.drain(..) {
+            // Safety: caller must ensure device is owner.
+            unsafe {
+                descriptors.destroy(device);
+            }
+        }
+    }
+
+    /// Returns number of resource references kept alive by the epoch.
+    fn resource_count(&self) -> usize {
+        let refs: usize = self.refs.iter().map(Refs::count).sum();
+        let reusable: usize = self.reusable.iter().map(|r| r.refs().count()).sum();
+        refs + reusable
+    }
+
+    /// Returns report of resources kept alive by the epoch.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    fn report(&self) -> RefsReport {
+        let mut report = RefsReport::default();
+        for refs in &self.refs {
+            refs.report_into(&mut report);
+        }
+        for reusable in &self.reusable {
+            reusable.refs().report_into(&mut report);
+        }
+        report
     }
 
     /// Resets the epoch for recycling.
     /// Drops all resource references and resets the fence.
+    /// Cleared refs are moved to `free_refs`.
+    /// Descriptor pool chains are reset and moved to `free_descriptors`.
     ///
     /// If this call fails the epoch is not completely reset, although resources are freed.
     /// `reset` may be called again to retry.
@@ -145,8 +358,15 @@ impl Epoch {
         &mut self,
         device: &ash::Device,
         pools: &mut VecDeque<Pool>,
+        free_refs: &mut Vec<Refs>,
+        free_descriptors: &mut Vec<DescriptorPools>,
     ) -> Result<(), OutOfMemory> {
-        self.refs.iter_mut().for_each(|r| r.clear());
+        free_refs.extend(self.refs.drain(..).map(|mut refs| {
+            refs.clear();
+            refs
+        }));
+        self.reusable.clear();
+        self.labels.clear();
 
         for (cbuf, pool) in self.cbufs.drain(..) {
             // Safety: caller must ensure pool exists.
@@ -155,9 +375,20 @@ impl Epoch {
             }
         }
 
-        // Safety: called must ensure device is owner.
-        unsafe {
-            device.reset_fences(&[self.fence]).map_err(map_oom)?;
+        for mut descriptors in self.descriptors.drain(..) {
+            // Safety: caller must ensure device is owner.
+            // Epoch is complete, so sets are not in use.
+            unsafe {
+                descriptors.reset(device);
+            }
+            free_descriptors.push(descriptors);
+        }
+
+        if self.fence != vk::Fence::null() {
+            // Safety: called must ensure device is owner.
+            unsafe {
+                device.reset_fences(&[self.fence]).map_err(map_oom)?;
+            }
         }
         Ok(())
     }
@@ -165,12 +396,21 @@ impl Epoch {
 
 struct PendingEpochs {
     array: Mutex<VecDeque<Epoch>>,
+
+    /// Latest submission of recycled epochs.
+    completed: u64,
+
+    /// Timeline semaphore signaled with index of each submission.
+    /// Null if timeline semaphores are not supported.
+    timeline: vk::Semaphore,
 }
 
 impl PendingEpochs {
-    fn new() -> Self {
+    fn new(timeline: vk::Semaphore) -> Self {
         PendingEpochs {
             array: Mutex::new(VecDeque::new()),
+            completed: 0,
+            timeline,
         }
     }
 
@@ -182,6 +422,8 @@ impl PendingEpochs {
         &mut self,
         device: &ash::Device,
         pools: &mut VecDeque<Pool>,
+        free_refs: &mut Vec<Refs>,
+        free_descriptors: &mut Vec<DescriptorPools>,
     ) -> Result<Option<Epoch>, DeviceError> {
         let mut array = self.array.get_mut();
         if array.len() < MAX_EPOCHS {
@@ -192,10 +434,12 @@ impl PendingEpochs {
         unsafe {
             let front_epoch = array.front_mut().unwrap_unchecked();
 
-            device
-                .wait_for_fences(&[front_epoch.fence], true, !0)
-                .map_err(map_device_error)?;
-            front_epoch.reset(device, pools)?;
+            {
+                trace_timed_span!("mev.epoch_wait", submission = front_epoch.last_submission);
+                wait_epoch(device, self.timeline, front_epoch).map_err(map_device_error)?;
+            }
+            self.completed = front_epoch.last_submission;
+            front_epoch.reset(device, pools, free_refs, free_descriptors)?;
         }
 
         // Epoch is properly reset and ready to be reused.
@@ -211,12 +455,111 @@ impl PendingEpochs {
         }
     }
 
-    /// Releases all resources but keeps the epochs.
-    fn queue_is_idle(&self) {
-        let mut array = self.array.lock();
-        for epoch in array.iter_mut() {
-            epoch.refs.clear();
+    /// Returns latest submission known to be complete.
+    ///
+    /// Reads value of the timeline semaphore if there is one.
+    /// Otherwise checks fences of pending epochs, earliest first.
+    fn last_completed(&self, device: &ash::Device) -> u64 {
+        let mut completed = self.completed;
+
+        if self.timeline != vk::Semaphore::null() {
+            // Lost device reports no further completions.
+            if let Ok(value) = unsafe { device.get_semaphore_counter_value(self.timeline) } {
+                completed = completed.max(value);
+            }
+            return completed;
+        }
+
+        let array = self.array.lock();
+        for epoch in array.iter() {
+            // Lost device reports no further completions.
+            match unsafe { device.get_fence_status(epoch.fence) } {
+                Ok(true) => completed = epoch.last_submission,
+                Ok(false) | Err(_) => break,
+            }
+        }
+        completed
+    }
+
+    /// Waits until submission with given index is complete.
+    ///
+    /// Returns `false` if the submission can't be waited for,
+    /// that is when queue has no timeline semaphore
+    /// and submission is not closed by a checkpoint yet.
+    fn wait(&self, device: &ash::Device, submission: u64) -> VkResult<bool> {
+        if submission <= self.completed {
+            return Ok(true);
+        }
+
+        if self.timeline != vk::Semaphore::null() {
+            unsafe {
+                device.wait_semaphores(
+                    &vk::SemaphoreWaitInfo::default()
+                        .semaphores(&[self.timeline])
+                        .values(&[submission]),
+                    !0,
+                )?;
+            }
+            return Ok(true);
+        }
+
+        let array = self.array.lock();
+        match array
+            .iter()
+            .find(|epoch| epoch.last_submission >= submission)
+        {
+            None => Ok(false),
+            Some(epoch) => {
+                wait_epoch(device, self.timeline, epoch)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Returns number of resource references kept alive by pending epochs.
+    fn resource_count(&self) -> usize {
+        let array = self.array.lock();
+        array.iter().map(Epoch::resource_count).sum()
+    }
+
+    /// Returns reports of resources for each pending epoch, earliest first.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    fn report(&self) -> Vec<RefsReport> {
+        let array = self.array.lock();
+        array.iter().map(Epoch::report).collect()
+    }
+
+    /// Resets complete epochs, earliest first, and moves them to `free_epochs`.
+    ///
+    /// If `idle` is true the queue is known to be idle and all epochs are complete.
+    /// Otherwise stops at the first incomplete epoch.
+    fn reclaim(
+        &mut self,
+        device: &ash::Device,
+        pools: &mut VecDeque<Pool>,
+        free_refs: &mut Vec<Refs>,
+        free_descriptors: &mut Vec<DescriptorPools>,
+        free_epochs: &mut Vec<Epoch>,
+        idle: bool,
+    ) -> Result<(), OutOfMemory> {
+        let array = self.array.get_mut();
+        while let Some(epoch) = array.front_mut() {
+            if !idle {
+                let complete = epoch_complete(device, self.timeline, epoch);
+                if !complete.map_err(map_oom)? {
+                    break;
+                }
+            }
+
+            self.completed = self.completed.max(epoch.last_submission);
+
+            // Safety: epoch was created by this queue.
+            unsafe {
+                epoch.reset(device, pools, free_refs, free_descriptors)?;
+            }
+            free_epochs.push(unsafe { array.pop_front().unwrap_unchecked() });
         }
+        Ok(())
     }
 }
 
@@ -236,16 +579,45 @@ pub struct Queue {
     /// Command pools to allocate command buffers from.
     pools: VecDeque<Pool>,
 
+    /// Policy of command pools.
+    pool_config: QueuePoolConfig,
+
     /// Free refs instances to reuse.
     /// Refs from recycled epochs are added here.
     free_refs: Vec<Refs>,
 
+    /// Free descriptor pool chains to reuse.
+    /// Chains from recycled epochs are added here after reset.
+    free_descriptors: Vec<DescriptorPools>,
+
+    /// Statistics of the queue.
+    stats: QueueStats,
+
+    /// Number of descriptor sets allocated since last checkpoint.
+    descriptor_sets_this_check_point: u64,
+
+    /// Number of submissions since last checkpoint.
+    submissions_since_check_point: u32,
+
+    /// Whether forced checkpoint was already reported.
+    warned_forced_check_point: bool,
+
+    /// Number of checkpoints submitted.
+    check_point_index: u64,
+
+    /// Latest submission made to the queue.
+    last_submission: u64,
+
     // Waits to add into next submission
     wait_semaphores: Vec<vk::Semaphore>,
 
     // Stages to wait for.
     wait_stages: Vec<vk::PipelineStageFlags>,
 
+    /// Flags of swapchain images whose acquire semaphores are in `wait_semaphores`.
+    /// Set when next submission is made.
+    wait_frames: Vec<Arc<AtomicBool>>,
+
     // Signals to add into next submission
     signal_semaphores: Vec<vk::Semaphore>,
 
@@ -253,21 +625,33 @@ pub struct Queue {
     this_epoch: Option<Epoch>,
 
     /// Pending epochs that are waiting for completion.
-    /// Epochs might be recycled when associated fence is signaled.
-    /// Or if Device::wait_idle or Queue::wait_idle wait is called.
+    /// Epochs might be recycled when their submissions complete.
+    /// Or if `Queue::wait_idle` or `Queue::reclaim` is called.
     pending_epochs: PendingEpochs,
 
-    /// Temporary array for command buffers.
-    command_buffers: Vec<CommandBuffer>,
-
-    /// Temporary array for command buffers to submit
-    command_buffer_submit: Vec<vk::CommandBuffer>,
+    /// Epochs reset by `Queue::wait_idle` or `Queue::reclaim` and ready to be reused.
+    free_epochs: Vec<Epoch>,
 
     // Present resources
     present_semaphores: Vec<vk::Semaphore>,
     present_swapchains: Vec<vk::SwapchainKHR>,
     present_indices: Vec<u32>,
     present_fences: Vec<vk::Fence>,
+
+    /// Flags of present fences set when present is queued.
+    /// Fences of frames that are never presented stay unsignaled.
+    present_submitted: Vec<Arc<AtomicBool>>,
+
+    /// Damage of presented frames.
+    /// Range in `present_rects` for each presented frame.
+    present_damage: Vec<Range<usize>>,
+    present_rects: Vec<vk::RectLayerKHR>,
+
+    /// Buffer for markers written by `CommandEncoder::checkpoint`.
+    breadcrumbs: Arc<Breadcrumbs>,
+
+    /// Buffer with all byte values, see `Queue::byte_table`.
+    byte_table: Option<Buffer>,
 }
 
 impl Drop for Queue {
@@ -276,6 +660,9 @@ impl Drop for Queue {
         unsafe {
             device.queue_wait_idle(self.handle).unwrap();
 
+            // Null handle is ignored.
+            device.destroy_semaphore(self.pending_epochs.timeline, None);
+
             // Queue is idle, all epochs must be complete.
             self.pending_epochs.destroy_all(device, &mut self.pools);
 
@@ -283,6 +670,14 @@ impl Drop for Queue {
                 epoch.destroy(device, &mut self.pools);
             }
 
+            for epoch in &mut self.free_epochs {
+                epoch.destroy(device, &mut self.pools);
+            }
+
+            for descriptors in &mut self.free_descriptors {
+                descriptors.destroy(device);
+            }
+
             for pool in &mut self.pools {
                 debug_assert_eq!(pool.allocated, 0, "All command buffers must be deallocated");
                 device.destroy_command_pool(pool.pool, None);
@@ -298,27 +693,73 @@ impl fmt::Debug for Queue {
 }
 
 impl Queue {
-    pub(super) fn new(device: Device, handle: vk::Queue, flags: QueueFlags, family: u32) -> Self {
-        Queue {
+    pub(super) fn new(
+        device: Device,
+        handle: vk::Queue,
+        flags: QueueFlags,
+        family: u32,
+    ) -> Result<Self, OutOfMemory> {
+        let timeline = if device.timeline_semaphore() {
+            device.new_timeline_semaphore()?
+        } else {
+            vk::Semaphore::null()
+        };
+
+        Ok(Queue {
             device,
             handle,
             flags,
             family,
             wait_semaphores: Vec::new(),
             wait_stages: Vec::new(),
+            wait_frames: Vec::new(),
             signal_semaphores: Vec::new(),
             pools: VecDeque::new(),
+            pool_config: QueuePoolConfig::default(),
             free_refs: Vec::new(),
+            free_descriptors: Vec::new(),
+            stats: QueueStats::default(),
+            descriptor_sets_this_check_point: 0,
+            submissions_since_check_point: 0,
+            warned_forced_check_point: false,
+            check_point_index: 0,
+            last_submission: 0,
             this_epoch: None,
-            pending_epochs: PendingEpochs::new(),
+            pending_epochs: PendingEpochs::new(timeline),
+            free_epochs: Vec::new(),
 
-            command_buffers: Vec::new(),
-            command_buffer_submit: Vec::new(),
             present_semaphores: Vec::new(),
             present_swapchains: Vec::new(),
             present_indices: Vec::new(),
             present_fences: Vec::new(),
+            present_submitted: Vec::new(),
+            present_damage: Vec::new(),
+            present_rects: Vec::new(),
+            breadcrumbs: Arc::new(Breadcrumbs::new()),
+            byte_table: None,
+        })
+    }
+
+    /// Returns host-visible buffer that contains every byte value at its own offset.
+    ///
+    /// Encoders copy unaligned bytes from it,
+    /// as `vkCmdUpdateBuffer` writes only whole dwords.
+    /// Created when the first encoder is.
+    fn byte_table(&mut self) -> Result<Buffer, OutOfMemory> {
+        if let Some(table) = &self.byte_table {
+            return Ok(table.clone());
         }
+
+        let data: [u8; 256] = std::array::from_fn(|byte| byte as u8);
+        let table = self.device.new_buffer_init(BufferInitDesc {
+            data: &data,
+            usage: BufferUsage::TRANSFER_SRC,
+            memory: Memory::Upload,
+            name: "mev.byte-table",
+        })?;
+
+        self.byte_table = Some(table.clone());
+        Ok(table)
     }
 
     pub(super) fn add_wait(&mut self, semaphores: vk::Semaphore, before: PipelineStages) {
@@ -327,261 +768,242 @@ impl Queue {
             .push(ash::vk::PipelineStageFlags::TOP_OF_PIPE | before.into_ash());
     }
 
-    fn refresh_pools(pools: &mut VecDeque<Pool>, device: &ash::Device) -> Result<(), OutOfMemory> {
-        if let Some(front) = pools.front_mut() {
-            if front.allocated == 0 {
-                // If front pool has no allocated command buffers, reuse it.
-
-                // Since pool is in array it *was* used to allocate command buffers
-                // unless allocation of the first command buffer failed.
-                // So don't hesitate to reset it first.
+    /// Clears waits added into the submission that was just made.
+    fn clear_waits(&mut self) {
+        self.wait_semaphores.clear();
+        self.wait_stages.clear();
+        for flag in self.wait_frames.drain(..) {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
 
-                // Keep resources allocated by the pool.
+    /// Submits command buffers with pending waits and signals.
+    ///
+    /// Signals queue's timeline with index of the new submission if there is one.
+    fn queue_submit(
+        &self,
+        command_buffers: &[vk::CommandBuffer],
+        fence: vk::Fence,
+    ) -> VkResult<()> {
+        let device = self.device.ash();
+        let timeline = self.pending_epochs.timeline;
 
-                // If resetting fails with oom, report it to the caller,
-                // allocating new command buffer will probably fail too.
-                unsafe {
-                    device.reset_command_pool(front.pool, vk::CommandPoolResetFlags::empty())
-                }
-                .map_err(map_oom)?;
+        let info = vk::SubmitInfo::default()
+            .wait_semaphores(&self.wait_semaphores)
+            .wait_dst_stage_mask(&self.wait_stages)
+            .command_buffers(command_buffers);
 
-                /// Place the pool to the back of the queue where it will be used in `get_pool`.
-                let reset_pool = unsafe { pools.pop_front().unwrap_unchecked() };
-                pools.push_back(reset_pool);
-            }
+        if timeline == vk::Semaphore::null() {
+            let info = info.signal_semaphores(&self.signal_semaphores);
+            return unsafe { device.queue_submit(self.handle, &[info], fence) };
         }
-        Ok(())
+
+        // Values for binary semaphores are ignored.
+        let wait_values = SmallVec::<[u64; 4]>::from_elem(0, self.wait_semaphores.len());
+
+        let mut signal_semaphores = SmallVec::<[vk::Semaphore; 4]>::new();
+        let mut signal_values = SmallVec::<[u64; 4]>::new();
+        signal_semaphores.extend_from_slice(&self.signal_semaphores);
+        signal_values.resize(self.signal_semaphores.len(), 0);
+        signal_semaphores.push(timeline);
+        signal_values.push(self.last_submission + 1);
+
+        let mut timeline_info = vk::TimelineSemaphoreSubmitInfo::default()
+            .wait_semaphore_values(&wait_values)
+            .signal_semaphore_values(&signal_values);
+
+        let info = info
+            .signal_semaphores(&signal_semaphores)
+            .push_next(&mut timeline_info);
+
+        unsafe { device.queue_submit(self.handle, &[info], fence) }
     }
 
-    #[inline]
-    fn get_pool<'a>(
-        pools: &'a mut VecDeque<Pool>,
-        device: &ash::Device,
-    ) -> Result<&'a mut Pool, OutOfMemory> {
-        let more_pools = pools.len() < MAX_POOLS;
-        match pools.back() {
-            Some(pool) if !more_pools || pool.allocated == 0 => {}
-            _ => {
-                // Create a new pool.
-                // Use non-inline cold function to reduce code size.
-                // As this branch would be taken only few times at the beginning of mev usage.
-                #[cold]
-                #[inline(never)]
-                fn create_pool(
-                    device: &ash::Device,
-                    pools: &mut VecDeque<Pool>,
-                ) -> Result<(), OutOfMemory> {
-                    let pool = unsafe {
-                        device.create_command_pool(
-                            &vk::CommandPoolCreateInfo::default()
-                                .flags(vk::CommandPoolCreateFlags::TRANSIENT),
-                            None,
-                        )
-                    }
-                    .map_err(map_oom)?;
+    /// Checks that all frames presented by command buffers
+    /// can be presented from this queue.
+    fn can_present(&self, command_buffers: &[CommandBuffer]) -> bool {
+        let mut frames = command_buffers
+            .iter()
+            .flat_map(|cbuf| &cbuf.present)
+            .filter(|frame| frame.is_real())
+            .peekable();
+
+        if frames.peek().is_none() {
+            return true;
+        }
 
-                    let pool = Pool {
-                        pool,
-                        free_cbufs: Vec::new(),
-                        allocated: 0,
-                    };
+        let Some(idx) = self
+            .device
+            .queue_families()
+            .iter()
+            .position(|&family| family == self.family)
+        else {
+            return false;
+        };
 
-                    pools.push_back(pool);
-                    Ok(())
-                }
+        frames.all(|frame| frame.family_supports[idx])
+    }
 
-                create_pool(device, pools)?;
+    /// Logs the latest complete submission, labels of pending ones,
+    /// breadcrumbs and fault information if available.
+    #[cold]
+    fn report_device_lost(&self) {
+        let completed = self.pending_epochs.last_completed(self.device.ash());
+
+        let array = self.pending_epochs.array.lock();
+        let pending = array
+            .iter()
+            .chain(&self.this_epoch)
+            .flat_map(|epoch| &epoch.labels)
+            .filter(|(id, _)| id.get() > completed)
+            .map(|(id, label)| format!("{id} \"{label}\""))
+            .collect::<Vec<_>>();
+
+        let breadcrumbs = self.breadcrumbs.read();
+
+        tracing::error!(
+            "Device lost. Last completed submission: {}. Pending labeled submissions: [{}]. Breadcrumbs: {:?}",
+            SubmissionId::new(completed),
+            pending.join(", "),
+            breadcrumbs,
+        );
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        if let Some(device_fault) = self.device.device_fault() {
+            match query_device_fault(self.device.ash(), device_fault) {
+                Ok(report) => tracing::error!("Device fault: {report}"),
+                Err(err) => tracing::error!("Failed to query device fault: {err:?}"),
             }
         }
-        Ok(unsafe { pools.back_mut().unwrap_unchecked() })
     }
 
-    /// # Safety
+    /// Submits command buffers and presents frames they contain.
     ///
-    /// Must be called after fence of the epoch returned by `get_epoch` is submitted.
-    unsafe fn next_epoch(&mut self) {
-        // Safety: caller must ensure that this_epoch is not None by calling get_epoch first.
-        let epoch = unsafe { self.this_epoch.take().unwrap_unchecked() };
-        self.pending_epochs.push(epoch);
+    /// Command buffers are consumed on all paths:
+    /// submitted ones are kept until epoch completes, others are dropped.
+    fn submit_buffers(
+        &mut self,
+        command_buffers: SmallVec<[CommandBuffer; 4]>,
+        label: Option<&str>,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError> {
+        let result = self.try_submit_buffers(command_buffers, label, check_point);
+        if let Err(DeviceError::DeviceLost) = result {
+            self.report_device_lost();
+        }
+        result
     }
 
-    /// Returns current epoch to use.
-    ///
-    /// If no current epoch is set:
-    /// - Reuses the earliest epoch if there are more than 3 pending epochs.
-    /// - Or creates a new one.
-    fn get_epoch<'a>(
-        this_epoch: &'a mut Option<Epoch>,
-        pending_epochs: &mut PendingEpochs,
-        pools: &mut VecDeque<Pool>,
-        free_refs: &mut Vec<Refs>,
-        device: &Device,
-    ) -> Result<&'a mut Epoch, DeviceError> {
-        if let Some(epoch) = this_epoch {
-            return Ok(epoch);
+    fn try_submit_buffers(
+        &mut self,
+        command_buffers: SmallVec<[CommandBuffer; 4]>,
+        label: Option<&str>,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError> {
+        check_split_passes(&command_buffers);
+
+        if !self.can_present(&command_buffers) {
+            self.drop_command_buffer(command_buffers);
+            return Err(DeviceError::PresentNotSupported);
         }
 
-        match pending_epochs.recycle(device.ash(), pools)? {
-            Some(epoch) => {
-                // Always inserts since this_epoch is None.
-                return Ok(this_epoch.get_or_insert(epoch));
-            }
-            None => {
-                /// Create a new epoch fence.
-                let fence = device.new_fence()?;
-
-                // Always inserts since this_epoch is None.
-                Ok(this_epoch.get_or_insert(Epoch {
-                    fence,
-                    refs: Vec::new(),
-                    cbufs: Vec::new(),
-                }))
-            }
+        if presents_twice(
+            command_buffers
+                .iter()
+                .flat_map(|cbuf| &cbuf.present)
+                .map(|frame| (frame.swapchain, frame.idx)),
+        ) {
+            self.drop_command_buffer(command_buffers);
+            return Err(DeviceError::FramePresentedTwice);
         }
-    }
-}
 
-impl Deref for Queue {
-    type Target = Device;
-
-    #[inline(always)]
-    fn deref(&self) -> &Device {
-        &self.device
-    }
-}
+        let check_point = self.check_point_required(check_point);
 
-#[hidden_trait::expose]
-impl crate::traits::Queue for Queue {
-    /// Get the device associated with this queue.
-    #[inline(always)]
-    fn device(&self) -> &Device {
-        &self.device
-    }
-
-    /// Get the queue family index.
-    #[inline(always)]
-    fn family(&self) -> u32 {
-        self.family
-    }
-
-    /// Create a new command encoder associated with this queue.
-    /// The encoder must be submitted to the queue it was created from.
-    fn new_command_encoder(&mut self) -> Result<CommandEncoder, OutOfMemory> {
-        let device = self.device.ash();
-        Self::refresh_pools(&mut self.pools, device)?;
-        let pool = Self::get_pool(&mut self.pools, device)?;
-
-        let device = self.device.ash();
-
-        let handle = pool.allocate(device)?;
-
-        Ok(CommandEncoder::new(
-            self.device.clone(),
-            handle,
-            pool.pool,
-            self.free_refs.pop().unwrap_or_else(Refs::new),
-        ))
-    }
-
-    /// Submit command buffers to the queue.
-    ///
-    /// If `check_point` is `true`, inserts a checkpoint into queue and check previous checkpoints.
-    /// Checkpoints are required for resource reclamation.
-    fn submit<I>(&mut self, command_buffers: I, check_point: bool) -> Result<(), DeviceError>
-    where
-        I: IntoIterator<Item = CommandBuffer>,
-    {
-        debug_assert!(self.command_buffer_submit.is_empty());
-        debug_assert!(self.command_buffers.is_empty());
+        trace_span!(
+            "mev.submit",
+            cbufs = command_buffers.len(),
+            waits = self.wait_semaphores.len(),
+            signals = self.signal_semaphores.len(),
+            check_point,
+            label = label.unwrap_or(""),
+        );
 
         let signal_semaphores_len = self.signal_semaphores.len();
         let present_semaphores_len = self.present_semaphores.len();
         let present_swapchains_len = self.present_swapchains.len();
         let present_indices_len = self.present_indices.len();
+        let present_fences_len = self.present_fences.len();
+        let present_submitted_len = self.present_submitted.len();
+        let present_damage_len = self.present_damage.len();
+        let present_rects_len = self.present_rects.len();
 
-        let epoch = match Self::get_epoch(
+        // Fence is null if queue has timeline semaphore.
+        let epoch_fence = match Self::get_epoch(
             &mut self.this_epoch,
             &mut self.pending_epochs,
+            &mut self.free_epochs,
             &mut self.pools,
             &mut self.free_refs,
+            &mut self.free_descriptors,
             &self.device,
         ) {
-            Ok(epoch) => epoch,
+            Ok(epoch) => epoch.fence,
             Err(DeviceError::OutOfMemory) => {
                 self.drop_command_buffer(command_buffers);
                 return Err(DeviceError::OutOfMemory);
             }
-            Err(DeviceError::DeviceLost) => return Err(DeviceError::DeviceLost),
+            Err(err) => return Err(err),
         };
 
-        // Add handle to list of command buffers to submit.
-        // Collect frames to present and command buffers into the cache array.
-        for mut cbuf in command_buffers {
-            self.command_buffer_submit.push(cbuf.handle);
+        // Collect handles of command buffers to submit and frames to present.
+        let mut handles = SmallVec::<[vk::CommandBuffer; 4]>::new();
+        for cbuf in &command_buffers {
+            handles.push(cbuf.handle);
 
             for frame in &cbuf.present {
-                if frame.is_real() {
-                    self.signal_semaphores.push(frame.present);
-                    self.present_semaphores.push(frame.present);
-                    self.present_swapchains.push(frame.swapchain);
-                    self.present_indices.push(frame.idx);
-                    self.present_fences.push(frame.fence);
-                } else {
-                    self.signal_semaphores.push(frame.present);
+                debug_assert!(frame.is_real(), "Fake frames are never presented");
+                self.signal_semaphores.push(frame.present);
+                self.present_semaphores.push(frame.present);
+                self.present_swapchains.push(frame.swapchain);
+                self.present_indices.push(frame.idx);
+                self.present_fences.push(frame.fence);
+                if let Some(submitted) = &frame.fence_submitted {
+                    self.present_submitted.push(submitted.clone());
                 }
-            }
 
-            self.command_buffers.push(cbuf);
+                let start = self.present_rects.len();
+                self.present_rects.extend_from_slice(&frame.damage);
+                self.present_damage.push(start..self.present_rects.len());
+            }
         }
 
         let fence = if check_point {
-            epoch.fence
+            epoch_fence
         } else {
             ash::vk::Fence::null()
         };
 
-        let result = unsafe {
-            self.device.ash().queue_submit(
-                self.handle,
-                &[vk::SubmitInfo::default()
-                    .wait_semaphores(&self.wait_semaphores)
-                    .wait_dst_stage_mask(&self.wait_stages)
-                    .signal_semaphores(&self.signal_semaphores)
-                    .command_buffers(&self.command_buffer_submit)],
-                fence,
-            )
-        };
-
-        self.command_buffer_submit.clear();
-
-        match result {
+        match self.queue_submit(&handles, fence) {
             Ok(()) => {}
             Err(err) => {
                 self.signal_semaphores.truncate(signal_semaphores_len);
                 self.present_semaphores.truncate(present_semaphores_len);
                 self.present_swapchains.truncate(present_swapchains_len);
                 self.present_indices.truncate(present_indices_len);
+                self.present_fences.truncate(present_fences_len);
+                self.present_submitted.truncate(present_submitted_len);
+                self.present_damage.truncate(present_damage_len);
+                self.present_rects.truncate(present_rects_len);
 
                 match err {
-                    vk::Result::ERROR_OUT_OF_HOST_MEMORY => {
-                        self.command_buffers.clear();
-                        handle_host_oom()
-                    }
+                    vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
                     vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
                         // Attempt to reclaim some resources.
-                        for mut cbuf in self.command_buffers.drain(..) {
-                            cbuf.refs.clear();
-                            self.free_refs.push(cbuf.refs);
-
-                            unsafe {
-                                deallocate_cbuf(cbuf.handle, cbuf.pool, &mut self.pools);
-                            }
-                        }
+                        self.drop_command_buffer(command_buffers);
                         return Err(DeviceError::OutOfMemory);
                     }
                     vk::Result::ERROR_DEVICE_LOST => {
                         // Nothing can be done now.
-                        self.command_buffers.clear();
                         return Err(DeviceError::DeviceLost);
                     }
                     _ => unexpected_error(err),
@@ -589,43 +1011,85 @@ impl crate::traits::Queue for Queue {
             }
         }
 
+        // Safety: `get_epoch` sets current epoch.
+        let epoch = unsafe { self.this_epoch.as_mut().unwrap_unchecked() };
+
+        self.last_submission += 1;
+        let id = SubmissionId::new(self.last_submission);
+        epoch.last_submission = self.last_submission;
+        if let Some(label) = label {
+            epoch.labels.push((id, label.into()));
+        }
+
         // Drain refs from command buffers and add them to the epoch
         // when submitting was successful.
-        for cbuf in self.command_buffers.drain(..) {
+        for mut cbuf in command_buffers {
+            let counters = cbuf.descriptors.take_counters();
+            self.stats.descriptor_pools_created += counters.pools_created;
+            self.stats.descriptor_sets_allocated += counters.sets_allocated;
+            self.descriptor_sets_this_check_point += counters.sets_allocated;
+
             epoch.refs.push(cbuf.refs);
             epoch.cbufs.push((cbuf.handle, cbuf.pool));
+            epoch.descriptors.push(cbuf.descriptors);
         }
 
-        self.wait_semaphores.clear();
-        self.wait_stages.clear();
+        self.clear_waits();
         self.signal_semaphores.clear();
 
         if check_point {
+            self.stats.descriptor_sets_last_check_point =
+                std::mem::take(&mut self.descriptor_sets_this_check_point);
+
             unsafe {
                 self.next_epoch();
             }
         }
 
         if !self.present_swapchains.is_empty() {
+            trace_span!("mev.present", swapchains = self.present_swapchains.len());
+
             debug_assert_eq!(self.present_swapchains.len(), self.present_indices.len());
             debug_assert_eq!(self.present_swapchains.len(), self.present_semaphores.len());
             debug_assert_eq!(self.present_swapchains.len(), self.present_fences.len());
 
-            let mut present_info = vk::PresentInfoKHR::default()
-                .swapchains(&self.present_swapchains)
-                .wait_semaphores(&self.present_semaphores)
-                .image_indices(&self.present_indices);
+            let result = {
+                let mut present_info = vk::PresentInfoKHR::default()
+                    .swapchains(&self.present_swapchains)
+                    .wait_semaphores(&self.present_semaphores)
+                    .image_indices(&self.present_indices);
 
-            let mut present_fence = vk::SwapchainPresentFenceInfoEXT::default();
-            if let Some(swapchain_maintenance1) = self.device.swapchain_maintenance1() {
-                present_fence = present_fence.fences(&self.present_fences);
-                present_info = present_info.push_next(&mut present_fence);
-            }
+                let mut present_fence = vk::SwapchainPresentFenceInfoEXT::default();
+                if let Some(swapchain_maintenance1) = self.device.swapchain_maintenance1() {
+                    present_fence = present_fence.fences(&self.present_fences);
+                    present_info = present_info.push_next(&mut present_fence);
+                }
+
+                // Region without rectangles means whole image is damaged.
+                let present_regions;
+                let mut present_regions_info = vk::PresentRegionsKHR::default();
+                if self
+                    .device
+                    .features()
+                    .contains(Features::INCREMENTAL_PRESENT)
+                {
+                    present_regions = self
+                        .present_damage
+                        .iter()
+                        .map(|range| {
+                            vk::PresentRegionKHR::default()
+                                .rectangles(&self.present_rects[range.clone()])
+                        })
+                        .collect::<SmallVec<[_; 4]>>();
+                    present_regions_info = present_regions_info.regions(&present_regions);
+                    present_info = present_info.push_next(&mut present_regions_info);
+                }
 
-            let result = unsafe {
-                self.device
-                    .swapchain()
-                    .queue_present(self.handle, &present_info)
+                unsafe {
+                    self.device
+                        .swapchain()
+                        .queue_present(self.handle, &present_info)
+                }
             };
 
             match result {
@@ -634,6 +1098,11 @@ impl crate::traits::Queue for Queue {
                     self.present_swapchains.clear();
                     self.present_indices.clear();
                     self.present_fences.clear();
+                    for submitted in self.present_submitted.drain(..) {
+                        submitted.store(true, Ordering::Relaxed);
+                    }
+                    self.present_damage.clear();
+                    self.present_rects.clear();
                 }
                 Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => handle_host_oom(),
                 Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
@@ -645,18 +1114,404 @@ impl crate::traits::Queue for Queue {
                     | vk::Result::ERROR_SURFACE_LOST_KHR
                     | vk::Result::ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT,
                 ) => {
-                    // Images are released and semaphores are queued.
+                    // Images are released, semaphores and fences are queued.
                     self.present_semaphores.clear();
                     self.present_swapchains.clear();
                     self.present_indices.clear();
                     self.present_fences.clear();
+                    for submitted in self.present_submitted.drain(..) {
+                        submitted.store(true, Ordering::Relaxed);
+                    }
+                    self.present_damage.clear();
+                    self.present_rects.clear();
                 }
                 Err(err) => unexpected_error(err),
             };
         }
+        Ok(id)
+    }
+
+    fn refresh_pools(
+        pools: &mut VecDeque<Pool>,
+        device: &ash::Device,
+        config: &QueuePoolConfig,
+        stats: &mut QueueStats,
+    ) -> Result<(), OutOfMemory> {
+        if let Some(front) = pools.front_mut() {
+            if front.allocated == 0 {
+                // Release command buffers accumulated above the threshold
+                // so the pool doesn't grow without bound.
+                // Safety: pool was created by this device.
+                let trimmed = unsafe { front.trim(device, config.trim_threshold) };
+                stats.command_buffers_trimmed += trimmed as u64;
+
+                // If front pool has no allocated command buffers, reuse it.
+
+                // Since pool is in array it *was* used to allocate command buffers
+                // unless allocation of the first command buffer failed.
+                // So don't hesitate to reset it first.
+
+                // Keep resources allocated by the pool.
+
+                // If resetting fails with oom, report it to the caller,
+                // allocating new command buffer will probably fail too.
+                unsafe {
+                    device.reset_command_pool(front.pool, vk::CommandPoolResetFlags::empty())
+                }
+                .map_err(map_oom)?;
+                stats.command_pool_resets += 1;
+
+                /// Place the pool to the back of the queue where it will be used in `get_pool`.
+                let reset_pool = unsafe { pools.pop_front().unwrap_unchecked() };
+                pools.push_back(reset_pool);
+            }
+        }
         Ok(())
     }
 
+    #[inline]
+    fn get_pool<'a>(
+        pools: &'a mut VecDeque<Pool>,
+        device: &ash::Device,
+        config: &QueuePoolConfig,
+        stats: &mut QueueStats,
+    ) -> Result<&'a mut Pool, OutOfMemory> {
+        let more_pools = pools.len() < config.max_pools;
+        match pools.back() {
+            Some(pool) if !more_pools || pool.allocated == 0 => {}
+            _ => {
+                // Create a new pool.
+                // Use non-inline cold function to reduce code size.
+                // As this branch would be taken only few times at the beginning of mev usage.
+                #[cold]
+                #[inline(never)]
+                fn create_pool(
+                    device: &ash::Device,
+                    pools: &mut VecDeque<Pool>,
+                    preallocate: usize,
+                    stats: &mut QueueStats,
+                ) -> Result<(), OutOfMemory> {
+                    let pool = unsafe {
+                        device.create_command_pool(
+                            &vk::CommandPoolCreateInfo::default()
+                                .flags(vk::CommandPoolCreateFlags::TRANSIENT),
+                            None,
+                        )
+                    }
+                    .map_err(map_oom)?;
+
+                    let mut free_cbufs = Vec::new();
+                    if preallocate > 0 {
+                        let result = unsafe {
+                            device.allocate_command_buffers(
+                                &vk::CommandBufferAllocateInfo::default()
+                                    .command_pool(pool)
+                                    .level(vk::CommandBufferLevel::PRIMARY)
+                                    .command_buffer_count(preallocate as u32),
+                            )
+                        };
+
+                        match result {
+                            Ok(cbufs) => free_cbufs = cbufs,
+                            Err(err) => {
+                                unsafe { device.destroy_command_pool(pool, None) };
+                                return Err(map_oom(err));
+                            }
+                        }
+                        stats.command_buffers_allocated += preallocate as u64;
+                    }
+
+                    let pool = Pool {
+                        pool,
+                        free_cbufs,
+                        allocated: 0,
+                    };
+
+                    pools.push_back(pool);
+                    stats.command_pools += 1;
+                    Ok(())
+                }
+
+                create_pool(device, pools, config.preallocate_cbufs, stats)?;
+            }
+        }
+        Ok(unsafe { pools.back_mut().unwrap_unchecked() })
+    }
+
+    /// Returns whether submission must insert checkpoint.
+    ///
+    /// Forces checkpoint after [`MAX_SUBMISSIONS_WITHOUT_CHECK_POINT`] submissions without one,
+    /// otherwise resources referenced by submitted command buffers are never released.
+    fn check_point_required(&mut self, check_point: bool) -> bool {
+        self.submissions_since_check_point += 1;
+
+        if !check_point && self.submissions_since_check_point < MAX_SUBMISSIONS_WITHOUT_CHECK_POINT
+        {
+            return false;
+        }
+
+        if !check_point {
+            self.stats.forced_check_points += 1;
+
+            if !self.warned_forced_check_point {
+                self.warned_forced_check_point = true;
+                tracing::warn!(
+                    "{} submissions without checkpoint, forcing one. \
+                    Resources used by submitted commands are released only when checkpoint completes, \
+                    pass `check_point = true` to `Queue::submit` periodically, e.g. once per frame",
+                    MAX_SUBMISSIONS_WITHOUT_CHECK_POINT
+                );
+            }
+        }
+
+        self.submissions_since_check_point = 0;
+        true
+    }
+
+    /// # Safety
+    ///
+    /// Must be called after submission that closes the epoch returned by `get_epoch`.
+    unsafe fn next_epoch(&mut self) {
+        // Safety: caller must ensure that this_epoch is not None by calling get_epoch first.
+        let epoch = unsafe { self.this_epoch.take().unwrap_unchecked() };
+        self.pending_epochs.push(epoch);
+        self.check_point_index += 1;
+    }
+
+    /// Returns current epoch to use.
+    ///
+    /// If no current epoch is set:
+    /// - Reuses epoch reclaimed earlier.
+    /// - Or reuses the earliest epoch if there are more than 3 pending epochs.
+    /// - Or creates a new one.
+    fn get_epoch<'a>(
+        this_epoch: &'a mut Option<Epoch>,
+        pending_epochs: &mut PendingEpochs,
+        free_epochs: &mut Vec<Epoch>,
+        pools: &mut VecDeque<Pool>,
+        free_refs: &mut Vec<Refs>,
+        free_descriptors: &mut Vec<DescriptorPools>,
+        device: &Device,
+    ) -> Result<&'a mut Epoch, DeviceError> {
+        if let Some(epoch) = this_epoch {
+            return Ok(epoch);
+        }
+
+        if let Some(epoch) = free_epochs.pop() {
+            return Ok(this_epoch.get_or_insert(epoch));
+        }
+
+        match pending_epochs.recycle(device.ash(), pools, free_refs, free_descriptors)? {
+            Some(epoch) => {
+                // Always inserts since this_epoch is None.
+                return Ok(this_epoch.get_or_insert(epoch));
+            }
+            None => {
+                // Queue's timeline tracks completion of the epoch if there is one.
+                let fence = if pending_epochs.timeline == vk::Semaphore::null() {
+                    device.new_fence()?
+                } else {
+                    vk::Fence::null()
+                };
+
+                // Always inserts since this_epoch is None.
+                Ok(this_epoch.get_or_insert(Epoch {
+                    fence,
+                    refs: Vec::new(),
+                    cbufs: Vec::new(),
+                    reusable: Vec::new(),
+                    descriptors: Vec::new(),
+                    last_submission: 0,
+                    labels: Vec::new(),
+                }))
+            }
+        }
+    }
+}
+
+impl Queue {
+    /// Returns resources kept alive by submissions that are not known to be complete.
+    ///
+    /// Each entry contains index of the epoch, earliest first, and report of its resources.
+    /// Current epoch that is not yet closed by a checkpoint comes last.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn pending_resources(&self) -> Vec<(usize, RefsReport)> {
+        let mut reports = self.pending_epochs.report();
+        if let Some(epoch) = &self.this_epoch {
+            reports.push(epoch.report());
+        }
+        reports.into_iter().enumerate().collect()
+    }
+}
+
+impl Deref for Queue {
+    type Target = Device;
+
+    #[inline(always)]
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Queue for Queue {
+    type Device = Device;
+    type CommandEncoder = CommandEncoder;
+    type CommandBuffer = CommandBuffer;
+    type ReusableCommandEncoder = ReusableCommandEncoder;
+    type ReusableCommandBuffer = ReusableCommandBuffer;
+    type Frame = Frame;
+
+    /// Get the device associated with this queue.
+    #[inline(always)]
+    fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Get the queue family index.
+    #[inline(always)]
+    fn family(&self) -> u32 {
+        self.family
+    }
+
+    /// Get the flags of the queue family.
+    #[inline(always)]
+    fn flags(&self) -> QueueFlags {
+        self.flags
+    }
+
+    /// Get statistics of the queue.
+    #[inline(always)]
+    fn stats(&self) -> QueueStats {
+        self.stats
+    }
+
+    fn set_pool_config(&mut self, config: QueuePoolConfig) {
+        assert!(
+            config.max_pools > 0,
+            "Queue must be allowed to have a command pool"
+        );
+        self.pool_config = config;
+    }
+
+    /// Returns number of resource references kept alive by submitted commands.
+    fn pending_resource_estimate(&self) -> usize {
+        let this = self.this_epoch.as_ref().map_or(0, Epoch::resource_count);
+        this + self.pending_epochs.resource_count()
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn check_point_index(&self) -> u64 {
+        self.check_point_index
+    }
+
+    /// Create a new command encoder associated with this queue.
+    /// The encoder must be submitted to the queue it was created from.
+    fn new_command_encoder(&mut self) -> Result<CommandEncoder, OutOfMemory> {
+        let byte_table = self.byte_table()?;
+
+        let device = self.device.ash();
+        Self::refresh_pools(&mut self.pools, device, &self.pool_config, &mut self.stats)?;
+        let pool = Self::get_pool(&mut self.pools, device, &self.pool_config, &mut self.stats)?;
+
+        let device = self.device.ash();
+
+        let handle = pool.allocate(device, &mut self.stats)?;
+
+        Ok(CommandEncoder::new(
+            self.device.clone(),
+            handle,
+            pool.pool,
+            self.free_refs.pop().unwrap_or_else(Refs::new),
+            self.free_descriptors.pop().unwrap_or_default(),
+            self.flags,
+            self.breadcrumbs.clone(),
+            byte_table,
+        ))
+    }
+
+    /// Submit command buffers to the queue.
+    ///
+    /// If `check_point` is `true`, inserts a checkpoint into queue and check previous checkpoints.
+    /// Checkpoints are required for resource reclamation.
+    fn submit<I>(
+        &mut self,
+        command_buffers: I,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>
+    where
+        I: IntoIterator<Item = CommandBuffer>,
+    {
+        self.submit_buffers(command_buffers.into_iter().collect(), None, check_point)
+    }
+
+    /// Submit command buffers to the queue with a label.
+    ///
+    /// Label is kept by the epoch of the submission until it is recycled.
+    fn submit_labeled<I>(
+        &mut self,
+        command_buffers: I,
+        label: &str,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError>
+    where
+        I: IntoIterator<Item = CommandBuffer>,
+    {
+        self.submit_buffers(
+            command_buffers.into_iter().collect(),
+            Some(label),
+            check_point,
+        )
+    }
+
+    /// Submit single command buffer to the queue.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn submit_one(
+        &mut self,
+        command_buffer: CommandBuffer,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError> {
+        let mut command_buffers = SmallVec::new();
+        command_buffers.push(command_buffer);
+        self.submit_buffers(command_buffers, None, check_point)
+    }
+
+    fn last_completed_submission(&self) -> SubmissionId {
+        SubmissionId::new(self.pending_epochs.last_completed(self.device.ash()))
+    }
+
+    fn wait_submission(&mut self, id: SubmissionId) -> Result<(), DeviceError> {
+        assert!(
+            id.get() <= self.last_submission,
+            "Submission {id} was not made to this queue"
+        );
+
+        trace_timed_span!("mev.epoch_wait", submission = id.get());
+
+        let result = self.pending_epochs.wait(self.device.ash(), id.get());
+        match result {
+            Ok(true) => {
+                self.reclaim()?;
+                Ok(())
+            }
+            Ok(false) => {
+                self.wait_idle()?;
+                Ok(())
+            }
+            Err(err) => {
+                let err = map_device_error(err);
+                if let DeviceError::DeviceLost = err {
+                    self.report_device_lost();
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn last_breadcrumbs(&self) -> Vec<u32> {
+        self.breadcrumbs.read()
+    }
+
     /// Drop command buffers without submitting them to the queue.
     fn drop_command_buffer<I>(&mut self, command_buffers: I)
     where
@@ -666,35 +1521,301 @@ impl crate::traits::Queue for Queue {
             cbuf.refs.clear();
             self.free_refs.push(cbuf.refs);
 
+            let counters = cbuf.descriptors.take_counters();
+            self.stats.descriptor_pools_created += counters.pools_created;
+            self.stats.descriptor_sets_allocated += counters.sets_allocated;
+
+            // Command buffer was never submitted, so its sets are not in use.
+            unsafe {
+                cbuf.descriptors.reset(self.device.ash());
+            }
+            self.free_descriptors.push(cbuf.descriptors);
+
             unsafe {
                 deallocate_cbuf(cbuf.handle, cbuf.pool, &mut self.pools);
             }
         }
     }
 
+    /// Create a new command encoder that produces reusable command buffer.
+    ///
+    /// Reusable command buffer is allocated from its own command pool
+    /// that lives as long as the command buffer.
+    fn new_command_encoder_reusable(
+        &mut self,
+    ) -> Result<ReusableCommandEncoder, ReusableCommandsError> {
+        let byte_table = self.byte_table()?;
+
+        let device = self.device.ash();
+
+        let pool = unsafe {
+            device.create_command_pool(
+                &vk::CommandPoolCreateInfo::default().queue_family_index(self.family),
+                None,
+            )
+        }
+        .map_err(map_oom)?;
+
+        let mut cbuf = vk::CommandBuffer::null();
+
+        let result = unsafe {
+            (device.fp_v1_0().allocate_command_buffers)(
+                device.handle(),
+                &vk::CommandBufferAllocateInfo::default()
+                    .command_pool(pool)
+                    .level(vk::CommandBufferLevel::PRIMARY)
+                    .command_buffer_count(1),
+                &mut cbuf,
+            )
+        };
+
+        let result = match result {
+            vk::Result::SUCCESS => unsafe {
+                // Without `ONE_TIME_SUBMIT` command buffer stays valid after submission.
+                // `SIMULTANEOUS_USE` allows resubmitting it while it is still pending.
+                device.begin_command_buffer(
+                    cbuf,
+                    &vk::CommandBufferBeginInfo::default()
+                        .flags(vk::CommandBufferUsageFlags::SIMULTANEOUS_USE),
+                )
+            },
+            err => Err(err),
+        };
+
+        if let Err(err) = result {
+            unsafe {
+                device.destroy_command_pool(pool, None);
+            }
+            return Err(map_oom(err).into());
+        }
+
+        Ok(ReusableCommandEncoder::new(
+            self.device.clone(),
+            cbuf,
+            pool,
+            self.flags,
+            self.breadcrumbs.clone(),
+            byte_table,
+        ))
+    }
+
+    /// Submit reusable command buffer to the queue.
+    ///
+    /// Queue keeps the command buffer alive until the submission is complete.
+    fn submit_reusable(
+        &mut self,
+        command_buffer: &ReusableCommandBuffer,
+        check_point: bool,
+    ) -> Result<SubmissionId, DeviceError> {
+        let check_point = self.check_point_required(check_point);
+
+        trace_span!(
+            "mev.submit",
+            cbufs = 1,
+            waits = self.wait_semaphores.len(),
+            signals = self.signal_semaphores.len(),
+            check_point,
+        );
+
+        // Fence is null if queue has timeline semaphore.
+        let epoch_fence = match Self::get_epoch(
+            &mut self.this_epoch,
+            &mut self.pending_epochs,
+            &mut self.free_epochs,
+            &mut self.pools,
+            &mut self.free_refs,
+            &mut self.free_descriptors,
+            &self.device,
+        ) {
+            Ok(epoch) => epoch.fence,
+            Err(err) => {
+                if matches!(err, DeviceError::DeviceLost) {
+                    self.report_device_lost();
+                }
+                return Err(err);
+            }
+        };
+
+        let fence = if check_point {
+            epoch_fence
+        } else {
+            ash::vk::Fence::null()
+        };
+
+        match self.queue_submit(&[command_buffer.handle()], fence) {
+            Ok(()) => {}
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => handle_host_oom(),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => return Err(DeviceError::OutOfMemory),
+            Err(vk::Result::ERROR_DEVICE_LOST) => {
+                self.report_device_lost();
+                return Err(DeviceError::DeviceLost);
+            }
+            Err(err) => unexpected_error(err),
+        }
+
+        // Safety: `get_epoch` sets current epoch.
+        let epoch = unsafe { self.this_epoch.as_mut().unwrap_unchecked() };
+
+        self.last_submission += 1;
+        epoch.last_submission = self.last_submission;
+        epoch.reusable.push(command_buffer.inner().clone());
+
+        self.clear_waits();
+        self.signal_semaphores.clear();
+
+        if check_point {
+            unsafe {
+                self.next_epoch();
+            }
+        }
+
+        Ok(SubmissionId::new(self.last_submission))
+    }
+
+    /// Moves buffers selected by the plan to newly allocated memory.
+    ///
+    /// Selected buffers are packed into new memory chunks and content is copied into them.
+    /// Old buffers are kept alive by the command buffer
+    /// until the epoch it's submitted in completes.
+    fn execute_compaction(
+        &mut self,
+        plan: &CompactionPlan,
+        buffers: &mut [Buffer],
+    ) -> Result<(), DeviceError> {
+        // Buffers cloned or used since the plan was made are skipped.
+        let selected = plan
+            .buffers()
+            .iter()
+            .copied()
+            .filter(|&idx| buffers[idx].movable())
+            .collect::<Vec<_>>();
+
+        if selected.is_empty() {
+            return Ok(());
+        }
+
+        let sources = selected
+            .iter()
+            .map(|&idx| &buffers[idx])
+            .collect::<Vec<_>>();
+        let packed = self.device.pack_buffers(&sources)?;
+
+        let mut encoder = self.new_command_encoder()?;
+        let mut copy = encoder.copy();
+        for (idx, new) in selected.into_iter().zip(packed) {
+            let Some(new) = new else {
+                continue;
+            };
+
+            copy.copy_buffer(buffers[idx].as_buffer_slice(), new.as_buffer_slice());
+
+            // Old buffer is referenced by the command buffer.
+            buffers[idx] = new;
+        }
+        drop(copy);
+
+        encoder.barrier(PipelineStages::TRANSFER, PipelineStages::ALL_NON_RT);
+        let cbuf = encoder.finish()?;
+        self.submit_one(cbuf, true)?;
+        Ok(())
+    }
+
     /// Synchronize the access to the frame resources.
     fn sync_frame(&mut self, frame: &mut Frame, before: PipelineStages) {
         assert!(!frame.synced, "Frame must be synced exactly once");
 
+        // Fake frames have no acquire semaphore.
         if frame.acquire != vk::Semaphore::null() {
             self.add_wait(frame.acquire, before);
+            if let Some(flag) = frame.image.acquire_waited_flag() {
+                self.wait_frames.push(flag.clone());
+            }
         }
 
         frame.synced = true;
     }
 
-    fn wait_idle(&self) -> Result<(), OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), DeviceError> {
+        trace_timed_span!("mev.wait_idle");
+
         let result = unsafe { self.device.ash().queue_wait_idle(self.handle) };
 
-        let result = result.map_err(|err| match err {
-            ash::vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-            ash::vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
-            ash::vk::Result::ERROR_DEVICE_LOST => unimplemented!("Device lost"),
-            _ => unexpected_error(err),
-        });
+        if let Err(err) = result {
+            let err = map_device_error(err);
+            if let DeviceError::DeviceLost = err {
+                self.report_device_lost();
+            }
+            return Err(err);
+        }
+
+        let device = self.device.ash();
+
+        self.pending_epochs.reclaim(
+            device,
+            &mut self.pools,
+            &mut self.free_refs,
+            &mut self.free_descriptors,
+            &mut self.free_epochs,
+            true,
+        )?;
+
+        // Commands submitted without checkpoint are complete too.
+        if let Some(epoch) = &mut self.this_epoch {
+            // Safety: epoch was created by this queue and its fence is not submitted.
+            unsafe {
+                epoch.reset(
+                    device,
+                    &mut self.pools,
+                    &mut self.free_refs,
+                    &mut self.free_descriptors,
+                )?;
+            }
+        }
+        self.pending_epochs.completed = self.last_submission;
 
-        self.pending_epochs.queue_is_idle();
+        Ok(())
+    }
 
-        result
+    fn reclaim(&mut self) -> Result<(), OutOfMemory> {
+        self.pending_epochs.reclaim(
+            self.device.ash(),
+            &mut self.pools,
+            &mut self.free_refs,
+            &mut self.free_descriptors,
+            &mut self.free_epochs,
+            false,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ash::vk::Handle;
+
+    use super::*;
+
+    fn frame(swapchain: u64, idx: u32) -> (vk::SwapchainKHR, u32) {
+        (vk::SwapchainKHR::from_raw(swapchain), idx)
+    }
+
+    #[test]
+    fn distinct_frames() {
+        assert!(!presents_twice([]));
+        assert!(!presents_twice([frame(1, 0)]));
+        assert!(!presents_twice([frame(1, 0), frame(1, 1), frame(1, 2)]));
+
+        // Same image index of different swapchains.
+        assert!(!presents_twice([frame(1, 0), frame(2, 0)]));
+    }
+
+    #[test]
+    fn frame_presented_twice() {
+        assert!(presents_twice([frame(1, 0), frame(1, 0)]));
+        assert!(presents_twice([
+            frame(1, 0),
+            frame(2, 0),
+            frame(1, 1),
+            frame(1, 0)
+        ]));
     }
 }
@@ -1,15 +1,38 @@
-use super::{Buffer, CommandBuffer, ComputePipeline, Image, RenderPipeline, Sampler};
+#[cfg(any(debug_assertions, feature = "debug"))]
+use std::fmt;
+use std::hash::BuildHasher;
+
+use hashbrown::{DefaultHashBuilder, HashTable};
+
+use super::{
+    bindless::BindlessTableRef, Buffer, CommandBuffer, ComputePipeline, Image, RenderPipeline,
+    Sampler,
+};
 
 /// Stores references to vulkan objects
 /// to keep them alive.
 pub struct Refs {
     buffers: Vec<Buffer>,
     images: Vec<Image>,
+
+    /// Indices in `images` by image identity.
+    /// Same image referenced many times is kept once.
+    image_indices: HashTable<usize>,
+    hash_builder: DefaultHashBuilder,
+
     samplers: Vec<Sampler>,
     render_pipelines: Vec<RenderPipeline>,
     compute_pipelines: Vec<ComputePipeline>,
+    bindless_tables: Vec<BindlessTableRef>,
     // cbufs: Vec<CommandBuffer>,
     // refs: Vec<Refs>,
+    /// Buffers referenced by passes recorded since last barrier.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pass_buffers: hashbrown::HashSet<ash::vk::Buffer>,
+
+    /// Number of buffers referenced before current pass began.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pass_start: Option<usize>,
 }
 
 impl Refs {
@@ -17,21 +40,59 @@ impl Refs {
         Refs {
             buffers: Vec::new(),
             images: Vec::new(),
+            image_indices: HashTable::new(),
+            hash_builder: DefaultHashBuilder::default(),
             samplers: Vec::new(),
             render_pipelines: Vec::new(),
             compute_pipelines: Vec::new(),
+            bindless_tables: Vec::new(),
             // cbufs: Vec::new(),
             // refs: Vec::new(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            pass_buffers: hashbrown::HashSet::new(),
+            #[cfg(any(debug_assertions, feature = "debug"))]
+            pass_start: None,
         }
     }
 
+    /// Drops all references.
+    /// Vectors keep their capacity, so recycled refs don't grow them again.
     pub fn clear(&mut self) {
-        // self.buffers.clear();
+        self.buffers.clear();
         self.images.clear();
+        self.image_indices.clear();
         self.samplers.clear();
         self.render_pipelines.clear();
+        self.compute_pipelines.clear();
+        self.bindless_tables.clear();
         // self.cbufs.clear();
         // self.refs.clear();
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        {
+            self.pass_buffers.clear();
+            self.pass_start = None;
+        }
+    }
+
+    /// Returns number of references kept.
+    pub fn count(&self) -> usize {
+        self.buffers.len()
+            + self.images.len()
+            + self.samplers.len()
+            + self.render_pipelines.len()
+            + self.compute_pipelines.len()
+            + self.bindless_tables.len()
+    }
+
+    /// Reserves space for references of expected number of draws.
+    ///
+    /// Draws usually reference vertex and index buffers and a few images.
+    pub fn with_capacity_hint(&mut self, draws: usize) {
+        self.buffers.reserve(draws * 2);
+        self.images.reserve(draws);
+        self.image_indices
+            .reserve(draws, |&idx| self.hash_builder.hash_one(&self.images[idx]));
     }
 
     pub fn add_buffer(&mut self, buffer: Buffer) {
@@ -42,12 +103,44 @@ impl Refs {
         self.buffers.extend_from_slice(buffers);
     }
 
+    /// Adds reference to the image unless it is already referenced.
     pub fn add_image(&mut self, image: Image) {
+        let hash = self.hash_builder.hash_one(&image);
+        let images = &self.images;
+        if self
+            .image_indices
+            .find(hash, |&idx| images[idx] == image)
+            .is_some()
+        {
+            return;
+        }
+
+        let hash_builder = &self.hash_builder;
+        self.image_indices
+            .insert_unique(hash, images.len(), |&idx| {
+                hash_builder.hash_one(&images[idx])
+            });
         self.images.push(image);
     }
 
     pub fn add_images(&mut self, images: &[Image]) {
-        self.images.extend_from_slice(images);
+        for image in images {
+            self.add_image(image.clone());
+        }
+    }
+
+    /// Adds all references of `other`.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn append(&mut self, other: &Refs) {
+        self.add_buffers(&other.buffers);
+        self.add_images(&other.images);
+        self.add_samplers(&other.samplers);
+        self.render_pipelines
+            .extend_from_slice(&other.render_pipelines);
+        self.compute_pipelines
+            .extend_from_slice(&other.compute_pipelines);
+        self.bindless_tables
+            .extend_from_slice(&other.bindless_tables);
     }
 
     pub fn add_sampler(&mut self, sampler: Sampler) {
@@ -58,6 +151,22 @@ impl Refs {
         self.samplers.extend_from_slice(samplers);
     }
 
+    pub(super) fn add_bindless_table(&mut self, table: BindlessTableRef) {
+        self.bindless_tables.push(table);
+    }
+
+    pub fn buffers(&self) -> &[Buffer] {
+        &self.buffers
+    }
+
+    pub fn images(&self) -> &[Image] {
+        &self.images
+    }
+
+    pub fn samplers(&self) -> &[Sampler] {
+        &self.samplers
+    }
+
     pub fn add_render_pipeline(&mut self, pipeline: RenderPipeline) {
         self.render_pipelines.push(pipeline);
     }
@@ -66,6 +175,78 @@ impl Refs {
         self.compute_pipelines.push(pipeline);
     }
 
+    /// Marks beginning of render or compute pass.
+    /// Buffers referenced until the pass ends are considered used by the pass.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn begin_pass(&mut self) {
+        self.pass_start = Some(self.buffers.len());
+    }
+
+    /// Marks end of render or compute pass.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn end_pass(&mut self) {
+        if let Some(start) = self.pass_start.take() {
+            self.pass_buffers
+                .extend(self.buffers[start..].iter().map(Buffer::handle));
+        }
+    }
+
+    /// Forgets buffers used by passes after barrier is recorded.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn barrier(&mut self) {
+        self.pass_buffers.clear();
+        if let Some(start) = &mut self.pass_start {
+            *start = self.buffers.len();
+        }
+    }
+
+    /// Checks if buffer was used by a pass recorded since last barrier.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn used_in_pass(&self, buffer: &Buffer) -> bool {
+        self.pass_buffers.contains(&buffer.handle())
+    }
+
+    /// Adds all referenced resources to the report.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    pub fn report_into(&self, report: &mut RefsReport) {
+        let buffers = self.buffers.iter().map(|buffer| ResourceReport {
+            kind: ResourceKind::Buffer,
+            name: Some(buffer.name().into()),
+            strong_count: buffer.strong_count(),
+        });
+        let images = self.images.iter().map(|image| ResourceReport {
+            kind: ResourceKind::Image,
+            name: Some(image.name().into()),
+            strong_count: image.strong_count(),
+        });
+        let samplers = self.samplers.iter().map(|sampler| ResourceReport {
+            kind: ResourceKind::Sampler,
+            name: None,
+            strong_count: sampler.strong_count(),
+        });
+        let render_pipelines = self.render_pipelines.iter().map(|pipeline| ResourceReport {
+            kind: ResourceKind::RenderPipeline,
+            name: None,
+            strong_count: pipeline.strong_count(),
+        });
+        let compute_pipelines = self
+            .compute_pipelines
+            .iter()
+            .map(|pipeline| ResourceReport {
+                kind: ResourceKind::ComputePipeline,
+                name: None,
+                strong_count: pipeline.strong_count(),
+            });
+
+        report.resources.extend(
+            buffers
+                .chain(images)
+                .chain(samplers)
+                .chain(render_pipelines)
+                .chain(compute_pipelines),
+        );
+    }
+
     // pub fn add_cbuf(&mut self, cbuf: CommandBuffer) {
     //     self.cbufs.push(cbuf);
     // }
@@ -74,3 +255,69 @@ impl Refs {
     //     self.refs.push(refs);
     // }
 }
+
+/// Kind of the resource in [`RefsReport`].
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Buffer,
+    Image,
+    Sampler,
+    RenderPipeline,
+    ComputePipeline,
+}
+
+#[cfg(any(debug_assertions, feature = "debug"))]
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceKind::Buffer => write!(f, "buffer"),
+            ResourceKind::Image => write!(f, "image"),
+            ResourceKind::Sampler => write!(f, "sampler"),
+            ResourceKind::RenderPipeline => write!(f, "render pipeline"),
+            ResourceKind::ComputePipeline => write!(f, "compute pipeline"),
+        }
+    }
+}
+
+/// Single resource kept alive by a command buffer or a pending submission.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[derive(Clone, Debug)]
+pub struct ResourceReport {
+    /// Kind of the resource.
+    pub kind: ResourceKind,
+
+    /// Debug name of the resource, if resource kind has one.
+    pub name: Option<Box<str>>,
+
+    /// Number of strong references to the resource at the moment of the report.
+    pub strong_count: usize,
+}
+
+#[cfg(any(debug_assertions, feature = "debug"))]
+impl fmt::Display for ResourceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} '{}'", self.kind, name)?,
+            None => write!(f, "{} <unnamed>", self.kind)?,
+        }
+        write!(f, " (strong count: {})", self.strong_count)
+    }
+}
+
+/// Report of resources kept alive by a command buffer or a pending submission.
+#[cfg(any(debug_assertions, feature = "debug"))]
+#[derive(Clone, Debug, Default)]
+pub struct RefsReport {
+    pub resources: Vec<ResourceReport>,
+}
+
+#[cfg(any(debug_assertions, feature = "debug"))]
+impl fmt::Display for RefsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for resource in &self.resources {
+            writeln!(f, "{resource}")?;
+        }
+        Ok(())
+    }
+}
@@ -2,16 +2,30 @@ use std::{error::Error, fmt, sync::Arc};
 
 use ash::vk;
 
-use crate::generic::OutOfMemory;
+use crate::generic::{
+    ArgumentGroupInfo, ArgumentStageMismatch, BufferRangeExceeded, DualSourceBlendError,
+    OutOfMemory, PipelineSummary, PixelFormat, ResourceId, StageInterfaceMismatch,
+    UnknownEntryPoint, VertexStepMode,
+};
 
 use super::{device::WeakDevice, layout::PipelineLayout, shader::Library};
 
 struct Inner {
     owner: WeakDevice,
+    id: ResourceId,
     layout: PipelineLayout,
     idx: usize,
     vertex_library: Library,
     fragment_library: Option<Library>,
+    summary: PipelineSummary,
+
+    /// Formats of color targets.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    color_formats: Vec<PixelFormat>,
+
+    /// Format of depth-stencil target.
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    depth_format: Option<PixelFormat>,
 }
 
 impl Drop for Inner {
@@ -30,25 +44,41 @@ pub struct RenderPipeline {
 impl RenderPipeline {
     pub(super) fn new(
         owner: WeakDevice,
+        id: ResourceId,
         handle: vk::Pipeline,
         idx: usize,
         layout: PipelineLayout,
         vertex_library: Library,
         fragment_library: Option<Library>,
+        summary: PipelineSummary,
+        #[cfg(any(debug_assertions, feature = "debug"))] color_formats: Vec<PixelFormat>,
+        #[cfg(any(debug_assertions, feature = "debug"))] depth_format: Option<PixelFormat>,
     ) -> Self {
         RenderPipeline {
             handle,
             layout: layout.handle(),
             inner: Arc::new(Inner {
                 owner,
+                id,
                 layout,
                 idx,
                 vertex_library,
                 fragment_library,
+                summary,
+                #[cfg(any(debug_assertions, feature = "debug"))]
+                color_formats,
+                #[cfg(any(debug_assertions, feature = "debug"))]
+                depth_format,
             }),
         }
     }
 
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
     pub(super) fn handle(&self) -> vk::Pipeline {
         self.handle
     }
@@ -56,12 +86,77 @@ impl RenderPipeline {
     pub(super) fn layout(&self) -> &PipelineLayout {
         &self.inner.layout
     }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn color_formats(&self) -> &[PixelFormat] {
+        &self.inner.color_formats
+    }
+
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn depth_format(&self) -> Option<PixelFormat> {
+        self.inner.depth_format
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Resource for RenderPipeline {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn id(&self) -> ResourceId {
+        self.inner.id
+    }
+
+    fn describe(&self) -> String {
+        self.inner.summary.describe(self.inner.id)
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Pipeline for RenderPipeline {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn argument_groups(&self) -> &[ArgumentGroupInfo] {
+        self.inner.summary.arguments()
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn constants_size(&self) -> usize {
+        self.inner.summary.constants()
+    }
 }
 
 #[derive(Debug)]
 pub enum CreatePipelineErrorKind {
     OutOfMemory,
     InvalidShaderEntry,
+    DualSourceBlend(DualSourceBlendError),
+    VertexStrideTooLarge {
+        buffer: u32,
+        stride: u32,
+        limit: u32,
+    },
+    UnsupportedVertexStepMode {
+        buffer: u32,
+        step_mode: VertexStepMode,
+    },
+    VertexAttributeBeyondStride {
+        attribute: u32,
+        buffer: u32,
+        stride: u32,
+    },
+    VertexStrideMisaligned {
+        buffer: u32,
+        stride: u32,
+        alignment: u32,
+    },
+    ArgumentStageMismatch(ArgumentStageMismatch),
+    BufferRangeExceeded(BufferRangeExceeded),
+    TooManyArgumentGroups {
+        requested: u32,
+        max: u32,
+    },
+    StageInterfaceMismatch(StageInterfaceMismatch),
+    UnknownEntryPoint(UnknownEntryPoint),
 }
 
 impl From<OutOfMemory> for CreatePipelineErrorKind {
@@ -76,6 +171,49 @@ impl fmt::Display for CreatePipelineErrorKind {
         match self {
             CreatePipelineErrorKind::OutOfMemory => fmt::Display::fmt(&OutOfMemory, f),
             CreatePipelineErrorKind::InvalidShaderEntry => write!(f, "invalid shader entry"),
+            CreatePipelineErrorKind::DualSourceBlend(err) => fmt::Display::fmt(err, f),
+            CreatePipelineErrorKind::VertexStrideTooLarge {
+                buffer,
+                stride,
+                limit,
+            } => write!(
+                f,
+                "vertex buffer {buffer} stride {stride} exceeds device limit `maxVertexInputBindingStride` {limit}"
+            ),
+            CreatePipelineErrorKind::UnsupportedVertexStepMode { buffer, step_mode } => write!(
+                f,
+                "vertex buffer {buffer} step mode {step_mode:?} is not supported by the device"
+            ),
+            CreatePipelineErrorKind::VertexAttributeBeyondStride {
+                attribute,
+                buffer,
+                stride,
+            } => write!(
+                f,
+                "vertex attribute {attribute} extends past stride {stride} of vertex buffer {buffer}, \
+                not supported by portability subset device"
+            ),
+            CreatePipelineErrorKind::VertexStrideMisaligned {
+                buffer,
+                stride,
+                alignment,
+            } => write!(
+                f,
+                "vertex buffer {buffer} stride {stride} is not a multiple of \
+                `minVertexInputBindingStrideAlignment` {alignment} of portability subset device"
+            ),
+            CreatePipelineErrorKind::ArgumentStageMismatch(mismatch) => {
+                fmt::Display::fmt(mismatch, f)
+            }
+            CreatePipelineErrorKind::BufferRangeExceeded(err) => fmt::Display::fmt(err, f),
+            CreatePipelineErrorKind::TooManyArgumentGroups { requested, max } => write!(
+                f,
+                "pipeline uses {requested} argument groups, device limit `maxBoundDescriptorSets` is {max}"
+            ),
+            CreatePipelineErrorKind::StageInterfaceMismatch(mismatch) => {
+                fmt::Display::fmt(mismatch, f)
+            }
+            CreatePipelineErrorKind::UnknownEntryPoint(err) => fmt::Display::fmt(err, f),
         }
     }
 }
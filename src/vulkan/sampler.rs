@@ -5,7 +5,7 @@ use std::{
 
 use ash::vk;
 
-use crate::generic::{ArgumentKind, Automatic, SamplerDesc};
+use crate::generic::{ArgumentKind, Automatic, ResourceId, SamplerDesc, SamplerSummary};
 
 use super::{
     arguments::ArgumentsField,
@@ -15,6 +15,7 @@ use super::{
 
 struct Inner {
     owner: WeakDevice,
+    id: ResourceId,
     desc: SamplerDesc,
 }
 
@@ -66,13 +67,24 @@ impl DeviceOwned for Sampler {
 
 impl Sampler {
     #[cfg_attr(feature = "inline-more", inline(always))]
-    pub(super) fn new(owner: WeakDevice, handle: vk::Sampler, desc: SamplerDesc) -> Self {
+    pub(super) fn new(
+        owner: WeakDevice,
+        id: ResourceId,
+        handle: vk::Sampler,
+        desc: SamplerDesc,
+    ) -> Self {
         Sampler {
             handle,
-            inner: Arc::new(Inner { owner, desc }),
+            inner: Arc::new(Inner { owner, id, desc }),
         }
     }
 
+    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
     #[cfg_attr(feature = "inline-more", inline(always))]
     pub(super) fn downgrade(&self) -> WeakSampler {
         WeakSampler {
@@ -85,6 +97,29 @@ impl Sampler {
     pub(super) fn handle(&self) -> vk::Sampler {
         self.handle
     }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn desc(&self) -> &SamplerDesc {
+        &self.inner.desc
+    }
+}
+
+#[hidden_trait::expose]
+impl crate::traits::Resource for Sampler {
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn id(&self) -> ResourceId {
+        self.inner.id
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{:#?}",
+            SamplerSummary {
+                id: self.inner.id,
+                desc: &self.inner.desc,
+            }
+        )
+    }
 }
 
 impl ArgumentsField<Automatic> for Sampler {
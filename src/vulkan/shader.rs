@@ -2,13 +2,20 @@ use std::{borrow::Cow, fmt, sync::Arc};
 
 use ash::vk;
 
-use crate::generic::Shader;
+use crate::generic::{EntryPoint, EntryReflection, Shader};
 
 use super::device::WeakDevice;
 
 struct LibraryInner {
     owner: WeakDevice,
     idx: usize,
+
+    /// Reflected entry points.
+    /// `None` if library was created from SPIR-V that naga failed to parse.
+    entries: Option<Box<[EntryReflection]>>,
+
+    /// Entry points collected from reflection.
+    entry_points: Box<[EntryPoint]>,
 }
 
 impl Drop for LibraryInner {
@@ -24,13 +31,46 @@ pub struct Library {
 }
 
 impl Library {
-    pub(super) fn new(owner: WeakDevice, module: vk::ShaderModule, idx: usize) -> Self {
+    pub(super) fn new(
+        owner: WeakDevice,
+        module: vk::ShaderModule,
+        idx: usize,
+        entries: Option<Box<[EntryReflection]>>,
+    ) -> Self {
+        let entry_points = entries
+            .iter()
+            .flatten()
+            .map(|entry| EntryPoint {
+                name: entry.name.clone(),
+                stage: entry.stage,
+            })
+            .collect();
+
         Library {
             module,
-            inner: Arc::new(LibraryInner { idx, owner }),
+            inner: Arc::new(LibraryInner {
+                idx,
+                owner,
+                entries,
+                entry_points,
+            }),
         }
     }
 
+    /// Checks if fragment entry point writes second blend source.
+    /// Returns `None` if it is unknown.
+    pub(super) fn writes_second_blend_source(&self, entry: &str) -> Option<bool> {
+        let entry = self.reflect_entry(entry)?;
+        Some(entry.writes_second_blend_source)
+    }
+
+    /// Returns reflected entry point.
+    /// Returns `None` if library was not reflected or entry is not found.
+    pub(super) fn reflect_entry(&self, entry: &str) -> Option<&EntryReflection> {
+        let entries = self.inner.entries.as_ref()?;
+        entries.iter().find(|e| *e.name == *entry)
+    }
+
     pub(super) fn module(&self) -> vk::ShaderModule {
         self.module
     }
@@ -44,4 +84,9 @@ impl crate::traits::Library for Library {
             entry: Cow::Borrowed(entry),
         }
     }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn entry_points(&self) -> &[EntryPoint] {
+        &self.inner.entry_points
+    }
 }
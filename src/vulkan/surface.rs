@@ -1,7 +1,11 @@
 use std::{
     collections::VecDeque,
     fmt,
-    ops::Deref,
+    ops::{Deref, RangeInclusive},
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -9,33 +13,83 @@ use ash::vk;
 use smallvec::SmallVec;
 
 use crate::{
-    generic::{Extent2, ImageExtent, OutOfMemory, PipelineStages, SurfaceError, Swizzle, ViewDesc},
+    generic::{
+        DeviceError, Extent2, Extent3, ImageExtent, ImageUsage, Offset2, Offset3, OutOfMemory,
+        PipelineStages, PixelFormat, Recreate, SuboptimalPolicy, SuboptimalTracker, SurfaceError,
+        Swizzle, ViewAspect, ViewDesc,
+    },
     ImageDesc,
 };
 
 use super::{
-    from::{AshInto, TryAshInto},
+    from::{AshInto, IntoAsh, TryAshInto},
     handle_host_oom, unexpected_error, Device, Image, Queue,
 };
 
-const SUBOPTIMAL_RETIRE_COOLDOWN: u64 = 10;
+/// Number of frames to wait for surface capabilities to report new extent
+/// before recreating suboptimal swapchain with the same extent.
+const STALE_EXTENT_RETRIES: u32 = 2;
+
+const DEFAULT_IMAGE_COUNT: u32 = 3;
+
+/// Number of retired swapchains that triggers waiting for them.
+///
+/// Without `VK_EXT_swapchain_maintenance1` retired swapchains
+/// are destroyed only after waiting for the whole device,
+/// so they are batched to avoid waiting on every recreation.
+const MAX_RETIRED_SWAPCHAINS: usize = 8;
+
+/// Fence signaled when presentation engine is done with the frame.
+struct PresentFence {
+    fence: vk::Fence,
+
+    /// Set by the queue when present with this fence is queued.
+    /// Fence of a frame that was dropped or failed to submit is never signaled.
+    /// Frames hold clones of the flag while they are alive.
+    submitted: Arc<AtomicBool>,
+}
+
+impl PresentFence {
+    /// Returns `true` if the fence is not used by a frame or presentation engine.
+    fn is_idle(&self) -> bool {
+        !self.submitted.load(Relaxed) && Arc::strong_count(&self.submitted) == 1
+    }
+
+    /// Returns `true` if present with this fence was queued.
+    fn is_submitted(&self) -> bool {
+        self.submitted.load(Relaxed)
+    }
+}
 
 struct SwachainFences {
-    array: SmallVec<[vk::Fence; 4]>,
+    array: SmallVec<[PresentFence; 4]>,
     next: usize,
 }
 
 struct Swapchain {
     handle: vk::SwapchainKHR,
+    extent: vk::Extent2D,
     images: SmallVec<[(Image, [vk::Semaphore; 2]); 4]>,
+
+    /// Whether acquire semaphore of each image was signaled by acquisition.
+    /// Semaphores that were never signaled or that a queue waited for are unsignaled.
+    acquired: SmallVec<[bool; 4]>,
+
+    /// Unsignaled semaphore for the next acquisition.
     next: vk::Semaphore,
+
+    /// Acquire semaphores of frames that no queue waited for.
+    /// They may stay signaled and are destroyed with the swapchain.
+    stale: SmallVec<[vk::Semaphore; 2]>,
+
     fences: Option<SwachainFences>,
 }
 
+/// Used while surface has zero extent, e.g. when window is minimized.
+/// Frames of fake swapchain are never presented
+/// and don't need semaphores to synchronize with presentation engine.
 struct FakeSwapchain {
     image: Image,
-    semaphore: vk::Semaphore,
-    frame_idx: u64,
 }
 
 enum MaybeFakeSwapchain {
@@ -43,12 +97,6 @@ enum MaybeFakeSwapchain {
     Fake(FakeSwapchain),
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum SuboptimalRetire {
-    Cooldown(u64),
-    Retire,
-}
-
 pub struct Surface {
     device: Device,
     surface: vk::SurfaceKHR,
@@ -57,15 +105,20 @@ pub struct Surface {
     caps: vk::SurfaceCapabilitiesKHR,
     formats: Vec<vk::SurfaceFormatKHR>,
     modes: Vec<vk::PresentModeKHR>,
-    family_supports: Vec<bool>,
+    family_supports: Arc<[bool]>,
 
     preferred_format: vk::SurfaceFormatKHR,
     preferred_mode: vk::PresentModeKHR,
     preferred_usage: vk::ImageUsageFlags,
+    preferred_image_count: u32,
     bound_queue_family: Option<u32>,
 
-    /// Number of frames to wait before retiring a suboptimal swapchain.
-    suboptimal_retire: SuboptimalRetire,
+    /// Decides when to recreate suboptimal swapchain.
+    suboptimal: SuboptimalTracker,
+
+    /// Number of frames suboptimal swapchain was kept
+    /// because capabilities reported the same extent.
+    stale_extent_retries: u32,
 
     /// Signals that surface or device was lost.
     lost: bool,
@@ -73,9 +126,11 @@ pub struct Surface {
 
 impl Drop for Surface {
     fn drop(&mut self) {
-        self.force_clear_retired().unwrap();
-
-        let device = self.device.ash();
+        // Swapchains are destroyed after device loss too.
+        match self.force_clear_retired() {
+            Ok(()) | Err(SurfaceError::SurfaceLost) => {}
+            Err(err) => panic!("Failed to destroy retired swapchains: {err}"),
+        }
 
         match self.current.take() {
             None => {}
@@ -86,34 +141,9 @@ impl Drop for Surface {
                     .all(|(image, _)| image.detached());
                 assert!(can_destroy);
 
-                for (_, [acquire, present]) in swapchain.images {
-                    unsafe {
-                        device.destroy_semaphore(acquire, None);
-                        device.destroy_semaphore(present, None);
-                    }
-                }
-
-                unsafe {
-                    device.destroy_semaphore(swapchain.next, None);
-                }
-
-                if let Some(fences) = swapchain.fences {
-                    for fence in fences.array {
-                        unsafe {
-                            device.destroy_fence(fence, None);
-                        }
-                    }
-                }
-
-                unsafe {
-                    self.device
-                        .swapchain()
-                        .destroy_swapchain(swapchain.handle, None);
-                }
+                destroy_swapchain(&self.device, swapchain);
             }
-            Some(MaybeFakeSwapchain::Fake(fake)) => unsafe {
-                device.destroy_semaphore(fake.semaphore, None);
-            },
+            Some(MaybeFakeSwapchain::Fake(_)) => {}
         }
 
         unsafe {
@@ -147,46 +177,49 @@ impl Surface {
             caps: vk::SurfaceCapabilitiesKHR::default(),
             formats,
             modes,
-            family_supports,
+            family_supports: family_supports.into(),
 
             preferred_format,
             preferred_mode,
-            preferred_usage: vk::ImageUsageFlags::COLOR_ATTACHMENT,
+            // Transfer source allows capturing frames.
+            // It is dropped when creating swapchain if not supported.
+            preferred_usage: vk::ImageUsageFlags::COLOR_ATTACHMENT
+                | vk::ImageUsageFlags::TRANSFER_SRC,
+            preferred_image_count: DEFAULT_IMAGE_COUNT,
             bound_queue_family: None,
 
-            suboptimal_retire: SuboptimalRetire::Cooldown(SUBOPTIMAL_RETIRE_COOLDOWN),
+            suboptimal: SuboptimalTracker::new(),
+            stale_extent_retries: 0,
             lost: false,
         }
     }
 
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn handle(&self) -> vk::SurfaceKHR {
+        self.surface
+    }
+
+    /// Returns present support for each queue family used by the device.
+    /// In the same order as `Device::queue_families`.
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    pub(super) fn family_supports(&self) -> &[bool] {
+        &self.family_supports
+    }
+
     // Initialize the swapchain.
     // Retires any old swapchain.
     fn init(&mut self) -> Result<(), SurfaceError> {
+        trace_timed_span!("mev.surface_init");
+
         self.handle_retired()?;
 
         if self.lost {
             return Err(SurfaceError::SurfaceLost);
         }
-        self.suboptimal_retire = SuboptimalRetire::Cooldown(SUBOPTIMAL_RETIRE_COOLDOWN);
-
-        let result = unsafe {
-            self.device
-                .surface()
-                .get_physical_device_surface_capabilities(
-                    self.device.physical_device(),
-                    self.surface,
-                )
-        };
+        self.suboptimal.reset();
+        self.stale_extent_retries = 0;
 
-        self.caps = result.map_err(|err| match err {
-            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
-            vk::Result::ERROR_SURFACE_LOST_KHR => {
-                self.lost = true;
-                SurfaceError::SurfaceLost
-            }
-            _ => unexpected_error(err),
-        })?;
+        self.update_caps()?;
 
         let old = self.current.take();
 
@@ -204,25 +237,26 @@ impl Surface {
 
             let pixel_format = self.preferred_format.format.try_ash_into().unwrap();
 
-            let image = self.device.new_image(ImageDesc {
-                extent: ImageExtent::D2(Extent2::new(
-                    self.caps.current_extent.width.max(1),
-                    self.caps.current_extent.height.max(1),
-                )),
-                format: pixel_format,
-                usage: self.preferred_usage.ash_into(),
-                layers: 1,
-                levels: 1,
-                name: "fake-swapchain-image",
-            })?;
-
-            let semaphore = new_semaphore(self.device.ash())?;
-
-            self.current = Some(MaybeFakeSwapchain::Fake(FakeSwapchain {
-                image,
-                semaphore,
-                frame_idx: 0,
-            }));
+            let image = self
+                .device
+                .new_image(ImageDesc {
+                    extent: ImageExtent::D2(
+                        Extent2::new(
+                            self.caps.current_extent.width,
+                            self.caps.current_extent.height,
+                        )
+                        .scaled(1.0, 1),
+                    ),
+                    format: pixel_format,
+                    usage: self.preferred_usage.ash_into(),
+                    layers: 1,
+                    levels: 1,
+                    name: "fake-swapchain-image",
+                    shared_across_queues: false,
+                })
+                .map_err(OutOfMemory::from)?;
+
+            self.current = Some(MaybeFakeSwapchain::Fake(FakeSwapchain { image }));
 
             return Ok(());
         }
@@ -235,11 +269,23 @@ impl Surface {
             self.caps.current_extent
         };
 
+        // Limits may change between swapchain recreations.
+        let image_count = clamp_image_count(self.preferred_image_count, &self.caps);
+        if image_count != self.preferred_image_count {
+            tracing::info!(
+                "Surface supports {}..={} images, using {} instead of {}",
+                self.caps.min_image_count,
+                max_image_count(&self.caps),
+                image_count,
+                self.preferred_image_count
+            );
+        }
+
         let result = unsafe {
             self.device.swapchain().create_swapchain(
                 &vk::SwapchainCreateInfoKHR::default()
                     .surface(self.surface)
-                    .min_image_count(3.clamp(self.caps.min_image_count, self.caps.max_image_count))
+                    .min_image_count(image_count)
                     .image_format(self.preferred_format.format)
                     .image_color_space(self.preferred_format.color_space)
                     .image_extent(use_extent)
@@ -279,7 +325,7 @@ impl Surface {
             _ => unexpected_error(err),
         })?;
 
-        let next = new_semaphore(self.device.ash())?;
+        let next = self.device.new_semaphore()?;
 
         let result = unsafe { self.device.swapchain().get_swapchain_images(handle) };
         let images = result.map_err(|err| match err {
@@ -289,7 +335,15 @@ impl Surface {
         })?;
 
         let pixel_format = self.preferred_format.format.try_ash_into().unwrap();
-        let usage = self.preferred_usage.ash_into();
+        let usage = (self.caps.supported_usage_flags & self.preferred_usage).ash_into();
+
+        trace_event!(
+            "mev.swapchain",
+            images = images.len(),
+            width = use_extent.width,
+            height = use_extent.height,
+            format = ?pixel_format,
+        );
 
         let mut swapchain_images = SmallVec::new();
         for &handle in &images {
@@ -305,15 +359,17 @@ impl Surface {
                         base_level: 0,
                         levels: 1,
                         swizzle: Swizzle::IDENTITY,
+                        aspect: ViewAspect::Auto,
                     },
                 )
                 .unwrap();
 
-            let acquire = new_semaphore(self.device.ash())?;
-            let present = new_semaphore(self.device.ash())?;
+            let acquire = self.device.new_semaphore()?;
+            let present = self.device.new_semaphore()?;
 
             let image = Image::from_swapchain_image(
                 self.device.weak(),
+                self.device.new_resource_id(),
                 handle,
                 view,
                 view_idx,
@@ -330,8 +386,11 @@ impl Surface {
 
         self.current = Some(MaybeFakeSwapchain::Real(Swapchain {
             handle,
+            extent: use_extent,
+            acquired: SmallVec::from_elem(false, swapchain_images.len()),
             images: swapchain_images,
             next,
+            stale: SmallVec::new(),
             fences: self.device.swapchain_maintenance1().is_some().then(|| SwachainFences {
                 array: SmallVec::new(),
                 next: 0,
@@ -340,101 +399,181 @@ impl Surface {
         Ok(())
     }
 
-    fn handle_retired(&mut self) -> Result<(), OutOfMemory> {
-        self.clear_retired(true)?;
+    fn update_caps(&mut self) -> Result<(), SurfaceError> {
+        let result = unsafe {
+            self.device
+                .surface()
+                .get_physical_device_surface_capabilities(
+                    self.device.physical_device(),
+                    self.surface,
+                )
+        };
+
+        self.caps = result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
+            vk::Result::ERROR_SURFACE_LOST_KHR => {
+                self.lost = true;
+                SurfaceError::SurfaceLost
+            }
+            _ => unexpected_error(err),
+        })?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if capabilities still report extent of the current swapchain.
+    ///
+    /// Some window systems, e.g. Wayland, may report old extent
+    /// for a frame or two after resize.
+    fn extent_is_stale(&mut self) -> Result<bool, SurfaceError> {
+        let Some(MaybeFakeSwapchain::Real(swapchain)) = &self.current else {
+            return Ok(false);
+        };
+        let extent = swapchain.extent;
 
-        if self.retired.len() >= 8 {
+        self.update_caps()?;
+        Ok(self.caps.current_extent == extent)
+    }
+
+    fn handle_retired(&mut self) -> Result<(), SurfaceError> {
+        self.clear_retired(false)?;
+
+        if self.retired.len() >= MAX_RETIRED_SWAPCHAINS {
+            // Present fences signal when the oldest swapchain is not in use anymore.
+            // Wait for them instead of the whole device.
+            // Fences of frames that were not presented are never signaled,
+            // if a frame still holds one the whole device is waited for below.
+            if let Some(MaybeFakeSwapchain::Real(Swapchain {
+                fences: Some(fences),
+                ..
+            })) = self.retired.front()
+            {
+                let pending = fences
+                    .array
+                    .iter()
+                    .filter(|fence| fence.is_submitted())
+                    .map(|fence| fence.fence)
+                    .collect::<SmallVec<[_; 4]>>();
+
+                let only_presented = fences
+                    .array
+                    .iter()
+                    .all(|fence| fence.is_submitted() || fence.is_idle());
+
+                if only_presented && !pending.is_empty() {
+                    let result = self.device.wait_fences(&pending);
+                    result.map_err(|err| wait_error(&mut self.lost, err))?;
+                }
+                self.clear_retired(false)?;
+            }
+        }
+
+        if self.retired.len() >= MAX_RETIRED_SWAPCHAINS {
             self.force_clear_retired()?;
         }
 
         Ok(())
     }
 
-    fn force_clear_retired(&mut self) -> Result<(), OutOfMemory> {
-        self.device.wait_idle()?;
+    /// Waits for the device to become idle and destroys all retired swapchains.
+    ///
+    /// After device loss swapchains are destroyed as well
+    /// and `SurfaceError::SurfaceLost` is returned.
+    fn force_clear_retired(&mut self) -> Result<(), SurfaceError> {
+        let result = self
+            .device
+            .wait_idle()
+            .map_err(|err| wait_error(&mut self.lost, err));
+        if let Err(SurfaceError::OutOfMemory) = result {
+            return result;
+        }
+
+        self.clear_retired(true)?;
+
+        #[cfg(any(debug_assertions, feature = "debug"))]
+        if !self.retired.is_empty() {
+            let mut attached = String::new();
+            for swapchain in &self.retired {
+                let images: SmallVec<[&Image; 4]> = match swapchain {
+                    MaybeFakeSwapchain::Real(swapchain) => {
+                        swapchain.images.iter().map(|(image, _)| image).collect()
+                    }
+                    MaybeFakeSwapchain::Fake(fake) => std::iter::once(&fake.image).collect(),
+                };
+
+                for image in images {
+                    if !image.detached() {
+                        attached.push_str(&format!(
+                            "\n  image '{}' (strong count: {})",
+                            image.name(),
+                            image.strong_count()
+                        ));
+                    }
+                }
+            }
+
+            panic!("User-code should not hold on to swapchain images. Still attached:{attached}");
+        }
 
-        self.clear_retired(false)?;
         assert_eq!(
             self.retired.len(),
             0,
             "User-code should not hold on to swapchain images."
         );
 
-        Ok(())
+        result
     }
 
-    fn clear_retired(&mut self, mut do_wait: bool) -> Result<(), OutOfMemory> {
-        let device = self.device.ash();
-
-        while let Some(mut swapchain) = self.retired.pop_front() {
+    /// Destroys retired swapchains that are not in use anymore, oldest first.
+    ///
+    /// If `idle` is true the device is known to be idle.
+    /// Otherwise swapchains are destroyed only when their present fences are signaled,
+    /// swapchains without present fences are kept until the device is idle.
+    fn clear_retired(&mut self, idle: bool) -> Result<(), SurfaceError> {
+        while let Some(swapchain) = self.retired.pop_front() {
             match swapchain {
                 MaybeFakeSwapchain::Real(swapchain) => {
-                    let images_detached = swapchain.images.iter().all(|(image, _)| image.detached());
-                    let mut can_destroy = false;
-
-                    if images_detached {
-                        if do_wait {
-                            match swapchain.fences {
-                                None => {
-                                    self.device.wait_idle()?;
-                                    do_wait = false;
-                                    can_destroy = true;
-                                }
-                                Some(ref fences) => {
-                                    let mut all_signaled = true;
-                                    for &fence in &fences.array {
-                                        all_signaled &= self.device.get_fence_status(fence)?;
-                                    }
-                                    if all_signaled {
-                                        can_destroy = true;
-                                    }
-                                }
+                    let images_detached =
+                        swapchain.images.iter().all(|(image, _)| image.detached());
+
+                    let can_destroy = if !images_detached {
+                        false
+                    } else if idle {
+                        true
+                    } else if let Some(fences) = &swapchain.fences {
+                        let mut all_signaled = true;
+                        for fence in &fences.array {
+                            if fence.is_idle() {
+                                continue;
                             }
-                        } else {
-                            can_destroy = true;
-                        }
-                    }
-
-                    if can_destroy {
-                        for (_, [acquire, present]) in swapchain.images {
-                            unsafe {
-                                device.destroy_semaphore(acquire, None);
-                                device.destroy_semaphore(present, None);
+                            if !fence.is_submitted() {
+                                all_signaled = false;
+                                break;
                             }
-                        }
-
-                        unsafe {
-                            device.destroy_semaphore(swapchain.next, None);
-                        }
-
-                        if let Some(fences) = swapchain.fences {
-                            for fence in fences.array {
-                                unsafe {
-                                    device.destroy_fence(fence, None);
-                                }
+                            let result = self.device.get_fence_status(fence.fence);
+                            if !result.map_err(|err| wait_error(&mut self.lost, err))? {
+                                all_signaled = false;
+                                break;
                             }
                         }
+                        all_signaled
+                    } else {
+                        // Without present fences wait until the device is idle.
+                        false
+                    };
 
-                        unsafe {
-                            self.device
-                                .swapchain()
-                                .destroy_swapchain(swapchain.handle, None);
-                        }
+                    if can_destroy {
+                        destroy_swapchain(&self.device, swapchain);
                     } else {
                         // Do this later.
                         self.retired.push_front(MaybeFakeSwapchain::Real(swapchain));
                         break;
                     }
                 }
-                MaybeFakeSwapchain::Fake(fake) => unsafe {
-                    if fake.image.detached() {
-                        device.destroy_semaphore(fake.semaphore, None);
-                    } else {
-                        // Do this later.
-                        self.retired.push_front(MaybeFakeSwapchain::Fake(fake));
-                        break;
-                    }
-                },
+                // Fake swapchain owns no resources besides the image
+                // that is kept alive by its frames.
+                MaybeFakeSwapchain::Fake(_) => {}
             }
         }
 
@@ -442,18 +581,143 @@ impl Surface {
     }
 }
 
+/// Maps error of waiting for the device and marks the surface as lost on device loss.
+/// Surface can't be used after device loss, same as after its own loss.
+fn wait_error(lost: &mut bool, err: DeviceError) -> SurfaceError {
+    match err {
+        DeviceError::DeviceLost => {
+            *lost = true;
+            SurfaceError::SurfaceLost
+        }
+        _ => SurfaceError::OutOfMemory,
+    }
+}
+
 #[hidden_trait::expose]
 impl crate::traits::Surface for Surface {
-    fn next_frame(&mut self) -> Result<Frame, SurfaceError> {
-        self.clear_retired(true)?;
+    type Frame = Frame;
 
-        match self.suboptimal_retire {
-            SuboptimalRetire::Cooldown(0) => {}
-            SuboptimalRetire::Cooldown(ref mut n) => {
-                *n -= 1;
+    fn supported_usage(&self) -> Result<ImageUsage, SurfaceError> {
+        let result = unsafe {
+            self.device
+                .surface()
+                .get_physical_device_surface_capabilities(
+                    self.device.physical_device(),
+                    self.surface,
+                )
+        };
+
+        let caps = result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
+            vk::Result::ERROR_SURFACE_LOST_KHR => SurfaceError::SurfaceLost,
+            _ => unexpected_error(err),
+        })?;
+
+        Ok(caps.supported_usage_flags.ash_into())
+    }
+
+    fn supported_image_count(&self) -> Result<RangeInclusive<u32>, SurfaceError> {
+        let result = unsafe {
+            self.device
+                .surface()
+                .get_physical_device_surface_capabilities(
+                    self.device.physical_device(),
+                    self.surface,
+                )
+        };
+
+        let caps = result.map_err(|err| match err {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => SurfaceError::OutOfMemory,
+            vk::Result::ERROR_SURFACE_LOST_KHR => SurfaceError::SurfaceLost,
+            _ => unexpected_error(err),
+        })?;
+
+        Ok(caps.min_image_count..=max_image_count(&caps))
+    }
+
+    fn set_image_count(&mut self, count: u32) -> Result<(), SurfaceError> {
+        let supported = self.supported_image_count()?;
+        assert!(
+            supported.contains(&count),
+            "Surface does not support {} frame images. Supported range is {:?}",
+            count,
+            supported
+        );
+
+        if self.preferred_image_count != count {
+            self.preferred_image_count = count;
+
+            // Recreate swapchain with new image count on next frame.
+            if self.current.is_some() {
+                self.suboptimal.request();
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn image_count(&self) -> u32 {
+        match &self.current {
+            None => 0,
+            Some(MaybeFakeSwapchain::Real(swapchain)) => swapchain.images.len() as u32,
+            Some(MaybeFakeSwapchain::Fake(_)) => 1,
+        }
+    }
+
+    fn set_suboptimal_policy(&mut self, policy: SuboptimalPolicy) {
+        self.suboptimal.set_policy(policy);
+    }
+
+    #[cfg_attr(feature = "inline-more", inline(always))]
+    fn is_suboptimal(&self) -> bool {
+        self.suboptimal.is_suboptimal()
+    }
+
+    fn recreate(&mut self) {
+        self.suboptimal.request();
+    }
+
+    fn set_usage(&mut self, usage: ImageUsage) -> Result<(), SurfaceError> {
+        let supported = self.supported_usage()?;
+        assert!(
+            supported.contains(usage),
+            "Surface does not support {:?} usage for frames. Supported usage is {:?}",
+            usage,
+            supported
+        );
+
+        let format: PixelFormat = self.preferred_format.format.try_ash_into().unwrap();
+        let usage: vk::ImageUsageFlags = (usage, format).into_ash();
+        let usage = usage | vk::ImageUsageFlags::TRANSFER_SRC;
+
+        if self.preferred_usage != usage {
+            self.preferred_usage = usage;
+
+            // Recreate swapchain with new usage on next frame.
+            if self.current.is_some() {
+                self.suboptimal.request();
             }
-            SuboptimalRetire::Retire => {
-                self.init()?;
+        }
+
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> Result<Frame, SurfaceError> {
+        self.clear_retired(false)?;
+
+        match self.suboptimal.next_frame() {
+            None => {}
+            Some(Recreate::Requested) => self.init()?,
+            Some(Recreate::Suboptimal) => {
+                if self.stale_extent_retries < STALE_EXTENT_RETRIES && self.extent_is_stale()? {
+                    // New swapchain would have the same extent.
+                    self.stale_extent_retries += 1;
+                } else {
+                    self.init()?;
+                }
             }
         }
 
@@ -477,9 +741,7 @@ impl crate::traits::Surface for Surface {
                     let idx = match result {
                         Ok((idx, false)) => idx,
                         Ok((idx, true)) => {
-                            if self.suboptimal_retire == SuboptimalRetire::Cooldown(0) {
-                                self.suboptimal_retire = SuboptimalRetire::Retire;
-                            }
+                            self.suboptimal.report_suboptimal();
                             idx
                         }
                         Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => handle_host_oom(),
@@ -502,27 +764,62 @@ impl crate::traits::Surface for Surface {
                     };
 
                     let (ref image, [ref mut acquire, present]) = swapchain.images[idx as usize];
+                    let acquired = &mut swapchain.acquired[idx as usize];
+
+                    // Previous acquire semaphore of the image is used for the next acquisition
+                    // unless no queue waited for it and it may be still signaled.
+                    let replacement = if *acquired && !image.acquire_waited() {
+                        Some(self.device.new_semaphore()?)
+                    } else {
+                        None
+                    };
+
                     std::mem::swap(&mut swapchain.next, acquire);
+                    if let Some(replacement) = replacement {
+                        swapchain
+                            .stale
+                            .push(std::mem::replace(&mut swapchain.next, replacement));
+                    }
+                    *acquired = true;
+                    image.reset_acquire_waited();
 
-                    let fence = match &mut swapchain.fences {
-                        None => vk::Fence::null(),
-                        Some(fences) if fences.array.is_empty() => {
-                            let fence = self.device.new_fence()?;
-                            fences.array.push(fence);
-                            fences.next = 0;
-                            fence
-                        }
+                    // Image is owned by the application again.
+                    #[cfg(any(debug_assertions, feature = "debug"))]
+                    image.set_presented(false);
+
+                    let (fence, fence_submitted) = match &mut swapchain.fences {
+                        None => (vk::Fence::null(), None),
                         Some(fences) => {
-                            let fence = fences.array[fences.next];
-                            if self.device.get_fence_status(fence)? {
-                                self.device.reset_fences(&[fence])?;
-                                fences.next = (fences.next + 1) % fences.array.len();
-                                fence
-                            } else {
-                                let fence = self.device.new_fence()?;
+                            let reuse = match fences.array.get(fences.next) {
+                                None => false,
+                                // Fence of the frame that was not presented is unsignaled.
+                                Some(fence) if fence.is_idle() => true,
+                                Some(fence) if fence.is_submitted() => {
+                                    let signaled = self.device.get_fence_status(fence.fence);
+                                    if signaled.map_err(|err| wait_error(&mut self.lost, err))? {
+                                        self.device.reset_fences(&[fence.fence])?;
+                                        fence.submitted.store(false, Relaxed);
+                                        true
+                                    } else {
+                                        false
+                                    }
+                                }
+                                // Held by a frame that is not presented yet.
+                                Some(_) => false,
+                            };
+
+                            if !reuse {
+                                let fence = PresentFence {
+                                    fence: self.device.new_fence()?,
+                                    submitted: Arc::new(AtomicBool::new(false)),
+                                };
                                 fences.array.insert(fences.next, fence);
-                                fence
                             }
+
+                            let fence = &fences.array[fences.next];
+                            let result = (fence.fence, Some(fence.submitted.clone()));
+                            fences.next = (fences.next + 1) % fences.array.len();
+                            result
                         }
                     };
 
@@ -534,28 +831,27 @@ impl crate::traits::Surface for Surface {
                         present,
                         synced: false,
                         fence,
+                        fence_submitted,
+                        family_supports: self.family_supports.clone(),
+                        damage: Vec::new(),
                     });
                 }
                 MaybeFakeSwapchain::Fake(fake) => {
-                    if self.suboptimal_retire == SuboptimalRetire::Cooldown(0) {
-                        self.suboptimal_retire = SuboptimalRetire::Retire;
-                    }
+                    // Check if surface can be presented to again.
+                    self.suboptimal.report_suboptimal();
 
-                    let frame = Frame {
+                    return Ok(Frame {
                         swapchain: vk::SwapchainKHR::null(),
                         image: fake.image.clone(),
                         idx: 0,
-                        acquire: if fake.frame_idx > 0 {
-                            fake.semaphore
-                        } else {
-                            vk::Semaphore::null()
-                        },
-                        present: fake.semaphore,
+                        acquire: vk::Semaphore::null(),
+                        present: vk::Semaphore::null(),
                         synced: false,
                         fence: vk::Fence::null(),
-                    };
-                    fake.frame_idx += 1;
-                    return Ok(frame);
+                        fence_submitted: None,
+                        family_supports: self.family_supports.clone(),
+                        damage: Vec::new(),
+                    });
                 }
             }
         }
@@ -570,6 +866,17 @@ pub struct Frame {
     pub(super) present: vk::Semaphore,
     pub(super) synced: bool,
     pub(super) fence: vk::Fence,
+
+    /// Set by the queue when present with `fence` is queued.
+    pub(super) fence_submitted: Option<Arc<AtomicBool>>,
+
+    /// Present support for each queue family used by the device.
+    /// In the same order as `Device::queue_families`.
+    pub(super) family_supports: Arc<[bool]>,
+
+    /// Damaged regions of the frame clamped to the image.
+    /// Empty if whole frame is damaged.
+    pub(super) damage: Vec<vk::RectLayerKHR>,
 }
 
 impl Frame {
@@ -598,9 +905,37 @@ impl Deref for Frame {
 
 #[hidden_trait::expose]
 impl crate::traits::Frame for Frame {
+    type Image = Image;
+
     fn image(&self) -> &Image {
         &self.image
     }
+
+    #[inline]
+    fn is_fake(&self) -> bool {
+        !self.is_real()
+    }
+
+    #[inline]
+    fn viewport(&self) -> (Offset3<f32>, Extent3<f32>) {
+        // Shaders are compiled with `ADJUST_COORDINATE_SPACE`,
+        // so regular viewport matches Metal convention.
+        let extent = self.image.extent().into_2d();
+        (
+            Offset3::ZERO,
+            Extent3::new(extent.width() as f32, extent.height() as f32, 1.0),
+        )
+    }
+
+    #[inline]
+    fn scissor(&self) -> (Offset2<i32>, Extent2<u32>) {
+        (Offset2::ZERO, self.image.extent().into_2d())
+    }
+
+    #[inline]
+    fn has_present_fence(&self) -> bool {
+        self.fence != vk::Fence::null()
+    }
 }
 
 fn pick_format(formats: &[vk::SurfaceFormatKHR]) -> vk::SurfaceFormatKHR {
@@ -646,11 +981,104 @@ fn pick_mode(modes: &[vk::PresentModeKHR]) -> vk::PresentModeKHR {
     panic!("Can't pick present mode");
 }
 
-fn new_semaphore(device: &ash::Device) -> Result<vk::Semaphore, OutOfMemory> {
-    let result = unsafe { device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None) };
-    result.map_err(|err| match err {
-        vk::Result::ERROR_OUT_OF_HOST_MEMORY => handle_host_oom(),
-        vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory,
-        _ => unexpected_error(err),
-    })
+/// Returns maximum image count, `u32::MAX` if unbounded.
+fn max_image_count(caps: &vk::SurfaceCapabilitiesKHR) -> u32 {
+    match caps.max_image_count {
+        0 => u32::MAX,
+        max => max,
+    }
+}
+
+fn clamp_image_count(count: u32, caps: &vk::SurfaceCapabilitiesKHR) -> u32 {
+    count.clamp(caps.min_image_count, max_image_count(caps))
+}
+
+/// Destroys swapchain that is not used by the device anymore.
+/// Its semaphores are returned to the device pool.
+fn destroy_swapchain(device: &Device, swapchain: Swapchain) {
+    // Only acquire semaphores known to be unsignaled are reused.
+    // Present semaphores are destroyed as waits of presentation engine can't be tracked.
+    let mut unsignaled = SmallVec::<[vk::Semaphore; 5]>::new();
+    let mut destroy = swapchain.stale;
+
+    for ((image, [acquire, present]), acquired) in
+        swapchain.images.into_iter().zip(swapchain.acquired)
+    {
+        if !acquired || image.acquire_waited() {
+            unsignaled.push(acquire);
+        } else {
+            destroy.push(acquire);
+        }
+        destroy.push(present);
+    }
+    unsignaled.push(swapchain.next);
+
+    device.recycle_semaphores(unsignaled);
+
+    for semaphore in destroy {
+        unsafe {
+            device.ash().destroy_semaphore(semaphore, None);
+        }
+    }
+
+    if let Some(fences) = swapchain.fences {
+        for fence in fences.array {
+            unsafe {
+                device.ash().destroy_fence(fence.fence, None);
+            }
+        }
+    }
+
+    unsafe {
+        device.swapchain().destroy_swapchain(swapchain.handle, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps(min: u32, max: u32) -> vk::SurfaceCapabilitiesKHR {
+        vk::SurfaceCapabilitiesKHR {
+            min_image_count: min,
+            max_image_count: max,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn image_count_within_limits() {
+        assert_eq!(clamp_image_count(2, &caps(2, 8)), 2);
+        assert_eq!(clamp_image_count(3, &caps(2, 8)), 3);
+        assert_eq!(clamp_image_count(8, &caps(2, 8)), 8);
+    }
+
+    #[test]
+    fn image_count_clamped() {
+        assert_eq!(clamp_image_count(1, &caps(2, 8)), 2);
+        assert_eq!(clamp_image_count(16, &caps(2, 8)), 8);
+
+        // Compositor raised minimum above preferred count.
+        assert_eq!(clamp_image_count(3, &caps(4, 8)), 4);
+    }
+
+    #[test]
+    fn image_count_unbounded() {
+        assert_eq!(max_image_count(&caps(2, 0)), u32::MAX);
+        assert_eq!(clamp_image_count(3, &caps(2, 0)), 3);
+        assert_eq!(clamp_image_count(100, &caps(2, 0)), 100);
+        assert_eq!(clamp_image_count(1, &caps(2, 0)), 2);
+    }
+
+    #[test]
+    fn device_loss_loses_surface() {
+        let mut lost = false;
+        let err = wait_error(&mut lost, DeviceError::OutOfMemory);
+        assert!(matches!(err, SurfaceError::OutOfMemory));
+        assert!(!lost);
+
+        let err = wait_error(&mut lost, DeviceError::DeviceLost);
+        assert!(matches!(err, SurfaceError::SurfaceLost));
+        assert!(lost);
+    }
 }
@@ -0,0 +1,84 @@
+//! Estimated allocation sizes match memory taken by created resources.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+use mev::{BufferDesc, BufferUsage, ImageDesc, ImageUsage, Memory, PixelFormat};
+
+/// Every resource is bound to memory object of its own,
+/// so dedicated bytes grow exactly by the size of the resource.
+fn dedicated_device() -> Option<(mev::Device, mev::Queue)> {
+    common::device_with_allocator(
+        mev::Features::empty(),
+        mev::AllocatorConfig {
+            dedicated_resource_threshold: 1,
+            ..mev::AllocatorConfig::default()
+        },
+    )
+}
+
+#[test]
+fn image_estimate_matches_allocation() {
+    let Some((device, _queue)) = dedicated_device() else {
+        return;
+    };
+
+    let descs = [
+        ImageDesc::new_d2_texture(256, 256, PixelFormat::Rgba8Unorm),
+        ImageDesc::new_d2_texture(100, 30, PixelFormat::R32Float),
+        ImageDesc {
+            levels: 9,
+            ..ImageDesc::new_d2_texture(256, 256, PixelFormat::Rgba8Srgb)
+        },
+        ImageDesc {
+            layers: 6,
+            ..ImageDesc::new_d2_texture(64, 64, PixelFormat::Rgba16Float)
+        },
+        ImageDesc::new_d2(1920, 1080, PixelFormat::D32Float, ImageUsage::TARGET),
+    ];
+
+    for desc in descs {
+        let estimate = device.image_allocation_size(&desc).unwrap();
+        assert!(estimate > 0);
+        let what = format!("{desc:?}");
+
+        let before = device.allocator_stats().dedicated_bytes;
+        let image = device.new_image(desc).unwrap();
+        let actual = device.allocator_stats().dedicated_bytes - before;
+        drop(image);
+
+        assert_eq!(estimate, actual, "{what}");
+    }
+}
+
+#[test]
+fn buffer_estimate_matches_allocation() {
+    let Some((device, _queue)) = dedicated_device() else {
+        return;
+    };
+
+    for (size, usage) in [
+        (4, BufferUsage::UNIFORM),
+        (1000, BufferUsage::STORAGE),
+        (65536, BufferUsage::VERTEX | BufferUsage::TRANSFER_DST),
+        (3 << 20, BufferUsage::STORAGE | BufferUsage::TRANSFER_SRC),
+    ] {
+        let desc = BufferDesc {
+            size,
+            usage,
+            memory: Memory::Device,
+            name: "estimate",
+            shared_across_queues: false,
+        };
+
+        let estimate = device.buffer_allocation_size(&desc);
+        assert!(estimate >= size);
+
+        let before = device.allocator_stats().dedicated_bytes;
+        let buffer = device.new_buffer(desc).unwrap();
+        let actual = device.allocator_stats().dedicated_bytes - before;
+        drop(buffer);
+
+        assert_eq!(estimate, actual, "{size} bytes with {usage:?}");
+    }
+}
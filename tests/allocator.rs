@@ -0,0 +1,168 @@
+mod common;
+
+const COUNT: usize = 10_000;
+const SIZE: u64 = 1024;
+
+/// Allocates `COUNT` small upload buffers, then frees them.
+///
+/// Returns stats observed while all buffers are alive.
+fn churn(device: &mev::Device) -> mev::AllocatorStats {
+    let buffers = (0..COUNT)
+        .map(|_| {
+            device
+                .new_buffer(mev::BufferDesc {
+                    size: SIZE,
+                    usage: mev::BufferUsage::UNIFORM,
+                    memory: mev::Memory::Upload,
+                    name: "small",
+                    shared_across_queues: false,
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let stats = device.allocator_stats();
+    drop(buffers);
+    stats
+}
+
+/// Linear sub-allocation keeps number of memory objects
+/// and peak reserved memory no larger than default configuration.
+#[test]
+fn linear_small_buffers() {
+    let Some((default_device, _queue)) =
+        common::device_with_allocator(mev::Features::empty(), mev::AllocatorConfig::default())
+    else {
+        return;
+    };
+
+    let config = mev::AllocatorConfig {
+        linear_threshold: 4096,
+        ..mev::AllocatorConfig::default()
+    };
+    let Some((linear_device, _queue)) =
+        common::device_with_allocator(mev::Features::empty(), config)
+    else {
+        return;
+    };
+
+    let default_stats = churn(&default_device);
+    let linear_stats = churn(&linear_device);
+
+    assert_eq!(default_stats.linear_buffers, 0);
+    assert_eq!(linear_stats.linear_buffers, COUNT);
+
+    // Buffers are packed into few chunks.
+    let max_chunks = (COUNT as u64 * SIZE).div_ceil(config.linear_chunk_size) as usize * 2;
+    assert!(
+        linear_stats.linear_chunks <= max_chunks,
+        "{} linear chunks, expected at most {max_chunks}",
+        linear_stats.linear_chunks,
+    );
+
+    assert!(linear_stats.memory_objects <= default_stats.memory_objects);
+    assert!(linear_stats.peak_reserved_bytes <= default_stats.peak_reserved_bytes);
+
+    // Freed chunks are reused, churning again does not grow peak.
+    let again = churn(&linear_device);
+    assert_eq!(again.peak_reserved_bytes, linear_stats.peak_reserved_bytes);
+
+    let stats = linear_device.allocator_stats();
+    assert_eq!(stats.linear_buffers, 0);
+
+    linear_device.trim_memory();
+    assert_eq!(linear_device.allocator_stats().linear_chunks, 0);
+}
+
+/// Memory of freed buffers is released by `trim_memory`
+/// once queue observed completion of their use.
+#[test]
+fn trim_after_free() {
+    const BUFFER_SIZE: u64 = 4 * 1024 * 1024;
+    const BUFFERS: usize = 256;
+
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut buffers = Vec::with_capacity(BUFFERS);
+    for idx in 0..BUFFERS {
+        match device.new_buffer(mev::BufferDesc {
+            size: BUFFER_SIZE,
+            usage: mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Device,
+            name: &format!("trim-{idx}"),
+            shared_across_queues: false,
+        }) {
+            Ok(buffer) => buffers.push(buffer),
+            Err(err) => {
+                eprintln!("Skipping test, failed to allocate 1GB: {err}");
+                return;
+            }
+        }
+    }
+
+    // Keep buffers referenced by submitted commands.
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut copy = encoder.copy();
+        for buffer in &buffers {
+            copy.fill_buffer(buffer, 0);
+        }
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let alive = device.allocator_stats().reserved_bytes;
+    assert!(alive >= BUFFER_SIZE * BUFFERS as u64);
+
+    drop(buffers);
+    queue.wait_idle().unwrap();
+    queue.reclaim().unwrap();
+
+    let before = device.allocator_stats().reserved_bytes;
+    let released = device.trim_memory();
+    let after = device.allocator_stats().reserved_bytes;
+
+    assert_eq!(before - after, released);
+    assert!(
+        after < alive,
+        "{after} bytes reserved after trim, {alive} while buffers were alive"
+    );
+}
+
+/// Device-only resources over device-local budget are allocated
+/// from host-visible memory when fallback is enabled.
+#[test]
+fn fallback_over_budget() {
+    const BUFFER_SIZE: u64 = 64 * 1024;
+
+    let desc = mev::BufferDesc {
+        size: BUFFER_SIZE,
+        usage: mev::BufferUsage::STORAGE,
+        memory: mev::Memory::Device,
+        name: "over-budget",
+        shared_across_queues: false,
+    };
+
+    let config = mev::AllocatorConfig {
+        fallback_to_host_memory: true,
+        device_local_budget: 0,
+        ..mev::AllocatorConfig::default()
+    };
+    let Some((device, _queue)) = common::device_with_allocator(mev::Features::empty(), config)
+    else {
+        return;
+    };
+
+    let _buffer = match device.new_buffer(desc) {
+        Ok(buffer) => buffer,
+        Err(err) => {
+            eprintln!("Skipping test, no host-visible memory besides device-local: {err}");
+            return;
+        }
+    };
+
+    let stats = device.allocator_stats();
+    assert_eq!(stats.fallback_allocations, 1);
+    assert!(stats.fallback_bytes >= BUFFER_SIZE);
+}
@@ -0,0 +1,67 @@
+use mev::{ArgumentGroupLayout, ArgumentKind, ArgumentLayout, Arguments, ShaderStages};
+
+#[derive(mev::Arguments)]
+pub struct Material {
+    #[mev(sampled, fragment)]
+    pub albedo: mev::Image,
+    #[mev(fragment)]
+    pub sampler: mev::Sampler,
+    #[mev(uniform, stages = "vertex, fragment")]
+    pub params: mev::Buffer,
+    #[mev(storage, shader(compute))]
+    pub output: mev::Image,
+    #[mev(vertex, fragment, immutable_sampler = "linear_repeat")]
+    pub repeat: (),
+}
+
+const MATERIAL_LAYOUT: ArgumentGroupLayout<'static> = ArgumentGroupLayout {
+    arguments: &[
+        ArgumentLayout {
+            kind: ArgumentKind::SampledImage,
+            size: 1,
+            stages: ShaderStages::FRAGMENT,
+            immutable_sampler: None,
+        },
+        ArgumentLayout {
+            kind: ArgumentKind::Sampler,
+            size: 1,
+            stages: ShaderStages::FRAGMENT,
+            immutable_sampler: None,
+        },
+        ArgumentLayout {
+            kind: ArgumentKind::UniformBuffer,
+            size: 1,
+            stages: ShaderStages::VERTEX.union(ShaderStages::FRAGMENT),
+            immutable_sampler: None,
+        },
+        ArgumentLayout {
+            kind: ArgumentKind::StorageImage,
+            size: 1,
+            stages: ShaderStages::COMPUTE,
+            immutable_sampler: None,
+        },
+        ArgumentLayout {
+            kind: ArgumentKind::Sampler,
+            size: 1,
+            stages: ShaderStages::VERTEX.union(ShaderStages::FRAGMENT),
+            immutable_sampler: Some(mev::WellKnownSampler::LinearRepeat),
+        },
+    ],
+};
+
+#[test]
+fn generated_layout_matches() {
+    assert_eq!(Material::LAYOUT, MATERIAL_LAYOUT);
+}
+
+#[test]
+fn fragment_only_texture() {
+    #[derive(mev::Arguments)]
+    struct Texture {
+        #[mev(sampled, fragment)]
+        texture: mev::Image,
+    }
+
+    assert_eq!(Texture::LAYOUT.arguments.len(), 1);
+    assert_eq!(Texture::LAYOUT.arguments[0].stages, ShaderStages::FRAGMENT);
+}
@@ -0,0 +1,8 @@
+//! Attachment descriptions are typed by the kind of attachment.
+
+/// Color clears can't be used for depth-stencil attachments and vice versa.
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/attachments/*.rs");
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use mev::PipelineStages;
+
+const LEN: usize = 64;
+
+/// One step of Hillis-Steele inclusive prefix sum.
+const SCAN_STEP: &str = r#"
+struct Constants {
+    step: u32,
+}
+
+var<push_constant> pc: Constants;
+
+@group(0) @binding(0) var<storage, read_write> src: array<u32>;
+@group(0) @binding(1) var<storage, read_write> dst: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    var value = src[i];
+    if i >= pc.step {
+        value += src[i - pc.step];
+    }
+    dst[i] = value;
+}
+"#;
+
+#[derive(mev::Arguments)]
+struct Scan {
+    #[mev(storage, compute)]
+    src: mev::Buffer,
+    #[mev(storage, compute)]
+    dst: mev::Buffer,
+}
+
+#[derive(mev::DeviceRepr)]
+struct Constants {
+    step: u32,
+}
+
+/// Dependent dispatches without manual barriers
+/// produce correct prefix sum when auto barriers are enabled.
+#[test]
+fn prefix_sum() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, SCAN_STEP);
+    let pipeline = device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library,
+                entry: "main".into(),
+            })
+            .name("scan")
+            .work_group_size([LEN as u32, 1, 1])
+            .constants::<Constants>()
+            .arguments::<Scan>()
+            .build(),
+        )
+        .unwrap();
+
+    let input = (1..=LEN as u32).collect::<Vec<_>>();
+    let usage = mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_SRC;
+
+    let a = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: bytemuck::cast_slice(&input),
+            usage,
+            memory: mev::Memory::Device,
+            name: "scan-a",
+        })
+        .unwrap();
+    let b = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: bytemuck::cast_slice(&[0u32; LEN]),
+            usage,
+            memory: mev::Memory::Device,
+            name: "scan-b",
+        })
+        .unwrap();
+
+    // Each pass reads what the previous one wrote.
+    let passes = [
+        (&a, &b, 1),
+        (&b, &a, 2),
+        (&a, &b, 4),
+        (&b, &a, 8),
+        (&a, &b, 16),
+        (&b, &a, 32),
+    ];
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut compute = encoder.compute();
+        compute.set_auto_barrier(true);
+        compute.with_pipeline(&pipeline);
+
+        for (src, dst, step) in passes {
+            compute.with_arguments(
+                0,
+                &Scan {
+                    src: src.clone(),
+                    dst: dst.clone(),
+                },
+            );
+            compute.with_constants(&Constants { step });
+            compute.dispatch(mev::Extent3::new(1, 1, 1));
+        }
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &a, PipelineStages::COMPUTE_SHADER);
+    let result: &[u32] = bytemuck::cast_slice(&data);
+
+    let expected = input
+        .iter()
+        .scan(0, |sum, &x| {
+            *sum += x;
+            Some(*sum)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(result, expected);
+}
@@ -0,0 +1,69 @@
+//! Bindless tables and shaders that index them.
+
+mod common;
+
+/// Shader with fixed-size binding array of `size` textures.
+fn sized_array_shader(size: u32) -> String {
+    format!(
+        r#"
+@group(0) @binding(0) var textures: binding_array<texture_2d<f32>, {size}>;
+
+@fragment
+fn main(@builtin(position) position: vec4f) -> @location(0) vec4f {{
+    let index = u32(position.x) % {size}u;
+    return textureLoad(textures[index], vec2u(0u, 0u), 0);
+}}
+"#
+    )
+}
+
+fn new_library(device: &mev::Device, code: &str) -> Result<mev::Library, mev::CreateLibraryError> {
+    device.new_shader_library(mev::LibraryDesc {
+        name: "bindless",
+        input: mev::LibraryInput::Source(mev::ShaderSource {
+            code: code.as_bytes().into(),
+            filename: None,
+            language: mev::ShaderLanguage::Wgsl,
+        }),
+    })
+}
+
+#[test]
+fn table_capacity_matches_limit() {
+    let Some((device, _queue)) = common::device(mev::Features::DESCRIPTOR_INDEXING) else {
+        return;
+    };
+
+    let max = device.limits().max_bindless_table_size;
+    assert!(max > 0);
+
+    let table = device.new_bindless_table(max).unwrap();
+    assert_eq!(table.capacity(), max);
+    assert!(table.get(max - 1).is_none());
+}
+
+#[test]
+fn binding_array_within_limit() {
+    let Some((device, _queue)) = common::device(mev::Features::DESCRIPTOR_INDEXING) else {
+        return;
+    };
+
+    let max = device.limits().max_bindless_table_size.min(64);
+    new_library(&device, &sized_array_shader(max)).unwrap();
+}
+
+/// Metal binds tables over fixed number of texture slots,
+/// larger arrays must be rejected instead of overlapping other textures.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[test]
+fn binding_array_over_limit() {
+    let Some((device, _queue)) = common::device(mev::Features::DESCRIPTOR_INDEXING) else {
+        return;
+    };
+
+    let max = device.limits().max_bindless_table_size;
+    let Err(err) = new_library(&device, &sized_array_shader(max + 1)) else {
+        panic!("binding array of {} textures is accepted", max + 1);
+    };
+    assert!(err.to_string().contains("`textures`"), "{err}");
+}
@@ -0,0 +1,190 @@
+//! Argument groups bound before pipeline changes.
+
+mod common;
+
+use mev::PipelineStages;
+
+const LEN: usize = 64;
+
+/// Doubles input values.
+const DOUBLE: &str = r#"
+@group(0) @binding(0) var<storage, read_write> src: array<u32>;
+@group(0) @binding(1) var<storage, read_write> dst: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    dst[id.x] = src[id.x] * 2u;
+}
+"#;
+
+/// Increments output values.
+/// Doesn't use `src`, so Metal assigns different slot to `dst`.
+const INCREMENT: &str = r#"
+@group(0) @binding(1) var<storage, read_write> dst: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    dst[id.x] += 1u;
+}
+"#;
+
+/// Increments values with layout that differs from the `Pass` group.
+const INCREMENT_SINGLE: &str = r#"
+@group(0) @binding(0) var<storage, read_write> dst: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    dst[id.x] += 1u;
+}
+"#;
+
+#[derive(mev::Arguments)]
+pub struct Pass {
+    #[mev(storage, compute)]
+    pub src: mev::Buffer,
+    #[mev(storage, compute)]
+    pub dst: mev::Buffer,
+}
+
+#[derive(mev::Arguments)]
+pub struct Single {
+    #[mev(storage, compute)]
+    pub dst: mev::Buffer,
+}
+
+fn pipeline<A: mev::Arguments>(device: &mev::Device, code: &str) -> mev::ComputePipeline {
+    let library = common::library(device, mev::ShaderLanguage::Wgsl, code);
+    device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library,
+                entry: "main".into(),
+            })
+            .name("bound-groups")
+            .work_group_size([LEN as u32, 1, 1])
+            .arguments::<A>()
+            .build(),
+        )
+        .unwrap()
+}
+
+fn buffers(device: &mev::Device) -> (mev::Buffer, mev::Buffer, Vec<u32>) {
+    let input = (0..LEN as u32).collect::<Vec<_>>();
+    let usage = mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_SRC;
+
+    let src = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: bytemuck::cast_slice(&input),
+            usage,
+            memory: mev::Memory::Device,
+            name: "src",
+        })
+        .unwrap();
+    let dst = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: bytemuck::cast_slice(&[0u32; LEN]),
+            usage,
+            memory: mev::Memory::Device,
+            name: "dst",
+        })
+        .unwrap();
+
+    (src, dst, input)
+}
+
+/// Group bound with one pipeline is used by the next one with the same layout,
+/// even if it uses different set of arguments.
+#[test]
+fn compatible_pipeline_keeps_group() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let double = pipeline::<Pass>(&device, DOUBLE);
+    let increment = pipeline::<Pass>(&device, INCREMENT);
+    let (src, dst, input) = buffers(&device);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut compute = encoder.compute();
+        compute.with_pipeline(&double);
+        compute.with_arguments(
+            0,
+            &Pass {
+                src: src.clone(),
+                dst: dst.clone(),
+            },
+        );
+        compute.dispatch(mev::Extent3::new(1, 1, 1));
+        compute.barrier(
+            PipelineStages::COMPUTE_SHADER,
+            PipelineStages::COMPUTE_SHADER,
+        );
+
+        // Not bound again.
+        compute.with_pipeline(&increment);
+        compute.dispatch(mev::Extent3::new(1, 1, 1));
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &dst, PipelineStages::COMPUTE_SHADER);
+    let result: &[u32] = bytemuck::cast_slice(&data);
+    let expected = input.iter().map(|x| x * 2 + 1).collect::<Vec<_>>();
+    assert_eq!(result, expected);
+}
+
+/// Group bound again after pipeline with different layout is set
+/// is used by following pipelines.
+#[test]
+fn incompatible_pipeline_rebinds_group() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let double = pipeline::<Pass>(&device, DOUBLE);
+    let increment = pipeline::<Single>(&device, INCREMENT_SINGLE);
+    let (src, dst, input) = buffers(&device);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut compute = encoder.compute();
+        compute.with_pipeline(&double);
+        compute.with_arguments(
+            0,
+            &Pass {
+                src: src.clone(),
+                dst: dst.clone(),
+            },
+        );
+        compute.dispatch(mev::Extent3::new(1, 1, 1));
+        compute.barrier(
+            PipelineStages::COMPUTE_SHADER,
+            PipelineStages::COMPUTE_SHADER,
+        );
+
+        // Layout differs, group must be bound again.
+        compute.with_pipeline(&increment);
+        compute.with_arguments(0, &Single { dst: dst.clone() });
+        compute.dispatch(mev::Extent3::new(1, 1, 1));
+        compute.barrier(
+            PipelineStages::COMPUTE_SHADER,
+            PipelineStages::COMPUTE_SHADER,
+        );
+
+        compute.with_pipeline(&double);
+        compute.with_arguments(
+            0,
+            &Pass {
+                src: dst.clone(),
+                dst: src.clone(),
+            },
+        );
+        compute.dispatch(mev::Extent3::new(1, 1, 1));
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &src, PipelineStages::COMPUTE_SHADER);
+    let result: &[u32] = bytemuck::cast_slice(&data);
+    let expected = input.iter().map(|x| (x * 2 + 1) * 2).collect::<Vec<_>>();
+    assert_eq!(result, expected);
+}
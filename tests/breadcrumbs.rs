@@ -0,0 +1,44 @@
+//! Breadcrumb markers written by command buffers.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+#[test]
+fn markers_are_ordered() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut first = queue.new_command_encoder().unwrap();
+    first.checkpoint(1);
+    first.checkpoint(2);
+    let first = first.finish().unwrap();
+
+    let mut second = queue.new_command_encoder().unwrap();
+    second.checkpoint(3);
+    let second = second.finish().unwrap();
+
+    queue.submit([first, second], true).unwrap();
+    queue.wait_idle().unwrap();
+
+    assert_eq!(queue.last_breadcrumbs(), [1, 2, 3]);
+}
+
+#[test]
+fn reusable_buffers_skip_markers() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let Ok(mut encoder) = queue.new_command_encoder_reusable() else {
+        return;
+    };
+    encoder.checkpoint(1);
+    let reusable = encoder.finish().unwrap();
+
+    queue.submit_reusable(&reusable, true).unwrap();
+    queue.submit_reusable(&reusable, true).unwrap();
+    queue.wait_idle().unwrap();
+
+    assert!(queue.last_breadcrumbs().is_empty());
+}
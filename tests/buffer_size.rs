@@ -0,0 +1,85 @@
+mod common;
+
+fn new_buffer(
+    device: &mev::Device,
+    size: u64,
+    usage: mev::BufferUsage,
+) -> Result<mev::Buffer, mev::AllocError> {
+    device.new_buffer(mev::BufferDesc {
+        size,
+        usage,
+        memory: mev::Memory::Device,
+        name: "large",
+        shared_across_queues: false,
+    })
+}
+
+/// Buffers larger than any device supports fail with reported limit.
+#[test]
+fn too_large() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let err = new_buffer(&device, u64::MAX, mev::BufferUsage::UNIFORM).unwrap_err();
+    let mev::AllocErrorKind::BufferTooLarge { size, max } = err.kind() else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(size, u64::MAX);
+    assert!(max < u64::MAX);
+    assert_eq!(
+        err.resource(),
+        mev::AllocResource::Buffer { size: u64::MAX }
+    );
+    assert_eq!(err.name(), "large");
+
+    // Size is reported as is when allocation size can't be queried.
+    let desc = mev::BufferDesc {
+        size: u64::MAX,
+        usage: mev::BufferUsage::UNIFORM,
+        memory: mev::Memory::Device,
+        name: "large",
+        shared_across_queues: false,
+    };
+    assert_eq!(device.buffer_allocation_size(&desc), u64::MAX);
+}
+
+/// Storage buffers are limited by storage buffer range.
+#[test]
+fn storage_range() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let range = device.limits().max_storage_buffer_range;
+    let Some(size) = range.checked_add(1) else {
+        return;
+    };
+
+    let err = new_buffer(&device, size, mev::BufferUsage::STORAGE).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        mev::AllocErrorKind::BufferTooLarge { size, max: range }
+    );
+}
+
+/// Sizes just above 4GB are not truncated to 32 bits.
+#[test]
+fn above_4gb() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    // Would be 1 byte if truncated to 32 bits.
+    let size = (1u64 << 32) + 1;
+    let desc = mev::BufferDesc {
+        size,
+        usage: mev::BufferUsage::UNIFORM,
+        memory: mev::Memory::Device,
+        name: "large",
+        shared_across_queues: false,
+    };
+
+    // Query requirements without allocating memory.
+    assert!(device.buffer_allocation_size(&desc) >= size);
+}
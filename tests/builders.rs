@@ -0,0 +1,175 @@
+mod common;
+
+const SHADERS: &str = r#"
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) uv: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position + uv * 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main() {}
+"#;
+
+#[derive(mev::Arguments)]
+struct Args {
+    #[mev(uniform, vertex)]
+    uniforms: mev::Buffer,
+}
+
+#[derive(mev::DeviceRepr)]
+struct Constants {
+    scale: f32,
+    offset: mev::vec2,
+}
+
+fn shader(library: &mev::Library, entry: &'static str) -> mev::Shader<'static> {
+    mev::Shader {
+        library: library.clone(),
+        entry: entry.into(),
+    }
+}
+
+/// Builder output equals explicitly constructed render pipeline descriptor.
+#[test]
+fn render_pipeline_builder() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, SHADERS);
+
+    let blend = mev::BlendDesc::default();
+    let built = mev::RenderPipelineDesc::builder(shader(&library, "vs_main"))
+        .name("builder")
+        .fragment(shader(&library, "fs_main"))
+        .color_target(mev::PixelFormat::Rgba8Unorm)
+        .color_target_blend(mev::PixelFormat::Rgba16Float, blend)
+        .depth(mev::PixelFormat::D32Float, mev::CompareFunction::LessEqual)
+        .vertex(
+            mev::VertexLayoutDesc {
+                stride: 8,
+                step_mode: mev::VertexStepMode::Vertex,
+            },
+            [mev::VertexAttributeDesc {
+                format: mev::VertexFormat::Float32x2,
+                buffer_index: 7,
+                offset: 0,
+            }],
+        )
+        .vertex(
+            mev::VertexLayoutDesc {
+                stride: 8,
+                step_mode: mev::VertexStepMode::Instance { rate: 1 },
+            },
+            [mev::VertexAttributeDesc {
+                format: mev::VertexFormat::Float32x2,
+                buffer_index: 7,
+                offset: 0,
+            }],
+        )
+        .constants::<Constants>()
+        .arguments::<Args>()
+        .build();
+
+    let arguments = [<Args as mev::Arguments>::LAYOUT];
+    let explicit = mev::RenderPipelineDesc {
+        name: "builder",
+        vertex_shader: shader(&library, "vs_main"),
+        vertex_attributes: vec![
+            mev::VertexAttributeDesc {
+                format: mev::VertexFormat::Float32x2,
+                buffer_index: 0,
+                offset: 0,
+            },
+            mev::VertexAttributeDesc {
+                format: mev::VertexFormat::Float32x2,
+                buffer_index: 1,
+                offset: 0,
+            },
+        ],
+        vertex_layouts: vec![
+            mev::VertexLayoutDesc {
+                stride: 8,
+                step_mode: mev::VertexStepMode::Vertex,
+            },
+            mev::VertexLayoutDesc {
+                stride: 8,
+                step_mode: mev::VertexStepMode::Instance { rate: 1 },
+            },
+        ],
+        primitive_topology: mev::PrimitiveTopology::Triangle,
+        raster: Some(mev::RasterDesc {
+            fragment_shader: Some(shader(&library, "fs_main")),
+            color_targets: vec![
+                mev::ColorTargetDesc {
+                    format: mev::PixelFormat::Rgba8Unorm,
+                    blend: None,
+                },
+                mev::ColorTargetDesc {
+                    format: mev::PixelFormat::Rgba16Float,
+                    blend: Some(blend),
+                },
+            ],
+            depth_stencil: Some(mev::DepthStencilDesc {
+                format: mev::PixelFormat::D32Float,
+                write_enabled: true,
+                compare: mev::CompareFunction::LessEqual,
+            }),
+            front_face: mev::FrontFace::CounterClockwise,
+            culling: mev::Culling::Back,
+            line_width: 1.0,
+        }),
+        constants: <Constants as mev::DeviceRepr>::SIZE,
+        arguments: &arguments,
+    };
+
+    // Library has no meaningful equality, debug output omits it.
+    assert_eq!(format!("{built:?}"), format!("{explicit:?}"));
+}
+
+/// Builder without fragment shader and targets leaves rasterization disabled.
+#[test]
+fn render_pipeline_builder_defaults() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, SHADERS);
+
+    let built = mev::RenderPipelineDesc::builder(shader(&library, "vs_main")).build();
+    assert!(built.raster.is_none());
+    assert!(built.vertex_layouts.is_empty());
+    assert!(built.arguments.is_empty());
+    assert_eq!(built.constants, 0);
+    assert_eq!(built.primitive_topology, mev::PrimitiveTopology::Triangle);
+}
+
+/// Builder output equals explicitly constructed compute pipeline descriptor.
+#[test]
+fn compute_pipeline_builder() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, SHADERS);
+
+    let built = mev::ComputePipelineDesc::builder(shader(&library, "cs_main"))
+        .name("builder")
+        .work_group_size([8, 8, 1])
+        .constants::<Constants>()
+        .arguments::<Args>()
+        .build();
+
+    let arguments = [<Args as mev::Arguments>::LAYOUT];
+    let explicit = mev::ComputePipelineDesc {
+        name: "builder",
+        shader: shader(&library, "cs_main"),
+        work_group_size: [8, 8, 1],
+        constants: <Constants as mev::DeviceRepr>::SIZE,
+        arguments: &arguments,
+    };
+
+    assert_eq!(format!("{built:?}"), format!("{explicit:?}"));
+}
@@ -0,0 +1,69 @@
+//! Checkpoints forced by the queue when submissions never request them.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+/// Number of submissions made without checkpoints.
+const SUBMISSIONS: u64 = 1000;
+
+/// Same as number of submissions after which Vulkan queue forces a checkpoint.
+const FORCED_EVERY: u64 = 64;
+
+#[test]
+fn forced_check_points() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut max_pending = 0;
+    for _ in 0..SUBMISSIONS {
+        // Each submission keeps its own buffer alive until checkpoint after it completes.
+        let buffer = device
+            .new_buffer(mev::BufferDesc {
+                size: 4,
+                usage: mev::BufferUsage::TRANSFER_DST,
+                memory: mev::Memory::Device,
+                name: "scratch",
+                shared_across_queues: false,
+            })
+            .unwrap();
+
+        let mut encoder = queue.new_command_encoder().unwrap();
+        encoder.copy().fill_buffer(&buffer, 0xff);
+        queue.submit_one(encoder.finish().unwrap(), false).unwrap();
+
+        max_pending = max_pending.max(queue.pending_resource_estimate());
+    }
+
+    assert_eq!(
+        queue.stats().forced_check_points,
+        SUBMISSIONS / FORCED_EVERY
+    );
+
+    // Without forced checkpoints all 1000 buffers would be kept.
+    // Open epoch and few pending ones hold at most `FORCED_EVERY` buffers each.
+    assert!(
+        max_pending <= 4 * FORCED_EVERY as usize,
+        "{max_pending} resources kept by submitted commands"
+    );
+
+    queue.wait_idle().unwrap();
+}
+
+#[test]
+fn requested_check_points_are_not_forced() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    for idx in 0..SUBMISSIONS {
+        let encoder = queue.new_command_encoder().unwrap();
+        let check_point = idx % (FORCED_EVERY / 2) == 0;
+        queue
+            .submit_one(encoder.finish().unwrap(), check_point)
+            .unwrap();
+    }
+
+    assert_eq!(queue.stats().forced_check_points, 0);
+    queue.wait_idle().unwrap();
+}
@@ -0,0 +1,110 @@
+//! Command pool policy of Vulkan queues. Metal has no command pools.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+use mev::QueuePoolConfig;
+
+/// Records and submits `count` empty command buffers at once.
+fn burst(queue: &mut mev::Queue, count: usize) {
+    let cbufs = (0..count)
+        .map(|_| queue.new_command_encoder().unwrap().finish().unwrap())
+        .collect::<Vec<_>>();
+    queue.submit(cbufs, true).unwrap();
+}
+
+/// Command buffers are recycled instead of allocated for every submission.
+#[test]
+fn steady_state_recycles() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    const SUBMISSIONS: usize = 1000;
+
+    for idx in 0..SUBMISSIONS {
+        let encoder = queue.new_command_encoder().unwrap();
+        queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+        if idx % 10 == 9 {
+            queue.wait_idle().unwrap();
+        }
+
+        let stats = queue.stats();
+        assert!(stats.command_pools <= QueuePoolConfig::default().max_pools as u64);
+    }
+
+    let stats = queue.stats();
+    assert!(
+        stats.command_buffers_allocated < SUBMISSIONS as u64 / 4,
+        "{} command buffers allocated for {SUBMISSIONS} submissions",
+        stats.command_buffers_allocated
+    );
+    assert!(stats.command_pool_resets > 0);
+    assert_eq!(stats.command_buffers_trimmed, 0);
+}
+
+/// Encoders alive at the same time spread over at most `max_pools` pools.
+#[test]
+fn max_pools() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    queue.set_pool_config(QueuePoolConfig {
+        max_pools: 8,
+        ..QueuePoolConfig::default()
+    });
+
+    burst(&mut queue, 64);
+    assert_eq!(queue.stats().command_pools, 8);
+    queue.wait_idle().unwrap();
+}
+
+#[test]
+fn preallocate() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    queue.set_pool_config(QueuePoolConfig {
+        preallocate_cbufs: 16,
+        ..QueuePoolConfig::default()
+    });
+
+    let encoder = queue.new_command_encoder().unwrap();
+    let stats = queue.stats();
+    assert_eq!(stats.command_pools, 1);
+    assert_eq!(stats.command_buffers_allocated, 16);
+
+    queue.drop_command_buffer([encoder.finish().unwrap()]);
+}
+
+/// Free command buffers above threshold are released when the pool is reset.
+#[test]
+fn trim_threshold() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    const BURST: usize = 32;
+    const KEEP: usize = 2;
+
+    queue.set_pool_config(QueuePoolConfig {
+        max_pools: 1,
+        trim_threshold: KEEP,
+        ..QueuePoolConfig::default()
+    });
+
+    burst(&mut queue, BURST);
+    queue.wait_idle().unwrap();
+    assert_eq!(queue.stats().command_buffers_trimmed, 0);
+
+    // Pool is trimmed when it is reset for the next encoder.
+    let encoder = queue.new_command_encoder().unwrap();
+    let stats = queue.stats();
+    assert_eq!(stats.command_buffers_trimmed, (BURST - KEEP) as u64);
+    assert_eq!(stats.command_buffers_allocated, BURST as u64);
+
+    queue.drop_command_buffer([encoder.finish().unwrap()]);
+}
@@ -0,0 +1,249 @@
+//! Helpers shared by integration tests.
+//!
+//! Tests that need a GPU skip themselves when no device is available,
+//! so the suite passes on machines without drivers.
+
+#![allow(dead_code)]
+
+/// Creates device with one queue that supports graphics and compute.
+///
+/// Returns `None` if there is no suitable device or it doesn't support `features`.
+pub fn device(features: mev::Features) -> Option<(mev::Device, mev::Queue)> {
+    find_device(
+        features,
+        mev::Quirks::empty(),
+        mev::AllocatorConfig::default(),
+    )
+}
+
+/// Same as [`device`] but forces given quirks.
+pub fn device_with_quirks(
+    features: mev::Features,
+    quirks: mev::Quirks,
+) -> Option<(mev::Device, mev::Queue)> {
+    find_device(features, quirks, mev::AllocatorConfig::default())
+}
+
+/// Same as [`device`] but with given allocator configuration.
+pub fn device_with_allocator(
+    features: mev::Features,
+    allocator: mev::AllocatorConfig,
+) -> Option<(mev::Device, mev::Queue)> {
+    find_device(features, mev::Quirks::empty(), allocator)
+}
+
+fn find_device(
+    features: mev::Features,
+    quirks: mev::Quirks,
+    allocator: mev::AllocatorConfig,
+) -> Option<(mev::Device, mev::Queue)> {
+    let instance = instance()?;
+
+    for idx in 0..instance.capabilities().devices.len() {
+        if let Some(device) = create_device(&instance, idx, features, quirks, allocator) {
+            return Some(device);
+        }
+    }
+
+    eprintln!("Skipping test, no suitable device");
+    None
+}
+
+/// Creates devices on two different physical devices.
+///
+/// Returns `None` if there are less than two suitable physical devices.
+pub fn two_devices(
+    features: mev::Features,
+) -> Option<((mev::Device, mev::Queue), (mev::Device, mev::Queue))> {
+    let instance = instance()?;
+
+    let mut devices = (0..instance.capabilities().devices.len()).filter_map(|idx| {
+        create_device(
+            &instance,
+            idx,
+            features,
+            mev::Quirks::empty(),
+            mev::AllocatorConfig::default(),
+        )
+    });
+
+    match (devices.next(), devices.next()) {
+        (Some(first), Some(second)) => Some((first, second)),
+        _ => {
+            eprintln!("Skipping test, less than two suitable devices");
+            None
+        }
+    }
+}
+
+/// Creates two devices on the same physical device.
+///
+/// Returns `None` if there is no suitable device.
+pub fn two_logical_devices(
+    features: mev::Features,
+) -> Option<((mev::Device, mev::Queue), (mev::Device, mev::Queue))> {
+    let instance = instance()?;
+
+    for idx in 0..instance.capabilities().devices.len() {
+        if let Some(first) = create_device(
+            &instance,
+            idx,
+            features,
+            mev::Quirks::empty(),
+            mev::AllocatorConfig::default(),
+        ) {
+            let second = create_device(
+                &instance,
+                idx,
+                features,
+                mev::Quirks::empty(),
+                mev::AllocatorConfig::default(),
+            )?;
+            return Some((first, second));
+        }
+    }
+
+    eprintln!("Skipping test, no suitable device");
+    None
+}
+
+/// Creates device with one queue of each family.
+///
+/// Returns `None` if there is no suitable device.
+pub fn device_with_all_families(features: mev::Features) -> Option<(mev::Device, Vec<mev::Queue>)> {
+    let instance = instance()?;
+
+    for (idx, caps) in instance.capabilities().devices.iter().enumerate() {
+        if !caps.features.contains(features) {
+            continue;
+        }
+
+        let families = (0..caps.families.len() as u32).collect::<Vec<_>>();
+        match instance.create(mev::DeviceDesc {
+            idx,
+            queues: &families,
+            features,
+            quirks: mev::Quirks::empty(),
+            allocator: mev::AllocatorConfig::default(),
+        }) {
+            Ok(device) => return Some(device),
+            Err(err) => eprintln!("Failed to create device {idx}: {err}"),
+        }
+    }
+
+    eprintln!("Skipping test, no suitable device");
+    None
+}
+
+fn instance() -> Option<mev::Instance> {
+    match mev::Instance::load() {
+        Ok(instance) => Some(instance),
+        Err(err) => {
+            eprintln!("Skipping test, failed to load instance: {err}");
+            None
+        }
+    }
+}
+
+/// Creates device at `idx` with one queue that supports graphics and compute,
+/// forced quirks and allocator configuration.
+fn create_device(
+    instance: &mev::Instance,
+    idx: usize,
+    features: mev::Features,
+    quirks: mev::Quirks,
+    allocator: mev::AllocatorConfig,
+) -> Option<(mev::Device, mev::Queue)> {
+    let caps = &instance.capabilities().devices[idx];
+    if !caps.features.contains(features) {
+        return None;
+    }
+
+    let required = mev::QueueFlags::GRAPHICS | mev::QueueFlags::COMPUTE;
+    let family = caps
+        .families
+        .iter()
+        .position(|family| family.queue_flags.contains(required))?;
+
+    let result = instance.create(mev::DeviceDesc {
+        idx,
+        queues: &[family as u32],
+        features,
+        quirks,
+        allocator,
+    });
+
+    match result {
+        Ok((device, mut queues)) => Some((device, queues.pop().unwrap())),
+        Err(err) => {
+            eprintln!("Failed to create device {idx}: {err}");
+            None
+        }
+    }
+}
+
+/// Creates library from shader source code.
+pub fn library(device: &mev::Device, language: mev::ShaderLanguage, code: &str) -> mev::Library {
+    device
+        .new_shader_library(mev::LibraryDesc {
+            name: "test",
+            input: mev::LibraryInput::Source(mev::ShaderSource {
+                code: code.as_bytes().into(),
+                filename: None,
+                language,
+            }),
+        })
+        .unwrap()
+}
+
+/// Creates 2D `Rgba8Unorm` image that can be rendered to and read back.
+pub fn render_target(device: &mev::Device, width: u32, height: u32) -> mev::Image {
+    device
+        .new_image(mev::ImageDesc::new_d2(
+            width,
+            height,
+            mev::PixelFormat::Rgba8Unorm,
+            mev::ImageUsage::TARGET | mev::ImageUsage::TRANSFER_SRC,
+        ))
+        .unwrap()
+}
+
+/// Returns RGBA of the pixel at `x`, `y` in tightly packed image of `width` pixels.
+pub fn pixel(pixels: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+    let idx = (y * width + x) as usize * 4;
+    pixels[idx..idx + 4].try_into().unwrap()
+}
+
+/// Copies buffer contents into host memory.
+///
+/// `after` are stages that wrote the buffer.
+/// Blocks until the queue is idle.
+pub fn read_buffer(
+    queue: &mut mev::Queue,
+    buffer: &mev::Buffer,
+    after: mev::PipelineStages,
+) -> Vec<u8> {
+    let mut download = queue
+        .new_buffer(mev::BufferDesc {
+            size: buffer.size(),
+            usage: mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Download,
+            name: "test-readback",
+            shared_across_queues: false,
+        })
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.barrier(after, mev::PipelineStages::TRANSFER);
+    encoder.copy().copy_buffer(buffer, &download);
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    queue.wait_idle().unwrap();
+
+    let mut data = vec![0; buffer.size() as usize];
+
+    // Safety: commands that write the buffer are completed.
+    unsafe {
+        download.read_unchecked(0, &mut data);
+    }
+    data
+}
@@ -0,0 +1,95 @@
+//! Buffer compaction. Metal allocates memory per resource and never compacts.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+use mev::PipelineStages;
+
+const COUNT: usize = 64;
+const SIZE: u64 = 256 << 10;
+
+/// Creates buffers filled with their index and keeps every fourth one.
+fn fragmented(queue: &mut mev::Queue) -> Vec<mev::Buffer> {
+    let buffers = (0..COUNT)
+        .map(|idx| {
+            queue
+                .new_buffer(mev::BufferDesc {
+                    size: SIZE,
+                    usage: mev::BufferUsage::TRANSFER_SRC | mev::BufferUsage::TRANSFER_DST,
+                    memory: mev::Memory::Device,
+                    name: &format!("compaction-{idx}"),
+                    shared_across_queues: false,
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut copy = encoder.copy();
+        for (idx, buffer) in buffers.iter().enumerate() {
+            copy.fill_buffer(buffer, idx as u8);
+        }
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    queue.wait_idle().unwrap();
+
+    buffers.into_iter().step_by(4).collect()
+}
+
+fn check_content(queue: &mut mev::Queue, buffers: &[mev::Buffer]) {
+    for (idx, buffer) in buffers.iter().enumerate() {
+        let data = common::read_buffer(queue, buffer, PipelineStages::TRANSFER);
+        let expected = (idx * 4) as u8;
+        assert!(data.iter().all(|&b| b == expected), "buffer {idx}");
+    }
+}
+
+#[test]
+fn compact_fragmented() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut buffers = fragmented(&mut queue);
+
+    let plan = queue.plan_compaction(&buffers);
+    assert_eq!(plan.buffers().len(), buffers.len());
+    assert_eq!(plan.bytes(), SIZE * buffers.len() as u64);
+    assert!(plan.fragmented_bytes() > 0);
+
+    let ids = buffers.iter().map(|buffer| buffer.id()).collect::<Vec<_>>();
+
+    queue.execute_compaction(&plan, &mut buffers).unwrap();
+    queue.wait_idle().unwrap();
+
+    check_content(&mut queue, &buffers);
+
+    // Moved buffers keep identifiers of the buffers they replace.
+    assert!(buffers.iter().map(|buffer| buffer.id()).eq(ids));
+
+    // Buffers are packed densely and stay in place.
+    assert!(queue.plan_compaction(&buffers).is_empty());
+}
+
+#[test]
+fn compact_skips_cloned() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut buffers = fragmented(&mut queue);
+    let plan = queue.plan_compaction(&buffers);
+    assert!(plan.buffers().contains(&0));
+
+    // Buffer is not movable anymore.
+    let cloned = buffers[0].clone();
+
+    queue.execute_compaction(&plan, &mut buffers).unwrap();
+    queue.wait_idle().unwrap();
+
+    assert_eq!(buffers[0], cloned);
+    drop(cloned);
+
+    check_content(&mut queue, &buffers);
+}
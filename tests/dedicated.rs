@@ -0,0 +1,31 @@
+//! Resources that get memory objects dedicated to them.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+/// Drivers may prefer dedicated memory for any buffer,
+/// small ones are sub-allocated regardless.
+#[test]
+fn small_uniform_is_sub_allocated() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let before = device.allocator_stats().dedicated_objects;
+    let buffers = (0..16)
+        .map(|_| {
+            device
+                .new_buffer(mev::BufferDesc {
+                    size: 16 * 1024,
+                    usage: mev::BufferUsage::UNIFORM,
+                    memory: mev::Memory::Device,
+                    name: "uniform",
+                    shared_across_queues: false,
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(device.allocator_stats().dedicated_objects, before);
+    drop(buffers);
+}
@@ -0,0 +1,308 @@
+//! Debug output of descriptors and resource descriptions is stable
+//! and contains no backend handles.
+
+mod common;
+
+use mev::ShaderLanguage;
+
+const SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+@compute @workgroup_size(64)
+fn blur(@builtin(global_invocation_id) id: vec3<u32>) {
+    data[id.x] = data[id.x] + 1u;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+"#;
+
+const LAYOUT: [mev::ArgumentLayout; 1] = [mev::ArgumentLayout {
+    kind: mev::ArgumentKind::StorageBuffer,
+    size: 1,
+    stages: mev::ShaderStages::COMPUTE,
+    immutable_sampler: None,
+}];
+
+const GROUPS: [mev::ArgumentGroupLayout<'static>; 1] =
+    [mev::ArgumentGroupLayout { arguments: &LAYOUT }];
+
+#[test]
+fn render_pipeline_desc() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+    let mut desc = mev::RenderPipelineDesc::builder(mev::Shader {
+        library: library.clone(),
+        entry: "vs_main".into(),
+    })
+    .name("sprite")
+    .fragment(mev::Shader {
+        library,
+        entry: "fs_main".into(),
+    })
+    .color_target_blend(mev::PixelFormat::Rgba8Srgb, mev::BlendDesc::default())
+    .build();
+    desc.constants = 16;
+
+    assert_eq!(
+        format!("{desc:#?}"),
+        r#"RenderPipelineDesc {
+    name: "sprite",
+    vertex_shader: Shader {
+        entry: "vs_main",
+        ..
+    },
+    vertex_attributes: [],
+    vertex_layouts: [],
+    primitive_topology: Triangle,
+    raster: Some(
+        RasterDesc {
+            fragment_shader: Some(
+                Shader {
+                    entry: "fs_main",
+                    ..
+                },
+            ),
+            color_targets: [
+                ColorTargetDesc {
+                    format: Rgba8Srgb,
+                    blend: Some(
+                        BlendDesc {
+                            mask: WriteMask(
+                                RED | GREEN | BLUE | ALPHA,
+                            ),
+                            color: src * One + dst * OneMinusSrcAlpha,
+                            alpha: src * One + dst * OneMinusSrcAlpha,
+                        },
+                    ),
+                },
+            ],
+            depth_stencil: None,
+            front_face: CounterClockwise,
+            culling: Back,
+            line_width: 1.0,
+        },
+    ),
+    constants: 16,
+    arguments: [],
+}"#
+    );
+}
+
+#[test]
+fn compute_pipeline_desc() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+    let mut desc = mev::ComputePipelineDesc::builder(mev::Shader {
+        library,
+        entry: "blur".into(),
+    })
+    .name("blur")
+    .work_group_size([64, 1, 1])
+    .build();
+    desc.arguments = &GROUPS;
+
+    assert_eq!(
+        format!("{desc:#?}"),
+        r#"ComputePipelineDesc {
+    name: "blur",
+    shader: Shader {
+        entry: "blur",
+        ..
+    },
+    work_group_size: [
+        64,
+        1,
+        1,
+    ],
+    constants: 0,
+    arguments: [
+        ArgumentGroupLayout {
+            arguments: [
+                ArgumentLayout {
+                    kind: StorageBuffer,
+                    size: 1,
+                    stages: ShaderStages(
+                        COMPUTE,
+                    ),
+                    immutable_sampler: None,
+                },
+            ],
+        },
+    ],
+}"#
+    );
+}
+
+#[test]
+fn render_pass_desc() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let color = common::render_target(&device, 64, 32);
+    let depth = device
+        .new_image(mev::ImageDesc::new_d2(
+            64,
+            32,
+            mev::PixelFormat::D32Float,
+            mev::ImageUsage::TARGET,
+        ))
+        .unwrap();
+
+    let colors = [mev::AttachmentDesc::clear_color(
+        &color,
+        mev::ClearColor::BLACK,
+    )];
+    let desc = mev::RenderPassDesc::new()
+        .name("main")
+        .color_attachments(&colors)
+        .depth_stencil_attachment(mev::AttachmentDesc::clear_depth(&depth, 1.0));
+
+    assert_eq!(
+        format!("{desc:#?}"),
+        format!(
+            r#"RenderPassDesc {{
+    name: "main",
+    color_attachments: [
+        AttachmentDesc {{
+            image: Image {{ id: {}, format: Rgba8Unorm, extent: D2(Extent([64, 32])) }},
+            load: Clear(
+                ClearColor(
+                    0.0,
+                    0.0,
+                    0.0,
+                    1.0,
+                ),
+            ),
+            store: Store,
+        }},
+    ],
+    depth_stencil_attachment: Some(
+        AttachmentDesc {{
+            image: Image {{ id: {}, format: D32Float, extent: D2(Extent([64, 32])) }},
+            load: Clear(
+                ClearDepthStencil {{
+                    depth: 1.0,
+                    stencil: 0,
+                }},
+            ),
+            store: Store,
+        }},
+    ),
+    flags: RenderPassFlags(
+        0x0,
+    ),
+    expected_draws: 0,
+}}"#,
+            color.id(),
+            depth.id(),
+        )
+    );
+}
+
+#[test]
+fn describe_resources() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let image = device
+        .new_image(
+            mev::ImageDesc::new_d2(
+                256,
+                128,
+                mev::PixelFormat::Rgba8Srgb,
+                mev::ImageUsage::SAMPLED,
+            )
+            .with_name("albedo"),
+        )
+        .unwrap();
+
+    // Metal resources don't keep names.
+    let name = if cfg!(any(target_os = "macos", target_os = "ios")) {
+        ""
+    } else {
+        "\n    name: \"albedo\","
+    };
+
+    assert_eq!(
+        device.describe(&image),
+        format!(
+            r#"Image {{
+    id: {},{name}
+    extent: D2(
+        Extent(
+            [
+                256,
+                128,
+            ],
+        ),
+    ),
+    format: Rgba8Srgb,
+    usage: ImageUsage(
+        SAMPLED,
+    ),
+    levels: 1,
+    layers: 1,
+}}"#,
+            image.id(),
+        )
+    );
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+    let mut desc = mev::ComputePipelineDesc::builder(mev::Shader {
+        library,
+        entry: "blur".into(),
+    })
+    .name("blur")
+    .work_group_size([64, 1, 1])
+    .build();
+    desc.arguments = &GROUPS;
+    let pipeline = device.new_compute_pipeline(desc).unwrap();
+
+    assert_eq!(
+        device.describe(&pipeline),
+        format!(
+            r#"ComputePipeline {{
+    id: {},
+    name: "blur",
+    entry: "blur",
+    work_group_size: [
+        64,
+        1,
+        1,
+    ],
+    arguments: [
+        ArgumentGroupInfo {{
+            arguments: [
+                ArgumentLayout {{
+                    kind: StorageBuffer,
+                    size: 1,
+                    stages: ShaderStages(
+                        COMPUTE,
+                    ),
+                    immutable_sampler: None,
+                }},
+            ],
+        }},
+    ],
+    constants: 0,
+}}"#,
+            pipeline.id(),
+        )
+    );
+}
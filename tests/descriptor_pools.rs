@@ -0,0 +1,88 @@
+//! Descriptor pool chains used when push descriptors are unavailable.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+/// Number of argument bindings per frame.
+const BINDINGS: u64 = 10_000;
+
+const FILL: &str = r#"
+@group(0) @binding(0) var<storage, read_write> dst: array<u32>;
+
+@compute @workgroup_size(1)
+fn main() {
+    dst[0] = 1u;
+}
+"#;
+
+#[derive(mev::Arguments)]
+pub struct Fill {
+    #[mev(storage, compute)]
+    pub dst: mev::Buffer,
+}
+
+/// Records a frame that binds arguments `BINDINGS` times and waits for it.
+fn frame(queue: &mut mev::Queue, pipeline: &mev::ComputePipeline, dst: &mev::Buffer) {
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut compute = encoder.compute();
+        compute.with_pipeline(pipeline);
+        for _ in 0..BINDINGS {
+            compute.with_arguments(0, &Fill { dst: dst.clone() });
+            compute.dispatch(mev::Extent3::new(1, 1, 1));
+        }
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    queue.wait_idle().unwrap();
+}
+
+#[test]
+fn pool_chain_10k_bindings() {
+    let Some((device, mut queue)) = common::device_with_quirks(
+        mev::Features::empty(),
+        mev::Quirks::DISABLE_PUSH_DESCRIPTORS,
+    ) else {
+        return;
+    };
+
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, FILL);
+    let pipeline = device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library,
+                entry: "main".into(),
+            })
+            .name("fill")
+            .work_group_size([1, 1, 1])
+            .arguments::<Fill>()
+            .build(),
+        )
+        .unwrap();
+
+    let dst = device
+        .new_buffer(mev::BufferDesc {
+            size: 4,
+            usage: mev::BufferUsage::STORAGE,
+            memory: mev::Memory::Device,
+            name: "fill-dst",
+            shared_across_queues: false,
+        })
+        .unwrap();
+
+    frame(&mut queue, &pipeline, &dst);
+
+    let stats = queue.stats();
+    assert_eq!(stats.descriptor_sets_allocated, BINDINGS);
+    assert_eq!(stats.descriptor_sets_last_check_point, BINDINGS);
+
+    // Pools double from 64 sets, 8 pools hold 16320 sets.
+    assert_eq!(stats.descriptor_pools_created, 8);
+
+    // Chain is reset and reused by the next frame.
+    frame(&mut queue, &pipeline, &dst);
+
+    let stats = queue.stats();
+    assert_eq!(stats.descriptor_sets_allocated, 2 * BINDINGS);
+    assert_eq!(stats.descriptor_sets_last_check_point, BINDINGS);
+    assert_eq!(stats.descriptor_pools_created, 8);
+}
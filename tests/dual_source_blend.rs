@@ -0,0 +1,125 @@
+//! Subpixel text rendering with dual-source blending.
+//!
+//! Fragment shader outputs text color and per-channel coverage,
+//! which blends text over the background like LCD font renderers do.
+
+mod common;
+
+use mev::{PipelineStages, ShaderLanguage};
+
+const WIDTH: u32 = 16;
+const HEIGHT: u32 = 4;
+
+const TEXT: [f32; 3] = [1.0, 0.5, 0.25];
+const BACKGROUND: f32 = 0.25;
+
+const SHADER: &str = r#"
+struct Out {
+    @location(0) color: vec4<f32>,
+    @location(0) @second_blend_source coverage: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> Out {
+    let x = u32(position.x);
+    let y = u32(position.y);
+    let coverage = vec4<f32>(f32(x % 4u) / 4.0, f32(x / 4u) / 4.0, f32(y) / 4.0, 1.0);
+    return Out(vec4<f32>(1.0, 0.5, 0.25, 1.0), coverage);
+}
+"#;
+
+/// Coverage of each color channel written by the shader for the pixel.
+fn coverage(x: u32, y: u32) -> [f32; 3] {
+    [(x % 4) as f32 / 4.0, (x / 4) as f32 / 4.0, y as f32 / 4.0]
+}
+
+/// Blends text over background with per-channel coverage.
+fn expected(x: u32, y: u32) -> [u8; 4] {
+    let coverage = coverage(x, y);
+    let mut rgba = [255; 4];
+    for c in 0..3 {
+        let value = TEXT[c] * coverage[c] + BACKGROUND * (1.0 - coverage[c]);
+        rgba[c] = (value * 255.0).round() as u8;
+    }
+    rgba
+}
+
+#[test]
+fn subpixel_text() {
+    let Some((device, mut queue)) = common::device(mev::Features::DUAL_SOURCE_BLEND) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+
+    let blend = mev::BlendDesc {
+        mask: mev::WriteMask::all(),
+        color: mev::Blend {
+            op: mev::BlendOp::Add,
+            src: mev::BlendFactor::Src1Color,
+            dst: mev::BlendFactor::OneMinusSrc1Color,
+        },
+        alpha: mev::Blend {
+            op: mev::BlendOp::Add,
+            src: mev::BlendFactor::One,
+            dst: mev::BlendFactor::Zero,
+        },
+    };
+
+    let pipeline = device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            })
+            .name("subpixel-text")
+            .fragment(mev::Shader {
+                library,
+                entry: "fs_main".into(),
+            })
+            .color_target_blend(mev::PixelFormat::Rgba8Unorm, blend)
+            .build(),
+        )
+        .unwrap();
+
+    let target = common::render_target(&device, WIDTH, HEIGHT);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let mut render = encoder.render(
+            mev::RenderPassDesc::new()
+                .name("subpixel-text")
+                .color_attachments(&[mev::AttachmentDesc::new(&target)
+                    .clear(mev::ClearColor(BACKGROUND, BACKGROUND, BACKGROUND, 1.0))]),
+        );
+        render.with_pipeline(&pipeline);
+        render.draw(0..3, 0..1);
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let pixels = mev::testing::read_image_to_rgba8(&mut queue, &target).unwrap();
+
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let actual = common::pixel(&pixels, WIDTH, x, y);
+            let expected = expected(x, y);
+            for c in 0..4 {
+                assert!(
+                    actual[c].abs_diff(expected[c]) <= 1,
+                    "pixel ({x}, {y}) is {actual:?}, expected {expected:?}"
+                );
+            }
+        }
+    }
+}
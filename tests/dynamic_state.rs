@@ -0,0 +1,141 @@
+//! Dynamic render state persists across pipeline changes.
+
+mod common;
+
+use mev::{PipelineStages, ShaderLanguage};
+
+const SIZE: u32 = 16;
+
+/// Each vertex entry covers half of clip space with a triangle,
+/// each fragment entry writes its own color.
+const SHADER: &str = r#"
+fn corner(idx: u32) -> vec2<f32> {
+    return vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u) * 2.0);
+}
+
+@vertex
+fn vs_left(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(vec2<f32>(-1.0, -1.0) + corner(idx), 0.0, 1.0);
+}
+
+@vertex
+fn vs_right(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(vec2<f32>(0.0, -1.0) + corner(idx), 0.0, 1.0);
+}
+
+@fragment
+fn fs_red() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_green() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+}
+"#;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+fn pipeline(
+    device: &mev::Device,
+    library: &mev::Library,
+    vertex: &str,
+    fragment: &str,
+) -> mev::RenderPipeline {
+    device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: vertex.into(),
+            })
+            .name(vertex)
+            .fragment(mev::Shader {
+                library: library.clone(),
+                entry: fragment.into(),
+            })
+            .color_target(mev::PixelFormat::Rgba8Unorm)
+            .build(),
+        )
+        .unwrap()
+}
+
+/// Sets viewport to the left half of the target and scissor to the upper half.
+fn left_half(render: &mut mev::RenderCommandEncoder) {
+    render.with_viewport(
+        mev::Offset3::ZERO,
+        mev::Extent3::new(SIZE as f32 / 2.0, SIZE as f32, 1.0),
+    );
+    render.with_scissor(mev::Offset2::ZERO, mev::Extent2::new(SIZE, SIZE / 2));
+}
+
+/// Draws with each pipeline in order.
+///
+/// With `once` viewport and scissor are set after the first pipeline only,
+/// otherwise they are set again after each pipeline change.
+fn render(
+    device: &mev::Device,
+    queue: &mut mev::Queue,
+    pipelines: &[mev::RenderPipeline],
+    once: bool,
+) -> Vec<u8> {
+    let target = common::render_target(device, SIZE, SIZE);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let mut render = encoder.render(
+            mev::RenderPassDesc::new()
+                .name("dynamic-state")
+                .color_attachments(&[
+                    mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)
+                ]),
+        );
+
+        for (idx, pipeline) in pipelines.iter().enumerate() {
+            render.with_pipeline(pipeline);
+            if idx == 0 || !once {
+                left_half(&mut render);
+            }
+            render.draw(0..3, 0..1);
+        }
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    mev::testing::read_image_to_rgba8(queue, &target).unwrap()
+}
+
+/// Viewport and scissor set once apply to draws with both pipelines.
+#[test]
+fn viewport_across_pipelines() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+    let pipelines = [
+        pipeline(&device, &library, "vs_left", "fs_red"),
+        pipeline(&device, &library, "vs_right", "fs_green"),
+    ];
+
+    let once = render(&device, &mut queue, &pipelines, true);
+    let each = render(&device, &mut queue, &pipelines, false);
+
+    // Triangles land in quarters of the viewport, both within the scissor.
+    assert_eq!(common::pixel(&once, SIZE, 2, 4), RED);
+    assert_eq!(common::pixel(&once, SIZE, 6, 4), GREEN);
+
+    // Outside of the viewport.
+    assert_eq!(common::pixel(&once, SIZE, 12, 4), BLACK);
+
+    // Outside of the scissor.
+    assert_eq!(common::pixel(&once, SIZE, 2, 12), BLACK);
+    assert_eq!(common::pixel(&once, SIZE, 6, 12), BLACK);
+
+    assert_eq!(once, each);
+}
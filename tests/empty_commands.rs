@@ -0,0 +1,204 @@
+//! Zero-sized resources and commands over empty ranges
+//! behave the same on all backends.
+
+mod common;
+
+use mev::{AllocErrorKind, PipelineStages, ShaderLanguage};
+
+const SIZE: u64 = 64;
+
+fn storage_buffer(device: &mev::Device) -> mev::Buffer {
+    device
+        .new_buffer(mev::BufferDesc {
+            size: SIZE,
+            usage: mev::BufferUsage::STORAGE
+                | mev::BufferUsage::TRANSFER_SRC
+                | mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Device,
+            name: "empty-commands",
+            shared_across_queues: false,
+        })
+        .unwrap()
+}
+
+#[test]
+fn zero_sized_buffer() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let err = device
+        .new_buffer(mev::BufferDesc {
+            size: 0,
+            usage: mev::BufferUsage::UNIFORM,
+            memory: mev::Memory::Shared,
+            name: "zero",
+            shared_across_queues: false,
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), AllocErrorKind::ZeroSizedBuffer);
+
+    let err = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &[],
+            usage: mev::BufferUsage::UNIFORM,
+            memory: mev::Memory::Shared,
+            name: "zero-init",
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), AllocErrorKind::ZeroSizedBuffer);
+}
+
+/// Empty copies, fills and writes leave buffer content untouched.
+#[test]
+fn empty_transfers() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let buffer = storage_buffer(&device);
+    let other = storage_buffer(&device);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut copy = encoder.copy();
+        copy.fill_buffer(&buffer, 0xAA);
+        copy.fill_buffer(&other, 0x55);
+        copy.barrier(PipelineStages::TRANSFER, PipelineStages::TRANSFER);
+
+        copy.fill_buffer(buffer.slice(8..8), 0);
+        copy.write_buffer_raw(buffer.slice(16..16), &[]);
+        copy.copy_buffer(other.slice(0..0), buffer.slice(32..32));
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &buffer, PipelineStages::TRANSFER);
+    assert_eq!(data, [0xAA; SIZE as usize]);
+}
+
+const WRITE_ONES: &str = r#"
+@group(0) @binding(0) var<storage, read_write> result: array<u32>;
+
+@compute @workgroup_size(1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    result[id.x] = 1u;
+}
+"#;
+
+#[derive(mev::Arguments)]
+pub struct WriteOnes {
+    #[mev(storage, compute)]
+    pub result: mev::Buffer,
+}
+
+#[test]
+fn zero_groups_dispatch() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, WRITE_ONES);
+    let pipeline = device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library,
+                entry: "main".into(),
+            })
+            .name("write-ones")
+            .work_group_size([1, 1, 1])
+            .arguments::<WriteOnes>()
+            .build(),
+        )
+        .unwrap();
+
+    let buffer = storage_buffer(&device);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.copy().fill_buffer(&buffer, 0);
+    encoder.barrier(PipelineStages::TRANSFER, PipelineStages::COMPUTE_SHADER);
+    {
+        let mut compute = encoder.compute();
+        compute.with_pipeline(&pipeline);
+        compute.with_arguments(
+            0,
+            &WriteOnes {
+                result: buffer.clone(),
+            },
+        );
+        compute.dispatch(mev::Extent3::new(0, 1, 1));
+        compute.dispatch(mev::Extent3::new(4, 0, 1));
+        compute.dispatch(mev::Extent3::new(4, 1, 0));
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &buffer, PipelineStages::COMPUTE_SHADER);
+    assert_eq!(data, [0; SIZE as usize]);
+}
+
+const FULLSCREEN: &str = r#"
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return vec4f(1.0);
+}
+"#;
+
+#[test]
+fn empty_draws() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, FULLSCREEN);
+    let pipeline = device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            })
+            .name("fullscreen")
+            .fragment(mev::Shader {
+                library,
+                entry: "fs_main".into(),
+            })
+            .color_target(mev::PixelFormat::Rgba8Unorm)
+            .culling(mev::Culling::None)
+            .build(),
+        )
+        .unwrap();
+
+    let target = common::render_target(&device, 4, 4);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let mut render = encoder.render(mev::RenderPassDesc {
+            name: "empty-draws",
+            color_attachments: &[mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)],
+            depth_stencil_attachment: None,
+            flags: mev::RenderPassFlags::empty(),
+            expected_draws: 0,
+        });
+        render.with_pipeline(&pipeline);
+        render.bind_vertex_buffers(0, &[] as &[mev::BufferSlice]);
+        render.draw(0..0, 0..1);
+        render.draw(0..3, 1..1);
+        #[allow(clippy::reversed_empty_ranges)]
+        render.draw(3..0, 0..1);
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let pixels = mev::testing::read_image_to_rgba8(&mut queue, &target).unwrap();
+    for pixel in pixels.chunks_exact(4) {
+        assert_eq!(pixel, [0, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Queue capabilities reported by command encoders.
+
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use mev::{PipelineStages, QueueFlags};
+
+/// Encoders of transfer-only queue report no graphics or compute capability
+/// and refuse to start compute and render passes in debug builds.
+#[test]
+fn transfer_only_queue() {
+    let Some((device, mut queues)) = common::device_with_all_families(mev::Features::empty())
+    else {
+        return;
+    };
+
+    let Some(queue) = queues.iter_mut().find(|queue| {
+        !queue
+            .flags()
+            .intersects(QueueFlags::GRAPHICS | QueueFlags::COMPUTE)
+    }) else {
+        eprintln!("Skipping test, device has no transfer-only queue");
+        return;
+    };
+
+    let flags = queue.flags();
+    assert!(flags.contains(QueueFlags::TRANSFER));
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    assert_eq!(encoder.capabilities(), flags);
+
+    if cfg!(debug_assertions) {
+        let compute = catch_unwind(AssertUnwindSafe(|| {
+            encoder.compute();
+        }));
+        assert!(
+            compute.is_err(),
+            "compute pass on transfer-only queue must panic"
+        );
+
+        let target = common::render_target(&device, 4, 4);
+        let render = catch_unwind(AssertUnwindSafe(|| {
+            encoder.render(
+                mev::RenderPassDesc::new()
+                    .name("transfer-only")
+                    .color_attachments(&[mev::AttachmentDesc::new(&target)]),
+            );
+        }));
+        assert!(
+            render.is_err(),
+            "render pass on transfer-only queue must panic"
+        );
+    }
+}
+
+/// Encoders of graphics and compute queue report its capabilities.
+#[test]
+fn graphics_queue() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let flags = queue.flags();
+    assert!(flags.contains(QueueFlags::GRAPHICS | QueueFlags::COMPUTE));
+
+    let target = common::render_target(&device, 4, 4);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    assert_eq!(encoder.capabilities(), flags);
+    assert_eq!(encoder.compute().capabilities(), flags);
+
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let render = encoder.render(
+            mev::RenderPassDesc::new()
+                .name("capabilities")
+                .color_attachments(&[
+                    mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)
+                ]),
+        );
+        assert_eq!(render.capabilities(), flags);
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    queue.wait_idle().unwrap();
+}
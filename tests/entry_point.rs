@@ -0,0 +1,49 @@
+mod common;
+
+const SHADER: &str = r#"
+@compute @workgroup_size(1)
+fn blur() {}
+
+@compute @workgroup_size(1)
+fn sharpen() {}
+"#;
+
+#[test]
+fn typo_in_entry_point() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, SHADER);
+    assert_eq!(
+        library.entry_points(),
+        [
+            mev::EntryPoint {
+                name: "blur".into(),
+                stage: mev::ShaderStage::Compute,
+            },
+            mev::EntryPoint {
+                name: "sharpen".into(),
+                stage: mev::ShaderStage::Compute,
+            },
+        ]
+    );
+
+    let result = device.new_compute_pipeline(
+        mev::ComputePipelineDesc::builder(mev::Shader {
+            library,
+            entry: "bulr".into(),
+        })
+        .build(),
+    );
+
+    let Err(err) = result else {
+        panic!("Pipeline with unknown entry point must not be created");
+    };
+    assert!(
+        err.to_string().contains(
+            "compute entry point `bulr` is not found in the library, available: blur, sharpen"
+        ),
+        "{err}"
+    );
+}
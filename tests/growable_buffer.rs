@@ -0,0 +1,86 @@
+mod common;
+
+use mev::{BufferDesc, BufferUsage, GrowableBuffer, Memory, PipelineStages};
+
+/// Content written before each growth step survives all following steps.
+#[test]
+fn content_survives_growth() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut buffer = GrowableBuffer::new(
+        &device,
+        BufferDesc {
+            size: 16,
+            usage: BufferUsage::STORAGE,
+            memory: Memory::Device,
+            name: "growable",
+            shared_across_queues: false,
+        },
+    )
+    .unwrap();
+
+    let mut expected = Vec::<u32>::new();
+    let mut encoder = queue.new_command_encoder().unwrap();
+    let mut copy = encoder.copy();
+
+    // Capacity grows 16 -> 32 -> 64 -> 160 bytes.
+    for (step, size) in [16u64, 24, 64, 160].into_iter().enumerate() {
+        let reallocated = buffer.ensure_capacity(&mut copy, size).unwrap();
+        assert_eq!(reallocated, step > 0, "step {step}");
+        assert!(buffer.capacity() >= size);
+
+        // Copy must complete before the new range is written.
+        copy.barrier(PipelineStages::TRANSFER, PipelineStages::TRANSFER);
+
+        let start = expected.len();
+        expected.extend((start as u32..size as u32 / 4).map(|i| i * 3 + 1));
+        copy.write_buffer_slice(
+            buffer.buffer().slice(start as u64 * 4..size),
+            &expected[start..],
+        );
+
+        // Write must complete before the next copy reads it.
+        copy.barrier(PipelineStages::TRANSFER, PipelineStages::TRANSFER);
+    }
+
+    assert_eq!(buffer.capacity(), 160);
+    assert!(!buffer.ensure_capacity(&mut copy, 100).unwrap());
+
+    drop(copy);
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, buffer.buffer(), PipelineStages::TRANSFER);
+    let data: &[u32] = bytemuck::cast_slice(&data);
+    assert_eq!(&data[..expected.len()], &expected[..]);
+}
+
+/// Buffers can't be empty, growable buffer starts with small capacity instead.
+#[test]
+fn zero_initial_size() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut buffer = GrowableBuffer::new(
+        &device,
+        BufferDesc {
+            size: 0,
+            usage: BufferUsage::STORAGE,
+            memory: Memory::Device,
+            name: "empty-growable",
+            shared_across_queues: false,
+        },
+    )
+    .unwrap();
+    assert!(buffer.capacity() > 0);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    let mut copy = encoder.copy();
+    assert!(!buffer.ensure_capacity(&mut copy, 0).unwrap());
+    assert!(buffer.ensure_capacity(&mut copy, 4096).unwrap());
+    assert_eq!(buffer.capacity(), 4096);
+    drop(copy);
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+}
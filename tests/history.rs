@@ -0,0 +1,132 @@
+//! `HistoryImage` keeps the previous frame's content.
+
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use mev::PipelineStages;
+
+const SIZE: u32 = 4;
+const FRAMES: u8 = 4;
+
+fn image_desc() -> mev::ImageDesc<'static> {
+    mev::ImageDesc::new_d2(
+        SIZE,
+        SIZE,
+        mev::PixelFormat::Rgba8Unorm,
+        mev::ImageUsage::TRANSFER_SRC | mev::ImageUsage::TRANSFER_DST,
+    )
+    .with_name("history")
+}
+
+/// Pixels of the frame, distinct for each frame.
+fn frame_pixels(frame: u8) -> Vec<u8> {
+    (0..SIZE * SIZE * 4)
+        .map(|i| (i as u8).wrapping_mul(7).wrapping_add(frame * 50))
+        .collect()
+}
+
+/// Records upload of frame's pixels into `current`.
+fn write_frame(
+    device: &mev::Device,
+    copy: &mut mev::CopyCommandEncoder,
+    current: &mev::Image,
+    frame: u8,
+) {
+    let upload = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &frame_pixels(frame),
+            usage: mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Upload,
+            name: "history-upload",
+        })
+        .unwrap();
+
+    if frame == 0 {
+        copy.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, current);
+    } else {
+        copy.sync_image(
+            PipelineStages::ALL_NON_RT,
+            PipelineStages::TRANSFER,
+            current,
+        );
+    }
+    copy.copy_buffer_to_image(
+        &upload,
+        0,
+        0,
+        0,
+        current,
+        mev::Offset3::ZERO,
+        current.extent().into_3d(),
+        0..1,
+        0,
+    );
+}
+
+/// Before resolve previous image holds the last frame,
+/// after resolve it holds the current one
+/// and the other image still holds the last frame.
+#[test]
+fn previous_is_one_frame_behind() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let current = device.new_image(image_desc()).unwrap();
+    let mut history = mev::HistoryImage::new(&device, image_desc()).unwrap();
+
+    for frame in 0..FRAMES {
+        assert_eq!(history.has_previous(), frame > 0);
+        if frame > 0 {
+            let previous = mev::testing::read_image_to_rgba8(&mut queue, history.previous());
+            assert_eq!(previous.unwrap(), frame_pixels(frame - 1), "frame {frame}");
+        }
+
+        let mut encoder = queue.new_command_encoder().unwrap();
+        {
+            let mut copy = encoder.copy();
+            write_frame(&device, &mut copy, &current, frame);
+            history.resolve(&queue, &mut copy, &current);
+        }
+        queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+        let previous = mev::testing::read_image_to_rgba8(&mut queue, history.previous());
+        assert_eq!(previous.unwrap(), frame_pixels(frame), "frame {frame}");
+
+        // First resolve fills both images.
+        let other = history
+            .images()
+            .iter()
+            .find(|image| *image != history.previous())
+            .unwrap();
+        let other = mev::testing::read_image_to_rgba8(&mut queue, other);
+        assert_eq!(
+            other.unwrap(),
+            frame_pixels(frame.saturating_sub(1)),
+            "frame {frame}"
+        );
+    }
+}
+
+/// Resolving twice without checkpoint in between would overwrite
+/// the image read by the current frame.
+#[test]
+fn resolve_twice_per_checkpoint() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let current = device.new_image(image_desc()).unwrap();
+    let mut history = mev::HistoryImage::new(&device, image_desc()).unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    let mut copy = encoder.copy();
+    write_frame(&device, &mut copy, &current, 0);
+    history.resolve(&queue, &mut copy, &current);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        history.resolve(&queue, &mut copy, &current);
+    }));
+    assert!(result.is_err(), "second resolve must panic");
+}
@@ -0,0 +1,32 @@
+mod common;
+
+/// Images larger than any device supports fail with reported limit.
+#[test]
+fn too_large() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let width = 1 << 30;
+    let err = match device.new_image(mev::ImageDesc {
+        name: "huge",
+        ..mev::ImageDesc::new_d2_texture(width, 4, mev::PixelFormat::Rgba8Unorm)
+    }) {
+        Ok(_) => panic!("image of width {width} is created"),
+        Err(err) => err,
+    };
+
+    let mev::AllocErrorKind::ExceedsLimits { dimension, max } = err.kind() else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(dimension, width);
+    assert!(max < width);
+    assert_eq!(err.name(), "huge");
+
+    let message = err.to_string();
+    assert!(
+        message.contains(&format!("dimension {width} exceeds device limit {max}")),
+        "{message}"
+    );
+    assert!(message.contains("'huge'"), "{message}");
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use mev::{PipelineStages, ShaderLanguage};
+
+const SIZE: u32 = 8;
+
+/// Samples 2x2 texture across the target with texture coordinates
+/// in `-0.5..1.5` range, so addressing and filtering are both visible.
+const SHADER: &str = r#"
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let xy = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(xy * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = xy * 2.0 - 0.5;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(texture, texture_sampler, in.uv);
+}
+"#;
+
+#[derive(mev::Arguments)]
+pub struct Bound {
+    #[mev(sampled, fragment)]
+    pub texture: mev::Image,
+    #[mev(fragment, sampler = "linear_repeat")]
+    pub sampler: (),
+}
+
+#[derive(mev::Arguments)]
+pub struct Immutable {
+    #[mev(sampled, fragment)]
+    pub texture: mev::Image,
+    #[mev(fragment, immutable_sampler = "linear_repeat")]
+    pub sampler: (),
+}
+
+/// Immutable sampler is not part of descriptor updates.
+#[test]
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+fn update_size() {
+    use mev::for_macro::Arguments;
+
+    assert_eq!(<Bound as Arguments>::template_entries().len(), 2);
+    assert_eq!(<Immutable as Arguments>::template_entries().len(), 1);
+    assert!(
+        size_of::<<Immutable as Arguments>::Update>() < size_of::<<Bound as Arguments>::Update>()
+    );
+}
+
+fn render<A: mev::Arguments>(
+    device: &mev::Device,
+    queue: &mut mev::Queue,
+    arguments: &A,
+) -> Vec<u8> {
+    let library = common::library(device, ShaderLanguage::Wgsl, SHADER);
+    let pipeline = device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            })
+            .name("immutable-sampler")
+            .fragment(mev::Shader {
+                library,
+                entry: "fs_main".into(),
+            })
+            .color_target(mev::PixelFormat::Rgba8Unorm)
+            .culling(mev::Culling::None)
+            .arguments::<A>()
+            .build(),
+        )
+        .unwrap();
+
+    let target = common::render_target(device, SIZE, SIZE);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let mut render = encoder.render(mev::RenderPassDesc {
+            name: "immutable-sampler",
+            color_attachments: &[mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)],
+            depth_stencil_attachment: None,
+            flags: mev::RenderPassFlags::empty(),
+            expected_draws: 1,
+        });
+        render.with_pipeline(&pipeline);
+        render.with_arguments(0, arguments);
+        render.draw(0..3, 0..1);
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    mev::testing::read_image_to_rgba8(queue, &target).unwrap()
+}
+
+/// Rendering with immutable sampler is identical to bound sampler.
+#[test]
+fn same_rendering() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let texture = mev::upload::new_image_with(
+        &mut queue,
+        mev::ImageDesc::new_d2(2, 2, mev::PixelFormat::Rgba8Unorm, mev::ImageUsage::SAMPLED),
+        |texel| match (texel.x, texel.y) {
+            (0, 0) => [255u8, 0, 0, 255],
+            (1, 0) => [0, 255, 0, 255],
+            (0, 1) => [0, 0, 255, 255],
+            _ => [255, 255, 255, 255],
+        },
+    )
+    .unwrap();
+
+    let bound = render(
+        &device,
+        &mut queue,
+        &Bound {
+            texture: texture.clone(),
+            sampler: (),
+        },
+    );
+    let immutable = render(
+        &device,
+        &mut queue,
+        &Immutable {
+            texture,
+            sampler: (),
+        },
+    );
+
+    mev::assert_images_match!(immutable, bound, mev::Extent2::new(SIZE, SIZE), 0);
+}
@@ -0,0 +1,116 @@
+//! Render passes over attachments with multiple layers.
+
+mod common;
+
+use mev::{AttachmentIndex, ImageDesc, ImageUsage, PipelineStages, PixelFormat, RenderPassError};
+
+const SIZE: u32 = 4;
+const LAYERS: u32 = 3;
+
+fn target(device: &mev::Device, layers: u32, format: PixelFormat) -> mev::Image {
+    device
+        .new_image(ImageDesc {
+            layers,
+            ..ImageDesc::new_d2(
+                SIZE,
+                SIZE,
+                format,
+                ImageUsage::TARGET | ImageUsage::TRANSFER_SRC,
+            )
+        })
+        .unwrap()
+}
+
+#[test]
+fn layer_count_mismatch() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let color = target(&device, LAYERS, PixelFormat::Rgba8Unorm);
+    let depth = target(&device, 1, PixelFormat::D32Float);
+
+    let attachments = [mev::AttachmentDesc::new(&color)];
+    let desc = mev::RenderPassDesc::new()
+        .name("layers")
+        .color_attachments(&attachments)
+        .depth_stencil_attachment(mev::AttachmentDesc::new(&depth));
+
+    assert_eq!(
+        desc.validate(),
+        Err(RenderPassError::LayerCountMismatch {
+            attachment: AttachmentIndex::DepthStencil,
+            layers: 1,
+            first: AttachmentIndex::Color(0),
+            expected: LAYERS,
+        })
+    );
+
+    let layer = color
+        .view(
+            &device,
+            mev::ViewDesc::new(PixelFormat::Rgba8Unorm).layers(0..1),
+        )
+        .unwrap();
+    let attachments = [mev::AttachmentDesc::new(&layer)];
+    assert_eq!(desc.color_attachments(&attachments).validate(), Ok(()));
+}
+
+/// Clear applies to every layer of the attachment.
+#[test]
+fn clear_all_layers() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let color = target(&device, LAYERS, PixelFormat::Rgba8Unorm);
+    let layer_bytes = u64::from(SIZE * SIZE * 4);
+
+    let result = device
+        .new_buffer(mev::BufferDesc {
+            size: layer_bytes * u64::from(LAYERS),
+            usage: mev::BufferUsage::TRANSFER_DST | mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Device,
+            name: "layers-readback",
+            shared_across_queues: false,
+        })
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &color,
+    );
+    {
+        let _render = encoder.render(mev::RenderPassDesc {
+            name: "clear-layers",
+            color_attachments: &[
+                mev::AttachmentDesc::new(&color).clear(mev::ClearColor(1.0, 0.0, 0.0, 1.0))
+            ],
+            depth_stencil_attachment: None,
+            flags: mev::RenderPassFlags::empty(),
+            expected_draws: 0,
+        });
+    }
+    encoder.barrier(PipelineStages::COLOR_OUTPUT, PipelineStages::TRANSFER);
+    encoder.copy().copy_image_to_buffer(
+        &color,
+        mev::Offset3::ZERO,
+        mev::Extent3::new(SIZE, SIZE, 1),
+        0..LAYERS,
+        0,
+        &result,
+        0,
+        0,
+        0,
+    );
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &result, PipelineStages::TRANSFER);
+    for (layer, pixels) in data.chunks_exact(layer_bytes as usize).enumerate() {
+        for pixel in pixels.chunks_exact(4) {
+            assert_eq!(pixel, [255, 0, 0, 255], "layer {layer}");
+        }
+    }
+}
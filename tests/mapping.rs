@@ -0,0 +1,93 @@
+//! Scoped mapping of host-visible buffers.
+
+mod common;
+
+use mev::{MapError, PipelineStages};
+
+const SIZE: u64 = 256;
+
+fn new_buffer(device: &mev::Device, memory: mev::Memory) -> mev::Buffer {
+    device
+        .new_buffer(mev::BufferDesc {
+            size: SIZE,
+            usage: mev::BufferUsage::TRANSFER_SRC,
+            memory,
+            name: "mapped",
+            shared_across_queues: false,
+        })
+        .unwrap()
+}
+
+#[test]
+fn map_errors() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let mut device_local = new_buffer(&device, mev::Memory::Device);
+    assert_eq!(
+        device_local.mapped_mut(..).err(),
+        Some(MapError::NotHostVisible)
+    );
+
+    let mut shared = new_buffer(&device, mev::Memory::Shared);
+    let clone = shared.clone();
+    assert_eq!(shared.mapped_mut(..).err(), Some(MapError::Shared));
+
+    drop(clone);
+    assert!(shared.mapped_mut(..).is_ok());
+}
+
+/// Bytes written through the guard are seen by commands submitted after it is dropped.
+#[test]
+fn writes_are_visible() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    for memory in [mev::Memory::Shared, mev::Memory::Upload] {
+        let mut buffer = new_buffer(&device, memory);
+
+        buffer.mapped_mut(..).unwrap().fill(0xAA);
+        {
+            let mut mapped = buffer.mapped_mut(16..32).unwrap();
+            assert_eq!(mapped.len(), 16);
+            mapped.copy_from_slice(&[0x55; 16]);
+        }
+
+        let data = common::read_buffer(&mut queue, &buffer, PipelineStages::empty());
+        for (idx, byte) in data.into_iter().enumerate() {
+            let expected = if (16..32).contains(&idx) { 0x55 } else { 0xAA };
+            assert_eq!(byte, expected, "{memory:?} byte {idx}");
+        }
+    }
+}
+
+/// Buffers sub-allocated from the same chunk are mapped at the same time.
+#[test]
+fn linear_buffers_mapped_together() {
+    let Some((device, mut queue)) = common::device_with_allocator(
+        mev::Features::empty(),
+        mev::AllocatorConfig {
+            linear_threshold: 4096,
+            ..mev::AllocatorConfig::default()
+        },
+    ) else {
+        return;
+    };
+
+    let mut first = new_buffer(&device, mev::Memory::Upload);
+    let mut second = new_buffer(&device, mev::Memory::Upload);
+
+    {
+        let mut a = first.mapped_mut(..).unwrap();
+        let mut b = second.mapped_mut(..).unwrap();
+        a.fill(1);
+        b.fill(2);
+    }
+
+    let first = common::read_buffer(&mut queue, &first, PipelineStages::empty());
+    let second = common::read_buffer(&mut queue, &second, PipelineStages::empty());
+    assert_eq!(first, [1; SIZE as usize]);
+    assert_eq!(second, [2; SIZE as usize]);
+}
@@ -0,0 +1,102 @@
+//! Surface creation from raw window and display handles
+//! that are rejected before reaching the driver.
+//!
+//! Handles are never dereferenced, so no window system is needed.
+#![cfg(all(
+    unix,
+    not(any(target_os = "android", target_os = "macos", target_os = "ios"))
+))]
+
+mod common;
+
+use std::{
+    num::{NonZeroIsize, NonZeroU32},
+    ptr::NonNull,
+};
+
+use raw_window_handle::{
+    AppKitDisplayHandle, AppKitWindowHandle, DisplayHandle, HandleError, HasDisplayHandle,
+    HasWindowHandle, RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, Win32WindowHandle,
+    WindowHandle, WindowsDisplayHandle, XcbDisplayHandle, XcbWindowHandle, XlibDisplayHandle,
+    XlibWindowHandle,
+};
+
+struct Handles {
+    window: RawWindowHandle,
+    display: RawDisplayHandle,
+}
+
+impl HasWindowHandle for Handles {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        // Safety: handle is never used to access the window.
+        Ok(unsafe { WindowHandle::borrow_raw(self.window) })
+    }
+}
+
+impl HasDisplayHandle for Handles {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        // Safety: handle is never used to access the display.
+        Ok(unsafe { DisplayHandle::borrow_raw(self.display) })
+    }
+}
+
+fn dangling() -> NonNull<std::ffi::c_void> {
+    NonNull::dangling()
+}
+
+fn xlib_window() -> RawWindowHandle {
+    XlibWindowHandle::new(1).into()
+}
+
+fn xcb_window() -> RawWindowHandle {
+    XcbWindowHandle::new(NonZeroU32::new(1).unwrap()).into()
+}
+
+#[test]
+fn unsupported_handles() {
+    let Some((device, _queue)) = common::device(mev::Features::SURFACE) else {
+        return;
+    };
+
+    let cases = [
+        (
+            "xlib window with wayland display",
+            xlib_window(),
+            WaylandDisplayHandle::new(dangling()).into(),
+        ),
+        (
+            "xcb window with xlib display",
+            xcb_window(),
+            XlibDisplayHandle::new(Some(dangling()), 0).into(),
+        ),
+        (
+            "xlib without display",
+            xlib_window(),
+            XlibDisplayHandle::new(None, 0).into(),
+        ),
+        (
+            "xcb without connection",
+            xcb_window(),
+            XcbDisplayHandle::new(None, 0).into(),
+        ),
+        (
+            "win32",
+            Win32WindowHandle::new(NonZeroIsize::new(1).unwrap()).into(),
+            WindowsDisplayHandle::new().into(),
+        ),
+        (
+            "appkit",
+            AppKitWindowHandle::new(dangling()).into(),
+            AppKitDisplayHandle::new().into(),
+        ),
+    ];
+
+    for (name, window, display) in cases {
+        let handles = Handles { window, display };
+        match device.new_surface(&handles, &handles) {
+            Err(mev::SurfaceError::Unsupported) => {}
+            Err(err) => panic!("{name}: unexpected error: {err}"),
+            Ok(_) => panic!("{name}: surface is created"),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Per-frame copies selected by queue checkpoints.
+
+use mev::{PerFrame, FRAMES_IN_FLIGHT};
+
+mod common;
+
+#[test]
+fn frame_index_follows_check_points() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let first = PerFrame::<()>::frame_index(&queue);
+
+    // Submissions without checkpoints stay in the same frame.
+    let encoder = queue.new_command_encoder().unwrap();
+    queue.submit_one(encoder.finish().unwrap(), false).unwrap();
+    assert_eq!(PerFrame::<()>::frame_index(&queue), first);
+
+    for frame in 1..=2 * FRAMES_IN_FLIGHT {
+        let encoder = queue.new_command_encoder().unwrap();
+        queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+        assert_eq!(
+            PerFrame::<()>::frame_index(&queue),
+            (first + frame) % FRAMES_IN_FLIGHT
+        );
+    }
+
+    queue.wait_idle().unwrap();
+}
+
+/// Copy of the current frame is not used by pending commands
+/// and can be mapped and written every frame without waiting.
+#[test]
+fn copies_are_writable_every_frame() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    const FRAMES: u64 = 4 * FRAMES_IN_FLIGHT as u64;
+
+    let mut uniforms = PerFrame::try_new(|idx| {
+        device.new_buffer(mev::BufferDesc {
+            size: 4,
+            usage: mev::BufferUsage::UNIFORM | mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Shared,
+            name: &format!("per-frame-{idx}"),
+            shared_across_queues: false,
+        })
+    })
+    .unwrap();
+
+    let results = device
+        .new_buffer(mev::BufferDesc {
+            size: FRAMES * 4,
+            usage: mev::BufferUsage::TRANSFER_DST | mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Device,
+            name: "per-frame-results",
+            shared_across_queues: false,
+        })
+        .unwrap();
+
+    for frame in 0..FRAMES {
+        uniforms
+            .get_mut(&queue)
+            .mapped_mut(..)
+            .unwrap()
+            .copy_from_slice(bytemuck::bytes_of(&(frame as u32)));
+
+        let mut encoder = queue.new_command_encoder().unwrap();
+        encoder.copy().copy_buffer(
+            uniforms.get(&queue),
+            results.slice(frame * 4..frame * 4 + 4),
+        );
+        queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    }
+
+    let data = common::read_buffer(&mut queue, &results, mev::PipelineStages::TRANSFER);
+    let values: &[u32] = bytemuck::cast_slice(&data);
+    assert_eq!(values, (0..FRAMES as u32).collect::<Vec<_>>());
+}
+
+/// Checkpoint without any command buffer submitted before it
+/// still advances the frame, same on all backends.
+#[test]
+fn empty_check_point_advances_frame() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    assert_eq!(queue.check_point_index(), 0);
+
+    queue.submit(std::iter::empty(), true).unwrap();
+    assert_eq!(queue.check_point_index(), 1);
+
+    let encoder = queue.new_command_encoder().unwrap();
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    assert_eq!(queue.check_point_index(), 2);
+
+    queue.wait_idle().unwrap();
+}
@@ -0,0 +1,164 @@
+//! Pipelines report argument groups and constants they were created with.
+
+mod common;
+
+use mev::{ArgumentGroupInfo, ArgumentGroupLayout, Arguments, DeviceRepr, ShaderLanguage};
+
+const COMPUTE: &str = r#"
+struct Constants {
+    scale: u32,
+}
+
+var<push_constant> pc: Constants;
+
+@group(0) @binding(0) var<storage, read_write> data: array<u32>;
+@group(1) @binding(0) var<uniform> params: vec4<u32>;
+@group(1) @binding(1) var image: texture_2d<f32>;
+
+@compute @workgroup_size(1)
+fn main() {
+    let texel = textureLoad(image, vec2u(0u, 0u), 0);
+    data[0] = params.x * pc.scale + u32(texel.x);
+}
+"#;
+
+const RENDER: &str = r#"
+@group(0) @binding(0) var<uniform> color: vec4f;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return color;
+}
+"#;
+
+#[derive(mev::Arguments)]
+pub struct Data {
+    #[mev(storage, compute)]
+    pub data: mev::Buffer,
+}
+
+#[derive(mev::Arguments)]
+pub struct Params {
+    #[mev(uniform, compute)]
+    pub params: mev::Buffer,
+    #[mev(sampled, compute)]
+    pub texture: mev::Image,
+}
+
+#[derive(mev::Arguments)]
+pub struct Color {
+    #[mev(uniform, fragment)]
+    pub color: mev::Buffer,
+}
+
+#[derive(mev::DeviceRepr)]
+pub struct Constants {
+    pub scale: u32,
+}
+
+fn compute_pipeline(
+    device: &mev::Device,
+    library: &mev::Library,
+    layouts: &[ArgumentGroupLayout],
+) -> mev::ComputePipeline {
+    device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "main".into(),
+            })
+            .name("layout")
+            .constants::<Constants>()
+            .argument_layouts(layouts)
+            .build(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn compute_groups_match_desc() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, COMPUTE);
+    let layouts = [Data::LAYOUT, Params::LAYOUT];
+    let pipeline = compute_pipeline(&device, &library, &layouts);
+
+    assert_eq!(pipeline.constants_size(), Constants::SIZE);
+    assert_eq!(pipeline.argument_groups().len(), layouts.len());
+    for (info, layout) in pipeline.argument_groups().iter().zip(layouts) {
+        assert_eq!(info.layout(), layout);
+        assert_eq!(*info, ArgumentGroupInfo::from(layout));
+    }
+
+    // Reported layouts create pipeline with the same groups.
+    let reported = pipeline
+        .argument_groups()
+        .iter()
+        .map(ArgumentGroupInfo::layout)
+        .collect::<Vec<_>>();
+    let other = compute_pipeline(&device, &library, &reported);
+    assert_eq!(other.argument_groups(), pipeline.argument_groups());
+    assert_eq!(other.constants_size(), pipeline.constants_size());
+}
+
+#[test]
+fn render_groups_match_desc() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, RENDER);
+    let pipeline = device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            })
+            .name("layout")
+            .fragment(mev::Shader {
+                library,
+                entry: "fs_main".into(),
+            })
+            .color_target(mev::PixelFormat::Rgba8Unorm)
+            .arguments::<Color>()
+            .build(),
+        )
+        .unwrap();
+
+    assert_eq!(pipeline.constants_size(), 0);
+    assert_eq!(pipeline.argument_groups().len(), 1);
+    assert_eq!(pipeline.argument_groups()[0].layout(), Color::LAYOUT);
+}
+
+#[test]
+fn no_groups() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(
+        &device,
+        ShaderLanguage::Wgsl,
+        "@compute @workgroup_size(1) fn main() {}",
+    );
+    let pipeline = device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library,
+                entry: "main".into(),
+            })
+            .build(),
+        )
+        .unwrap();
+
+    assert!(pipeline.argument_groups().is_empty());
+    assert_eq!(pipeline.constants_size(), 0);
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use mev::{PipelineStages, PrimitiveTopology, ShaderLanguage, ShaderStage};
+
+const WIDTH: u32 = 16;
+const HEIGHT: u32 = 8;
+
+/// Places 4 pixel points at (4, 4) and (12, 4) in window coordinates,
+/// covering pixels `2..6` and `10..14` horizontally and `2..6` vertically.
+const POINTS_VERTEX: &str = r#"
+#version 450
+
+void main() {
+    float x = gl_VertexIndex == 0 ? -0.5 : 0.5;
+    gl_Position = vec4(x, 0.0, 0.0, 1.0);
+    gl_PointSize = 4.0;
+}
+"#;
+
+/// Horizontal line through centers of pixels in row 4.
+const LINE_VERTEX: &str = r#"
+#version 450
+
+void main() {
+    float x = gl_VertexIndex == 0 ? -1.0 : 1.0;
+    gl_Position = vec4(x, 0.125, 0.0, 1.0);
+}
+"#;
+
+const WHITE_FRAGMENT: &str = r#"
+#version 450
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0);
+}
+"#;
+
+const WHITE: [u8; 4] = [255; 4];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+/// Renders two vertices with given topology and reads the target back.
+fn render(
+    device: &mev::Device,
+    queue: &mut mev::Queue,
+    topology: PrimitiveTopology,
+    vertex: &str,
+) -> Vec<u8> {
+    let vertex = common::library(
+        device,
+        ShaderLanguage::Glsl {
+            stage: ShaderStage::Vertex,
+        },
+        vertex,
+    );
+    let fragment = common::library(
+        device,
+        ShaderLanguage::Glsl {
+            stage: ShaderStage::Fragment,
+        },
+        WHITE_FRAGMENT,
+    );
+
+    let pipeline = device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: vertex,
+                entry: "main".into(),
+            })
+            .name("primitives")
+            .topology(topology)
+            .fragment(mev::Shader {
+                library: fragment,
+                entry: "main".into(),
+            })
+            .color_target(mev::PixelFormat::Rgba8Unorm)
+            .build(),
+        )
+        .unwrap();
+
+    let target = common::render_target(device, WIDTH, HEIGHT);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let mut render = encoder.render(mev::RenderPassDesc {
+            name: "primitives",
+            color_attachments: &[mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)],
+            depth_stencil_attachment: None,
+            flags: mev::RenderPassFlags::empty(),
+            expected_draws: 1,
+        });
+        render.with_pipeline(&pipeline);
+        render.draw(0..2, 0..1);
+    }
+    let cbuf = encoder.finish().unwrap();
+    queue.submit_one(cbuf, true).unwrap();
+
+    mev::testing::read_image_to_rgba8(queue, &target).unwrap()
+}
+
+#[test]
+fn point_size() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let pixels = render(&device, &mut queue, PrimitiveTopology::Point, POINTS_VERTEX);
+
+    for (cx, cy) in [(4, 4), (12, 4)] {
+        for y in cy - 2..cy + 2 {
+            for x in cx - 2..cx + 2 {
+                assert_eq!(common::pixel(&pixels, WIDTH, x, y), WHITE, "({x}, {y})");
+            }
+        }
+        assert_eq!(common::pixel(&pixels, WIDTH, cx - 3, cy), BLACK);
+        assert_eq!(common::pixel(&pixels, WIDTH, cx + 2, cy), BLACK);
+        assert_eq!(common::pixel(&pixels, WIDTH, cx, cy - 3), BLACK);
+        assert_eq!(common::pixel(&pixels, WIDTH, cx, cy + 2), BLACK);
+    }
+
+    assert_eq!(common::pixel(&pixels, WIDTH, 8, 4), BLACK);
+}
+
+#[test]
+fn line() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let pixels = render(&device, &mut queue, PrimitiveTopology::Line, LINE_VERTEX);
+
+    for x in 0..WIDTH {
+        for y in 0..HEIGHT {
+            let expected = if y == 4 { WHITE } else { BLACK };
+            assert_eq!(common::pixel(&pixels, WIDTH, x, y), expected, "({x}, {y})");
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Release of resources used by submitted commands.
+
+mod common;
+
+fn new_buffer(device: &mev::Device, name: &str) -> mev::Buffer {
+    device
+        .new_buffer(mev::BufferDesc {
+            size: 256,
+            usage: mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Device,
+            name,
+            shared_across_queues: false,
+        })
+        .unwrap()
+}
+
+fn fill(queue: &mut mev::Queue, buffer: &mev::Buffer, check_point: bool) {
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.copy().fill_buffer(buffer, 0xff);
+    queue
+        .submit_one(encoder.finish().unwrap(), check_point)
+        .unwrap();
+}
+
+/// Resources used by commands submitted with and without checkpoints
+/// are released by `wait_idle`.
+#[test]
+fn wait_idle_releases_resources() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let buffers = (0..8)
+        .map(|idx| new_buffer(&device, &format!("idle-{idx}")))
+        .collect::<Vec<_>>();
+
+    for (idx, buffer) in buffers.iter().enumerate() {
+        fill(&mut queue, buffer, idx % 3 == 0);
+    }
+    fill(&mut queue, &buffers[0], false);
+
+    queue.wait_idle().unwrap();
+
+    assert_eq!(queue.pending_resource_estimate(), 0);
+    for buffer in &buffers {
+        assert!(
+            buffer.detached(),
+            "buffer is kept by the queue after wait_idle"
+        );
+    }
+
+    // Queue stays usable after its epochs are reset.
+    fill(&mut queue, &buffers[1], true);
+    queue.wait_idle().unwrap();
+    assert!(buffers[1].detached());
+}
+
+/// `reclaim` releases resources of completed checkpoints,
+/// but not of commands submitted after the last checkpoint.
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+#[test]
+fn reclaim_stops_at_open_epoch() {
+    use std::time::{Duration, Instant};
+
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let closed = new_buffer(&device, "closed-epoch");
+    let open = new_buffer(&device, "open-epoch");
+
+    fill(&mut queue, &closed, true);
+    fill(&mut queue, &open, false);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !closed.detached() {
+        assert!(Instant::now() < deadline, "checkpoint did not complete");
+        queue.reclaim().unwrap();
+        std::thread::yield_now();
+    }
+
+    assert!(
+        !open.detached(),
+        "commands after the last checkpoint are released"
+    );
+
+    queue.wait_idle().unwrap();
+    assert!(open.detached());
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use mev::PipelineStages;
+
+const LEN: usize = 64;
+
+const DOUBLE: &str = r#"
+@group(0) @binding(0) var<storage, read_write> src: array<u32>;
+@group(0) @binding(1) var<storage, read_write> dst: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    dst[id.x] = src[id.x] * 2u;
+}
+"#;
+
+#[derive(mev::Arguments)]
+struct Double {
+    #[mev(storage, compute)]
+    src: mev::Buffer,
+    #[mev(storage, compute)]
+    dst: mev::Buffer,
+}
+
+/// Runs compute dispatch that binds arguments and checks its output.
+fn double(device: &mev::Device, queue: &mut mev::Queue) {
+    let library = common::library(device, mev::ShaderLanguage::Wgsl, DOUBLE);
+    let pipeline = device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library,
+                entry: "main".into(),
+            })
+            .name("double")
+            .work_group_size([LEN as u32, 1, 1])
+            .arguments::<Double>()
+            .build(),
+        )
+        .unwrap();
+
+    let input = (0..LEN as u32).collect::<Vec<_>>();
+    let usage = mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_SRC;
+
+    let src = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: bytemuck::cast_slice(&input),
+            usage,
+            memory: mev::Memory::Device,
+            name: "double-src",
+        })
+        .unwrap();
+    let dst = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: bytemuck::cast_slice(&[0u32; LEN]),
+            usage,
+            memory: mev::Memory::Device,
+            name: "double-dst",
+        })
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut compute = encoder.compute();
+        compute.with_pipeline(&pipeline);
+        compute.with_arguments(
+            0,
+            &Double {
+                src: src.clone(),
+                dst: dst.clone(),
+            },
+        );
+        compute.dispatch(mev::Extent3::new(1, 1, 1));
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(queue, &dst, PipelineStages::COMPUTE_SHADER);
+    let result: &[u32] = bytemuck::cast_slice(&data);
+
+    let expected = input.iter().map(|x| x * 2).collect::<Vec<_>>();
+    assert_eq!(result, expected);
+}
+
+/// Forced quirks are reported by the device.
+#[test]
+fn forced_quirks_reported() {
+    let forced = mev::Quirks::CLAMP_ANISOTROPY_TO_8 | mev::Quirks::DISABLE_SWAPCHAIN_MAINTENANCE1;
+    let Some((device, _queue)) = common::device_with_quirks(mev::Features::empty(), forced) else {
+        return;
+    };
+
+    assert!(device.quirks().contains(forced));
+}
+
+/// Arguments are bound with plain descriptor writes
+/// instead of push descriptor templates when the quirk is forced.
+#[test]
+fn avoid_push_descriptor_templates() {
+    let Some((device, mut queue)) = common::device_with_quirks(
+        mev::Features::empty(),
+        mev::Quirks::AVOID_PUSH_DESCRIPTOR_TEMPLATES,
+    ) else {
+        return;
+    };
+
+    assert!(device
+        .quirks()
+        .contains(mev::Quirks::AVOID_PUSH_DESCRIPTOR_TEMPLATES));
+    double(&device, &mut queue);
+}
+
+/// Same dispatch without quirks to compare against.
+#[test]
+fn without_quirks() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    double(&device, &mut queue);
+}
+
+/// Sampler with anisotropy above the clamped limit is still created.
+#[test]
+fn clamp_anisotropy_to_8() {
+    let Some((device, _queue)) =
+        common::device_with_quirks(mev::Features::empty(), mev::Quirks::CLAMP_ANISOTROPY_TO_8)
+    else {
+        return;
+    };
+
+    device
+        .new_sampler(mev::SamplerDesc {
+            anisotropy: Some(16.0),
+            ..mev::SamplerDesc::new()
+        })
+        .unwrap();
+}
+
+/// Present fences are not enabled with surfaces when the quirk is forced.
+#[test]
+fn disable_swapchain_maintenance1() {
+    let Some((device, _queue)) = common::device_with_quirks(
+        mev::Features::SURFACE,
+        mev::Quirks::DISABLE_SWAPCHAIN_MAINTENANCE1,
+    ) else {
+        return;
+    };
+
+    assert!(!device.features().contains(mev::Features::PRESENT_FENCES));
+}
@@ -0,0 +1,55 @@
+//! Debug reports of resources kept alive by command buffers and queues.
+#![cfg(all(debug_assertions, not(any(target_os = "macos", target_os = "ios"))))]
+
+mod common;
+
+use mev::PipelineStages;
+
+fn reports_image(report: &mev::RefsReport, name: &str) -> bool {
+    report.to_string().contains(&format!("image '{name}'"))
+}
+
+/// Image dropped by the application is still reported
+/// while command buffer and then pending submission reference it.
+#[test]
+fn leaked_image() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let image = device
+        .new_image(mev::ImageDesc {
+            name: "leaky",
+            ..mev::ImageDesc::new_d2(
+                4,
+                4,
+                mev::PixelFormat::Rgba8Unorm,
+                mev::ImageUsage::TRANSFER_DST,
+            )
+        })
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, &image);
+    drop(image);
+
+    let cbuf = encoder.finish().unwrap();
+    let report = cbuf.referenced_resources();
+    assert!(reports_image(&report, "leaky"), "{report}");
+
+    // Without checkpoint submission stays in the current epoch.
+    queue.submit_one(cbuf, false).unwrap();
+    assert!(
+        queue
+            .pending_resources()
+            .iter()
+            .any(|(_, report)| reports_image(report, "leaky")),
+        "image is not reported by pending submissions"
+    );
+
+    queue.wait_idle().unwrap();
+    assert!(!queue
+        .pending_resources()
+        .iter()
+        .any(|(_, report)| reports_image(report, "leaky")));
+}
@@ -0,0 +1,211 @@
+//! Rendering, readback and comparison shared by golden-image cases.
+
+use std::path::PathBuf;
+
+use mev::PipelineStages;
+
+use crate::common;
+
+/// Width and height of rendered images.
+pub const SIZE: u32 = 128;
+
+/// Maximum per-channel difference from golden image.
+/// Covers rounding of blended colors.
+pub const TOLERANCE: u8 = 2;
+
+/// Environment variable that enables golden-image cases.
+const ENABLE_VAR: &str = "MEV_GOLDEN";
+
+/// Environment variable that makes cases overwrite golden images
+/// with rendered ones instead of comparing.
+const BLESS_VAR: &str = "MEV_BLESS";
+
+/// Format of the depth target.
+pub const DEPTH_FORMAT: mev::PixelFormat = mev::PixelFormat::D32Float;
+
+pub struct Harness {
+    pub device: mev::Device,
+    pub queue: mev::Queue,
+}
+
+/// Returns harness if golden-image cases are enabled and a device is available.
+pub fn harness() -> Option<Harness> {
+    if std::env::var_os(ENABLE_VAR).is_none() {
+        eprintln!("Skipping golden-image case, set {ENABLE_VAR} to run it");
+        return None;
+    }
+
+    let (device, queue) = common::device(mev::Features::empty())?;
+    Some(Harness { device, queue })
+}
+
+impl Harness {
+    pub fn library(&self, code: &str) -> mev::Library {
+        common::library(&self.device, mev::ShaderLanguage::Wgsl, code)
+    }
+
+    /// Creates render pipeline with `vs_main` and `fs_main` entry points
+    /// that renders to `Rgba8Unorm` target.
+    pub fn render_pipeline(
+        &self,
+        name: &str,
+        code: &str,
+        configure: impl FnOnce(mev::RenderPipelineBuilder) -> mev::RenderPipelineBuilder,
+    ) -> mev::RenderPipeline {
+        let library = self.library(code);
+        let builder = mev::RenderPipelineDesc::builder(mev::Shader {
+            library: library.clone(),
+            entry: "vs_main".into(),
+        })
+        .name(name)
+        .fragment(mev::Shader {
+            library,
+            entry: "fs_main".into(),
+        })
+        .culling(mev::Culling::None);
+
+        self.device
+            .new_render_pipeline(configure(builder).build())
+            .unwrap()
+    }
+
+    /// Renders single pass into new target cleared to opaque black.
+    /// Depth target cleared to `depth` value is attached if it is set.
+    pub fn render(
+        &mut self,
+        name: &str,
+        depth: Option<f32>,
+        draw: impl FnOnce(&mut mev::RenderCommandEncoder),
+    ) -> mev::Image {
+        self.render_sized(name, mev::Extent2::new(SIZE, SIZE), depth, draw)
+    }
+
+    /// Same as [`render`](Harness::render) with target of given extent.
+    pub fn render_sized(
+        &mut self,
+        name: &str,
+        extent: mev::Extent2,
+        depth: Option<f32>,
+        draw: impl FnOnce(&mut mev::RenderCommandEncoder),
+    ) -> mev::Image {
+        let target = common::render_target(&self.device, extent.width(), extent.height());
+        let depth = depth.map(|clear| {
+            let image = self
+                .device
+                .new_image(mev::ImageDesc::new_d2(
+                    extent.width(),
+                    extent.height(),
+                    DEPTH_FORMAT,
+                    mev::ImageUsage::TARGET,
+                ))
+                .unwrap();
+            (image, clear)
+        });
+
+        let mut encoder = self.queue.new_command_encoder().unwrap();
+        encoder.init_image(
+            PipelineStages::empty(),
+            PipelineStages::COLOR_OUTPUT,
+            &target,
+        );
+        if let Some((depth, _)) = &depth {
+            encoder.init_image(
+                PipelineStages::empty(),
+                PipelineStages::EARLY_FRAGMENT_TEST | PipelineStages::LATE_FRAGMENT_TEST,
+                depth,
+            );
+        }
+        {
+            let mut render = encoder.render(mev::RenderPassDesc {
+                name,
+                color_attachments: &[
+                    mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)
+                ],
+                depth_stencil_attachment: depth
+                    .as_ref()
+                    .map(|(depth, clear)| mev::AttachmentDesc::clear_depth(depth, *clear)),
+                flags: mev::RenderPassFlags::empty(),
+                expected_draws: 0,
+            });
+            draw(&mut render);
+        }
+        self.queue
+            .submit_one(encoder.finish().unwrap(), true)
+            .unwrap();
+
+        target
+    }
+
+    /// Reads the image back and compares it with golden image `name`.
+    ///
+    /// Writes the image as new golden instead if bless mode is enabled.
+    #[track_caller]
+    pub fn check(&mut self, name: &str, image: &mev::Image) {
+        let pixels = mev::testing::read_image_to_rgba8(&mut self.queue, image).unwrap();
+        let path = golden_path(name);
+
+        if std::env::var_os(BLESS_VAR).is_some() {
+            mev::testing::write_png(&path, mev::Extent2::new(SIZE, SIZE), &pixels).unwrap();
+            eprintln!("Blessed {}", path.display());
+            return;
+        }
+
+        let Some(golden) = read_png(&path) else {
+            panic!(
+                "Golden image {} is missing, run with {BLESS_VAR} to create it",
+                path.display()
+            );
+        };
+
+        mev::assert_images_match!(
+            pixels,
+            golden,
+            mev::Extent2::new(SIZE, SIZE),
+            TOLERANCE,
+            format!("{}/{name}", mev::testing::DEFAULT_DIFF_DIR)
+        );
+    }
+
+    /// Reads the image back and compares it with `expected` pixels.
+    ///
+    /// Used by cases that depend on target size,
+    /// their expected images are computed instead of stored.
+    #[track_caller]
+    pub fn check_expected(&mut self, name: &str, image: &mev::Image, expected: &[u8]) {
+        let pixels = mev::testing::read_image_to_rgba8(&mut self.queue, image).unwrap();
+
+        mev::assert_images_match!(
+            pixels,
+            expected,
+            image.extent().into_2d(),
+            TOLERANCE,
+            format!("{}/{name}", mev::testing::DEFAULT_DIFF_DIR)
+        );
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/render/golden")
+        .join(format!("{name}.png"))
+}
+
+/// Reads 8-bit RGBA PNG image of `SIZE` x `SIZE` pixels.
+/// Returns `None` if file doesn't exist.
+fn read_png(path: &PathBuf) -> Option<Vec<u8>> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut reader = png::Decoder::new(file).read_info().unwrap();
+
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+
+    assert_eq!(
+        (info.width, info.height, info.color_type, info.bit_depth),
+        (SIZE, SIZE, png::ColorType::Rgba, png::BitDepth::Eight),
+        "Golden image {} must be {SIZE}x{SIZE} 8-bit RGBA",
+        path.display()
+    );
+
+    pixels.truncate(info.buffer_size());
+    Some(pixels)
+}
@@ -0,0 +1,540 @@
+//! Golden-image cases.
+//!
+//! Each case renders a small scene offscreen using public API,
+//! reads it back and compares with PNG image in `tests/render/golden`.
+//! Cases that render targets of different sizes compare with computed images instead.
+//!
+//! Cases are skipped unless `MEV_GOLDEN` environment variable is set,
+//! so CI without GPU passes:
+//!
+//! ```sh
+//! MEV_GOLDEN=1 cargo test --features golden --test render
+//! ```
+//!
+//! With `MEV_BLESS` set as well cases overwrite golden images with rendered ones.
+//! Review the changes before committing them.
+//!
+//! Scenes place primitive edges so that no pixel center lies on them
+//! and use nearest sampling aligned to texels,
+//! so golden images are exact up to rounding of blended colors.
+//! Shaders specify vertices in pixels with `(0, 0)` at the top-left corner.
+
+#[path = "../common/mod.rs"]
+mod common;
+mod harness;
+
+use harness::{harness, Harness, DEPTH_FORMAT, SIZE};
+use mev::PipelineStages;
+
+/// Converts position in pixels to clip space.
+const TO_CLIP: &str = r#"
+fn to_clip(pixel: vec2<f32>, depth: f32) -> vec4<f32> {
+    return vec4<f32>(pixel / 64.0 - 1.0, depth, 1.0);
+}
+"#;
+
+/// Corners of a quad as two triangles.
+const QUAD: &str = r#"
+const CORNERS = array<vec2<f32>, 6>(
+    vec2<f32>(0.0, 0.0),
+    vec2<f32>(1.0, 0.0),
+    vec2<f32>(0.0, 1.0),
+    vec2<f32>(0.0, 1.0),
+    vec2<f32>(1.0, 0.0),
+    vec2<f32>(1.0, 1.0),
+);
+"#;
+
+fn shader(code: &str) -> String {
+    format!("{TO_CLIP}{QUAD}{code}")
+}
+
+/// Red triangle with hypotenuse that never crosses pixel centers.
+#[test]
+fn solid_triangle() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = shader(
+        r#"
+const VERTICES = array<vec2<f32>, 3>(
+    vec2<f32>(16.0, 16.0),
+    vec2<f32>(112.0, 16.0),
+    vec2<f32>(16.0, 64.0),
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    return to_clip(VERTICES[index], 0.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+"#,
+    );
+
+    let pipeline = h.render_pipeline("solid-triangle", &code, |b| {
+        b.color_target(mev::PixelFormat::Rgba8Unorm)
+    });
+
+    let image = h.render("solid-triangle", None, |render| {
+        render.with_pipeline(&pipeline);
+        render.draw(0..3, 0..1);
+    });
+
+    h.check("solid_triangle", &image);
+}
+
+#[derive(mev::Arguments)]
+pub struct Textured {
+    #[mev(sampled, fragment)]
+    pub texture: mev::Image,
+    #[mev(fragment, sampler = "nearest_clamp")]
+    pub sampler: (),
+}
+
+/// 4x4 texture stretched over 64x64 quad, each texel covers 16x16 pixels.
+#[test]
+fn textured_quad() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = shader(
+        r#"
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let corner = CORNERS[index];
+    var out: VertexOutput;
+    out.position = to_clip(32.0 + corner * 64.0, 0.0);
+    out.uv = corner;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(texture, texture_sampler, in.uv);
+}
+"#,
+    );
+
+    let pipeline = h.render_pipeline("textured-quad", &code, |b| {
+        b.color_target(mev::PixelFormat::Rgba8Unorm)
+            .arguments::<Textured>()
+    });
+
+    let texture = mev::upload::new_image_with(
+        &mut h.queue,
+        mev::ImageDesc::new_d2(4, 4, mev::PixelFormat::Rgba8Unorm, mev::ImageUsage::SAMPLED),
+        |texel| {
+            let x = texel.x as u8 * 85;
+            let y = texel.y as u8 * 85;
+            [x, y, 255 - x, 255]
+        },
+    )
+    .unwrap();
+
+    let image = h.render("textured-quad", None, |render| {
+        render.with_pipeline(&pipeline);
+        render.with_arguments(
+            0,
+            &Textured {
+                texture,
+                sampler: (),
+            },
+        );
+        render.draw(0..6, 0..1);
+    });
+
+    h.check("textured_quad", &image);
+}
+
+/// Overlapping half-transparent red and green quads
+/// with premultiplied alpha blending.
+#[test]
+fn blended_quads() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = shader(
+        r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let quad = index / 6u;
+    let corner = CORNERS[index % 6u];
+    var out: VertexOutput;
+    out.position = to_clip(16.0 + f32(quad) * 32.0 + corner * 64.0, 0.0);
+    if quad == 0u {
+        out.color = vec4<f32>(0.5, 0.0, 0.0, 0.5);
+    } else {
+        out.color = vec4<f32>(0.0, 0.5, 0.0, 0.5);
+    }
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#,
+    );
+
+    let pipeline = h.render_pipeline("blended-quads", &code, |b| {
+        b.color_target_blend(mev::PixelFormat::Rgba8Unorm, mev::BlendDesc::default())
+    });
+
+    let image = h.render("blended-quads", None, |render| {
+        render.with_pipeline(&pipeline);
+        render.draw(0..12, 0..1);
+    });
+
+    h.check("blended_quads", &image);
+}
+
+/// Far yellow quad is drawn after near blue quad and hidden where they overlap.
+#[test]
+fn depth_test() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = shader(
+        r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let quad = index / 6u;
+    let corner = CORNERS[index % 6u];
+    var out: VertexOutput;
+    if quad == 0u {
+        out.position = to_clip(16.0 + corner * 64.0, 0.25);
+        out.color = vec4<f32>(0.0, 0.0, 1.0, 1.0);
+    } else {
+        out.position = to_clip(48.0 + corner * 64.0, 0.75);
+        out.color = vec4<f32>(1.0, 1.0, 0.0, 1.0);
+    }
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#,
+    );
+
+    let pipeline = h.render_pipeline("depth-test", &code, |b| {
+        b.color_target(mev::PixelFormat::Rgba8Unorm)
+            .depth(DEPTH_FORMAT, mev::CompareFunction::Less)
+    });
+
+    let image = h.render("depth-test", Some(1.0), |render| {
+        render.with_pipeline(&pipeline);
+        render.draw(0..12, 0..1);
+    });
+
+    h.check("depth_test", &image);
+}
+
+/// Same scene as `depth_test` with reversed depth range.
+///
+/// Near quad maps to greater depth and wins with `GreaterEqual` test
+/// against depth cleared to `0.0`, so the image matches `depth_test`.
+#[test]
+fn reverse_z() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = shader(
+        r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let quad = index / 6u;
+    let corner = CORNERS[index % 6u];
+    var out: VertexOutput;
+    if quad == 0u {
+        out.position = to_clip(16.0 + corner * 64.0, 0.25);
+        out.color = vec4<f32>(0.0, 0.0, 1.0, 1.0);
+    } else {
+        out.position = to_clip(48.0 + corner * 64.0, 0.75);
+        out.color = vec4<f32>(1.0, 1.0, 0.0, 1.0);
+    }
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#,
+    );
+
+    let pipeline = h.render_pipeline("reverse-z", &code, |b| {
+        b.color_target(mev::PixelFormat::Rgba8Unorm)
+            .depth(DEPTH_FORMAT, mev::CompareFunction::GreaterEqual)
+    });
+
+    let image = h.render("reverse-z", Some(0.0), |render| {
+        render.with_pipeline(&pipeline);
+        render.with_depth_range(1.0, 0.0);
+        render.draw(0..12, 0..1);
+    });
+
+    h.check("depth_test", &image);
+}
+
+/// Four 16x16 squares placed and colored by instance index.
+#[test]
+fn instanced_draw() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = shader(
+        r#"
+const COLORS = array<vec4<f32>, 4>(
+    vec4<f32>(1.0, 0.0, 0.0, 1.0),
+    vec4<f32>(0.0, 1.0, 0.0, 1.0),
+    vec4<f32>(0.0, 0.0, 1.0, 1.0),
+    vec4<f32>(1.0, 1.0, 1.0, 1.0),
+);
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    @builtin(instance_index) instance: u32,
+) -> VertexOutput {
+    let origin = vec2<f32>(16.0 + f32(instance) * 32.0, 56.0);
+    var out: VertexOutput;
+    out.position = to_clip(origin + CORNERS[index] * 16.0, 0.0);
+    out.color = COLORS[instance];
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#,
+    );
+
+    let pipeline = h.render_pipeline("instanced-draw", &code, |b| {
+        b.color_target(mev::PixelFormat::Rgba8Unorm)
+    });
+
+    let image = h.render("instanced-draw", None, |render| {
+        render.with_pipeline(&pipeline);
+        render.draw(0..6, 0..4);
+    });
+
+    h.check("instanced_draw", &image);
+}
+
+#[derive(mev::Arguments)]
+pub struct Gradient {
+    #[mev(storage, compute)]
+    pub output: mev::Image,
+}
+
+/// Gradient written by compute shader into storage image.
+#[test]
+fn compute_written_image() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = r#"
+@group(0) @binding(0) var output: texture_storage_2d<rgba8unorm, write>;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let color = vec4<f32>(
+        f32(id.x * 2u) / 255.0,
+        f32(id.y * 2u) / 255.0,
+        f32(id.x + id.y) / 255.0,
+        1.0,
+    );
+    textureStore(output, vec2<i32>(id.xy), color);
+}
+"#;
+
+    let pipeline = h
+        .device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library: h.library(code),
+                entry: "main".into(),
+            })
+            .name("compute-written-image")
+            .work_group_size([8, 8, 1])
+            .arguments::<Gradient>()
+            .build(),
+        )
+        .unwrap();
+
+    let image = h
+        .device
+        .new_image(mev::ImageDesc::new_d2(
+            SIZE,
+            SIZE,
+            mev::PixelFormat::Rgba8Unorm,
+            mev::ImageUsage::STORAGE | mev::ImageUsage::TRANSFER_SRC,
+        ))
+        .unwrap();
+
+    let mut encoder = h.queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COMPUTE_SHADER,
+        &image,
+    );
+    {
+        let mut compute = encoder.compute();
+        compute.with_pipeline(&pipeline);
+        compute.with_arguments(
+            0,
+            &Gradient {
+                output: image.clone(),
+            },
+        );
+        compute.dispatch(mev::Extent3::new(SIZE / 8, SIZE / 8, 1));
+    }
+    h.queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    h.check("compute_written_image", &image);
+}
+
+/// Creates full-screen pipeline with fragment shader `fs_main` from `code`
+/// composed after the embedded full-screen vertex shader.
+fn fullscreen_pipeline(h: &Harness, name: &str, code: &str) -> mev::RenderPipeline {
+    let library = h
+        .device
+        .new_shader_library(mev::LibraryDesc {
+            name,
+            input: mev::LibraryInput::Sources(vec![
+                mev::FULLSCREEN_VERTEX_SHADER,
+                mev::ShaderSource {
+                    code: code.as_bytes().into(),
+                    filename: None,
+                    language: mev::ShaderLanguage::Wgsl,
+                },
+            ]),
+        })
+        .unwrap();
+
+    let desc = mev::RenderPipelineDesc::fullscreen(
+        mev::Shader {
+            library,
+            entry: "fs_main".into(),
+        },
+        mev::PixelFormat::Rgba8Unorm,
+    )
+    .name(name)
+    .build();
+
+    assert!(desc.vertex_layouts.is_empty());
+    assert!(desc.vertex_attributes.is_empty());
+
+    h.device.new_render_pipeline(desc).unwrap()
+}
+
+/// Full-screen triangle drawn without vertex buffers covers every pixel
+/// and fragment positions match pixel coordinates.
+///
+/// Expected image is computed, it has a pixel per coordinate.
+#[test]
+fn fullscreen_empty_vertex_layouts() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = r#"
+@fragment
+fn fs_main(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(floor(in.position.xy) / 255.0, 1.0, 1.0);
+}
+"#;
+
+    let pipeline = fullscreen_pipeline(&h, "fullscreen-empty-vertex-layouts", code);
+
+    let image = h.render("fullscreen-empty-vertex-layouts", None, |render| {
+        render.with_pipeline(&pipeline);
+        render.draw(0..3, 0..1);
+    });
+
+    let expected = (0..SIZE)
+        .flat_map(|y| (0..SIZE).flat_map(move |x| [x as u8, y as u8, 255, 255]))
+        .collect::<Vec<_>>();
+
+    h.check_expected("fullscreen-empty-vertex-layouts", &image, &expected);
+}
+
+/// Full-screen triangle covers targets of odd sizes
+/// and texture coordinates span the viewport from the top-left corner.
+///
+/// Blue channel marks covered pixels.
+#[test]
+fn fullscreen_odd_sizes() {
+    let Some(mut h) = harness() else {
+        return;
+    };
+
+    let code = r#"
+@fragment
+fn fs_main(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.uv, 1.0, 1.0);
+}
+"#;
+
+    let pipeline = fullscreen_pipeline(&h, "fullscreen-odd-sizes", code);
+
+    for (width, height) in [(1, 1), (3, 7), (65, 33), (127, 129)] {
+        let name = format!("fullscreen-odd-sizes-{width}x{height}");
+
+        let image = h.render_sized(&name, mev::Extent2::new(width, height), None, |render| {
+            render.with_pipeline(&pipeline);
+            render.draw(0..3, 0..1);
+        });
+
+        let unorm = |v: u32, size: u32| ((v as f32 + 0.5) / size as f32 * 255.0).round() as u8;
+        let expected = (0..height)
+            .flat_map(|y| {
+                (0..width).flat_map(move |x| [unorm(x, width), unorm(y, height), 255, 255])
+            })
+            .collect::<Vec<_>>();
+
+        h.check_expected(&name, &image, &expected);
+    }
+}
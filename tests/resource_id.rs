@@ -0,0 +1,71 @@
+//! Identifiers of resources.
+
+mod common;
+
+use std::collections::HashSet;
+
+use mev::{ImageDesc, PixelFormat, ViewDesc};
+
+#[test]
+fn unique_ids() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let buffer = device
+        .new_buffer(mev::BufferDesc {
+            size: 16,
+            usage: mev::BufferUsage::UNIFORM,
+            memory: mev::Memory::Shared,
+            name: "ids",
+            shared_across_queues: false,
+        })
+        .unwrap();
+    let image = device
+        .new_image(ImageDesc::new_d2_texture(4, 4, PixelFormat::Rgba8Unorm))
+        .unwrap();
+    let sampler = device.new_sampler(mev::SamplerDesc::new()).unwrap();
+
+    let ids = [buffer.id(), image.id(), sampler.id()];
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+
+    // Clones are the same resource.
+    assert_eq!(buffer.clone().id(), buffer.id());
+    assert_eq!(image.clone().id(), image.id());
+    assert_eq!(image.image_id(), image.id());
+}
+
+#[test]
+fn view_ids() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let image = device
+        .new_image(ImageDesc {
+            levels: 3,
+            ..ImageDesc::new_d2_texture(16, 16, PixelFormat::Rgba8Unorm)
+        })
+        .unwrap();
+
+    let first = image
+        .view(&device, ViewDesc::new(PixelFormat::Rgba8Unorm).levels(1..2))
+        .unwrap();
+    let second = image
+        .view(&device, ViewDesc::new(PixelFormat::Rgba8Unorm).levels(2..3))
+        .unwrap();
+
+    assert_ne!(first.id(), image.id());
+    assert_ne!(first.id(), second.id());
+    assert_eq!(first.image_id(), image.id());
+    assert_eq!(second.image_id(), image.id());
+
+    // Vulkan caches views, the same view keeps its identifier.
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    for _ in 0..100 {
+        let again = image
+            .view(&device, ViewDesc::new(PixelFormat::Rgba8Unorm).levels(1..2))
+            .unwrap();
+        assert_eq!(again.id(), first.id());
+    }
+}
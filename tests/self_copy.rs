@@ -0,0 +1,116 @@
+//! Copies within the same image.
+
+mod common;
+
+use mev::{ImageDesc, ImageUsage, PipelineStages, PixelFormat, ViewDesc};
+
+const SIZE: u32 = 8;
+const COPIES: u32 = 4;
+
+/// Each copy reads the layer written by the previous one through a different view,
+/// so content of the first layer reaches the last only if copies are ordered.
+#[test]
+fn four_copy_chain() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let layer_bytes = u64::from(SIZE * SIZE * 4);
+    let pattern = (0..layer_bytes).map(|idx| idx as u8).collect::<Vec<_>>();
+
+    let image = device
+        .new_image(ImageDesc {
+            layers: COPIES + 1,
+            ..ImageDesc::new_d2(
+                SIZE,
+                SIZE,
+                PixelFormat::Rgba8Unorm,
+                ImageUsage::TRANSFER_SRC | ImageUsage::TRANSFER_DST,
+            )
+        })
+        .unwrap();
+
+    let layers = (0..=COPIES)
+        .map(|layer| {
+            image
+                .view(
+                    &device,
+                    ViewDesc::new(PixelFormat::Rgba8Unorm).layers(layer..layer + 1),
+                )
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let upload = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &pattern,
+            usage: mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Upload,
+            name: "pattern",
+        })
+        .unwrap();
+
+    let result = device
+        .new_buffer(mev::BufferDesc {
+            size: layer_bytes * u64::from(COPIES + 1),
+            usage: mev::BufferUsage::TRANSFER_DST | mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Device,
+            name: "self-copy-result",
+            shared_across_queues: false,
+        })
+        .unwrap();
+
+    let extent = mev::Extent3::new(SIZE, SIZE, 1);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, &image);
+    {
+        let mut copy = encoder.copy();
+        copy.copy_buffer_to_image(
+            &upload,
+            0,
+            0,
+            0,
+            &layers[0],
+            mev::Offset3::ZERO,
+            extent,
+            0..1,
+            0,
+        );
+        copy.barrier(PipelineStages::TRANSFER, PipelineStages::TRANSFER);
+
+        for pair in layers.windows(2) {
+            copy.copy_image_region(
+                &pair[0],
+                0,
+                0,
+                mev::Offset3::ZERO,
+                &pair[1],
+                0,
+                0,
+                mev::Offset3::ZERO,
+                extent,
+                1,
+            );
+        }
+
+        copy.barrier(PipelineStages::TRANSFER, PipelineStages::TRANSFER);
+        copy.copy_image_to_buffer(
+            &image,
+            mev::Offset3::ZERO,
+            extent,
+            0..COPIES + 1,
+            0,
+            &result,
+            0,
+            0,
+            0,
+        );
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &result, PipelineStages::TRANSFER);
+    for (layer, pixels) in data.chunks_exact(layer_bytes as usize).enumerate() {
+        assert_eq!(pixels, &pattern[..], "layer {layer}");
+    }
+}
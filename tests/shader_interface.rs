@@ -0,0 +1,33 @@
+//! Structs generated from WGSL match shader layout.
+
+use mev::DeviceRepr;
+
+mev::shader_interface!("tests/shaders/interface.wgsl", pub struct Light);
+mev::shader_interface!("tests/shaders/interface.wgsl", pub struct Frame);
+
+#[test]
+fn layout_matches_shader() {
+    // Field offsets are checked at compile time.
+    assert_eq!(Light::SIZE, 32);
+    assert_eq!(Frame::SIZE, 224);
+}
+
+#[test]
+fn repr_writes_fields() {
+    let light = Light {
+        position: mev::vec3(1.0, 2.0, 3.0),
+        intensity: 4.0,
+        color: mev::vec3(5.0, 6.0, 7.0),
+    };
+
+    let repr = light.as_repr();
+    let floats: &[f32] = bytemuck::cast_slice(Light::as_bytes(&repr));
+    assert_eq!(&floats[..4], &[1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(&floats[4..7], &[5.0, 6.0, 7.0]);
+}
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/shader_interface/*.rs");
+}
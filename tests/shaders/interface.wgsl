@@ -0,0 +1,22 @@
+struct Light {
+    position: vec3<f32>,
+    intensity: f32,
+    color: vec3<f32>,
+}
+
+struct Frame {
+    time: f32,
+    camera: vec3<f32>,
+    view: mat4x4<f32>,
+    jitter: vec2<f32>,
+    index: u32,
+    lights: array<Light, 2>,
+    offsets: array<vec4<i32>, 3>,
+}
+
+struct Flags {
+    count: u32,
+    enabled: bool,
+}
+
+@group(0) @binding(0) var<uniform> frame: Frame;
@@ -0,0 +1,71 @@
+//! sRGB and linear views of the same image.
+
+mod common;
+
+use mev::{PipelineStages, PixelFormat, ViewError};
+
+const SIZE: u32 = 4;
+
+#[test]
+fn view_formats() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let image = common::render_target(&device, SIZE, SIZE);
+
+    let srgb = image.srgb_view(&device).unwrap();
+    assert_eq!(srgb.format(), PixelFormat::Rgba8Srgb);
+
+    let linear = srgb.linear_view(&device).unwrap();
+    assert_eq!(linear.format(), PixelFormat::Rgba8Unorm);
+
+    let data = device
+        .new_image(mev::ImageDesc {
+            name: "data",
+            ..mev::ImageDesc::new_d2_texture(SIZE, SIZE, PixelFormat::R32Float)
+        })
+        .unwrap();
+
+    let err = data.srgb_view(&device).unwrap_err();
+    assert_eq!(
+        err,
+        ViewError::NoSrgbVariant {
+            image: "data".to_owned(),
+            format: PixelFormat::R32Float,
+        }
+    );
+}
+
+/// Clear color written through sRGB view is stored encoded.
+#[test]
+fn srgb_view_encodes() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let target = common::render_target(&device, SIZE, SIZE);
+    let srgb = target.srgb_view(&device).unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(PipelineStages::empty(), PipelineStages::COLOR_OUTPUT, &srgb);
+    {
+        let _render = encoder.render(mev::RenderPassDesc {
+            name: "clear-srgb",
+            color_attachments: &[
+                mev::AttachmentDesc::new(&srgb).clear(mev::ClearColor(0.5, 0.0, 1.0, 1.0))
+            ],
+            depth_stencil_attachment: None,
+            flags: mev::RenderPassFlags::empty(),
+            expected_draws: 0,
+        });
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let pixels = mev::testing::read_image_to_rgba8(&mut queue, &target).unwrap();
+
+    // 0.5 is encoded as 188 in sRGB, linear view would store 128.
+    let [r, g, b, a] = common::pixel(&pixels, SIZE, 1, 1);
+    assert!(r.abs_diff(188) <= 1, "red is {r}");
+    assert_eq!([g, b, a], [0, 255, 255]);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+const SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main() -> VertexOutput {
+    return VertexOutput(vec4<f32>(0.0), vec4<f32>(1.0));
+}
+
+@fragment
+fn fs_match(@location(0) color: vec4<f32>) -> @location(0) vec4<f32> {
+    return color;
+}
+
+@fragment
+fn fs_mismatch(@location(0) color: vec2<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(color, 0.0, 1.0);
+}
+"#;
+
+fn pipeline(
+    device: &mev::Device,
+    library: &mev::Library,
+    fragment: &str,
+) -> Result<mev::RenderPipeline, mev::CreatePipelineError> {
+    device.new_render_pipeline(
+        mev::RenderPipelineDesc::builder(mev::Shader {
+            library: library.clone(),
+            entry: "vs_main".into(),
+        })
+        .fragment(mev::Shader {
+            library: library.clone(),
+            entry: fragment.into(),
+        })
+        .color_target(mev::PixelFormat::Rgba8Unorm)
+        .build(),
+    )
+}
+
+#[test]
+fn mismatched_stages() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, SHADER);
+
+    assert!(pipeline(&device, &library, "fs_match").is_ok());
+
+    let Err(err) = pipeline(&device, &library, "fs_mismatch") else {
+        panic!("Pipeline with mismatched stages must not be created");
+    };
+    assert!(
+        err.to_string()
+            .contains("writes `vec4<f32>` at location 0 but fragment shader reads `vec2<f32>`"),
+        "{err}"
+    );
+}
@@ -0,0 +1,52 @@
+//! Identifiers of queue submissions.
+
+mod common;
+
+use mev::SubmissionId;
+
+#[test]
+fn ids_grow() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    assert_eq!(queue.last_completed_submission(), SubmissionId::NONE);
+
+    let mut last = SubmissionId::NONE;
+    for idx in 0..10 {
+        let encoder = queue.new_command_encoder().unwrap();
+        let cbuf = encoder.finish().unwrap();
+
+        let id = match idx % 3 {
+            0 => queue.submit_one(cbuf, false),
+            1 => queue.submit([cbuf], idx % 2 == 0),
+            _ => queue.submit_labeled([cbuf], "labeled", true),
+        }
+        .unwrap();
+
+        assert!(id > last, "{id} after {last}");
+        last = id;
+    }
+
+    queue.wait_idle().unwrap();
+    assert_eq!(queue.last_completed_submission(), last);
+}
+
+#[test]
+fn wait_for_submission() {
+    let Some((_device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let encoder = queue.new_command_encoder().unwrap();
+    let id = queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    queue.wait_submission(id).unwrap();
+    assert!(queue.last_completed_submission() >= id);
+}
+
+#[test]
+fn display() {
+    assert_eq!(SubmissionId::NONE.to_string(), "#0");
+    assert_eq!(SubmissionId::NONE.get(), 0);
+}
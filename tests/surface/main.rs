@@ -0,0 +1,516 @@
+//! Cases that need a window to create surfaces for.
+//!
+//! Runs without test harness, as windows must be created on the main thread
+//! on some platforms. Cases run one after another with the same window.
+//! They are skipped when there is no display, suitable device
+//! or the window system is not supported.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::Duration,
+};
+
+use mev::PipelineStages;
+use winit::{
+    application::ApplicationHandler,
+    dpi::PhysicalSize,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+/// Width and height of the window.
+const SIZE: u32 = 64;
+
+type Case = fn(&Window);
+
+const CASES: &[(&str, Case)] = &[
+    (
+        "dropped_frames_survive_recreation",
+        dropped_frames_survive_recreation,
+    ),
+    ("use_after_present_panics", use_after_present_panics),
+    ("present_from_queue_families", present_from_queue_families),
+    ("frame_viewport_is_upright", frame_viewport_is_upright),
+    ("present_fence_paths", present_fence_paths),
+    ("fake_frames_while_minimized", fake_frames_while_minimized),
+];
+
+fn main() {
+    let event_loop = match EventLoop::new() {
+        Ok(event_loop) => event_loop,
+        Err(err) => {
+            eprintln!("Skipping surface cases, failed to create event loop: {err}");
+            return;
+        }
+    };
+
+    let mut runner = Runner {
+        started: false,
+        failed: Vec::new(),
+    };
+    event_loop.run_app(&mut runner).unwrap();
+
+    if !runner.failed.is_empty() {
+        eprintln!("Failed cases: {}", runner.failed.join(", "));
+        std::process::exit(1);
+    }
+}
+
+struct Runner {
+    started: bool,
+    failed: Vec<&'static str>,
+}
+
+impl ApplicationHandler for Runner {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+
+        let attributes = Window::default_attributes()
+            .with_title("mev surface tests")
+            .with_inner_size(PhysicalSize::new(SIZE, SIZE))
+            .with_resizable(false);
+
+        match event_loop.create_window(attributes) {
+            Ok(window) => {
+                for &(name, case) in CASES {
+                    match catch_unwind(AssertUnwindSafe(|| case(&window))) {
+                        Ok(()) => println!("test {name} ... ok"),
+                        Err(_) => {
+                            println!("test {name} ... FAILED");
+                            self.failed.push(name);
+                        }
+                    }
+                }
+            }
+            Err(err) => eprintln!("Skipping surface cases, failed to create window: {err}"),
+        }
+
+        event_loop.exit();
+    }
+
+    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
+}
+
+/// Creates device with given features and quirks and a surface for the window.
+///
+/// Returns `None` if there is no suitable device or window system is not supported.
+fn surface(
+    window: &Window,
+    features: mev::Features,
+    quirks: mev::Quirks,
+) -> Option<(mev::Device, mev::Queue, mev::Surface)> {
+    let (device, queue) = common::device_with_quirks(features | mev::Features::SURFACE, quirks)?;
+
+    match device.new_surface(window, window) {
+        Ok(surface) => Some((device, queue, surface)),
+        Err(err) => {
+            eprintln!("Skipping test, failed to create surface: {err}");
+            None
+        }
+    }
+}
+
+/// Clears the frame and presents it.
+fn present(queue: &mut mev::Queue, mut frame: mev::Frame) -> mev::SubmissionId {
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        frame.image(),
+    );
+    encoder.render(
+        mev::RenderPassDesc::new()
+            .name("clear")
+            .color_attachments(&[
+                mev::AttachmentDesc::new(frame.image()).clear(mev::ClearColor::BLACK)
+            ]),
+    );
+
+    queue.sync_frame(&mut frame, PipelineStages::COLOR_OUTPUT);
+    encoder.present(frame, PipelineStages::COLOR_OUTPUT);
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap()
+}
+
+/// Frames dropped without presenting don't block swapchain recreation.
+/// Their present fences are never signaled.
+fn dropped_frames_survive_recreation(window: &Window) {
+    let Some((_device, mut queue, mut surface)) =
+        surface(window, mev::Features::empty(), mev::Quirks::empty())
+    else {
+        return;
+    };
+
+    // Twice the number of retired swapchains that makes surface wait for them.
+    for _ in 0..16 {
+        let frame = surface.next_frame().unwrap();
+        drop(frame);
+        surface.recreate();
+    }
+
+    let frame = surface.next_frame().unwrap();
+    present(&mut queue, frame);
+    queue.wait_idle().unwrap();
+}
+
+/// Debug builds panic when frame's image is used after present is recorded.
+fn use_after_present_panics(window: &Window) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let Some((device, mut queue, mut surface)) =
+        surface(window, mev::Features::empty(), mev::Quirks::empty())
+    else {
+        return;
+    };
+
+    let target = common::render_target(&device, SIZE, SIZE);
+
+    let mut frame = surface.next_frame().unwrap();
+    if frame.is_fake() {
+        eprintln!("Skipping test, surface has zero extent");
+        return;
+    }
+    let image = frame.image().clone();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &image,
+    );
+    queue.sync_frame(&mut frame, PipelineStages::COLOR_OUTPUT);
+    encoder.present(frame, PipelineStages::COLOR_OUTPUT);
+
+    let render = catch_unwind(AssertUnwindSafe(|| {
+        encoder.render(
+            mev::RenderPassDesc::new()
+                .name("after-present")
+                .color_attachments(&[
+                    mev::AttachmentDesc::new(&image).clear(mev::ClearColor::BLACK)
+                ]),
+        );
+    }));
+    assert!(
+        render.is_err(),
+        "render pass into presented image must panic"
+    );
+
+    let copy = catch_unwind(AssertUnwindSafe(|| {
+        encoder.copy().copy_image(&image, &target);
+    }));
+    assert!(copy.is_err(), "copy from presented image must panic");
+}
+
+/// Queues of families that can't present to the surface fail to submit present.
+/// Queues without graphics capability present if their family supports the surface.
+fn present_from_queue_families(window: &Window) {
+    let Some((device, mut queues)) = common::device_with_all_families(mev::Features::SURFACE)
+    else {
+        return;
+    };
+
+    let mut surface = match device.new_surface(window, window) {
+        Ok(surface) => surface,
+        Err(err) => {
+            eprintln!("Skipping test, failed to create surface: {err}");
+            return;
+        }
+    };
+
+    let mut unsupported = false;
+    let mut non_graphics = false;
+
+    for queue in &mut queues {
+        let supported = device.supports_present(queue.family(), &surface);
+        let flags = queue.flags();
+
+        if supported && flags.contains(mev::QueueFlags::GRAPHICS) {
+            continue;
+        }
+
+        let mut frame = surface.next_frame().unwrap();
+        if frame.is_fake() {
+            eprintln!("Skipping test, surface has zero extent");
+            return;
+        }
+
+        let stages = if flags.contains(mev::QueueFlags::COMPUTE) {
+            PipelineStages::COMPUTE_SHADER
+        } else {
+            PipelineStages::TRANSFER
+        };
+
+        let mut encoder = queue.new_command_encoder().unwrap();
+        encoder.init_image(PipelineStages::empty(), stages, frame.image());
+        queue.sync_frame(&mut frame, stages);
+        encoder.present(frame, stages);
+        let result = queue.submit_one(encoder.finish().unwrap(), true);
+
+        if supported {
+            result.unwrap();
+            queue.wait_idle().unwrap();
+            non_graphics = true;
+        } else {
+            assert!(matches!(result, Err(mev::DeviceError::PresentNotSupported)));
+            unsupported = true;
+
+            // Frame is dropped without present.
+            surface.recreate();
+        }
+    }
+
+    if !unsupported {
+        eprintln!("No queue family without present support, rejection is not tested");
+    }
+    if !non_graphics {
+        eprintln!("No queue family without graphics that presents, compute present is not tested");
+    }
+}
+
+/// Draws triangle that covers upper half of clip space.
+const UPPER_HALF: &str = r#"
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u));
+    return vec4<f32>(uv * 2.0 - vec2<f32>(1.0, 0.0), 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+"#;
+
+/// Frame's viewport and scissor cover the whole frame
+/// and positive Y in clip space ends up at the top of presented image.
+fn frame_viewport_is_upright(window: &Window) {
+    let Some((device, mut queue, mut surface)) =
+        surface(window, mev::Features::empty(), mev::Quirks::empty())
+    else {
+        return;
+    };
+
+    let usage = mev::ImageUsage::TARGET | mev::ImageUsage::TRANSFER_SRC;
+    if !surface.supported_usage().unwrap().contains(usage) {
+        eprintln!("Skipping test, frames can't be read back");
+        return;
+    }
+    surface.set_usage(usage).unwrap();
+
+    let mut frame = surface.next_frame().unwrap();
+    if frame.is_fake() {
+        eprintln!("Skipping test, surface has zero extent");
+        return;
+    }
+
+    let image = frame.image().clone();
+    let format = image.format();
+    if format.size() != 4 {
+        eprintln!("Skipping test, frame format {format:?} is not 4 bytes per pixel");
+        return;
+    }
+
+    let extent = image.extent().into_2d();
+    let (width, height) = (extent.width(), extent.height());
+
+    assert_eq!(
+        frame.viewport(),
+        (
+            mev::Offset3::ZERO,
+            mev::Extent3::new(width as f32, height as f32, 1.0)
+        )
+    );
+    assert_eq!(frame.scissor(), (mev::Offset2::ZERO, extent));
+
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, UPPER_HALF);
+    let pipeline = device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            })
+            .name("upper-half")
+            .fragment(mev::Shader {
+                library,
+                entry: "fs_main".into(),
+            })
+            .color_target(format)
+            .build(),
+        )
+        .unwrap();
+
+    let bytes_per_line = u64::from(width) * 4;
+    let mut download = device
+        .new_buffer(mev::BufferDesc {
+            size: bytes_per_line * u64::from(height),
+            usage: mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Download,
+            name: "frame-readback",
+            shared_across_queues: false,
+        })
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &image,
+    );
+    {
+        let mut render = encoder.render(
+            mev::RenderPassDesc::new()
+                .name("upper-half")
+                .color_attachments(&[
+                    mev::AttachmentDesc::new(&image).clear(mev::ClearColor::BLACK)
+                ]),
+        );
+        let (offset, extent) = frame.viewport();
+        render.with_viewport(offset, extent);
+        let (offset, extent) = frame.scissor();
+        render.with_scissor(offset, extent);
+        render.with_pipeline(&pipeline);
+        render.draw(0..3, 0..1);
+    }
+    encoder.barrier(PipelineStages::COLOR_OUTPUT, PipelineStages::TRANSFER);
+    encoder.copy().copy_image_to_buffer(
+        &image,
+        mev::Offset3::ZERO,
+        extent.to_3d(),
+        0..1,
+        0,
+        &download,
+        0,
+        bytes_per_line,
+        bytes_per_line * u64::from(height),
+    );
+
+    queue.sync_frame(&mut frame, PipelineStages::TRANSFER);
+    encoder.present(frame, PipelineStages::TRANSFER);
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    queue.wait_idle().unwrap();
+
+    let mut pixels = vec![0; download.size() as usize];
+
+    // Safety: commands that write the buffer are completed.
+    unsafe {
+        download.read_unchecked(0, &mut pixels);
+    }
+
+    // Color channels are the same, so channel order doesn't matter.
+    let top = common::pixel(&pixels, width, width / 2, height / 4);
+    let bottom = common::pixel(&pixels, width, width / 2, height * 3 / 4);
+    assert_eq!(top[..3], [255; 3], "upper half is not drawn");
+    assert_eq!(bottom[..3], [0; 3], "lower half is drawn");
+}
+
+/// Frames report present fences only when the feature is active,
+/// and pacing on submissions works on both paths.
+///
+/// Present fences are force-disabled with a quirk for the second path.
+fn present_fence_paths(window: &Window) {
+    for quirks in [
+        mev::Quirks::empty(),
+        mev::Quirks::DISABLE_SWAPCHAIN_MAINTENANCE1,
+    ] {
+        let Some((device, mut queue, mut surface)) =
+            surface(window, mev::Features::empty(), quirks)
+        else {
+            return;
+        };
+
+        let present_fences = device.features().contains(mev::Features::PRESENT_FENCES);
+        if quirks.contains(mev::Quirks::DISABLE_SWAPCHAIN_MAINTENANCE1) {
+            assert!(!present_fences, "quirk must disable present fences");
+        } else if !present_fences {
+            eprintln!("Present fences are not supported, only fallback path is tested");
+        }
+
+        // Keeps at most two frames in flight, as frame pacers do.
+        let mut in_flight = std::collections::VecDeque::new();
+        for idx in 0..16 {
+            if in_flight.len() == 2 {
+                queue
+                    .wait_submission(in_flight.pop_front().unwrap())
+                    .unwrap();
+            }
+
+            // Retired swapchain is released by present fences or by submissions.
+            if idx == 8 {
+                surface.recreate();
+            }
+
+            let frame = surface.next_frame().unwrap();
+            if frame.is_fake() {
+                assert!(!frame.has_present_fence());
+                eprintln!("Skipping test, surface has zero extent");
+                return;
+            }
+            assert_eq!(frame.has_present_fence(), present_fences);
+
+            in_flight.push_back(present(&mut queue, frame));
+        }
+
+        queue.wait_idle().unwrap();
+    }
+}
+
+/// Acquires fake frame, recreating swapchain until window is minimized.
+///
+/// Returns `None` if window system doesn't minimize the window.
+fn next_fake_frame(surface: &mut mev::Surface) -> Option<mev::Frame> {
+    for _ in 0..50 {
+        let frame = surface.next_frame().unwrap();
+        if frame.is_fake() {
+            return Some(frame);
+        }
+        drop(frame);
+        std::thread::sleep(Duration::from_millis(20));
+        surface.recreate();
+    }
+    None
+}
+
+/// Long minimization produces many fake frames in a row.
+/// Each is synced and presented like a real one.
+///
+/// Keep it last, window may stay minimized for a while.
+fn fake_frames_while_minimized(window: &Window) {
+    let Some((_device, mut queue, mut surface)) =
+        surface(window, mev::Features::empty(), mev::Quirks::empty())
+    else {
+        return;
+    };
+
+    window.set_minimized(true);
+
+    let Some(first) = next_fake_frame(&mut surface) else {
+        eprintln!("Skipping test, window was not minimized");
+        window.set_minimized(false);
+        return;
+    };
+
+    let mut first = Some(first);
+    for _ in 0..100 {
+        let mut frame = match first.take() {
+            Some(frame) => frame,
+            None => surface.next_frame().unwrap(),
+        };
+        assert!(frame.is_fake());
+
+        let mut encoder = queue.new_command_encoder().unwrap();
+        queue.sync_frame(&mut frame, PipelineStages::COLOR_OUTPUT);
+        encoder.present(frame, PipelineStages::COLOR_OUTPUT);
+        queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+    }
+
+    queue.wait_idle().unwrap();
+    window.set_minimized(false);
+}
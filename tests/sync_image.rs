@@ -0,0 +1,127 @@
+//! `sync_image` keeps image content between uses.
+
+mod common;
+
+use mev::{PipelineStages, ShaderLanguage};
+
+const SIZE: u32 = 4;
+
+/// Copies texels of the texture to the target one to one.
+const SHADER: &str = r#"
+@group(0) @binding(0) var texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureLoad(texture, vec2<u32>(position.xy), 0);
+}
+"#;
+
+#[derive(mev::Arguments)]
+pub struct Texture {
+    #[mev(sampled, fragment)]
+    pub texture: mev::Image,
+}
+
+/// Image uploaded in one submission and synced in the next is sampled intact.
+#[test]
+fn upload_then_sample() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let pixels = (0..SIZE * SIZE * 4)
+        .map(|i| (i as u8).wrapping_mul(13).wrapping_add(5))
+        .collect::<Vec<_>>();
+
+    let texture = device
+        .new_image(mev::ImageDesc::new_d2(
+            SIZE,
+            SIZE,
+            mev::PixelFormat::Rgba8Unorm,
+            mev::ImageUsage::SAMPLED | mev::ImageUsage::TRANSFER_DST,
+        ))
+        .unwrap();
+
+    let upload = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &pixels,
+            usage: mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Upload,
+            name: "sync-image-upload",
+        })
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, &texture);
+    encoder.copy().copy_buffer_to_image(
+        &upload,
+        0,
+        0,
+        0,
+        &texture,
+        mev::Offset3::ZERO,
+        texture.extent().into_3d(),
+        0..1,
+        0,
+    );
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+    let pipeline = device
+        .new_render_pipeline(
+            mev::RenderPipelineDesc::builder(mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            })
+            .name("sync-image")
+            .fragment(mev::Shader {
+                library,
+                entry: "fs_main".into(),
+            })
+            .color_target(mev::PixelFormat::Rgba8Unorm)
+            .arguments::<Texture>()
+            .build(),
+        )
+        .unwrap();
+
+    let target = common::render_target(&device, SIZE, SIZE);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.sync_image(
+        PipelineStages::TRANSFER,
+        PipelineStages::FRAGMENT_SHADER,
+        &texture,
+    );
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let mut render = encoder.render(
+            mev::RenderPassDesc::new()
+                .name("sync-image")
+                .color_attachments(&[
+                    mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)
+                ]),
+        );
+        render.with_pipeline(&pipeline);
+        render.with_arguments(
+            0,
+            &Texture {
+                texture: texture.clone(),
+            },
+        );
+        render.draw(0..3, 0..1);
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let rendered = mev::testing::read_image_to_rgba8(&mut queue, &target).unwrap();
+    assert_eq!(rendered, pixels);
+}
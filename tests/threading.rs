@@ -0,0 +1,83 @@
+//! Thread-safety guarantees of public types.
+//!
+//! Assertions are evaluated at compile time,
+//! so a type that stops being `Send` or `Sync` fails to build this test.
+
+use std::sync::mpsc;
+
+mod common;
+
+const fn assert_send<T: Send>() {}
+const fn assert_sync<T: Sync>() {}
+
+const _: () = {
+    assert_send::<mev::Instance>();
+    assert_sync::<mev::Instance>();
+    assert_send::<mev::Device>();
+    assert_sync::<mev::Device>();
+    assert_send::<mev::Queue>();
+    assert_sync::<mev::Queue>();
+
+    // Recorded on worker threads and sent to the thread that owns the queue.
+    assert_send::<mev::CommandEncoder>();
+    assert_send::<mev::CommandBuffer>();
+
+    assert_send::<mev::Buffer>();
+    assert_sync::<mev::Buffer>();
+    assert_send::<mev::Image>();
+    assert_sync::<mev::Image>();
+    assert_send::<mev::Sampler>();
+    assert_sync::<mev::Sampler>();
+    assert_send::<mev::Library>();
+    assert_sync::<mev::Library>();
+    assert_send::<mev::ComputePipeline>();
+    assert_sync::<mev::ComputePipeline>();
+    assert_send::<mev::RenderPipeline>();
+    assert_sync::<mev::RenderPipeline>();
+};
+
+/// Command buffers recorded on worker threads are submitted by the queue owner.
+#[test]
+fn record_on_workers() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let buffers = (0..4u8)
+        .map(|idx| {
+            device
+                .new_buffer(mev::BufferDesc {
+                    size: 16,
+                    usage: mev::BufferUsage::TRANSFER_DST | mev::BufferUsage::TRANSFER_SRC,
+                    memory: mev::Memory::Device,
+                    name: &format!("worker-{idx}"),
+                    shared_across_queues: false,
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::scope(|scope| {
+        for (idx, buffer) in buffers.iter().enumerate() {
+            let mut encoder = queue.new_command_encoder().unwrap();
+            let tx = tx.clone();
+
+            scope.spawn(move || {
+                encoder.copy().fill_buffer(buffer, idx as u8 + 1);
+                tx.send(encoder.finish().unwrap()).unwrap();
+            });
+        }
+    });
+    drop(tx);
+
+    let cbufs = rx.iter().collect::<Vec<_>>();
+    assert_eq!(cbufs.len(), buffers.len());
+    queue.submit(cbufs, true).unwrap();
+
+    for (idx, buffer) in buffers.iter().enumerate() {
+        let data = common::read_buffer(&mut queue, buffer, mev::PipelineStages::TRANSFER);
+        assert_eq!(data, [idx as u8 + 1; 16]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Completion of submissions queried out of submission order.
+
+mod common;
+
+use mev::SubmissionId;
+
+const SUBMISSIONS: usize = 16;
+
+/// Makes submissions that fill a buffer each,
+/// every fourth one with a checkpoint.
+fn submit_fills(device: &mev::Device, queue: &mut mev::Queue) -> Vec<(SubmissionId, mev::Buffer)> {
+    (0..SUBMISSIONS)
+        .map(|idx| {
+            let buffer = device
+                .new_buffer(mev::BufferDesc {
+                    size: 1 << 20,
+                    usage: mev::BufferUsage::TRANSFER_DST,
+                    memory: mev::Memory::Device,
+                    name: "timeline",
+                    shared_across_queues: false,
+                })
+                .unwrap();
+
+            let mut encoder = queue.new_command_encoder().unwrap();
+            encoder.copy().fill_buffer(&buffer, idx as u8);
+            let id = queue
+                .submit_one(encoder.finish().unwrap(), idx % 4 == 3)
+                .unwrap();
+            (id, buffer)
+        })
+        .collect()
+}
+
+/// Waiting for a submission reports it and all earlier ones complete,
+/// regardless of order in which submissions are waited for.
+#[test]
+fn wait_out_of_order() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let submissions = submit_fills(&device, &mut queue);
+
+    // Middle, last, first, then the rest backwards.
+    let mut order = vec![SUBMISSIONS / 2, SUBMISSIONS - 1, 0];
+    order.extend((1..SUBMISSIONS - 1).rev());
+
+    let mut completed = queue.last_completed_submission();
+    for idx in order {
+        let (id, _) = submissions[idx];
+        queue.wait_submission(id).unwrap();
+
+        let last = queue.last_completed_submission();
+        assert!(
+            last >= id,
+            "{id} is waited for, but last completed is {last}"
+        );
+        assert!(
+            last >= completed,
+            "completion went back from {completed} to {last}"
+        );
+        completed = last;
+    }
+
+    queue.wait_idle().unwrap();
+    assert_eq!(
+        queue.last_completed_submission(),
+        submissions[SUBMISSIONS - 1].0
+    );
+}
+
+/// Completed submission stays complete when later ones are queried.
+#[test]
+fn completion_is_monotonic() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let first = submit_fills(&device, &mut queue);
+    queue.wait_submission(first[SUBMISSIONS - 1].0).unwrap();
+    let completed = queue.last_completed_submission();
+
+    let second = submit_fills(&device, &mut queue);
+    for _ in 0..100 {
+        assert!(queue.last_completed_submission() >= completed);
+    }
+
+    // Waiting for submissions that are long complete returns immediately.
+    for (id, _) in first.iter().rev() {
+        queue.wait_submission(*id).unwrap();
+    }
+
+    queue.wait_idle().unwrap();
+    assert_eq!(queue.last_completed_submission(), second[SUBMISSIONS - 1].0);
+}
@@ -0,0 +1,166 @@
+//! Spans listed in README are emitted with `trace-gpu` feature.
+#![cfg(not(any(target_os = "macos", target_os = "ios")))]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Span or event with fields recorded so far.
+#[derive(Debug)]
+struct Record {
+    name: &'static str,
+    target: String,
+    fields: HashMap<&'static str, String>,
+}
+
+impl Visit for Record {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.fields.insert(field.name(), format!("{value:?}"));
+    }
+}
+
+/// Subscriber that keeps every span and event.
+#[derive(Clone, Default)]
+struct Recorder {
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl Recorder {
+    fn push(&self, metadata: &Metadata<'static>, record: impl FnOnce(&mut Record)) -> usize {
+        let mut entry = Record {
+            name: metadata.name(),
+            target: metadata.target().to_owned(),
+            fields: HashMap::new(),
+        };
+        record(&mut entry);
+
+        let mut records = self.records.lock().unwrap();
+        records.push(entry);
+        records.len()
+    }
+
+    /// Returns records with given name emitted with `mev` target.
+    fn named(&self, name: &str) -> Vec<HashMap<&'static str, String>> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|record| record.name == name && record.target == "mev")
+            .map(|record| record.fields.clone())
+            .collect()
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let idx = self.push(span.metadata(), |record| span.record(record));
+        span::Id::from_u64(idx as u64)
+    }
+
+    fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+        let idx = span.into_u64() as usize - 1;
+        values.record(&mut self.records.lock().unwrap()[idx]);
+    }
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        self.push(event.metadata(), |record| event.record(record));
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[test]
+fn spans_are_emitted() {
+    let recorder = Recorder::default();
+
+    let done = tracing::subscriber::with_default(recorder.clone(), || {
+        let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+            return false;
+        };
+
+        let buffer = device
+            .new_buffer(mev::BufferDesc {
+                size: 16,
+                usage: mev::BufferUsage::TRANSFER_DST,
+                memory: mev::Memory::Device,
+                name: "traced",
+                shared_across_queues: false,
+            })
+            .unwrap();
+
+        let mut encoder = queue.new_command_encoder().unwrap();
+        encoder.copy().fill_buffer(&buffer, 0xff);
+        queue
+            .submit_labeled([encoder.finish().unwrap()], "frame", true)
+            .unwrap();
+
+        if let Ok(mut encoder) = queue.new_command_encoder_reusable() {
+            encoder.copy().fill_buffer(&buffer, 0);
+            let reusable = encoder.finish().unwrap();
+            queue.submit_reusable(&reusable, true).unwrap();
+        }
+
+        queue.wait_idle().unwrap();
+        true
+    });
+
+    if !done {
+        return;
+    }
+
+    let device = recorder.named("mev.create_device");
+    assert_eq!(device.len(), 1);
+    assert!(device[0].contains_key("duration_us"));
+
+    let buffers = recorder.named("mev.new_buffer");
+    assert!(buffers
+        .iter()
+        .any(|fields| fields["name"] == "\"traced\"" && fields["size"] == "16"));
+
+    assert!(recorder
+        .named("mev.encoder")
+        .iter()
+        .any(|fields| fields.contains_key("kind")));
+
+    let submits = recorder.named("mev.submit");
+    let labeled = submits
+        .iter()
+        .find(|fields| {
+            fields
+                .get("label")
+                .is_some_and(|label| label == "\"frame\"")
+        })
+        .expect("labeled submission is traced");
+    assert_eq!(labeled["cbufs"], "1");
+    assert_eq!(labeled["check_point"], "true");
+
+    // Reusable command buffers have no label.
+    for fields in submits
+        .iter()
+        .filter(|fields| !fields.contains_key("label"))
+    {
+        assert_eq!(fields["cbufs"], "1");
+        assert!(fields.contains_key("waits"));
+    }
+
+    let wait_idle = recorder.named("mev.wait_idle");
+    assert_eq!(wait_idle.len(), 1);
+    assert!(wait_idle[0].contains_key("duration_us"));
+}
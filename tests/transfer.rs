@@ -0,0 +1,110 @@
+mod common;
+
+use mev::{
+    transfer::ImageTransfer, BufferInitDesc, BufferUsage, ImageDesc, ImageUsage, Memory, Offset3,
+    PipelineStages, PixelFormat,
+};
+
+const SIZE: u32 = 8;
+
+/// Creates image filled with pixels unique for `seed`.
+fn source_image(device: &mev::Device, queue: &mut mev::Queue, seed: u8) -> (mev::Image, Vec<u8>) {
+    let pixels = (0..SIZE * SIZE * 4)
+        .map(|i| (i as u8).wrapping_mul(7).wrapping_add(seed))
+        .collect::<Vec<_>>();
+
+    let image = device
+        .new_image(ImageDesc::new_d2(
+            SIZE,
+            SIZE,
+            PixelFormat::Rgba8Unorm,
+            ImageUsage::TRANSFER_SRC | ImageUsage::TRANSFER_DST,
+        ))
+        .unwrap();
+
+    let upload = device
+        .new_buffer_init(BufferInitDesc {
+            data: &pixels,
+            usage: BufferUsage::TRANSFER_SRC,
+            memory: Memory::Upload,
+            name: "transfer-source",
+        })
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, &image);
+    encoder.copy().copy_buffer_to_image(
+        &upload,
+        0,
+        0,
+        0,
+        &image,
+        Offset3::ZERO,
+        image.extent().into_3d(),
+        0..1,
+        0,
+    );
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    (image, pixels)
+}
+
+/// Creates image the content is copied into.
+fn destination_image(device: &mev::Device, queue: &mut mev::Queue) -> mev::Image {
+    let image = device
+        .new_image(ImageDesc::new_d2(
+            SIZE,
+            SIZE,
+            PixelFormat::Rgba8Unorm,
+            ImageUsage::TRANSFER_SRC | ImageUsage::TRANSFER_DST,
+        ))
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(PipelineStages::empty(), PipelineStages::TRANSFER, &image);
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    image
+}
+
+/// Transfers few images with the same staging pools
+/// and checks destination content after each one.
+fn transfer_repeatedly(
+    (src_device, mut src_queue): (mev::Device, mev::Queue),
+    (dst_device, mut dst_queue): (mev::Device, mev::Queue),
+) {
+    let mut transfer = ImageTransfer::new();
+    let dst = destination_image(&dst_device, &mut dst_queue);
+
+    for seed in 0..3 {
+        let (src, expected) = source_image(&src_device, &mut src_queue, seed);
+
+        let guard = transfer
+            .copy(&mut src_queue, &src, &mut dst_queue, &dst)
+            .unwrap();
+        guard.wait(&mut dst_queue).unwrap();
+
+        let pixels = mev::testing::read_image_to_rgba8(&mut dst_queue, &dst).unwrap();
+        assert_eq!(pixels, expected, "transfer {seed}");
+    }
+}
+
+#[test]
+fn between_physical_devices() {
+    let Some((src, dst)) = common::two_devices(mev::Features::empty()) else {
+        return;
+    };
+
+    transfer_repeatedly(src, dst);
+}
+
+/// Uses two logical devices on the same physical device,
+/// so the same code path is exercised on machines with single GPU.
+#[test]
+fn between_logical_devices() {
+    let Some((src, dst)) = common::two_logical_devices(mev::Features::empty()) else {
+        return;
+    };
+
+    transfer_repeatedly(src, dst);
+}
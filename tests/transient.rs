@@ -0,0 +1,55 @@
+//! Memory taken by transient render targets.
+
+mod common;
+
+const SIZE: u32 = 4096;
+
+fn target_desc(name: &str, usage: mev::ImageUsage) -> mev::ImageDesc<'_> {
+    mev::ImageDesc {
+        usage,
+        name,
+        ..mev::ImageDesc::new_d2_texture(SIZE, SIZE, mev::PixelFormat::D32Float)
+    }
+}
+
+/// Returns growth of reserved device memory caused by creating an image.
+fn measure(device: &mev::Device, desc: mev::ImageDesc) -> (mev::Image, u64) {
+    let before = device.allocator_stats().reserved_bytes;
+    let image = device.new_image(desc).unwrap();
+    let after = device.allocator_stats().reserved_bytes;
+    (image, after.saturating_sub(before))
+}
+
+#[test]
+fn transient_memory_footprint() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let regular_desc = target_desc("regular-depth", mev::ImageUsage::TARGET);
+    let transient_desc = target_desc(
+        "transient-depth",
+        mev::ImageUsage::TARGET | mev::ImageUsage::TRANSIENT,
+    );
+
+    let estimate = device.image_allocation_size(&transient_desc).unwrap();
+
+    let (_regular, regular) = measure(&device, regular_desc);
+    let (_transient, transient) = measure(&device, transient_desc);
+
+    eprintln!("{SIZE}x{SIZE} depth target takes {regular} bytes, transient one {transient} bytes");
+
+    assert!(regular > 0, "regular target takes no memory");
+    assert!(
+        transient <= regular,
+        "transient target takes {transient} bytes, more than regular {regular}"
+    );
+
+    if estimate == 0 {
+        // Memoryless storage, only bookkeeping may be allocated.
+        assert!(
+            transient < regular / 16,
+            "memoryless target takes {transient} bytes, regular {regular}"
+        );
+    }
+}
@@ -0,0 +1,6 @@
+fn pass(image: &mev::Image) -> mev::RenderPassDesc<'_> {
+    let depth = mev::AttachmentDesc::clear_color(image, mev::ClearColor::BLACK);
+    mev::RenderPassDesc::new().depth_stencil_attachment(depth)
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+fn pass(image: &mev::Image) {
+    let colors = [mev::AttachmentDesc::clear_depth(image, 1.0)];
+    let _ = mev::RenderPassDesc::new().color_attachments(&colors);
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+fn pass(image: &mev::Image) -> mev::RenderPassDesc<'_> {
+    let depth: mev::DepthAttachmentDesc = mev::AttachmentDesc {
+        image,
+        load: mev::LoadOp::Clear(mev::ClearColor::BLACK),
+        store: mev::StoreOp::Store,
+    };
+    mev::RenderPassDesc::new().depth_stencil_attachment(depth)
+}
+
+fn main() {}
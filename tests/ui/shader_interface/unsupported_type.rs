@@ -0,0 +1,4 @@
+// Paths are relative to the project trybuild creates in `target/tests/trybuild/mev`.
+mev::shader_interface!("../../../../tests/shaders/interface.wgsl", pub struct Flags);
+
+fn main() {}
@@ -0,0 +1,138 @@
+//! Vertex buffer layout validation and step modes.
+
+mod common;
+
+use mev::{PipelineStages, ShaderLanguage};
+
+const SIZE: u32 = 8;
+
+const SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32, @location(0) color: vec4<f32>) -> VertexOutput {
+    let uv = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u));
+    return VertexOutput(vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0), color);
+}
+
+@fragment
+fn fs_main(@location(0) color: vec4<f32>) -> @location(0) vec4<f32> {
+    return color;
+}
+"#;
+
+fn pipeline_desc(
+    library: &mev::Library,
+    layout: mev::VertexLayoutDesc,
+) -> mev::RenderPipelineDesc<'_> {
+    mev::RenderPipelineDesc::builder(mev::Shader {
+        library: library.clone(),
+        entry: "vs_main".into(),
+    })
+    .name("vertex-layout")
+    .fragment(mev::Shader {
+        library: library.clone(),
+        entry: "fs_main".into(),
+    })
+    .color_target(mev::PixelFormat::Rgba8Unorm)
+    .vertex(
+        layout,
+        [mev::VertexAttributeDesc {
+            format: mev::VertexFormat::Unorm8x4,
+            buffer_index: 0,
+            offset: 0,
+        }],
+    )
+    .build()
+}
+
+#[test]
+fn stride_over_limit() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+
+    let Err(err) = device.new_render_pipeline(pipeline_desc(
+        &library,
+        mev::VertexLayoutDesc {
+            stride: u32::MAX,
+            step_mode: mev::VertexStepMode::Vertex,
+        },
+    )) else {
+        panic!("pipeline with stride over the limit is created");
+    };
+
+    let message = err.to_string().to_lowercase();
+    assert!(
+        message.contains("vertex buffer 0 stride 4294967295 exceeds"),
+        "{message}"
+    );
+}
+
+/// Constant attribute with zero stride is read by all vertices.
+#[test]
+fn constant_zero_stride() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let library = common::library(&device, ShaderLanguage::Wgsl, SHADER);
+
+    let pipeline = match device.new_render_pipeline(pipeline_desc(
+        &library,
+        mev::VertexLayoutDesc {
+            stride: 0,
+            step_mode: mev::VertexStepMode::Constant,
+        },
+    )) {
+        Ok(pipeline) => pipeline,
+        Err(err) => {
+            eprintln!("Skipping test, constant step mode is not supported: {err}");
+            return;
+        }
+    };
+
+    let color = [255, 128, 0, 255];
+    let vertices = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &color,
+            usage: mev::BufferUsage::VERTEX,
+            memory: mev::Memory::Device,
+            name: "constant-color",
+        })
+        .unwrap();
+
+    let target = common::render_target(&device, SIZE, SIZE);
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::COLOR_OUTPUT,
+        &target,
+    );
+    {
+        let mut render = encoder.render(
+            mev::RenderPassDesc::new()
+                .name("constant-color")
+                .color_attachments(&[
+                    mev::AttachmentDesc::new(&target).clear(mev::ClearColor::BLACK)
+                ]),
+        );
+        render.with_pipeline(&pipeline);
+        render.bind_vertex_buffers(0, &[&vertices]);
+        render.draw(0..3, 0..1);
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let pixels = mev::testing::read_image_to_rgba8(&mut queue, &target).unwrap();
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            assert_eq!(common::pixel(&pixels, SIZE, x, y), color, "({x}, {y})");
+        }
+    }
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use mev::{ImageDesc, PipelineStages, PixelFormat, ViewAspect, ViewDesc, ViewError};
+
+#[test]
+fn view_out_of_bounds() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let image = device
+        .new_image(ImageDesc {
+            layers: 2,
+            levels: 4,
+            name: "view-bounds",
+            ..ImageDesc::new_d2_texture(64, 64, PixelFormat::Rgba8Unorm)
+        })
+        .unwrap();
+
+    let view = image.view(&device, ViewDesc::new(PixelFormat::Rgba8Unorm).levels(1..4));
+    assert!(view.is_ok());
+
+    let err = image
+        .view(&device, ViewDesc::new(PixelFormat::Rgba8Unorm).levels(2..6))
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        ViewError::LevelsOutOfBounds {
+            image: "view-bounds".to_owned(),
+            base: 2,
+            count: 4,
+            available: 4,
+        }
+    );
+
+    let err = image
+        .view(&device, ViewDesc::new(PixelFormat::Rgba8Unorm).layers(1..3))
+        .unwrap_err();
+
+    assert!(matches!(err, ViewError::LayersOutOfBounds { .. }));
+    assert!(err.to_string().contains("view-bounds"), "{err}");
+}
+
+#[test]
+fn view_aspect_mismatch() {
+    let Some((device, _queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let image = device
+        .new_image(ImageDesc {
+            name: "color",
+            ..ImageDesc::new_d2_texture(4, 4, PixelFormat::Rgba8Unorm)
+        })
+        .unwrap();
+
+    let err = image
+        .view(
+            &device,
+            ViewDesc::new(PixelFormat::Rgba8Unorm).aspect(ViewAspect::DepthOnly),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        ViewError::AspectMismatch {
+            image: "color".to_owned(),
+            aspect: ViewAspect::DepthOnly,
+            format: PixelFormat::Rgba8Unorm,
+        }
+    );
+}
+
+const WIDTH: u32 = 16;
+const DEPTH: f32 = 0.25;
+
+const READ_DEPTH: &str = r#"
+@group(0) @binding(0) var depth: texture_depth_2d;
+@group(0) @binding(1) var<storage, read_write> result: array<f32>;
+
+@compute @workgroup_size(16)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    result[id.x] = textureLoad(depth, vec2<u32>(id.x, 0u), 0);
+}
+"#;
+
+#[derive(mev::Arguments)]
+pub struct ReadDepth {
+    #[mev(sampled, compute)]
+    pub depth: mev::Image,
+    #[mev(storage, compute)]
+    pub result: mev::Buffer,
+}
+
+/// Depth written by a render pass is read back through depth-only view
+/// of combined depth-stencil image.
+#[test]
+fn depth_only_view_readback() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let Some(format) = device.select_depth_format(mev::DepthFormatRequirements {
+        stencil: true,
+        min_depth_bits: 16,
+        sampled: true,
+    }) else {
+        return;
+    };
+
+    let image = device
+        .new_image(ImageDesc::new_d2(
+            WIDTH,
+            1,
+            format,
+            mev::ImageUsage::TARGET | mev::ImageUsage::SAMPLED,
+        ))
+        .unwrap();
+
+    let depth = image
+        .view(&device, ViewDesc::new(format).aspect(ViewAspect::DepthOnly))
+        .unwrap();
+
+    let result = device
+        .new_buffer(mev::BufferDesc {
+            size: u64::from(WIDTH) * 4,
+            usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Device,
+            name: "depth-readback",
+            shared_across_queues: false,
+        })
+        .unwrap();
+
+    let library = common::library(&device, mev::ShaderLanguage::Wgsl, READ_DEPTH);
+    let pipeline = device
+        .new_compute_pipeline(
+            mev::ComputePipelineDesc::builder(mev::Shader {
+                library,
+                entry: "main".into(),
+            })
+            .name("read-depth")
+            .work_group_size([WIDTH, 1, 1])
+            .arguments::<ReadDepth>()
+            .build(),
+        )
+        .unwrap();
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    encoder.init_image(
+        PipelineStages::empty(),
+        PipelineStages::EARLY_FRAGMENT_TEST,
+        &image,
+    );
+    {
+        let _render = encoder.render(mev::RenderPassDesc {
+            name: "clear-depth",
+            color_attachments: &[],
+            depth_stencil_attachment: Some(mev::AttachmentDesc::clear_depth(&image, DEPTH)),
+            flags: mev::RenderPassFlags::empty(),
+            expected_draws: 0,
+        });
+    }
+    encoder.barrier(
+        PipelineStages::LATE_FRAGMENT_TEST,
+        PipelineStages::COMPUTE_SHADER,
+    );
+    {
+        let mut compute = encoder.compute();
+        compute.with_pipeline(&pipeline);
+        compute.with_arguments(
+            0,
+            &ReadDepth {
+                depth,
+                result: result.clone(),
+            },
+        );
+        compute.dispatch(mev::Extent3::new(1, 1, 1));
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &result, PipelineStages::COMPUTE_SHADER);
+    let values: &[f32] = bytemuck::cast_slice(&data);
+    assert_eq!(values, [DEPTH; WIDTH as usize]);
+}
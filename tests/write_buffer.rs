@@ -0,0 +1,73 @@
+//! Buffer writes of sizes that are not multiple of 4.
+
+mod common;
+
+use mev::PipelineStages;
+
+const SIZE: u64 = 64;
+
+fn buffer(device: &mev::Device) -> mev::Buffer {
+    device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &[0xAA; SIZE as usize],
+            usage: mev::BufferUsage::TRANSFER_DST | mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Device,
+            name: "write-target",
+        })
+        .unwrap()
+}
+
+#[test]
+fn unaligned_tails() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let buffer = buffer(&device);
+    let mut expected = vec![0xAA; SIZE as usize];
+
+    let mut encoder = queue.new_command_encoder().unwrap();
+    {
+        let mut copy = encoder.copy();
+        for len in 1..=7u8 {
+            let offset = u64::from(len - 1) * 8;
+            let data = (0..len).map(|i| len * 16 + i).collect::<Vec<_>>();
+
+            copy.write_buffer_raw(buffer.slice(offset..offset + 8), &data);
+            expected[offset as usize..][..data.len()].copy_from_slice(&data);
+        }
+    }
+    queue.submit_one(encoder.finish().unwrap(), true).unwrap();
+
+    // Bytes after each write are preserved.
+    let data = common::read_buffer(&mut queue, &buffer, PipelineStages::TRANSFER);
+    assert_eq!(data, expected);
+}
+
+// Metal doesn't support reusable command buffers.
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+#[test]
+fn unaligned_tail_in_reusable_buffer() {
+    let Some((device, mut queue)) = common::device(mev::Features::empty()) else {
+        return;
+    };
+
+    let buffer = buffer(&device);
+
+    let Ok(mut encoder) = queue.new_command_encoder_reusable() else {
+        return;
+    };
+    encoder
+        .copy()
+        .write_buffer_raw(buffer.slice(4..SIZE), &[1, 2, 3, 4, 5, 6]);
+    let reusable = encoder.finish().unwrap();
+
+    queue.submit_reusable(&reusable, true).unwrap();
+    queue.submit_reusable(&reusable, true).unwrap();
+
+    let data = common::read_buffer(&mut queue, &buffer, PipelineStages::TRANSFER);
+    assert_eq!(
+        data[..12],
+        [0xAA, 0xAA, 0xAA, 0xAA, 1, 2, 3, 4, 5, 6, 0xAA, 0xAA]
+    );
+}